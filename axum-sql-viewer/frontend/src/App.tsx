@@ -2,13 +2,15 @@ import { PureComponent } from "react";
 import { Panel, PanelGroup, PanelResizeHandle } from "react-resizable-panels";
 import { Table, Terminal } from "lucide-react";
 import TableList from "./components/TableList";
-import TableViewer from "./components/TableViewer";
+import TableViewer, { TableView } from "./components/TableViewer";
+import MigrationsViewer, { MIGRATION_TABLES } from "./components/MigrationsViewer";
 import QueryEditor from "./components/QueryEditor";
 import QueryResults from "./components/QueryResults";
 import SavedQueries from "./components/SavedQueries";
 import QueryHistory from "./components/QueryHistory";
 import ThemeToggle from "./components/ThemeToggle";
 import { QueryResult } from "./types/database";
+import { apiService } from "./services/ApiService";
 
 interface AppProps {}
 
@@ -19,6 +21,12 @@ interface AppState {
     responseTimeMilliseconds: number | null;
     currentQuery: string;
     queryLoadTimestamp: number;
+    /** Parameters of a query opened from a permalink */
+    currentParams: string | undefined;
+    /** Filters, sort, and search of a table opened from a permalink */
+    tableView: TableView | undefined;
+    /** Changed to open the table viewer afresh with `tableView` */
+    tableViewKey: number;
 }
 
 export default class App extends PureComponent<AppProps, AppState> {
@@ -30,11 +38,15 @@ export default class App extends PureComponent<AppProps, AppState> {
             responseTimeMilliseconds: null,
             currentQuery: ``,
             queryLoadTimestamp: 0,
+            currentParams: undefined,
+            tableView: undefined,
+            tableViewKey: 0,
         };
     }
 
     componentDidMount(): void {
         window.addEventListener(`hashchange`, this.handleHashChange);
+        void this.openPermalinkFromHash();
     }
 
     componentWillUnmount(): void {
@@ -49,6 +61,11 @@ export default class App extends PureComponent<AppProps, AppState> {
             return { activeView: `query`, selectedTable: null };
         }
 
+        // A permalink (#/view/<token>) is opened once its view is fetched
+        if (segments[0] === `view`) {
+            return { activeView: this.state?.activeView ?? `tables`, selectedTable: this.state?.selectedTable ?? null };
+        }
+
         if (segments[0] === `tables` || segments.length === 0) {
             const tableName = segments[1] ? decodeURIComponent(segments[1]) : null;
             return { activeView: `tables`, selectedTable: tableName };
@@ -76,7 +93,45 @@ export default class App extends PureComponent<AppProps, AppState> {
 
     private handleHashChange = (): void => {
         const { activeView, selectedTable } = this.parseUrlHash();
-        this.setState({ activeView, selectedTable });
+        this.setState({ activeView, selectedTable, tableView: undefined });
+        void this.openPermalinkFromHash();
+    };
+
+    /**
+     * Open the table or query shared under the token in a #/view/<token> URL
+     */
+    private openPermalinkFromHash = async (): Promise<void> => {
+        const segments = window.location.hash.slice(1).split(`/`).filter(Boolean);
+        if (segments[0] !== `view` || !segments[1]) {
+            return;
+        }
+
+        try {
+            const view = await apiService.getPermalink(decodeURIComponent(segments[1]));
+            if (view.type === `table`) {
+                const { table, filters, sortBy, sortOrder, search } = view;
+                this.setState({
+                    activeView: `tables`,
+                    selectedTable: table,
+                    tableView: { filters, sortBy, sortOrder, search },
+                    tableViewKey: Date.now(),
+                });
+                // Replace the permalink so reloading shows the table as it is then
+                window.history.replaceState(null, ``, `#/tables/${encodeURIComponent(table)}`);
+            } else {
+                const parameters = view.parameters ?? [];
+                this.setState({
+                    activeView: `query`,
+                    currentQuery: view.sql,
+                    currentParams: Object.keys(parameters).length > 0 ? JSON.stringify(parameters) : ``,
+                    queryLoadTimestamp: Date.now(),
+                });
+                window.history.replaceState(null, ``, `#/query`);
+            }
+        } catch (error) {
+            alert(`Failed to open link: ${error instanceof Error ? error.message : `Unknown error`}`);
+            window.history.replaceState(null, ``, `#/tables`);
+        }
     };
 
     private handleViewChange = (view: `tables` | `query`): void => {
@@ -85,7 +140,7 @@ export default class App extends PureComponent<AppProps, AppState> {
     };
 
     private handleTableSelect = (tableName: string): void => {
-        this.setState({ selectedTable: tableName });
+        this.setState({ selectedTable: tableName, tableView: undefined });
         this.updateUrl(`tables`, tableName);
     };
 
@@ -98,7 +153,7 @@ export default class App extends PureComponent<AppProps, AppState> {
     };
 
     render() {
-        const { activeView, selectedTable, queryResult, responseTimeMilliseconds, currentQuery, queryLoadTimestamp } = this.state;
+        const { activeView, selectedTable, queryResult, responseTimeMilliseconds, currentQuery, queryLoadTimestamp, currentParams, tableView, tableViewKey } = this.state;
 
         return (
             <div className={`flex h-screen w-full flex-col bg-background text-foreground`}>
@@ -156,8 +211,10 @@ export default class App extends PureComponent<AppProps, AppState> {
                             {/* Tables View - Main Panel */}
                             <Panel defaultSize={80} minSize={40}>
                                 <main className={`h-full overflow-hidden`}>
-                                    {selectedTable ? (
-                                        <TableViewer tableName={selectedTable} />
+                                    {selectedTable && MIGRATION_TABLES.includes(selectedTable) ? (
+                                        <MigrationsViewer tableName={selectedTable} />
+                                    ) : selectedTable ? (
+                                        <TableViewer key={tableViewKey} tableName={selectedTable} initialView={tableView} />
                                     ) : (
                                         <div className={`flex h-full items-center justify-center text-muted-foreground`}>
                                             <p>Select a table from the sidebar to view its contents</p>
@@ -205,6 +262,7 @@ export default class App extends PureComponent<AppProps, AppState> {
                                                     onQueryResult={this.handleQueryExecute}
                                                     initialQuery={currentQuery}
                                                     queryLoadTimestamp={queryLoadTimestamp}
+                                                    initialParams={currentParams}
                                                     onQueryChange={this.handleLoadQuery}
                                                 />
                                             </div>
@@ -0,0 +1,228 @@
+import { PureComponent } from 'react';
+import { CheckCircle2, Clock, Play, XCircle } from 'lucide-react';
+import { Migration, MigrationHistory } from '../types/database';
+import { apiService } from '../services/ApiService';
+import { cn } from '../lib/utils';
+
+interface MigrationsViewerProps {
+  /** Migration table to show (`_sqlx_migrations` or `__diesel_schema_migrations`) */
+  tableName: string;
+  className?: string;
+}
+
+interface MigrationsViewerState {
+  history: MigrationHistory | null;
+  /** Migrations of the configured migrator not applied yet */
+  pending: Migration[];
+  /** Whether pending migrations can be applied */
+  runnable: boolean;
+  running: boolean;
+  loading: boolean;
+  error: string | null;
+  runError: string | null;
+}
+
+/** Milliseconds between checks of a running migration job */
+const JOB_POLL_INTERVAL = 500;
+
+/** Tables sqlx and Diesel record their migrations in */
+export const MIGRATION_TABLES = [`_sqlx_migrations`, `__diesel_schema_migrations`];
+
+/**
+ * MigrationsViewer - Shows the history recorded in a migration table instead of its plain rows.
+ * Lists versions, descriptions, install times, checksums and durations; failed migrations are
+ * highlighted, since sqlx runs no later migration until they are fixed. With a migrator configured,
+ * its pending migrations follow and can be applied.
+ */
+export default class MigrationsViewer extends PureComponent<MigrationsViewerProps, MigrationsViewerState> {
+  constructor(props: MigrationsViewerProps) {
+    super(props);
+    this.state = {
+      history: null,
+      pending: [],
+      runnable: false,
+      running: false,
+      loading: true,
+      error: null,
+      runError: null,
+    };
+  }
+
+  componentDidMount(): void {
+    this.loadMigrations();
+  }
+
+  componentDidUpdate(previousProps: MigrationsViewerProps): void {
+    if (previousProps.tableName !== this.props.tableName) {
+      this.loadMigrations();
+    }
+  }
+
+  private loadMigrations = async (): Promise<void> => {
+    this.setState({ loading: true, error: null });
+    try {
+      const { histories, pending, runnable } = await apiService.getMigrations();
+      const history = histories.find((candidate) => candidate.table === this.props.tableName) ?? null;
+      // Pending migrations come from a sqlx migrator
+      this.setState({ history, pending: history?.tool === `sqlx` ? pending : [], runnable, loading: false });
+    } catch (error) {
+      console.error(`Failed to load migrations:`, error);
+      this.setState({
+        error: error instanceof Error ? error.message : `Failed to load migrations`,
+        loading: false,
+      });
+    }
+  };
+
+  private handleRunMigrations = async (): Promise<void> => {
+    this.setState({ running: true, runError: null });
+    try {
+      let job = await apiService.runMigrations();
+      while (job.status === `running`) {
+        await new Promise((resolve) => setTimeout(resolve, JOB_POLL_INTERVAL));
+        job = await apiService.getJob(job.id);
+      }
+      if (job.status === `failed`) {
+        this.setState({ runError: job.error ?? `Failed to apply migrations` });
+      }
+    } catch (error) {
+      console.error(`Failed to apply migrations:`, error);
+      this.setState({ runError: error instanceof Error ? error.message : `Failed to apply migrations` });
+    }
+    this.setState({ running: false });
+    await this.loadMigrations();
+  };
+
+  render(): React.ReactNode {
+    const { className, tableName } = this.props;
+    const { history, pending, runnable, running, loading, error, runError } = this.state;
+
+    if (loading) {
+      return (
+        <div className={cn(`flex h-full items-center justify-center`, className)}>
+          <div className={`h-8 w-8 animate-spin rounded-full border-4 border-gray-300 border-t-blue-600 dark:border-gray-600 dark:border-t-blue-400`} />
+        </div>
+      );
+    }
+
+    if (error || !history) {
+      return (
+        <div className={cn(`flex h-full items-center justify-center`, className)}>
+          <div className={`rounded-lg border border-red-300 bg-red-50 p-6 dark:border-red-800 dark:bg-red-900/20`}>
+            <div className={`mb-2 text-lg font-semibold text-red-900 dark:text-red-200`}>
+              Error Loading Migrations
+            </div>
+            <div className={`text-red-700 dark:text-red-300`}>{error ?? `No migrations found in ${tableName}`}</div>
+          </div>
+        </div>
+      );
+    }
+
+    const isSqlx = history.tool === `sqlx`;
+    const failed = history.migrations.filter((migration) => migration.success === false).length;
+
+    return (
+      <div className={cn(`flex h-full flex-col`, className)}>
+        <div className={`flex items-center gap-4 border-b border-border bg-card px-4 py-3`}>
+          <h2 className={`text-lg font-semibold text-foreground`}>
+            {isSqlx ? `sqlx` : `Diesel`} migrations
+          </h2>
+          <span className={`text-sm text-muted-foreground`}>
+            {history.migrations.length} applied{failed > 0 ? `, ${failed} failed` : ``}
+            {pending.length > 0 ? `, ${pending.length} pending` : ``}
+          </span>
+          {runnable && (
+            <button
+              onClick={this.handleRunMigrations}
+              disabled={running || pending.length === 0}
+              className={`ml-auto inline-flex items-center gap-2 rounded-md bg-primary px-3 py-1.5 text-sm font-medium text-primary-foreground transition-colors hover:bg-primary/90 disabled:opacity-50 disabled:cursor-not-allowed`}
+              title={`Apply the pending migrations in order`}
+            >
+              <Play size={14} />
+              {running ? `Applying...` : `Apply pending`}
+            </button>
+          )}
+        </div>
+
+        {runError && (
+          <div className={`border-b border-destructive/50 bg-destructive/10 px-4 py-2 text-sm text-destructive`}>
+            {runError}
+          </div>
+        )}
+
+        <div className={`flex-1 overflow-auto`}>
+          <table className={`w-full text-sm`}>
+            <thead className={`sticky top-0 bg-card text-left text-muted-foreground`}>
+              <tr className={`border-b border-border`}>
+                <th className={`px-4 py-2 font-medium`}>Version</th>
+                {isSqlx && <th className={`px-4 py-2 font-medium`}>Description</th>}
+                <th className={`px-4 py-2 font-medium`}>Installed on</th>
+                {isSqlx && <th className={`px-4 py-2 font-medium`}>Status</th>}
+                {isSqlx && <th className={`px-4 py-2 font-medium`}>Checksum</th>}
+                {isSqlx && <th className={`px-4 py-2 text-right font-medium`}>Duration</th>}
+              </tr>
+            </thead>
+            <tbody>
+              {history.migrations.map((migration) => (
+                <tr
+                  key={migration.version}
+                  className={cn(
+                    `border-b border-border`,
+                    migration.success === false && `bg-destructive/10 text-destructive`
+                  )}
+                >
+                  <td className={`px-4 py-2 font-mono`}>{migration.version}</td>
+                  {isSqlx && <td className={`px-4 py-2`}>{migration.description}</td>}
+                  <td className={`px-4 py-2 text-muted-foreground`}>{migration.installedOn}</td>
+                  {isSqlx && (
+                    <td className={`px-4 py-2`}>
+                      {migration.success === false ? (
+                        <span className={`inline-flex items-center gap-1`}>
+                          <XCircle size={14} /> Failed
+                        </span>
+                      ) : (
+                        <span className={`inline-flex items-center gap-1 text-green-600 dark:text-green-400`}>
+                          <CheckCircle2 size={14} /> Applied
+                        </span>
+                      )}
+                    </td>
+                  )}
+                  {isSqlx && (
+                    <td className={`px-4 py-2 font-mono text-xs text-muted-foreground`} title={migration.checksum ?? undefined}>
+                      {migration.checksum ? `${migration.checksum.slice(0, 12)}…` : ``}
+                    </td>
+                  )}
+                  {isSqlx && (
+                    <td className={`px-4 py-2 text-right text-muted-foreground`}>
+                      {migration.executionTimeMilliseconds !== null ? `${migration.executionTimeMilliseconds} ms` : ``}
+                    </td>
+                  )}
+                </tr>
+              ))}
+              {pending.map((migration) => (
+                <tr key={`pending-${migration.version}`} className={`border-b border-border text-muted-foreground`}>
+                  <td className={`px-4 py-2 font-mono`}>{migration.version}</td>
+                  {isSqlx && <td className={`px-4 py-2`}>{migration.description}</td>}
+                  <td className={`px-4 py-2`} />
+                  {isSqlx && (
+                    <td className={`px-4 py-2`}>
+                      <span className={`inline-flex items-center gap-1`}>
+                        <Clock size={14} /> Pending
+                      </span>
+                    </td>
+                  )}
+                  {isSqlx && (
+                    <td className={`px-4 py-2 font-mono text-xs`} title={migration.checksum ?? undefined}>
+                      {migration.checksum ? `${migration.checksum.slice(0, 12)}…` : ``}
+                    </td>
+                  )}
+                  {isSqlx && <td className={`px-4 py-2`} />}
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      </div>
+    );
+  }
+}
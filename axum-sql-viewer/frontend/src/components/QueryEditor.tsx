@@ -4,11 +4,21 @@ import { EditorState, StateField, StateEffect } from '@codemirror/state';
 import { sql } from '@codemirror/lang-sql';
 import { syntaxHighlighting, HighlightStyle } from '@codemirror/language';
 import { tags } from '@lezer/highlight';
-import { QueryResult } from '../types/database';
+import { ConfirmationRequired, QueryDownloadFormat, QueryResult, QueryValidation } from '../types/database';
 import { apiService } from '../services/ApiService';
 import { savedQueriesService } from '../services/SavedQueriesService';
 import { queryHistoryService } from '../services/QueryHistoryService';
 
+/**
+ * Rows shown before more are fetched on scroll
+ */
+const QUERY_PAGE_SIZE = 500;
+
+/**
+ * Pause in typing after which the SQL is checked
+ */
+const VALIDATION_DELAY_MILLISECONDS = 600;
+
 /**
  * State effect to set error line highlighting
  */
@@ -63,6 +73,8 @@ interface QueryEditorProps {
   onQueryResult: (result: QueryResult, responseTimeMilliseconds: number) => void;
   initialQuery?: string;
   queryLoadTimestamp?: number;
+  /** JSON parameters to put in the parameters field, e.g. from a permalink */
+  initialParams?: string;
   onQueryChange?: (sql: string) => void;
 }
 
@@ -71,12 +83,23 @@ interface QueryEditorProps {
  */
 interface QueryEditorState {
   sql: string;
+  /** JSON array of values for the placeholders in the SQL */
+  params: string;
   executing: boolean;
   lastResult: QueryResult | null;
   error: string | null;
   executionTime: number | null;
   showSaveDialog: boolean;
   queryName: string;
+  /** Identifier of the open transaction queries run in, if any */
+  transaction: string | null;
+  /** Result of copying a link to the query, shown briefly on the button */
+  linkStatus: string | null;
+  /** Problem found by checking the SQL while it is typed */
+  validationError: string | null;
+  /** File format the download button saves the result in */
+  downloadFormat: QueryDownloadFormat;
+  downloading: boolean;
 }
 
 /**
@@ -86,18 +109,28 @@ interface QueryEditorState {
 class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState> {
   private editorView: EditorView | null = null;
   private editorContainer: React.RefObject<HTMLDivElement>;
+  /** Identifier of the running query, used to cancel it */
+  private runningQueryId: string | null = null;
+  /** Timer that checks the SQL once typing pauses */
+  private validationTimer: number | null = null;
 
   constructor(props: QueryEditorProps) {
     super(props);
     this.editorContainer = React.createRef();
     this.state = {
       sql: props.initialQuery ?? ``,
+      params: props.initialParams ?? ``,
       executing: false,
       lastResult: null,
       error: null,
       executionTime: null,
       showSaveDialog: false,
       queryName: ``,
+      transaction: null,
+      linkStatus: null,
+      validationError: null,
+      downloadFormat: `csv`,
+      downloading: false,
     };
   }
 
@@ -119,12 +152,22 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
         this.setState({ sql: this.props.initialQuery });
       }
     }
+    if (previousProps.initialParams !== this.props.initialParams && this.props.initialParams !== undefined) {
+      this.setState({ params: this.props.initialParams });
+    }
   }
 
   componentWillUnmount(): void {
+    if (this.validationTimer !== null) {
+      window.clearTimeout(this.validationTimer);
+    }
     if (this.editorView) {
       this.editorView.destroy();
     }
+    // Don't leave a transaction holding a connection until it expires
+    if (this.state.transaction) {
+      void apiService.endTransaction(this.state.transaction, `rollback`).catch(() => undefined);
+    }
   }
 
   /**
@@ -170,7 +213,8 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
         EditorView.updateListener.of((update) => {
           if (update.docChanged) {
             const newSql = update.state.doc.toString();
-            this.setState({ sql: newSql, error: null });
+            this.setState({ sql: newSql, error: null, validationError: null });
+            this.scheduleValidation();
           }
         }),
         EditorView.theme({
@@ -288,6 +332,63 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
     }
   };
 
+  /**
+   * Check the SQL once typing pauses
+   */
+  private scheduleValidation = (): void => {
+    if (this.validationTimer !== null) {
+      window.clearTimeout(this.validationTimer);
+    }
+    this.validationTimer = window.setTimeout(() => {
+      this.validationTimer = null;
+      void this.validateSql();
+    }, VALIDATION_DELAY_MILLISECONDS);
+  };
+
+  /**
+   * Check the SQL without running it and mark the first problem
+   */
+  private validateSql = async (): Promise<void> => {
+    if (!this.editorView) {
+      return;
+    }
+    const sqlText = this.editorView.state.doc.toString();
+    if (!sqlText.trim()) {
+      return;
+    }
+
+    let validation: QueryValidation;
+    try {
+      validation = await apiService.validateQuery(sqlText);
+    } catch {
+      // Checking is a hint only; running the query reports the error
+      return;
+    }
+    // Ignore the result if the SQL was changed meanwhile
+    if (!this.editorView || this.editorView.state.doc.toString() !== sqlText || this.state.error) {
+      return;
+    }
+    if (validation.valid || !validation.error) {
+      this.clearErrorHighlight();
+      return;
+    }
+
+    this.setState({ validationError: validation.error });
+    const lines = this.editorView.state.doc;
+    if (validation.line !== null && validation.line <= lines.lines) {
+      const line = lines.line(validation.line);
+      const from = Math.min(line.from + Math.max((validation.column ?? 1) - 1, 0), line.to);
+      // Mark the word at the error, or a single character
+      const word = /^\w+/.exec(sqlText.substring(from, line.to));
+      const to = word ? from + word[0].length : Math.min(from + 1, line.to);
+      this.editorView.dispatch({
+        effects: setErrorLine.of({ line: validation.line, from, to }),
+      });
+    } else {
+      this.highlightErrorLine(validation.error);
+    }
+  };
+
   /**
    * Clear error line highlighting
    */
@@ -312,6 +413,14 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
       return;
     }
 
+    const params = this.parseParams();
+    if (params === null) {
+      this.setState({
+        error: `Parameters must be a JSON array, e.g. ["alice@example.com", 42], or object, e.g. {"user": 42}`,
+      });
+      return;
+    }
+
     // Clear any previous error highlighting
     this.clearErrorHighlight();
 
@@ -324,7 +433,20 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
     const startTime = performance.now();
 
     try {
-      const result = await apiService.executeQuery(sql);
+      const { transaction } = this.state;
+      // Queries in a transaction run on its session and can't be cancelled by id
+      this.runningQueryId = transaction
+        ? null
+        : `editor-${Date.now().toString(36)}-${Math.random().toString(36).slice(2, 8)}`;
+      const result = await apiService.executeQuery(
+        sql,
+        params,
+        transaction,
+        this.runningQueryId,
+        this.confirmDestructiveStatement,
+        QUERY_PAGE_SIZE
+      );
+      this.runningQueryId = null;
       const endTime = performance.now();
       const executionTime = Math.round(endTime - startTime);
 
@@ -355,6 +477,7 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
         this.props.onQueryResult(result, executionTime);
       }
     } catch (error) {
+      this.runningQueryId = null;
       const endTime = performance.now();
       const executionTime = Math.round(endTime - startTime);
       const errorMessage = error instanceof Error ? error.message : `Unknown error occurred`;
@@ -378,9 +501,133 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
     }
   };
 
+  /**
+   * Ask before running a destructive statement the server wants confirmed
+   */
+  private confirmDestructiveStatement = (plan: ConfirmationRequired): boolean => {
+    const keyword = plan.statementKind.toUpperCase();
+    const rows = plan.estimatedAffectedRows;
+    const message =
+      rows === null
+        ? `Run this ${keyword} statement?`
+        : `This ${keyword} would affect ${rows} row${rows === 1 ? `` : `s`}. Run it?`;
+    return window.confirm(message);
+  };
+
+  /**
+   * Stop the running query on the server
+   */
+  private cancelQuery = async (): Promise<void> => {
+    if (!this.runningQueryId) {
+      return;
+    }
+
+    try {
+      await apiService.cancelQuery(this.runningQueryId);
+    } catch (error) {
+      // The query may have finished in the meantime
+      console.warn(`Failed to cancel query:`, error);
+    }
+  };
+
+  /**
+   * Begin a transaction that the following queries run in
+   */
+  private beginTransaction = async (): Promise<void> => {
+    try {
+      const { tx } = await apiService.beginTransaction();
+      this.setState({ transaction: tx, error: null });
+    } catch (error) {
+      this.setState({ error: error instanceof Error ? error.message : `Failed to begin transaction` });
+    }
+  };
+
+  /**
+   * Commit or roll back the open transaction
+   */
+  private endTransaction = async (action: `commit` | `rollback`): Promise<void> => {
+    const { transaction } = this.state;
+    if (!transaction) {
+      return;
+    }
+
+    try {
+      await apiService.endTransaction(transaction, action);
+      this.setState({ transaction: null, error: null });
+    } catch (error) {
+      const message = error instanceof Error ? error.message : `Failed to ${action} transaction`;
+      // An expired transaction has already been rolled back; a failed commit stays open
+      const ended = action === `rollback` || message.includes(`not found`);
+      this.setState({ error: message, transaction: ended ? null : transaction });
+    }
+  };
+
+  /**
+   * Parse the parameters field (empty means no parameters)
+   *
+   * @returns The positional or named parameter values, or null if the field is not a JSON array or object
+   */
+  private parseParams = (): unknown[] | Record<string, unknown> | null => {
+    const text = this.state.params.trim();
+    if (!text) {
+      return [];
+    }
+
+    try {
+      const params: unknown = JSON.parse(text);
+      if (Array.isArray(params)) {
+        return params;
+      }
+      return typeof params === `object` && params !== null ? (params as Record<string, unknown>) : null;
+    } catch {
+      return null;
+    }
+  };
+
   /**
    * Clear the editor content
    */
+  private copyLink = async (): Promise<void> => {
+    const params = this.parseParams();
+    if (params === null) {
+      this.setState({
+        error: `Parameters must be a JSON array, e.g. ["alice@example.com", 42], or object, e.g. {"user": 42}`,
+      });
+      return;
+    }
+
+    try {
+      const link = await apiService.createPermalink({ type: `query`, sql: this.state.sql, parameters: params });
+      await navigator.clipboard.writeText(link);
+      this.setState({ linkStatus: `Link copied` });
+    } catch (error) {
+      this.setState({ linkStatus: error instanceof Error ? error.message : `Failed to copy link` });
+    }
+    window.setTimeout(() => this.setState({ linkStatus: null }), 3000);
+  };
+
+  /**
+   * Run the query on the server and save its rows as a file
+   */
+  private downloadResult = async (): Promise<void> => {
+    const params = this.parseParams();
+    if (params === null) {
+      this.setState({
+        error: `Parameters must be a JSON array, e.g. ["alice@example.com", 42], or object, e.g. {"user": 42}`,
+      });
+      return;
+    }
+
+    this.setState({ downloading: true, error: null });
+    try {
+      await apiService.downloadQuery(this.state.sql, params, this.state.downloadFormat);
+    } catch (error) {
+      this.setState({ error: error instanceof Error ? error.message : `Failed to download query result` });
+    } finally {
+      this.setState({ downloading: false });
+    }
+  };
+
   private clearEditor = (): void => {
     if (this.editorView) {
       this.clearErrorHighlight();
@@ -414,7 +661,7 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
   /**
    * Save the current query with the provided name
    */
-  private saveQuery = (): void => {
+  private saveQuery = async (): Promise<void> => {
     const name = this.state.queryName.trim();
     const sql = this.state.sql.trim();
 
@@ -428,8 +675,16 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
       return;
     }
 
+    const params = this.parseParams();
+    if (params === null) {
+      this.setState({
+        error: `Parameters must be a JSON array, e.g. ["alice@example.com", 42], or object, e.g. {"user": 42}`,
+      });
+      return;
+    }
+
     try {
-      savedQueriesService.save(name, sql);
+      await savedQueriesService.save(name, sql, null, params);
       this.setState({
         showSaveDialog: false,
         queryName: ``,
@@ -449,7 +704,18 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
   };
 
   render(): React.ReactNode {
-    const { executing, error, showSaveDialog, queryName } = this.state;
+    const {
+      executing,
+      error,
+      showSaveDialog,
+      queryName,
+      params,
+      transaction,
+      linkStatus,
+      validationError,
+      downloadFormat,
+      downloading,
+    } = this.state;
 
     return (
       <div className={`flex h-full flex-col`}>
@@ -504,6 +770,16 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
               )}
             </button>
 
+            {executing && !transaction && (
+              <button
+                onClick={() => void this.cancelQuery()}
+                className={`inline-flex items-center justify-center rounded-md border border-destructive bg-background px-4 py-2 text-sm font-medium text-destructive transition-colors hover:bg-destructive/10`}
+                title={`Stop the running query on the server`}
+              >
+                Cancel
+              </button>
+            )}
+
             <button
               onClick={this.showSaveDialog}
               disabled={executing}
@@ -527,6 +803,64 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
               Save
             </button>
 
+            <button
+              onClick={() => void this.copyLink()}
+              disabled={!this.state.sql.trim()}
+              className={`inline-flex items-center justify-center rounded-md border border-input bg-background px-4 py-2 text-sm font-medium transition-colors hover:bg-accent hover:text-accent-foreground disabled:pointer-events-none disabled:opacity-50`}
+              title={`Copy a link that opens this query with its parameters`}
+            >
+              <svg
+                className={`mr-2 h-4 w-4`}
+                xmlns={`http://www.w3.org/2000/svg`}
+                viewBox={`0 0 24 24`}
+                fill={`none`}
+                stroke={`currentColor`}
+                strokeWidth={`2`}
+                strokeLinecap={`round`}
+                strokeLinejoin={`round`}
+              >
+                <path d={`M10 13a5 5 0 0 0 7.54.54l3-3a5 5 0 0 0-7.07-7.07l-1.72 1.71`} />
+                <path d={`M14 11a5 5 0 0 0-7.54-.54l-3 3a5 5 0 0 0 7.07 7.07l1.71-1.71`} />
+              </svg>
+              {linkStatus ?? `Copy link`}
+            </button>
+
+            <div className={`inline-flex`}>
+              <button
+                onClick={() => void this.downloadResult()}
+                disabled={executing || downloading || !this.state.sql.trim()}
+                className={`inline-flex items-center justify-center rounded-l-md border border-input bg-background px-4 py-2 text-sm font-medium transition-colors hover:bg-accent hover:text-accent-foreground disabled:pointer-events-none disabled:opacity-50`}
+                title={`Run the query and save all of its rows as a file`}
+              >
+                <svg
+                  className={`mr-2 h-4 w-4`}
+                  xmlns={`http://www.w3.org/2000/svg`}
+                  viewBox={`0 0 24 24`}
+                  fill={`none`}
+                  stroke={`currentColor`}
+                  strokeWidth={`2`}
+                  strokeLinecap={`round`}
+                  strokeLinejoin={`round`}
+                >
+                  <path d={`M21 15v4a2 2 0 0 1-2 2H5a2 2 0 0 1-2-2v-4`} />
+                  <polyline points={`7 10 12 15 17 10`} />
+                  <line x1={`12`} y1={`15`} x2={`12`} y2={`3`} />
+                </svg>
+                {downloading ? `Downloading...` : `Download`}
+              </button>
+              <select
+                value={downloadFormat}
+                onChange={(event) => this.setState({ downloadFormat: event.target.value as QueryDownloadFormat })}
+                className={`rounded-r-md border border-l-0 border-input bg-background px-2 py-2 text-sm`}
+                title={`File format of the download`}
+              >
+                <option value={`csv`}>CSV</option>
+                <option value={`xlsx`}>Excel</option>
+                <option value={`json`}>JSON</option>
+                <option value={`ndjson`}>NDJSON</option>
+              </select>
+            </div>
+
             <button
               onClick={this.clearEditor}
               disabled={executing}
@@ -549,11 +883,55 @@ class QueryEditor extends React.PureComponent<QueryEditorProps, QueryEditorState
               </svg>
               Clear
             </button>
+
+            {transaction ? (
+              <>
+                <button
+                  onClick={() => void this.endTransaction(`commit`)}
+                  disabled={executing}
+                  className={`inline-flex items-center justify-center rounded-md border border-input bg-background px-4 py-2 text-sm font-medium transition-colors hover:bg-accent hover:text-accent-foreground disabled:pointer-events-none disabled:opacity-50`}
+                  title={`Commit the open transaction`}
+                >
+                  Commit
+                </button>
+                <button
+                  onClick={() => void this.endTransaction(`rollback`)}
+                  disabled={executing}
+                  className={`inline-flex items-center justify-center rounded-md border border-destructive bg-background px-4 py-2 text-sm font-medium text-destructive transition-colors hover:bg-destructive/10 disabled:pointer-events-none disabled:opacity-50`}
+                  title={`Roll back everything run since Begin`}
+                >
+                  Rollback
+                </button>
+              </>
+            ) : (
+              <button
+                onClick={() => void this.beginTransaction()}
+                disabled={executing}
+                className={`inline-flex items-center justify-center rounded-md border border-input bg-background px-4 py-2 text-sm font-medium transition-colors hover:bg-accent hover:text-accent-foreground disabled:pointer-events-none disabled:opacity-50`}
+                title={`Run the following queries in a transaction that can be rolled back`}
+              >
+                Begin
+              </button>
+            )}
+
+            <input
+              type={`text`}
+              value={params}
+              onChange={(event) => this.setState({ params: event.target.value })}
+              placeholder={`Parameters, e.g. ["alice@example.com", 42]`}
+              title={`JSON array of values for the placeholders (? or $1, $2, ...) in order, or JSON object of values for named placeholders (:name)`}
+              className={`min-w-0 flex-1 rounded-md border border-input bg-background px-3 py-2 font-mono text-sm ring-offset-background placeholder:text-muted-foreground focus:outline-none focus:ring-2 focus:ring-ring focus:ring-offset-2`}
+            />
         </div>
 
         {/* Editor container */}
         <div ref={this.editorContainer} className={`min-h-0 flex-1`} />
 
+        {/* Problem found while typing */}
+        {validationError && !error && (
+          <div className={`mb-4 font-mono text-xs text-destructive`}>{validationError}</div>
+        )}
+
         {/* Error message */}
         {error && (
           <div className={`mb-4 rounded-lg border border-destructive/50 bg-destructive/10 p-4 text-sm text-destructive`}>
@@ -1,19 +1,90 @@
 import { PureComponent } from 'react';
+import { apiService } from '../services/ApiService';
 import { QueryResult } from '../types/database';
 import VirtualizedTable from './VirtualizedTable';
 
+/**
+ * Rows fetched per scroll of a paged result
+ */
+const BATCH_SIZE = 500;
+
 interface QueryResultsProps {
   result: QueryResult | null;
   responseTimeMilliseconds: number | null;
 }
 
-interface QueryResultsState {}
+interface QueryResultsState {
+  /** Rows of a paged result fetched after the first page */
+  moreRows: Record<string, unknown>[];
+  hasMore: boolean;
+  loadingMore: boolean;
+  loadError: string | null;
+}
 
 /**
  * QueryResults - A class-based component that displays query execution results in a table format.
  * Shows execution time, affected rows, and any errors that occurred.
+ * Rows of a paged result are fetched while scrolling.
  */
 export default class QueryResults extends PureComponent<QueryResultsProps, QueryResultsState> {
+  state: QueryResultsState = {
+    moreRows: [],
+    hasMore: this.props.result?.hasMore ?? false,
+    loadingMore: false,
+    loadError: null,
+  };
+
+  componentDidUpdate(previousProps: QueryResultsProps): void {
+    if (previousProps.result !== this.props.result) {
+      this.setState({
+        moreRows: [],
+        hasMore: this.props.result?.hasMore ?? false,
+        loadingMore: false,
+        loadError: null,
+      });
+    }
+  }
+
+  private loadMoreRows = async (): Promise<void> => {
+    const { result } = this.props;
+    const { moreRows, hasMore, loadingMore } = this.state;
+
+    if (!result?.resultId || !hasMore || loadingMore) {
+      return;
+    }
+
+    this.setState({ loadingMore: true });
+
+    try {
+      const page = await apiService.getQueryResultRows(
+        result.resultId,
+        result.rows.length + moreRows.length,
+        BATCH_SIZE
+      );
+
+      // A newer query replaced the result meanwhile
+      if (this.props.result !== result) {
+        return;
+      }
+
+      this.setState({
+        moreRows: [...moreRows, ...page.rows],
+        hasMore: page.hasMore ?? false,
+        loadingMore: false,
+        loadError: page.error ?? null,
+      });
+    } catch (error) {
+      if (this.props.result !== result) {
+        return;
+      }
+
+      this.setState({
+        hasMore: false,
+        loadingMore: false,
+        loadError: error instanceof Error ? error.message : `Failed to load more rows`,
+      });
+    }
+  };
 
   render() {
     const { result } = this.props;
@@ -40,6 +111,8 @@ export default class QueryResults extends PureComponent<QueryResultsProps, Query
     }
 
     const { responseTimeMilliseconds } = this.props;
+    const { moreRows, hasMore, loadingMore, loadError } = this.state;
+    const rows = moreRows.length === 0 ? result.rows : [...result.rows, ...moreRows];
 
     return (
       <div className={`flex h-full flex-col`}>
@@ -55,11 +128,20 @@ export default class QueryResults extends PureComponent<QueryResultsProps, Query
           <span className={`opacity-50`}>|</span>
           <span>Affected rows: {result.affectedRows}</span>
           <span className={`opacity-50`}>|</span>
-          <span>{result.rows.length} rows returned</span>
+          <span>
+            {rows.length}
+            {hasMore ? `+` : ``} rows returned
+          </span>
+          {loadError && (
+            <>
+              <span className={`opacity-50`}>|</span>
+              <span className={`text-destructive`}>{loadError}</span>
+            </>
+          )}
         </div>
 
         {/* Results Table */}
-        {result.rows.length === 0 ? (
+        {rows.length === 0 ? (
           <div className={`flex flex-1 items-center justify-center text-muted-foreground`}>
             <p>No rows returned from query.</p>
           </div>
@@ -67,7 +149,10 @@ export default class QueryResults extends PureComponent<QueryResultsProps, Query
           <div className={`flex-1`}>
             <VirtualizedTable
               columns={result.columns}
-              rows={result.rows}
+              rows={rows}
+              hasMore={hasMore}
+              loadingMore={loadingMore}
+              onLoadMore={this.loadMoreRows}
             />
           </div>
         )}
@@ -1,5 +1,5 @@
 import { PureComponent } from 'react';
-import { X } from 'lucide-react';
+import { Star, Tag, X } from 'lucide-react';
 import { SavedQuery } from '../types/database';
 import { savedQueriesService } from '../services/SavedQueriesService';
 
@@ -10,11 +10,16 @@ interface SavedQueriesProps {
 interface SavedQueriesState {
   queries: SavedQuery[];
   loading: boolean;
+  /**
+   * Only show queries with this tag
+   */
+  tag: string | null;
+  favoritesOnly: boolean;
 }
 
 /**
  * SavedQueries - A class-based component that displays a list of saved queries.
- * Allows loading, deleting, importing, and exporting saved queries.
+ * Allows loading, deleting, tagging, favoriting, importing, and exporting saved queries.
  */
 export default class SavedQueries extends PureComponent<SavedQueriesProps, SavedQueriesState> {
   private fileInputRef: HTMLInputElement | null = null;
@@ -25,6 +30,8 @@ export default class SavedQueries extends PureComponent<SavedQueriesProps, Saved
     this.state = {
       queries: [],
       loading: true,
+      tag: null,
+      favoritesOnly: false,
     };
   }
 
@@ -39,9 +46,9 @@ export default class SavedQueries extends PureComponent<SavedQueriesProps, Saved
     }
   }
 
-  private loadQueries = (): void => {
+  private loadQueries = async (): Promise<void> => {
     try {
-      const queries = savedQueriesService.getAll();
+      const queries = await savedQueriesService.getAll();
       this.setState({ queries, loading: false });
     } catch (error) {
       console.error(`Failed to load saved queries:`, error);
@@ -53,19 +60,51 @@ export default class SavedQueries extends PureComponent<SavedQueriesProps, Saved
     this.props.onLoadQuery(sql);
   };
 
-  private handleDeleteQuery = (event: React.MouseEvent, id: string): void => {
+  private handleDeleteQuery = async (event: React.MouseEvent, id: string): Promise<void> => {
     event.stopPropagation();
     try {
-      savedQueriesService.delete(id);
-      this.loadQueries();
+      await savedQueriesService.delete(id);
     } catch (error) {
       console.error(`Failed to delete query:`, error);
     }
   };
 
-  private handleExport = (): void => {
+  private handleToggleFavorite = async (event: React.MouseEvent, query: SavedQuery): Promise<void> => {
+    event.stopPropagation();
+    try {
+      await savedQueriesService.update(query.id, { favorite: !query.favorite });
+    } catch (error) {
+      console.error(`Failed to update query:`, error);
+    }
+  };
+
+  private handleEditTags = async (event: React.MouseEvent, query: SavedQuery): Promise<void> => {
+    event.stopPropagation();
+    const input = prompt(`Tags, separated by commas`, query.tags.join(`, `));
+    if (input === null) return;
+
+    const tags = input
+      .split(`,`)
+      .map((tag) => tag.trim())
+      .filter((tag) => tag.length > 0);
     try {
-      const json = savedQueriesService.exportToJson();
+      await savedQueriesService.update(query.id, { tags });
+    } catch (error) {
+      console.error(`Failed to update query:`, error);
+    }
+  };
+
+  private handleTagChange = (event: React.ChangeEvent<HTMLSelectElement>): void => {
+    this.setState({ tag: event.target.value || null });
+  };
+
+  private handleToggleFavoritesOnly = (): void => {
+    this.setState((state) => ({ favoritesOnly: !state.favoritesOnly }));
+  };
+
+  private handleExport = async (): Promise<void> => {
+    try {
+      const json = await savedQueriesService.exportToJson();
       const blob = new Blob([json], { type: `application/json` });
       const url = URL.createObjectURL(blob);
       const link = document.createElement(`a`);
@@ -84,11 +123,10 @@ export default class SavedQueries extends PureComponent<SavedQueriesProps, Saved
     if (!file) return;
 
     const reader = new FileReader();
-    reader.onload = (e) => {
+    reader.onload = async (e) => {
       try {
         const json = e.target?.result as string;
-        savedQueriesService.importFromJson(json);
-        this.loadQueries();
+        await savedQueriesService.importFromJson(json);
         alert(`Queries imported successfully`);
       } catch (error) {
         console.error(`Failed to import queries:`, error);
@@ -96,6 +134,8 @@ export default class SavedQueries extends PureComponent<SavedQueriesProps, Saved
       }
     };
     reader.readAsText(file);
+    // Allow importing the same file again
+    event.target.value = ``;
   };
 
   private triggerFileInput = (): void => {
@@ -103,7 +143,11 @@ export default class SavedQueries extends PureComponent<SavedQueriesProps, Saved
   };
 
   render() {
-    const { queries, loading } = this.state;
+    const { queries, loading, tag, favoritesOnly } = this.state;
+    const tags = [...new Set(queries.flatMap((query) => query.tags))].sort();
+    const visibleQueries = queries.filter(
+      (query) => (!tag || query.tags.includes(tag)) && (!favoritesOnly || query.favorite)
+    );
 
     if (loading) {
       return (
@@ -193,28 +237,88 @@ export default class SavedQueries extends PureComponent<SavedQueriesProps, Saved
           </div>
         </div>
 
-        {queries.length === 0 ? (
-          <p className={`text-center text-muted-foreground text-sm py-4`}>No saved queries</p>
+        {queries.length > 0 && (
+          <div className={`flex items-center gap-2`}>
+            <select
+              value={tag ?? ``}
+              onChange={this.handleTagChange}
+              className={`h-8 min-w-0 flex-1 rounded-md border border-input bg-background px-2 text-sm text-foreground focus-visible:outline-none`}
+              title={`Only show queries with this tag`}
+            >
+              <option value={``}>All tags</option>
+              {tags.map((name) => (
+                <option key={name} value={name}>
+                  {name}
+                </option>
+              ))}
+            </select>
+            <button
+              onClick={this.handleToggleFavoritesOnly}
+              className={`inline-flex items-center justify-center rounded-md p-1.5 transition-colors hover:bg-accent focus-visible:outline-none ${favoritesOnly ? `text-yellow-500` : `text-muted-foreground`}`}
+              title={favoritesOnly ? `Show all queries` : `Only show favorites`}
+            >
+              <Star size={16} fill={favoritesOnly ? `currentColor` : `none`} />
+            </button>
+          </div>
+        )}
+
+        {visibleQueries.length === 0 ? (
+          <p className={`text-center text-muted-foreground text-sm py-4`}>
+            {queries.length === 0 ? `No saved queries` : `No saved queries match the filter`}
+          </p>
         ) : (
           <ul className={`flex flex-col gap-2`}>
-            {queries.map((query) => (
+            {visibleQueries.map((query) => (
               <li
                 key={query.id}
                 onClick={() => this.handleLoadQuery(query.sql)}
                 className={`group cursor-pointer rounded-md border border-border bg-muted p-3 transition-colors hover:bg-accent hover:border-accent`}
               >
-                {/* Header row with name and delete button */}
+                {/* Header row with name, favorite, tag, and delete buttons */}
                 <div className={`mb-1 flex items-center justify-between gap-2`}>
                   <p className={`text-sm font-medium text-foreground truncate`}>{query.name}</p>
-                  <button
-                    onClick={(event) => this.handleDeleteQuery(event, query.id)}
-                    className={`rounded p-0.5 text-muted-foreground transition-colors hover:bg-destructive/20 hover:text-destructive`}
-                    title={`Delete this query`}
-                  >
-                    <X size={14} />
-                  </button>
+                  <div className={`flex shrink-0 items-center gap-0.5`}>
+                    <button
+                      onClick={(event) => this.handleToggleFavorite(event, query)}
+                      className={`rounded p-0.5 transition-colors hover:bg-accent ${query.favorite ? `text-yellow-500` : `text-muted-foreground`}`}
+                      title={query.favorite ? `Remove from favorites` : `Add to favorites`}
+                    >
+                      <Star size={14} fill={query.favorite ? `currentColor` : `none`} />
+                    </button>
+                    <button
+                      onClick={(event) => this.handleEditTags(event, query)}
+                      className={`rounded p-0.5 text-muted-foreground transition-colors hover:bg-accent hover:text-accent-foreground`}
+                      title={`Edit tags`}
+                    >
+                      <Tag size={14} />
+                    </button>
+                    <button
+                      onClick={(event) => this.handleDeleteQuery(event, query.id)}
+                      className={`rounded p-0.5 text-muted-foreground transition-colors hover:bg-destructive/20 hover:text-destructive`}
+                      title={`Delete this query`}
+                    >
+                      <X size={14} />
+                    </button>
+                  </div>
                 </div>
 
+                {query.description && (
+                  <p className={`mb-1 text-xs text-muted-foreground line-clamp-2`}>{query.description}</p>
+                )}
+
+                {query.tags.length > 0 && (
+                  <div className={`mb-1 flex flex-wrap gap-1`}>
+                    {query.tags.map((name) => (
+                      <span
+                        key={name}
+                        className={`rounded bg-background px-1.5 py-0.5 text-xs text-muted-foreground`}
+                      >
+                        {name}
+                      </span>
+                    ))}
+                  </div>
+                )}
+
                 {/* SQL preview */}
                 <p className={`break-all text-xs font-mono text-foreground line-clamp-2`}>
                   {query.sql}
@@ -222,7 +326,7 @@ export default class SavedQueries extends PureComponent<SavedQueriesProps, Saved
 
                 {/* Created date */}
                 <p className={`mt-1 text-xs text-muted-foreground`}>
-                  {new Date(query.createdAt).toLocaleString()}
+                  {new Date(query.createdAtMilliseconds).toLocaleString()}
                 </p>
               </li>
             ))}
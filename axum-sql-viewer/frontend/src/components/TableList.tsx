@@ -1,6 +1,6 @@
 import React from 'react';
 import { apiService } from '../services/ApiService';
-import { TableInfo } from '../types/database';
+import { AttachedDatabase, TableInfo } from '../types/database';
 
 interface TableListProps {
   selectedTable: string | null;
@@ -9,6 +9,7 @@ interface TableListProps {
 
 interface TableListState {
   tables: TableInfo[];
+  attachments: AttachedDatabase[];
   loading: boolean;
   error: string | null;
 }
@@ -20,18 +21,43 @@ interface TableListState {
 class TableList extends React.PureComponent<TableListProps, TableListState> {
   state: TableListState = {
     tables: [],
+    attachments: [],
     loading: true,
     error: null,
   };
 
+  private unsubscribeFromSchemaChanges: (() => void) | null = null;
+
   /**
-   * Fetch tables from API when component mounts
+   * Fetch tables from API when component mounts and refetch them when the schema changes
    */
-  async componentDidMount(): Promise<void> {
+  componentDidMount(): void {
+    this.loadTables();
+    this.unsubscribeFromSchemaChanges = apiService.subscribeToSchemaChanges(() => {
+      this.loadTables();
+    });
+  }
+
+  /**
+   * Stop listening for schema changes
+   */
+  componentWillUnmount(): void {
+    this.unsubscribeFromSchemaChanges?.();
+    this.unsubscribeFromSchemaChanges = null;
+  }
+
+  /**
+   * Fetch tables from API
+   */
+  private loadTables = async (): Promise<void> => {
     try {
-      const response = await apiService.getTables();
+      const [response, attachments] = await Promise.all([
+        apiService.getTables(),
+        apiService.getAttachments(),
+      ]);
       this.setState({
         tables: response.tables,
+        attachments,
         loading: false,
         error: null,
       });
@@ -42,7 +68,7 @@ class TableList extends React.PureComponent<TableListProps, TableListState> {
         error: errorMessage,
       });
     }
-  }
+  };
 
   /**
    * Handle table selection
@@ -82,8 +108,7 @@ class TableList extends React.PureComponent<TableListProps, TableListState> {
    * Render table list items
    */
   private renderTableItems = (): React.ReactNode => {
-    const { tables } = this.state;
-    const { selectedTable } = this.props;
+    const { tables, attachments } = this.state;
 
     if (tables.length === 0) {
       return (
@@ -93,8 +118,39 @@ class TableList extends React.PureComponent<TableListProps, TableListState> {
       );
     }
 
+    // Tables of attached files are listed as `alias.table`, grouped below the main database
+    const attachedTables = (alias: string): TableInfo[] =>
+      tables.filter((table) => table.name.startsWith(`${alias}.`));
+    const mainTables = tables.filter(
+      (table) => !attachments.some((attachment) => table.name.startsWith(`${attachment.alias}.`))
+    );
+
+    return (
+      <div className={`py-2`}>
+        {this.renderTableGroup(mainTables, ``)}
+        {attachments.map((attachment) => (
+          <div key={attachment.alias} className={`mt-2`}>
+            <p
+              className={`px-5 py-1 text-xs font-semibold uppercase tracking-wide text-muted-foreground`}
+              title={attachment.path}
+            >
+              {attachment.alias}
+            </p>
+            {this.renderTableGroup(attachedTables(attachment.alias), `${attachment.alias}.`)}
+          </div>
+        ))}
+      </div>
+    );
+  };
+
+  /**
+   * Render the buttons of one group of tables, showing names without the group's prefix
+   */
+  private renderTableGroup = (tables: TableInfo[], prefix: string): React.ReactNode => {
+    const { selectedTable } = this.props;
+
     return (
-      <ul className={`space-y-1 py-2 px-2`}>
+      <ul className={`space-y-1 px-2`}>
         {tables.map((table) => (
           <li key={table.name}>
             <button
@@ -106,9 +162,17 @@ class TableList extends React.PureComponent<TableListProps, TableListState> {
               }`}
               type={`button`}
             >
-              <span className={`truncate`}>{table.name}</span>
+              <span className={`truncate`}>{table.name.slice(prefix.length)}</span>
               {table.rowCount != null && (
-                <span className={`flex-shrink-0 text-xs text-muted-foreground`}>
+                <span
+                  className={`flex-shrink-0 text-xs text-muted-foreground`}
+                  title={
+                    table.asOfMilliseconds != null
+                      ? `${table.exact ? `Counted` : `Approximate, counted`} ${new Date(table.asOfMilliseconds).toLocaleString()}`
+                      : undefined
+                  }
+                >
+                  {table.exact === false ? `~` : ``}
                   {table.rowCount.toLocaleString()}
                 </span>
               )}
@@ -1,14 +1,21 @@
 import React from 'react';
-import { Search } from 'lucide-react';
+import { Download, Link, Search } from 'lucide-react';
 import { apiService } from '../services/ApiService';
-import { TableSchema } from '../types/database';
+import { SharedView, TableSchema } from '../types/database';
 import { cn } from '../lib/utils';
 import VirtualizedTable from './VirtualizedTable';
 import Switch from './ui/Switch';
 
+/**
+ * Filters, sort, and search of a table opened from a permalink
+ */
+export type TableView = Omit<Extract<SharedView, { type: `table` }>, `type` | `table`>;
+
 interface TableViewerProps {
   tableName: string;
   className?: string;
+  /** Filters, sort, and search to open the table with */
+  initialView?: TableView;
 }
 
 interface TableViewerState {
@@ -16,7 +23,11 @@ interface TableViewerState {
   columns: string[];
   schema: TableSchema | null;
   total: number;
+  /** Whether rows follow the loaded ones */
+  hasMore: boolean;
   offset: number;
+  /** Cursor continuing after the loaded rows, when the table has one */
+  nextCursor: string | null;
   loading: boolean;
   loadingMore: boolean;
   sorting: boolean;
@@ -24,10 +35,14 @@ interface TableViewerState {
   error: string | null;
   sortBy: string | null;
   sortOrder: `ascending` | `descending` | null;
+  /** Column filters sent with every row request */
+  filters: Record<string, string>;
   searchQuery: string;
   appliedSearchQuery: string;
   searchOnType: boolean;
   selectedRows: Set<number>;
+  linkStatus: string | null;
+  dumpStatus: string | null;
 }
 
 interface _TablesResponse {
@@ -46,24 +61,42 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
     columns: [],
     schema: null,
     total: 0,
+    hasMore: false,
     offset: 0,
+    nextCursor: null,
     loading: true,
     loadingMore: false,
     sorting: false,
     switching: false,
     error: null,
-    sortBy: null,
-    sortOrder: null,
-    searchQuery: ``,
-    appliedSearchQuery: ``,
+    sortBy: this.props.initialView?.sortBy ?? null,
+    sortOrder: this.props.initialView?.sortOrder ?? null,
+    filters: this.props.initialView?.filters ?? {},
+    searchQuery: this.props.initialView?.search ?? ``,
+    appliedSearchQuery: this.props.initialView?.search ?? ``,
     searchOnType: true,
     selectedRows: new Set<number>(),
+    linkStatus: null,
+    dumpStatus: null,
   };
 
+  /** Stops listening for database notifications */
+  private unsubscribeFromNotifications: (() => void) | null = null;
+
   async componentDidMount(): Promise<void> {
+    this.unsubscribeFromNotifications = apiService.subscribeToNotifications(({ payload }) => {
+      if (payload === `` || payload === this.props.tableName) {
+        void this.refreshRows();
+      }
+    });
     await this.loadInitialData();
   }
 
+  componentWillUnmount(): void {
+    this.unsubscribeFromNotifications?.();
+    this.unsubscribeFromNotifications = null;
+  }
+
   async componentDidUpdate(previousProps: TableViewerProps): Promise<void> {
     if (previousProps.tableName !== this.props.tableName) {
       await this.loadInitialData(true);
@@ -81,6 +114,7 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
         error: null,
         sortBy: null,
         sortOrder: null,
+        filters: {},
         searchQuery: ``,
         appliedSearchQuery: ``,
         selectedRows: new Set<number>(),
@@ -91,11 +125,13 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
         error: null,
         rows: [],
         offset: 0,
-        searchQuery: ``,
-        appliedSearchQuery: ``,
+        nextCursor: null,
         selectedRows: new Set<number>(),
       });
     }
+    const { sortBy, sortOrder, filters } = isTableSwitch
+      ? { sortBy: null, sortOrder: null, filters: {} }
+      : this.state;
 
     try {
       // Load schema and initial rows in parallel
@@ -104,6 +140,9 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
         apiService.getRows(tableName, {
           offset: 0,
           limit: BATCH_SIZE,
+          sortBy: sortBy ?? undefined,
+          sortOrder: sortOrder ?? undefined,
+          filters,
         }),
       ]);
 
@@ -112,7 +151,9 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
         rows: rowsResponse.rows,
         columns: rowsResponse.columns,
         total: rowsResponse.total,
+        hasMore: rowsResponse.hasMore,
         offset: rowsResponse.rows.length,
+        nextCursor: rowsResponse.nextCursor ?? null,
         loading: false,
         switching: false,
       });
@@ -125,11 +166,50 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
     }
   };
 
+  /**
+   * Reload the rows in view, keeping sort and filters, after the table's data changed
+   */
+  private refreshRows = async (): Promise<void> => {
+    const { tableName } = this.props;
+    const { offset, sortBy, sortOrder, filters, loading, switching } = this.state;
+
+    if (loading || switching) {
+      return;
+    }
+
+    try {
+      const rowsResponse = await apiService.getRows(tableName, {
+        offset: 0,
+        limit: Math.max(offset, BATCH_SIZE),
+        sortBy: sortBy ?? undefined,
+        sortOrder: sortOrder ?? undefined,
+        filters,
+      });
+
+      // The user may have switched tables while the rows loaded
+      if (tableName !== this.props.tableName) {
+        return;
+      }
+      this.setState({
+        rows: rowsResponse.rows,
+        columns: rowsResponse.columns,
+        total: rowsResponse.total,
+        hasMore: rowsResponse.hasMore,
+        offset: rowsResponse.rows.length,
+        nextCursor: rowsResponse.nextCursor ?? null,
+      });
+    } catch (error) {
+      this.setState({
+        error: error instanceof Error ? error.message : `Failed to refresh rows`,
+      });
+    }
+  };
+
   private loadMoreRows = async (): Promise<void> => {
     const { tableName } = this.props;
-    const { rows, offset, total, loadingMore } = this.state;
+    const { rows, offset, nextCursor, hasMore, loadingMore } = this.state;
 
-    if (loadingMore || offset >= total) {
+    if (loadingMore || !hasMore) {
       return;
     }
 
@@ -139,13 +219,20 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
       const rowsResponse = await apiService.getRows(tableName, {
         offset,
         limit: BATCH_SIZE,
+        // Continues after the last loaded row without the database skipping `offset` rows
+        cursor: nextCursor ?? undefined,
+        // The total is known from the first page
+        count: false,
         sortBy: this.state.sortBy ?? undefined,
         sortOrder: this.state.sortOrder ?? undefined,
+        filters: this.state.filters,
       });
 
       this.setState({
         rows: [...rows, ...rowsResponse.rows],
         offset: offset + rowsResponse.rows.length,
+        nextCursor: rowsResponse.nextCursor ?? null,
+        hasMore: rowsResponse.hasMore,
         loadingMore: false,
       });
     } catch (error) {
@@ -188,13 +275,16 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
         limit: BATCH_SIZE,
         sortBy: newSortBy ?? undefined,
         sortOrder: newSortOrder ?? undefined,
+        filters: this.state.filters,
       });
 
       this.setState({
         rows: rowsResponse.rows,
         columns: rowsResponse.columns,
         total: rowsResponse.total,
+        hasMore: rowsResponse.hasMore,
         offset: rowsResponse.rows.length,
+        nextCursor: rowsResponse.nextCursor ?? null,
         sorting: false,
       });
     } catch (error) {
@@ -249,6 +339,34 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
     });
   };
 
+  private handleCopyLink = async (): Promise<void> => {
+    const { sortBy, sortOrder, filters, appliedSearchQuery } = this.state;
+    try {
+      const link = await apiService.createPermalink({
+        type: `table`,
+        table: this.props.tableName,
+        filters,
+        sortBy,
+        sortOrder,
+        search: appliedSearchQuery || null,
+      });
+      await navigator.clipboard.writeText(link);
+      this.setState({ linkStatus: `Link copied` });
+    } catch (error) {
+      this.setState({ linkStatus: error instanceof Error ? error.message : `Failed to copy link` });
+    }
+    window.setTimeout(() => this.setState({ linkStatus: null }), 3000);
+  };
+
+  private handleDump = async (): Promise<void> => {
+    try {
+      await apiService.downloadDump([this.props.tableName]);
+    } catch (error) {
+      this.setState({ dumpStatus: error instanceof Error ? error.message : `Failed to dump table` });
+      window.setTimeout(() => this.setState({ dumpStatus: null }), 3000);
+    }
+  };
+
   private handleRowSelect = (rowIndex: number, selected: boolean): void => {
     this.setState((previousState) => {
       const newSelectedRows = new Set(previousState.selectedRows);
@@ -315,7 +433,7 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
       ).join(`, `);
       const deleteQuery = `DELETE FROM "${tableName}" WHERE "${primaryKeyColumn.name}" IN (${placeholders})`;
 
-      await apiService.executeQuery(deleteQuery);
+      await apiService.executeConfirmedQuery(deleteQuery);
 
       // Clear selection and reload data
       this.setState({ selectedRows: new Set<number>() });
@@ -368,7 +486,7 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
 
     const updateQuery = `UPDATE "${tableName}" SET "${column}" = ${escapedValue} WHERE "${primaryKeyColumn.name}" = ${escapedPrimaryKey}`;
 
-    await apiService.executeQuery(updateQuery);
+    await apiService.executeConfirmedQuery(updateQuery);
 
     // Update local state immediately for responsive UI
     this.setState((previousState) => {
@@ -405,7 +523,7 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
 
   render(): React.ReactNode {
     const { className } = this.props;
-    const { loading, switching, error, rows, columns, schema, total, loadingMore, sortBy, sortOrder, searchQuery, appliedSearchQuery, searchOnType, selectedRows } = this.state;
+    const { loading, switching, error, rows, columns, schema, total, hasMore, loadingMore, sortBy, sortOrder, searchQuery, appliedSearchQuery, searchOnType, selectedRows, linkStatus, dumpStatus } = this.state;
     const filteredRows = this.getFilteredRows();
 
     if (loading) {
@@ -490,6 +608,22 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
               onCheckedChange={this.handleSearchModeToggle}
             />
           </div>
+          <button
+            onClick={() => void this.handleCopyLink()}
+            className={`inline-flex items-center gap-2 rounded-md border border-input bg-background px-3 py-2 text-sm font-medium hover:bg-accent hover:text-accent-foreground transition-colors`}
+            title={`Copy a link that opens this table with its filters, sort, and search`}
+          >
+            <Link size={16} />
+            {linkStatus ?? `Copy link`}
+          </button>
+          <button
+            onClick={() => void this.handleDump()}
+            className={`inline-flex items-center gap-2 rounded-md border border-input bg-background px-3 py-2 text-sm font-medium hover:bg-accent hover:text-accent-foreground transition-colors`}
+            title={`Download this table's schema and rows as a SQL script (PostgreSQL only)`}
+          >
+            <Download size={16} />
+            {dumpStatus ?? `Dump`}
+          </button>
           {selectedRows.size > 0 && (
             <button
               onClick={this.handleDeleteSelected}
@@ -533,7 +667,7 @@ class TableViewer extends React.PureComponent<TableViewerProps, TableViewerState
             schema={schema}
             tableName={this.props.tableName}
             total={displayTotal}
-            hasMore={!isFiltered && rows.length < total}
+            hasMore={!isFiltered && hasMore}
             loadingMore={loadingMore}
             onLoadMore={isFiltered ? undefined : this.loadMoreRows}
             sortBy={sortBy}
@@ -4,7 +4,18 @@ import {
   RowsResponse,
   CountResponse,
   QueryResult,
+  QueryValidation,
+  QueryDownloadFormat,
   RowQuery,
+  SchemaChange,
+  DatabaseNotification,
+  AttachedDatabase,
+  TransactionResponse,
+  ConfirmationRequired,
+  SharedView,
+  PermalinkResponse,
+  MigrationsResponse,
+  JobInfo,
 } from '../types/database';
 
 class ApiService {
@@ -36,6 +47,20 @@ class ApiService {
     return response.json();
   }
 
+  /**
+   * Fetch the attached database files (none if the database does not support attaching)
+   */
+  public async getAttachments(): Promise<AttachedDatabase[]> {
+    const response = await fetch(`${this.basePath}/api/attachments`);
+    if (response.status === 501) {
+      return [];
+    }
+    if (!response.ok) {
+      throw new Error(`Failed to fetch attachments: ${response.statusText}`);
+    }
+    return response.json();
+  }
+
   /**
    * Fetch schema information for a specific table
    */
@@ -65,12 +90,36 @@ class ApiService {
       parameters.append(`sortOrder`, query.sortOrder);
     }
 
+    if (query.nulls) {
+      parameters.append(`nulls`, query.nulls);
+    }
+
     if (query.filters) {
       for (const [key, value] of Object.entries(query.filters)) {
         parameters.append(`filter[${key}]`, value);
       }
     }
 
+    if (query.caseInsensitive) {
+      parameters.append(`caseInsensitive`, `true`);
+    }
+
+    if (query.q) {
+      parameters.append(`q`, query.q);
+    }
+
+    if (query.cursor) {
+      parameters.append(`cursor`, query.cursor);
+    }
+
+    if (query.count === false) {
+      parameters.append(`count`, `false`);
+    }
+
+    if (query.columns && query.columns.length > 0) {
+      parameters.append(`columns`, query.columns.join(`,`));
+    }
+
     const response = await fetch(
       `${this.basePath}/api/tables/${encodeURIComponent(name)}/rows?${parameters.toString()}`
     );
@@ -98,21 +147,281 @@ class ApiService {
   }
 
   /**
-   * Execute a raw SQL query
+   * Execute a raw SQL query, binding `params` to its placeholders in order (array) or by name (object)
+   *
+   * With `tx`, the query runs in that transaction (see `beginTransaction`). Without one, an
+   * `id` makes the query cancellable with `cancelQuery` while it runs.
+   *
+   * A destructive statement (DELETE, UPDATE, DROP, ...) only runs if `confirm` accepts
+   * what the server reports about it; otherwise the result carries the server's message.
+   *
+   * With `pageSize` (outside a transaction), a query returning rows only returns its first
+   * `pageSize` rows and a `resultId` to fetch the rest with `getQueryResultRows`.
    */
-  public async executeQuery(sql: string): Promise<QueryResult> {
-    const response = await fetch(`${this.basePath}/api/query`, {
-      method: `POST`,
-      headers: {
-        'Content-Type': `application/json`,
-      },
-      body: JSON.stringify({ sql }),
-    });
+  public async executeQuery(
+    sql: string,
+    params: unknown[] | Record<string, unknown> = [],
+    tx: string | null = null,
+    id: string | null = null,
+    confirm: (plan: ConfirmationRequired) => boolean = () => false,
+    pageSize: number | null = null
+  ): Promise<QueryResult> {
+    const path = tx ? `/api/tx/${encodeURIComponent(tx)}/query` : `/api/query`;
+    const send = (confirmation: string | null): Promise<Response> =>
+      fetch(`${this.basePath}${path}`, {
+        method: `POST`,
+        headers: {
+          'Content-Type': `application/json`,
+        },
+        body: JSON.stringify({
+          sql,
+          params,
+          id: id ?? undefined,
+          confirmation: confirmation ?? undefined,
+          pageSize: tx ? undefined : pageSize ?? undefined,
+        }),
+      });
+
+    let response = await send(null);
+    if (response.status === 428) {
+      const plan: ConfirmationRequired = await response.json();
+      if (!confirm(plan)) {
+        return { columns: [], rows: [], affectedRows: 0, executionTimeMilliseconds: 0, error: plan.error };
+      }
+      response = await send(plan.confirmation);
+    }
 
     // Always try to parse the JSON body since error details are in the response
     const result: QueryResult = await response.json();
     return result;
   }
+
+  /**
+   * Execute a statement the user has already confirmed, skipping the server's confirmation step
+   */
+  public async executeConfirmedQuery(sql: string): Promise<QueryResult> {
+    return this.executeQuery(sql, [], null, null, () => true);
+  }
+
+  /**
+   * Check SQL without running it
+   */
+  public async validateQuery(sql: string): Promise<QueryValidation> {
+    const response = await fetch(`${this.basePath}/api/query`, {
+      method: `POST`,
+      headers: { 'Content-Type': `application/json` },
+      body: JSON.stringify({ sql, validateOnly: true }),
+    });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to validate query`));
+    }
+    return response.json();
+  }
+
+  /**
+   * Run a query and save its rows as a file in the given format
+   */
+  public async downloadQuery(
+    sql: string,
+    params: unknown[] | Record<string, unknown>,
+    format: QueryDownloadFormat
+  ): Promise<void> {
+    const response = await fetch(`${this.basePath}/api/query`, {
+      method: `POST`,
+      headers: { 'Content-Type': `application/json` },
+      body: JSON.stringify({ sql, params, format }),
+    });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to download query result`));
+    }
+    await this.saveDownload(response, `query-result.${format}`);
+  }
+
+  /**
+   * Save a SQL script recreating the given tables with their rows (PostgreSQL only)
+   */
+  public async downloadDump(tables: string[]): Promise<void> {
+    const parameters = new URLSearchParams();
+    parameters.append(`tables`, tables.join(`,`));
+
+    const response = await fetch(`${this.basePath}/api/dump?${parameters.toString()}`);
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to dump tables`));
+    }
+    await this.saveDownload(response, `dump.sql`);
+  }
+
+  /**
+   * Fetch more rows of a result returned by `executeQuery` with a `pageSize`
+   */
+  public async getQueryResultRows(resultId: string, offset: number, limit: number): Promise<QueryResult> {
+    const parameters = new URLSearchParams();
+    parameters.append(`offset`, String(offset));
+    parameters.append(`limit`, String(limit));
+
+    const response = await fetch(
+      `${this.basePath}/api/query/${encodeURIComponent(resultId)}/rows?${parameters.toString()}`
+    );
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to fetch more rows`));
+    }
+    return response.json();
+  }
+
+  /**
+   * Stop a query started with `executeQuery` and an `id`
+   */
+  public async cancelQuery(id: string): Promise<void> {
+    const response = await fetch(`${this.basePath}/api/query/${encodeURIComponent(id)}/cancel`, {
+      method: `POST`,
+    });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to cancel query`));
+    }
+  }
+
+  /**
+   * Begin a transaction on a dedicated connection
+   */
+  public async beginTransaction(): Promise<TransactionResponse> {
+    const response = await fetch(`${this.basePath}/api/tx/begin`, { method: `POST` });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to begin transaction`));
+    }
+    return response.json();
+  }
+
+  /**
+   * Commit or roll back a transaction
+   */
+  public async endTransaction(tx: string, action: `commit` | `rollback`): Promise<void> {
+    const response = await fetch(`${this.basePath}/api/tx/${encodeURIComponent(tx)}/${action}`, {
+      method: `POST`,
+    });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to ${action} transaction`));
+    }
+  }
+
+  /**
+   * Keep a view on the server and return the link that opens it
+   */
+  public async createPermalink(view: SharedView): Promise<string> {
+    const response = await fetch(`${this.basePath}/api/permalinks`, {
+      method: `POST`,
+      headers: { 'Content-Type': `application/json` },
+      body: JSON.stringify(view),
+    });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to create link`));
+    }
+    const { token } = (await response.json()) as PermalinkResponse;
+    return `${window.location.origin}${window.location.pathname}#/view/${encodeURIComponent(token)}`;
+  }
+
+  /**
+   * Fetch the view kept under a permalink token
+   */
+  public async getPermalink(token: string): Promise<SharedView> {
+    const response = await fetch(`${this.basePath}/api/permalinks/${encodeURIComponent(token)}`);
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to open link`));
+    }
+    const { view } = (await response.json()) as PermalinkResponse;
+    return view;
+  }
+
+  /**
+   * Fetch the migrations recorded by sqlx and Diesel
+   */
+  public async getMigrations(): Promise<MigrationsResponse> {
+    const response = await fetch(`${this.basePath}/api/migrations`);
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to fetch migrations`));
+    }
+    return response.json();
+  }
+
+  /**
+   * Start a job applying the pending migrations of the configured migrator
+   */
+  public async runMigrations(): Promise<JobInfo> {
+    const response = await fetch(`${this.basePath}/api/migrations/run`, { method: `POST` });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to apply migrations`));
+    }
+    return response.json();
+  }
+
+  /**
+   * Fetch the current state of a job
+   */
+  public async getJob(id: number): Promise<JobInfo> {
+    const response = await fetch(`${this.basePath}/api/jobs/${id}`);
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to fetch job`));
+    }
+    return response.json();
+  }
+
+  /**
+   * Save a response body as a file, named by its `Content-Disposition` header or `fallbackName`
+   */
+  private async saveDownload(response: Response, fallbackName: string): Promise<void> {
+    const disposition = response.headers.get(`Content-Disposition`) ?? ``;
+    const filename = /filename="([^"]+)"/.exec(disposition)?.[1] ?? fallbackName;
+    const url = URL.createObjectURL(await response.blob());
+    const link = document.createElement(`a`);
+    link.href = url;
+    link.download = filename;
+    link.click();
+    URL.revokeObjectURL(url);
+  }
+
+  /**
+   * Error message from a failed response's JSON body, or `fallback` with the status text
+   */
+  private async errorMessage(response: Response, fallback: string): Promise<string> {
+    try {
+      const body = (await response.json()) as { error?: string };
+      if (body.error) {
+        return body.error;
+      }
+    } catch {
+      // Not a JSON body
+    }
+    return `${fallback}: ${response.statusText}`;
+  }
+
+  /**
+   * Listen for schema change events; returns a function that stops listening
+   */
+  public subscribeToSchemaChanges(onChange: (change: SchemaChange) => void): () => void {
+    const source = new EventSource(`${this.basePath}/api/events`);
+    const listener = (event: MessageEvent): void => {
+      onChange(JSON.parse(event.data) as SchemaChange);
+    };
+    source.addEventListener(`schemaChanged`, listener);
+    return () => {
+      source.removeEventListener(`schemaChanged`, listener);
+      source.close();
+    };
+  }
+
+  /**
+   * Listen for database notifications; returns a function that stops listening
+   */
+  public subscribeToNotifications(onNotification: (notification: DatabaseNotification) => void): () => void {
+    const source = new EventSource(`${this.basePath}/api/notifications`);
+    const listener = (event: MessageEvent): void => {
+      onNotification(JSON.parse(event.data) as DatabaseNotification);
+    };
+    source.addEventListener(`notification`, listener);
+    return () => {
+      source.removeEventListener(`notification`, listener);
+      source.close();
+    };
+  }
 }
 
 export const apiService = ApiService.getInstance();
@@ -1,11 +1,36 @@
 import { SavedQuery } from '../types/database';
 
+type QueryParameters = unknown[] | Record<string, unknown>;
+
+/**
+ * Fields of a saved query that are sent when saving it
+ */
+type SavedQueryContents = Pick<SavedQuery, `name` | `sql` | `description` | `parameters` | `tags` | `favorite`>;
+
+/**
+ * Which saved queries to list
+ */
+export interface SavedQueryFilter {
+  tag?: string;
+  favorite?: boolean;
+}
+
 class SavedQueriesService {
   private static instance: SavedQueriesService;
-  private readonly STORAGE_KEY = `axum-sql-viewer:saved-queries`;
+  /**
+   * Key under which earlier versions kept saved queries in localStorage
+   */
+  private readonly LEGACY_STORAGE_KEY = `axum-sql-viewer:saved-queries`;
+  private basePath: string;
   private listeners: Set<() => void> = new Set();
+  private migration: Promise<void> | null = null;
 
-  private constructor() {}
+  private constructor() {
+    // Extract base path from <base href> tag
+    const baseElement = document.querySelector(`base`);
+    const href = baseElement?.getAttribute(`href`);
+    this.basePath = href?.replace(/\/$/, ``) ?? ``;
+  }
 
   public static getInstance(): SavedQueriesService {
     if (!SavedQueriesService.instance) {
@@ -15,93 +40,119 @@ class SavedQueriesService {
   }
 
   /**
-   * Get all saved queries from localStorage
+   * Get the saved queries from the server, favorites first
    */
-  public getAll(): SavedQuery[] {
-    try {
-      const data = localStorage.getItem(this.STORAGE_KEY);
-      return data ? JSON.parse(data) : [];
-    } catch (error) {
-      console.error(`Failed to retrieve saved queries from localStorage:`, error);
-      return [];
+  public async getAll(filter: SavedQueryFilter = {}): Promise<SavedQuery[]> {
+    await this.migrateLocalQueries();
+    const parameters = new URLSearchParams();
+    if (filter.tag) {
+      parameters.append(`tag`, filter.tag);
     }
+    if (filter.favorite !== undefined) {
+      parameters.append(`favorite`, String(filter.favorite));
+    }
+    const response = await fetch(`${this.basePath}/api/saved-queries?${parameters.toString()}`);
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to fetch saved queries`));
+    }
+    const body = (await response.json()) as { queries: SavedQuery[] };
+    return body.queries;
   }
 
   /**
-   * Save a new query to localStorage
+   * Save a new query on the server
    */
-  public save(name: string, sql: string): SavedQuery {
-    const queries = this.getAll();
-    const newQuery: SavedQuery = {
-      id: crypto.randomUUID(),
-      name,
-      sql,
-      createdAt: new Date().toISOString(),
-      updatedAt: new Date().toISOString(),
-    };
-    queries.push(newQuery);
-    localStorage.setItem(this.STORAGE_KEY, JSON.stringify(queries));
+  public async save(
+    name: string,
+    sql: string,
+    description: string | null = null,
+    parameters: QueryParameters = [],
+    tags: string[] = [],
+    favorite = false
+  ): Promise<SavedQuery> {
+    const query = await this.create({ name, sql, description, parameters, tags, favorite });
     this.notifyListeners();
-    return newQuery;
+    return query;
   }
 
   /**
-   * Update an existing saved query
+   * Replace the contents of an existing saved query
    */
-  public update(id: string, updates: Partial<SavedQuery>): void {
-    const queries = this.getAll();
-    const index = queries.findIndex((query) => query.id === id);
-    if (index === -1) {
-      console.warn(`Saved query with id "${id}" not found`);
-      return;
+  public async update(
+    id: string,
+    updates: Partial<SavedQueryContents>
+  ): Promise<SavedQuery> {
+    const queries = await this.getAll();
+    const existing = queries.find((query) => query.id === id);
+    if (!existing) {
+      throw new Error(`Saved query with id "${id}" not found`);
+    }
+
+    const response = await fetch(`${this.basePath}/api/saved-queries/${encodeURIComponent(id)}`, {
+      method: `PUT`,
+      headers: { 'Content-Type': `application/json` },
+      body: JSON.stringify({
+        name: updates.name ?? existing.name,
+        sql: updates.sql ?? existing.sql,
+        description: updates.description !== undefined ? updates.description : existing.description,
+        parameters: updates.parameters ?? existing.parameters,
+        tags: updates.tags ?? existing.tags,
+        favorite: updates.favorite ?? existing.favorite,
+      }),
+    });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to update saved query`));
     }
-    queries[index] = {
-      ...queries[index],
-      ...updates,
-      updatedAt: new Date().toISOString(),
-    };
-    localStorage.setItem(this.STORAGE_KEY, JSON.stringify(queries));
     this.notifyListeners();
+    return response.json();
   }
 
   /**
    * Delete a saved query by id
    */
-  public delete(id: string): void {
-    const queries = this.getAll();
-    const filtered = queries.filter((query) => query.id !== id);
-    if (filtered.length === queries.length) {
-      console.warn(`Saved query with id "${id}" not found`);
-      return;
+  public async delete(id: string): Promise<void> {
+    const response = await fetch(`${this.basePath}/api/saved-queries/${encodeURIComponent(id)}`, {
+      method: `DELETE`,
+    });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to delete saved query`));
     }
-    localStorage.setItem(this.STORAGE_KEY, JSON.stringify(filtered));
     this.notifyListeners();
   }
 
   /**
    * Export all saved queries to JSON string
    */
-  public exportToJson(): string {
-    const queries = this.getAll();
+  public async exportToJson(): Promise<string> {
+    const queries = await this.getAll();
     return JSON.stringify(queries, null, 2);
   }
 
   /**
-   * Import saved queries from JSON string
+   * Import saved queries from JSON string, adding them to the ones already saved
    */
-  public importFromJson(json: string): void {
+  public async importFromJson(json: string): Promise<void> {
     try {
-      const queries = JSON.parse(json) as SavedQuery[];
+      const queries = JSON.parse(json) as Partial<SavedQuery>[];
       if (!Array.isArray(queries)) {
         throw new Error(`Invalid format: expected an array of queries`);
       }
       // Validate query objects
       queries.forEach((query) => {
-        if (!query.id || !query.name || !query.sql) {
-          throw new Error(`Invalid query object: missing required fields (id, name, sql)`);
+        if (!query.name || !query.sql) {
+          throw new Error(`Invalid query object: missing required fields (name, sql)`);
         }
       });
-      localStorage.setItem(this.STORAGE_KEY, JSON.stringify(queries));
+      for (const query of queries) {
+        await this.create({
+          name: query.name!,
+          sql: query.sql!,
+          description: query.description ?? null,
+          parameters: query.parameters ?? [],
+          tags: query.tags ?? [],
+          favorite: query.favorite ?? false,
+        });
+      }
       this.notifyListeners();
     } catch (error) {
       console.error(`Failed to import saved queries:`, error);
@@ -125,6 +176,71 @@ class SavedQueriesService {
   private notifyListeners(): void {
     this.listeners.forEach((listener) => listener());
   }
+
+  /**
+   * Save a query on the server without notifying listeners
+   */
+  private async create(
+    query: SavedQueryContents
+  ): Promise<SavedQuery> {
+    const response = await fetch(`${this.basePath}/api/saved-queries`, {
+      method: `POST`,
+      headers: { 'Content-Type': `application/json` },
+      body: JSON.stringify(query),
+    });
+    if (!response.ok) {
+      throw new Error(await this.errorMessage(response, `Failed to save query`));
+    }
+    return response.json();
+  }
+
+  /**
+   * Move queries saved in localStorage by earlier versions to the server, once
+   */
+  private migrateLocalQueries(): Promise<void> {
+    if (!this.migration) {
+      this.migration = (async () => {
+        const data = localStorage.getItem(this.LEGACY_STORAGE_KEY);
+        if (!data) {
+          return;
+        }
+        try {
+          const queries = JSON.parse(data) as { name?: string; sql?: string }[];
+          for (const query of Array.isArray(queries) ? queries : []) {
+            if (query.name && query.sql) {
+              await this.create({
+                name: query.name,
+                sql: query.sql,
+                description: null,
+                parameters: [],
+                tags: [],
+                favorite: false,
+              });
+            }
+          }
+          localStorage.removeItem(this.LEGACY_STORAGE_KEY);
+        } catch (error) {
+          console.error(`Failed to move saved queries from localStorage to the server:`, error);
+        }
+      })();
+    }
+    return this.migration;
+  }
+
+  /**
+   * Error message from a failed response's JSON body, or `fallback` with the status text
+   */
+  private async errorMessage(response: Response, fallback: string): Promise<string> {
+    try {
+      const body = (await response.json()) as { error?: string };
+      if (body.error) {
+        return body.error;
+      }
+    } catch {
+      // Not a JSON body
+    }
+    return `${fallback}: ${response.statusText}`;
+  }
 }
 
 export const savedQueriesService = SavedQueriesService.getInstance();
@@ -9,6 +9,10 @@
 export interface TableInfo {
   name: string;
   rowCount?: number;
+  /** Whether `rowCount` was counted exactly for this response (not cached or estimated) */
+  exact?: boolean;
+  /** When `rowCount` was taken, in milliseconds since the Unix epoch */
+  asOfMilliseconds?: number;
 }
 
 /**
@@ -18,11 +22,21 @@ export interface TablesResponse {
   tables: TableInfo[];
 }
 
+/**
+ * Database file attached under an alias; its tables are listed as `alias.table`
+ */
+export interface AttachedDatabase {
+  alias: string;
+  path: string;
+}
+
 /**
  * Response from fetching row count
  */
 export interface CountResponse {
   count: number;
+  exact: boolean;
+  asOfMilliseconds: number;
 }
 
 /**
@@ -71,10 +85,16 @@ export interface TableSchema {
 export interface RowsResponse {
   rows: Record<string, unknown>[];
   total: number;
+  /** Whether `total` was counted exactly for this response */
+  exact: boolean;
+  /** When `total` was counted, in milliseconds since the Unix epoch */
+  asOfMilliseconds: number;
   offset: number;
   limit: number;
   hasMore: boolean;
   columns: string[];
+  /** Cursor continuing after the last row, for tables with a primary key or rowid */
+  nextCursor?: string;
 }
 
 /**
@@ -85,7 +105,21 @@ export interface RowQuery {
   limit: number;
   sortBy?: string;
   sortOrder?: SortOrder;
+  /** Where rows with NULL in the sort column go */
+  nulls?: NullsOrder;
   filters?: Record<string, string>;
+  /** Groups of filters of which at least one must match (POSTed queries only) */
+  filterGroups?: Record<string, string>[];
+  /** Match filters ignoring case */
+  caseInsensitive?: boolean;
+  /** Text to search for in all textual columns */
+  q?: string;
+  /** `nextCursor` of the previous page, to continue after its last row */
+  cursor?: string;
+  /** `false` to skip counting the rows; `total` of the response is then `null` */
+  count?: boolean;
+  /** Columns to fetch (all columns when not set) */
+  columns?: string[];
 }
 
 /**
@@ -93,6 +127,8 @@ export interface RowQuery {
  */
 export type SortOrder = `ascending` | `descending`;
 
+export type NullsOrder = `first` | `last`;
+
 /**
  * Request body for executing a raw SQL query
  */
@@ -109,17 +145,89 @@ export interface QueryResult {
   affectedRows: number;
   executionTimeMilliseconds: number;
   error?: string;
+  /** Handle to fetch more rows with, if the query was paged and rows follow */
+  resultId?: string | null;
+  /** Whether a paged result has rows after these */
+  hasMore?: boolean;
+}
+
+/**
+ * Result of checking SQL without running it
+ */
+export interface QueryValidation {
+  valid: boolean;
+  /** Whether the database explained the statements, not only the parser */
+  explained: boolean;
+  error: string | null;
+  /** Line of the syntax error, starting at 1 */
+  line: number | null;
+  /** Column of the syntax error, starting at 1 */
+  column: number | null;
+}
+
+/**
+ * File formats query results can be downloaded as
+ */
+export type QueryDownloadFormat = `csv` | `xlsx` | `ndjson` | `json`;
+
+/**
+ * Response to a destructive statement that has to be confirmed before it runs
+ */
+export interface ConfirmationRequired {
+  error: string;
+  statementKind: `delete` | `update` | `drop` | `truncate` | `merge` | `insert`;
+  estimatedAffectedRows: number | null;
+  confirmation: string;
+  expiresInSeconds: number;
+}
+
+/**
+ * Response from beginning an interactive transaction
+ */
+export interface TransactionResponse {
+  tx: string;
+  idleTimeoutSeconds: number;
 }
 
 /**
- * Represents a saved query stored in localStorage
+ * A view of a table or a query that can be shared by permalink
+ */
+export type SharedView =
+  | {
+      type: `table`;
+      table: string;
+      filters?: Record<string, string>;
+      sortBy?: string | null;
+      sortOrder?: SortOrder | null;
+      search?: string | null;
+    }
+  | {
+      type: `query`;
+      sql: string;
+      parameters?: unknown[] | Record<string, unknown>;
+    };
+
+/**
+ * Response from sharing a view or opening a permalink
+ */
+export interface PermalinkResponse {
+  token: string;
+  view: SharedView;
+}
+
+/**
+ * Represents a saved query stored on the server
  */
 export interface SavedQuery {
   id: string;
   name: string;
   sql: string;
-  createdAt: string;
-  updatedAt: string;
+  description: string | null;
+  parameters: unknown[] | Record<string, unknown>;
+  tags: string[];
+  favorite: boolean;
+  createdAtMilliseconds: number;
+  updatedAtMilliseconds: number;
 }
 
 /**
@@ -134,3 +242,66 @@ export interface QueryHistoryEntry {
   error?: string;
   rowCount?: number;
 }
+
+/**
+ * Tables that changed, sent by the server in a schemaChanged event
+ */
+export interface SchemaChange {
+  added: string[];
+  removed: string[];
+  changed: string[];
+}
+
+/**
+ * Database notification, sent by the server in a notification event
+ */
+export interface DatabaseNotification {
+  channel: string;
+  /** Payload of the NOTIFY; a table name or empty refreshes that table (or any open table) */
+  payload: string;
+}
+
+/**
+ * Migration recorded by sqlx or Diesel; fields Diesel does not record are null
+ */
+export interface Migration {
+  version: string;
+  description: string | null;
+  installedOn: string | null;
+  success: boolean | null;
+  checksum: string | null;
+  executionTimeMilliseconds: number | null;
+}
+
+/**
+ * Migrations recorded in one table
+ */
+export interface MigrationHistory {
+  table: string;
+  tool: `sqlx` | `diesel`;
+  migrations: Migration[];
+}
+
+/**
+ * Response from the migrations endpoint
+ */
+export interface MigrationsResponse {
+  histories: MigrationHistory[];
+  /** Whether a migrator is configured, so pending migrations can be applied */
+  runnable: boolean;
+  /** Migrations of the configured migrator not applied yet */
+  pending: Migration[];
+}
+
+/**
+ * Background job started through the API
+ */
+export interface JobInfo {
+  id: number;
+  kind: string;
+  status: `running` | `succeeded` | `failed`;
+  startedAtMilliseconds: number;
+  finishedAtMilliseconds: number | null;
+  result: unknown;
+  error: string | null;
+}
@@ -0,0 +1,407 @@
+//! Anonymization of exported values
+//!
+//! Rules registered on the layer replace the values of matching columns in
+//! every export format, so dumps of tables holding emails or names can be
+//! shared. A rule matches columns by name (`email` or `users.email`) or by a
+//! regular expression on the column name, and replaces their values with NULL,
+//! a hash, or a made-up value of the same kind.
+//!
+//! Hashes and made-up values are derived from the original value, so equal
+//! values stay equal (joins and duplicates survive) and exports can be
+//! compared. The hash is not cryptographic: without a secret salt (see
+//! [`AnonymizationRules::with_salt`]) short values such as phone numbers can
+//! be found by trying them all. NULL stays NULL under every rule.
+
+use regex::Regex;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// Replacement for the values of anonymized columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anonymization {
+    /// Replace values with NULL
+    Null,
+
+    /// Replace values with a 16-digit hexadecimal hash of the (salted) value
+    Hash,
+
+    /// Replace values with a made-up value of the given kind
+    Fake(Fake),
+}
+
+/// Kind of made-up value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fake {
+    /// First and last name (`Maria Schmidt`)
+    Name,
+
+    /// First name (`Maria`)
+    FirstName,
+
+    /// Last name (`Schmidt`)
+    LastName,
+
+    /// Address in the reserved `example.com` domain (`maria.schmidt42@example.com`)
+    Email,
+
+    /// Phone number from the fictional 555 range (`+1-555-014-2938`)
+    Phone,
+
+    /// Placeholder words, as many as the original value has
+    Text,
+}
+
+/// Columns a rule applies to
+#[derive(Debug, Clone)]
+enum ColumnMatcher {
+    /// Column names, optionally qualified with the table (`users.email`), ignoring case
+    Names(Vec<String>),
+
+    /// Regular expression matched against the column name
+    Pattern(Regex),
+}
+
+impl ColumnMatcher {
+    fn matches(&self, table: &str, column: &str) -> bool {
+        match self {
+            ColumnMatcher::Names(names) => names.iter().any(|name| match name.rsplit_once('.') {
+                Some((name_table, name_column)) => {
+                    name_table.eq_ignore_ascii_case(table)
+                        && name_column.eq_ignore_ascii_case(column)
+                }
+                None => name.eq_ignore_ascii_case(column),
+            }),
+            ColumnMatcher::Pattern(pattern) => pattern.is_match(column),
+        }
+    }
+}
+
+/// Anonymization rules for exports, checked in the order they were added
+///
+/// Cloning `AnonymizationRules` is cheap; all clones share the same rules.
+///
+/// ```rust
+/// use axum_sql_viewer::anonymize::{Anonymization, AnonymizationRules, Fake};
+///
+/// let rules = AnonymizationRules::new()
+///     .columns(["email", "billing.contact"], Anonymization::Fake(Fake::Email))
+///     .pattern("(?i)^(first_|last_)?name$", Anonymization::Fake(Fake::Name))
+///     .unwrap()
+///     .columns(["password_hash"], Anonymization::Null)
+///     .with_salt("kept out of the repository");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AnonymizationRules {
+    rules: Arc<Vec<(ColumnMatcher, Anonymization)>>,
+    salt: Arc<str>,
+}
+
+impl AnonymizationRules {
+    /// Create an empty set of rules (nothing is anonymized)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Anonymize the columns named in `names`
+    ///
+    /// Names are compared ignoring case; `table.column` applies to one table only.
+    pub fn columns<I, S>(self, names: I, anonymization: Anonymization) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names = names.into_iter().map(Into::into).collect();
+        self.with_rule(ColumnMatcher::Names(names), anonymization)
+    }
+
+    /// Anonymize the columns whose names match the regular expression `pattern`
+    ///
+    /// The pattern may match anywhere in the name; anchor it with `^` and `$`
+    /// to match whole names.
+    pub fn pattern(
+        self,
+        pattern: &str,
+        anonymization: Anonymization,
+    ) -> Result<Self, regex::Error> {
+        let pattern = Regex::new(pattern)?;
+        Ok(self.with_rule(ColumnMatcher::Pattern(pattern), anonymization))
+    }
+
+    /// Mix `salt` into hashes and made-up values
+    ///
+    /// Keep the salt secret to prevent original values from being found by
+    /// hashing guesses, and keep it stable to get the same values in every export.
+    pub fn with_salt(mut self, salt: impl Into<String>) -> Self {
+        self.salt = Arc::from(salt.into());
+        self
+    }
+
+    /// Whether no rule is registered
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Anonymizer for the rows of `table`, applying the first matching rule to each column
+    pub fn for_table(&self, table: &str, columns: &[String]) -> TableAnonymizer {
+        let columns = columns
+            .iter()
+            .filter_map(|column| {
+                self.rules
+                    .iter()
+                    .find(|(matcher, _)| matcher.matches(table, column))
+                    .map(|(_, anonymization)| (column.clone(), *anonymization))
+            })
+            .collect();
+
+        TableAnonymizer {
+            columns,
+            salt: self.salt.clone(),
+        }
+    }
+
+    fn with_rule(mut self, matcher: ColumnMatcher, anonymization: Anonymization) -> Self {
+        Arc::make_mut(&mut self.rules).push((matcher, anonymization));
+        self
+    }
+}
+
+/// Anonymization of the columns of one table
+#[derive(Debug, Clone, Default)]
+pub struct TableAnonymizer {
+    columns: Vec<(String, Anonymization)>,
+    salt: Arc<str>,
+}
+
+impl TableAnonymizer {
+    /// Whether no column of the table is anonymized
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Names of the anonymized columns with their replacement
+    pub fn columns(&self) -> &[(String, Anonymization)] {
+        &self.columns
+    }
+
+    /// Rows with the values of anonymized columns replaced
+    ///
+    /// Borrows the rows unchanged if no column is anonymized.
+    pub fn apply<'a>(&self, rows: &'a [Value]) -> Cow<'a, [Value]> {
+        if self.is_empty() {
+            return Cow::Borrowed(rows);
+        }
+
+        let mut rows = rows.to_vec();
+        for row in rows.iter_mut().filter_map(Value::as_object_mut) {
+            for (column, anonymization) in &self.columns {
+                if let Some(value) = row.get_mut(column) {
+                    *value = self.replace(value, *anonymization);
+                }
+            }
+        }
+        Cow::Owned(rows)
+    }
+
+    /// Replacement for a single value
+    fn replace(&self, value: &Value, anonymization: Anonymization) -> Value {
+        let text = match value {
+            Value::Null => return Value::Null,
+            Value::String(text) => Cow::Borrowed(text.as_str()),
+            other => Cow::Owned(other.to_string()),
+        };
+        let hash = salted_hash(&self.salt, &text);
+
+        match anonymization {
+            Anonymization::Null => Value::Null,
+            Anonymization::Hash => Value::String(format!("{:016x}", hash)),
+            Anonymization::Fake(fake) => Value::String(fake_value(fake, hash, &text)),
+        }
+    }
+}
+
+const FIRST_NAMES: &[&str] = &[
+    "Alex", "Maria", "Jordan", "Aiko", "Samir", "Lena", "Mateo", "Priya", "Noah", "Fatima",
+    "Jonas", "Chloe", "Kwame", "Ines", "Oskar", "Mei",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Smith", "Schmidt", "Garcia", "Tanaka", "Okafor", "Rossi", "Novak", "Silva", "Kim", "Dubois",
+    "Jensen", "Haddad", "Kowalski", "Nguyen", "Murphy", "Costa",
+];
+
+const WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+    "enim",
+];
+
+/// Made-up value of kind `fake`, chosen by `hash`
+fn fake_value(fake: Fake, hash: u64, original: &str) -> String {
+    let mut numbers = Numbers(hash);
+    match fake {
+        Fake::Name => format!("{} {}", numbers.pick(FIRST_NAMES), numbers.pick(LAST_NAMES)),
+        Fake::FirstName => numbers.pick(FIRST_NAMES).to_string(),
+        Fake::LastName => numbers.pick(LAST_NAMES).to_string(),
+        Fake::Email => format!(
+            "{}.{}{}@example.com",
+            numbers.pick(FIRST_NAMES).to_lowercase(),
+            numbers.pick(LAST_NAMES).to_lowercase(),
+            numbers.below(1000)
+        ),
+        Fake::Phone => format!(
+            "+1-555-{:03}-{:04}",
+            numbers.below(1000),
+            numbers.below(10_000)
+        ),
+        Fake::Text => {
+            let count = original.split_whitespace().count().max(1);
+            (0..count)
+                .map(|_| numbers.pick(WORDS))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+}
+
+/// Pseudo-random numbers derived from a hash
+struct Numbers(u64);
+
+impl Numbers {
+    fn next(&mut self) -> u64 {
+        // 64-bit linear congruential generator (Knuth's MMIX constants)
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        self.0 >> 16
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// 64-bit FNV-1a hash of `text` with `salt` in front
+fn salted_hash(salt: &str, text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in salt.bytes().chain([0]).chain(text.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn columns(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_matching_rules() {
+        let rules = AnonymizationRules::new()
+            .columns(["users.email"], Anonymization::Fake(Fake::Email))
+            .pattern("(?i)name$", Anonymization::Fake(Fake::Name))
+            .unwrap()
+            .columns(["Email", "display_name"], Anonymization::Null);
+
+        let users = rules.for_table(
+            "users",
+            &columns(&["id", "email", "full_name", "Display_Name"]),
+        );
+        assert_eq!(
+            users.columns(),
+            &[
+                ("email".to_string(), Anonymization::Fake(Fake::Email)),
+                ("full_name".to_string(), Anonymization::Fake(Fake::Name)),
+                ("Display_Name".to_string(), Anonymization::Fake(Fake::Name)),
+            ]
+        );
+
+        let orders = rules.for_table("orders", &columns(&["id", "email"]));
+        assert_eq!(
+            orders.columns(),
+            &[("email".to_string(), Anonymization::Null)]
+        );
+
+        assert!(rules
+            .for_table("tags", &columns(&["id", "label"]))
+            .is_empty());
+        assert!(AnonymizationRules::new()
+            .pattern("(", Anonymization::Null)
+            .is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let rules = AnonymizationRules::new()
+            .columns(["email"], Anonymization::Fake(Fake::Email))
+            .columns(["phone"], Anonymization::Hash)
+            .columns(["notes"], Anonymization::Null);
+        let anonymizer = rules.for_table("users", &columns(&["id", "email", "phone", "notes"]));
+
+        let rows = vec![
+            json!({ "id": 1, "email": "alice@corp.test", "phone": "+49 170 1234567", "notes": "VIP" }),
+            json!({ "id": 2, "email": "alice@corp.test", "phone": null, "notes": "call back" }),
+        ];
+        let anonymized = anonymizer.apply(&rows);
+
+        assert_eq!(anonymized[0]["id"], 1);
+        let email = anonymized[0]["email"].as_str().unwrap();
+        assert!(email.ends_with("@example.com"));
+        assert_eq!(anonymized[1]["email"], anonymized[0]["email"]);
+        assert_eq!(anonymized[0]["phone"].as_str().unwrap().len(), 16);
+        assert_eq!(anonymized[1]["phone"], Value::Null);
+        assert_eq!(anonymized[0]["notes"], Value::Null);
+
+        let untouched = rules.for_table("tags", &columns(&["id", "label"]));
+        assert!(matches!(untouched.apply(&rows), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_salt_changes_values() {
+        let columns = columns(&["email"]);
+        let row = [json!({ "email": "alice@corp.test" })];
+        let plain = AnonymizationRules::new().columns(["email"], Anonymization::Hash);
+        let salted = plain.clone().with_salt("secret");
+
+        assert_ne!(
+            plain.for_table("users", &columns).apply(&row)[0]["email"],
+            salted.for_table("users", &columns).apply(&row)[0]["email"]
+        );
+        assert_eq!(salted_hash("", "a"), salted_hash("", "a"));
+        assert_ne!(salted_hash("a", "b"), salted_hash("ab", ""));
+    }
+
+    #[test]
+    fn test_fake_text_keeps_word_count() {
+        let text = fake_value(Fake::Text, salted_hash("", "x"), "three little words");
+        assert_eq!(text.split(' ').count(), 3);
+        assert!(fake_value(Fake::Phone, 7, "").starts_with("+1-555-"));
+    }
+}
@@ -0,0 +1,68 @@
+//! Activity monitor endpoint
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::DatabaseProvider;
+
+/// Handler for GET /api/activity
+///
+/// Lists the statements running on the database, to find out what is keeping
+/// it busy. On PostgreSQL these are the connections to the current database
+/// from `pg_stat_activity` (including idle ones and their last statement);
+/// SQLite has no view of other connections, so only queries the viewer runs
+/// with an `id` are listed. `queryId` links a statement to
+/// `POST /api/query/:id/cancel`.
+///
+/// Response:
+/// ```json
+/// {
+///   "statements": [{
+///     "processId": 4711,
+///     "queryId": "editor-lq2x7f-k3j9a1",
+///     "state": "active",
+///     "sql": "SELECT count(*) FROM events a, events b",
+///     "user": "app",
+///     "application": "",
+///     "startedAtMilliseconds": 1730000000000,
+///     "durationMilliseconds": 15320,
+///     "waitingFor": null
+///   }],
+///   "openConnections": 3,
+///   "idleConnections": 1
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+///
+/// # Returns
+///
+/// JSON response containing the running statements and the pool's usage
+pub async fn activity_handler<DB: DatabaseProvider>(State(database): State<Arc<DB>>) -> Response {
+    match database.activity().await {
+        Ok(activity) => (StatusCode::OK, Json(activity)).into_response(),
+        Err(error) => {
+            eprintln!("Failed to read database activity: {}", error);
+
+            let status = if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
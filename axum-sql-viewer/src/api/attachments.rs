@@ -0,0 +1,142 @@
+//! Endpoints for attaching further database files (SQLite only)
+//!
+//! Attached files show up in the table list as `alias.table`, next to the
+//! tables of the main database, and can be joined with them in the console.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::schema::AttachDatabaseRequest;
+use crate::schema_cache::SchemaCache;
+
+/// Handler for GET /api/attachments
+///
+/// Lists the attached database files.
+///
+/// Response:
+/// ```json
+/// [
+///   { "alias": "theirs", "path": "/home/me/Downloads/app.db" }
+/// ]
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+///
+/// # Returns
+///
+/// JSON response containing the attachments
+pub async fn list_attachments_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+) -> Response {
+    match database.list_attachments().await {
+        Ok(attachments) => (StatusCode::OK, Json(attachments)).into_response(),
+        Err(error) => {
+            eprintln!("Failed to list attachments: {}", error);
+            error_response(&error)
+        }
+    }
+}
+
+/// Handler for POST /api/attachments
+///
+/// Attaches an existing database file on the server under an alias. Its
+/// tables are listed as `alias.table` from then on.
+///
+/// Request body:
+/// ```json
+/// {
+///   "alias": "theirs",
+///   "path": "/home/me/Downloads/app.db"
+/// }
+/// ```
+///
+/// Responds with 201 Created and the attachment, with the full path of the file.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `schema_cache` - Cached table list, which no longer matches
+/// * `request` - Alias and path of the file
+///
+/// # Returns
+///
+/// JSON response containing the attachment
+pub async fn attach_database_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(schema_cache): State<SchemaCache>,
+    Json(request): Json<AttachDatabaseRequest>,
+) -> Response {
+    match database
+        .attach_database(&request.alias, &request.path)
+        .await
+    {
+        Ok(attachment) => {
+            schema_cache.clear();
+            (StatusCode::CREATED, Json(attachment)).into_response()
+        }
+        Err(error) => {
+            eprintln!("Failed to attach '{}': {}", request.path, error);
+            error_response(&error)
+        }
+    }
+}
+
+/// Handler for DELETE /api/attachments/:alias
+///
+/// Detaches a database file attached with `POST /api/attachments`. Console
+/// sessions opened while it was attached keep it until they are closed.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `schema_cache` - Cached table list, which no longer matches
+/// * `alias` - Alias the file is attached as
+///
+/// # Returns
+///
+/// Empty response with status 204 on success
+pub async fn detach_database_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(schema_cache): State<SchemaCache>,
+    Path(alias): Path<String>,
+) -> Response {
+    match database.detach_database(&alias).await {
+        Ok(()) => {
+            schema_cache.clear();
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(error) => {
+            eprintln!("Failed to detach '{}': {}", alias, error);
+            error_response(&error)
+        }
+    }
+}
+
+/// Map an attachment error to its response
+fn error_response(error: &DatabaseError) -> Response {
+    let message = error.to_string();
+    let status = if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("Invalid") {
+        StatusCode::BAD_REQUEST
+    } else if message.contains("Not supported") {
+        StatusCode::NOT_IMPLEMENTED
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "error": message
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1,116 @@
+//! Code generation endpoints
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::codegen::{json_schema, rust_struct, typescript_interface};
+use crate::database::traits::DatabaseProvider;
+use crate::schema::{CodegenLanguage, CodegenQuery};
+
+/// Handler for GET /api/tables/:name/codegen
+///
+/// Returns source code for a type matching a row of the table, ready to be
+/// pasted into the host application.
+///
+/// Query parameters:
+/// - lang: Target language, "rust" (a `sqlx::FromRow` struct) or "typescript"
+///   (an interface matching the rows returned by the API)
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table to generate code for
+/// * `query` - Code generation options
+///
+/// # Returns
+///
+/// Plain text response containing the generated code
+pub async fn codegen_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+    Query(query): Query<CodegenQuery>,
+) -> Response {
+    match database.get_table_schema(&table_name).await {
+        Ok(schema) => {
+            let code = match query.lang {
+                CodegenLanguage::Rust => rust_struct(&schema, database.dialect()),
+                CodegenLanguage::Typescript => typescript_interface(&schema, database.dialect()),
+            };
+
+            (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                code,
+            )
+                .into_response()
+        }
+        Err(error) => {
+            eprintln!(
+                "Failed to generate code for table '{}': {}",
+                table_name, error
+            );
+
+            // Return appropriate status code based on error type
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/tables/:name/json-schema
+///
+/// Returns a draft-07 JSON Schema document describing a row of the table,
+/// derived from the column types and nullability.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table to describe
+///
+/// # Returns
+///
+/// JSON response containing the JSON Schema document
+pub async fn json_schema_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+) -> Response {
+    match database.get_table_schema(&table_name).await {
+        Ok(schema) => (StatusCode::OK, Json(json_schema(&schema))).into_response(),
+        Err(error) => {
+            eprintln!(
+                "Failed to generate JSON schema for table '{}': {}",
+                table_name, error
+            );
+
+            // Return appropriate status code based on error type
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
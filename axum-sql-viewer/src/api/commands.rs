@@ -0,0 +1,1377 @@
+//! Command metadata endpoint
+//!
+//! Lists the server-side actions of the API in a uniform shape, so a command
+//! palette or a script can discover them without knowing every endpoint.
+//! New endpoints should be added to [`COMMANDS`].
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::DatabaseProvider;
+use crate::row_key::encode_path_segment;
+use crate::schema::{Command, CommandParameter, CommandsResponse, ParameterLocation};
+
+/// Static description of a command
+struct CommandTemplate {
+    id: &'static str,
+    title: &'static str,
+    category: &'static str,
+    method: &'static str,
+    path: &'static str,
+    parameters: &'static [ParameterTemplate],
+}
+
+/// Static description of a command parameter
+struct ParameterTemplate {
+    name: &'static str,
+    location: ParameterLocation,
+    required: bool,
+    description: &'static str,
+}
+
+const TABLE_NAME: ParameterTemplate = ParameterTemplate {
+    name: "name",
+    location: ParameterLocation::Path,
+    required: true,
+    description: "Table name",
+};
+
+const ROW_KEY: ParameterTemplate = ParameterTemplate {
+    name: "key",
+    location: ParameterLocation::Path,
+    required: true,
+    description: "Primary key values, separated by commas",
+};
+
+const PRIMARY_KEY: ParameterTemplate = ParameterTemplate {
+    name: "pk[column]",
+    location: ParameterLocation::Query,
+    required: true,
+    description: "Value of each primary key column, e.g. pk[user_id]=7&pk[group_id]=3",
+};
+
+const SQL: ParameterTemplate = ParameterTemplate {
+    name: "sql",
+    location: ParameterLocation::Body,
+    required: true,
+    description: "SQL text",
+};
+
+const PARAMS: ParameterTemplate = ParameterTemplate {
+    name: "params",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Values for the placeholders: array in order (`?`, `$1`) or object (`:name`)",
+};
+
+const ANALYZE: ParameterTemplate = ParameterTemplate {
+    name: "analyze",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Run the (read-only) statement to report actual rows and timings",
+};
+
+const SNAPSHOT: ParameterTemplate = ParameterTemplate {
+    name: "snapshot",
+    location: ParameterLocation::Query,
+    required: false,
+    description: "Snapshot to read from",
+};
+
+const SNAPSHOT_NAME: ParameterTemplate = ParameterTemplate {
+    name: "name",
+    location: ParameterLocation::Path,
+    required: true,
+    description: "Name of the named snapshot",
+};
+
+const CONSOLE_SESSION: ParameterTemplate = ParameterTemplate {
+    name: "session",
+    location: ParameterLocation::Path,
+    required: true,
+    description: "Console session identifier",
+};
+
+const QUERY_ID: ParameterTemplate = ParameterTemplate {
+    name: "id",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Identifier to cancel the query by while it runs",
+};
+
+const CONFIRMATION: ParameterTemplate = ParameterTemplate {
+    name: "confirmation",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Token from a 428 response that confirms a destructive statement",
+};
+
+const STREAM: ParameterTemplate = ParameterTemplate {
+    name: "stream",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Send the rows as NDJSON while they are read, without the row limit",
+};
+
+const PAGE_SIZE: ParameterTemplate = ParameterTemplate {
+    name: "pageSize",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Return only this many rows and a handle to fetch the rest from",
+};
+
+const VALIDATE_ONLY: ParameterTemplate = ParameterTemplate {
+    name: "validateOnly",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Only check the SQL (parse, and EXPLAIN queries) without running it",
+};
+
+const RESULT_FORMAT: ParameterTemplate = ParameterTemplate {
+    name: "format",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Download the rows as a file (csv, xlsx, ndjson or json)",
+};
+
+const RESULT_ID: ParameterTemplate = ParameterTemplate {
+    name: "id",
+    location: ParameterLocation::Path,
+    required: true,
+    description: "Result handle from a query sent with pageSize",
+};
+
+const RUNNING_QUERY: ParameterTemplate = ParameterTemplate {
+    name: "id",
+    location: ParameterLocation::Path,
+    required: true,
+    description: "Identifier the running query was sent with",
+};
+
+const SAVED_QUERY: ParameterTemplate = ParameterTemplate {
+    name: "id",
+    location: ParameterLocation::Path,
+    required: true,
+    description: "Saved query identifier",
+};
+
+const SAVED_QUERY_NAME: ParameterTemplate = ParameterTemplate {
+    name: "name",
+    location: ParameterLocation::Body,
+    required: true,
+    description: "Name shown in the list of saved queries",
+};
+
+const SAVED_QUERY_DESCRIPTION: ParameterTemplate = ParameterTemplate {
+    name: "description",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "What the query is for",
+};
+
+const SAVED_QUERY_PARAMETERS: ParameterTemplate = ParameterTemplate {
+    name: "parameters",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Values to run the query with, as an array or an object of named values",
+};
+
+const SAVED_QUERY_TAGS: ParameterTemplate = ParameterTemplate {
+    name: "tags",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Tags to group the query by",
+};
+
+const SAVED_QUERY_FAVORITE: ParameterTemplate = ParameterTemplate {
+    name: "favorite",
+    location: ParameterLocation::Body,
+    required: false,
+    description: "Whether the query is listed among the favorites",
+};
+
+const SHARED_VIEW: ParameterTemplate = ParameterTemplate {
+    name: "type",
+    location: ParameterLocation::Body,
+    required: true,
+    description:
+        "table (with table, filters, sortBy, sortOrder, search) or query (sql, parameters)",
+};
+
+const PERMALINK: ParameterTemplate = ParameterTemplate {
+    name: "token",
+    location: ParameterLocation::Path,
+    required: true,
+    description: "Token from POST /api/permalinks",
+};
+
+const TRANSACTION: ParameterTemplate = ParameterTemplate {
+    name: "tx",
+    location: ParameterLocation::Path,
+    required: true,
+    description: "Transaction identifier from POST /api/tx/begin",
+};
+
+/// Every action offered by the API
+const COMMANDS: &[CommandTemplate] = &[
+    CommandTemplate {
+        id: "tables.list",
+        title: "List tables",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables",
+        parameters: &[ParameterTemplate {
+            name: "exact",
+            location: ParameterLocation::Query,
+            required: false,
+            description: "true to count the rows of every table instead of estimating",
+        }],
+    },
+    CommandTemplate {
+        id: "table.schema",
+        title: "Show table schema",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}",
+        parameters: &[TABLE_NAME],
+    },
+    CommandTemplate {
+        id: "cache.invalidate",
+        title: "Invalidate schema cache",
+        category: "tables",
+        method: "POST",
+        path: "/api/cache/invalidate",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "table.rows",
+        title: "Browse table rows",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/rows",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "offset",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Number of rows to skip",
+            },
+            ParameterTemplate {
+                name: "limit",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Maximum number of rows to return",
+            },
+            ParameterTemplate {
+                name: "sortBy",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Column to sort by",
+            },
+            ParameterTemplate {
+                name: "sortOrder",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "ascending or descending",
+            },
+            ParameterTemplate {
+                name: "nulls",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "first or last: where rows with NULL in the sort column go",
+            },
+            ParameterTemplate {
+                name: "columns",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Columns to fetch, separated by commas (default: all)",
+            },
+            ParameterTemplate {
+                name: "caseInsensitive",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "true to match filters ignoring case",
+            },
+            ParameterTemplate {
+                name: "filter[column]",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Filter value for a column (% as wildcard)",
+            },
+            ParameterTemplate {
+                name: "filter[column][gte]",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "ISO 8601 date or timestamp range (gt, gte, lt or lte)",
+            },
+            ParameterTemplate {
+                name: "q",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Text to search for in all textual columns",
+            },
+            SNAPSHOT,
+            ParameterTemplate {
+                name: "labels",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "true to add labels of rows referenced by foreign keys",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "table.queryRows",
+        title: "Query rows with filter groups",
+        category: "tables",
+        method: "POST",
+        path: "/api/tables/{name}/rows",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "filters",
+                location: ParameterLocation::Body,
+                required: false,
+                description: "Filter values by column, all of which must match",
+            },
+            ParameterTemplate {
+                name: "filterGroups",
+                location: ParameterLocation::Body,
+                required: false,
+                description: "Groups of column filters, at least one of which must match",
+            },
+            ParameterTemplate {
+                name: "limit",
+                location: ParameterLocation::Body,
+                required: false,
+                description: "Maximum number of rows to return",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "table.count",
+        title: "Count table rows",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/count",
+        parameters: &[TABLE_NAME, SNAPSHOT],
+    },
+    CommandTemplate {
+        id: "table.checksum",
+        title: "Check table for changes",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/checksum",
+        parameters: &[TABLE_NAME],
+    },
+    CommandTemplate {
+        id: "table.sample",
+        title: "Sample random rows",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/sample",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "n",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Number of rows to sample (default: 100)",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "table.duplicates",
+        title: "Find duplicate rows",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/duplicates",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "columns",
+                location: ParameterLocation::Query,
+                required: true,
+                description: "Columns to compare, separated by commas",
+            },
+            ParameterTemplate {
+                name: "limit",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Number of groups (default: 100)",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "table.profile",
+        title: "Profile table",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/profile",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "refresh",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Compute a new profile instead of the cached one",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "column.stats",
+        title: "Show column statistics",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/columns/{column}/stats",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "column",
+                location: ParameterLocation::Path,
+                required: true,
+                description: "Column name",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "column.histogram",
+        title: "Show column histogram",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/columns/{column}/histogram",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "column",
+                location: ParameterLocation::Path,
+                required: true,
+                description: "Numeric or date column",
+            },
+            ParameterTemplate {
+                name: "buckets",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Number of buckets (default: 20, at most 100)",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "row.get",
+        title: "Show row",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/rows/{key}",
+        parameters: &[TABLE_NAME, ROW_KEY],
+    },
+    CommandTemplate {
+        id: "row.related",
+        title: "Show rows referenced by row",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/rows/{key}/related",
+        parameters: &[TABLE_NAME, ROW_KEY],
+    },
+    CommandTemplate {
+        id: "row.referencedBy",
+        title: "Show rows referencing row",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/rows/{key}/referenced-by",
+        parameters: &[
+            TABLE_NAME,
+            ROW_KEY,
+            ParameterTemplate {
+                name: "limit",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Maximum rows per foreign key",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "row.update",
+        title: "Update row",
+        category: "tables",
+        method: "PATCH",
+        path: "/api/tables/{name}/rows/{key}",
+        parameters: &[TABLE_NAME, ROW_KEY],
+    },
+    CommandTemplate {
+        id: "rows.insert",
+        title: "Insert rows",
+        category: "tables",
+        method: "POST",
+        path: "/api/tables/{name}/rows:bulk",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "rows",
+                location: ParameterLocation::Body,
+                required: true,
+                description: "Array of rows, each an object of values by column name",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "rows.delete",
+        title: "Delete rows",
+        category: "tables",
+        method: "POST",
+        path: "/api/tables/{name}/delete",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "keys",
+                location: ParameterLocation::Body,
+                required: true,
+                description: "Key of each row: values separated by commas, or an object by column",
+            },
+            ParameterTemplate {
+                name: "onReferenced",
+                location: ParameterLocation::Body,
+                required: false,
+                description: "abort, skip or cascade",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "row.delete",
+        title: "Delete row",
+        category: "tables",
+        method: "DELETE",
+        path: "/api/tables/{name}/row",
+        parameters: &[
+            TABLE_NAME,
+            PRIMARY_KEY,
+            ParameterTemplate {
+                name: "onReferenced",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "abort, skip or cascade",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "row.resolve",
+        title: "Resolve row reference",
+        category: "tables",
+        method: "GET",
+        path: "/api/resolve",
+        parameters: &[
+            ParameterTemplate {
+                name: "table",
+                location: ParameterLocation::Query,
+                required: true,
+                description: "Table name",
+            },
+            ParameterTemplate {
+                name: "key",
+                location: ParameterLocation::Query,
+                required: true,
+                description: "Primary key values, separated by commas",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "table.jsonSchema",
+        title: "Export table JSON Schema",
+        category: "export",
+        method: "GET",
+        path: "/api/tables/{name}/json-schema",
+        parameters: &[TABLE_NAME],
+    },
+    CommandTemplate {
+        id: "table.codegen",
+        title: "Generate code for table",
+        category: "export",
+        method: "GET",
+        path: "/api/tables/{name}/codegen",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "lang",
+                location: ParameterLocation::Query,
+                required: true,
+                description: "rust or typescript",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "table.download",
+        title: "Download table",
+        category: "export",
+        method: "GET",
+        path: "/api/tables/{name}/export",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "format",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "File format (csv, xlsx, ndjson, json or sql)",
+            },
+            ParameterTemplate {
+                name: "filter[column]",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Filter value for a column",
+            },
+            ParameterTemplate {
+                name: "onLimit",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "truncate, sample or fail when the table is over its export limits",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "table.export",
+        title: "Export table as file",
+        category: "export",
+        method: "POST",
+        path: "/api/tables/{name}/exports",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "format",
+                location: ParameterLocation::Body,
+                required: false,
+                description: "File format (csv, xlsx, ndjson, json or sql)",
+            },
+            ParameterTemplate {
+                name: "onLimit",
+                location: ParameterLocation::Body,
+                required: false,
+                description: "truncate, sample or fail when the table is over its export limits",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "export.download",
+        title: "Download export",
+        category: "export",
+        method: "GET",
+        path: "/api/exports/{id}",
+        parameters: &[ParameterTemplate {
+            name: "id",
+            location: ParameterLocation::Path,
+            required: true,
+            description: "Export identifier from the export job result",
+        }],
+    },
+    CommandTemplate {
+        id: "diagram.erd",
+        title: "Export ER diagram (Mermaid)",
+        category: "export",
+        method: "GET",
+        path: "/api/erd.mmd",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "diagram.graph",
+        title: "Show relationship graph",
+        category: "export",
+        method: "GET",
+        path: "/api/graph",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "schema.search",
+        title: "Search tables and columns",
+        category: "tables",
+        method: "GET",
+        path: "/api/search",
+        parameters: &[ParameterTemplate {
+            name: "q",
+            location: ParameterLocation::Query,
+            required: true,
+            description: "Text to search for in table and column names",
+        }],
+    },
+    CommandTemplate {
+        id: "column.lineage",
+        title: "Show column lineage",
+        category: "tables",
+        method: "GET",
+        path: "/api/tables/{name}/columns/{column}/lineage",
+        parameters: &[
+            TABLE_NAME,
+            ParameterTemplate {
+                name: "column",
+                location: ParameterLocation::Path,
+                required: true,
+                description: "Column name",
+            },
+            ParameterTemplate {
+                name: "depth",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Number of foreign keys to follow in each direction (default 3)",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "query.execute",
+        title: "Execute SQL",
+        category: "query",
+        method: "POST",
+        path: "/api/query",
+        parameters: &[
+            SQL,
+            PARAMS,
+            QUERY_ID,
+            CONFIRMATION,
+            STREAM,
+            PAGE_SIZE,
+            VALIDATE_ONLY,
+            RESULT_FORMAT,
+        ],
+    },
+    CommandTemplate {
+        id: "query.inspect",
+        title: "Inspect application query",
+        category: "query",
+        method: "POST",
+        path: "/api/query/inspect",
+        parameters: &[SQL],
+    },
+    CommandTemplate {
+        id: "query.explain",
+        title: "Show query plan",
+        category: "query",
+        method: "POST",
+        path: "/api/explain",
+        parameters: &[SQL, ANALYZE],
+    },
+    CommandTemplate {
+        id: "query.format",
+        title: "Format SQL",
+        category: "query",
+        method: "POST",
+        path: "/api/query/format",
+        parameters: &[SQL],
+    },
+    CommandTemplate {
+        id: "query.cancel",
+        title: "Cancel running query",
+        category: "query",
+        method: "POST",
+        path: "/api/query/{id}/cancel",
+        parameters: &[RUNNING_QUERY],
+    },
+    CommandTemplate {
+        id: "query.rows",
+        title: "Fetch more query result rows",
+        category: "query",
+        method: "GET",
+        path: "/api/query/{id}/rows",
+        parameters: &[
+            RESULT_ID,
+            ParameterTemplate {
+                name: "offset",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Position of the first row to return",
+            },
+            ParameterTemplate {
+                name: "limit",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Number of rows to return (at most 1000)",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "query.release",
+        title: "Release query result",
+        category: "query",
+        method: "DELETE",
+        path: "/api/query/{id}/rows",
+        parameters: &[RESULT_ID],
+    },
+    CommandTemplate {
+        id: "query.script",
+        title: "Run SQL script in a transaction",
+        category: "query",
+        method: "POST",
+        path: "/api/query/script",
+        parameters: &[SQL, CONFIRMATION],
+    },
+    CommandTemplate {
+        id: "savedQueries.list",
+        title: "List saved queries",
+        category: "query",
+        method: "GET",
+        path: "/api/saved-queries",
+        parameters: &[
+            ParameterTemplate {
+                name: "tag",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Only list queries with this tag",
+            },
+            ParameterTemplate {
+                name: "favorite",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Only list favorites (true) or the other queries (false)",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "savedQuery.create",
+        title: "Save query",
+        category: "query",
+        method: "POST",
+        path: "/api/saved-queries",
+        parameters: &[
+            SAVED_QUERY_NAME,
+            SQL,
+            SAVED_QUERY_DESCRIPTION,
+            SAVED_QUERY_PARAMETERS,
+            SAVED_QUERY_TAGS,
+            SAVED_QUERY_FAVORITE,
+        ],
+    },
+    CommandTemplate {
+        id: "savedQuery.update",
+        title: "Update saved query",
+        category: "query",
+        method: "PUT",
+        path: "/api/saved-queries/{id}",
+        parameters: &[
+            SAVED_QUERY,
+            SAVED_QUERY_NAME,
+            SQL,
+            SAVED_QUERY_DESCRIPTION,
+            SAVED_QUERY_PARAMETERS,
+            SAVED_QUERY_TAGS,
+            SAVED_QUERY_FAVORITE,
+        ],
+    },
+    CommandTemplate {
+        id: "savedQuery.delete",
+        title: "Delete saved query",
+        category: "query",
+        method: "DELETE",
+        path: "/api/saved-queries/{id}",
+        parameters: &[SAVED_QUERY],
+    },
+    CommandTemplate {
+        id: "permalink.create",
+        title: "Share view as permalink",
+        category: "query",
+        method: "POST",
+        path: "/api/permalinks",
+        parameters: &[SHARED_VIEW],
+    },
+    CommandTemplate {
+        id: "permalink.get",
+        title: "Open permalink",
+        category: "query",
+        method: "GET",
+        path: "/api/permalinks/{token}",
+        parameters: &[PERMALINK],
+    },
+    CommandTemplate {
+        id: "console.open",
+        title: "Open console session",
+        category: "query",
+        method: "POST",
+        path: "/api/console/sessions",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "console.query",
+        title: "Execute SQL in console session",
+        category: "query",
+        method: "POST",
+        path: "/api/console/sessions/{session}/query",
+        parameters: &[CONSOLE_SESSION, SQL, PARAMS, CONFIRMATION],
+    },
+    CommandTemplate {
+        id: "console.history",
+        title: "Show console session history",
+        category: "query",
+        method: "GET",
+        path: "/api/console/sessions/{session}/history",
+        parameters: &[CONSOLE_SESSION],
+    },
+    CommandTemplate {
+        id: "console.close",
+        title: "Close console session",
+        category: "query",
+        method: "DELETE",
+        path: "/api/console/sessions/{session}",
+        parameters: &[CONSOLE_SESSION],
+    },
+    CommandTemplate {
+        id: "tx.begin",
+        title: "Begin transaction",
+        category: "query",
+        method: "POST",
+        path: "/api/tx/begin",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "tx.query",
+        title: "Execute SQL in transaction",
+        category: "query",
+        method: "POST",
+        path: "/api/tx/{tx}/query",
+        parameters: &[TRANSACTION, SQL, PARAMS, CONFIRMATION],
+    },
+    CommandTemplate {
+        id: "tx.commit",
+        title: "Commit transaction",
+        category: "query",
+        method: "POST",
+        path: "/api/tx/{tx}/commit",
+        parameters: &[TRANSACTION],
+    },
+    CommandTemplate {
+        id: "tx.rollback",
+        title: "Roll back transaction",
+        category: "query",
+        method: "POST",
+        path: "/api/tx/{tx}/rollback",
+        parameters: &[TRANSACTION],
+    },
+    CommandTemplate {
+        id: "config.show",
+        title: "Show database capabilities",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/config",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "seed.status",
+        title: "Show seed status",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/seed",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "seed.create",
+        title: "Save database as seed",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/seed",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "seed.reset",
+        title: "Reset database to seed",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/seed/reset",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "maintenance.journal",
+        title: "Show journal mode and WAL size",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/maintenance/journal",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "maintenance.vacuum",
+        title: "Vacuum database",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/maintenance/vacuum",
+        parameters: &[ParameterTemplate {
+            name: "tables",
+            location: ParameterLocation::Query,
+            required: false,
+            description:
+                "Comma-separated table names (PostgreSQL only; the whole database by default)",
+        }],
+    },
+    CommandTemplate {
+        id: "maintenance.analyze",
+        title: "Analyze tables",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/maintenance/analyze",
+        parameters: &[ParameterTemplate {
+            name: "tables",
+            location: ParameterLocation::Query,
+            required: false,
+            description: "Comma-separated table names (the whole database by default)",
+        }],
+    },
+    CommandTemplate {
+        id: "maintenance.reindex",
+        title: "Rebuild indexes",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/maintenance/reindex",
+        parameters: &[ParameterTemplate {
+            name: "tables",
+            location: ParameterLocation::Query,
+            required: false,
+            description: "Comma-separated table names (the whole database by default)",
+        }],
+    },
+    CommandTemplate {
+        id: "maintenance.checkpoint",
+        title: "Checkpoint write-ahead log",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/maintenance/checkpoint",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "migrations.list",
+        title: "Show applied migrations",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/migrations",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "migrations.run",
+        title: "Apply pending migrations",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/migrations/run",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "fixture.download",
+        title: "Download fixture",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/fixtures",
+        parameters: &[
+            ParameterTemplate {
+                name: "tables",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Comma-separated table names (all tables by default)",
+            },
+            ParameterTemplate {
+                name: "format",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "json (default) or yaml",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "fixture.load",
+        title: "Load fixture",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/fixtures",
+        parameters: &[
+            ParameterTemplate {
+                name: "<table>",
+                location: ParameterLocation::Body,
+                required: true,
+                description: "New rows of each table, as an array of objects by column name",
+            },
+            ParameterTemplate {
+                name: "format",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "yaml to read a YAML document (also chosen by a YAML Content-Type)",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "database.dump",
+        title: "Dump tables",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/dump",
+        parameters: &[
+            ParameterTemplate {
+                name: "tables",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Comma-separated table names (all tables by default)",
+            },
+            ParameterTemplate {
+                name: "schemaOnly",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "true to leave out the rows",
+            },
+            ParameterTemplate {
+                name: "dataOnly",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "true to leave out the statements creating the tables",
+            },
+            ParameterTemplate {
+                name: "inserts",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "true to write INSERT statements instead of COPY blocks",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "attachments.list",
+        title: "List attached databases",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/attachments",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "attachment.create",
+        title: "Attach database file",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/attachments",
+        parameters: &[
+            ParameterTemplate {
+                name: "alias",
+                location: ParameterLocation::Body,
+                required: true,
+                description: "Name the file's tables are listed under, as alias.table",
+            },
+            ParameterTemplate {
+                name: "path",
+                location: ParameterLocation::Body,
+                required: true,
+                description: "Path of the database file on the server",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "attachment.delete",
+        title: "Detach database file",
+        category: "maintenance",
+        method: "DELETE",
+        path: "/api/attachments/{alias}",
+        parameters: &[ParameterTemplate {
+            name: "alias",
+            location: ParameterLocation::Path,
+            required: true,
+            description: "Alias the file is attached as",
+        }],
+    },
+    CommandTemplate {
+        id: "activity.show",
+        title: "Show running statements",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/activity",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "jobs.list",
+        title: "List jobs",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/jobs",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "job.get",
+        title: "Show job",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/jobs/{id}",
+        parameters: &[ParameterTemplate {
+            name: "id",
+            location: ParameterLocation::Path,
+            required: true,
+            description: "Job identifier",
+        }],
+    },
+    CommandTemplate {
+        id: "snapshot.create",
+        title: "Open snapshot",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/snapshots",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "snapshot.release",
+        title: "Release snapshot",
+        category: "maintenance",
+        method: "DELETE",
+        path: "/api/snapshots/{snapshot}",
+        parameters: &[ParameterTemplate {
+            name: "snapshot",
+            location: ParameterLocation::Path,
+            required: true,
+            description: "Snapshot identifier",
+        }],
+    },
+    CommandTemplate {
+        id: "snapshot.list",
+        title: "List named snapshots",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/snapshots/named",
+        parameters: &[],
+    },
+    CommandTemplate {
+        id: "snapshot.save",
+        title: "Save named snapshot",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/snapshots/named",
+        parameters: &[
+            ParameterTemplate {
+                name: "name",
+                location: ParameterLocation::Body,
+                required: true,
+                description: "Snapshot name (letters, digits, underscores and dashes)",
+            },
+            ParameterTemplate {
+                name: "table",
+                location: ParameterLocation::Body,
+                required: false,
+                description: "Table to copy; all tables if omitted",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "snapshot.restore",
+        title: "Restore named snapshot",
+        category: "maintenance",
+        method: "POST",
+        path: "/api/snapshots/named/{name}/restore",
+        parameters: &[SNAPSHOT_NAME],
+    },
+    CommandTemplate {
+        id: "snapshot.diff",
+        title: "Compare with named snapshot",
+        category: "maintenance",
+        method: "GET",
+        path: "/api/snapshots/named/{name}/diff",
+        parameters: &[
+            SNAPSHOT_NAME,
+            ParameterTemplate {
+                name: "table",
+                location: ParameterLocation::Query,
+                required: false,
+                description: "Table to compare; optional for snapshots of one table",
+            },
+        ],
+    },
+    CommandTemplate {
+        id: "snapshot.delete",
+        title: "Delete named snapshot",
+        category: "maintenance",
+        method: "DELETE",
+        path: "/api/snapshots/named/{name}",
+        parameters: &[SNAPSHOT_NAME],
+    },
+];
+
+impl CommandTemplate {
+    fn to_command(&self) -> Command {
+        Command {
+            id: self.id.to_string(),
+            title: self.title.to_string(),
+            category: self.category.to_string(),
+            method: self.method.to_string(),
+            path: self.path.to_string(),
+            parameters: self
+                .parameters
+                .iter()
+                .map(|parameter| CommandParameter {
+                    name: parameter.name.to_string(),
+                    location: parameter.location,
+                    required: parameter.required,
+                    description: parameter.description.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Command opening the rows of a single table (path already filled in)
+fn open_table_command(table_name: &str) -> Command {
+    Command {
+        id: format!("table.open:{}", table_name),
+        title: format!("Open table {}", table_name),
+        category: "tables".to_string(),
+        method: "GET".to_string(),
+        path: format!("/api/tables/{}/rows", encode_path_segment(table_name)),
+        parameters: Vec::new(),
+    }
+}
+
+/// Handler for GET /api/commands
+///
+/// Enumerates the available server-side actions with their identifiers,
+/// HTTP method, path and parameters, followed by an "open table" command for
+/// every table in the database.
+///
+/// Response:
+/// ```json
+/// {
+///   "commands": [
+///     {
+///       "id": "table.count",
+///       "title": "Count table rows",
+///       "category": "tables",
+///       "method": "GET",
+///       "path": "/api/tables/{name}/count",
+///       "parameters": [
+///         { "name": "name", "location": "path", "required": true, "description": "Table name" },
+///         { "name": "snapshot", "location": "query", "required": false, "description": "Snapshot to read from" }
+///       ]
+///     },
+///     {
+///       "id": "table.open:users",
+///       "title": "Open table users",
+///       "category": "tables",
+///       "method": "GET",
+///       "path": "/api/tables/users/rows",
+///       "parameters": []
+///     }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+///
+/// # Returns
+///
+/// JSON response containing the list of commands
+pub async fn list_commands_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+) -> Response {
+    let tables = match database.list_tables().await {
+        Ok(tables) => tables,
+        Err(error) => {
+            eprintln!("Failed to list tables for commands: {}", error);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let commands = COMMANDS
+        .iter()
+        .map(CommandTemplate::to_command)
+        .chain(tables.iter().map(|table| open_table_command(&table.name)))
+        .collect();
+
+    (StatusCode::OK, Json(CommandsResponse { commands })).into_response()
+}
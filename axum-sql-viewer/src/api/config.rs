@@ -0,0 +1,68 @@
+//! Viewer configuration endpoint
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::api::version::API_VERSION;
+use crate::artifacts::ArtifactStore;
+use crate::database::traits::DatabaseProvider;
+use crate::profile::Profiler;
+use crate::schema::ConfigResponse;
+
+/// Handler for GET /api/config
+///
+/// Describes the database and the limits of the API, so the frontend can hide
+/// features the database does not support instead of running into errors.
+///
+/// Response:
+/// ```json
+/// {
+///   "apiVersion": 1,
+///   "dialect": "sqlite",
+///   "capabilities": {
+///     "supportsLiveUpdates": false,
+///     "supportsExplainAnalyze": false,
+///     "supportsSchemas": false,
+///     "supportsReturning": true,
+///     "supportsSnapshots": false,
+///     "supportsSessions": true,
+///     "supportsCancellation": true,
+///     "maxPlaceholderCount": 32766
+///   },
+///   "maxPageSize": 500,
+///   "maxResultRows": 10000,
+///   "exportRetentionSeconds": 3600,
+///   "profiling": false
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `artifacts` - Artifact store from state
+/// * `profiler` - Profiler from state
+///
+/// # Returns
+///
+/// JSON response containing the configuration
+pub async fn config_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(artifacts): State<ArtifactStore>,
+    State(profiler): State<Profiler>,
+) -> Response {
+    let config = ConfigResponse {
+        api_version: API_VERSION,
+        dialect: database.dialect(),
+        capabilities: database.capabilities(),
+        max_page_size: database.result_limits().max_page_size,
+        max_result_rows: database.result_limits().max_result_rows,
+        export_retention_seconds: artifacts.retention().as_secs(),
+        profiling: profiler.is_enabled(),
+    };
+
+    (StatusCode::OK, Json(config)).into_response()
+}
@@ -0,0 +1,395 @@
+//! Console session endpoints
+//!
+//! Each console tab opens its own session, so transactions and other
+//! connection state started in one tab are not visible in another.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::api::query::{check_confirmation, cost_exceeded_response};
+use crate::confirmations::Confirmations;
+use crate::console::{ConsoleHistory, HistoryEntry, SessionHistoryResponse};
+use crate::cost_limits::{check_cost, CostLimits};
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::schema::{QueryRequest, QueryResult};
+
+/// Handler for POST /api/console/sessions
+///
+/// Opens a console session on a dedicated connection. Statements run through
+/// the session (`POST /api/console/sessions/:session/query`) share open
+/// transactions, settings and temporary tables with each other only. Sessions
+/// are closed after a period of inactivity or when deleted; closing a session
+/// rolls back a transaction left open.
+///
+/// Response:
+/// ```json
+/// {
+///   "session": "192f1c2a3b4-1",
+///   "idleTimeoutSeconds": 600
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `history` - Console history from state
+///
+/// # Returns
+///
+/// JSON response containing the session identifier
+pub async fn open_session_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(history): State<ConsoleHistory>,
+) -> Response {
+    if !database.capabilities().supports_sessions {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            Json(serde_json::json!({
+                "error": "Not supported: Console sessions are not supported by this database"
+            })),
+        )
+            .into_response();
+    }
+
+    match database.open_session().await {
+        Ok(response) => {
+            history.open(
+                &response.session,
+                Duration::from_secs(response.idle_timeout_seconds),
+            );
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(error) => {
+            eprintln!("Failed to open console session: {}", error);
+
+            let status = if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for POST /api/console/sessions/:session/query
+///
+/// Executes a raw SQL statement in a console session and adds it to the
+/// session's history. Request and response are the same as for `/api/query`,
+/// including the cost limits and the confirmation of destructive statements
+/// (whose affected rows are not estimated, as a dry run could not see the
+/// session's open transaction). `format`, `stream`, `pageSize` and
+/// `validateOnly` are not supported in sessions and are rejected with status
+/// 400. Also serves POST /api/tx/:tx/query, as transactions are sessions.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `history` - Console history from state
+/// * `cost_limits` - Limits on the estimated cost of statements, from state
+/// * `confirmations` - Destructive statements waiting for confirmation, from state
+/// * `session` - Identifier of the session
+/// * `request` - JSON request containing the SQL to execute
+///
+/// # Returns
+///
+/// JSON response containing query results or error information
+pub async fn session_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(history): State<ConsoleHistory>,
+    State(cost_limits): State<CostLimits>,
+    State(confirmations): State<Confirmations>,
+    Path(session): Path<String>,
+    Json(request): Json<QueryRequest>,
+) -> Response {
+    eprintln!(
+        "Executing SQL query in session {}: {}",
+        session, request.sql
+    );
+
+    let unsupported = [
+        ("format", request.format.is_some()),
+        ("stream", request.stream),
+        ("pageSize", request.page_size.is_some()),
+        ("validateOnly", request.validate_only),
+    ]
+    .into_iter()
+    .find_map(|(field, set)| set.then_some(field));
+    if let Some(field) = unsupported {
+        let message = format!("Not supported: `{}` in console sessions", field);
+        eprintln!("Rejected query in session {}: {}", session, message);
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(QueryResult {
+                columns: vec![],
+                rows: vec![],
+                affected_rows: 0,
+                execution_time_milliseconds: 0,
+                error: Some(message),
+                timeout: None,
+            }),
+        )
+            .into_response();
+    }
+
+    let (sql, params) = match request.params.bind(&request.sql, database.dialect()) {
+        Ok(bound) => bound,
+        Err(message) => {
+            eprintln!(
+                "Failed to bind query parameters in session {}: {}",
+                session, message
+            );
+            history.record(
+                &session,
+                HistoryEntry::new(&request.sql, Err(message.clone())),
+            );
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(QueryResult {
+                    columns: vec![],
+                    rows: vec![],
+                    affected_rows: 0,
+                    execution_time_milliseconds: 0,
+                    error: Some(message),
+                    timeout: None,
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    match check_cost(database.as_ref(), &cost_limits, &sql).await {
+        Ok(None) => {}
+        Ok(Some(message)) => {
+            history.record(
+                &session,
+                HistoryEntry::new(&request.sql, Err(message.clone())),
+            );
+            return cost_exceeded_response(message);
+        }
+        // Leave errors to running the statement
+        Err(error) => eprintln!("Failed to estimate query cost: {}", error),
+    }
+
+    if let Some(response) = check_confirmation(
+        database.as_ref(),
+        &confirmations,
+        &sql,
+        &params,
+        request.confirmation.as_deref(),
+        false,
+    )
+    .await
+    {
+        return response;
+    }
+
+    match database.execute_in_session(&session, &sql, &params).await {
+        Ok(result) => {
+            history.record(&session, HistoryEntry::new(&request.sql, Ok(&result)));
+
+            if result.error.is_some() {
+                (StatusCode::BAD_REQUEST, Json(result)).into_response()
+            } else {
+                (StatusCode::OK, Json(result)).into_response()
+            }
+        }
+        Err(error) => {
+            eprintln!("Failed to execute query in session {}: {}", session, error);
+
+            let status = match &error {
+                DatabaseError::SessionNotFound(_) => {
+                    history.close(&session);
+                    StatusCode::NOT_FOUND
+                }
+                DatabaseError::Timeout(_) => StatusCode::REQUEST_TIMEOUT,
+                DatabaseError::TooManyRows(_) => StatusCode::PAYLOAD_TOO_LARGE,
+                _ if error.to_string().contains("busy") => StatusCode::CONFLICT,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            history.record(
+                &session,
+                HistoryEntry::new(&request.sql, Err(error.to_string())),
+            );
+
+            (
+                status,
+                Json(QueryResult {
+                    columns: vec![],
+                    rows: vec![],
+                    affected_rows: 0,
+                    execution_time_milliseconds: 0,
+                    error: Some(error.to_string()),
+                    timeout: error.timeout_kind(),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/console/sessions/:session/history
+///
+/// Lists the statements run in a console session, oldest first.
+///
+/// Response:
+/// ```json
+/// {
+///   "session": "192f1c2a3b4-1",
+///   "entries": [{
+///     "sql": "UPDATE users SET active = false",
+///     "executedAtMilliseconds": 1730000000000,
+///     "executionTimeMilliseconds": 4,
+///     "affectedRows": 12,
+///     "error": null
+///   }]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `history` - Console history from state
+/// * `session` - Identifier of the session
+///
+/// # Returns
+///
+/// JSON response containing the history, or 404 if the session is unknown or expired
+pub async fn session_history_handler(
+    State(history): State<ConsoleHistory>,
+    Path(session): Path<String>,
+) -> Response {
+    match history.entries(&session) {
+        Some(entries) => (
+            StatusCode::OK,
+            Json(SessionHistoryResponse { session, entries }),
+        )
+            .into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("Session not found: {}", session)
+            })),
+        )
+            .into_response(),
+    }
+}
+
+/// Handler for DELETE /api/console/sessions/:session
+///
+/// Closes a console session, rolling back a transaction left open, and
+/// forgets its history.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `history` - Console history from state
+/// * `session` - Identifier of the session
+///
+/// # Returns
+///
+/// Empty response with status 204 on success
+pub async fn close_session_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(history): State<ConsoleHistory>,
+    Path(session): Path<String>,
+) -> Response {
+    history.close(&session);
+
+    match database.close_session(&session).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(error) => {
+            eprintln!("Failed to close console session '{}': {}", session, error);
+
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::database::sqlite::SqliteProvider;
+    use crate::schema::ConfirmationRequired;
+    use serde_json::json;
+
+    async fn query(
+        database: &Arc<SqliteProvider>,
+        confirmations: &Confirmations,
+        session: &str,
+        request: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = session_query_handler(
+            State(database.clone()),
+            State(ConsoleHistory::default()),
+            State(CostLimits::default()),
+            State(confirmations.clone()),
+            Path(session.to_string()),
+            Json(serde_json::from_value(request).unwrap()),
+        )
+        .await;
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_destructive_session_query_needs_confirmation() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let database = Arc::new(SqliteProvider::new(pool));
+        let confirmations = Confirmations::default();
+        let session = database.open_session().await.unwrap().session;
+
+        let setup = "CREATE TABLE t (id INTEGER); INSERT INTO t VALUES (1), (2)";
+        let (status, _) = query(&database, &confirmations, &session, json!({ "sql": setup })).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let delete = json!({ "sql": "DELETE FROM t" });
+        let (status, body) = query(&database, &confirmations, &session, delete).await;
+        assert_eq!(status, StatusCode::PRECONDITION_REQUIRED);
+        let plan: ConfirmationRequired = serde_json::from_value(body).unwrap();
+        assert_eq!(plan.estimated_affected_rows, None);
+
+        let count = json!({ "sql": "SELECT count(*) AS n FROM t" });
+        let (_, body) = query(&database, &confirmations, &session, count.clone()).await;
+        assert_eq!(body["rows"][0]["n"], json!(2));
+
+        let confirmed = json!({ "sql": "DELETE FROM t", "confirmation": plan.confirmation });
+        let (status, body) = query(&database, &confirmations, &session, confirmed).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["affectedRows"], json!(2));
+
+        let streamed = json!({ "sql": "SELECT * FROM t", "stream": true });
+        let (status, _) = query(&database, &confirmations, &session, streamed).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let paged = json!({ "sql": "SELECT * FROM t", "pageSize": 10 });
+        let (status, _) = query(&database, &confirmations, &session, paged).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,98 @@
+//! Entity-relationship diagram endpoints
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::DatabaseProvider;
+use crate::diagram::{mermaid_er_diagram, relationship_graph};
+use crate::schema_cache::SchemaCache;
+
+/// Handler for GET /api/graph
+///
+/// Returns all tables as nodes and all foreign keys as edges, with cardinality
+/// hints, so clients can render an ER diagram from a single request.
+///
+/// Response:
+/// ```json
+/// {
+///   "nodes": [{ "id": "users", "columns": [...], "primaryKey": ["id"] }],
+///   "edges": [{
+///     "id": "orders.user_id->users.id",
+///     "source": "orders",
+///     "sourceColumn": "user_id",
+///     "target": "users",
+///     "targetColumn": "id",
+///     "cardinality": "zeroOrMany",
+///     "optional": true,
+///     "dangling": false
+///   }]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `schema_cache` - Cache answering the request if all schemas were loaded before
+///
+/// # Returns
+///
+/// JSON response containing the relationship graph
+pub async fn relationship_graph_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(schema_cache): State<SchemaCache>,
+) -> Response {
+    match schema_cache.all_table_schemas(database.as_ref()).await {
+        Ok(schemas) => (StatusCode::OK, Json(relationship_graph(&schemas))).into_response(),
+        Err(error) => {
+            eprintln!("Failed to build relationship graph: {}", error);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/erd.mmd
+///
+/// Returns a Mermaid `erDiagram` definition covering all tables, their columns
+/// and the foreign key relationships between them.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `schema_cache` - Cache answering the request if all schemas were loaded before
+///
+/// # Returns
+///
+/// Plain text response containing the Mermaid diagram definition
+pub async fn erd_mermaid_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(schema_cache): State<SchemaCache>,
+) -> Response {
+    match schema_cache.all_table_schemas(database.as_ref()).await {
+        Ok(schemas) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            mermaid_er_diagram(&schemas),
+        )
+            .into_response(),
+        Err(error) => {
+            eprintln!("Failed to build ER diagram: {}", error);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
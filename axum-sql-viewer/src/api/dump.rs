@@ -0,0 +1,275 @@
+//! Dump endpoint
+//!
+//! Streams a SQL script recreating tables with their rows, like `pg_dump`
+//! (see [`crate::dump`]). Rows are read in pages within one snapshot, so the
+//! dump is consistent across tables, and anonymized columns are replaced as
+//! in exports (see [`crate::anonymize`]).
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::anonymize::AnonymizationRules;
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::dump::DumpWriter;
+use crate::fixtures::load_order;
+use crate::schema::{DumpQuery, RowAddress};
+
+/// Rows read per page while writing a dump
+const DUMP_PAGE_SIZE: u64 = 1000;
+
+/// Handler for GET /api/dump
+///
+/// Downloads a SQL script creating the tables listed in `tables`
+/// (comma-separated; all tables when not set, leaving out views) and filling
+/// them with their rows. Tables come in the order they are loaded in,
+/// referenced tables first; indexes and foreign keys are created after the
+/// rows, and sequences of serial and identity columns are moved past them.
+/// Only PostgreSQL supports dumps.
+///
+/// Query parameters:
+/// - tables: Comma-separated table names
+/// - schemaOnly: true to leave out the rows
+/// - dataOnly: true to leave out the statements creating the tables
+/// - inserts: true to write rows as `INSERT` statements instead of `COPY`
+///   blocks (slower to load, but runs through any SQL client)
+///
+/// Response (excerpt; `COPY` rows separate their values with tabs):
+/// ```sql
+/// CREATE TABLE "users" (
+///     "id" serial NOT NULL,
+///     "name" text,
+///     CONSTRAINT "users_pkey" PRIMARY KEY (id)
+/// );
+///
+/// COPY "users" ("id", "name") FROM stdin;
+/// 1    Alice
+/// \.
+/// ```
+///
+/// Errors found before the first table is written (unknown table, view
+/// requested, unsupported database) are reported with an error status. A
+/// failure later on ends the download early.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `anonymization` - Configured anonymization rules from state
+/// * `query` - Tables and dump options
+///
+/// # Returns
+///
+/// The streamed script with a `Content-Disposition: attachment` header
+pub async fn dump_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(anonymization): State<AnonymizationRules>,
+    Query(query): Query<DumpQuery>,
+) -> Response {
+    if query.schema_only && query.data_only {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "schemaOnly and dataOnly cannot be combined"
+            })),
+        )
+            .into_response();
+    }
+
+    let result = async {
+        let requested = query.tables.is_some();
+        let names = match &query.tables {
+            Some(tables) => tables
+                .split(',')
+                .map(str::trim)
+                .filter(|table| !table.is_empty())
+                .map(str::to_string)
+                .collect(),
+            None => database
+                .list_tables()
+                .await?
+                .into_iter()
+                .map(|table| table.name)
+                .collect::<Vec<_>>(),
+        };
+
+        let mut schemas = Vec::with_capacity(names.len());
+        for name in &names {
+            let schema = database.get_table_schema(name).await?;
+            if requested || schema.row_address != RowAddress::None {
+                schemas.push(schema);
+            }
+        }
+        // Tables referencing each other are written in the order they were requested
+        let order = load_order(&schemas).unwrap_or_else(|_| (0..schemas.len()).collect());
+
+        let mut tables = Vec::with_capacity(schemas.len());
+        for index in order {
+            tables.push(database.table_definition(&schemas[index].name).await?);
+        }
+        Ok::<_, DatabaseError>(tables)
+    }
+    .await;
+
+    let tables = match result {
+        Ok(tables) => tables,
+        Err(error) => return dump_error(error),
+    };
+
+    let writer = DumpWriter::new(tables, &query);
+    let snapshot = if writer.writes_data() && database.capabilities().supports_snapshots {
+        database
+            .begin_snapshot()
+            .await
+            .ok()
+            .map(|snapshot| snapshot.snapshot)
+    } else {
+        None
+    };
+
+    let stream = DumpStream {
+        database,
+        anonymization,
+        writer,
+        snapshot,
+        position: DumpPosition::Start,
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, "application/sql; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"dump.sql\"",
+            ),
+        ],
+        stream.into_body(),
+    )
+        .into_response()
+}
+
+/// Next part of a streamed dump
+enum DumpPosition {
+    Start,
+    /// Rows of the table at the index, starting at the offset
+    Rows(usize, u64),
+    Finish,
+    Done,
+}
+
+/// State of a streamed dump
+struct DumpStream<DB: DatabaseProvider> {
+    database: Arc<DB>,
+    anonymization: AnonymizationRules,
+    writer: DumpWriter,
+    snapshot: Option<String>,
+    position: DumpPosition,
+}
+
+impl<DB: DatabaseProvider> DumpStream<DB> {
+    /// Turn into a response body producing one chunk per page
+    fn into_body(self) -> Body {
+        let stream = futures_util::stream::unfold(self, |mut state| async move {
+            match state.position {
+                DumpPosition::Start => {
+                    state.position = if state.writer.writes_data() {
+                        state.next_table(0)
+                    } else {
+                        DumpPosition::Finish
+                    };
+                    Some((Ok(state.writer.start()), state))
+                }
+                DumpPosition::Rows(index, offset) => match state.rows(index, offset).await {
+                    Ok(chunk) => Some((Ok(chunk), state)),
+                    Err(error) => {
+                        let table = &state.writer.tables()[index].table;
+                        eprintln!("Dump of table '{}' failed: {}", table, error);
+                        state.finish().await;
+                        Some((Err(std::io::Error::other(error.to_string())), state))
+                    }
+                },
+                DumpPosition::Finish => {
+                    state.finish().await;
+                    Some((Ok(state.writer.finish()), state))
+                }
+                DumpPosition::Done => None,
+            }
+        });
+
+        Body::from_stream(stream)
+    }
+
+    /// Write a page of rows of the table at `index`, moving on to the next table after the last
+    async fn rows(&mut self, index: usize, offset: u64) -> Result<String, DatabaseError> {
+        let table = &self.writer.tables()[index];
+        let mut chunk = if offset == 0 {
+            self.writer.table_start(index)
+        } else {
+            String::new()
+        };
+
+        let rows = if table.columns.is_empty() {
+            Vec::new()
+        } else {
+            self.database
+                .dump_rows(
+                    &table.table,
+                    &table.columns,
+                    self.snapshot.as_deref(),
+                    offset,
+                    DUMP_PAGE_SIZE,
+                )
+                .await?
+        };
+        let anonymizer = self.anonymization.for_table(&table.table, &table.columns);
+        chunk.push_str(&self.writer.rows(index, &anonymizer.apply(&rows)));
+
+        if rows.len() as u64 == DUMP_PAGE_SIZE {
+            self.position = DumpPosition::Rows(index, offset + DUMP_PAGE_SIZE);
+        } else {
+            chunk.push_str(&self.writer.table_end(index));
+            self.position = self.next_table(index + 1);
+        }
+        Ok(chunk)
+    }
+
+    /// Position of the rows of the table at `index`, or of the end after the last table
+    fn next_table(&self, index: usize) -> DumpPosition {
+        if index < self.writer.tables().len() {
+            DumpPosition::Rows(index, 0)
+        } else {
+            DumpPosition::Finish
+        }
+    }
+
+    /// Stop after the current chunk, releasing the snapshot
+    async fn finish(&mut self) {
+        self.position = DumpPosition::Done;
+        if let Some(snapshot) = self.snapshot.take() {
+            let _ = self.database.release_snapshot(&snapshot).await;
+        }
+    }
+}
+
+/// Error response for a dump that could not be started
+fn dump_error(error: DatabaseError) -> Response {
+    eprintln!("Failed to create dump: {}", error);
+
+    let status = match error {
+        DatabaseError::TableNotFound(_) => StatusCode::NOT_FOUND,
+        DatabaseError::AmbiguousTable(..) => StatusCode::BAD_REQUEST,
+        DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "error": error.to_string()
+        })),
+    )
+        .into_response()
+}
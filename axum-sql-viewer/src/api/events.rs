@@ -0,0 +1,75 @@
+//! Server-sent events endpoints
+
+use axum::{
+    extract::State,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use futures_util::StreamExt;
+
+use crate::notifications::NotificationEvents;
+use crate::schema_watch::SchemaEvents;
+
+/// Handler for GET /api/events
+///
+/// Streams server-sent events to the viewer. A `schemaChanged` event is sent
+/// whenever the schema watcher (`SqlViewerLayer::with_schema_watch`) notices
+/// that tables were created, dropped or altered; without the watcher the
+/// stream stays silent apart from keep-alive comments.
+///
+/// Event:
+/// ```text
+/// event: schemaChanged
+/// data: {"added":["invoices"],"removed":[],"changed":["users"]}
+/// ```
+///
+/// # Arguments
+///
+/// * `events` - Schema change publisher from state
+///
+/// # Returns
+///
+/// A `text/event-stream` response that stays open
+pub async fn events_handler(State(events): State<SchemaEvents>) -> Response {
+    let stream = events
+        .subscribe()
+        .map(|change| Event::default().event("schemaChanged").json_data(change));
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Handler for GET /api/notifications
+///
+/// Streams the notifications sent with `NOTIFY` to the channels configured
+/// with `SqlViewerLayer::with_notification_channels` as `notification`
+/// events. Without configured channels, or on a database without
+/// notifications, the stream stays silent apart from keep-alive comments.
+///
+/// Event:
+/// ```text
+/// event: notification
+/// data: {"channel":"table_changed","payload":"orders"}
+/// ```
+///
+/// # Arguments
+///
+/// * `notifications` - Notification publisher from state
+///
+/// # Returns
+///
+/// A `text/event-stream` response that stays open
+pub async fn notifications_handler(State(notifications): State<NotificationEvents>) -> Response {
+    let stream = notifications.subscribe().map(|notification| {
+        Event::default()
+            .event("notification")
+            .json_data(notification)
+    });
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
@@ -0,0 +1,127 @@
+//! Structured query plan endpoint
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::classify::is_read_only;
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::schema::ExplainRequest;
+use crate::sql::{normalize, replace_placeholders_with_null, split_statements};
+
+/// Handler for POST /api/explain
+///
+/// Asks the database how it would run a statement and returns the plan as a
+/// tree of steps, without executing the statement. PostgreSQL plans come from
+/// `EXPLAIN (FORMAT JSON)` and carry row and cost estimates; SQLite plans come
+/// from `EXPLAIN QUERY PLAN` and only describe the steps. Placeholders are
+/// replaced with NULL before explaining.
+///
+/// With `"analyze": true` the statement is run to measure it. PostgreSQL then
+/// reports the actual rows, runs and time of every step
+/// (`EXPLAIN (ANALYZE, FORMAT JSON)`); SQLite only the total execution time.
+/// Since the statement really runs, only read-only statements are accepted,
+/// and the transaction it runs in is rolled back.
+///
+/// Request body:
+/// ```json
+/// {
+///   "sql": "SELECT * FROM orders JOIN users ON users.id = orders.user_id WHERE users.id = $1",
+///   "analyze": false
+/// }
+/// ```
+///
+/// Response:
+/// ```json
+/// {
+///   "explainedSql": "SELECT * FROM orders JOIN users ON users.id = orders.user_id WHERE users.id = NULL",
+///   "plan": [{
+///     "operation": "Nested Loop",
+///     "detail": "Nested Loop",
+///     "relation": null,
+///     "index": null,
+///     "estimatedRows": 10.0,
+///     "estimatedCost": 32.6,
+///     "properties": { "Join Type": "Inner" },
+///     "children": [{
+///       "operation": "Index Scan",
+///       "detail": "Index Scan using users_pkey on users",
+///       "relation": "users",
+///       "index": "users_pkey",
+///       "estimatedRows": 1.0,
+///       "estimatedCost": 8.17,
+///       "properties": { "Index Cond": "(id = NULL::integer)" },
+///       "children": []
+///     }]
+///   }],
+///   "planningTimeMilliseconds": null,
+///   "executionTimeMilliseconds": null
+/// }
+/// ```
+///
+/// Analyzed steps additionally carry `actualRows` (per run), `actualLoops`,
+/// `actualTimeMilliseconds` (including children, over all runs) and
+/// `selfTimeMilliseconds` (the step alone).
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `request` - JSON request containing the statement
+///
+/// # Returns
+///
+/// JSON response containing the plan tree
+pub async fn explain_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Json(request): Json<ExplainRequest>,
+) -> Response {
+    let dialect = database.dialect();
+
+    // Only a single statement can be explained safely
+    if split_statements(&request.sql, dialect).len() != 1 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Invalid SQL: expected exactly one statement"
+            })),
+        )
+            .into_response();
+    }
+
+    // EXPLAIN ANALYZE executes the statement
+    if request.analyze && !is_read_only(&request.sql, dialect) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Invalid request: only read-only statements can be analyzed"
+            })),
+        )
+            .into_response();
+    }
+
+    let explained_sql = replace_placeholders_with_null(&normalize(&request.sql, dialect), dialect);
+
+    match database.explain_plan(&explained_sql, request.analyze).await {
+        Ok(plan) => (StatusCode::OK, Json(plan)).into_response(),
+        Err(error) => {
+            eprintln!("Failed to explain query: {}", error);
+
+            let status = match &error {
+                DatabaseError::Timeout(_) => StatusCode::REQUEST_TIMEOUT,
+                _ if error.to_string().contains("Not supported") => StatusCode::NOT_IMPLEMENTED,
+                _ => StatusCode::BAD_REQUEST,
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
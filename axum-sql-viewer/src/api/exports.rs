@@ -0,0 +1,812 @@
+//! Export endpoints
+//!
+//! A table can be exported in two ways: streamed directly as the response of
+//! `/api/tables/:name/export`, or by a job that writes the file to the artifact
+//! store, to be downloaded from `/api/exports/:id`. Downloads of artifacts
+//! support `Range` requests, so a download interrupted near the end can be
+//! resumed instead of restarted.
+//!
+//! Both respect the export limits configured for the table (see
+//! [`crate::export_limits`]) and replace the values of anonymized columns
+//! (see [`crate::anonymize`]).
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{AppendHeaders, IntoResponse, Json, Response},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::anonymize::{AnonymizationRules, TableAnonymizer};
+use crate::artifacts::{parse_range, Artifact, ArtifactStore, ByteRange};
+use crate::database::traits::DatabaseProvider;
+use crate::export::ExportEncoder;
+use crate::export_limits::{
+    byte_limit_manifest, plan_rows, ExportGuardrails, ExportLimits, ExportPages,
+};
+use crate::filters::filter_parameters;
+use crate::jobs::JobRegistry;
+use crate::runtime;
+use crate::schema::{
+    ExportArtifactResponse, ExportManifest, ExportRequest, OnExportLimit, RowQuery, RowsResponse,
+    TableSchema,
+};
+
+/// Rows fetched per page while writing an export
+const EXPORT_PAGE_SIZE: u64 = 500;
+
+/// Bytes read from the artifact per chunk of a download
+const DOWNLOAD_CHUNK_SIZE: u64 = 256 * 1024;
+
+/// Handler for GET /api/tables/:name/export
+///
+/// Streams the rows of a table as a file download, with the same filters and
+/// sorting as `/api/tables/:name/rows` but without its row limit: the rows are
+/// read and sent in pages, so memory use does not grow with the table.
+///
+/// Query parameters:
+/// - format: "csv" (default), "xlsx" (Excel workbook), "ndjson" (one JSON
+///   object per line, values as returned by the database) or "sql" (one
+///   `INSERT` statement per row, literals escaped for the database's dialect)
+/// - sortBy, sortOrder: Sorting, as for rows
+/// - filter[column]: Filter value for a column, as for rows
+/// - filter[column][gt|gte|lt|lte]: Date or timestamp range, as for rows
+/// - caseInsensitive: "true" to match filters ignoring case, as for rows
+/// - q: Text to search for in all textual columns, as for rows
+/// - delimiter, decimalSeparator, dateFormat: Locale options (CSV only)
+/// - nullValue: "empty" (default), "backslashN" (`\N`) or "null" (`NULL`)
+/// - quoting: "minimal" (default) or "always" (every field except NULL)
+/// - bom: true to start the CSV file with a UTF-8 byte order mark
+/// - onLimit: "truncate" (default), "sample" or "fail", for tables over the
+///   configured export limits
+///
+/// Errors found before the first rows are sent (unknown table or column,
+/// invalid options, row limit exceeded with `onLimit=fail`) are reported with
+/// an error status. A failure later on ends the download early. If rows are
+/// left out because of the row limit, the `X-Export-Warning` header says so;
+/// a file cut off at the size limit simply ends after the last row that fit.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `guardrails` - Configured export limits from state
+/// * `anonymization` - Configured anonymization rules from state
+/// * `table_name` - Name of the table to export
+/// * `request` - Export format, sorting and locale options
+/// * `parameters` - All query parameters (for `filter[column]` and ranges)
+///
+/// # Returns
+///
+/// The streamed file with a `Content-Disposition: attachment` header
+pub async fn export_table_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(guardrails): State<ExportGuardrails>,
+    State(anonymization): State<AnonymizationRules>,
+    Path(table_name): Path<String>,
+    Query(mut request): Query<ExportRequest>,
+    Query(parameters): Query<HashMap<String, String>>,
+) -> Response {
+    match filter_parameters(&parameters) {
+        Ok(filters) => {
+            request.filters.extend(filters.values);
+            request.ranges = filters.ranges;
+        }
+        Err(error) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response();
+        }
+    }
+
+    if let Err(error) = request.options.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": error
+            })),
+        )
+            .into_response();
+    }
+
+    let schema = match database.get_table_schema(&table_name).await {
+        Ok(schema) => schema,
+        Err(error) => return export_error_response(&table_name, error.to_string()),
+    };
+
+    let format = request.format;
+    let columns = columns_of(schema);
+    let rules = TableRules {
+        limits: guardrails.for_table(&table_name),
+        anonymizer: anonymization.for_table(&table_name, &columns),
+    };
+    let encoder = ExportEncoder::new(
+        format,
+        &table_name,
+        columns,
+        request.options.clone(),
+        database.dialect(),
+    );
+
+    // Plan the export and fetch the first page before responding, so invalid
+    // requests get an error status
+    let snapshot = begin_export_snapshot(database.as_ref()).await;
+    let (progress, pending) = match start_export(
+        database.as_ref(),
+        &table_name,
+        &request,
+        rules,
+        encoder,
+        snapshot.clone(),
+    )
+    .await
+    {
+        Ok(started) => started,
+        Err(error) => {
+            if let Some(snapshot) = &snapshot {
+                let _ = database.release_snapshot(snapshot).await;
+            }
+            return export_error_response(&table_name, error);
+        }
+    };
+
+    let file_name = format!(
+        "{}.{}",
+        attachment_file_name(&table_name),
+        format.extension()
+    );
+    let mut headers: Vec<(HeaderName, String)> = vec![
+        (header::CONTENT_TYPE, format.content_type().to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file_name),
+        ),
+    ];
+    if let Some(manifest) = &progress.manifest {
+        headers.push((
+            HeaderName::from_static("x-export-warning"),
+            manifest.warnings.join("; "),
+        ));
+    }
+
+    let stream = ExportStream {
+        database,
+        table_name,
+        request,
+        snapshot,
+        progress,
+        pending,
+        finished: false,
+    };
+
+    (StatusCode::OK, AppendHeaders(headers), stream.into_body()).into_response()
+}
+
+/// Plan an export and fetch its first page
+///
+/// Counts the matching rows if the table has a row limit, so an export over
+/// the limit can be refused, truncated or sampled before anything is written.
+///
+/// # Returns
+///
+/// The export's progress and its first page (`None` if no rows are to be read)
+async fn start_export<DB: DatabaseProvider>(
+    database: &DB,
+    table_name: &str,
+    request: &ExportRequest,
+    rules: TableRules,
+    encoder: ExportEncoder,
+    snapshot: Option<String>,
+) -> Result<(ExportProgress, Option<RowsResponse>), String> {
+    let total = match rules.limits.max_rows {
+        Some(_) => {
+            let query = export_page_query(request, 0, EXPORT_PAGE_SIZE, snapshot.clone());
+            let count = database
+                .count_rows(table_name, &query)
+                .await
+                .map_err(|error| error.to_string())?;
+            Some(count.count)
+        }
+        None => None,
+    };
+    let (windows, manifest) = plan_rows(total, rules.limits, request.on_limit)?;
+
+    let progress = ExportProgress {
+        encoder,
+        anonymizer: rules.anonymizer,
+        pages: ExportPages::new(windows),
+        limits: rules.limits,
+        on_limit: request.on_limit,
+        manifest,
+        started: false,
+        rows: 0,
+        bytes: 0,
+    };
+
+    let first_page = match progress.next_query(request, snapshot) {
+        Some(query) => Some(
+            database
+                .get_rows(table_name, query)
+                .await
+                .map_err(|error| error.to_string())?,
+        ),
+        None => None,
+    };
+
+    Ok((progress, first_page))
+}
+
+/// Limits and anonymization configured for an exported table
+struct TableRules {
+    limits: ExportLimits,
+    anonymizer: TableAnonymizer,
+}
+
+/// Encoder and position of an export within its limits
+struct ExportProgress {
+    encoder: ExportEncoder,
+    anonymizer: TableAnonymizer,
+    pages: ExportPages,
+    limits: ExportLimits,
+    on_limit: OnExportLimit,
+    /// Why rows are left out, if they are
+    manifest: Option<ExportManifest>,
+    /// Whether the start of the file has been encoded
+    started: bool,
+    rows: u64,
+    bytes: u64,
+}
+
+impl ExportProgress {
+    /// Query for the next page, or `None` when all planned rows have been read
+    fn next_query(&self, request: &ExportRequest, snapshot: Option<String>) -> Option<RowQuery> {
+        let (offset, limit) = self.pages.next_page(EXPORT_PAGE_SIZE)?;
+        Some(export_page_query(request, offset, limit, snapshot))
+    }
+
+    /// Encode a page, stopping at the size limit
+    ///
+    /// The start of the file is encoded with the first page. Values of
+    /// anonymized columns are replaced before encoding.
+    fn page(&mut self, page: &RowsResponse) -> Result<Vec<u8>, String> {
+        let mut chunk = self.start();
+        let budget = self
+            .limits
+            .max_bytes
+            .map(|max_bytes| max_bytes.saturating_sub(self.bytes + chunk.len() as u64));
+        let page_rows = self.anonymizer.apply(&page.rows);
+        let (rows, taken) = self.encoder.rows_within(&page_rows, budget)?;
+        chunk.extend(rows);
+        self.rows += taken as u64;
+        self.bytes += chunk.len() as u64;
+        self.pages.advance(page.rows.len() as u64, page.has_more);
+
+        if let (Some(max_bytes), true) = (self.limits.max_bytes, taken < page.rows.len()) {
+            if self.on_limit == OnExportLimit::Fail {
+                return Err(format!(
+                    "Export too large: the file exceeds the limit of {} bytes",
+                    max_bytes
+                ));
+            }
+            self.manifest = Some(byte_limit_manifest(
+                self.manifest.take(),
+                self.on_limit,
+                self.rows,
+                max_bytes,
+            ));
+            self.pages = ExportPages::new(Vec::new());
+        }
+
+        Ok(chunk)
+    }
+
+    /// End of the file (including its start if no page was encoded)
+    fn finish(&mut self) -> Vec<u8> {
+        let mut chunk = self.start();
+        chunk.extend(self.encoder.finish());
+        self.bytes += chunk.len() as u64;
+        chunk
+    }
+
+    /// Start of the file, the first time it is asked for
+    fn start(&mut self) -> Vec<u8> {
+        if std::mem::replace(&mut self.started, true) {
+            Vec::new()
+        } else {
+            self.encoder.start()
+        }
+    }
+}
+
+/// State of a streamed export
+struct ExportStream<DB: DatabaseProvider> {
+    database: Arc<DB>,
+    table_name: String,
+    request: ExportRequest,
+    snapshot: Option<String>,
+    progress: ExportProgress,
+    /// Page fetched but not yet sent
+    pending: Option<RowsResponse>,
+    finished: bool,
+}
+
+impl<DB: DatabaseProvider> ExportStream<DB> {
+    /// Turn into a response body producing one chunk per page
+    fn into_body(self) -> Body {
+        let stream = futures_util::stream::unfold(self, |mut state| async move {
+            if state.finished {
+                return None;
+            }
+
+            let page = match state.pending.take() {
+                Some(page) => page,
+                None => {
+                    let Some(query) = state
+                        .progress
+                        .next_query(&state.request, state.snapshot.clone())
+                    else {
+                        let chunk = state.progress.finish();
+                        state.finish().await;
+                        return Some((Ok(chunk), state));
+                    };
+                    match state.database.get_rows(&state.table_name, query).await {
+                        Ok(page) => page,
+                        Err(error) => {
+                            eprintln!("Export of table '{}' failed: {}", state.table_name, error);
+                            state.finish().await;
+                            return Some((Err(std::io::Error::other(error.to_string())), state));
+                        }
+                    }
+                }
+            };
+
+            match state.progress.page(&page) {
+                Ok(chunk) => Some((Ok(chunk), state)),
+                Err(error) => {
+                    eprintln!("Export of table '{}' failed: {}", state.table_name, error);
+                    state.finish().await;
+                    Some((Err(std::io::Error::other(error)), state))
+                }
+            }
+        });
+
+        Body::from_stream(stream)
+    }
+
+    /// Stop after the current chunk, releasing the snapshot
+    async fn finish(&mut self) {
+        self.finished = true;
+        if let Some(snapshot) = self.snapshot.take() {
+            let _ = self.database.release_snapshot(&snapshot).await;
+        }
+    }
+}
+
+/// Error response for an export that could not be started
+fn export_error_response(table_name: &str, error: String) -> Response {
+    eprintln!("Failed to export table '{}': {}", table_name, error);
+
+    // Return appropriate status code based on error type
+    let status = if error.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if error.contains("too large") {
+        StatusCode::PAYLOAD_TOO_LARGE
+    } else if error.contains("Invalid") {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "error": error
+        })),
+    )
+        .into_response()
+}
+
+/// Column names of a table, in order
+fn columns_of(schema: TableSchema) -> Vec<String> {
+    schema
+        .columns
+        .into_iter()
+        .map(|column| column.name)
+        .collect()
+}
+
+/// Handler for POST /api/tables/:name/exports
+///
+/// Starts a job exporting the table (with optional filters and sorting) to a
+/// file. Responds with 202 Accepted and the job; once it has succeeded, its
+/// result describes the file:
+///
+/// ```json
+/// {
+///   "id": "192f1c2a3b4-1",
+///   "fileName": "users.csv",
+///   "size": 1048576,
+///   "rows": 12000,
+///   "url": "/api/exports/192f1c2a3b4-1",
+///   "expiresInSeconds": 3600
+/// }
+/// ```
+///
+/// If the table is over its export limits, `onLimit` decides what happens:
+/// "truncate" (default) exports the first rows, "sample" a sample spread
+/// evenly over the table, and "fail" makes the job fail. Rows left out are
+/// explained by a `manifest` in the result:
+///
+/// ```json
+/// {
+///   "manifest": {
+///     "mode": "sample",
+///     "totalRows": 2500000,
+///     "warnings": ["A sample of 100000 of 2500000 rows, spread evenly over the table, was exported (row limit)"]
+///   }
+/// }
+/// ```
+///
+/// Request body (`format` is "csv", "xlsx", "ndjson" or "sql"):
+/// ```json
+/// {
+///   "format": "csv",
+///   "sortBy": "id",
+///   "sortOrder": "ascending",
+///   "filters": { "role": "admin" },
+///   "delimiter": ";",
+///   "decimalSeparator": ",",
+///   "nullValue": "backslashN",
+///   "quoting": "always",
+///   "bom": true,
+///   "onLimit": "sample"
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `jobs` - Job registry from state
+/// * `artifacts` - Artifact store from state
+/// * `guardrails` - Configured export limits from state
+/// * `anonymization` - Configured anonymization rules from state
+/// * `table_name` - Name of the table to export
+/// * `request` - Export format, row selection and locale options
+///
+/// # Returns
+///
+/// JSON response containing the started job
+pub async fn start_export_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(jobs): State<JobRegistry>,
+    State(artifacts): State<ArtifactStore>,
+    State(guardrails): State<ExportGuardrails>,
+    State(anonymization): State<AnonymizationRules>,
+    Path(table_name): Path<String>,
+    Json(request): Json<ExportRequest>,
+) -> Response {
+    if let Err(error) = request.options.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": error
+            })),
+        )
+            .into_response();
+    }
+
+    let schema = match database.get_table_schema(&table_name).await {
+        Ok(schema) => schema,
+        Err(error) => return export_error_response(&table_name, error.to_string()),
+    };
+
+    let file_name = format!(
+        "{}.{}",
+        attachment_file_name(&table_name),
+        request.format.extension()
+    );
+
+    let artifact = match artifacts
+        .create(&file_name, request.format.content_type())
+        .await
+    {
+        Ok(artifact) => artifact,
+        Err(error) => {
+            eprintln!("Failed to create export file: {}", error);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to create export file: {}", error)
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let columns = columns_of(schema);
+    let rules = TableRules {
+        limits: guardrails.for_table(&table_name),
+        anonymizer: anonymization.for_table(&table_name, &columns),
+    };
+    let encoder = ExportEncoder::new(
+        request.format,
+        &table_name,
+        columns,
+        request.options.clone(),
+        database.dialect(),
+    );
+    let job = jobs
+        .start("export", async move {
+            let written =
+                write_export(&*database, &table_name, encoder, &request, rules, &artifact).await;
+
+            let (rows, size, manifest) = match written {
+                Ok(written) => written,
+                Err(error) => {
+                    eprintln!("Export of table '{}' failed: {}", table_name, error);
+                    artifacts.remove(&artifact.id).await;
+                    return Err(error);
+                }
+            };
+            artifacts.complete(&artifact.id, size);
+
+            let response = ExportArtifactResponse {
+                url: format!("/api/exports/{}", artifact.id),
+                id: artifact.id,
+                file_name: artifact.file_name,
+                size,
+                rows,
+                expires_in_seconds: artifacts.retention().as_secs(),
+                manifest,
+            };
+            serde_json::to_value(response).map_err(|error| error.to_string())
+        })
+        .await;
+
+    (StatusCode::ACCEPTED, Json(job)).into_response()
+}
+
+/// Handler for GET /api/exports/:id
+///
+/// Downloads an export file. A single byte range can be requested with the
+/// `Range` header (e.g. `Range: bytes=1048576-`) to resume a download; the
+/// response is then 206 Partial Content. `If-Range` with the `ETag` of the
+/// earlier response makes sure the parts belong to the same file.
+///
+/// # Arguments
+///
+/// * `artifacts` - Artifact store from state
+/// * `id` - Artifact identifier from the export job result
+/// * `headers` - Request headers (`Range`, `If-Range`)
+///
+/// # Returns
+///
+/// The file (or the requested part of it), 404 if it is unknown or expired,
+/// or 416 if the range lies outside the file
+pub async fn download_export_handler(
+    State(artifacts): State<ArtifactStore>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(artifact) = artifacts.get(&id).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("Export not found: {}", id)
+            })),
+        )
+            .into_response();
+    };
+
+    let etag = artifact.etag();
+
+    // A range only applies to the version of the file the client already has
+    let range_applies = !matches!(
+        headers.get(header::IF_RANGE),
+        Some(value) if value.as_bytes() != etag.as_bytes()
+    );
+    let range = match headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(range) if range_applies => parse_range(range, artifact.size),
+        _ => ByteRange::Full,
+    };
+
+    let mut response_headers: Vec<(HeaderName, String)> = vec![
+        (header::ACCEPT_RANGES, "bytes".to_string()),
+        (header::ETAG, etag),
+    ];
+
+    let (status, start, length) = match range {
+        ByteRange::Full => (StatusCode::OK, 0, artifact.size),
+        ByteRange::Partial { start, end } => {
+            response_headers.push((
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, end, artifact.size),
+            ));
+            (StatusCode::PARTIAL_CONTENT, start, end - start + 1)
+        }
+        ByteRange::Unsatisfiable => {
+            response_headers.push((header::CONTENT_RANGE, format!("bytes */{}", artifact.size)));
+            return (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                AppendHeaders(response_headers),
+            )
+                .into_response();
+        }
+    };
+
+    response_headers.push((header::CONTENT_TYPE, artifact.content_type.to_string()));
+    response_headers.push((header::CONTENT_LENGTH, length.to_string()));
+    response_headers.push((
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{}\"", artifact.file_name),
+    ));
+
+    (
+        status,
+        AppendHeaders(response_headers),
+        file_body(artifact, start, length),
+    )
+        .into_response()
+}
+
+/// Write the rows of a table to the artifact's file, page by page
+///
+/// Reads from a snapshot where the database supports them, so the pages are
+/// consistent with each other even if the table changes during the export.
+///
+/// # Returns
+///
+/// Number of rows and bytes written, and the manifest if rows were left out
+/// because of the export limits
+async fn write_export<DB: DatabaseProvider>(
+    database: &DB,
+    table_name: &str,
+    encoder: ExportEncoder,
+    request: &ExportRequest,
+    rules: TableRules,
+    artifact: &Artifact,
+) -> Result<(u64, u64, Option<ExportManifest>), String> {
+    let snapshot = begin_export_snapshot(database).await;
+
+    let outcome = write_pages(
+        database, table_name, encoder, request, rules, artifact, &snapshot,
+    )
+    .await;
+
+    if let Some(snapshot) = snapshot {
+        let _ = database.release_snapshot(&snapshot).await;
+    }
+
+    outcome
+}
+
+/// Write the planned pages of an export, reading from `snapshot` if there is one
+async fn write_pages<DB: DatabaseProvider>(
+    database: &DB,
+    table_name: &str,
+    encoder: ExportEncoder,
+    request: &ExportRequest,
+    rules: TableRules,
+    artifact: &Artifact,
+    snapshot: &Option<String>,
+) -> Result<(u64, u64, Option<ExportManifest>), String> {
+    let (mut progress, mut pending) = start_export(
+        database,
+        table_name,
+        request,
+        rules,
+        encoder,
+        snapshot.clone(),
+    )
+    .await?;
+
+    loop {
+        let page = match pending.take() {
+            Some(page) => page,
+            None => match progress.next_query(request, snapshot.clone()) {
+                Some(query) => database
+                    .get_rows(table_name, query)
+                    .await
+                    .map_err(|error| error.to_string())?,
+                None => break,
+            },
+        };
+
+        let chunk = progress.page(&page)?;
+        runtime::append_file(&artifact.path, &chunk)
+            .await
+            .map_err(|error| error.to_string())?;
+    }
+
+    let chunk = progress.finish();
+    runtime::append_file(&artifact.path, &chunk)
+        .await
+        .map_err(|error| error.to_string())?;
+
+    Ok((progress.rows, progress.bytes, progress.manifest))
+}
+
+/// Open a snapshot for an export if the database supports them
+async fn begin_export_snapshot<DB: DatabaseProvider>(database: &DB) -> Option<String> {
+    if !database.capabilities().supports_snapshots {
+        return None;
+    }
+
+    database
+        .begin_snapshot()
+        .await
+        .ok()
+        .map(|snapshot| snapshot.snapshot)
+}
+
+/// Query for the page of an export starting at `offset`
+fn export_page_query(
+    request: &ExportRequest,
+    offset: u64,
+    limit: u64,
+    snapshot: Option<String>,
+) -> RowQuery {
+    RowQuery {
+        offset,
+        limit,
+        sort_by: request.sort_by.clone(),
+        sort_order: request.sort_order,
+        nulls: request.nulls,
+        filters: request.filters.clone(),
+        ranges: request.ranges.clone(),
+        filter_groups: request.filter_groups.clone(),
+        snapshot,
+        cursor: None,
+        // Pages end with `has_more`, so the rows need no count
+        count: false,
+        columns: None,
+        case_insensitive: request.case_insensitive,
+        q: request.q.clone(),
+    }
+}
+
+/// Stream `length` bytes of the artifact's file starting at `start`
+fn file_body(artifact: Artifact, start: u64, length: u64) -> Body {
+    let stream = futures_util::stream::unfold(
+        (artifact.path, start, length),
+        |(path, position, remaining)| async move {
+            if remaining == 0 {
+                return None;
+            }
+
+            let chunk_length = remaining.min(DOWNLOAD_CHUNK_SIZE);
+            match runtime::read_file_range(&path, position, chunk_length).await {
+                Ok(chunk) => Some((
+                    Ok(chunk),
+                    (path, position + chunk_length, remaining - chunk_length),
+                )),
+                // End the stream after reporting the error
+                Err(error) => Some((Err(error), (path, position, 0))),
+            }
+        },
+    );
+
+    Body::from_stream(stream)
+}
+
+/// Table name reduced to characters that are safe in a `Content-Disposition` file name
+fn attachment_file_name(table_name: &str) -> String {
+    table_name
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || matches!(character, '-' | '_' | '.') {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
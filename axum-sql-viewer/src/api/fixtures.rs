@@ -0,0 +1,292 @@
+//! Fixture endpoints for saving and restoring the rows of selected tables
+//!
+//! Unlike a seed, which covers the whole database, a fixture holds only the
+//! tables it was made from, as a JSON or YAML document that can be kept in a
+//! repository and edited by hand (see [`crate::fixtures`]).
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::fixtures::{load_order, parse_fixture, write_fixture};
+use crate::schema::{FixtureFormat, FixtureQuery, FixtureTable, RowQuery, SortOrder, TableSchema};
+
+/// Maximum number of rows per table in a downloaded fixture
+pub const MAX_FIXTURE_ROWS: u64 = 10_000;
+
+/// Rows read per request while building a fixture
+const FIXTURE_PAGE_SIZE: u64 = 500;
+
+/// Handler for GET /api/fixtures
+///
+/// Downloads the rows of the tables listed in `tables` (comma-separated; all
+/// tables when not set) as a fixture document. `format=yaml` writes YAML
+/// instead of JSON. Tables come in the order they are loaded in, referenced
+/// tables first, and rows are sorted by primary key.
+///
+/// Response (`format=yaml`):
+/// ```yaml
+/// users:
+///   - id: 1
+///     name: "Alice"
+/// posts:
+///   - id: 1
+///     title: "Hello"
+///     user_id: 1
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `query` - Tables and document format
+///
+/// # Returns
+///
+/// The fixture document as a download
+pub async fn download_fixture_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Query(query): Query<FixtureQuery>,
+) -> Response {
+    let format = query.format.unwrap_or_default();
+
+    let result = async {
+        let names = match &query.tables {
+            Some(tables) => tables
+                .split(',')
+                .map(str::trim)
+                .filter(|table| !table.is_empty())
+                .map(str::to_string)
+                .collect(),
+            None => database
+                .list_tables()
+                .await?
+                .into_iter()
+                .map(|table| table.name)
+                .collect::<Vec<_>>(),
+        };
+
+        let mut schemas = Vec::with_capacity(names.len());
+        for name in &names {
+            schemas.push(database.get_table_schema(name).await?);
+        }
+        // Tables referencing each other are written in the order they were requested
+        let order = load_order(&schemas).unwrap_or_else(|_| (0..schemas.len()).collect());
+
+        let mut tables = Vec::with_capacity(schemas.len());
+        for index in order {
+            tables.push(dump_table(database.as_ref(), &schemas[index]).await?);
+        }
+        Ok::<_, DatabaseError>(tables)
+    }
+    .await;
+
+    match result {
+        Ok(tables) => (
+            [
+                (header::CONTENT_TYPE, format.content_type().to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"fixture.{}\"", format.extension()),
+                ),
+            ],
+            write_fixture(&tables, format),
+        )
+            .into_response(),
+        Err(error) => {
+            eprintln!("Failed to create fixture: {}", error);
+            error_response(&error.to_string())
+        }
+    }
+}
+
+/// Handler for POST /api/fixtures
+///
+/// Loads a fixture document: every table in it is emptied and filled with
+/// the rows of the fixture, in one transaction. Tables are emptied with
+/// referencing tables first and filled with referenced tables first, following
+/// their foreign keys; tables not in the fixture are left alone (a delete they
+/// block through a foreign key fails the whole load). On PostgreSQL, sequences
+/// of serial and identity columns are moved past the loaded keys.
+///
+/// The document is read as YAML if `format=yaml` is given or the request's
+/// `Content-Type` is a YAML type, and as JSON otherwise.
+///
+/// Request body:
+/// ```json
+/// {
+///   "users": [{ "id": 1, "name": "Alice" }],
+///   "posts": []
+/// }
+/// ```
+///
+/// Response:
+/// ```json
+/// {
+///   "tables": 2,
+///   "deleted": 17,
+///   "inserted": 1
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `query` - Document format
+/// * `headers` - Request headers, for the content type
+/// * `body` - Fixture document
+///
+/// # Returns
+///
+/// JSON response with the number of tables replaced and rows deleted and inserted
+pub async fn load_fixture_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Query(query): Query<FixtureQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let format = query
+        .format
+        .or_else(|| {
+            headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(FixtureFormat::from_content_type)
+        })
+        .unwrap_or_default();
+
+    let tables = match parse_fixture(&body, format) {
+        Ok(tables) => tables,
+        Err(message) => return error_response(&format!("Invalid fixture: {}", message)),
+    };
+
+    let result = async {
+        let mut schemas: Vec<TableSchema> = Vec::with_capacity(tables.len());
+        for table in &tables {
+            let schema = database.get_table_schema(&table.table).await?;
+            if schemas.iter().any(|other| other.name == schema.name) {
+                return Err(DatabaseError::Query(format!(
+                    "Invalid fixture: table '{}' appears more than once",
+                    schema.name
+                )));
+            }
+            schemas.push(schema);
+        }
+
+        let order = load_order(&schemas).map_err(|cycle| {
+            DatabaseError::Query(format!(
+                "Invalid fixture: tables {} reference each other and cannot be loaded in order",
+                cycle.join(", ")
+            ))
+        })?;
+        let ordered: Vec<FixtureTable> = order
+            .into_iter()
+            .map(|index| FixtureTable {
+                table: schemas[index].name.clone(),
+                rows: tables[index].rows.clone(),
+            })
+            .collect();
+
+        database.replace_rows(&ordered).await
+    }
+    .await;
+
+    match result {
+        Ok(loaded) => (StatusCode::OK, Json(loaded)).into_response(),
+        Err(error) => {
+            eprintln!("Failed to load fixture: {}", error);
+            error_response(&error.to_string())
+        }
+    }
+}
+
+/// Read all rows of a table, sorted by primary key and without the row
+/// identifier column added for tables addressed by rowid or ctid
+async fn dump_table<DB: DatabaseProvider>(
+    database: &DB,
+    schema: &TableSchema,
+) -> Result<FixtureTable, DatabaseError> {
+    let identifier_column = schema.row_address.identifier_column();
+    let sort_by = schema
+        .primary_key
+        .as_ref()
+        .and_then(|columns| columns.first())
+        .cloned();
+
+    let mut rows = Vec::new();
+    loop {
+        let page = database
+            .get_rows(
+                &schema.name,
+                RowQuery {
+                    offset: rows.len() as u64,
+                    limit: FIXTURE_PAGE_SIZE,
+                    sort_order: sort_by.as_ref().map(|_| SortOrder::Ascending),
+                    sort_by: sort_by.clone(),
+                    nulls: None,
+                    filters: Default::default(),
+                    ranges: Vec::new(),
+                    filter_groups: Vec::new(),
+                    snapshot: None,
+                    cursor: None,
+                    // Pages end with `has_more`, so the rows need no count
+                    count: false,
+                    columns: None,
+                    case_insensitive: false,
+                    q: None,
+                },
+            )
+            .await?;
+        let done = !page.has_more || page.rows.is_empty();
+
+        for row in page.rows {
+            if let Value::Object(mut row) = row {
+                if let Some(column) = identifier_column {
+                    row.remove(column);
+                }
+                rows.push(row);
+            }
+        }
+        if rows.len() as u64 > MAX_FIXTURE_ROWS {
+            return Err(DatabaseError::Query(format!(
+                "Fixture too large: table '{}' has more than {} rows",
+                schema.name, MAX_FIXTURE_ROWS
+            )));
+        }
+        if done {
+            break;
+        }
+    }
+
+    Ok(FixtureTable {
+        table: schema.name.clone(),
+        rows,
+    })
+}
+
+/// Map a fixture error message to its response
+fn error_response(message: &str) -> Response {
+    let status = if message.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("Invalid") {
+        StatusCode::BAD_REQUEST
+    } else if message.contains("too large") {
+        StatusCode::PAYLOAD_TOO_LARGE
+    } else if message.contains("Not supported") {
+        StatusCode::NOT_IMPLEMENTED
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "error": message
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1,106 @@
+//! Application query inspection endpoint
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::DatabaseProvider;
+use crate::explain::{analyze_references, extract_plan_indexes};
+use crate::schema::{InspectQueryRequest, InspectQueryResponse};
+use crate::sql::{normalize, placeholders, replace_placeholders_with_null, split_statements};
+
+/// Handler for POST /api/query/inspect
+///
+/// Takes a query as written in the host application (including `?`, `$1` or
+/// `:name` placeholders), normalizes it, runs EXPLAIN with the placeholders
+/// replaced by NULL, and matches the identifiers it uses against the live schema.
+/// The query itself is never executed.
+///
+/// Request body:
+/// ```json
+/// {
+///   "sql": "SELECT * FROM users WHERE email = $1"
+/// }
+/// ```
+///
+/// Response:
+/// ```json
+/// {
+///   "normalizedSql": "SELECT * FROM users WHERE email = $1",
+///   "explainedSql": "SELECT * FROM users WHERE email = NULL",
+///   "placeholders": ["$1"],
+///   "plan": ["Index Scan using users_email_key on users  (cost=0.15..8.17 rows=1 width=72)"],
+///   "indexes": ["users_email_key"],
+///   "referencedTables": [{ "name": "users", "columns": ["email"] }],
+///   "unknownTables": [],
+///   "error": null
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `request` - JSON request containing the application query
+///
+/// # Returns
+///
+/// JSON response containing the normalized query, plan and schema references
+pub async fn inspect_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Json(request): Json<InspectQueryRequest>,
+) -> Response {
+    let dialect = database.dialect();
+
+    // Only a single statement can be explained safely
+    if split_statements(&request.sql, dialect).len() != 1 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Expected exactly one SQL statement"
+            })),
+        )
+            .into_response();
+    }
+
+    let normalized_sql = normalize(&request.sql, dialect);
+    let explained_sql = replace_placeholders_with_null(&normalized_sql, dialect);
+
+    let schemas = match database.get_all_table_schemas().await {
+        Ok(schemas) => schemas,
+        Err(error) => {
+            eprintln!("Failed to load schemas for query inspection: {}", error);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+    let (referenced_tables, unknown_tables) =
+        analyze_references(&normalized_sql, dialect, &schemas);
+
+    let (status, plan, error) = match database.explain_query(&explained_sql).await {
+        Ok(plan) => (StatusCode::OK, plan, None),
+        Err(error) => (StatusCode::BAD_REQUEST, Vec::new(), Some(error.to_string())),
+    };
+
+    (
+        status,
+        Json(InspectQueryResponse {
+            placeholders: placeholders(&normalized_sql, dialect),
+            indexes: extract_plan_indexes(&plan),
+            normalized_sql,
+            explained_sql,
+            plan,
+            referenced_tables,
+            unknown_tables,
+            error,
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,68 @@
+//! Background job endpoints
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+
+use crate::jobs::JobRegistry;
+
+/// Handler for GET /api/jobs
+///
+/// Lists running and recently finished jobs, most recent first.
+///
+/// # Arguments
+///
+/// * `jobs` - Job registry from state
+///
+/// # Returns
+///
+/// JSON response containing the jobs
+pub async fn list_jobs_handler(State(jobs): State<JobRegistry>) -> Response {
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "jobs": jobs.list()
+        })),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/jobs/:id
+///
+/// Returns the state of a job; poll until `status` is no longer "running".
+///
+/// Response:
+/// ```json
+/// {
+///   "id": 3,
+///   "kind": "seed.reset",
+///   "status": "succeeded",
+///   "startedAtMilliseconds": 1760000000000,
+///   "finishedAtMilliseconds": 1760000000420,
+///   "result": { "location": "app.db.seed", "exists": true },
+///   "error": null
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `jobs` - Job registry from state
+/// * `id` - Job identifier
+///
+/// # Returns
+///
+/// JSON response containing the job, or 404 if it is unknown or expired
+pub async fn get_job_handler(State(jobs): State<JobRegistry>, Path(id): Path<u64>) -> Response {
+    match jobs.get(id) {
+        Some(job) => (StatusCode::OK, Json(job)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("Job not found: {}", id)
+            })),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,103 @@
+//! Column lineage endpoint
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::lineage::{column_lineage, MAX_LINEAGE_DEPTH};
+use crate::schema::LineageQuery;
+use crate::schema_cache::SchemaCache;
+use crate::sql::resolve_table_name;
+
+/// Handler for GET /api/tables/:name/columns/:column/lineage
+///
+/// Reports the columns connected to a column through foreign keys: the columns
+/// it references (upstream) and the columns referencing it (downstream), each
+/// followed transitively for up to `depth` hops (default 3, at most 10).
+///
+/// Response:
+/// ```json
+/// {
+///   "table": "users",
+///   "column": "id",
+///   "depth": 3,
+///   "upstream": [],
+///   "downstream": [
+///     { "table": "orders", "column": "user_id", "depth": 1, "via": { "table": "users", "column": "id" } }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `schema_cache` - Cache answering the request if all schemas were loaded before
+/// * `table_name` - Name of the table
+/// * `column` - Name of the column
+/// * `query` - Number of hops to follow
+///
+/// # Returns
+///
+/// JSON response containing the upstream and downstream columns
+pub async fn column_lineage_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(schema_cache): State<SchemaCache>,
+    Path((table_name, column)): Path<(String, String)>,
+    Query(query): Query<LineageQuery>,
+) -> Response {
+    let schemas = match schema_cache.all_table_schemas(database.as_ref()).await {
+        Ok(schemas) => schemas,
+        Err(error) => {
+            eprintln!("Failed to load schemas for column lineage: {}", error);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let names: Vec<String> = schemas.iter().map(|schema| schema.name.clone()).collect();
+    let schema = match resolve_table_name(&table_name, &names) {
+        Ok(Some(name)) => schemas.iter().find(|schema| schema.name == name),
+        Ok(None) => None,
+        Err(candidates) => {
+            let error = DatabaseError::AmbiguousTable(table_name, candidates.join(", "));
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+    let Some(schema) = schema else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("Table not found: {}", table_name)
+            })),
+        )
+            .into_response();
+    };
+    if !schema.columns.iter().any(|info| info.name == column) {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("Column not found: {}", column)
+            })),
+        )
+            .into_response();
+    }
+
+    let depth = query.depth.clamp(1, MAX_LINEAGE_DEPTH);
+    let lineage = column_lineage(&schemas, &schema.name, &column, depth);
+    (StatusCode::OK, Json(lineage)).into_response()
+}
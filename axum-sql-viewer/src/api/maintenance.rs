@@ -0,0 +1,176 @@
+//! Maintenance endpoints for upkeep of development databases
+//!
+//! Only a fixed set of operations can be run, each as a job: rebuilding the
+//! database file or indexes can take a while and locks the tables involved.
+//! SQLite databases also report their journal mode and write-ahead log size.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::jobs::JobRegistry;
+use crate::schema::{MaintenanceOperation, MaintenanceQuery};
+
+/// Handler for GET /api/maintenance/journal
+///
+/// Reports the journal mode of a SQLite database and, in WAL mode, the size
+/// of its `-wal` file, which can grow large while many rows are written
+/// (e.g. when seeding) until a checkpoint truncates it.
+///
+/// Response:
+/// ```json
+/// {
+///   "journalMode": "wal",
+///   "wal": true,
+///   "walSize": 104857600,
+///   "walAutocheckpoint": 1000,
+///   "pageSize": 4096
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+///
+/// # Returns
+///
+/// JSON response containing the journal information
+pub async fn journal_info_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+) -> Response {
+    match database.journal_info().await {
+        Ok(journal) => (StatusCode::OK, Json(journal)).into_response(),
+        Err(error) => {
+            let status = match error {
+                DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            maintenance_error(status, error.to_string())
+        }
+    }
+}
+
+/// Handler for POST /api/maintenance/:operation
+///
+/// Starts a job running an upkeep operation on the tables listed in `tables`
+/// (comma-separated), or on the whole database when not set:
+///
+/// - `vacuum`: Reclaim the space of deleted rows. SQLite rebuilds the whole
+///   database file (`VACUUM`) regardless of `tables`; PostgreSQL runs
+///   `VACUUM (ANALYZE)`, also refreshing statistics.
+/// - `analyze`: Refresh the statistics the query planner works with
+///   (`ANALYZE`)
+/// - `reindex`: Rebuild indexes (`REINDEX`)
+/// - `checkpoint`: Move the pages of SQLite's write-ahead log into the database
+///   file and truncate the `-wal` file (`PRAGMA wal_checkpoint(TRUNCATE)`);
+///   SQLite only
+///
+/// Responds with 202 Accepted and the job, or 409 Conflict while another
+/// maintenance or seed job is running. Once it has succeeded, the job's result
+/// is:
+///
+/// ```json
+/// {
+///   "operation": "vacuum",
+///   "tables": [],
+///   "sizeBefore": 1048576,
+///   "sizeAfter": 524288,
+///   "executionTimeMilliseconds": 120
+/// }
+/// ```
+///
+/// A checkpoint's result also has a `checkpoint` object with `busy` (readers
+/// kept it from completing), `logFrames`, `checkpointedFrames`,
+/// `walSizeBefore` and `walSizeAfter`.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `jobs` - Job registry from state
+/// * `operation` - Name of the operation
+/// * `query` - Tables to run the operation on
+///
+/// # Returns
+///
+/// JSON response containing the started job
+pub async fn run_maintenance_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(jobs): State<JobRegistry>,
+    Path(operation): Path<String>,
+    Query(query): Query<MaintenanceQuery>,
+) -> Response {
+    let Some(operation) = MaintenanceOperation::from_name(&operation) else {
+        return maintenance_error(
+            StatusCode::NOT_FOUND,
+            format!(
+                "Unknown maintenance operation '{}'; \
+                 expected vacuum, analyze, reindex or checkpoint",
+                operation
+            ),
+        );
+    };
+
+    // Checked before starting the job, so a mistyped table gets an error status
+    let mut tables = Vec::new();
+    for table in query
+        .tables
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|table| !table.is_empty())
+    {
+        match database.get_table_schema(table).await {
+            Ok(schema) => tables.push(schema.name),
+            Err(error) => {
+                let status = match error {
+                    DatabaseError::TableNotFound(_) => StatusCode::NOT_FOUND,
+                    DatabaseError::AmbiguousTable(..) => StatusCode::BAD_REQUEST,
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                };
+                return maintenance_error(status, error.to_string());
+            }
+        }
+    }
+
+    // Rebuilding the database file must not overlap with replacing it
+    if jobs.is_running(|kind| kind.starts_with("maintenance.") || kind.starts_with("seed.")) {
+        return maintenance_error(
+            StatusCode::CONFLICT,
+            "Another maintenance or seed job is still running".to_string(),
+        );
+    }
+
+    let kind = format!("maintenance.{}", operation.name());
+    let job = jobs
+        .start(&kind, async move {
+            let result = database
+                .run_maintenance(operation, &tables)
+                .await
+                .map_err(|error| {
+                    eprintln!("Maintenance job failed: {}", error);
+                    error.to_string()
+                })?;
+            serde_json::to_value(result).map_err(|error| error.to_string())
+        })
+        .await;
+
+    (StatusCode::ACCEPTED, Json(job)).into_response()
+}
+
+/// Error response for a failed maintenance request
+fn maintenance_error(status: StatusCode, error: String) -> Response {
+    eprintln!("Maintenance request failed: {}", error);
+
+    (
+        status,
+        Json(serde_json::json!({
+            "error": error
+        })),
+    )
+        .into_response()
+}
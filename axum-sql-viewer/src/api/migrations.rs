@@ -0,0 +1,179 @@
+//! Migration endpoints
+//!
+//! Show the migrations recorded by sqlx and Diesel and, with a migrator
+//! configured, apply the pending ones (see [`crate::migrations`]).
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::jobs::JobRegistry;
+use crate::migrations::{read_migrations, Migrations, MIGRATOR_REQUIRED};
+use crate::schema::{MigrationRunResult, MigrationsResponse};
+use crate::schema_cache::SchemaCache;
+
+/// Handler for GET /api/migrations
+///
+/// Lists the migrations recorded by sqlx (`_sqlx_migrations`) and Diesel
+/// (`__diesel_schema_migrations`), by version. Fields only one tool records
+/// are null for the other; `histories` is empty if neither table exists.
+/// With a migrator configured, `runnable` is true and `pending` lists its
+/// migrations not applied yet.
+///
+/// Response:
+/// ```json
+/// {
+///   "histories": [
+///     {
+///       "table": "_sqlx_migrations",
+///       "tool": "sqlx",
+///       "migrations": [
+///         {
+///           "version": "20240101120000",
+///           "description": "create users",
+///           "installedOn": "2024-01-01 12:00:00",
+///           "success": true,
+///           "checksum": "9f86d081884c7d65...",
+///           "executionTimeMilliseconds": 12
+///         }
+///       ]
+///     }
+///   ],
+///   "runnable": true,
+///   "pending": [
+///     { "version": "20240201090000", "description": "add posts", "checksum": "60303ae2...", ... }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `migrations` - Configured migrator from state
+///
+/// # Returns
+///
+/// JSON response containing the migration histories and pending migrations
+pub async fn migrations_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(migrations): State<Migrations>,
+) -> Response {
+    match read_migrations(database.as_ref()).await {
+        Ok(histories) => {
+            let response = MigrationsResponse {
+                runnable: migrations.is_configured(),
+                pending: migrations.pending(&histories),
+                histories,
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(error) => migrations_error(error),
+    }
+}
+
+/// Handler for POST /api/migrations/run
+///
+/// Starts a job applying the pending migrations of the configured migrator,
+/// in order, like `sqlx migrate run`. Responds with 202 Accepted and the job,
+/// 501 Not Implemented without a migrator, or 409 Conflict while migrations,
+/// maintenance or a seed reset are running. Once it has succeeded, the job's
+/// result is:
+///
+/// ```json
+/// {
+///   "applied": [
+///     { "version": "20240201090000", "description": "add posts", ... }
+///   ],
+///   "executionTimeMilliseconds": 35
+/// }
+/// ```
+///
+/// A failing migration fails the job; migrations applied before it stay.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `jobs` - Job registry from state
+/// * `migrations` - Configured migrator from state
+/// * `schema_cache` - Cached schemas, which no longer match once migrations ran
+///
+/// # Returns
+///
+/// JSON response containing the started job
+pub async fn run_migrations_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(jobs): State<JobRegistry>,
+    State(migrations): State<Migrations>,
+    State(schema_cache): State<SchemaCache>,
+) -> Response {
+    if !migrations.is_configured() {
+        return migrations_error(DatabaseError::Unsupported(MIGRATOR_REQUIRED.to_string()));
+    }
+
+    let conflicting = |kind: &str| {
+        kind.starts_with("migrations.")
+            || kind.starts_with("maintenance.")
+            || kind.starts_with("seed.")
+    };
+    if jobs.is_running(conflicting) {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "Another migration, maintenance or seed job is still running"
+            })),
+        )
+            .into_response();
+    }
+
+    let job = jobs
+        .start("migrations.run", async move {
+            let result = async {
+                let pending = migrations.pending(&read_migrations(database.as_ref()).await?);
+                let start_time = Instant::now();
+                let outcome = migrations.run().await;
+                schema_cache.clear();
+                outcome?;
+
+                let remaining = migrations.pending(&read_migrations(database.as_ref()).await?);
+                Ok::<_, DatabaseError>(MigrationRunResult {
+                    applied: pending
+                        .into_iter()
+                        .filter(|migration| !remaining.contains(migration))
+                        .collect(),
+                    execution_time_milliseconds: start_time.elapsed().as_millis() as u64,
+                })
+            }
+            .await
+            .map_err(|error| {
+                eprintln!("Migration job failed: {}", error);
+                error.to_string()
+            })?;
+            serde_json::to_value(result).map_err(|error| error.to_string())
+        })
+        .await;
+
+    (StatusCode::ACCEPTED, Json(job)).into_response()
+}
+
+/// Error response for a failed migrations request
+fn migrations_error(error: DatabaseError) -> Response {
+    eprintln!("Migrations request failed: {}", error);
+
+    let status = match error {
+        DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "error": error.to_string()
+        })),
+    )
+        .into_response()
+}
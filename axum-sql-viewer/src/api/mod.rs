@@ -2,19 +2,333 @@
 //!
 //! This module contains all API endpoint handlers for the SQL viewer.
 
-use axum::Router;
+use axum::{
+    extract::FromRef,
+    middleware,
+    routing::{delete, get, post, put},
+    Router,
+};
 use std::sync::Arc;
 
+use crate::anonymize::AnonymizationRules;
+use crate::artifacts::ArtifactStore;
+use crate::confirmations::Confirmations;
+use crate::console::ConsoleHistory;
+use crate::cost_limits::CostLimits;
+use crate::cursors::ResultCursors;
 use crate::database::traits::DatabaseProvider;
+use crate::export_limits::ExportGuardrails;
+use crate::jobs::JobRegistry;
+use crate::labels::DisplayColumns;
+use crate::migrations::Migrations;
+use crate::notifications::NotificationEvents;
+use crate::permalinks::Permalinks;
+use crate::profile::Profiler;
+use crate::saved_queries::SavedQueries;
+use crate::schema_cache::SchemaCache;
+use crate::schema_watch::SchemaEvents;
 
+pub mod activity;
+pub mod attachments;
+pub mod codegen;
+pub mod commands;
+pub mod config;
+pub mod console;
+pub mod diagram;
+pub mod dump;
+pub mod events;
+pub mod explain;
+pub mod exports;
+pub mod fixtures;
+pub mod inspect;
+pub mod jobs;
+pub mod lineage;
+pub mod maintenance;
+pub mod migrations;
+pub mod permalinks;
+pub mod profile;
 pub mod query;
+pub mod resolve;
 pub mod rows;
+pub mod saved_queries;
+pub mod schema_search;
+pub mod seed;
+pub mod snapshots;
+#[cfg(feature = "query-log")]
+pub mod statements;
 pub mod tables;
+pub mod transactions;
+pub mod version;
 
 // Re-export handlers for convenience
-pub use query::execute_query_handler;
-pub use rows::{count_rows_handler, get_rows_handler};
-pub use tables::{get_table_schema_handler, list_tables_handler};
+pub use activity::activity_handler;
+pub use attachments::{attach_database_handler, detach_database_handler, list_attachments_handler};
+pub use codegen::{codegen_handler, json_schema_handler};
+pub use commands::list_commands_handler;
+pub use config::config_handler;
+pub use console::{
+    close_session_handler, open_session_handler, session_history_handler, session_query_handler,
+};
+pub use diagram::{erd_mermaid_handler, relationship_graph_handler};
+pub use dump::dump_handler;
+pub use events::{events_handler, notifications_handler};
+pub use explain::explain_handler;
+pub use exports::{download_export_handler, export_table_handler, start_export_handler};
+pub use fixtures::{download_fixture_handler, load_fixture_handler};
+pub use inspect::inspect_query_handler;
+pub use jobs::{get_job_handler, list_jobs_handler};
+pub use lineage::column_lineage_handler;
+pub use maintenance::{journal_info_handler, run_maintenance_handler};
+pub use migrations::{migrations_handler, run_migrations_handler};
+pub use permalinks::{create_permalink_handler, get_permalink_handler};
+pub use profile::table_profile_handler;
+pub use query::{
+    cancel_query_handler, close_result_handler, execute_query_handler, execute_script_handler,
+    format_query_handler, result_rows_handler,
+};
+pub use resolve::resolve_row_handler;
+pub use rows::{
+    count_rows_handler, delete_row_by_query_handler, delete_rows_handler, find_duplicates_handler,
+    get_referencing_rows_handler, get_related_rows_handler, get_row_by_query_handler,
+    get_row_handler, get_rows_handler, insert_rows_handler, query_rows_handler,
+    sample_rows_handler, table_checksum_handler, update_row_by_query_handler, update_row_handler,
+};
+pub use saved_queries::{
+    create_saved_query_handler, delete_saved_query_handler, list_saved_queries_handler,
+    update_saved_query_handler,
+};
+pub use schema_search::search_schema_handler;
+pub use seed::{create_seed_handler, reset_to_seed_handler, seed_status_handler};
+pub use snapshots::{
+    create_snapshot_handler, delete_named_snapshot_handler, list_named_snapshots_handler,
+    named_snapshot_diff_handler, release_snapshot_handler, restore_named_snapshot_handler,
+    save_named_snapshot_handler,
+};
+#[cfg(feature = "query-log")]
+pub use statements::create_statements_router;
+pub use tables::{
+    column_histogram_handler, column_stats_handler, get_table_schema_handler,
+    invalidate_cache_handler, list_tables_handler,
+};
+pub use transactions::{
+    begin_transaction_handler, commit_transaction_handler, rollback_transaction_handler,
+};
+pub use version::{API_VERSION, SUPPORTED_API_VERSIONS};
+
+/// State shared by the API handlers
+///
+/// Handlers extract the parts they need (`State<Arc<DB>>`, `State<JobRegistry>`,
+/// `State<ArtifactStore>`, `State<ExportGuardrails>`, `State<AnonymizationRules>`,
+/// `State<DisplayColumns>`, `State<SchemaCache>`,
+/// `State<ConsoleHistory>`, `State<SchemaEvents>`, `State<Confirmations>`,
+/// `State<ResultCursors>`, `State<SavedQueries>`, `State<Permalinks>`,
+/// `State<CostLimits>`, `State<NotificationEvents>`, `State<Profiler>`).
+pub struct ViewerState<DB: DatabaseProvider> {
+    /// Database provider
+    pub database: Arc<DB>,
+
+    /// Background jobs started through the API
+    pub jobs: JobRegistry,
+
+    /// Files produced by export jobs
+    pub exports: ArtifactStore,
+
+    /// Size limits of exports, by table
+    pub export_limits: ExportGuardrails,
+
+    /// Anonymization applied to exported values
+    pub anonymization: AnonymizationRules,
+
+    /// Configured display columns for labelling rows
+    pub display_columns: DisplayColumns,
+
+    /// Cached table list and schemas (disabled unless prefetching or a TTL is configured)
+    pub schema_cache: SchemaCache,
+
+    /// History of the open console sessions
+    pub console: ConsoleHistory,
+
+    /// Schema changes pushed to `/api/events` listeners
+    pub schema_events: SchemaEvents,
+
+    /// Database notifications pushed to `/api/notifications` listeners
+    pub notifications: NotificationEvents,
+
+    /// Destructive statements waiting to be confirmed
+    pub confirmations: Confirmations,
+
+    /// Query results that can be paged through
+    pub cursors: ResultCursors,
+
+    /// Queries saved from the console
+    pub saved_queries: SavedQueries,
+
+    /// Views shared by permalink
+    pub permalinks: Permalinks,
+
+    /// Limits on the estimated cost of console queries
+    pub cost_limits: CostLimits,
+
+    /// Migrations of the host application that can be applied
+    pub migrations: Migrations,
+
+    /// Cached data profiles of tables (disabled unless profiling is enabled)
+    pub profiler: Profiler,
+}
+
+impl<DB: DatabaseProvider> ViewerState<DB> {
+    /// Create the state for `database` with default export retention, no
+    /// export limits or anonymization, guessed display columns, no schema cache,
+    /// confirmation of destructive statements, saved queries kept in memory, no
+    /// cost limits, no migrations to apply and profiling disabled
+    pub fn new(database: Arc<DB>) -> Self {
+        Self {
+            database,
+            jobs: JobRegistry::new(),
+            exports: ArtifactStore::default(),
+            export_limits: ExportGuardrails::default(),
+            anonymization: AnonymizationRules::default(),
+            display_columns: DisplayColumns::default(),
+            schema_cache: SchemaCache::disabled(),
+            console: ConsoleHistory::default(),
+            schema_events: SchemaEvents::default(),
+            notifications: NotificationEvents::default(),
+            confirmations: Confirmations::default(),
+            cursors: ResultCursors::default(),
+            saved_queries: SavedQueries::default(),
+            permalinks: Permalinks::default(),
+            cost_limits: CostLimits::default(),
+            migrations: Migrations::default(),
+            profiler: Profiler::disabled(),
+        }
+    }
+}
+
+impl<DB: DatabaseProvider> Clone for ViewerState<DB> {
+    fn clone(&self) -> Self {
+        Self {
+            database: self.database.clone(),
+            jobs: self.jobs.clone(),
+            exports: self.exports.clone(),
+            export_limits: self.export_limits.clone(),
+            anonymization: self.anonymization.clone(),
+            display_columns: self.display_columns.clone(),
+            schema_cache: self.schema_cache.clone(),
+            console: self.console.clone(),
+            schema_events: self.schema_events.clone(),
+            notifications: self.notifications.clone(),
+            confirmations: self.confirmations.clone(),
+            cursors: self.cursors.clone(),
+            saved_queries: self.saved_queries.clone(),
+            permalinks: self.permalinks.clone(),
+            cost_limits: self.cost_limits,
+            migrations: self.migrations.clone(),
+            profiler: self.profiler.clone(),
+        }
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for Arc<DB> {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.database.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for JobRegistry {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.jobs.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for ArtifactStore {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.exports.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for ExportGuardrails {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.export_limits.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for AnonymizationRules {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.anonymization.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for DisplayColumns {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.display_columns.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for Migrations {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.migrations.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for SchemaCache {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.schema_cache.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for ConsoleHistory {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.console.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for Confirmations {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.confirmations.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for ResultCursors {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.cursors.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for SavedQueries {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.saved_queries.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for Permalinks {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.permalinks.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for CostLimits {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.cost_limits
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for SchemaEvents {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.schema_events.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for NotificationEvents {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.notifications.clone()
+    }
+}
+
+impl<DB: DatabaseProvider> FromRef<ViewerState<DB>> for Profiler {
+    fn from_ref(state: &ViewerState<DB>) -> Self {
+        state.profiler.clone()
+    }
+}
 
 /// Create the API router with all endpoints
 ///
@@ -28,11 +342,226 @@ pub use tables::{get_table_schema_handler, list_tables_handler};
 ///
 /// An Axum Router configured with all API routes
 pub fn create_api_router<DB: DatabaseProvider>(database: Arc<DB>) -> Router {
+    create_api_router_with_state(ViewerState::new(database))
+}
+
+/// Create the API router with all endpoints for an existing state
+///
+/// # Arguments
+///
+/// * `state` - Database provider, job registry and export artifact store
+///
+/// # Returns
+///
+/// An Axum Router configured with all API routes
+pub fn create_api_router_with_state<DB: DatabaseProvider>(state: ViewerState<DB>) -> Router {
+    // Note: Axum 0.8 uses {param} syntax instead of :param
     Router::new()
-        .route("/tables", axum::routing::get(tables::list_tables_handler::<DB>))
-        .route("/tables/:name", axum::routing::get(tables::get_table_schema_handler::<DB>))
-        .route("/tables/:name/rows", axum::routing::get(rows::get_rows_handler::<DB>))
-        .route("/tables/:name/count", axum::routing::get(rows::count_rows_handler::<DB>))
-        .route("/query", axum::routing::post(query::execute_query_handler::<DB>))
-        .with_state(database)
+        .route("/tables", get(tables::list_tables_handler::<DB>))
+        .route("/search", get(schema_search::search_schema_handler::<DB>))
+        .route(
+            "/tables/{name}",
+            get(tables::get_table_schema_handler::<DB>),
+        )
+        .route("/cache/invalidate", post(tables::invalidate_cache_handler))
+        .route(
+            "/tables/{name}/rows",
+            get(rows::get_rows_handler::<DB>).post(rows::query_rows_handler::<DB>),
+        )
+        .route("/tables/{name}/count", get(rows::count_rows_handler::<DB>))
+        .route(
+            "/tables/{name}/checksum",
+            get(rows::table_checksum_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/sample",
+            get(rows::sample_rows_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/duplicates",
+            get(rows::find_duplicates_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/profile",
+            get(profile::table_profile_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/columns/{column}/stats",
+            get(tables::column_stats_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/columns/{column}/histogram",
+            get(tables::column_histogram_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/rows/{key}",
+            get(rows::get_row_handler::<DB>).patch(rows::update_row_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/rows/{key}/related",
+            get(rows::get_related_rows_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/rows/{key}/referenced-by",
+            get(rows::get_referencing_rows_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/row",
+            get(rows::get_row_by_query_handler::<DB>)
+                .patch(rows::update_row_by_query_handler::<DB>)
+                .delete(rows::delete_row_by_query_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/rows:bulk",
+            post(rows::insert_rows_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/delete",
+            post(rows::delete_rows_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/json-schema",
+            get(codegen::json_schema_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/codegen",
+            get(codegen::codegen_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/columns/{column}/lineage",
+            get(lineage::column_lineage_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/export",
+            get(exports::export_table_handler::<DB>),
+        )
+        .route(
+            "/tables/{name}/exports",
+            post(exports::start_export_handler::<DB>),
+        )
+        .route("/exports/{id}", get(exports::download_export_handler))
+        .route("/query", post(query::execute_query_handler::<DB>))
+        .route("/query/inspect", post(inspect::inspect_query_handler::<DB>))
+        .route("/query/format", post(query::format_query_handler::<DB>))
+        .route("/query/script", post(query::execute_script_handler::<DB>))
+        .route(
+            "/query/{id}/cancel",
+            post(query::cancel_query_handler::<DB>),
+        )
+        .route(
+            "/query/{id}/rows",
+            get(query::result_rows_handler).delete(query::close_result_handler),
+        )
+        .route("/explain", post(explain::explain_handler::<DB>))
+        .route(
+            "/saved-queries",
+            get(saved_queries::list_saved_queries_handler::<DB>)
+                .post(saved_queries::create_saved_query_handler::<DB>),
+        )
+        .route(
+            "/saved-queries/{id}",
+            put(saved_queries::update_saved_query_handler::<DB>)
+                .delete(saved_queries::delete_saved_query_handler::<DB>),
+        )
+        .route("/permalinks", post(permalinks::create_permalink_handler))
+        .route(
+            "/permalinks/{token}",
+            get(permalinks::get_permalink_handler),
+        )
+        .route(
+            "/console/sessions",
+            post(console::open_session_handler::<DB>),
+        )
+        .route(
+            "/console/sessions/{session}",
+            delete(console::close_session_handler::<DB>),
+        )
+        .route(
+            "/console/sessions/{session}/query",
+            post(console::session_query_handler::<DB>),
+        )
+        .route(
+            "/console/sessions/{session}/history",
+            get(console::session_history_handler),
+        )
+        .route(
+            "/tx/begin",
+            post(transactions::begin_transaction_handler::<DB>),
+        )
+        .route("/tx/{tx}/query", post(console::session_query_handler::<DB>))
+        .route(
+            "/tx/{tx}/commit",
+            post(transactions::commit_transaction_handler::<DB>),
+        )
+        .route(
+            "/tx/{tx}/rollback",
+            post(transactions::rollback_transaction_handler::<DB>),
+        )
+        .route("/erd.mmd", get(diagram::erd_mermaid_handler::<DB>))
+        .route("/graph", get(diagram::relationship_graph_handler::<DB>))
+        .route("/snapshots", post(snapshots::create_snapshot_handler::<DB>))
+        .route(
+            "/snapshots/{snapshot}",
+            delete(snapshots::release_snapshot_handler::<DB>),
+        )
+        .route(
+            "/snapshots/named",
+            get(snapshots::list_named_snapshots_handler::<DB>)
+                .post(snapshots::save_named_snapshot_handler::<DB>),
+        )
+        .route(
+            "/snapshots/named/{name}",
+            delete(snapshots::delete_named_snapshot_handler::<DB>),
+        )
+        .route(
+            "/snapshots/named/{name}/restore",
+            post(snapshots::restore_named_snapshot_handler::<DB>),
+        )
+        .route(
+            "/snapshots/named/{name}/diff",
+            get(snapshots::named_snapshot_diff_handler::<DB>),
+        )
+        .route("/config", get(config::config_handler::<DB>))
+        .route("/commands", get(commands::list_commands_handler::<DB>))
+        .route("/resolve", get(resolve::resolve_row_handler::<DB>))
+        .route(
+            "/seed",
+            get(seed::seed_status_handler::<DB>).post(seed::create_seed_handler::<DB>),
+        )
+        .route("/seed/reset", post(seed::reset_to_seed_handler::<DB>))
+        .route(
+            "/maintenance/journal",
+            get(maintenance::journal_info_handler::<DB>),
+        )
+        .route(
+            "/maintenance/{operation}",
+            post(maintenance::run_maintenance_handler::<DB>),
+        )
+        .route("/migrations", get(migrations::migrations_handler::<DB>))
+        .route(
+            "/migrations/run",
+            post(migrations::run_migrations_handler::<DB>),
+        )
+        .route(
+            "/fixtures",
+            get(fixtures::download_fixture_handler::<DB>)
+                .post(fixtures::load_fixture_handler::<DB>),
+        )
+        .route("/dump", get(dump::dump_handler::<DB>))
+        .route(
+            "/attachments",
+            get(attachments::list_attachments_handler::<DB>)
+                .post(attachments::attach_database_handler::<DB>),
+        )
+        .route(
+            "/attachments/{alias}",
+            delete(attachments::detach_database_handler::<DB>),
+        )
+        .route("/activity", get(activity::activity_handler::<DB>))
+        .route("/jobs", get(jobs::list_jobs_handler))
+        .route("/jobs/{id}", get(jobs::get_job_handler))
+        .route("/events", get(events::events_handler))
+        .route("/notifications", get(events::notifications_handler))
+        .layer(middleware::from_fn(version::negotiate_api_version))
+        .with_state(state)
 }
@@ -0,0 +1,94 @@
+//! Permalink endpoints
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+
+use crate::permalinks::{PermalinkResponse, Permalinks, SharedView};
+
+/// Handler for POST /api/permalinks
+///
+/// Keeps a view under a short token that `GET /api/permalinks/:token` turns
+/// back into the view. A view is either a table with its filters, sort and
+/// search text, or a query with its parameters.
+///
+/// Request body:
+/// ```json
+/// {
+///   "type": "table",
+///   "table": "orders",
+///   "filters": { "status": "open" },
+///   "sortBy": "created_at",
+///   "sortOrder": "descending"
+/// }
+/// ```
+/// or
+/// ```json
+/// { "type": "query", "sql": "SELECT * FROM orders WHERE user_id = ?", "parameters": [42] }
+/// ```
+///
+/// Response:
+/// ```json
+/// { "token": "3kTMd2pQx7a1", "view": { "type": "table", "table": "orders", ... } }
+/// ```
+///
+/// # Arguments
+///
+/// * `permalinks` - Shared views from state
+/// * `view` - JSON request containing the view to share
+///
+/// # Returns
+///
+/// JSON response containing the token and the view, status 201
+pub async fn create_permalink_handler(
+    State(permalinks): State<Permalinks>,
+    Json(view): Json<SharedView>,
+) -> Response {
+    match permalinks.share(view.clone()) {
+        Ok(token) => (StatusCode::CREATED, Json(PermalinkResponse { token, view })).into_response(),
+        Err(error) => {
+            eprintln!("Failed to share view: {}", error);
+            let status = if error.contains("too large") {
+                StatusCode::PAYLOAD_TOO_LARGE
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/permalinks/:token
+///
+/// # Arguments
+///
+/// * `permalinks` - Shared views from state
+/// * `token` - Token returned when the view was shared
+///
+/// # Returns
+///
+/// JSON response containing the token and the view, or 404 if the token is
+/// unknown or the view was dropped
+pub async fn get_permalink_handler(
+    State(permalinks): State<Permalinks>,
+    Path(token): Path<String>,
+) -> Response {
+    match permalinks.resolve(&token) {
+        Some(view) => (StatusCode::OK, Json(PermalinkResponse { token, view })).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("Permalink not found: {}", token)
+            })),
+        )
+            .into_response(),
+    }
+}
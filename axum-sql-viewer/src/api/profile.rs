@@ -0,0 +1,90 @@
+//! Data profile endpoint
+//!
+//! Profiles describe what a table's columns hold, computed from a sample of
+//! its rows and cached (see [`crate::profile`]).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::profile::Profiler;
+use crate::schema::ProfileQuery;
+
+/// Handler for GET /api/tables/:name/profile
+///
+/// Returns a profile of each column of a table, computed from a random
+/// sample of up to 1000 rows: the share of NULLs, which kinds of values occur
+/// (numbers and timestamps stored as text are recognized) and how consistent
+/// they are, the most frequent values and the lengths of text values.
+/// Profiles are cached until the configured time to live has passed.
+///
+/// Query parameters:
+/// - refresh: Compute a new profile even if a cached one exists (default: false)
+///
+/// Response:
+/// ```json
+/// {
+///   "table": "users",
+///   "sampledRows": 1000,
+///   "method": "tableSample",
+///   "profiledAtMilliseconds": 1718000000000,
+///   "columns": [{
+///     "column": "age",
+///     "dataType": "TEXT",
+///     "nullCount": 20,
+///     "nullRatio": 0.02,
+///     "valueKinds": { "integer": 975, "text": 5 },
+///     "dominantKind": "integer",
+///     "consistency": 0.9949,
+///     "topValues": [{ "value": "34", "count": 41 }],
+///     "lengths": { "min": 1, "max": 7, "average": 2.01, "median": 2, "p90": 2 }
+///   }]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `profiler` - Cached profiles from state
+/// * `table_name` - Name of the table
+/// * `query` - Whether to refresh the profile
+///
+/// # Returns
+///
+/// JSON response containing the profile, 404 if the table does not exist or
+/// 501 if profiling is not enabled or the database has no samples
+pub async fn table_profile_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(profiler): State<Profiler>,
+    Path(table_name): Path<String>,
+    Query(query): Query<ProfileQuery>,
+) -> Response {
+    match profiler
+        .profile(database.as_ref(), &table_name, query.refresh)
+        .await
+    {
+        Ok(profile) => (StatusCode::OK, Json(profile)).into_response(),
+        Err(error) => {
+            eprintln!("Failed to profile table '{}': {}", table_name, error);
+
+            let status = match error {
+                DatabaseError::TableNotFound(_) => StatusCode::NOT_FOUND,
+                DatabaseError::AmbiguousTable(..) => StatusCode::BAD_REQUEST,
+                DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,14 +1,32 @@
 //! Raw SQL query execution endpoint
 
 use axum::{
-    extract::State,
-    http::StatusCode,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
     response::{IntoResponse, Json, Response},
 };
+use futures_util::StreamExt;
+use serde_json::Value;
 use std::sync::Arc;
+use std::time::Instant;
 
-use crate::database::traits::DatabaseProvider;
-use crate::schema::{QueryRequest, QueryResult};
+use crate::classify::{classify, DestructiveKind, StatementKind};
+use crate::confirmations::{Confirmations, CONFIRMATION_TIMEOUT};
+use crate::cost_limits::{check_cost, CostLimits};
+use crate::cursors::{ResultCursor, ResultCursors, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::export::{ExportEncoder, ExportOptions};
+use crate::format::format_sql;
+use crate::json_body::streamed_rows;
+use crate::schema::{
+    ConfirmationRequired, ExportFormat, FormatQueryRequest, FormatQueryResponse, QueryRequest,
+    QueryResult, QueryStreamLine, ResultRowsQuery, ScriptRequest,
+};
+use crate::schema_cache::SchemaCache;
+use crate::schema_watch::{SchemaChange, SchemaEvents};
+use crate::sql::split_statements;
+use crate::validate::validate;
 
 /// Handler for POST /api/query
 ///
@@ -26,6 +44,117 @@ use crate::schema::{QueryRequest, QueryResult};
 /// }
 /// ```
 ///
+/// Values can be bound to placeholders (`?` on SQLite, `$1`, `$2`, ... on
+/// PostgreSQL) instead of being pasted into the SQL, in the order of `params`.
+/// Strings are bound as text; on PostgreSQL, cast placeholders that need
+/// another type (`$1::date`):
+/// ```json
+/// {
+///   "sql": "SELECT * FROM users WHERE email = ? AND active = ?",
+///   "params": ["alice@example.com", true]
+/// }
+/// ```
+///
+/// `params` can also be an object of named parameters, which are written as
+/// `:name` (or `@name`) and may be used several times:
+/// ```json
+/// {
+///   "sql": "SELECT * FROM posts WHERE author = :user OR reviewer = :user",
+///   "params": { "user": 42 }
+/// }
+/// ```
+///
+/// A query sent with an `id` of the client's choice (letters, digits, `-`,
+/// `_` and `.`) can be stopped while it runs with
+/// `POST /api/query/:id/cancel`; it then fails with the database's
+/// cancellation error.
+///
+/// `DELETE`, `UPDATE`, `DROP`, `TRUNCATE`, `MERGE`, `INSERT ... SELECT` and
+/// queries with a data-modifying CTE (`WITH d AS (DELETE ...) SELECT ...`)
+/// are not run right away (unless the layer was built with
+/// `with_destructive_confirmation(false)`). They get a response with status
+/// 428 telling what the statement would do, with the rows it affects found by
+/// running it in a transaction that is rolled back:
+/// ```json
+/// {
+///   "error": "Confirmation required: DELETE would affect 1204 rows",
+///   "statementKind": "delete",
+///   "estimatedAffectedRows": 1204,
+///   "confirmation": "9f86d081884c7d65-3",
+///   "expiresInSeconds": 300
+/// }
+/// ```
+/// Sending the same `sql` and `params` again with
+/// `"confirmation": "9f86d081884c7d65-3"` runs the statement. A token can
+/// only be used once.
+///
+/// With `"stream": true` the result is sent as `application/x-ndjson` while
+/// the rows are read, without the row limit and without holding the result in
+/// memory. Each line is a JSON object with a `type`: the column names, one
+/// line per row, and finally the row count and time, or an error if reading
+/// failed part way through (the status is already 200 by then). `id` is
+/// ignored; closing the connection stops the query.
+/// ```text
+/// {"type":"columns","columns":["id","name"]}
+/// {"type":"row","row":{"id":1,"name":"Alice"}}
+/// {"type":"row","row":{"id":2,"name":"Bob"}}
+/// {"type":"end","rowCount":2,"executionTimeMilliseconds":4}
+/// ```
+///
+/// With `"format": "csv"` (or `"json"`, `"ndjson"`, `"xlsx"`) the rows of a
+/// query are sent as a file to download, with a `Content-Disposition:
+/// attachment` header. Like a stream, the file is written while the rows are
+/// read and has no row limit; if reading fails part way through, the download
+/// is cut off. `id` and `pageSize` are ignored, and statements that return no
+/// rows are rejected with status 400.
+///
+/// With `"pageSize": 100` a query returning rows is answered with its first
+/// 100 rows only. The rest is read from the database when it is fetched with
+/// `GET /api/query/:id/rows`, using the `resultId` of the response (which is
+/// `null` when there are no more rows). Other statements ignore `pageSize`, and
+/// a paged query cannot be cancelled by `id`.
+/// ```json
+/// {
+///   "resultId": "result-3",
+///   "columns": ["id", "name"],
+///   "rows": [...],
+///   "offset": 0,
+///   "hasMore": true,
+///   "affectedRows": 0,
+///   "executionTimeMilliseconds": 3,
+///   "error": null
+/// }
+/// ```
+///
+/// With `"validateOnly": true` the statement is only checked, never run: it
+/// is parsed, and queries are also `EXPLAIN`ed by the database, which finds
+/// unknown tables and columns. Placeholders need no `params`. The response
+/// has status 200 whether or not the SQL is valid; `line` and `column` are
+/// set for syntax errors the parser found, and `explained` tells whether the
+/// database checked every statement:
+/// ```json
+/// {
+///   "valid": false,
+///   "explained": false,
+///   "error": "Expected: an expression, found: AND",
+///   "line": 2,
+///   "column": 18
+/// }
+/// ```
+///
+/// If the layer was built with `with_cost_limits`, queries and data changes
+/// are `EXPLAIN`ed first, and ones the planner expects to return more rows or
+/// cost more than allowed are rejected with status 422 without running:
+/// ```json
+/// {
+///   "columns": [],
+///   "rows": [],
+///   "affectedRows": 0,
+///   "executionTimeMilliseconds": 0,
+///   "error": "Query rejected: an estimated 2500000000 rows exceeds the limit of 1000000"
+/// }
+/// ```
+///
 /// Response (successful SELECT):
 /// ```json
 /// {
@@ -59,54 +188,851 @@ use crate::schema::{QueryRequest, QueryResult};
 /// }
 /// ```
 ///
+/// After a schema change (`CREATE`, `ALTER`, `DROP`, ...) runs, the schema
+/// cache is cleared, and tables it created or dropped are published as a
+/// `schemaChanged` event on `/api/events`, so the sidebar lists them without a
+/// manual refresh.
+///
+/// Timeouts respond with status 408 and tell whether no pool connection became
+/// free in time (`"acquire"`) or the statement ran too long (`"statement"`):
+/// ```json
+/// {
+///   "columns": [],
+///   "rows": [],
+///   "affectedRows": 0,
+///   "executionTimeMilliseconds": 0,
+///   "error": "Connection acquire timeout exceeded",
+///   "timeout": "acquire"
+/// }
+/// ```
+///
 /// # Arguments
 ///
 /// * `database` - Database provider from state
-/// * `request` - JSON request containing SQL query to execute
+/// * `confirmations` - Destructive statements waiting for confirmation, from state
+/// * `cursors` - Results that can be paged through, from state
+/// * `cost_limits` - Limits on the estimated cost of statements, from state
+/// * `schema_cache` - Cache cleared after schema changes, from state
+/// * `schema_events` - Listeners told about created and dropped tables, from state
+/// * `request` - JSON request containing SQL query to execute and its parameters
 ///
 /// # Returns
 ///
 /// JSON response containing query results or error information
 pub async fn execute_query_handler<DB: DatabaseProvider>(
     State(database): State<Arc<DB>>,
+    State(confirmations): State<Confirmations>,
+    State(cursors): State<ResultCursors>,
+    State(cost_limits): State<CostLimits>,
+    State(schema_cache): State<SchemaCache>,
+    State(schema_events): State<SchemaEvents>,
     Json(request): Json<QueryRequest>,
 ) -> Response {
     // Log the query execution attempt (be careful with sensitive data in production)
     eprintln!("Executing SQL query: {}", request.sql);
 
-    match database.execute_query(&request.sql).await {
-        Ok(result) => {
-            // Check if there was an error in the result
-            if result.error.is_some() {
-                // Query execution failed, return bad request
-                (StatusCode::BAD_REQUEST, Json(result)).into_response()
+    // Checking a statement runs nothing, so it needs neither parameters nor
+    // confirmation
+    if request.validate_only {
+        return match validate(database.as_ref(), &request.sql).await {
+            Ok(validation) => (StatusCode::OK, Json(validation)).into_response(),
+            Err(error) => query_error_response(error),
+        };
+    }
+
+    let (sql, params) = match request.params.bind(&request.sql, database.dialect()) {
+        Ok(bound) => bound,
+        Err(message) => {
+            eprintln!("Failed to bind query parameters: {}", message);
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(QueryResult {
+                    columns: vec![],
+                    rows: vec![],
+                    affected_rows: 0,
+                    execution_time_milliseconds: 0,
+                    error: Some(message),
+                    timeout: None,
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    // Checked before confirming, as estimating affected rows runs the statement
+    match check_cost(database.as_ref(), &cost_limits, &sql).await {
+        Ok(None) => {}
+        Ok(Some(message)) => return cost_exceeded_response(message),
+        Err(error) => return query_error_response(error),
+    }
+
+    if let Some(response) = check_confirmation(
+        database.as_ref(),
+        &confirmations,
+        &sql,
+        &params,
+        request.confirmation.as_deref(),
+        true,
+    )
+    .await
+    {
+        return response;
+    }
+
+    if let Some(format) = request.format {
+        return download_query_response(database.as_ref(), &sql, &params, format).await;
+    }
+
+    if request.stream {
+        return stream_query_response(database.as_ref(), &sql, &params).await;
+    }
+
+    // Only queries returning rows are paged; other statements run as usual
+    if let Some(page_size) = request.page_size {
+        if classify(&sql, database.dialect()).returns_rows {
+            return paged_query_response(database.as_ref(), &cursors, &sql, &params, page_size)
+                .await;
+        }
+    }
+
+    // Tables listed before a schema change, to tell which ones it created or dropped
+    let changes_schema = classify(&sql, database.dialect()).kind == StatementKind::Ddl;
+    let tables_before = if changes_schema {
+        table_names(database.as_ref(), &schema_cache).await
+    } else {
+        None
+    };
+
+    let result = match &request.id {
+        Some(id) => database.execute_cancellable_query(id, &sql, &params).await,
+        None => database.execute_query(&sql, &params).await,
+    };
+
+    if changes_schema && matches!(&result, Ok(result) if result.error.is_none()) {
+        schema_cache.clear();
+        if let Some(before) = tables_before {
+            publish_table_changes(database.as_ref(), &schema_cache, &schema_events, before).await;
+        }
+    }
+
+    match result {
+        Ok(mut result) => {
+            // Query execution failed if the result has an error
+            let status = if result.error.is_some() {
+                StatusCode::BAD_REQUEST
             } else {
-                // Query executed successfully
-                (StatusCode::OK, Json(result)).into_response()
+                StatusCode::OK
+            };
+            // Rows are written into the body while it is sent
+            let rows = std::mem::take(&mut result.rows);
+            streamed_rows(status, &result, rows)
+        }
+        Err(error) => query_error_response(error),
+    }
+}
+
+/// Names of all tables, from the schema cache if it has them
+async fn table_names<DB: DatabaseProvider>(
+    database: &DB,
+    schema_cache: &SchemaCache,
+) -> Option<Vec<String>> {
+    let tables = schema_cache.list_tables(database).await.ok()?;
+    Some(tables.into_iter().map(|table| table.name).collect())
+}
+
+/// Publish the tables created or dropped since `before` was listed
+///
+/// Altered tables keep their names, so they are not published; the schema
+/// cache is cleared for them all the same.
+async fn publish_table_changes<DB: DatabaseProvider>(
+    database: &DB,
+    schema_cache: &SchemaCache,
+    schema_events: &SchemaEvents,
+    before: Vec<String>,
+) {
+    let Some(after) = table_names(database, schema_cache).await else {
+        return;
+    };
+    let change = SchemaChange {
+        added: after
+            .iter()
+            .filter(|name| !before.contains(name))
+            .cloned()
+            .collect(),
+        removed: before
+            .iter()
+            .filter(|name| !after.contains(name))
+            .cloned()
+            .collect(),
+        changed: Vec::new(),
+    };
+    if !change.is_empty() {
+        schema_events.publish(change);
+    }
+}
+
+/// Response for a query that failed with a database error
+fn query_error_response(error: DatabaseError) -> Response {
+    eprintln!("Failed to execute query: {}", error);
+
+    // Return appropriate status code based on error type
+    let status = if error.to_string().contains("timeout") {
+        StatusCode::REQUEST_TIMEOUT
+    } else if error.to_string().contains("too large") || error.to_string().contains("TooManyRows") {
+        StatusCode::PAYLOAD_TOO_LARGE
+    } else if error.to_string().contains("Not supported") {
+        StatusCode::NOT_IMPLEMENTED
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+
+    // Return error as part of QueryResult structure
+    (
+        status,
+        Json(QueryResult {
+            columns: vec![],
+            rows: vec![],
+            affected_rows: 0,
+            execution_time_milliseconds: 0,
+            error: Some(error.to_string()),
+            timeout: error.timeout_kind(),
+        }),
+    )
+        .into_response()
+}
+
+/// Response for a statement rejected by the cost limits
+pub(crate) fn cost_exceeded_response(message: String) -> Response {
+    eprintln!("{}", message);
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(QueryResult {
+            columns: vec![],
+            rows: vec![],
+            affected_rows: 0,
+            execution_time_milliseconds: 0,
+            error: Some(message),
+            timeout: None,
+        }),
+    )
+        .into_response()
+}
+
+/// Response streaming the result of a query as NDJSON
+///
+/// The first line is awaited before responding, so a query that fails right
+/// away still gets an error status. Once the response has started, an error
+/// can only be reported as the last line.
+async fn stream_query_response<DB: DatabaseProvider>(
+    database: &DB,
+    sql: &str,
+    params: &[Value],
+) -> Response {
+    let start_time = Instant::now();
+    let mut rows = match database.stream_query(sql, params).await {
+        Ok(rows) => rows,
+        Err(error) => return query_error_response(error),
+    };
+    let first = match rows.next().await {
+        Some(Err(error)) => return query_error_response(error),
+        first => first,
+    };
+
+    let lines = futures_util::stream::iter(first).chain(rows);
+    let body = futures_util::stream::unfold(
+        (lines, 0u64, false),
+        move |(mut lines, mut row_count, finished)| async move {
+            if finished {
+                return None;
+            }
+
+            let (line, finished) = match lines.next().await {
+                Some(Ok(line)) => {
+                    if let QueryStreamLine::Row { .. } = line {
+                        row_count += 1;
+                    }
+                    (line, false)
+                }
+                Some(Err(error)) => {
+                    eprintln!("Failed to stream query result: {}", error);
+                    let error = error.to_string();
+                    (QueryStreamLine::Error { error }, true)
+                }
+                None => {
+                    let execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+                    let end = QueryStreamLine::End {
+                        row_count,
+                        execution_time_milliseconds,
+                    };
+                    (end, true)
+                }
+            };
+
+            let mut bytes = serde_json::to_vec(&line).unwrap_or_default();
+            bytes.push(b'\n');
+            Some((Ok::<_, std::io::Error>(bytes), (lines, row_count, finished)))
+        },
+    );
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+/// Response sending the rows of a query as a file to download
+///
+/// Like [`stream_query_response`], the first line is awaited before
+/// responding. An error after that can only end the body early.
+async fn download_query_response<DB: DatabaseProvider>(
+    database: &DB,
+    sql: &str,
+    params: &[Value],
+    format: ExportFormat,
+) -> Response {
+    let problem = if format == ExportFormat::Sql {
+        Some("Query results cannot be downloaded as SQL".to_string())
+    } else if !classify(sql, database.dialect()).returns_rows {
+        Some("Only queries returning rows can be downloaded".to_string())
+    } else {
+        None
+    };
+    if let Some(message) = problem {
+        eprintln!("Failed to download query result: {}", message);
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(QueryResult {
+                columns: vec![],
+                rows: vec![],
+                affected_rows: 0,
+                execution_time_milliseconds: 0,
+                error: Some(message),
+                timeout: None,
+            }),
+        )
+            .into_response();
+    }
+
+    let mut rows = match database.stream_query(sql, params).await {
+        Ok(rows) => rows,
+        Err(error) => return query_error_response(error),
+    };
+    let first = match rows.next().await {
+        Some(Err(error)) => return query_error_response(error),
+        first => first,
+    };
+
+    // The encoder is created once the column names are known; a result
+    // without rows has none
+    let dialect = database.dialect();
+    let encoder = move |columns: Vec<String>| {
+        ExportEncoder::new(format, "Query", columns, ExportOptions::default(), dialect)
+    };
+    let lines = futures_util::stream::iter(first).chain(rows);
+    let body = futures_util::stream::unfold(
+        (lines, None::<ExportEncoder>, false),
+        move |(mut lines, mut current, finished)| async move {
+            if finished {
+                return None;
+            }
+
+            let (bytes, finished) = match lines.next().await {
+                Some(Ok(QueryStreamLine::Columns { columns })) => {
+                    let mut started = encoder(columns);
+                    let bytes = started.start();
+                    current = Some(started);
+                    (Ok(bytes), false)
+                }
+                Some(Ok(QueryStreamLine::Row { row })) => {
+                    let current = current.get_or_insert_with(|| encoder(Vec::new()));
+                    (current.rows(&[row]), false)
+                }
+                Some(Ok(QueryStreamLine::Error { error })) => (Err(error), true),
+                Some(Ok(QueryStreamLine::End { .. })) | None => {
+                    let bytes = match current.as_mut() {
+                        Some(current) => current.finish(),
+                        None => {
+                            let mut empty = encoder(Vec::new());
+                            let mut bytes = empty.start();
+                            bytes.extend(empty.finish());
+                            bytes
+                        }
+                    };
+                    (Ok(bytes), true)
+                }
+                Some(Err(error)) => (Err(error.to_string()), true),
+            };
+
+            let bytes = bytes.map_err(|error| {
+                eprintln!("Failed to download query result: {}", error);
+                std::io::Error::other(error)
+            });
+            Some((bytes, (lines, current, finished)))
+        },
+    );
+
+    let file_name = format!("query-result.{}", format.extension());
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+/// Check that a destructive statement is confirmed before running it
+///
+/// Shared by every endpoint running raw SQL. Scripts and other text with
+/// several statements need confirming if any of them is destructive.
+///
+/// # Arguments
+///
+/// * `database` - Database the statement runs on
+/// * `confirmations` - Destructive statements waiting for confirmation
+/// * `sql` - Statement with placeholders, as it will run
+/// * `params` - Values bound to the placeholders
+/// * `token` - Confirmation sent with the request
+/// * `estimate` - Whether to find the affected rows with a dry run on the
+///   pool; not for sessions, whose open transaction it could not see (and
+///   whose locks it would wait for)
+///
+/// # Returns
+///
+/// `None` if the statement may run, or the response to send instead: status
+/// 428 with a confirmation token, or 400 if the dry run found the statement
+/// fails
+pub(crate) async fn check_confirmation<DB: DatabaseProvider>(
+    database: &DB,
+    confirmations: &Confirmations,
+    sql: &str,
+    params: &[Value],
+    token: Option<&str>,
+    estimate: bool,
+) -> Option<Response> {
+    if !confirmations.is_enabled() {
+        return None;
+    }
+
+    let statements = split_statements(sql, database.dialect());
+    let kind = statements
+        .iter()
+        .find_map(|statement| classify(statement, database.dialect()).destructive)?;
+    if token.is_some_and(|token| confirmations.confirm(token, sql, params)) {
+        return None;
+    }
+
+    // A dry run can only count the rows of a single statement
+    let estimate = estimate && statements.len() == 1;
+    Some(confirmation_required(database, confirmations, kind, sql, params, estimate).await)
+}
+
+/// Response asking to confirm a destructive statement before running it
+async fn confirmation_required<DB: DatabaseProvider>(
+    database: &DB,
+    confirmations: &Confirmations,
+    kind: DestructiveKind,
+    sql: &str,
+    params: &[Value],
+    estimate: bool,
+) -> Response {
+    // DROP and TRUNCATE report no affected rows, so they are not tried
+    let estimated_affected_rows = match kind {
+        DestructiveKind::Drop | DestructiveKind::Truncate => None,
+        _ if !estimate => None,
+        _ => {
+            match database.dry_run(sql, params).await {
+                Ok(rows) => Some(rows),
+                // A statement that fails now would fail when confirmed as well
+                Err(DatabaseError::Query(message)) => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(QueryResult {
+                            columns: vec![],
+                            rows: vec![],
+                            affected_rows: 0,
+                            execution_time_milliseconds: 0,
+                            error: Some(message),
+                            timeout: None,
+                        }),
+                    )
+                        .into_response();
+                }
+                Err(error) => {
+                    eprintln!("Failed to estimate affected rows: {}", error);
+                    None
+                }
             }
         }
+    };
+
+    let error = match estimated_affected_rows {
+        Some(rows) => format!(
+            "Confirmation required: {} would affect {} rows",
+            kind.keyword(),
+            rows
+        ),
+        None => format!("Confirmation required: {} statement", kind.keyword()),
+    };
+
+    (
+        StatusCode::PRECONDITION_REQUIRED,
+        Json(ConfirmationRequired {
+            error,
+            statement_kind: kind,
+            estimated_affected_rows,
+            confirmation: confirmations.issue(sql, params),
+            expires_in_seconds: CONFIRMATION_TIMEOUT.as_secs(),
+        }),
+    )
+        .into_response()
+}
+
+/// Handler for POST /api/query/format
+///
+/// Pretty-prints SQL using the formatting rules of the connected database's
+/// dialect. The SQL is never executed, so this also works for statements that
+/// would fail to run.
+///
+/// Request body:
+/// ```json
+/// {
+///   "sql": "select id, name from users where active = true order by name"
+/// }
+/// ```
+///
+/// Response:
+/// ```json
+/// {
+///   "sql": "SELECT\n  id,\n  name\nFROM\n  users\nWHERE\n  active = TRUE\nORDER BY\n  name"
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `request` - JSON request containing the SQL to format
+///
+/// # Returns
+///
+/// JSON response containing the formatted SQL
+pub async fn format_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Json(request): Json<FormatQueryRequest>,
+) -> Json<FormatQueryResponse> {
+    Json(FormatQueryResponse {
+        sql: format_sql(&request.sql, database.dialect()),
+    })
+}
+
+/// Handler for POST /api/query/script
+///
+/// Runs a script of statements separated by semicolons in one transaction.
+/// If a statement fails, the transaction is rolled back, nothing the script
+/// did is kept, and the response names the failing statement.
+///
+/// # Security Warning
+///
+/// Like `/api/query`, this executes ANY SQL statement. The script must not
+/// contain `BEGIN`, `COMMIT` or `ROLLBACK` itself.
+///
+/// A script with a destructive statement needs confirming like one sent to
+/// `/api/query`: the first request gets status 428 and a token (without an
+/// estimate of the affected rows), and sending the same `sql` again with
+/// `"confirmation"` set to the token runs it.
+///
+/// Request body:
+/// ```json
+/// {
+///   "sql": "UPDATE users SET active = false WHERE id = 7; DELETE FROM sessions WHERE user_id = 7;"
+/// }
+/// ```
+///
+/// Response (committed):
+/// ```json
+/// {
+///   "committed": true,
+///   "statements": [
+///     {
+///       "sql": "UPDATE users SET active = false WHERE id = 7",
+///       "affectedRows": 1,
+///       "executionTimeMilliseconds": 2
+///     },
+///     {
+///       "sql": "DELETE FROM sessions WHERE user_id = 7",
+///       "affectedRows": 3,
+///       "executionTimeMilliseconds": 1
+///     }
+///   ],
+///   "failedStatement": null,
+///   "executionTimeMilliseconds": 4
+/// }
+/// ```
+///
+/// Response (rolled back, status 400):
+/// ```json
+/// {
+///   "committed": false,
+///   "statements": [
+///     {
+///       "sql": "UPDATE users SET active = false WHERE id = 7",
+///       "affectedRows": 1,
+///       "executionTimeMilliseconds": 2
+///     }
+///   ],
+///   "failedStatement": {
+///     "index": 1,
+///     "sql": "DELETE FROM sesions WHERE user_id = 7",
+///     "error": "no such table: sesions"
+///   },
+///   "executionTimeMilliseconds": 3
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `confirmations` - Destructive statements waiting for confirmation, from state
+/// * `request` - JSON request containing the script
+///
+/// # Returns
+///
+/// JSON response describing the statements that ran and whether they were committed
+pub async fn execute_script_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(confirmations): State<Confirmations>,
+    Json(request): Json<ScriptRequest>,
+) -> Response {
+    let statements = split_statements(&request.sql, database.dialect());
+    if statements.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Invalid script: no statements"
+            })),
+        )
+            .into_response();
+    }
+
+    if let Some(response) = check_confirmation(
+        database.as_ref(),
+        &confirmations,
+        &request.sql,
+        &[],
+        request.confirmation.as_deref(),
+        false,
+    )
+    .await
+    {
+        return response;
+    }
+
+    eprintln!("Executing SQL script of {} statements", statements.len());
+
+    match database.execute_script(&statements).await {
+        Ok(result) if result.committed => (StatusCode::OK, Json(result)).into_response(),
+        Ok(result) => {
+            if let Some(failed) = &result.failed_statement {
+                eprintln!(
+                    "Rolled back script at statement {}: {}",
+                    failed.index + 1,
+                    failed.error
+                );
+            }
+            (StatusCode::BAD_REQUEST, Json(result)).into_response()
+        }
         Err(error) => {
-            eprintln!("Failed to execute query: {}", error);
+            eprintln!("Failed to execute script: {}", error);
 
-            // Return appropriate status code based on error type
-            let status = if error.to_string().contains("timeout") {
+            let status = if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else if error.to_string().contains("timeout") {
                 StatusCode::REQUEST_TIMEOUT
-            } else if error.to_string().contains("too large") || error.to_string().contains("TooManyRows") {
-                StatusCode::PAYLOAD_TOO_LARGE
             } else {
-                StatusCode::BAD_REQUEST
+                StatusCode::INTERNAL_SERVER_ERROR
             };
 
-            // Return error as part of QueryResult structure
             (
                 status,
-                Json(QueryResult {
-                    columns: vec![],
-                    rows: vec![],
-                    affected_rows: 0,
-                    execution_time_milliseconds: 0,
-                    error: Some(error.to_string()),
-                }),
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Response with the first page of a query, keeping the rest to be fetched later
+async fn paged_query_response<DB: DatabaseProvider>(
+    database: &DB,
+    cursors: &ResultCursors,
+    sql: &str,
+    params: &[Value],
+    page_size: usize,
+) -> Response {
+    let start_time = Instant::now();
+    let mut cursor = match database.stream_query(sql, params).await {
+        Ok(rows) => ResultCursor::new(rows),
+        Err(error) => return query_error_response(error),
+    };
+    let mut page = cursor.page(0, page_size.clamp(1, MAX_PAGE_SIZE)).await;
+    page.execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+
+    // A query that fails before its first row fails like an unpaged one
+    if page.error.is_some() && page.rows.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(page)).into_response();
+    }
+
+    if page.has_more {
+        page.result_id = Some(cursors.open(cursor));
+    }
+    (StatusCode::OK, Json(page)).into_response()
+}
+
+/// Handler for GET /api/query/:id/rows
+///
+/// Returns a page of a result opened by `/api/query` with a `pageSize`. Rows
+/// after the ones read so far are read from the database now; rows read
+/// before are kept, so any page can be fetched again. A result is dropped
+/// after five minutes without use, or when newer results push it out.
+///
+/// Query parameters: `offset` (default 0) and `limit` (default 100, at most
+/// 1000).
+///
+/// Response:
+/// ```json
+/// {
+///   "resultId": "result-3",
+///   "columns": ["id", "name"],
+///   "rows": [...],
+///   "offset": 100,
+///   "hasMore": false,
+///   "affectedRows": 0,
+///   "executionTimeMilliseconds": 6,
+///   "error": null
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `cursors` - Results that can be paged through, from state
+/// * `id` - Handle of the result
+/// * `query` - Position and size of the page
+///
+/// # Returns
+///
+/// JSON response containing the page, 404 if the result is unknown or was
+/// dropped, or 409 while another page of it is being read
+pub async fn result_rows_handler(
+    State(cursors): State<ResultCursors>,
+    Path(id): Path<String>,
+    Query(query): Query<ResultRowsQuery>,
+) -> Response {
+    let mut cursor = match cursors.acquire(&id) {
+        Ok(cursor) => cursor,
+        Err(message) => {
+            eprintln!("Failed to read result '{}': {}", id, message);
+
+            let status = if message.contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::CONFLICT
+            };
+            return (status, Json(serde_json::json!({ "error": message }))).into_response();
+        }
+    };
+
+    let start_time = Instant::now();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let mut page = cursor.page(query.offset, limit).await;
+    page.execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+    page.result_id = Some(id.clone());
+    cursors.release(&id, cursor);
+
+    (StatusCode::OK, Json(page)).into_response()
+}
+
+/// Handler for DELETE /api/query/:id/rows
+///
+/// Drops a result opened by `/api/query` with a `pageSize`, stopping its
+/// query and freeing its connection if not all rows were read yet.
+///
+/// # Arguments
+///
+/// * `cursors` - Results that can be paged through, from state
+/// * `id` - Handle of the result
+///
+/// # Returns
+///
+/// Empty response with status 204, or 404 if the result is unknown
+pub async fn close_result_handler(
+    State(cursors): State<ResultCursors>,
+    Path(id): Path<String>,
+) -> Response {
+    if cursors.close(&id) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("Result not found: {}", id)
+            })),
+        )
+            .into_response()
+    }
+}
+
+/// Handler for POST /api/query/:id/cancel
+///
+/// Stops a query sent to `/api/query` with this `id` that is still running.
+/// On PostgreSQL the backend running it is sent a cancel request; on SQLite
+/// the statement is interrupted. The query's own request then fails with the
+/// database's cancellation error.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `id` - Identifier the query was sent with
+///
+/// # Returns
+///
+/// Empty response with status 204, or 404 if no query with the id is running
+pub async fn cancel_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(id): Path<String>,
+) -> Response {
+    match database.cancel_query(&id).await {
+        Ok(()) => {
+            eprintln!("Cancelled query '{}'", id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(error) => {
+            eprintln!("Failed to cancel query '{}': {}", id, error);
+
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
             )
                 .into_response()
         }
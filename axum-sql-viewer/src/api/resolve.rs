@@ -0,0 +1,151 @@
+//! Row reference resolution endpoint
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::labels::DisplayColumns;
+use crate::row_key::{format_row_key, parse_row_key, primary_key_columns, value_to_key_text};
+use crate::schema::{ReferenceCount, ResolveQuery, ResolveResponse};
+
+/// Handler for GET /api/resolve
+///
+/// Validates a reference to a single row and returns the context needed to
+/// link to it: whether it exists, a human-readable label and how many rows in
+/// other tables reference it through foreign keys. Rows are addressed by table
+/// name and primary key only, so links keep working when columns are added,
+/// renamed or reordered.
+///
+/// A key that is well-formed but matches no row is not an error: the response
+/// has `exists: false` and no references.
+///
+/// Example: `GET /api/resolve?table=users&key=42`
+///
+/// Response:
+/// ```json
+/// {
+///   "table": "users",
+///   "key": "42",
+///   "primaryKey": { "id": "42" },
+///   "exists": true,
+///   "displayColumn": "name",
+///   "displayValue": "Alice",
+///   "references": [
+///     { "table": "posts", "column": "author_id", "count": 3 }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `display_columns` - Configured display columns from state
+/// * `query` - Table name and row key
+///
+/// # Returns
+///
+/// JSON response describing the referenced row
+pub async fn resolve_row_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(display_columns): State<DisplayColumns>,
+    Query(query): Query<ResolveQuery>,
+) -> Response {
+    match resolve_row(database.as_ref(), &display_columns, &query).await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(error) => {
+            eprintln!(
+                "Failed to resolve row '{}' in table '{}': {}",
+                query.key, query.table, error
+            );
+
+            // Return appropriate status code based on error type
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if error.to_string().contains("Invalid") {
+                StatusCode::BAD_REQUEST
+            } else if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Look up a row and the rows referencing it
+async fn resolve_row<DB: DatabaseProvider>(
+    database: &DB,
+    display_columns: &DisplayColumns,
+    query: &ResolveQuery,
+) -> Result<ResolveResponse, DatabaseError> {
+    let schema = database.get_table_schema(&query.table).await?;
+    let key = parse_row_key(&query.key);
+    let key_columns = primary_key_columns(&schema, &key)?;
+
+    let primary_key = key_columns
+        .iter()
+        .zip(&key)
+        .map(|(column, value)| (column.clone(), serde_json::Value::String(value.clone())))
+        .collect();
+    let display_column = display_columns.column_for(&schema).map(str::to_string);
+    let row = database.get_row(&query.table, &key).await?;
+
+    let mut response = ResolveResponse {
+        table: schema.name.clone(),
+        key: format_row_key(&key),
+        primary_key,
+        exists: row.is_some(),
+        display_column,
+        display_value: None,
+        references: Vec::new(),
+    };
+
+    let Some(row) = row else {
+        return Ok(response);
+    };
+
+    response.display_value = response
+        .display_column
+        .as_ref()
+        .and_then(|column| row.get(column))
+        .and_then(value_to_key_text)
+        .or_else(|| Some(key.join(", ")));
+
+    for referencing_schema in database.get_all_table_schemas().await? {
+        for foreign_key in &referencing_schema.foreign_keys {
+            if foreign_key.references_table != schema.name {
+                continue;
+            }
+
+            let Some(value) = row
+                .get(&foreign_key.references_column)
+                .and_then(value_to_key_text)
+            else {
+                continue;
+            };
+
+            let count = database
+                .count_rows_with_values(&referencing_schema.name, &foreign_key.column, &[value])
+                .await?;
+            response.references.push(ReferenceCount {
+                table: referencing_schema.name.clone(),
+                column: foreign_key.column.clone(),
+                count,
+            });
+        }
+    }
+
+    Ok(response)
+}
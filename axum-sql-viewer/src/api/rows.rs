@@ -1,17 +1,28 @@
-//! Row fetching endpoints with pagination
+//! Row fetching and editing endpoints
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
 use std::sync::Arc;
 
-use crate::database::traits::DatabaseProvider;
-use crate::schema::RowQuery;
-
-/// Maximum allowed limit to prevent excessive memory usage
-const MAX_LIMIT: u64 = 500;
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::filters::filter_parameters;
+use crate::json_body::streamed_rows;
+use crate::labels::{column_values, labels_by_value, DisplayColumns, ForeignKeyLabels};
+use crate::references::{cascade_paths, references_to};
+use crate::row_key::{
+    format_row_key, key_from_query, parse_row_key, primary_key_parameters, resolve_row_key,
+    value_to_key_text,
+};
+use crate::schema::{
+    DeleteRowQuery, DeleteRowsRequest, DeleteRowsResponse, DuplicatesQuery, OnReferenced,
+    ReferenceCount, ReferencingRows, ReferencingRowsQuery, ReferencingRowsResponse, RelatedRow,
+    RelatedRowsResponse, RowKey, RowLabelsQuery, RowQuery, RowResponse, SampleQuery,
+    UpdateRowResponse,
+};
+use std::collections::HashMap;
 
 /// Handler for GET /api/tables/:name/rows
 ///
@@ -19,32 +30,166 @@ const MAX_LIMIT: u64 = 500;
 ///
 /// Query parameters:
 /// - offset: Starting row offset (default: 0)
-/// - limit: Maximum rows to return (default: 100, max: 500)
+/// - limit: Maximum rows to return (default: 100, max: the provider's
+///   `maxPageSize`, 500 unless configured otherwise)
 /// - sortBy: Column name to sort by (optional)
 /// - sortOrder: "ascending" or "descending" (optional, default: "ascending")
 /// - filter[column]: Filter value for specific column (supports % wildcards)
+///   (on boolean columns, `true`/`false`, `1`/`0`, `yes`/`no` and the like)
+/// - filter[column][gt|gte|lt|lte]: ISO 8601 date or timestamp the column must
+///   be after, at or after, before, or at or before (e.g.
+///   `filter[created_at][gte]=2024-01-01T00:00:00Z`); compared as points in
+///   time, not as text. Values without a time zone are taken as UTC
+/// - caseInsensitive: "true" to match filters ignoring case (`ILIKE` on
+///   PostgreSQL, `COLLATE NOCASE` on SQLite)
+/// - q: Text to search for; matches rows containing it (ignoring case) in
+///   any textual column, as determined from the table schema
+/// - columns: Columns to fetch, separated by commas (default: all); unknown
+///   columns are rejected with 400. Cursors need the sort and key columns
+/// - cursor: `nextCursor` of the previous page, to continue after its last
+///   row instead of skipping `offset` rows (same sort and filters)
+/// - count: "false" to skip counting the matching rows; `total` is then
+///   `null`. `hasMore` comes from reading one row past the page, so it does
+///   not need the count (pages after the first can leave it out)
+/// - labels: "true" to add labels of the rows referenced by foreign keys
+///
+/// Rows are ordered by `sortBy` and then by the row key. For tables with a
+/// primary key (or SQLite `rowid`), a response with more rows after it has a
+/// `nextCursor`; following cursors stays fast deep into large tables, where
+/// offsets get slow.
+///
+/// With `labels=true` the response contains the display value of every
+/// referenced row, by foreign key column and value:
+/// ```json
+/// {
+///   "labels": { "author_id": { "7": "Alice Johnson" } }
+/// }
+/// ```
 ///
 /// # Arguments
 ///
 /// * `database` - Database provider from state
+/// * `display_columns` - Configured display columns from state
 /// * `table_name` - Name of the table to fetch rows from
 /// * `query` - Query parameters for pagination, sorting, and filtering
+/// * `labels_query` - Whether to resolve foreign key labels
+/// * `parameters` - All query parameters (for `filter[column]` and ranges)
 ///
 /// # Returns
 ///
 /// JSON response containing rows, columns, and pagination metadata
 pub async fn get_rows_handler<DB: DatabaseProvider>(
     State(database): State<Arc<DB>>,
+    State(display_columns): State<DisplayColumns>,
     Path(table_name): Path<String>,
-    Query(mut query): Query<RowQuery>,
+    Query(query): Query<RowQuery>,
+    Query(labels_query): Query<RowLabelsQuery>,
+    Query(parameters): Query<HashMap<String, String>>,
+) -> Response {
+    rows_response(
+        database.as_ref(),
+        &display_columns,
+        &table_name,
+        query,
+        labels_query.labels,
+        &parameters,
+    )
+    .await
+}
+
+/// Handler for POST /api/tables/:name/rows
+///
+/// Fetches rows like `GET /api/tables/:name/rows`, with the query as a JSON
+/// body instead of query parameters. The body can also hold `filterGroups`:
+/// groups of column filters of which at least one must match, each group
+/// matching only if all of its filters do. Labels and date ranges
+/// (`filter[column][gte]=...`) stay query parameters.
+///
+/// Request body:
+/// ```json
+/// {
+///   "limit": 50,
+///   "sortBy": "created_at",
+///   "sortOrder": "descending",
+///   "filters": { "queue": "emails" },
+///   "filterGroups": [{ "status": "failed" }, { "status": "cancelled" }]
+/// }
+/// ```
+///
+/// selects the rows of the `emails` queue whose status is `failed` or
+/// `cancelled`. The response is the same as for `GET`.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `display_columns` - Configured display columns from state
+/// * `table_name` - Name of the table to fetch rows from
+/// * `labels_query` - Whether to resolve foreign key labels
+/// * `parameters` - All query parameters (for `filter[column]` and ranges)
+/// * `query` - Pagination, sorting, filters and filter groups
+///
+/// # Returns
+///
+/// JSON response containing rows, columns, and pagination metadata
+pub async fn query_rows_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(display_columns): State<DisplayColumns>,
+    Path(table_name): Path<String>,
+    Query(labels_query): Query<RowLabelsQuery>,
+    Query(parameters): Query<HashMap<String, String>>,
+    Json(query): Json<RowQuery>,
+) -> Response {
+    rows_response(
+        database.as_ref(),
+        &display_columns,
+        &table_name,
+        query,
+        labels_query.labels,
+        &parameters,
+    )
+    .await
+}
+
+/// Fetch a page of rows and respond with it, or with the error
+async fn rows_response<DB: DatabaseProvider>(
+    database: &DB,
+    display_columns: &DisplayColumns,
+    table_name: &str,
+    mut query: RowQuery,
+    labels: bool,
+    parameters: &HashMap<String, String>,
 ) -> Response {
     // Enforce maximum limit
-    if query.limit > MAX_LIMIT {
-        query.limit = MAX_LIMIT;
-    }
+    query.limit = query.limit.min(database.result_limits().max_page_size);
 
-    match database.get_rows(&table_name, query).await {
-        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+    let result = match filter_parameters(parameters) {
+        Ok(filters) => {
+            query.filters.extend(filters.values);
+            query.ranges = filters.ranges;
+            database.get_rows(table_name, query).await
+        }
+        Err(error) => Err(error),
+    };
+
+    match result {
+        Ok(mut response) => {
+            if labels {
+                // Labels are a convenience; the rows are still useful without them
+                match foreign_key_labels(database, table_name, &response.rows, display_columns)
+                    .await
+                {
+                    Ok(labels) => response.labels = Some(labels),
+                    Err(error) => eprintln!(
+                        "Failed to resolve foreign key labels for table '{}': {}",
+                        table_name, error
+                    ),
+                }
+            }
+
+            // Rows are written into the body while it is sent
+            let rows = std::mem::take(&mut response.rows);
+            streamed_rows(StatusCode::OK, &response, rows)
+        }
         Err(error) => {
             eprintln!(
                 "Failed to get rows from table '{}': {}",
@@ -55,7 +200,12 @@ pub async fn get_rows_handler<DB: DatabaseProvider>(
             // Return appropriate status code based on error type
             let status = if error.to_string().contains("not found") {
                 StatusCode::NOT_FOUND
-            } else if error.to_string().contains("Invalid column") {
+            } else if error.to_string().contains("Invalid column")
+                || matches!(
+                    error,
+                    DatabaseError::InvalidCursor(_) | DatabaseError::InvalidFilter(_)
+                )
+            {
                 StatusCode::BAD_REQUEST
             } else if error.to_string().contains("timeout") {
                 StatusCode::REQUEST_TIMEOUT
@@ -63,15 +213,60 @@ pub async fn get_rows_handler<DB: DatabaseProvider>(
                 StatusCode::INTERNAL_SERVER_ERROR
             };
 
-            (
-                status,
-                Json(serde_json::json!({
-                    "error": error.to_string()
-                })),
-            )
-                .into_response()
+            let mut body = serde_json::json!({
+                "error": error.to_string()
+            });
+            if let Some(kind) = error.timeout_kind() {
+                body["timeout"] = serde_json::json!(kind);
+            }
+
+            (status, Json(body)).into_response()
+        }
+    }
+}
+
+/// Look up the labels of the rows referenced by the foreign keys of `rows`
+async fn foreign_key_labels<DB: DatabaseProvider>(
+    database: &DB,
+    table_name: &str,
+    rows: &[serde_json::Value],
+    display_columns: &DisplayColumns,
+) -> Result<ForeignKeyLabels, DatabaseError> {
+    let schema = database.get_table_schema(table_name).await?;
+    let mut labels = ForeignKeyLabels::new();
+
+    for foreign_key in &schema.foreign_keys {
+        let values = column_values(rows, &foreign_key.column);
+        if values.is_empty() {
+            continue;
         }
+
+        let referenced_schema = database
+            .get_table_schema(&foreign_key.references_table)
+            .await?;
+        let Some(display_column) = display_columns.column_for(&referenced_schema) else {
+            continue;
+        };
+
+        let referenced_rows = database
+            .get_rows_with_values(
+                &foreign_key.references_table,
+                &foreign_key.references_column,
+                &values,
+                values.len() as u64,
+            )
+            .await?;
+        labels.insert(
+            foreign_key.column.clone(),
+            labels_by_value(
+                &referenced_rows,
+                &foreign_key.references_column,
+                display_column,
+            ),
+        );
     }
+
+    Ok(labels)
 }
 
 /// Handler for GET /api/tables/:name/count
@@ -80,12 +275,29 @@ pub async fn get_rows_handler<DB: DatabaseProvider>(
 ///
 /// Query parameters:
 /// - filter[column]: Filter value for specific column (same as get_rows_handler)
+/// - filter[column][gt|gte|lt|lte]: Date or timestamp range (same as get_rows_handler)
+/// - caseInsensitive: "true" to match filters ignoring case (same as get_rows_handler)
+/// - q: Text to search for in all textual columns (same as get_rows_handler)
+///
+/// Response:
+/// ```json
+/// {
+///   "count": 1234,
+///   "exact": true,
+///   "asOfMilliseconds": 1760611200000
+/// }
+/// ```
+///
+/// `exact` and `asOfMilliseconds` tell how and when the count was taken, here
+/// and in the `total` of row pages and the `rowCount` of the table list, so
+/// estimates and cached counts can be told apart from fresh exact ones.
 ///
 /// # Arguments
 ///
 /// * `database` - Database provider from state
 /// * `table_name` - Name of the table to count rows from
 /// * `query` - Query parameters (filters only, other fields ignored)
+/// * `parameters` - All query parameters (for `filter[column]` and ranges)
 ///
 /// # Returns
 ///
@@ -93,9 +305,19 @@ pub async fn get_rows_handler<DB: DatabaseProvider>(
 pub async fn count_rows_handler<DB: DatabaseProvider>(
     State(database): State<Arc<DB>>,
     Path(table_name): Path<String>,
-    Query(query): Query<RowQuery>,
+    Query(mut query): Query<RowQuery>,
+    Query(parameters): Query<HashMap<String, String>>,
 ) -> Response {
-    match database.count_rows(&table_name, &query).await {
+    let result = match filter_parameters(&parameters) {
+        Ok(filters) => {
+            query.filters.extend(filters.values);
+            query.ranges = filters.ranges;
+            database.count_rows(&table_name, &query).await
+        }
+        Err(error) => Err(error),
+    };
+
+    match result {
         Ok(response) => (StatusCode::OK, Json(response)).into_response(),
         Err(error) => {
             eprintln!(
@@ -107,8 +329,527 @@ pub async fn count_rows_handler<DB: DatabaseProvider>(
             // Return appropriate status code based on error type
             let status = if error.to_string().contains("not found") {
                 StatusCode::NOT_FOUND
-            } else if error.to_string().contains("Invalid column") {
+            } else if error.to_string().contains("Invalid column")
+                || matches!(error, DatabaseError::InvalidFilter(_))
+            {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/tables/:name/sample
+///
+/// Returns `n` randomly picked rows of a table, to look at a representative
+/// slice of a large table without paging through it. Every row has the same
+/// chance of being picked: large PostgreSQL tables are thinned out with
+/// `TABLESAMPLE BERNOULLI` first, other tables shuffled with `ORDER BY random()`.
+///
+/// Query parameters:
+/// - n: Number of rows (default: 100, at most the maximum page size)
+///
+/// Response:
+/// ```json
+/// {
+///   "rows": [{ "id": 8812, "email": "user@example.com" }],
+///   "columns": ["id", "email"],
+///   "size": 100,
+///   "method": "tableSample"
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table
+/// * `query` - Number of rows to sample
+///
+/// # Returns
+///
+/// JSON response containing the sampled rows, 404 if the table does not
+/// exist or 501 if the database has no samples
+pub async fn sample_rows_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+    Query(query): Query<SampleQuery>,
+) -> Response {
+    match database.sample_rows(&table_name, query.n).await {
+        Ok(sample) => (StatusCode::OK, Json(sample)).into_response(),
+        Err(error) => {
+            eprintln!("Failed to sample rows of table '{}': {}", table_name, error);
+
+            let status = match error {
+                DatabaseError::TableNotFound(_) => StatusCode::NOT_FOUND,
+                DatabaseError::AmbiguousTable(..) => StatusCode::BAD_REQUEST,
+                DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/tables/:name/duplicates
+///
+/// Finds rows sharing the values of the given columns with `GROUP BY ...
+/// HAVING COUNT(*) > 1` and returns the groups, the largest first. NULLs are
+/// grouped together like any other value.
+///
+/// Query parameters:
+/// - columns: Columns to compare, separated by commas (required)
+/// - limit: Number of groups (default: 100, at most the maximum page size)
+///
+/// Response:
+/// ```json
+/// {
+///   "table": "users",
+///   "columns": ["email"],
+///   "groups": [
+///     { "values": { "email": "jane@example.com" }, "count": 3 },
+///     { "values": { "email": "joe@example.com" }, "count": 2 }
+///   ],
+///   "truncated": false
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table
+/// * `query` - Columns to compare and number of groups
+///
+/// # Returns
+///
+/// JSON response containing the duplicate groups, 404 if the table does not
+/// exist, 400 if a column does not exist or cannot be compared, or 501 if the
+/// database cannot find duplicates
+pub async fn find_duplicates_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+    Query(query): Query<DuplicatesQuery>,
+) -> Response {
+    match database
+        .find_duplicates(&table_name, &query.column_names(), query.limit)
+        .await
+    {
+        Ok(duplicates) => (StatusCode::OK, Json(duplicates)).into_response(),
+        Err(error) => {
+            eprintln!(
+                "Failed to find duplicate rows of table '{}': {}",
+                table_name, error
+            );
+
+            let status = match error {
+                DatabaseError::TableNotFound(_) => StatusCode::NOT_FOUND,
+                DatabaseError::InvalidColumn(_) | DatabaseError::AmbiguousTable(..) => {
+                    StatusCode::BAD_REQUEST
+                }
+                DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/tables/:name/checksum
+///
+/// Returns a cheap fingerprint of the table's data, so clients can poll for
+/// changes instead of refetching pages. It is built from the row count, the
+/// highest primary key (or row id), the latest value of an `updated_at`-like
+/// column and, on PostgreSQL, the newest row version: inserts and deletes
+/// always change it, updates do on PostgreSQL or if the table has such a
+/// column.
+///
+/// Response:
+/// ```json
+/// {
+///   "table": "orders",
+///   "checksum": "8c3f0e5a9b12d47e",
+///   "rowCount": 1234
+/// }
+/// ```
+///
+/// The checksum is also sent as `ETag`; a request with `If-None-Match` set to
+/// it is answered with 304 Not Modified while the table is unchanged.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table
+/// * `headers` - Request headers (`If-None-Match`)
+///
+/// # Returns
+///
+/// JSON response containing the checksum, 304 if it matches `If-None-Match`,
+/// 404 if the table does not exist or 501 if the database has no checksums
+pub async fn table_checksum_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    match database.table_checksum(&table_name).await {
+        Ok(checksum) => {
+            let etag = format!("\"{}\"", checksum.checksum);
+            let unchanged = headers
+                .get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|tags| {
+                    tags.split(',')
+                        .map(str::trim)
+                        .any(|tag| tag == "*" || tag == etag)
+                });
+            if unchanged {
+                return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+            }
+            (StatusCode::OK, [(header::ETAG, etag)], Json(checksum)).into_response()
+        }
+        Err(error) => {
+            eprintln!(
+                "Failed to compute checksum of table '{}': {}",
+                table_name, error
+            );
+
+            let status = match error {
+                DatabaseError::TableNotFound(_) => StatusCode::NOT_FOUND,
+                DatabaseError::AmbiguousTable(..) => StatusCode::BAD_REQUEST,
+                DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/tables/:name/rows/:key
+///
+/// Fetches a single row by its primary key, for deep links and row detail
+/// views. For composite primary keys the key values are separated by commas,
+/// in the order of the table's primary key columns (e.g. `/rows/7,en`).
+///
+/// Response:
+/// ```json
+/// {
+///   "row": { "id": 42, "email": "user@example.com" }
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table containing the row
+/// * `key` - Primary key values of the row
+///
+/// # Returns
+///
+/// JSON response containing the row, or 404 if no row has this key
+pub async fn get_row_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path((table_name, key)): Path<(String, String)>,
+) -> Response {
+    let key = parse_row_key(&key);
+    row_response(database.get_row(&table_name, &key).await, &table_name, &key)
+}
+
+/// Handler for GET /api/tables/:name/row
+///
+/// Same as `GET /api/tables/:name/rows/:key`, with the primary key in the
+/// canonical query form: one `pk[column]=value` parameter per key column
+/// (e.g. `/row?pk[user_id]=7&pk[group_id]=3`), so no escaping or column order
+/// is needed. Works the same for single-column and composite keys.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table containing the row
+/// * `parameters` - Query parameters containing the primary key values
+///
+/// # Returns
+///
+/// JSON response containing the row, or 404 if no row has this key
+pub async fn get_row_by_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+    Query(parameters): Query<HashMap<String, String>>,
+) -> Response {
+    let key = match key_for_query(database.as_ref(), &table_name, &parameters).await {
+        Ok(key) => key,
+        Err(error) => return row_response(Err(error), &table_name, &[]),
+    };
+
+    row_response(database.get_row(&table_name, &key).await, &table_name, &key)
+}
+
+/// Handler for PATCH /api/tables/:name/row
+///
+/// Same as `PATCH /api/tables/:name/rows/:key`, with the primary key passed
+/// as `pk[column]=value` query parameters.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table containing the row
+/// * `parameters` - Query parameters containing the primary key values
+/// * `values` - New values by column name
+///
+/// # Returns
+///
+/// JSON response containing the updated row
+pub async fn update_row_by_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+    Query(parameters): Query<HashMap<String, String>>,
+    Json(values): Json<serde_json::Map<String, serde_json::Value>>,
+) -> Response {
+    let result = match key_for_query(database.as_ref(), &table_name, &parameters).await {
+        Ok(key) => database.update_row(&table_name, &key, &values).await,
+        Err(error) => Err(error),
+    };
+
+    update_response(result, &table_name)
+}
+
+/// Handler for DELETE /api/tables/:name/row
+///
+/// Deletes a single row, with the primary key passed as `pk[column]=value`
+/// query parameters. References are handled as by `POST /api/tables/:name/delete`,
+/// with `onReferenced` passed as a query parameter too
+/// (e.g. `/row?pk[user_id]=7&pk[group_id]=3&onReferenced=cascade`).
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table containing the row
+/// * `parameters` - Query parameters containing the primary key values
+/// * `query` - Handling of referenced rows
+///
+/// # Returns
+///
+/// JSON response summarizing deleted, skipped and referencing rows
+pub async fn delete_row_by_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+    Query(parameters): Query<HashMap<String, String>>,
+    Query(query): Query<DeleteRowQuery>,
+) -> Response {
+    let keys = [RowKey::Columns(
+        primary_key_parameters(&parameters)
+            .into_iter()
+            .map(|(column, value)| (column, serde_json::Value::String(value)))
+            .collect(),
+    )];
+    let result = delete_rows(database.as_ref(), &table_name, &keys, query.on_referenced).await;
+
+    delete_response(result, &table_name, query.on_referenced)
+}
+
+/// Look up the table schema and build a row key from `pk[column]` parameters
+async fn key_for_query<DB: DatabaseProvider>(
+    database: &DB,
+    table_name: &str,
+    parameters: &HashMap<String, String>,
+) -> Result<Vec<String>, DatabaseError> {
+    let schema = database.get_table_schema(table_name).await?;
+    key_from_query(&schema, parameters)
+}
+
+/// Handler for GET /api/tables/:name/rows/:key/related
+///
+/// Follows every foreign key of a row and returns the parent rows it
+/// references, for click-through navigation between tables.
+///
+/// Response:
+/// ```json
+/// {
+///   "related": [
+///     {
+///       "column": "author_id",
+///       "table": "users",
+///       "referencesColumn": "id",
+///       "row": { "id": 7, "name": "Alice Johnson" }
+///     }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table containing the row
+/// * `key` - Primary key values of the row
+///
+/// # Returns
+///
+/// JSON response containing the referenced rows, one entry per foreign key
+pub async fn get_related_rows_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path((table_name, key)): Path<(String, String)>,
+) -> Response {
+    let key = parse_row_key(&key);
+
+    match get_related_rows(database.as_ref(), &table_name, &key).await {
+        Ok(related) => (StatusCode::OK, Json(RelatedRowsResponse { related })).into_response(),
+        Err(error) => {
+            eprintln!(
+                "Failed to get related rows from table '{}': {}",
+                table_name,
+                error
+            );
+
+            // Return appropriate status code based on error type
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if error.to_string().contains("Invalid") {
+                StatusCode::BAD_REQUEST
+            } else if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Look up the parent rows referenced by the foreign keys of a row
+async fn get_related_rows<DB: DatabaseProvider>(
+    database: &DB,
+    table_name: &str,
+    key: &[String],
+) -> Result<Vec<RelatedRow>, DatabaseError> {
+    let schema = database.get_table_schema(table_name).await?;
+    let row = database
+        .get_row(table_name, key)
+        .await?
+        .ok_or_else(|| DatabaseError::RowNotFound(key.join(", ")))?;
+
+    let mut related = Vec::with_capacity(schema.foreign_keys.len());
+    for foreign_key in &schema.foreign_keys {
+        let parent = match row.get(&foreign_key.column).and_then(value_to_key_text) {
+            Some(value) => database
+                .get_rows_with_values(
+                    &foreign_key.references_table,
+                    &foreign_key.references_column,
+                    &[value],
+                    1,
+                )
+                .await?
+                .into_iter()
+                .next(),
+            None => None,
+        };
+
+        related.push(RelatedRow {
+            column: foreign_key.column.clone(),
+            table: foreign_key.references_table.clone(),
+            references_column: foreign_key.references_column.clone(),
+            row: parent,
+        });
+    }
+
+    Ok(related)
+}
+
+/// Handler for GET /api/tables/:name/rows/:key/referenced-by
+///
+/// The reverse of `/related`: finds the rows of other tables whose foreign
+/// keys point at this row (e.g. all `orders` of a `users` row), using the
+/// foreign keys of every table in the schema.
+///
+/// Query parameters:
+/// - limit: Maximum rows returned per foreign key (default: 20, max: 500)
+///
+/// Response:
+/// ```json
+/// {
+///   "referencedBy": [
+///     {
+///       "table": "orders",
+///       "column": "user_id",
+///       "referencesColumn": "id",
+///       "count": 2,
+///       "rows": [{ "id": 10, "user_id": 7 }, { "id": 11, "user_id": 7 }]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table containing the row
+/// * `key` - Primary key values of the row
+/// * `query` - Maximum number of rows per foreign key
+///
+/// # Returns
+///
+/// JSON response containing the referencing rows, grouped by foreign key
+pub async fn get_referencing_rows_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path((table_name, key)): Path<(String, String)>,
+    Query(query): Query<ReferencingRowsQuery>,
+) -> Response {
+    let key = parse_row_key(&key);
+    let limit = query.limit.min(database.result_limits().max_page_size);
+
+    match get_referencing_rows(database.as_ref(), &table_name, &key, limit).await {
+        Ok(referenced_by) => (
+            StatusCode::OK,
+            Json(ReferencingRowsResponse { referenced_by }),
+        )
+            .into_response(),
+        Err(error) => {
+            eprintln!(
+                "Failed to get rows referencing a row of table '{}': {}",
+                table_name,
+                error
+            );
+
+            // Return appropriate status code based on error type
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if error.to_string().contains("Invalid") {
                 StatusCode::BAD_REQUEST
+            } else if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
             } else {
                 StatusCode::INTERNAL_SERVER_ERROR
             };
@@ -123,3 +864,435 @@ pub async fn count_rows_handler<DB: DatabaseProvider>(
         }
     }
 }
+
+/// Look up the rows of other tables referencing a row
+async fn get_referencing_rows<DB: DatabaseProvider>(
+    database: &DB,
+    table_name: &str,
+    key: &[String],
+    limit: u64,
+) -> Result<Vec<ReferencingRows>, DatabaseError> {
+    let schema = database.get_table_schema(table_name).await?;
+    let row = database
+        .get_row(table_name, key)
+        .await?
+        .ok_or_else(|| DatabaseError::RowNotFound(key.join(", ")))?;
+    let schemas = database.get_all_table_schemas().await?;
+
+    let mut referenced_by = Vec::new();
+    for reference in references_to(&schemas, &schema.name) {
+        let (count, rows) = match row
+            .get(&reference.references_column)
+            .and_then(value_to_key_text)
+        {
+            Some(value) => {
+                let values = [value];
+                let count = database
+                    .count_rows_with_values(&reference.table, &reference.column, &values)
+                    .await?;
+                let rows = database
+                    .get_rows_with_values(&reference.table, &reference.column, &values, limit)
+                    .await?;
+                (count, rows)
+            }
+            None => (0, Vec::new()),
+        };
+
+        referenced_by.push(ReferencingRows {
+            table: reference.table,
+            column: reference.column,
+            references_column: reference.references_column,
+            count,
+            rows,
+        });
+    }
+
+    Ok(referenced_by)
+}
+
+/// Turn the result of a single-row lookup into a response
+fn row_response(
+    result: Result<Option<serde_json::Value>, DatabaseError>,
+    table_name: &str,
+    key: &[String],
+) -> Response {
+    match result.and_then(|row| row.ok_or_else(|| DatabaseError::RowNotFound(key.join(", ")))) {
+        Ok(row) => (StatusCode::OK, Json(RowResponse { row })).into_response(),
+        Err(error) => {
+            eprintln!("Failed to get row from table '{}': {}", table_name, error);
+
+            // Return appropriate status code based on error type
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if error.to_string().contains("Invalid") {
+                StatusCode::BAD_REQUEST
+            } else if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for PATCH /api/tables/:name/rows/:key
+///
+/// Updates the supplied columns of a single row, identified by its primary key.
+/// For composite primary keys the key values are separated by commas, in the
+/// order of the table's primary key columns (e.g. `/rows/7,en`); commas and
+/// backslashes inside a value are escaped with a backslash.
+///
+/// Request body (column name -> new value):
+/// ```json
+/// {
+///   "email": "new@example.com",
+///   "nickname": null
+/// }
+/// ```
+///
+/// Response:
+/// ```json
+/// {
+///   "row": { "id": 42, "email": "new@example.com", "nickname": null }
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table containing the row
+/// * `key` - Primary key values of the row
+/// * `values` - New values by column name
+///
+/// # Returns
+///
+/// JSON response containing the updated row
+pub async fn update_row_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path((table_name, key)): Path<(String, String)>,
+    Json(values): Json<serde_json::Map<String, serde_json::Value>>,
+) -> Response {
+    let key = parse_row_key(&key);
+    update_response(
+        database.update_row(&table_name, &key, &values).await,
+        &table_name,
+    )
+}
+
+/// Turn the result of a row update into a response
+fn update_response(result: Result<serde_json::Value, DatabaseError>, table_name: &str) -> Response {
+    match result {
+        Ok(row) => (StatusCode::OK, Json(UpdateRowResponse { row })).into_response(),
+        Err(error) => {
+            eprintln!("Failed to update row in table '{}': {}", table_name, error);
+
+            // Return appropriate status code based on error type
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if error.to_string().contains("Invalid") {
+                StatusCode::BAD_REQUEST
+            } else if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for POST /api/tables/:name/rows:bulk
+///
+/// Inserts an array of rows in a single transaction. Every row is checked
+/// against the table's columns first (unknown columns, missing or null values
+/// for required columns); if any row is invalid or any insert fails, nothing
+/// is inserted. Omitted columns get their default value.
+///
+/// Request body:
+/// ```json
+/// [
+///   { "email": "alice@example.com", "nickname": "alice" },
+///   { "email": "bob@example.com" }
+/// ]
+/// ```
+///
+/// Response (201 Created):
+/// ```json
+/// {
+///   "inserted": 2
+/// }
+/// ```
+///
+/// An invalid row is reported with its position:
+/// ```json
+/// {
+///   "error": "Invalid row 1: column 'email' is required"
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table to insert into
+/// * `rows` - Values of each row by column name
+///
+/// # Returns
+///
+/// JSON response containing the number of inserted rows
+pub async fn insert_rows_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+    Json(rows): Json<Vec<serde_json::Map<String, serde_json::Value>>>,
+) -> Response {
+    match database.insert_rows(&table_name, &rows).await {
+        Ok(inserted) => (StatusCode::CREATED, Json(inserted)).into_response(),
+        Err(error) => {
+            eprintln!(
+                "Failed to insert rows into table '{}': {}",
+                table_name,
+                error
+            );
+
+            // Return appropriate status code based on error type
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if error.to_string().contains("Invalid") {
+                StatusCode::BAD_REQUEST
+            } else if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for POST /api/tables/:name/delete
+///
+/// Deletes rows by primary key. Before anything is deleted, every table with a
+/// foreign key to this table is checked for rows referencing the requested
+/// rows, and the result is summarized per foreign key. `onReferenced` decides
+/// what happens when references exist:
+/// - `abort` (default): nothing is deleted, the summary is returned with 409 Conflict
+/// - `skip`: only rows without references are deleted
+/// - `cascade`: referencing rows are deleted too, recursively, in one transaction
+///
+/// Request body (keys as `/rows/:key` path segments or by column name):
+/// ```json
+/// {
+///   "keys": ["1", "2", { "id": 3 }],
+///   "onReferenced": "abort"
+/// }
+/// ```
+///
+/// Response:
+/// ```json
+/// {
+///   "deleted": 0,
+///   "cascaded": 0,
+///   "skipped": [],
+///   "missing": [],
+///   "references": [
+///     { "table": "posts", "column": "author_id", "count": 5 }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table to delete rows from
+/// * `request` - Keys of the rows and handling of referenced rows
+///
+/// # Returns
+///
+/// JSON response summarizing deleted, skipped and referencing rows
+pub async fn delete_rows_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(table_name): Path<String>,
+    Json(request): Json<DeleteRowsRequest>,
+) -> Response {
+    let result = delete_rows(
+        database.as_ref(),
+        &table_name,
+        &request.keys,
+        request.on_referenced,
+    )
+    .await;
+
+    delete_response(result, &table_name, request.on_referenced)
+}
+
+/// Turn the result of a delete into a response (409 Conflict if aborted)
+fn delete_response(
+    result: Result<DeleteRowsResponse, DatabaseError>,
+    table_name: &str,
+    on_referenced: OnReferenced,
+) -> Response {
+    match result {
+        Ok(response) => {
+            let status = if on_referenced == OnReferenced::Abort && !response.references.is_empty()
+            {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::OK
+            };
+
+            (status, Json(response)).into_response()
+        }
+        Err(error) => {
+            eprintln!(
+                "Failed to delete rows from table '{}': {}",
+                table_name,
+                error
+            );
+
+            // Return appropriate status code based on error type
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if error.to_string().contains("Invalid") {
+                StatusCode::BAD_REQUEST
+            } else if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Check references to the requested rows and delete them as requested
+async fn delete_rows<DB: DatabaseProvider>(
+    database: &DB,
+    table_name: &str,
+    keys: &[RowKey],
+    on_referenced: OnReferenced,
+) -> Result<DeleteRowsResponse, DatabaseError> {
+    let schema = database.get_table_schema(table_name).await?;
+    let keys = keys
+        .iter()
+        .map(|key| resolve_row_key(&schema, key))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The rows are deleted with one statement binding every key value
+    let key_values: usize = keys.iter().map(Vec::len).sum();
+    if let Some(max_placeholder_count) = database.capabilities().max_placeholder_count {
+        if key_values > max_placeholder_count as usize {
+            return Err(DatabaseError::InvalidKey(format!(
+                "too many rows to delete at once ({} key values, the database accepts {})",
+                key_values, max_placeholder_count
+            )));
+        }
+    }
+
+    let mut response = DeleteRowsResponse {
+        deleted: 0,
+        cascaded: 0,
+        skipped: Vec::new(),
+        missing: Vec::new(),
+        references: Vec::new(),
+    };
+
+    let mut rows = Vec::new();
+    for key in keys {
+        match database.get_row(table_name, &key).await? {
+            Some(row) => rows.push((key, row)),
+            None => response.missing.push(format_row_key(&key)),
+        }
+    }
+
+    // Count referencing rows per foreign key, remembering which rows are referenced
+    let schemas = database.get_all_table_schemas().await?;
+    let mut is_referenced = vec![false; rows.len()];
+    for reference in references_to(&schemas, &schema.name) {
+        let mut values = Vec::new();
+        for (index, (_, row)) in rows.iter().enumerate() {
+            let Some(value) = row
+                .get(&reference.references_column)
+                .and_then(value_to_key_text)
+            else {
+                continue;
+            };
+
+            if on_referenced == OnReferenced::Skip && !is_referenced[index] {
+                is_referenced[index] = database
+                    .count_rows_with_values(
+                        &reference.table,
+                        &reference.column,
+                        std::slice::from_ref(&value),
+                    )
+                    .await?
+                    > 0;
+            }
+            values.push(value);
+        }
+
+        let count = database
+            .count_rows_with_values(&reference.table, &reference.column, &values)
+            .await?;
+        if count > 0 {
+            response.references.push(ReferenceCount {
+                table: reference.table,
+                column: reference.column,
+                count,
+            });
+        }
+    }
+
+    let (keys, cascade) = match on_referenced {
+        OnReferenced::Abort if !response.references.is_empty() => return Ok(response),
+        OnReferenced::Abort => (rows.into_iter().map(|(key, _)| key).collect(), Vec::new()),
+        OnReferenced::Skip => {
+            let mut keys = Vec::new();
+            for ((key, _), is_referenced) in rows.into_iter().zip(is_referenced) {
+                if is_referenced {
+                    response.skipped.push(format_row_key(&key));
+                } else {
+                    keys.push(key);
+                }
+            }
+            (keys, Vec::new())
+        }
+        OnReferenced::Cascade => (
+            rows.into_iter().map(|(key, _)| key).collect(),
+            cascade_paths(&schemas, &schema.name),
+        ),
+    };
+
+    let deleted_rows = database.delete_rows(table_name, &keys, &cascade).await?;
+    response.deleted = deleted_rows.deleted;
+    response.cascaded = deleted_rows.cascaded;
+
+    Ok(response)
+}
@@ -0,0 +1,165 @@
+//! Saved query endpoints
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::DatabaseProvider;
+use crate::saved_queries::{
+    SavedQueries, SavedQueriesResponse, SavedQueryError, SavedQueryFilter, SavedQueryRequest,
+};
+
+/// Handler for GET /api/saved-queries
+///
+/// Lists the saved queries, favorites first and then by name. `tag` lists
+/// only the queries with that tag, and `favorite=true` only the favorites,
+/// e.g. `GET /api/saved-queries?tag=reports`.
+///
+/// Response:
+/// ```json
+/// {
+///   "queries": [{
+///     "id": "18c4b2f0a1e-1",
+///     "name": "Orders of a user",
+///     "sql": "SELECT * FROM orders WHERE user_id = :user",
+///     "description": "Newest first",
+///     "parameters": { "user": 42 },
+///     "tags": ["reports"],
+///     "favorite": true,
+///     "createdAtMilliseconds": 1700000000000,
+///     "updatedAtMilliseconds": 1700000000000
+///   }]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `saved_queries` - Saved queries from state
+/// * `filter` - Tag and favorite flag to filter by
+///
+/// # Returns
+///
+/// JSON response containing the saved queries
+pub async fn list_saved_queries_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(saved_queries): State<SavedQueries>,
+    Query(filter): Query<SavedQueryFilter>,
+) -> Response {
+    match saved_queries.list(database.as_ref(), &filter).await {
+        Ok(queries) => (StatusCode::OK, Json(SavedQueriesResponse { queries })).into_response(),
+        Err(error) => saved_query_error_response("list saved queries", error),
+    }
+}
+
+/// Handler for POST /api/saved-queries
+///
+/// Saves a query. `name` and `sql` are required; `description`,
+/// `parameters` (an array or object, as for `/api/query`), `tags` and
+/// `favorite` are optional.
+///
+/// Request body:
+/// ```json
+/// {
+///   "name": "Orders of a user",
+///   "sql": "SELECT * FROM orders WHERE user_id = :user",
+///   "description": "Newest first",
+///   "parameters": { "user": 42 },
+///   "tags": ["reports"],
+///   "favorite": true
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `saved_queries` - Saved queries from state
+/// * `request` - JSON request containing the query to save
+///
+/// # Returns
+///
+/// JSON response containing the saved query with its identifier, status 201
+pub async fn create_saved_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(saved_queries): State<SavedQueries>,
+    Json(request): Json<SavedQueryRequest>,
+) -> Response {
+    match saved_queries.create(database.as_ref(), request).await {
+        Ok(query) => (StatusCode::CREATED, Json(query)).into_response(),
+        Err(error) => saved_query_error_response("save query", error),
+    }
+}
+
+/// Handler for PUT /api/saved-queries/:id
+///
+/// Replaces the name, SQL, description, parameters, tags and favorite flag
+/// of a saved query. The
+/// request body is the same as for `POST /api/saved-queries`.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `saved_queries` - Saved queries from state
+/// * `id` - Identifier of the saved query
+/// * `request` - JSON request containing the new contents
+///
+/// # Returns
+///
+/// JSON response containing the updated query, or 404 if it does not exist
+pub async fn update_saved_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(saved_queries): State<SavedQueries>,
+    Path(id): Path<String>,
+    Json(request): Json<SavedQueryRequest>,
+) -> Response {
+    match saved_queries.update(database.as_ref(), &id, request).await {
+        Ok(query) => (StatusCode::OK, Json(query)).into_response(),
+        Err(error) => saved_query_error_response("update saved query", error),
+    }
+}
+
+/// Handler for DELETE /api/saved-queries/:id
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `saved_queries` - Saved queries from state
+/// * `id` - Identifier of the saved query
+///
+/// # Returns
+///
+/// Empty response with status 204, or 404 if the query does not exist
+pub async fn delete_saved_query_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(saved_queries): State<SavedQueries>,
+    Path(id): Path<String>,
+) -> Response {
+    match saved_queries.delete(database.as_ref(), &id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(error) => saved_query_error_response("delete saved query", error),
+    }
+}
+
+/// Response for a failed saved query operation
+fn saved_query_error_response(action: &str, error: SavedQueryError) -> Response {
+    eprintln!("Failed to {}: {}", action, error);
+
+    let status = match &error {
+        SavedQueryError::NotFound(_) => StatusCode::NOT_FOUND,
+        SavedQueryError::Invalid(_) => StatusCode::BAD_REQUEST,
+        SavedQueryError::Storage(_) | SavedQueryError::Database(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "error": error.to_string()
+        })),
+    )
+        .into_response()
+}
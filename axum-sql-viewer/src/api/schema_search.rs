@@ -0,0 +1,73 @@
+//! Schema-wide search endpoint
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+
+use crate::database::traits::DatabaseProvider;
+use crate::schema::SchemaSearchQuery;
+use crate::schema_cache::SchemaCache;
+use crate::schema_search::search_schema;
+
+/// Handler for GET /api/search
+///
+/// Finds the tables and columns whose names contain `q` (ignoring case)
+/// across all tables, e.g. every table with a `tenant_id` column. Exact name
+/// matches come first, then the others in schema order.
+///
+/// Query parameters:
+/// - q: Text to search for (required)
+///
+/// Response:
+/// ```json
+/// {
+///   "query": "tenant_id",
+///   "matches": [
+///     { "kind": "column", "table": "users", "column": "tenant_id", "dataType": "INTEGER", "exact": true },
+///     { "kind": "table", "table": "tenant_ids", "exact": false }
+///   ]
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `schema_cache` - Cache answering the request if all schemas were loaded before
+/// * `query` - Text to search for
+///
+/// # Returns
+///
+/// JSON response containing the matching tables and columns
+pub async fn search_schema_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(schema_cache): State<SchemaCache>,
+    Query(query): Query<SchemaSearchQuery>,
+) -> Response {
+    let text = query.q.trim();
+    if text.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Missing search text (q)"
+            })),
+        )
+            .into_response();
+    }
+
+    match schema_cache.all_table_schemas(database.as_ref()).await {
+        Ok(schemas) => (StatusCode::OK, Json(search_schema(&schemas, text))).into_response(),
+        Err(error) => {
+            eprintln!("Failed to load schemas for search: {}", error);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
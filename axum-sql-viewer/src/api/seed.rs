@@ -0,0 +1,139 @@
+//! Seed endpoints for resetting the database between manual test runs
+//!
+//! The seed is a saved copy of the database (a file next to the SQLite
+//! database, or a template database for PostgreSQL). Creating it and resetting
+//! to it can take a while on larger databases, so both run as jobs.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::jobs::JobRegistry;
+use crate::schema::SeedStatus;
+
+/// Handler for GET /api/seed
+///
+/// Reports whether a seed exists and where it is kept.
+///
+/// Response:
+/// ```json
+/// {
+///   "location": "/data/app.db.seed",
+///   "exists": true
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+///
+/// # Returns
+///
+/// JSON response containing the seed status
+pub async fn seed_status_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+) -> Response {
+    match database.seed_status().await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(error) => {
+            eprintln!("Failed to get seed status: {}", error);
+
+            let status = if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for POST /api/seed
+///
+/// Starts a job saving the current database state as the seed, replacing any
+/// previous seed. Responds with 202 Accepted and the job; its result is the
+/// new seed status.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `jobs` - Job registry from state
+///
+/// # Returns
+///
+/// JSON response containing the started job
+pub async fn create_seed_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(jobs): State<JobRegistry>,
+) -> Response {
+    start_seed_job(
+        &jobs,
+        "seed.create",
+        async move { database.create_seed().await },
+    )
+    .await
+}
+
+/// Handler for POST /api/seed/reset
+///
+/// Starts a job replacing the database contents with the seed. Responds with
+/// 202 Accepted and the job; poll `/api/jobs/:id` to see when the reset is done.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `jobs` - Job registry from state
+///
+/// # Returns
+///
+/// JSON response containing the started job
+pub async fn reset_to_seed_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(jobs): State<JobRegistry>,
+) -> Response {
+    start_seed_job(&jobs, "seed.reset", async move {
+        database.reset_to_seed().await
+    })
+    .await
+}
+
+/// Start a seed job unless another one is still running
+async fn start_seed_job<F>(jobs: &JobRegistry, kind: &str, work: F) -> Response
+where
+    F: Future<Output = Result<SeedStatus, DatabaseError>> + Send + 'static,
+{
+    // Creating and resetting both replace whole databases; never overlap them
+    if jobs.is_running(|running_kind| running_kind.starts_with("seed.")) {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "Another seed job is still running"
+            })),
+        )
+            .into_response();
+    }
+
+    let job = jobs
+        .start(kind, async move {
+            let status = work.await.map_err(|error| {
+                eprintln!("Seed job failed: {}", error);
+                error.to_string()
+            })?;
+            serde_json::to_value(status).map_err(|error| error.to_string())
+        })
+        .await;
+
+    (StatusCode::ACCEPTED, Json(job)).into_response()
+}
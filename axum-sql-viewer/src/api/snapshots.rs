@@ -0,0 +1,389 @@
+//! Snapshot endpoints
+//!
+//! Read snapshots (`/api/snapshots`) keep paging consistent while the data
+//! changes. Named snapshots (`/api/snapshots/named`) are copies of table rows
+//! to restore or compare with later; saving and restoring run as jobs.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::jobs::JobRegistry;
+use crate::named_snapshots::{diff_rows, validate_snapshot_name, MAX_DIFF_ROWS};
+use crate::schema::{NamedSnapshot, SaveSnapshotRequest, SnapshotDiffQuery};
+
+/// Handler for POST /api/snapshots
+///
+/// Opens a snapshot of the database. Passing the returned identifier as the
+/// `snapshot` query parameter of `/api/tables/:name/rows` and
+/// `/api/tables/:name/count` makes every page of a browsing session read the
+/// same data, so concurrent writes do not shift or duplicate rows.
+///
+/// Snapshots are released after a period of inactivity or when deleted.
+/// Only supported by PostgreSQL.
+///
+/// Response:
+/// ```json
+/// {
+///   "snapshot": "00000003-0000001B-1",
+///   "idleTimeoutSeconds": 300
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+///
+/// # Returns
+///
+/// JSON response containing the snapshot identifier
+pub async fn create_snapshot_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+) -> Response {
+    if !database.capabilities().supports_snapshots {
+        return (
+            StatusCode::NOT_IMPLEMENTED,
+            Json(serde_json::json!({
+                "error": "Not supported: Snapshot reads are not supported by this database"
+            })),
+        )
+            .into_response();
+    }
+
+    match database.begin_snapshot().await {
+        Ok(response) => (StatusCode::CREATED, Json(response)).into_response(),
+        Err(error) => {
+            eprintln!("Failed to open snapshot: {}", error);
+
+            let status = if error.to_string().contains("Not supported") {
+                StatusCode::NOT_IMPLEMENTED
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for DELETE /api/snapshots/:snapshot
+///
+/// Releases a snapshot opened with `POST /api/snapshots`.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `snapshot` - Identifier of the snapshot to release
+///
+/// # Returns
+///
+/// Empty response with status 204 on success
+pub async fn release_snapshot_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(snapshot): Path<String>,
+) -> Response {
+    match database.release_snapshot(&snapshot).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(error) => {
+            eprintln!("Failed to release snapshot '{}': {}", snapshot, error);
+
+            let status = if error.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/snapshots/named
+///
+/// Lists the named snapshots with the tables each one holds.
+///
+/// Response:
+/// ```json
+/// [
+///   {
+///     "name": "before-checkout",
+///     "tables": ["orders", "users"],
+///     "createdAtMilliseconds": 1760612400000
+///   }
+/// ]
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+///
+/// # Returns
+///
+/// JSON response containing the named snapshots
+pub async fn list_named_snapshots_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+) -> Response {
+    match database.list_named_snapshots().await {
+        Ok(snapshots) => (StatusCode::OK, Json(snapshots)).into_response(),
+        Err(error) => {
+            eprintln!("Failed to list named snapshots: {}", error);
+            named_snapshot_error(error)
+        }
+    }
+}
+
+/// Handler for POST /api/snapshots/named
+///
+/// Starts a job copying the rows of one table, or of all tables, into a named
+/// snapshot, replacing any snapshot of the same name. Responds with 202
+/// Accepted and the job; its result is the new snapshot.
+///
+/// Request:
+/// ```json
+/// {
+///   "name": "before-checkout",
+///   "table": "orders"
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `jobs` - Job registry from state
+/// * `request` - Name of the snapshot and the table to copy (all if omitted)
+///
+/// # Returns
+///
+/// JSON response containing the started job, or 400 if the name is not usable
+pub async fn save_named_snapshot_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(jobs): State<JobRegistry>,
+    Json(request): Json<SaveSnapshotRequest>,
+) -> Response {
+    if let Err(error) = validate_snapshot_name(&request.name) {
+        return named_snapshot_error(error);
+    }
+
+    start_named_snapshot_job(&jobs, "snapshot.save", async move {
+        database
+            .save_named_snapshot(&request.name, request.table.as_deref())
+            .await
+    })
+    .await
+}
+
+/// Handler for POST /api/snapshots/named/:name/restore
+///
+/// Starts a job replacing the rows of the snapshot's tables with the rows in
+/// the snapshot. The tables must still have the snapshot's columns. Responds
+/// with 202 Accepted and the job; its result is the restored snapshot.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `jobs` - Job registry from state
+/// * `name` - Name of the snapshot
+///
+/// # Returns
+///
+/// JSON response containing the started job
+pub async fn restore_named_snapshot_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(jobs): State<JobRegistry>,
+    Path(name): Path<String>,
+) -> Response {
+    start_named_snapshot_job(&jobs, "snapshot.restore", async move {
+        database.restore_named_snapshot(&name).await
+    })
+    .await
+}
+
+/// Handler for DELETE /api/snapshots/named/:name
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `name` - Name of the snapshot
+///
+/// # Returns
+///
+/// Empty response with status 204 on success, 404 if there is no such snapshot
+pub async fn delete_named_snapshot_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(name): Path<String>,
+) -> Response {
+    match database.delete_named_snapshot(&name).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(error) => {
+            eprintln!("Failed to delete named snapshot '{}': {}", name, error);
+            named_snapshot_error(error)
+        }
+    }
+}
+
+/// Handler for GET /api/snapshots/named/:name/diff
+///
+/// Compares a table with its copy in a named snapshot. Rows are paired by
+/// primary key: `changed` rows exist on both sides with other values (listing
+/// the differing columns), `added` rows only in the table and `removed` rows
+/// only in the snapshot. At most 1000 differing rows are read from each side;
+/// `truncated` is set if there are more.
+///
+/// The `table` query parameter may be left out for snapshots of a single table.
+///
+/// Response:
+/// ```json
+/// {
+///   "snapshot": "before-checkout",
+///   "table": "orders",
+///   "added": [{ "id": 12, "status": "new" }],
+///   "removed": [],
+///   "changed": [
+///     {
+///       "before": { "id": 7, "status": "new" },
+///       "after": { "id": 7, "status": "paid" },
+///       "columns": ["status"]
+///     }
+///   ],
+///   "truncated": false
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `name` - Name of the snapshot
+/// * `query` - Table to compare
+///
+/// # Returns
+///
+/// JSON response containing the differences, 404 if the snapshot or the table
+/// does not exist
+pub async fn named_snapshot_diff_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path(name): Path<String>,
+    Query(query): Query<SnapshotDiffQuery>,
+) -> Response {
+    let table = match query.table {
+        Some(table) => table,
+        None => match single_table(&*database, &name).await {
+            Ok(table) => table,
+            Err(response) => return response,
+        },
+    };
+
+    let diff = async {
+        let schema = database.get_table_schema(&table).await?;
+        let differences = database
+            .named_snapshot_differences(&name, &schema.name, MAX_DIFF_ROWS)
+            .await?;
+        Ok::<_, DatabaseError>(diff_rows(&name, &schema, differences))
+    };
+
+    match diff.await {
+        Ok(diff) => (StatusCode::OK, Json(diff)).into_response(),
+        Err(error) => {
+            eprintln!(
+                "Failed to compare with named snapshot '{}': {}",
+                name, error
+            );
+            named_snapshot_error(error)
+        }
+    }
+}
+
+/// The only table of the named snapshot `name`, for comparisons without a `table`
+async fn single_table<DB: DatabaseProvider>(database: &DB, name: &str) -> Result<String, Response> {
+    let snapshots = database
+        .list_named_snapshots()
+        .await
+        .map_err(named_snapshot_error)?;
+    let snapshot = snapshots
+        .into_iter()
+        .find(|snapshot| snapshot.name == name)
+        .ok_or_else(|| named_snapshot_error(DatabaseError::SnapshotNotFound(name.to_string())))?;
+
+    <[String; 1]>::try_from(snapshot.tables)
+        .map(|[table]| table)
+        .map_err(|tables| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!(
+                        "Snapshot '{}' holds {} tables; choose one with the table parameter",
+                        name,
+                        tables.len()
+                    )
+                })),
+            )
+                .into_response()
+        })
+}
+
+/// Start a named snapshot job unless another one is still running
+async fn start_named_snapshot_job<F>(jobs: &JobRegistry, kind: &str, work: F) -> Response
+where
+    F: Future<Output = Result<NamedSnapshot, DatabaseError>> + Send + 'static,
+{
+    // A restore must not read a snapshot that is being replaced, nor overlap another restore
+    if jobs.is_running(|running_kind| running_kind.starts_with("snapshot.")) {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "Another snapshot job is still running"
+            })),
+        )
+            .into_response();
+    }
+
+    let job = jobs
+        .start(kind, async move {
+            let snapshot = work.await.map_err(|error| {
+                eprintln!("Snapshot job failed: {}", error);
+                error.to_string()
+            })?;
+            serde_json::to_value(snapshot).map_err(|error| error.to_string())
+        })
+        .await;
+
+    (StatusCode::ACCEPTED, Json(job)).into_response()
+}
+
+/// Error response for a failed named snapshot request
+fn named_snapshot_error(error: DatabaseError) -> Response {
+    let status = match error {
+        DatabaseError::SnapshotNotFound(_) | DatabaseError::TableNotFound(_) => {
+            StatusCode::NOT_FOUND
+        }
+        DatabaseError::InvalidSnapshotName(_) | DatabaseError::AmbiguousTable(..) => {
+            StatusCode::BAD_REQUEST
+        }
+        DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "error": error.to_string()
+        })),
+    )
+        .into_response()
+}
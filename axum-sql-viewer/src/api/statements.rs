@@ -0,0 +1,81 @@
+//! Recent statements endpoints backed by the sqlx query log
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::query_log::{LoggedStatement, QueryLog};
+
+/// Maximum number of statements returned per request
+const MAX_STATEMENTS: usize = 1000;
+
+/// Query parameters for fetching recent statements
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentStatementsQuery {
+    /// Only return statements newer than this identifier
+    pub since: Option<u64>,
+
+    /// Maximum number of statements to return
+    #[serde(default = "default_statement_limit")]
+    pub limit: usize,
+}
+
+fn default_statement_limit() -> usize {
+    100
+}
+
+/// Response containing recently executed statements
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentStatementsResponse {
+    /// Statements, newest first
+    pub statements: Vec<LoggedStatement>,
+}
+
+/// Create a router serving the recent statements endpoints
+///
+/// # Arguments
+///
+/// * `query_log` - Query log receiving sqlx statement events
+pub fn create_statements_router(query_log: QueryLog) -> Router {
+    Router::new()
+        .route(
+            "/statements",
+            get(recent_statements_handler).delete(clear_statements_handler),
+        )
+        .with_state(query_log)
+}
+
+/// Handler for GET /api/statements
+///
+/// Returns the statements most recently executed through the wrapped pool,
+/// including the host application's own queries.
+///
+/// Query parameters:
+/// - since: Only return statements with a greater id (for incremental polling)
+/// - limit: Maximum statements to return (default: 100, max: 1000)
+pub async fn recent_statements_handler(
+    State(query_log): State<QueryLog>,
+    Query(query): Query<RecentStatementsQuery>,
+) -> Response {
+    let statements = query_log.recent(query.since, query.limit.min(MAX_STATEMENTS));
+    (
+        StatusCode::OK,
+        Json(RecentStatementsResponse { statements }),
+    )
+        .into_response()
+}
+
+/// Handler for DELETE /api/statements
+///
+/// Clears the recorded statements.
+pub async fn clear_statements_handler(State(query_log): State<QueryLog>) -> Response {
+    query_log.clear();
+    StatusCode::NO_CONTENT.into_response()
+}
@@ -1,30 +1,50 @@
 //! Table listing and schema endpoints
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Json, Response},
 };
+use futures_util::StreamExt;
 use std::sync::Arc;
 
-use crate::database::traits::DatabaseProvider;
-use crate::schema::TablesResponse;
+use crate::database::limits::TABLE_COUNT_CONCURRENCY;
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::schema::{HistogramQuery, RowQuery, TableInfo, TablesQuery, TablesResponse};
+use crate::schema_cache::SchemaCache;
 
 /// Handler for GET /api/tables
 ///
-/// Returns a list of all tables in the database with row counts.
+/// Returns a list of all tables in the database with row counts. Counts are
+/// estimates from the database's statistics (`pg_class.reltuples`,
+/// `sqlite_stat1`) and counts kept by the schema cache; both come back with
+/// `exact: false` and the time they were taken in `asOfMilliseconds`.
+///
+/// Query parameters:
+/// - exact: "true" to count the rows of every table with `COUNT(*)` (slow on
+///   large databases; bypasses the schema cache)
 ///
 /// # Arguments
 ///
 /// * `database` - Database provider from state
+/// * `schema_cache` - Cache answering the request if the tables were listed before
+/// * `query` - Whether to count the rows exactly
 ///
 /// # Returns
 ///
 /// JSON response containing list of tables
 pub async fn list_tables_handler<DB: DatabaseProvider>(
     State(database): State<Arc<DB>>,
+    State(schema_cache): State<SchemaCache>,
+    Query(query): Query<TablesQuery>,
 ) -> Response {
-    match database.list_tables().await {
+    let tables = if query.exact {
+        list_tables_counted(database.as_ref()).await
+    } else {
+        schema_cache.list_tables(database.as_ref()).await
+    };
+
+    match tables {
         Ok(tables) => (StatusCode::OK, Json(TablesResponse { tables })).into_response(),
         Err(error) => {
             eprintln!("Failed to list tables: {}", error);
@@ -39,6 +59,65 @@ pub async fn list_tables_handler<DB: DatabaseProvider>(
     }
 }
 
+/// Handler for POST /api/cache/invalidate
+///
+/// Empties the schema cache, so the table list and table schemas are read
+/// from the database again (e.g. after the schema was changed outside the
+/// viewer). Does nothing if the cache is disabled.
+///
+/// # Arguments
+///
+/// * `schema_cache` - Cache to empty
+///
+/// # Returns
+///
+/// 204 No Content
+pub async fn invalidate_cache_handler(State(schema_cache): State<SchemaCache>) -> Response {
+    schema_cache.clear();
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// List all tables with the rows of each counted exactly
+///
+/// A few tables are counted at a time; tables whose rows cannot be counted
+/// are listed without a count.
+async fn list_tables_counted<DB: DatabaseProvider>(
+    database: &DB,
+) -> Result<Vec<TableInfo>, DatabaseError> {
+    let all_rows = RowQuery {
+        offset: 0,
+        limit: 0,
+        sort_by: None,
+        sort_order: None,
+        nulls: None,
+        filters: Default::default(),
+        ranges: Vec::new(),
+        filter_groups: Vec::new(),
+        snapshot: None,
+        cursor: None,
+        count: true,
+        columns: None,
+        case_insensitive: false,
+        q: None,
+    };
+
+    let tables = database.list_tables().await?;
+    let tables = futures_util::stream::iter(tables)
+        .map(|mut table| {
+            let all_rows = &all_rows;
+            async move {
+                let count = database.count_rows(&table.name, all_rows).await.ok();
+                table.row_count = count.as_ref().map(|count| count.count);
+                table.row_count_accuracy = count.map(|count| count.accuracy);
+                table
+            }
+        })
+        .buffered(TABLE_COUNT_CONCURRENCY)
+        .collect()
+        .await;
+    Ok(tables)
+}
+
 /// Handler for GET /api/tables/:name
 ///
 /// Returns the schema information for a specific table including columns,
@@ -47,6 +126,7 @@ pub async fn list_tables_handler<DB: DatabaseProvider>(
 /// # Arguments
 ///
 /// * `database` - Database provider from state
+/// * `schema_cache` - Cache answering the request if the schema was loaded before
 /// * `table_name` - Name of the table to get schema for
 ///
 /// # Returns
@@ -54,9 +134,13 @@ pub async fn list_tables_handler<DB: DatabaseProvider>(
 /// JSON response containing table schema information
 pub async fn get_table_schema_handler<DB: DatabaseProvider>(
     State(database): State<Arc<DB>>,
+    State(schema_cache): State<SchemaCache>,
     Path(table_name): Path<String>,
 ) -> Response {
-    match database.get_table_schema(&table_name).await {
+    match schema_cache
+        .table_schema(database.as_ref(), &table_name)
+        .await
+    {
         Ok(schema) => (StatusCode::OK, Json(schema)).into_response(),
         Err(error) => {
             eprintln!("Failed to get schema for table '{}': {}", table_name, error);
@@ -78,3 +162,141 @@ pub async fn get_table_schema_handler<DB: DatabaseProvider>(
         }
     }
 }
+
+/// Handler for GET /api/tables/:name/columns/:column/stats
+///
+/// Returns summary statistics of a column, computed by the database in one
+/// aggregate query. Averages are only set for numeric columns, minimum and
+/// maximum for numbers, text, dates and times. On PostgreSQL the distinct
+/// count is the planner's estimate once the table has been analyzed
+/// (`distinctExact: false`); otherwise it is counted.
+///
+/// Response:
+/// ```json
+/// {
+///   "table": "orders",
+///   "column": "total",
+///   "dataType": "numeric",
+///   "rowCount": 1234,
+///   "nullCount": 12,
+///   "distinctCount": 980,
+///   "distinctExact": false,
+///   "min": 0.5,
+///   "max": 1999.99,
+///   "avg": 87.31
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table
+/// * `column` - Name of the column
+///
+/// # Returns
+///
+/// JSON response containing the column statistics, 404 if the table or
+/// column does not exist or 501 if the database has no column statistics
+pub async fn column_stats_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path((table_name, column)): Path<(String, String)>,
+) -> Response {
+    match database.column_stats(&table_name, &column).await {
+        Ok(stats) => (StatusCode::OK, Json(stats)).into_response(),
+        Err(error) => {
+            eprintln!(
+                "Failed to compute statistics of column '{}' of table '{}': {}",
+                column, table_name, error
+            );
+
+            let status = match error {
+                DatabaseError::TableNotFound(_) | DatabaseError::InvalidColumn(_) => {
+                    StatusCode::NOT_FOUND
+                }
+                DatabaseError::AmbiguousTable(..) => StatusCode::BAD_REQUEST,
+                DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Handler for GET /api/tables/:name/columns/:column/histogram
+///
+/// Returns the distribution of a numeric or date column's values in buckets
+/// of equal width between its smallest and largest value, for drawing small
+/// histograms. Date and timestamp bounds are ISO 8601 timestamps in UTC.
+///
+/// Query parameters:
+/// - buckets: Number of buckets (default: 20, at most 100)
+///
+/// Response:
+/// ```json
+/// {
+///   "table": "orders",
+///   "column": "total",
+///   "dataType": "numeric",
+///   "scale": "number",
+///   "buckets": [
+///     { "lower": 0.0, "upper": 50.0, "count": 812 },
+///     { "lower": 50.0, "upper": 100.0, "count": 301 }
+///   ],
+///   "nullCount": 12
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `table_name` - Name of the table
+/// * `column` - Name of the column
+/// * `query` - Number of buckets
+///
+/// # Returns
+///
+/// JSON response containing the buckets, 404 if the table does not exist,
+/// 400 if the column does not exist or is not numeric or a date, or 501 if
+/// the database has no histograms
+pub async fn column_histogram_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    Path((table_name, column)): Path<(String, String)>,
+    Query(query): Query<HistogramQuery>,
+) -> Response {
+    match database
+        .column_histogram(&table_name, &column, query.buckets)
+        .await
+    {
+        Ok(histogram) => (StatusCode::OK, Json(histogram)).into_response(),
+        Err(error) => {
+            eprintln!(
+                "Failed to compute histogram of column '{}' of table '{}': {}",
+                column, table_name, error
+            );
+
+            let status = match error {
+                DatabaseError::TableNotFound(_) => StatusCode::NOT_FOUND,
+                DatabaseError::InvalidColumn(_) | DatabaseError::AmbiguousTable(..) => {
+                    StatusCode::BAD_REQUEST
+                }
+                DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": error.to_string()
+                })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Interactive transaction endpoints
+//!
+//! A transaction is a console session with a `BEGIN` issued on its dedicated
+//! connection, so writes made through `POST /api/tx/:tx/query` stay invisible
+//! to other requests until they are committed, and can be rolled back. A
+//! transaction left idle is rolled back when its session expires.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::console::ConsoleHistory;
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::schema::TransactionResponse;
+
+/// Handler for POST /api/tx/begin
+///
+/// Opens a session on a dedicated connection and starts a transaction on it.
+/// Statements are then run with `POST /api/tx/:tx/query` (same request and
+/// response as `/api/query`) until the transaction is committed or rolled
+/// back.
+///
+/// Response (status 201):
+/// ```json
+/// {
+///   "tx": "192f1c2a3b4-1",
+///   "idleTimeoutSeconds": 600
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `history` - Console history from state
+///
+/// # Returns
+///
+/// JSON response containing the transaction identifier
+pub async fn begin_transaction_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(history): State<ConsoleHistory>,
+) -> Response {
+    if !database.capabilities().supports_sessions {
+        return error_response(
+            StatusCode::NOT_IMPLEMENTED,
+            "Not supported: Transactions are not supported by this database",
+        );
+    }
+
+    let session = match database.open_session().await {
+        Ok(session) => session,
+        Err(error) => {
+            eprintln!("Failed to open transaction session: {}", error);
+            return database_error_response(&error);
+        }
+    };
+
+    let failure = match database
+        .execute_in_session(&session.session, "BEGIN", &[])
+        .await
+    {
+        Ok(result) => result.error,
+        Err(error) => Some(error.to_string()),
+    };
+    if let Some(message) = failure {
+        eprintln!("Failed to begin transaction: {}", message);
+        let _ = database.close_session(&session.session).await;
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, &message);
+    }
+
+    history.open(
+        &session.session,
+        Duration::from_secs(session.idle_timeout_seconds),
+    );
+    (
+        StatusCode::CREATED,
+        Json(TransactionResponse {
+            tx: session.session,
+            idle_timeout_seconds: session.idle_timeout_seconds,
+        }),
+    )
+        .into_response()
+}
+
+/// Handler for POST /api/tx/:tx/commit
+///
+/// Commits the transaction and closes its session. A transaction a failed
+/// statement has aborted (PostgreSQL) is not committed: the request fails
+/// with status 409 and the transaction stays open so it can be rolled back.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `history` - Console history from state
+/// * `tx` - Identifier of the transaction
+///
+/// # Returns
+///
+/// Empty response with status 204 on success
+pub async fn commit_transaction_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(history): State<ConsoleHistory>,
+    Path(tx): Path<String>,
+) -> Response {
+    // PostgreSQL answers COMMIT in an aborted transaction with a silent
+    // rollback, so check that statements still run first
+    for sql in ["SELECT 1", "COMMIT"] {
+        match database.execute_in_session(&tx, sql, &[]).await {
+            Ok(result) => {
+                if let Some(message) = result.error {
+                    eprintln!("Failed to commit transaction '{}': {}", tx, message);
+                    return error_response(StatusCode::CONFLICT, &message);
+                }
+            }
+            Err(error) => {
+                eprintln!("Failed to commit transaction '{}': {}", tx, error);
+                if let DatabaseError::SessionNotFound(_) = error {
+                    history.close(&tx);
+                }
+                return database_error_response(&error);
+            }
+        }
+    }
+
+    close(database.as_ref(), &history, &tx).await
+}
+
+/// Handler for POST /api/tx/:tx/rollback
+///
+/// Rolls back the transaction and closes its session.
+///
+/// # Arguments
+///
+/// * `database` - Database provider from state
+/// * `history` - Console history from state
+/// * `tx` - Identifier of the transaction
+///
+/// # Returns
+///
+/// Empty response with status 204 on success
+pub async fn rollback_transaction_handler<DB: DatabaseProvider>(
+    State(database): State<Arc<DB>>,
+    State(history): State<ConsoleHistory>,
+    Path(tx): Path<String>,
+) -> Response {
+    // Closing the session drops its connection, which rolls the transaction back
+    close(database.as_ref(), &history, &tx).await
+}
+
+/// Close the session of a transaction
+async fn close<DB: DatabaseProvider>(
+    database: &DB,
+    history: &ConsoleHistory,
+    tx: &str,
+) -> Response {
+    history.close(tx);
+
+    match database.close_session(tx).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(error) => {
+            eprintln!("Failed to close transaction '{}': {}", tx, error);
+            database_error_response(&error)
+        }
+    }
+}
+
+fn database_error_response(error: &DatabaseError) -> Response {
+    let status = match error {
+        DatabaseError::SessionNotFound(_) => StatusCode::NOT_FOUND,
+        DatabaseError::Timeout(_) => StatusCode::REQUEST_TIMEOUT,
+        DatabaseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+        _ if error.to_string().contains("busy") => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error_response(status, &error.to_string())
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": message
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1,130 @@
+//! Wire format versioning
+//!
+//! Scripts built against the JSON API can pin the version they were written
+//! for with the `X-Api-Version` request header. Every response carries the
+//! version it was produced for in the same header; requests without it get
+//! the current version, and requests for a version that is not supported are
+//! answered with 400 Bad Request instead of a response the script cannot read.
+//!
+//! Stability policy, for every supported version:
+//!
+//! - Response fields are never removed, renamed or changed in type. New fields
+//!   may be added at any time, so clients must ignore fields they don't know.
+//! - Request fields and query parameters keep their meaning. New optional ones
+//!   may be added; new required ones are not.
+//! - Endpoints are not removed; new endpoints may be added.
+//! - Error messages are meant for people and may change; status codes don't.
+//!
+//! Changes breaking these rules raise [`API_VERSION`]. The previous version
+//! stays in [`SUPPORTED_API_VERSIONS`] for at least one minor release of the
+//! crate after that, so pinned scripts keep working while they are migrated.
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+
+/// Current version of the JSON API
+pub const API_VERSION: u32 = 1;
+
+/// Versions requests may ask for with `X-Api-Version`
+pub const SUPPORTED_API_VERSIONS: &[u32] = &[1];
+
+/// Header carrying the API version of requests and responses
+pub const API_VERSION_HEADER: HeaderName = HeaderName::from_static("x-api-version");
+
+/// Middleware checking the requested API version and stating it in the response
+pub async fn negotiate_api_version(request: Request, next: Next) -> Response {
+    let version = match requested_api_version(request.headers()) {
+        Ok(version) => version,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                [(API_VERSION_HEADER, HeaderValue::from(API_VERSION))],
+                Json(serde_json::json!({
+                    "error": message,
+                    "supportedVersions": SUPPORTED_API_VERSIONS
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert(API_VERSION_HEADER, HeaderValue::from(version));
+    response
+}
+
+/// Version asked for by a request (the current one if it doesn't say)
+///
+/// # Returns
+///
+/// The version, or an error message if it is malformed or not supported
+pub fn requested_api_version(headers: &HeaderMap) -> Result<u32, String> {
+    let Some(value) = headers.get(API_VERSION_HEADER) else {
+        return Ok(API_VERSION);
+    };
+
+    let text = value.to_str().unwrap_or_default().trim();
+    // Accept `2` as well as `v2`
+    let version: u32 = text
+        .strip_prefix(['v', 'V'])
+        .unwrap_or(text)
+        .parse()
+        .map_err(|_| format!("Invalid API version '{}'", text))?;
+
+    if !SUPPORTED_API_VERSIONS.contains(&version) {
+        return Err(format!(
+            "Invalid API version {}: supported versions are {}",
+            version,
+            SUPPORTED_API_VERSIONS
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(version: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(API_VERSION_HEADER, HeaderValue::from_str(version).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_default_version() {
+        assert_eq!(requested_api_version(&HeaderMap::new()), Ok(API_VERSION));
+    }
+
+    #[test]
+    fn test_parse_version_header() {
+        assert_eq!(requested_api_version(&headers("1")), Ok(1));
+        assert_eq!(requested_api_version(&headers(" v1 ")), Ok(1));
+        assert_eq!(requested_api_version(&headers("V1")), Ok(1));
+
+        for malformed in ["", "one", "1.0", "-1", "vv1"] {
+            let error = requested_api_version(&headers(malformed)).unwrap_err();
+            assert!(error.starts_with("Invalid API version '"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_reject_unsupported_version() {
+        assert_eq!(
+            requested_api_version(&headers("99")),
+            Err("Invalid API version 99: supported versions are 1".to_string())
+        );
+        assert!(requested_api_version(&headers("0")).is_err());
+    }
+}
@@ -0,0 +1,303 @@
+//! Export artifacts
+//!
+//! Export jobs write their output to a file in the artifact directory. The
+//! file is kept for a retention window after it was produced, so an
+//! interrupted download can be resumed with a `Range` request instead of
+//! starting over. Expired artifacts are deleted the next time the store is used.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::runtime;
+
+/// How long artifacts are kept by default
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// An export file available for download
+#[derive(Debug, Clone)]
+pub struct Artifact {
+    /// Artifact identifier (used in the download URL)
+    pub id: String,
+
+    /// Location of the file
+    pub path: PathBuf,
+
+    /// File name suggested to the client
+    pub file_name: String,
+
+    /// Media type of the file
+    pub content_type: &'static str,
+
+    /// Size in bytes (0 until the artifact is complete)
+    pub size: u64,
+
+    /// Whether the file has been fully written
+    pub complete: bool,
+
+    created: Instant,
+}
+
+impl Artifact {
+    /// Strong entity tag identifying this version of the artifact
+    pub fn etag(&self) -> String {
+        format!("\"{}-{}\"", self.id, self.size)
+    }
+}
+
+/// Store of export artifacts
+///
+/// Cloning an `ArtifactStore` is cheap; all clones share the same artifacts.
+#[derive(Clone)]
+pub struct ArtifactStore {
+    inner: Arc<ArtifactStoreInner>,
+}
+
+struct ArtifactStoreInner {
+    directory: PathBuf,
+    retention: Duration,
+    next_id: AtomicU64,
+    artifacts: Mutex<HashMap<String, Artifact>>,
+}
+
+/// Directory artifacts are written to by default
+pub fn default_directory() -> PathBuf {
+    std::env::temp_dir().join("axum-sql-viewer-exports")
+}
+
+impl Default for ArtifactStore {
+    fn default() -> Self {
+        Self::new(default_directory(), DEFAULT_RETENTION)
+    }
+}
+
+impl ArtifactStore {
+    /// Create a store keeping artifacts in `directory` for `retention`
+    ///
+    /// The directory is created when the first artifact is written.
+    pub fn new(directory: impl Into<PathBuf>, retention: Duration) -> Self {
+        Self {
+            inner: Arc::new(ArtifactStoreInner {
+                directory: directory.into(),
+                retention,
+                next_id: AtomicU64::new(0),
+                artifacts: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// How long artifacts are kept after they were produced
+    pub fn retention(&self) -> Duration {
+        self.inner.retention
+    }
+
+    /// Register a new, still empty artifact and create its directory
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - File name suggested to the client (e.g. "users.csv")
+    /// * `content_type` - Media type of the file
+    ///
+    /// # Returns
+    ///
+    /// The artifact; write its file at `path`, then call [`ArtifactStore::complete`]
+    pub async fn create(
+        &self,
+        file_name: &str,
+        content_type: &'static str,
+    ) -> std::io::Result<Artifact> {
+        self.remove_expired().await;
+        runtime::create_dir_all(&self.inner.directory).await?;
+
+        // The start time keeps identifiers unique across restarts sharing a directory
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or(0);
+        let sequence = self.inner.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let id = format!("{:x}-{}", started, sequence);
+
+        let artifact = Artifact {
+            path: self.inner.directory.join(format!("{}.export", id)),
+            id: id.clone(),
+            file_name: file_name.to_string(),
+            content_type,
+            size: 0,
+            complete: false,
+            created: Instant::now(),
+        };
+        self.inner
+            .artifacts
+            .lock()
+            .unwrap()
+            .insert(id, artifact.clone());
+
+        Ok(artifact)
+    }
+
+    /// Mark an artifact as fully written; its retention window starts now
+    pub fn complete(&self, id: &str, size: u64) -> Option<Artifact> {
+        let mut artifacts = self.inner.artifacts.lock().unwrap();
+        let artifact = artifacts.get_mut(id)?;
+        artifact.size = size;
+        artifact.complete = true;
+        artifact.created = Instant::now();
+        Some(artifact.clone())
+    }
+
+    /// Forget an artifact and delete its file (e.g. after a failed export)
+    pub async fn remove(&self, id: &str) {
+        let artifact = self.inner.artifacts.lock().unwrap().remove(id);
+        if let Some(artifact) = artifact {
+            let _ = runtime::remove_file(&artifact.path).await;
+        }
+    }
+
+    /// Get a complete artifact that has not expired yet
+    pub async fn get(&self, id: &str) -> Option<Artifact> {
+        self.remove_expired().await;
+        let artifacts = self.inner.artifacts.lock().unwrap();
+        artifacts
+            .get(id)
+            .filter(|artifact| artifact.complete)
+            .cloned()
+    }
+
+    /// Delete complete artifacts older than the retention window
+    async fn remove_expired(&self) {
+        let expired: Vec<Artifact> = {
+            let mut artifacts = self.inner.artifacts.lock().unwrap();
+            let retention = self.inner.retention;
+            let expired_ids: Vec<String> = artifacts
+                .values()
+                .filter(|artifact| artifact.complete && artifact.created.elapsed() > retention)
+                .map(|artifact| artifact.id.clone())
+                .collect();
+            expired_ids
+                .iter()
+                .filter_map(|id| artifacts.remove(id))
+                .collect()
+        };
+
+        for artifact in expired {
+            let _ = runtime::remove_file(&artifact.path).await;
+        }
+    }
+}
+
+/// Outcome of evaluating a `Range` header against a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// Send the whole file (no usable range was requested)
+    Full,
+
+    /// Send bytes `start..=end`
+    Partial { start: u64, end: u64 },
+
+    /// The requested range lies outside the file
+    Unsatisfiable,
+}
+
+/// Evaluate a `Range` header for a file of `size` bytes
+///
+/// Single byte ranges (`bytes=0-499`, `bytes=500-`, `bytes=-500`) are
+/// supported. Headers that cannot be parsed, other units and multiple ranges
+/// are ignored, so the whole file is sent, as allowed by RFC 9110.
+pub fn parse_range(header: &str, size: u64) -> ByteRange {
+    let Some(specification) = header.trim().strip_prefix("bytes=") else {
+        return ByteRange::Full;
+    };
+    if specification.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((first, last)) = specification.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let parse = |text: &str| text.trim().parse::<u64>().ok();
+
+    match (first.trim().is_empty(), last.trim().is_empty()) {
+        // Suffix range: the last `n` bytes
+        (true, false) => match parse(last) {
+            Some(0) => ByteRange::Unsatisfiable,
+            Some(_) if size == 0 => ByteRange::Unsatisfiable,
+            Some(length) => ByteRange::Partial {
+                start: size.saturating_sub(length),
+                end: size - 1,
+            },
+            None => ByteRange::Full,
+        },
+        (false, open_ended) => {
+            let Some(start) = parse(first) else {
+                return ByteRange::Full;
+            };
+            let end = if open_ended {
+                u64::MAX
+            } else {
+                match parse(last) {
+                    Some(end) if end >= start => end,
+                    _ => return ByteRange::Full,
+                }
+            };
+            if start >= size {
+                ByteRange::Unsatisfiable
+            } else {
+                ByteRange::Partial {
+                    start,
+                    end: end.min(size - 1),
+                }
+            }
+        }
+        (true, true) => ByteRange::Full,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(
+            parse_range("bytes=0-499", 1000),
+            ByteRange::Partial { start: 0, end: 499 }
+        );
+        assert_eq!(
+            parse_range("bytes=500-", 1000),
+            ByteRange::Partial {
+                start: 500,
+                end: 999
+            }
+        );
+        assert_eq!(
+            parse_range("bytes=-100", 1000),
+            ByteRange::Partial {
+                start: 900,
+                end: 999
+            }
+        );
+        assert_eq!(
+            parse_range("bytes=900-5000", 1000),
+            ByteRange::Partial {
+                start: 900,
+                end: 999
+            }
+        );
+        assert_eq!(
+            parse_range("bytes=-5000", 1000),
+            ByteRange::Partial { start: 0, end: 999 }
+        );
+    }
+
+    #[test]
+    fn test_parse_range_edge_cases() {
+        assert_eq!(parse_range("bytes=1000-", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-", 0), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), ByteRange::Full);
+        assert_eq!(parse_range("bytes=5-1", 1000), ByteRange::Full);
+        assert_eq!(parse_range("items=0-1", 1000), ByteRange::Full);
+        assert_eq!(parse_range("bytes=abc", 1000), ByteRange::Full);
+    }
+}
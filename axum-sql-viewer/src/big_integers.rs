@@ -0,0 +1,66 @@
+//! 64-bit integers in JSON read by JavaScript
+//!
+//! JavaScript parses every JSON number into a double, which holds integers
+//! exactly only up to 2^53 - 1. Larger `BIGINT`/`INT8` values (IDs from
+//! snowflake generators, hashes) silently change in a JavaScript client.
+//! Providers can send them as strings of their decimal digits instead
+//! ([`BigIntegerFormat::SafeNumber`]); as that changes the type of a field
+//! for every client, numbers are kept by default. Smaller values stay numbers
+//! either way.
+
+use serde_json::Value;
+
+/// Largest integer a JavaScript number holds exactly (`Number.MAX_SAFE_INTEGER`)
+pub const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// How 64-bit integers are written in rows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BigIntegerFormat {
+    /// JSON numbers, whatever their size
+    #[default]
+    Number,
+    /// JSON numbers while JavaScript reads them exactly, strings beyond that
+    SafeNumber,
+}
+
+impl BigIntegerFormat {
+    /// JSON value of an integer in this format
+    pub fn to_json(self, value: i64) -> Value {
+        match self {
+            BigIntegerFormat::SafeNumber
+                if !(-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&value) =>
+            {
+                Value::String(value.to_string())
+            }
+            _ => Value::Number(value.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let safe = BigIntegerFormat::SafeNumber;
+        assert_eq!(safe.to_json(42), serde_json::json!(42));
+        assert_eq!(
+            safe.to_json(-MAX_SAFE_INTEGER),
+            serde_json::json!(-9_007_199_254_740_991i64)
+        );
+        assert_eq!(
+            safe.to_json(MAX_SAFE_INTEGER + 1),
+            serde_json::json!("9007199254740992")
+        );
+        assert_eq!(
+            safe.to_json(i64::MIN),
+            serde_json::json!("-9223372036854775808")
+        );
+
+        assert_eq!(
+            BigIntegerFormat::default().to_json(MAX_SAFE_INTEGER + 1),
+            serde_json::json!(9_007_199_254_740_992i64)
+        );
+    }
+}
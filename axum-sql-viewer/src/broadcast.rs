@@ -0,0 +1,89 @@
+//! Events pushed to any number of listeners
+//!
+//! Backs the server-sent event endpoints: producers publish to a
+//! [`Broadcast`], every open event stream holds a subscription.
+
+use futures_util::stream::{self, Stream};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+/// Number of recent events kept for listeners that have not caught up yet
+const RETAINED_EVENTS: usize = 16;
+
+/// Publishes events to any number of listeners
+///
+/// Cloning a `Broadcast` is cheap; all clones share the same listeners. A
+/// listener that falls more than a few events behind misses the oldest ones.
+pub struct Broadcast<T> {
+    inner: Arc<Mutex<EventLog<T>>>,
+}
+
+struct EventLog<T> {
+    /// Recent events with their sequence numbers, oldest first
+    events: VecDeque<(u64, T)>,
+    last_id: u64,
+    /// Listeners waiting for the next event
+    wakers: Vec<Waker>,
+}
+
+impl<T> Default for Broadcast<T> {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(EventLog {
+                events: VecDeque::new(),
+                last_id: 0,
+                wakers: Vec::new(),
+            })),
+        }
+    }
+}
+
+impl<T> Clone for Broadcast<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> Broadcast<T> {
+    /// Send an event to every current listener
+    pub fn publish(&self, event: T) {
+        let mut log = self.inner.lock().unwrap();
+        log.last_id += 1;
+        let id = log.last_id;
+        log.events.push_back((id, event));
+        if log.events.len() > RETAINED_EVENTS {
+            log.events.pop_front();
+        }
+        for waker in log.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Listen for events published from now on
+    ///
+    /// The stream never ends. It does not depend on an async runtime, so it
+    /// works with any executor.
+    pub fn subscribe(&self) -> impl Stream<Item = T> + Send + 'static {
+        let inner = self.inner.clone();
+        let mut seen = inner.lock().unwrap().last_id;
+
+        stream::poll_fn(move |context| {
+            let mut log = inner.lock().unwrap();
+            if let Some((id, event)) = log.events.iter().find(|(id, _)| *id > seen) {
+                seen = *id;
+                return Poll::Ready(Some(event.clone()));
+            }
+            if !log
+                .wakers
+                .iter()
+                .any(|waker| waker.will_wake(context.waker()))
+            {
+                log.wakers.push(context.waker().clone());
+            }
+            Poll::Pending
+        })
+    }
+}
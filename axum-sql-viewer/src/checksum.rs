@@ -0,0 +1,160 @@
+//! Cheap fingerprints of a table's data
+//!
+//! A checksum is built from a single aggregate query: the row count, the
+//! highest primary key (or row id), the latest value of a column like
+//! `updated_at` and, on PostgreSQL, the newest row version (`xmin`). Inserts
+//! and deletes always change it; updates do if the table has such a column or
+//! lives in PostgreSQL. Clients can poll it instead of refetching pages.
+
+use crate::schema::{RowAddress, TableChecksum, TableSchema};
+use crate::sql::{quote_identifier, Dialect};
+
+/// Column names (compared case-insensitively) taken as last-modified times
+const MODIFIED_AT_COLUMNS: &[&str] = &[
+    "updated_at",
+    "updatedat",
+    "modified_at",
+    "modifiedat",
+    "last_modified",
+];
+
+/// Select-list expressions whose values make up the checksum of a table
+///
+/// The first expression is the row count; the others are cast to text so
+/// every provider can read them the same way.
+pub fn checksum_expressions(schema: &TableSchema, dialect: Dialect) -> Vec<String> {
+    let latest = |expression: String| format!("CAST(MAX({}) AS TEXT)", expression);
+    let as_text = |column: &str| format!("CAST({} AS TEXT)", quote_identifier(column));
+
+    let mut expressions = vec!["COUNT(*)".to_string()];
+    match (&schema.primary_key, schema.row_address) {
+        (Some(primary_key), _) => {
+            expressions.extend(primary_key.iter().map(|column| latest(as_text(column))));
+        }
+        (None, RowAddress::Rowid) => expressions.push(latest("rowid".to_string())),
+        (None, _) => {}
+    }
+    expressions.extend(
+        schema
+            .columns
+            .iter()
+            .filter(|column| {
+                MODIFIED_AT_COLUMNS
+                    .iter()
+                    .any(|name| column.name.eq_ignore_ascii_case(name))
+            })
+            .map(|column| latest(as_text(&column.name))),
+    );
+
+    // Every insert or update writes a row version with a newer transaction id
+    let is_table = matches!(
+        schema.row_address,
+        RowAddress::PrimaryKey | RowAddress::Ctid
+    );
+    if dialect == Dialect::Postgres && is_table {
+        expressions.push(latest("xmin::text::bigint".to_string()));
+    }
+
+    expressions
+}
+
+/// Checksum of a table from the values of its [`checksum_expressions`]
+///
+/// # Arguments
+///
+/// * `table` - Name of the table
+/// * `row_count` - Value of the first expression
+/// * `values` - Values of the other expressions, in order
+pub fn table_checksum(table: &str, row_count: u64, values: &[Option<String>]) -> TableChecksum {
+    // 64-bit FNV-1a over the values, each tagged (so NULL and '' differ) and terminated
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+    feed(row_count.to_string().as_bytes());
+    feed(&[0]);
+    for value in values {
+        match value {
+            Some(text) => {
+                feed(b"v");
+                feed(text.as_bytes());
+            }
+            None => feed(b"n"),
+        }
+        feed(&[0]);
+    }
+
+    TableChecksum {
+        table: table.to_string(),
+        checksum: format!("{:016x}", hash),
+        row_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::table;
+
+    #[test]
+    fn test_checksum_expressions() {
+        let orders = table("orders")
+            .text_columns(&["id", "Updated_At"])
+            .primary_key(&["id"])
+            .build();
+        assert_eq!(
+            checksum_expressions(&orders, Dialect::Sqlite),
+            vec![
+                "COUNT(*)",
+                "CAST(MAX(CAST(\"id\" AS TEXT)) AS TEXT)",
+                "CAST(MAX(CAST(\"Updated_At\" AS TEXT)) AS TEXT)",
+            ]
+        );
+        assert_eq!(
+            checksum_expressions(&orders, Dialect::Postgres)
+                .last()
+                .unwrap(),
+            "CAST(MAX(xmin::text::bigint) AS TEXT)"
+        );
+
+        let log = table("log").text_columns(&["message"]).build();
+        assert_eq!(
+            checksum_expressions(&log, Dialect::Sqlite),
+            vec!["COUNT(*)", "CAST(MAX(rowid) AS TEXT)"]
+        );
+
+        // Views only have their row count
+        let view = table("totals")
+            .text_columns(&["total"])
+            .row_address(RowAddress::None)
+            .build();
+        assert_eq!(
+            checksum_expressions(&view, Dialect::Postgres),
+            vec!["COUNT(*)"]
+        );
+    }
+
+    #[test]
+    fn test_table_checksum() {
+        let checksum = table_checksum("orders", 3, &[Some("42".to_string()), None]);
+        assert_eq!(checksum.row_count, 3);
+        assert_eq!(checksum.checksum.len(), 16);
+        assert_eq!(
+            checksum,
+            table_checksum("orders", 3, &[Some("42".to_string()), None])
+        );
+
+        // A changed value, a NULL instead of an empty string or another count differ
+        let changed = [
+            table_checksum("orders", 3, &[Some("43".to_string()), None]),
+            table_checksum("orders", 3, &[Some("42".to_string()), Some(String::new())]),
+            table_checksum("orders", 4, &[Some("42".to_string()), None]),
+        ];
+        for other in changed {
+            assert_ne!(checksum.checksum, other.checksum);
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! Statement classification
+//!
+//! Decides what a raw SQL statement does (read rows, change rows, change the
+//! schema, control a transaction) by parsing it with `sqlparser`. This is what
+//! the providers use to pick between fetching rows and counting affected rows,
+//! and what tells read-only statements apart, e.g. for `EXPLAIN ANALYZE`.
+//!
+//! Statements `sqlparser` cannot parse (database-specific syntax it does not
+//! know yet) fall back to looking at their keywords, which errs on the side of
+//! treating them as writes.
+
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::{Query, SetExpr, Statement};
+use sqlparser::dialect::{PostgreSqlDialect, SQLiteDialect};
+use sqlparser::parser::Parser;
+
+use crate::sql::{tokenize, Dialect, Token, TokenKind};
+
+/// What kind of statement a statement is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    /// Reads data: `SELECT`, `WITH ... SELECT`, `VALUES`, `EXPLAIN`, ...
+    Query,
+    /// Changes rows: `INSERT`, `UPDATE`, `DELETE`, `MERGE`
+    Dml,
+    /// Changes the schema: `CREATE`, `ALTER`, `DROP`, `TRUNCATE`
+    Ddl,
+    /// Controls a transaction: `BEGIN`, `COMMIT`, `ROLLBACK`, `SAVEPOINT`
+    Transaction,
+    /// Anything else: `PRAGMA`, `SET`, `VACUUM`, `GRANT`, ...
+    Other,
+}
+
+/// Statement that destroys existing rows or tables, or changes rows in bulk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DestructiveKind {
+    Delete,
+    Update,
+    Drop,
+    Truncate,
+    /// `MERGE`, which can update and delete rows
+    Merge,
+    /// `INSERT ... SELECT`, or `INSERT` in a data-modifying CTE
+    Insert,
+}
+
+impl DestructiveKind {
+    /// The statement's keyword, e.g. `DELETE`
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            DestructiveKind::Delete => "DELETE",
+            DestructiveKind::Update => "UPDATE",
+            DestructiveKind::Drop => "DROP",
+            DestructiveKind::Truncate => "TRUNCATE",
+            DestructiveKind::Merge => "MERGE",
+            DestructiveKind::Insert => "INSERT",
+        }
+    }
+}
+
+/// Classification of a single statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatementClass {
+    /// What kind of statement it is
+    pub kind: StatementKind,
+
+    /// Whether running the statement leaves data and schema untouched
+    ///
+    /// A query with a data-modifying CTE, `SELECT ... INTO` or a locking clause
+    /// (`FOR UPDATE`) is not read-only. Functions with side effects are not
+    /// detected.
+    pub read_only: bool,
+
+    /// Whether the statement produces a result set (including `RETURNING`)
+    pub returns_rows: bool,
+
+    /// Set for `DELETE`, `UPDATE`, `DROP`, `TRUNCATE`, `MERGE`,
+    /// `INSERT ... SELECT` and queries with a data-modifying CTE, which the
+    /// viewer asks to confirm before running them
+    pub destructive: Option<DestructiveKind>,
+}
+
+/// Classify a single SQL statement
+///
+/// Text containing several statements is classified by its keywords only.
+pub fn classify(sql: &str, dialect: Dialect) -> StatementClass {
+    let parsed = match dialect {
+        Dialect::Sqlite => Parser::parse_sql(&SQLiteDialect {}, sql),
+        Dialect::Postgres => Parser::parse_sql(&PostgreSqlDialect {}, sql),
+    };
+
+    match parsed.as_deref() {
+        Ok([statement]) => classify_statement(statement, &words(sql, dialect)),
+        _ => classify_by_keywords(&words(sql, dialect)),
+    }
+}
+
+/// Whether a statement only reads data (see [`StatementClass::read_only`])
+pub fn is_read_only(sql: &str, dialect: Dialect) -> bool {
+    classify(sql, dialect).read_only
+}
+
+/// Keyword and identifier tokens of a statement, without comments and literals
+fn words(sql: &str, dialect: Dialect) -> Vec<Token<'_>> {
+    tokenize(sql, dialect)
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Word)
+        .collect()
+}
+
+fn classify_statement(statement: &Statement, words: &[Token<'_>]) -> StatementClass {
+    let returning = words.iter().any(|word| word.is_keyword("RETURNING"));
+    let destructive = statement_destructive(statement);
+    let class = |kind, read_only, returns_rows| StatementClass {
+        kind,
+        read_only,
+        returns_rows,
+        destructive,
+    };
+
+    match statement {
+        Statement::Query(query) => class(StatementKind::Query, query_is_read_only(query), true),
+        Statement::Explain {
+            analyze, statement, ..
+        } => {
+            // EXPLAIN ANALYZE runs the statement it explains
+            let read_only = !*analyze || classify_statement(statement, &[]).read_only;
+            class(StatementKind::Query, read_only, true)
+        }
+        Statement::Insert { .. }
+        | Statement::Update { .. }
+        | Statement::Delete { .. }
+        | Statement::Merge { .. } => class(StatementKind::Dml, false, returning),
+        Statement::CreateTable { .. }
+        | Statement::CreateView { .. }
+        | Statement::CreateIndex { .. }
+        | Statement::CreateVirtualTable { .. }
+        | Statement::CreateSchema { .. }
+        | Statement::CreateSequence { .. }
+        | Statement::AlterTable { .. }
+        | Statement::AlterIndex { .. }
+        | Statement::AlterView { .. }
+        | Statement::Drop { .. }
+        | Statement::Truncate { .. } => class(StatementKind::Ddl, false, false),
+        Statement::StartTransaction { .. }
+        | Statement::Commit { .. }
+        | Statement::Rollback { .. }
+        | Statement::Savepoint { .. }
+        | Statement::ReleaseSavepoint { .. } => class(StatementKind::Transaction, false, false),
+        // `PRAGMA name` reads a setting, `PRAGMA name = value` changes it;
+        // both (and table-valued pragmas) can return rows
+        Statement::Pragma { value, .. } => class(StatementKind::Other, value.is_none(), true),
+        _ => class(StatementKind::Other, false, false),
+    }
+}
+
+/// What a statement destroys, see [`StatementClass::destructive`]
+fn statement_destructive(statement: &Statement) -> Option<DestructiveKind> {
+    match statement {
+        Statement::Delete { .. } => Some(DestructiveKind::Delete),
+        Statement::Update { .. } => Some(DestructiveKind::Update),
+        Statement::Drop { .. } => Some(DestructiveKind::Drop),
+        Statement::Truncate { .. } => Some(DestructiveKind::Truncate),
+        Statement::Merge { .. } => Some(DestructiveKind::Merge),
+        Statement::Insert(insert) => insert.source.as_deref().and_then(|source| {
+            // A CTE of the source may destroy more than the insert itself
+            query_destructive(source).or(match &*source.body {
+                SetExpr::Values(_) => None,
+                _ => Some(DestructiveKind::Insert),
+            })
+        }),
+        Statement::Query(query) => query_destructive(query),
+        // EXPLAIN ANALYZE runs the statement it explains
+        Statement::Explain {
+            analyze: true,
+            statement,
+            ..
+        } => statement_destructive(statement),
+        _ => None,
+    }
+}
+
+/// What the data-modifying CTEs and nested statements of a query destroy
+fn query_destructive(query: &Query) -> Option<DestructiveKind> {
+    query
+        .with
+        .iter()
+        .flat_map(|with| &with.cte_tables)
+        .find_map(|cte| query_destructive(&cte.query))
+        .or_else(|| set_expression_destructive(&query.body))
+}
+
+fn set_expression_destructive(expression: &SetExpr) -> Option<DestructiveKind> {
+    match expression {
+        SetExpr::Query(query) => query_destructive(query),
+        SetExpr::SetOperation { left, right, .. } => {
+            set_expression_destructive(left).or_else(|| set_expression_destructive(right))
+        }
+        // An insert in a CTE counts whatever its source is
+        SetExpr::Insert(statement) => {
+            statement_destructive(statement).or(Some(DestructiveKind::Insert))
+        }
+        SetExpr::Update(statement) => statement_destructive(statement),
+        _ => None,
+    }
+}
+
+/// Whether a query and everything nested in it only reads
+fn query_is_read_only(query: &Query) -> bool {
+    let ctes_read_only = query
+        .with
+        .iter()
+        .flat_map(|with| &with.cte_tables)
+        .all(|cte| query_is_read_only(&cte.query));
+
+    ctes_read_only && query.locks.is_empty() && set_expression_is_read_only(&query.body)
+}
+
+fn set_expression_is_read_only(expression: &SetExpr) -> bool {
+    match expression {
+        SetExpr::Select(select) => select.into.is_none(),
+        SetExpr::Query(query) => query_is_read_only(query),
+        SetExpr::SetOperation { left, right, .. } => {
+            set_expression_is_read_only(left) && set_expression_is_read_only(right)
+        }
+        SetExpr::Values(_) | SetExpr::Table(_) => true,
+        // INSERT/UPDATE/DELETE used as a query, e.g. in a CTE
+        _ => false,
+    }
+}
+
+/// Classification from the keywords of a statement the parser does not understand
+///
+/// The statement is only considered read-only if it starts like a query and
+/// contains no keyword that writes anywhere, so `SELECT ... FOR UPDATE` is not.
+/// Likewise, it counts as destructive if any of its keywords is `DELETE`,
+/// `UPDATE`, `DROP`, `TRUNCATE` or `MERGE`, or if it has both `INSERT` and
+/// `SELECT`.
+fn classify_by_keywords(words: &[Token<'_>]) -> StatementClass {
+    const QUERY_KEYWORDS: [&str; 6] = ["SELECT", "WITH", "VALUES", "TABLE", "EXPLAIN", "SHOW"];
+    const DML_KEYWORDS: [&str; 5] = ["INSERT", "UPDATE", "DELETE", "MERGE", "REPLACE"];
+    const DDL_KEYWORDS: [&str; 4] = ["CREATE", "ALTER", "DROP", "TRUNCATE"];
+    const TRANSACTION_KEYWORDS: [&str; 7] = [
+        "BEGIN",
+        "START",
+        "COMMIT",
+        "END",
+        "ROLLBACK",
+        "SAVEPOINT",
+        "RELEASE",
+    ];
+    const WRITE_KEYWORDS: [&str; 16] = [
+        "INSERT", "UPDATE", "DELETE", "MERGE", "UPSERT", "REPLACE", "INTO", "CREATE", "ALTER",
+        "DROP", "TRUNCATE", "GRANT", "REVOKE", "COPY", "VACUUM", "REINDEX",
+    ];
+
+    let is_any = |word: &Token<'_>, keywords: &[&str]| {
+        keywords.iter().any(|keyword| word.is_keyword(keyword))
+    };
+    let first = words.first();
+    let starts_with = |keywords: &[&str]| first.is_some_and(|word| is_any(word, keywords));
+
+    let kind = if starts_with(&QUERY_KEYWORDS) {
+        StatementKind::Query
+    } else if starts_with(&DML_KEYWORDS) {
+        StatementKind::Dml
+    } else if starts_with(&DDL_KEYWORDS) {
+        StatementKind::Ddl
+    } else if starts_with(&TRANSACTION_KEYWORDS) {
+        StatementKind::Transaction
+    } else {
+        StatementKind::Other
+    };
+
+    let writes = words.iter().any(|word| is_any(word, &WRITE_KEYWORDS));
+    let destructive = words
+        .iter()
+        .find_map(|word| {
+            [
+                DestructiveKind::Delete,
+                DestructiveKind::Update,
+                DestructiveKind::Drop,
+                DestructiveKind::Truncate,
+                DestructiveKind::Merge,
+            ]
+            .into_iter()
+            .find(|kind| word.is_keyword(kind.keyword()))
+        })
+        .or_else(|| {
+            let has = |keyword| words.iter().any(|word| word.is_keyword(keyword));
+            (has("INSERT") && has("SELECT")).then_some(DestructiveKind::Insert)
+        });
+    let returning = words.iter().any(|word| word.is_keyword("RETURNING"));
+
+    StatementClass {
+        kind,
+        read_only: kind == StatementKind::Query && !writes,
+        returns_rows: match kind {
+            StatementKind::Query => true,
+            StatementKind::Dml => returning,
+            _ => starts_with(&["PRAGMA"]),
+        },
+        destructive,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_statements() {
+        let query = classify("SELECT 'DELETE' FROM \"update\"", Dialect::Sqlite);
+        assert_eq!(query.kind, StatementKind::Query);
+        assert!(query.read_only && query.returns_rows);
+
+        let insert = classify("INSERT INTO users (name) VALUES ('a')", Dialect::Sqlite);
+        assert_eq!(insert.kind, StatementKind::Dml);
+        assert!(!insert.read_only && !insert.returns_rows);
+
+        let returning = classify(
+            "UPDATE users SET name = 'b' WHERE id = 1 RETURNING id",
+            Dialect::Postgres,
+        );
+        assert_eq!(returning.kind, StatementKind::Dml);
+        assert!(returning.returns_rows);
+
+        let create = classify("CREATE TABLE t (id INTEGER)", Dialect::Sqlite);
+        assert_eq!(create.kind, StatementKind::Ddl);
+        assert_eq!(
+            classify("BEGIN", Dialect::Sqlite).kind,
+            StatementKind::Transaction
+        );
+
+        assert_eq!(insert.destructive, None);
+        assert_eq!(returning.destructive, Some(DestructiveKind::Update));
+        assert_eq!(
+            classify("DROP TABLE t", Dialect::Sqlite).destructive,
+            Some(DestructiveKind::Drop)
+        );
+        assert_eq!(
+            classify("TRUNCATE orders", Dialect::Postgres).destructive,
+            Some(DestructiveKind::Truncate)
+        );
+        assert_eq!(
+            classify("SELECT * FROM orders FOR UPDATE", Dialect::Postgres).destructive,
+            None
+        );
+
+        let pragma = classify("PRAGMA user_version", Dialect::Sqlite);
+        assert!(pragma.read_only && pragma.returns_rows);
+        assert!(!classify("PRAGMA user_version = 3", Dialect::Sqlite).read_only);
+    }
+
+    #[test]
+    fn test_is_read_only() {
+        assert!(is_read_only(
+            "-- report\nSELECT 'DELETE' FROM \"update\"",
+            Dialect::Sqlite
+        ));
+        assert!(is_read_only(
+            "WITH recent AS (SELECT * FROM orders) SELECT count(*) FROM recent",
+            Dialect::Postgres
+        ));
+        assert!(!is_read_only("DELETE FROM orders", Dialect::Sqlite));
+        assert!(!is_read_only(
+            "WITH gone AS (DELETE FROM orders RETURNING *) SELECT * FROM gone",
+            Dialect::Postgres
+        ));
+        assert!(!is_read_only(
+            "SELECT * INTO copy FROM orders",
+            Dialect::Postgres
+        ));
+        assert!(!is_read_only(
+            "SELECT * FROM orders FOR UPDATE",
+            Dialect::Postgres
+        ));
+        assert!(!is_read_only(
+            "EXPLAIN ANALYZE DELETE FROM orders",
+            Dialect::Postgres
+        ));
+    }
+
+    #[test]
+    fn test_destructive_data_modifying_statements() {
+        let destructive = |sql| classify(sql, Dialect::Postgres).destructive;
+
+        assert_eq!(
+            destructive("WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d"),
+            Some(DestructiveKind::Delete)
+        );
+        assert_eq!(
+            destructive("WITH u AS (UPDATE t SET x = 1 RETURNING *) SELECT * FROM u"),
+            Some(DestructiveKind::Update)
+        );
+        assert_eq!(
+            destructive("WITH i AS (INSERT INTO t VALUES (1) RETURNING *) SELECT * FROM i"),
+            Some(DestructiveKind::Insert)
+        );
+        assert_eq!(
+            destructive("INSERT INTO archive SELECT * FROM orders"),
+            Some(DestructiveKind::Insert)
+        );
+        assert_eq!(
+            destructive(
+                "MERGE INTO t USING s ON t.id = s.id \
+                 WHEN MATCHED THEN DELETE \
+                 WHEN NOT MATCHED THEN INSERT (id) VALUES (s.id)"
+            ),
+            Some(DestructiveKind::Merge)
+        );
+        assert_eq!(
+            destructive("EXPLAIN ANALYZE DELETE FROM orders"),
+            Some(DestructiveKind::Delete)
+        );
+
+        assert_eq!(destructive("INSERT INTO t VALUES (1), (2)"), None);
+        assert_eq!(destructive("EXPLAIN DELETE FROM orders"), None);
+        assert_eq!(
+            destructive("WITH recent AS (SELECT * FROM orders) SELECT * FROM recent"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_classify_unparsable_statements() {
+        // Several statements are classified by keywords, conservatively
+        let script = classify("SELECT 1; DELETE FROM orders", Dialect::Sqlite);
+        assert_eq!(script.kind, StatementKind::Query);
+        assert!(!script.read_only);
+        assert_eq!(script.destructive, Some(DestructiveKind::Delete));
+
+        let copy = classify(
+            "SELECT 1; INSERT INTO archive SELECT * FROM orders",
+            Dialect::Sqlite,
+        );
+        assert_eq!(copy.destructive, Some(DestructiveKind::Insert));
+    }
+}
@@ -0,0 +1,1324 @@
+//! Typed HTTP client for a running viewer
+//!
+//! Mirrors the REST API under `/api`, so integration tests and command line
+//! tools can drive a viewer without building requests by hand. Responses are
+//! decoded into the same types the server sends; errors the server answers
+//! with (`{ "error": "..." }`) become [`ClientError::Api`].
+//!
+//! ```rust,no_run
+//! use axum_sql_viewer::client::ViewerClient;
+//! use axum_sql_viewer::schema::ExportFormat;
+//!
+//! # async fn example() -> Result<(), axum_sql_viewer::client::ClientError> {
+//! let client = ViewerClient::new("http://localhost:3000/sql-viewer")?;
+//!
+//! for table in client.list_tables().await?.tables {
+//!     let csv = client.export_table(&table.name, ExportFormat::Csv).await?;
+//!     println!("{}: {} bytes", table.name, csv.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::{Method, RequestBuilder, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+
+use crate::api::version::{API_VERSION, API_VERSION_HEADER};
+use crate::column_stats::ColumnStats;
+use crate::console::SessionHistoryResponse;
+use crate::duplicates::DuplicatesResponse;
+use crate::histogram::Histogram;
+use crate::jobs::JobInfo;
+use crate::permalinks::{PermalinkResponse, SharedView};
+use crate::profile::TableProfile;
+use crate::row_key::format_row_key;
+use crate::sample::SampleResponse;
+use crate::saved_queries::{SavedQueriesResponse, SavedQuery, SavedQueryFilter, SavedQueryRequest};
+use crate::schema::{
+    ActivityResponse, AttachedDatabase, CommandsResponse, ConfigResponse, ConfirmationRequired,
+    CountResponse, DeleteRowsResponse, DumpQuery, ExplainResponse, ExportFormat, FixtureFormat,
+    FormatQueryResponse, InsertedRows, InspectQueryResponse, JournalInfo, LoadedFixture,
+    MaintenanceOperation, MigrationsResponse, NamedSnapshot, NullsOrder, OnReferenced, QueryPage,
+    QueryResult, QueryStreamLine, QueryValidation, RelatedRowsResponse, ResolveResponse, RowQuery,
+    RowResponse, RowsResponse, ScriptResult, SeedStatus, SessionResponse, SnapshotDiff,
+    SnapshotResponse, SortOrder, TableChecksum, TableSchema, TablesResponse, TransactionResponse,
+    UpdateRowResponse,
+};
+use crate::schema_search::SchemaSearchResult;
+
+/// Errors returned by [`ViewerClient`]
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The base URL cannot be parsed or cannot have paths appended
+    #[error("Invalid base URL: {0}")]
+    InvalidUrl(String),
+
+    /// The request could not be sent or the response could not be read
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The viewer answered with an error status
+    #[error("Viewer responded with {status}: {message}")]
+    Api {
+        /// Response status
+        status: StatusCode,
+
+        /// Error message from the response body (or the body itself)
+        message: String,
+    },
+}
+
+impl ClientError {
+    /// Status of the error response, if the viewer sent one
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            ClientError::Http(error) => error.status(),
+            ClientError::InvalidUrl(_) => None,
+        }
+    }
+}
+
+/// Client for the API of a viewer mounted at a base URL
+#[derive(Debug, Clone)]
+pub struct ViewerClient {
+    http: reqwest::Client,
+    base_url: Url,
+}
+
+impl ViewerClient {
+    /// Create a client for the viewer mounted at `base_url`
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - URL the viewer is served under (e.g. `http://localhost:3000/sql-viewer`)
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        Self::with_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Create a client sending its requests through `http`
+    ///
+    /// Use this to set timeouts, default headers or a proxy.
+    pub fn with_client(http: reqwest::Client, base_url: &str) -> Result<Self, ClientError> {
+        let base_url =
+            Url::parse(base_url).map_err(|error| ClientError::InvalidUrl(error.to_string()))?;
+        if base_url.cannot_be_a_base() {
+            return Err(ClientError::InvalidUrl(format!(
+                "'{}' cannot have paths appended",
+                base_url
+            )));
+        }
+        Ok(Self { http, base_url })
+    }
+
+    /// URL the viewer is served under
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    // Tables and rows
+
+    /// List all tables (`GET /api/tables`)
+    pub async fn list_tables(&self) -> Result<TablesResponse, ClientError> {
+        self.send_json(self.request(Method::GET, &["tables"])).await
+    }
+
+    /// List all tables with their rows counted exactly (`GET /api/tables?exact=true`)
+    ///
+    /// [`list_tables`](Self::list_tables) reports estimated counts; counting
+    /// every table can take minutes on large databases.
+    pub async fn list_tables_exact(&self) -> Result<TablesResponse, ClientError> {
+        self.send_json(
+            self.request(Method::GET, &["tables"])
+                .query(&[("exact", "true")]),
+        )
+        .await
+    }
+
+    /// Empty the schema cache of the viewer (`POST /api/cache/invalidate`)
+    pub async fn invalidate_cache(&self) -> Result<(), ClientError> {
+        self.send(self.request(Method::POST, &["cache", "invalidate"]))
+            .await
+            .map(drop)
+    }
+
+    /// Get the schema of a table (`GET /api/tables/:name`)
+    pub async fn table_schema(&self, table: &str) -> Result<TableSchema, ClientError> {
+        self.send_json(self.request(Method::GET, &["tables", table]))
+            .await
+    }
+
+    /// Find tables and columns by name across the schema (`GET /api/search`)
+    pub async fn search_schema(&self, text: &str) -> Result<SchemaSearchResult, ClientError> {
+        self.send_json(self.request(Method::GET, &["search"]).query(&[("q", text)]))
+            .await
+    }
+
+    /// Fetch a page of rows (`GET /api/tables/:name/rows`)
+    ///
+    /// Queries with `filter_groups` are POSTed as a JSON body instead, as only
+    /// bodies can hold them.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Table name
+    /// * `query` - Pagination, sorting, filters and snapshot
+    pub async fn get_rows(
+        &self,
+        table: &str,
+        query: &RowQuery,
+    ) -> Result<RowsResponse, ClientError> {
+        if !query.filter_groups.is_empty() {
+            // Date ranges are not part of the body
+            return self
+                .send_json(
+                    self.request(Method::POST, &["tables", table, "rows"])
+                        .query(&range_parameters(query))
+                        .json(query),
+                )
+                .await;
+        }
+
+        self.send_json(
+            self.request(Method::GET, &["tables", table, "rows"])
+                .query(&row_query_parameters(query)),
+        )
+        .await
+    }
+
+    /// Count the rows matching `filters` (`GET /api/tables/:name/count`)
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Table name
+    /// * `filters` - Filter values by column name
+    pub async fn count_rows(
+        &self,
+        table: &str,
+        filters: &[(&str, &str)],
+    ) -> Result<CountResponse, ClientError> {
+        let parameters: Vec<(String, String)> = filters
+            .iter()
+            .map(|(column, value)| (format!("filter[{}]", column), value.to_string()))
+            .collect();
+        self.send_json(
+            self.request(Method::GET, &["tables", table, "count"])
+                .query(&parameters),
+        )
+        .await
+    }
+
+    /// Fingerprint of the table's data, to poll for changes (`GET /api/tables/:name/checksum`)
+    pub async fn table_checksum(&self, table: &str) -> Result<TableChecksum, ClientError> {
+        self.send_json(self.request(Method::GET, &["tables", table, "checksum"]))
+            .await
+    }
+
+    /// Randomly picked rows of a table (`GET /api/tables/:name/sample`)
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Table name
+    /// * `size` - Number of rows to sample
+    pub async fn sample_rows(&self, table: &str, size: u64) -> Result<SampleResponse, ClientError> {
+        self.send_json(
+            self.request(Method::GET, &["tables", table, "sample"])
+                .query(&[("n", size)]),
+        )
+        .await
+    }
+
+    /// Groups of rows sharing the values of `columns` (`GET /api/tables/:name/duplicates`)
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Table name
+    /// * `columns` - Columns to compare
+    /// * `limit` - Number of groups
+    pub async fn find_duplicates(
+        &self,
+        table: &str,
+        columns: &[&str],
+        limit: u64,
+    ) -> Result<DuplicatesResponse, ClientError> {
+        self.send_json(
+            self.request(Method::GET, &["tables", table, "duplicates"])
+                .query(&[("columns", columns.join(",")), ("limit", limit.to_string())]),
+        )
+        .await
+    }
+
+    /// Data profile of a table's columns (`GET /api/tables/:name/profile`)
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Table name
+    /// * `refresh` - Compute a new profile instead of returning the cached one
+    pub async fn table_profile(
+        &self,
+        table: &str,
+        refresh: bool,
+    ) -> Result<TableProfile, ClientError> {
+        self.send_json(
+            self.request(Method::GET, &["tables", table, "profile"])
+                .query(&[("refresh", refresh)]),
+        )
+        .await
+    }
+
+    /// Statistics of a column (`GET /api/tables/:name/columns/:column/stats`)
+    pub async fn column_stats(
+        &self,
+        table: &str,
+        column: &str,
+    ) -> Result<ColumnStats, ClientError> {
+        self.send_json(self.request(Method::GET, &["tables", table, "columns", column, "stats"]))
+            .await
+    }
+
+    /// Buckets of a numeric or date column (`GET /api/tables/:name/columns/:column/histogram`)
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Table name
+    /// * `column` - Column name
+    /// * `buckets` - Number of buckets
+    pub async fn column_histogram(
+        &self,
+        table: &str,
+        column: &str,
+        buckets: u32,
+    ) -> Result<Histogram, ClientError> {
+        self.send_json(
+            self.request(
+                Method::GET,
+                &["tables", table, "columns", column, "histogram"],
+            )
+            .query(&[("buckets", buckets)]),
+        )
+        .await
+    }
+
+    /// Fetch a row by its primary key values (`GET /api/tables/:name/rows/:key`)
+    pub async fn get_row(&self, table: &str, key: &[String]) -> Result<RowResponse, ClientError> {
+        let key = format_row_key(key);
+        self.send_json(self.request(Method::GET, &["tables", table, "rows", &key]))
+            .await
+    }
+
+    /// Update columns of a row (`PATCH /api/tables/:name/rows/:key`)
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Table name
+    /// * `key` - Primary key values of the row
+    /// * `values` - New values by column name
+    pub async fn update_row(
+        &self,
+        table: &str,
+        key: &[String],
+        values: &Map<String, Value>,
+    ) -> Result<UpdateRowResponse, ClientError> {
+        let key = format_row_key(key);
+        self.send_json(
+            self.request(Method::PATCH, &["tables", table, "rows", &key])
+                .json(values),
+        )
+        .await
+    }
+
+    /// Rows referenced by a row's foreign keys (`GET /api/tables/:name/rows/:key/related`)
+    pub async fn related_rows(
+        &self,
+        table: &str,
+        key: &[String],
+    ) -> Result<RelatedRowsResponse, ClientError> {
+        let key = format_row_key(key);
+        self.send_json(self.request(Method::GET, &["tables", table, "rows", &key, "related"]))
+            .await
+    }
+
+    /// Insert rows in one transaction (`POST /api/tables/:name/rows:bulk`)
+    pub async fn insert_rows(
+        &self,
+        table: &str,
+        rows: &[Map<String, Value>],
+    ) -> Result<InsertedRows, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["tables", table, "rows:bulk"])
+                .json(rows),
+        )
+        .await
+    }
+
+    /// Delete rows by primary key values (`POST /api/tables/:name/delete`)
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Table name
+    /// * `keys` - Primary key values of each row
+    /// * `on_referenced` - Handling of rows referenced through foreign keys
+    pub async fn delete_rows(
+        &self,
+        table: &str,
+        keys: &[Vec<String>],
+        on_referenced: OnReferenced,
+    ) -> Result<DeleteRowsResponse, ClientError> {
+        let keys: Vec<String> = keys.iter().map(|key| format_row_key(key)).collect();
+        self.send_json(
+            self.request(Method::POST, &["tables", table, "delete"])
+                .json(&json!({ "keys": keys, "onReferenced": on_referenced })),
+        )
+        .await
+    }
+
+    /// Resolve a row reference (`GET /api/resolve`)
+    pub async fn resolve_row(
+        &self,
+        table: &str,
+        key: &[String],
+    ) -> Result<ResolveResponse, ClientError> {
+        self.send_json(
+            self.request(Method::GET, &["resolve"])
+                .query(&[("table", table), ("key", &format_row_key(key))]),
+        )
+        .await
+    }
+
+    // Queries
+
+    /// Execute a SQL statement (`POST /api/query`)
+    ///
+    /// A failing statement is returned as [`ClientError::Api`] with the
+    /// database's error message.
+    pub async fn execute_query(&self, sql: &str) -> Result<QueryResult, ClientError> {
+        self.execute_query_with_params(sql, &[]).await
+    }
+
+    /// Execute a SQL statement with values bound to its placeholders (`POST /api/query`)
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - Statement with placeholders (`?` on SQLite, `$1`, `$2`, ... on PostgreSQL)
+    /// * `params` - Values for the placeholders, in order
+    pub async fn execute_query_with_params(
+        &self,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<QueryResult, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["query"])
+                .json(&json!({ "sql": sql, "params": params })),
+        )
+        .await
+    }
+
+    /// Execute a SQL statement with values bound to named placeholders (`POST /api/query`)
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - Statement with named placeholders (`:name`), each usable several times
+    /// * `params` - Values by placeholder name
+    pub async fn execute_query_with_named_params(
+        &self,
+        sql: &str,
+        params: &Map<String, Value>,
+    ) -> Result<QueryResult, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["query"])
+                .json(&json!({ "sql": sql, "params": params })),
+        )
+        .await
+    }
+
+    /// Execute a SQL statement, confirming it if the viewer asks to (`POST /api/query`)
+    ///
+    /// [`ViewerClient::execute_query`] fails with status 428 for destructive
+    /// statements (`DELETE`, `UPDATE`, `DROP`, ...); this sends the statement
+    /// again with the confirmation token from that response.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - Statement to execute
+    /// * `params` - Values for the placeholders, in order
+    pub async fn execute_confirmed_query(
+        &self,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<QueryResult, ClientError> {
+        let response = self
+            .request(Method::POST, &["query"])
+            .json(&json!({ "sql": sql, "params": params }))
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+
+        if status == StatusCode::PRECONDITION_REQUIRED {
+            if let Ok(plan) = serde_json::from_str::<ConfirmationRequired>(&body) {
+                return self
+                    .send_json(self.request(Method::POST, &["query"]).json(&json!({
+                        "sql": sql,
+                        "params": params,
+                        "confirmation": plan.confirmation,
+                    })))
+                    .await;
+            }
+        }
+
+        match serde_json::from_str::<QueryResult>(&body) {
+            Ok(result) if status.is_success() => Ok(result),
+            _ => Err(ClientError::Api {
+                status,
+                message: error_message(&body),
+            }),
+        }
+    }
+
+    /// Execute a SQL query and receive its rows as they are read (`POST /api/query`)
+    ///
+    /// The result is streamed as NDJSON and never collected, so it is not
+    /// subject to the row limit. `on_line` is called with the column names,
+    /// every row and finally the end line, or an error line if reading the rows
+    /// failed part way through.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - Query to execute
+    /// * `on_line` - Called with each line of the result in order
+    pub async fn stream_query(
+        &self,
+        sql: &str,
+        mut on_line: impl FnMut(QueryStreamLine),
+    ) -> Result<(), ClientError> {
+        let mut response = self
+            .send(
+                self.request(Method::POST, &["query"])
+                    .json(&json!({ "sql": sql, "stream": true })),
+            )
+            .await?;
+        let status = response.status();
+        let mut buffer = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let line = serde_json::from_slice(&line).map_err(|error| ClientError::Api {
+                    status,
+                    message: format!("Invalid result line: {}", error),
+                })?;
+                on_line(line);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Download the rows of a query as a file (`POST /api/query` with `format`)
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - Query returning rows
+    /// * `format` - File format; SQL is not supported for query results
+    pub async fn download_query(
+        &self,
+        sql: &str,
+        format: ExportFormat,
+    ) -> Result<Vec<u8>, ClientError> {
+        let response = self
+            .send(
+                self.request(Method::POST, &["query"])
+                    .json(&json!({ "sql": sql, "format": format })),
+            )
+            .await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Check SQL without running it (`POST /api/query` with `validateOnly`)
+    ///
+    /// Invalid SQL is returned as `Ok` with `valid: false` and the problem.
+    pub async fn validate_query(&self, sql: &str) -> Result<QueryValidation, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["query"])
+                .json(&json!({ "sql": sql, "validateOnly": true })),
+        )
+        .await
+    }
+
+    /// Execute a SQL query and receive only its first rows (`POST /api/query`)
+    ///
+    /// If more rows follow, the page carries a `result_id` to fetch them with
+    /// [`ViewerClient::query_result_rows`].
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - Query to execute
+    /// * `page_size` - Number of rows in the first page
+    pub async fn execute_paged_query(
+        &self,
+        sql: &str,
+        page_size: usize,
+    ) -> Result<QueryPage, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["query"])
+                .json(&json!({ "sql": sql, "pageSize": page_size })),
+        )
+        .await
+    }
+
+    /// Fetch a page of a paged query result (`GET /api/query/:id/rows`)
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Result handle from [`ViewerClient::execute_paged_query`]
+    /// * `offset` - Position of the first row to return
+    /// * `limit` - Number of rows to return
+    pub async fn query_result_rows(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<QueryPage, ClientError> {
+        self.send_json(
+            self.request(Method::GET, &["query", id, "rows"])
+                .query(&[("offset", offset), ("limit", limit)]),
+        )
+        .await
+    }
+
+    /// Drop a paged query result before it expires (`DELETE /api/query/:id/rows`)
+    pub async fn release_query_result(&self, id: &str) -> Result<(), ClientError> {
+        self.send(self.request(Method::DELETE, &["query", id, "rows"]))
+            .await
+            .map(drop)
+    }
+
+    /// Execute a SQL statement that can be cancelled while it runs (`POST /api/query`)
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier to pass to [`ViewerClient::cancel_query`] from another task
+    /// * `sql` - Statement to execute
+    pub async fn execute_cancellable_query(
+        &self,
+        id: &str,
+        sql: &str,
+    ) -> Result<QueryResult, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["query"])
+                .json(&json!({ "sql": sql, "id": id })),
+        )
+        .await
+    }
+
+    /// Stop a query started with an identifier (`POST /api/query/:id/cancel`)
+    pub async fn cancel_query(&self, id: &str) -> Result<(), ClientError> {
+        self.send(self.request(Method::POST, &["query", id, "cancel"]))
+            .await
+            .map(drop)
+    }
+
+    /// List the saved queries passing `filter`, favorites first and then by
+    /// name (`GET /api/saved-queries`)
+    pub async fn saved_queries(
+        &self,
+        filter: &SavedQueryFilter,
+    ) -> Result<Vec<SavedQuery>, ClientError> {
+        let response: SavedQueriesResponse = self
+            .send_json(self.request(Method::GET, &["saved-queries"]).query(filter))
+            .await?;
+        Ok(response.queries)
+    }
+
+    /// Save a query (`POST /api/saved-queries`)
+    pub async fn create_saved_query(
+        &self,
+        query: &SavedQueryRequest,
+    ) -> Result<SavedQuery, ClientError> {
+        self.send_json(self.request(Method::POST, &["saved-queries"]).json(query))
+            .await
+    }
+
+    /// Replace the contents of a saved query (`PUT /api/saved-queries/:id`)
+    pub async fn update_saved_query(
+        &self,
+        id: &str,
+        query: &SavedQueryRequest,
+    ) -> Result<SavedQuery, ClientError> {
+        self.send_json(
+            self.request(Method::PUT, &["saved-queries", id])
+                .json(query),
+        )
+        .await
+    }
+
+    /// Delete a saved query (`DELETE /api/saved-queries/:id`)
+    pub async fn delete_saved_query(&self, id: &str) -> Result<(), ClientError> {
+        self.send(self.request(Method::DELETE, &["saved-queries", id]))
+            .await
+            .map(drop)
+    }
+
+    /// Keep a view under a token for a link (`POST /api/permalinks`)
+    pub async fn create_permalink(
+        &self,
+        view: &SharedView,
+    ) -> Result<PermalinkResponse, ClientError> {
+        self.send_json(self.request(Method::POST, &["permalinks"]).json(view))
+            .await
+    }
+
+    /// The view shared under a token (`GET /api/permalinks/:token`)
+    pub async fn permalink(&self, token: &str) -> Result<PermalinkResponse, ClientError> {
+        self.send_json(self.request(Method::GET, &["permalinks", token]))
+            .await
+    }
+
+    /// Run statements separated by semicolons in one transaction (`POST /api/query/script`)
+    ///
+    /// A script that was rolled back because a statement failed is returned
+    /// as `Ok` with `committed: false` and the failing statement. A script
+    /// with a destructive statement fails with status 428; see
+    /// [`ViewerClient::execute_confirmed_script`].
+    pub async fn execute_script(&self, sql: &str) -> Result<ScriptResult, ClientError> {
+        let response = self
+            .request(Method::POST, &["query", "script"])
+            .json(&json!({ "sql": sql }))
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        script_result(status, &body)
+    }
+
+    /// Run a script in one transaction, confirming it if the viewer asks to
+    /// (`POST /api/query/script`)
+    pub async fn execute_confirmed_script(&self, sql: &str) -> Result<ScriptResult, ClientError> {
+        let send = |confirmation: Option<String>| {
+            self.request(Method::POST, &["query", "script"])
+                .json(&json!({ "sql": sql, "confirmation": confirmation }))
+                .send()
+        };
+        let response = send(None).await?;
+        let mut status = response.status();
+        let mut body = response.text().await?;
+
+        if status == StatusCode::PRECONDITION_REQUIRED {
+            if let Ok(plan) = serde_json::from_str::<ConfirmationRequired>(&body) {
+                let response = send(Some(plan.confirmation)).await?;
+                status = response.status();
+                body = response.text().await?;
+            }
+        }
+        script_result(status, &body)
+    }
+
+    /// Pretty-print a SQL statement (`POST /api/query/format`)
+    pub async fn format_query(&self, sql: &str) -> Result<FormatQueryResponse, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["query", "format"])
+                .json(&json!({ "sql": sql })),
+        )
+        .await
+    }
+
+    /// Explain a SQL statement without running it (`POST /api/query/inspect`)
+    pub async fn inspect_query(&self, sql: &str) -> Result<InspectQueryResponse, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["query", "inspect"])
+                .json(&json!({ "sql": sql })),
+        )
+        .await
+    }
+
+    /// Query plan of a SQL statement as a tree (`POST /api/explain`)
+    pub async fn explain(&self, sql: &str) -> Result<ExplainResponse, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["explain"])
+                .json(&json!({ "sql": sql })),
+        )
+        .await
+    }
+
+    /// Run a read-only SQL statement and return its plan with actual rows and
+    /// timings (`POST /api/explain` with `analyze`)
+    pub async fn explain_analyze(&self, sql: &str) -> Result<ExplainResponse, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["explain"])
+                .json(&json!({ "sql": sql, "analyze": true })),
+        )
+        .await
+    }
+
+    /// Open a console session (`POST /api/console/sessions`)
+    pub async fn open_session(&self) -> Result<SessionResponse, ClientError> {
+        self.send_json(self.request(Method::POST, &["console", "sessions"]))
+            .await
+    }
+
+    /// Execute a statement in a console session (`POST /api/console/sessions/:session/query`)
+    pub async fn session_query(
+        &self,
+        session: &str,
+        sql: &str,
+    ) -> Result<QueryResult, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["console", "sessions", session, "query"])
+                .json(&json!({ "sql": sql })),
+        )
+        .await
+    }
+
+    /// Statements executed in a console session (`GET /api/console/sessions/:session/history`)
+    pub async fn session_history(
+        &self,
+        session: &str,
+    ) -> Result<SessionHistoryResponse, ClientError> {
+        self.send_json(self.request(Method::GET, &["console", "sessions", session, "history"]))
+            .await
+    }
+
+    /// Close a console session (`DELETE /api/console/sessions/:session`)
+    pub async fn close_session(&self, session: &str) -> Result<(), ClientError> {
+        self.send(self.request(Method::DELETE, &["console", "sessions", session]))
+            .await
+            .map(drop)
+    }
+
+    /// Begin a transaction on a dedicated connection (`POST /api/tx/begin`)
+    pub async fn begin_transaction(&self) -> Result<TransactionResponse, ClientError> {
+        self.send_json(self.request(Method::POST, &["tx", "begin"]))
+            .await
+    }
+
+    /// Execute a statement in a transaction (`POST /api/tx/:tx/query`)
+    pub async fn transaction_query(&self, tx: &str, sql: &str) -> Result<QueryResult, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["tx", tx, "query"])
+                .json(&json!({ "sql": sql })),
+        )
+        .await
+    }
+
+    /// Commit a transaction (`POST /api/tx/:tx/commit`)
+    pub async fn commit_transaction(&self, tx: &str) -> Result<(), ClientError> {
+        self.send(self.request(Method::POST, &["tx", tx, "commit"]))
+            .await
+            .map(drop)
+    }
+
+    /// Roll back a transaction (`POST /api/tx/:tx/rollback`)
+    pub async fn rollback_transaction(&self, tx: &str) -> Result<(), ClientError> {
+        self.send(self.request(Method::POST, &["tx", tx, "rollback"]))
+            .await
+            .map(drop)
+    }
+
+    /// Statements running on the database (`GET /api/activity`)
+    pub async fn activity(&self) -> Result<ActivityResponse, ClientError> {
+        self.send_json(self.request(Method::GET, &["activity"]))
+            .await
+    }
+
+    /// Open a snapshot to read rows from (`POST /api/snapshots`)
+    pub async fn create_snapshot(&self) -> Result<SnapshotResponse, ClientError> {
+        self.send_json(self.request(Method::POST, &["snapshots"]))
+            .await
+    }
+
+    /// Release a snapshot (`DELETE /api/snapshots/:snapshot`)
+    pub async fn release_snapshot(&self, snapshot: &str) -> Result<(), ClientError> {
+        self.send(self.request(Method::DELETE, &["snapshots", snapshot]))
+            .await
+            .map(drop)
+    }
+
+    // Exports and jobs
+
+    /// Download a whole table as a file (`GET /api/tables/:name/export`)
+    pub async fn export_table(
+        &self,
+        table: &str,
+        format: ExportFormat,
+    ) -> Result<Vec<u8>, ClientError> {
+        let response = self
+            .send(
+                self.request(Method::GET, &["tables", table, "export"])
+                    .query(&[("format", export_format_name(format))]),
+            )
+            .await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Start an export job (`POST /api/tables/:name/exports`)
+    ///
+    /// Poll the job with [`ViewerClient::job`]; once it succeeded, its result
+    /// holds the artifact to pass to [`ViewerClient::download_export`].
+    pub async fn start_export(
+        &self,
+        table: &str,
+        format: ExportFormat,
+    ) -> Result<JobInfo, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["tables", table, "exports"])
+                .json(&json!({ "format": format })),
+        )
+        .await
+    }
+
+    /// Download the file of a finished export job (`GET /api/exports/:id`)
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Artifact identifier (`id` in the job result)
+    pub async fn download_export(&self, id: &str) -> Result<Vec<u8>, ClientError> {
+        let response = self
+            .send(self.request(Method::GET, &["exports", id]))
+            .await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Current state of a job (`GET /api/jobs/:id`)
+    pub async fn job(&self, id: u64) -> Result<JobInfo, ClientError> {
+        self.send_json(self.request(Method::GET, &["jobs", &id.to_string()]))
+            .await
+    }
+
+    // Seed, fixtures and attachments
+
+    /// Whether a seed exists and when it was taken (`GET /api/seed`)
+    pub async fn seed_status(&self) -> Result<SeedStatus, ClientError> {
+        self.send_json(self.request(Method::GET, &["seed"])).await
+    }
+
+    /// Start a job saving the current state as the seed (`POST /api/seed`)
+    pub async fn create_seed(&self) -> Result<JobInfo, ClientError> {
+        self.send_json(self.request(Method::POST, &["seed"])).await
+    }
+
+    /// Start a job resetting the database to the seed (`POST /api/seed/reset`)
+    pub async fn reset_to_seed(&self) -> Result<JobInfo, ClientError> {
+        self.send_json(self.request(Method::POST, &["seed", "reset"]))
+            .await
+    }
+
+    /// Start a job running an upkeep operation on `tables` (the whole
+    /// database when empty) (`POST /api/maintenance/:operation`)
+    pub async fn run_maintenance(
+        &self,
+        operation: MaintenanceOperation,
+        tables: &[&str],
+    ) -> Result<JobInfo, ClientError> {
+        let mut request = self.request(Method::POST, &["maintenance", operation.name()]);
+        if !tables.is_empty() {
+            request = request.query(&[("tables", tables.join(","))]);
+        }
+        self.send_json(request).await
+    }
+
+    /// Journal mode and write-ahead log size (`GET /api/maintenance/journal`)
+    pub async fn journal_info(&self) -> Result<JournalInfo, ClientError> {
+        self.send_json(self.request(Method::GET, &["maintenance", "journal"]))
+            .await
+    }
+
+    /// Migrations recorded by sqlx and Diesel (`GET /api/migrations`)
+    pub async fn migrations(&self) -> Result<MigrationsResponse, ClientError> {
+        self.send_json(self.request(Method::GET, &["migrations"]))
+            .await
+    }
+
+    /// Start a job applying the pending migrations of the configured migrator
+    /// (`POST /api/migrations/run`)
+    pub async fn run_migrations(&self) -> Result<JobInfo, ClientError> {
+        self.send_json(self.request(Method::POST, &["migrations", "run"]))
+            .await
+    }
+
+    /// Named snapshots (`GET /api/snapshots/named`)
+    pub async fn named_snapshots(&self) -> Result<Vec<NamedSnapshot>, ClientError> {
+        self.send_json(self.request(Method::GET, &["snapshots", "named"]))
+            .await
+    }
+
+    /// Start a job copying `table` (all tables if `None`) into a named
+    /// snapshot (`POST /api/snapshots/named`)
+    pub async fn save_named_snapshot(
+        &self,
+        name: &str,
+        table: Option<&str>,
+    ) -> Result<JobInfo, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["snapshots", "named"])
+                .json(&json!({ "name": name, "table": table })),
+        )
+        .await
+    }
+
+    /// Start a job restoring the rows of a named snapshot
+    /// (`POST /api/snapshots/named/:name/restore`)
+    pub async fn restore_named_snapshot(&self, name: &str) -> Result<JobInfo, ClientError> {
+        self.send_json(self.request(Method::POST, &["snapshots", "named", name, "restore"]))
+            .await
+    }
+
+    /// Compare `table` with its copy in a named snapshot; the table may be
+    /// left out for snapshots of one table (`GET /api/snapshots/named/:name/diff`)
+    pub async fn named_snapshot_diff(
+        &self,
+        name: &str,
+        table: Option<&str>,
+    ) -> Result<SnapshotDiff, ClientError> {
+        let mut request = self.request(Method::GET, &["snapshots", "named", name, "diff"]);
+        if let Some(table) = table {
+            request = request.query(&[("table", table)]);
+        }
+        self.send_json(request).await
+    }
+
+    /// Delete a named snapshot (`DELETE /api/snapshots/named/:name`)
+    pub async fn delete_named_snapshot(&self, name: &str) -> Result<(), ClientError> {
+        self.send(self.request(Method::DELETE, &["snapshots", "named", name]))
+            .await
+            .map(drop)
+    }
+
+    /// Download the rows of `tables` (all tables when empty) as a fixture (`GET /api/fixtures`)
+    pub async fn download_fixture(
+        &self,
+        tables: &[&str],
+        format: FixtureFormat,
+    ) -> Result<String, ClientError> {
+        let mut parameters = vec![("format", fixture_format_name(format).to_string())];
+        if !tables.is_empty() {
+            parameters.push(("tables", tables.join(",")));
+        }
+        let response = self
+            .send(self.request(Method::GET, &["fixtures"]).query(&parameters))
+            .await?;
+        Ok(response.text().await?)
+    }
+
+    /// Replace the rows of the tables in a fixture document (`POST /api/fixtures`)
+    pub async fn load_fixture(
+        &self,
+        document: &str,
+        format: FixtureFormat,
+    ) -> Result<LoadedFixture, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["fixtures"])
+                .query(&[("format", fixture_format_name(format))])
+                .header(reqwest::header::CONTENT_TYPE, format.content_type())
+                .body(document.to_string()),
+        )
+        .await
+    }
+
+    /// Dump `tables` (all tables when empty) as a SQL script (`GET /api/dump`)
+    ///
+    /// The `tables` field of `options` is ignored.
+    pub async fn dump(&self, tables: &[&str], options: &DumpQuery) -> Result<String, ClientError> {
+        let mut parameters = vec![
+            ("schemaOnly", options.schema_only.to_string()),
+            ("dataOnly", options.data_only.to_string()),
+            ("inserts", options.inserts.to_string()),
+        ];
+        if !tables.is_empty() {
+            parameters.push(("tables", tables.join(",")));
+        }
+        let response = self
+            .send(self.request(Method::GET, &["dump"]).query(&parameters))
+            .await?;
+        Ok(response.text().await?)
+    }
+
+    /// Attached SQLite database files (`GET /api/attachments`)
+    pub async fn list_attachments(&self) -> Result<Vec<AttachedDatabase>, ClientError> {
+        self.send_json(self.request(Method::GET, &["attachments"]))
+            .await
+    }
+
+    /// Attach a SQLite database file under `alias` (`POST /api/attachments`)
+    pub async fn attach_database(
+        &self,
+        alias: &str,
+        path: &str,
+    ) -> Result<AttachedDatabase, ClientError> {
+        self.send_json(
+            self.request(Method::POST, &["attachments"])
+                .json(&json!({ "alias": alias, "path": path })),
+        )
+        .await
+    }
+
+    /// Detach the SQLite database file attached under `alias` (`DELETE /api/attachments/:alias`)
+    pub async fn detach_database(&self, alias: &str) -> Result<(), ClientError> {
+        self.send(self.request(Method::DELETE, &["attachments", alias]))
+            .await
+            .map(drop)
+    }
+
+    // Metadata
+
+    /// Viewer configuration and database capabilities (`GET /api/config`)
+    pub async fn config(&self) -> Result<ConfigResponse, ClientError> {
+        self.send_json(self.request(Method::GET, &["config"])).await
+    }
+
+    /// Machine-readable list of the API's commands (`GET /api/commands`)
+    pub async fn commands(&self) -> Result<CommandsResponse, ClientError> {
+        self.send_json(self.request(Method::GET, &["commands"]))
+            .await
+    }
+
+    /// Entity relationship diagram in Mermaid syntax (`GET /api/erd.mmd`)
+    pub async fn erd_mermaid(&self) -> Result<String, ClientError> {
+        let response = self.send(self.request(Method::GET, &["erd.mmd"])).await?;
+        Ok(response.text().await?)
+    }
+
+    /// Build a request for the API endpoint at `segments` (each percent-encoded)
+    ///
+    /// Requests are pinned to the API version this client was built for.
+    fn request(&self, method: Method, segments: &[&str]) -> RequestBuilder {
+        self.http
+            .request(method, self.endpoint(segments))
+            .header(API_VERSION_HEADER.as_str(), API_VERSION)
+    }
+
+    /// URL of the API endpoint at `segments` below the base URL
+    fn endpoint(&self, segments: &[&str]) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("checked in the constructor")
+            .pop_if_empty()
+            .push("api")
+            .extend(segments);
+        url
+    }
+
+    /// Send a request, turning error statuses into [`ClientError::Api`]
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, ClientError> {
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        Err(ClientError::Api {
+            status,
+            message: error_message(&body),
+        })
+    }
+
+    /// Send a request and decode its JSON response
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, ClientError> {
+        Ok(self.send(request).await?.json().await?)
+    }
+}
+
+/// Query parameters of a row request, as the rows endpoint reads them
+fn row_query_parameters(query: &RowQuery) -> Vec<(String, String)> {
+    let mut parameters = vec![
+        ("offset".to_string(), query.offset.to_string()),
+        ("limit".to_string(), query.limit.to_string()),
+    ];
+    if let Some(sort_by) = &query.sort_by {
+        parameters.push(("sortBy".to_string(), sort_by.clone()));
+    }
+    if let Some(sort_order) = query.sort_order {
+        let name = match sort_order {
+            SortOrder::Ascending => "ascending",
+            SortOrder::Descending => "descending",
+        };
+        parameters.push(("sortOrder".to_string(), name.to_string()));
+    }
+    if let Some(nulls) = query.nulls {
+        let name = match nulls {
+            NullsOrder::First => "first",
+            NullsOrder::Last => "last",
+        };
+        parameters.push(("nulls".to_string(), name.to_string()));
+    }
+
+    // Sorted so that requests are reproducible
+    let mut filters: Vec<_> = query.filters.iter().collect();
+    filters.sort();
+    for (column, value) in filters {
+        parameters.push((format!("filter[{}]", column), value.clone()));
+    }
+    parameters.extend(range_parameters(query));
+    if query.case_insensitive {
+        parameters.push(("caseInsensitive".to_string(), "true".to_string()));
+    }
+    if let Some(q) = &query.q {
+        parameters.push(("q".to_string(), q.clone()));
+    }
+
+    if let Some(snapshot) = &query.snapshot {
+        parameters.push(("snapshot".to_string(), snapshot.clone()));
+    }
+    if let Some(cursor) = &query.cursor {
+        parameters.push(("cursor".to_string(), cursor.clone()));
+    }
+    if !query.count {
+        parameters.push(("count".to_string(), "false".to_string()));
+    }
+    if let Some(columns) = &query.columns {
+        parameters.push(("columns".to_string(), columns.clone()));
+    }
+    parameters
+}
+
+/// `filter[column][operator]` parameters of the date ranges of a row query
+fn range_parameters(query: &RowQuery) -> Vec<(String, String)> {
+    query
+        .ranges
+        .iter()
+        .map(|range| {
+            (
+                format!("filter[{}][{}]", range.column, range.operator.name()),
+                range.value.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Value of the `format` parameter of exports
+fn export_format_name(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Csv => "csv",
+        ExportFormat::Xlsx => "xlsx",
+        ExportFormat::Ndjson => "ndjson",
+        ExportFormat::Json => "json",
+        ExportFormat::Sql => "sql",
+    }
+}
+
+/// Value of the `format` parameter of fixtures
+fn fixture_format_name(format: FixtureFormat) -> &'static str {
+    match format {
+        FixtureFormat::Json => "json",
+        FixtureFormat::Yaml => "yaml",
+    }
+}
+
+/// Result of a script, which is also sent when it was rolled back (status 400)
+fn script_result(status: StatusCode, body: &str) -> Result<ScriptResult, ClientError> {
+    match serde_json::from_str::<ScriptResult>(body) {
+        Ok(result) if status.is_success() || status == StatusCode::BAD_REQUEST => Ok(result),
+        _ => Err(ClientError::Api {
+            status,
+            message: error_message(body),
+        }),
+    }
+}
+
+/// The `error` field of an error response, or the body if it has none
+fn error_message(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|value| value.get("error")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::{RangeFilter, RangeOperator};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_endpoint_encodes_segments() {
+        let client = ViewerClient::new("http://localhost:3000/sql-viewer/").unwrap();
+        let key = format_row_key(&["7".to_string(), "a/b".to_string()]);
+
+        assert_eq!(
+            client
+                .endpoint(&["tables", "audit log", "rows", &key])
+                .as_str(),
+            "http://localhost:3000/sql-viewer/api/tables/audit%20log/rows/7,a%2Fb"
+        );
+        assert_eq!(
+            client.endpoint(&["tables", "users", "rows:bulk"]).as_str(),
+            "http://localhost:3000/sql-viewer/api/tables/users/rows:bulk"
+        );
+    }
+
+    #[test]
+    fn test_invalid_base_url() {
+        assert!(matches!(
+            ViewerClient::new("not a url"),
+            Err(ClientError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            ViewerClient::new("mailto:someone@example.com"),
+            Err(ClientError::InvalidUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_row_query_parameters() {
+        let query = RowQuery {
+            offset: 200,
+            limit: 50,
+            sort_by: Some("name".to_string()),
+            sort_order: Some(SortOrder::Descending),
+            nulls: Some(NullsOrder::Last),
+            filters: HashMap::from([
+                ("status".to_string(), "active".to_string()),
+                ("name".to_string(), "A%".to_string()),
+            ]),
+            ranges: vec![RangeFilter {
+                column: "created_at".to_string(),
+                operator: RangeOperator::GreaterOrEqual,
+                value: "2024-01-01T00:00:00Z".to_string(),
+            }],
+            filter_groups: Vec::new(),
+            snapshot: None,
+            cursor: None,
+            count: false,
+            columns: Some("id,name".to_string()),
+            case_insensitive: true,
+            q: Some("alice".to_string()),
+        };
+
+        let parameters = row_query_parameters(&query);
+        let parameters: Vec<(&str, &str)> = parameters
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            parameters,
+            vec![
+                ("offset", "200"),
+                ("limit", "50"),
+                ("sortBy", "name"),
+                ("sortOrder", "descending"),
+                ("nulls", "last"),
+                ("filter[name]", "A%"),
+                ("filter[status]", "active"),
+                ("filter[created_at][gte]", "2024-01-01T00:00:00Z"),
+                ("caseInsensitive", "true"),
+                ("q", "alice"),
+                ("count", "false"),
+                ("columns", "id,name"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(
+            error_message(r#"{"error":"Table not found: users"}"#),
+            "Table not found: users"
+        );
+        assert_eq!(error_message("Bad Gateway\n"), "Bad Gateway");
+    }
+}
@@ -0,0 +1,490 @@
+//! Code generation from table schemas
+//!
+//! Converts discovered table schemas into documents and type definitions that
+//! can be used by the host application.
+
+use serde_json::{json, Map, Value};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::schema::{ColumnInfo, TableSchema};
+use crate::sql::Dialect;
+
+/// Words that cannot be used as plain Rust identifiers
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Broad category of a column's SQL data type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Boolean,
+    Integer,
+    Float,
+    /// Exact numeric types (NUMERIC, DECIMAL)
+    Decimal,
+    Text,
+    Uuid,
+    Date,
+    Time,
+    DateTime,
+    Json,
+    Binary,
+    Array,
+    /// Any other type (including columns without a declared type)
+    Unknown,
+}
+
+/// Classify a SQL data type as reported by a provider
+///
+/// Handles Postgres type names (`integer`, `character varying`, `timestamp with
+/// time zone`, `ARRAY`, ...) and falls back to SQLite's type affinity rules
+/// (any type containing `INT` is an integer, `CHAR`/`CLOB`/`TEXT` is text, ...).
+pub fn column_kind(data_type: &str) -> ColumnKind {
+    let data_type = data_type.trim().to_ascii_lowercase();
+
+    if data_type.ends_with("[]") || data_type == "array" {
+        return ColumnKind::Array;
+    }
+
+    // Drop any length or precision, e.g. VARCHAR(255) or NUMERIC(10, 2)
+    let base_type = data_type.split('(').next().unwrap_or_default().trim();
+
+    match base_type {
+        "" => return ColumnKind::Unknown,
+        "bool" | "boolean" => return ColumnKind::Boolean,
+        "uuid" => return ColumnKind::Uuid,
+        "json" | "jsonb" => return ColumnKind::Json,
+        "bytea" => return ColumnKind::Binary,
+        "date" => return ColumnKind::Date,
+        "numeric" | "decimal" => return ColumnKind::Decimal,
+        "interval" | "point" | "money" => return ColumnKind::Text,
+        _ => {}
+    }
+
+    if base_type.starts_with("timestamp") || base_type == "datetime" {
+        ColumnKind::DateTime
+    } else if base_type.starts_with("time") {
+        ColumnKind::Time
+    } else if base_type.contains("int") || base_type.contains("serial") {
+        ColumnKind::Integer
+    } else if ["char", "clob", "text", "string", "name"]
+        .iter()
+        .any(|fragment| base_type.contains(fragment))
+    {
+        ColumnKind::Text
+    } else if base_type.contains("blob") {
+        ColumnKind::Binary
+    } else if ["real", "floa", "doub"]
+        .iter()
+        .any(|fragment| base_type.contains(fragment))
+    {
+        ColumnKind::Float
+    } else {
+        ColumnKind::Unknown
+    }
+}
+
+/// Generate a draft-07 JSON Schema document describing a row of the table
+///
+/// Nullable columns accept `null`; columns that are NOT NULL and have no
+/// default value are required.
+pub fn json_schema(schema: &TableSchema) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    for column in &schema.columns {
+        properties.insert(column.name.clone(), json_schema_property(column));
+
+        if !column.nullable && column.default_value.is_none() {
+            required.push(Value::String(column.name.clone()));
+        }
+    }
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": schema.name,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn json_schema_property(column: &ColumnInfo) -> Value {
+    let (json_type, format) = match column_kind(&column.data_type) {
+        ColumnKind::Boolean => ("boolean", None),
+        ColumnKind::Integer => ("integer", None),
+        ColumnKind::Float | ColumnKind::Decimal => ("number", None),
+        ColumnKind::Text | ColumnKind::Binary => ("string", None),
+        ColumnKind::Uuid => ("string", Some("uuid")),
+        ColumnKind::Date => ("string", Some("date")),
+        ColumnKind::Time => ("string", Some("time")),
+        ColumnKind::DateTime => ("string", Some("date-time")),
+        ColumnKind::Array => ("array", None),
+        ColumnKind::Json | ColumnKind::Unknown => {
+            // Any JSON value is acceptable
+            return json!({ "description": column.data_type });
+        }
+    };
+
+    let mut property = Map::new();
+    property.insert(
+        "type".to_string(),
+        if column.nullable {
+            json!([json_type, "null"])
+        } else {
+            json!(json_type)
+        },
+    );
+    if let Some(format) = format {
+        property.insert("format".to_string(), json!(format));
+    }
+    property.insert("description".to_string(), json!(column.data_type));
+
+    Value::Object(property)
+}
+
+/// Generate a Rust struct deriving `sqlx::FromRow` for a row of the table
+///
+/// Field types follow sqlx's type mapping for the given dialect; nullable
+/// columns become `Option<T>`. Columns whose names are not valid Rust
+/// identifiers are renamed with `#[sqlx(rename = "...")]`.
+pub fn rust_struct(schema: &TableSchema, dialect: Dialect) -> String {
+    let mut imports = BTreeSet::new();
+    let mut fields = String::new();
+
+    for column in &schema.columns {
+        let (rust_type, import) = rust_type(&column.data_type, dialect);
+        if let Some(import) = import {
+            imports.insert(import);
+        }
+
+        let field_name = rust_field_name(&column.name);
+        if field_name.trim_start_matches("r#") != column.name {
+            let _ = writeln!(
+                fields,
+                "    #[sqlx(rename = \"{}\")]",
+                column.name.replace('\\', "\\\\").replace('"', "\\\"")
+            );
+        }
+
+        let field_type = if column.nullable {
+            format!("Option<{}>", rust_type)
+        } else {
+            rust_type.to_string()
+        };
+        let _ = writeln!(fields, "    pub {}: {},", field_name, field_type);
+    }
+
+    let mut output = String::new();
+    for import in &imports {
+        let _ = writeln!(output, "use {};", import);
+    }
+    if !imports.is_empty() {
+        output.push('\n');
+    }
+    let _ = writeln!(output, "#[derive(Debug, Clone, sqlx::FromRow)]");
+    let _ = writeln!(output, "pub struct {} {{", type_name(&schema.name));
+    output.push_str(&fields);
+    output.push_str("}\n");
+
+    output
+}
+
+/// Rust type (and the import it needs) that sqlx decodes a column type into
+fn rust_type(data_type: &str, dialect: Dialect) -> (&'static str, Option<&'static str>) {
+    let lowercase = data_type.trim().to_ascii_lowercase();
+
+    match (column_kind(data_type), dialect) {
+        (ColumnKind::Boolean, _) => ("bool", None),
+        (ColumnKind::Integer, Dialect::Postgres) => match lowercase.as_str() {
+            "smallint" | "int2" | "smallserial" => ("i16", None),
+            "integer" | "int" | "int4" | "serial" => ("i32", None),
+            _ => ("i64", None),
+        },
+        (ColumnKind::Integer, Dialect::Sqlite) => ("i64", None),
+        (ColumnKind::Float, Dialect::Postgres) if lowercase == "real" || lowercase == "float4" => {
+            ("f32", None)
+        }
+        (ColumnKind::Float, _) => ("f64", None),
+        (ColumnKind::Decimal, Dialect::Postgres) => ("Decimal", Some("sqlx::types::Decimal")),
+        // SQLite stores NUMERIC values as integers or floating point numbers
+        (ColumnKind::Decimal, Dialect::Sqlite) => ("f64", None),
+        (ColumnKind::Text, Dialect::Postgres) if lowercase == "interval" => {
+            ("PgInterval", Some("sqlx::postgres::types::PgInterval"))
+        }
+        (ColumnKind::Text, Dialect::Postgres) if lowercase == "money" => {
+            ("PgMoney", Some("sqlx::postgres::types::PgMoney"))
+        }
+        (ColumnKind::Text, _) => ("String", None),
+        (ColumnKind::Uuid, _) => ("Uuid", Some("sqlx::types::Uuid")),
+        (ColumnKind::Date, _) => ("NaiveDate", Some("sqlx::types::chrono::NaiveDate")),
+        (ColumnKind::Time, _) => ("NaiveTime", Some("sqlx::types::chrono::NaiveTime")),
+        (ColumnKind::DateTime, Dialect::Postgres) if lowercase.contains("with time zone") => (
+            "DateTime<Utc>",
+            Some("sqlx::types::chrono::{DateTime, Utc}"),
+        ),
+        (ColumnKind::DateTime, _) => ("NaiveDateTime", Some("sqlx::types::chrono::NaiveDateTime")),
+        (ColumnKind::Json, _) => ("JsonValue", Some("sqlx::types::JsonValue")),
+        (ColumnKind::Binary, _) => ("Vec<u8>", None),
+        // The element type of an array column is not part of the reported type
+        (ColumnKind::Array, _) => ("Vec<String>", None),
+        (ColumnKind::Unknown, _) => ("String", None),
+    }
+}
+
+/// Generate a TypeScript interface for a row of the table as returned by the API
+///
+/// Types follow the JSON representation of the values (e.g. PostgreSQL
+/// NUMERIC is a string to preserve precision). Rows contain every column, so
+/// no field is optional; nullable columns accept `null`.
+pub fn typescript_interface(schema: &TableSchema, dialect: Dialect) -> String {
+    let mut output = String::new();
+    let _ = writeln!(output, "export interface {} {{", type_name(&schema.name));
+
+    for column in &schema.columns {
+        let mut field_type = typescript_type(&column.data_type, dialect).to_string();
+        if column.nullable {
+            field_type.push_str(" | null");
+        }
+
+        let _ = writeln!(
+            output,
+            "    {}: {};",
+            typescript_property_name(&column.name),
+            field_type
+        );
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+/// TypeScript type of a column value in the API's JSON output
+fn typescript_type(data_type: &str, dialect: Dialect) -> &'static str {
+    match column_kind(data_type) {
+        ColumnKind::Boolean => "boolean",
+        ColumnKind::Integer | ColumnKind::Float => "number",
+        ColumnKind::Decimal => match dialect {
+            Dialect::Postgres => "string",
+            Dialect::Sqlite => "number",
+        },
+        ColumnKind::Text
+        | ColumnKind::Uuid
+        | ColumnKind::Date
+        | ColumnKind::Time
+        | ColumnKind::DateTime
+        | ColumnKind::Binary => "string",
+        ColumnKind::Array => "unknown[]",
+        ColumnKind::Json | ColumnKind::Unknown => "unknown",
+    }
+}
+
+/// Property name, quoted unless it is a valid identifier
+fn typescript_property_name(column: &str) -> String {
+    let is_identifier = column
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_' || first == '$')
+        && column.chars().all(|character| {
+            character.is_ascii_alphanumeric() || character == '_' || character == '$'
+        });
+
+    if is_identifier {
+        column.to_string()
+    } else {
+        serde_json::to_string(column).unwrap_or_default()
+    }
+}
+
+/// Convert a table name into a PascalCase type name
+fn type_name(table: &str) -> String {
+    let name: String = table
+        .split(|character: char| !character.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut characters = part.chars();
+            match characters.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + characters.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    if name.is_empty() || name.starts_with(|character: char| character.is_ascii_digit()) {
+        format!("Table{}", name)
+    } else {
+        name
+    }
+}
+
+/// Convert a column name into a valid Rust field name
+fn rust_field_name(column: &str) -> String {
+    let mut name: String = column
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || character == '_' {
+                character.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if name.is_empty() || name.starts_with(|character: char| character.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+
+    match name.as_str() {
+        // These keywords cannot be raw identifiers
+        "self" | "super" | "crate" | "_" => format!("{}_", name),
+        keyword if RUST_KEYWORDS.contains(&keyword) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{column, table, ColumnInfoExt};
+
+    #[test]
+    fn test_column_kind() {
+        assert_eq!(column_kind("INTEGER"), ColumnKind::Integer);
+        assert_eq!(column_kind("bigint"), ColumnKind::Integer);
+        assert_eq!(column_kind("interval"), ColumnKind::Text);
+        assert_eq!(column_kind("VARCHAR(255)"), ColumnKind::Text);
+        assert_eq!(column_kind("character varying"), ColumnKind::Text);
+        assert_eq!(
+            column_kind("timestamp with time zone"),
+            ColumnKind::DateTime
+        );
+        assert_eq!(column_kind("DATETIME"), ColumnKind::DateTime);
+        assert_eq!(column_kind("time without time zone"), ColumnKind::Time);
+        assert_eq!(column_kind("double precision"), ColumnKind::Float);
+        assert_eq!(column_kind("NUMERIC(10, 2)"), ColumnKind::Decimal);
+        assert_eq!(column_kind("BOOLEAN"), ColumnKind::Boolean);
+        assert_eq!(column_kind("jsonb"), ColumnKind::Json);
+        assert_eq!(column_kind("ARRAY"), ColumnKind::Array);
+        assert_eq!(column_kind("BLOB"), ColumnKind::Binary);
+        assert_eq!(column_kind(""), ColumnKind::Unknown);
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = table("users")
+            .column(column("id", "INTEGER").not_null())
+            .column(column("email", "TEXT").not_null())
+            .column(column("created_at", "DATETIME").default_value("CURRENT_TIMESTAMP"))
+            .column(
+                column("is_active", "BOOLEAN")
+                    .not_null()
+                    .default_value("true"),
+            )
+            .primary_key(&["id"])
+            .build();
+
+        let document = json_schema(&schema);
+        assert_eq!(
+            document["$schema"],
+            "http://json-schema.org/draft-07/schema#"
+        );
+        assert_eq!(document["title"], "users");
+        assert_eq!(document["properties"]["id"]["type"], "integer");
+        assert_eq!(
+            document["properties"]["created_at"]["type"],
+            json!(["string", "null"])
+        );
+        assert_eq!(document["properties"]["created_at"]["format"], "date-time");
+        assert_eq!(document["required"], json!(["id", "email"]));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_json_schema_of_sqlite_table() {
+        use crate::database::sqlite::SqliteProvider;
+        use crate::database::traits::DatabaseProvider;
+
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, \
+             email TEXT NOT NULL, bio TEXT, active BOOLEAN NOT NULL DEFAULT 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let schema = SqliteProvider::new(pool)
+            .get_table_schema("users")
+            .await
+            .unwrap();
+
+        assert_eq!(schema.columns[3].default_value, None);
+        assert_eq!(schema.columns[4].default_value.as_deref(), Some("1"));
+        assert_eq!(json_schema(&schema)["required"], json!(["name", "email"]));
+    }
+
+    #[test]
+    fn test_rust_struct() {
+        let schema = table("order_items")
+            .column(column("id", "integer").not_null())
+            .column(column("type", "text").not_null())
+            .column(column("Unit Price", "numeric"))
+            .column(
+                column("created_at", "timestamp with time zone")
+                    .not_null()
+                    .default_value("now()"),
+            )
+            .primary_key(&["id"])
+            .build();
+
+        assert_eq!(
+            rust_struct(&schema, Dialect::Postgres),
+            "use sqlx::types::Decimal;\n\
+             use sqlx::types::chrono::{DateTime, Utc};\n\
+             \n\
+             #[derive(Debug, Clone, sqlx::FromRow)]\n\
+             pub struct OrderItems {\n\
+             \x20   pub id: i32,\n\
+             \x20   pub r#type: String,\n\
+             \x20   #[sqlx(rename = \"Unit Price\")]\n\
+             \x20   pub unit_price: Option<Decimal>,\n\
+             \x20   pub created_at: DateTime<Utc>,\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_typescript_interface() {
+        let schema = table("users")
+            .column(column("id", "INTEGER").not_null())
+            .column(column("display name", "TEXT"))
+            .column(column("is_active", "BOOLEAN").not_null().default_value("1"))
+            .column(column("balance", "NUMERIC").not_null())
+            .primary_key(&["id"])
+            .build();
+
+        assert_eq!(
+            typescript_interface(&schema, Dialect::Postgres),
+            "export interface Users {\n\
+             \x20   id: number;\n\
+             \x20   \"display name\": string | null;\n\
+             \x20   is_active: boolean;\n\
+             \x20   balance: string;\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(type_name("users"), "Users");
+        assert_eq!(type_name("2fa-codes"), "Table2faCodes");
+        assert_eq!(rust_field_name("createdAt"), "createdat");
+        assert_eq!(rust_field_name("1st"), "_1st");
+        assert_eq!(rust_field_name("self"), "self_");
+    }
+}
@@ -0,0 +1,210 @@
+//! Summary statistics of a single column
+//!
+//! Minimum, maximum, average, NULL and distinct counts are computed by the
+//! database in one aggregate query, so a column summary needs no rows sent to
+//! the client. Which aggregates apply depends on the column's type (see
+//! [`column_kind`]): averages only exist for numbers, and minimum and maximum
+//! for numbers, text, dates and times.
+//!
+//! Counting distinct values reads and sorts the whole column. PostgreSQL
+//! therefore takes the planner's `n_distinct` estimate from `pg_stats` when
+//! the column has been analyzed, and only counts exactly otherwise.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::codegen::{column_kind, ColumnKind};
+use crate::schema::ColumnInfo;
+use crate::sql::{quote_identifier, Dialect};
+
+/// Statistics of a column's values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnStats {
+    /// Table name
+    pub table: String,
+
+    /// Column name
+    pub column: String,
+
+    /// SQL data type of the column
+    pub data_type: String,
+
+    /// Number of rows in the table
+    pub row_count: u64,
+
+    /// Number of rows with NULL in the column
+    pub null_count: u64,
+
+    /// Number of distinct non-NULL values (not set for types without equality)
+    pub distinct_count: Option<u64>,
+
+    /// Whether `distinct_count` was counted rather than estimated
+    pub distinct_exact: bool,
+
+    /// Smallest value (numbers as numbers, other values in the database's text form)
+    pub min: Option<Value>,
+
+    /// Largest value
+    pub max: Option<Value>,
+
+    /// Average of numeric columns
+    pub avg: Option<f64>,
+}
+
+/// Aggregates of a column, selected in this order by [`stats_expressions`]
+///
+/// Values are the row count, the non-NULL count, the minimum and maximum as
+/// text, the average as a float and the distinct count. Aggregates that do
+/// not apply to the column are selected as typed NULLs, so every column is
+/// read the same way.
+pub fn stats_expressions(
+    column: &ColumnInfo,
+    dialect: Dialect,
+    count_distinct: bool,
+) -> Vec<String> {
+    let kind = column_kind(&column.data_type);
+    let quoted = quote_identifier(&column.name);
+    let null = |sql_type: &str| match dialect {
+        Dialect::Sqlite => "NULL".to_string(),
+        Dialect::Postgres => format!("NULL::{}", sql_type),
+    };
+    let text = |aggregate: &str| match dialect {
+        Dialect::Sqlite => format!("CAST({}({}) AS TEXT)", aggregate, quoted),
+        Dialect::Postgres => format!("{}({})::text", aggregate, quoted),
+    };
+
+    let (min, max) = if has_order(kind) {
+        (text("MIN"), text("MAX"))
+    } else {
+        (null("text"), null("text"))
+    };
+    let avg = match (is_numeric(kind), dialect) {
+        (false, _) => null("float8"),
+        (true, Dialect::Sqlite) => format!("AVG({})", quoted),
+        (true, Dialect::Postgres) => format!("AVG({})::float8", quoted),
+    };
+    let distinct = if count_distinct && has_equality(kind, dialect) {
+        format!("COUNT(DISTINCT {})", quoted)
+    } else {
+        null("bigint")
+    };
+
+    vec![
+        "COUNT(*)".to_string(),
+        format!("COUNT({})", quoted),
+        min,
+        max,
+        avg,
+        distinct,
+    ]
+}
+
+/// Whether values of this kind have a distinct count
+///
+/// PostgreSQL's `json` type (unlike `jsonb`) cannot be compared for equality.
+pub fn has_equality(kind: ColumnKind, dialect: Dialect) -> bool {
+    !(kind == ColumnKind::Json && dialect == Dialect::Postgres)
+}
+
+/// Whether values of this kind have a meaningful minimum and maximum
+fn has_order(kind: ColumnKind) -> bool {
+    is_numeric(kind)
+        || matches!(
+            kind,
+            ColumnKind::Text | ColumnKind::Date | ColumnKind::Time | ColumnKind::DateTime
+        )
+}
+
+/// Whether values of this kind are numbers
+fn is_numeric(kind: ColumnKind) -> bool {
+    matches!(
+        kind,
+        ColumnKind::Integer | ColumnKind::Float | ColumnKind::Decimal
+    )
+}
+
+/// JSON value of a minimum or maximum read as text
+///
+/// Numbers become JSON numbers when they fit one, everything else stays text.
+pub fn stats_value(column: &ColumnInfo, text: Option<String>) -> Option<Value> {
+    let text = text?;
+    if is_numeric(column_kind(&column.data_type)) {
+        if let Ok(number) = text.parse::<serde_json::Number>() {
+            return Some(Value::Number(number));
+        }
+    }
+    Some(Value::String(text))
+}
+
+/// Distinct count from PostgreSQL's `n_distinct` statistic
+///
+/// Positive values are the estimated count itself; negative values are the
+/// negated share of rows that are distinct (e.g. -1 for a unique column), so
+/// they scale with the current row count.
+pub fn estimated_distinct(n_distinct: f32, row_count: u64) -> u64 {
+    if n_distinct >= 0.0 {
+        n_distinct.round() as u64
+    } else {
+        (-f64::from(n_distinct) * row_count as f64).round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::column;
+
+    #[test]
+    fn test_stats_expressions() {
+        assert_eq!(
+            stats_expressions(&column("price", "NUMERIC(10, 2)"), Dialect::Postgres, false),
+            vec![
+                "COUNT(*)",
+                "COUNT(\"price\")",
+                "MIN(\"price\")::text",
+                "MAX(\"price\")::text",
+                "AVG(\"price\")::float8",
+                "NULL::bigint",
+            ]
+        );
+        assert_eq!(
+            stats_expressions(&column("email", "TEXT"), Dialect::Sqlite, true),
+            vec![
+                "COUNT(*)",
+                "COUNT(\"email\")",
+                "CAST(MIN(\"email\") AS TEXT)",
+                "CAST(MAX(\"email\") AS TEXT)",
+                "NULL",
+                "COUNT(DISTINCT \"email\")",
+            ]
+        );
+
+        let settings = stats_expressions(&column("settings", "json"), Dialect::Postgres, true);
+        assert_eq!(settings[2], "NULL::text");
+        assert_eq!(settings[5], "NULL::bigint");
+    }
+
+    #[test]
+    fn test_stats_value() {
+        let price = column("price", "numeric");
+        assert_eq!(
+            stats_value(&price, Some("12.50".to_string())),
+            Some(serde_json::json!(12.5))
+        );
+        assert_eq!(stats_value(&price, None), None);
+
+        let created = column("created_at", "timestamp");
+        assert_eq!(
+            stats_value(&created, Some("2024-01-01 10:00:00".to_string())),
+            Some(Value::String("2024-01-01 10:00:00".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_estimated_distinct() {
+        assert_eq!(estimated_distinct(42.0, 1000), 42);
+        assert_eq!(estimated_distinct(-1.0, 1000), 1000);
+        assert_eq!(estimated_distinct(-0.25, 1000), 250);
+    }
+}
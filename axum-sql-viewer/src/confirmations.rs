@@ -0,0 +1,127 @@
+//! Confirmation of destructive statements
+//!
+//! Destructive statements (see [`crate::classify::StatementClass::destructive`])
+//! sent as raw SQL are not run right away. The first request gets back what
+//! the statement would do and a confirmation token; only a second request
+//! carrying the token for exactly the same statement and parameters runs it.
+//! This is a seatbelt against a forgotten or mistyped `WHERE` clause, not an
+//! access control.
+
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a confirmation token stays valid
+pub const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Statements waiting for confirmation
+///
+/// Cloning `Confirmations` is cheap; all clones share the same tokens.
+#[derive(Clone)]
+pub struct Confirmations {
+    enabled: bool,
+    pending: Arc<Mutex<HashMap<String, PendingStatement>>>,
+    sequence: Arc<AtomicU64>,
+    random: RandomState,
+}
+
+struct PendingStatement {
+    sql: String,
+    params: Vec<Value>,
+    issued_at: Instant,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl Confirmations {
+    /// Create the registry; when `enabled` is false, nothing needs confirming
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            sequence: Arc::new(AtomicU64::new(0)),
+            random: RandomState::new(),
+        }
+    }
+
+    /// Whether destructive statements have to be confirmed
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Issue a token that confirms running `sql` with `params` once
+    pub fn issue(&self, sql: &str, params: &[Value]) -> String {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let hash = self.random.hash_one((sequence, sql));
+        let token = format!("{:016x}-{}", hash, sequence);
+
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, statement| statement.issued_at.elapsed() < CONFIRMATION_TIMEOUT);
+        pending.insert(
+            token.clone(),
+            PendingStatement {
+                sql: sql.to_string(),
+                params: params.to_vec(),
+                issued_at: Instant::now(),
+            },
+        );
+        token
+    }
+
+    /// Use up a token
+    ///
+    /// # Returns
+    ///
+    /// Whether the token was issued for this statement and parameters and has
+    /// not expired. A token is removed by its first use, even a mismatching one.
+    pub fn confirm(&self, token: &str, sql: &str, params: &[Value]) -> bool {
+        match self.pending.lock().unwrap().remove(token) {
+            Some(statement) => {
+                statement.issued_at.elapsed() < CONFIRMATION_TIMEOUT
+                    && statement.sql == sql
+                    && statement.params == params
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_confirms_its_statement_once() {
+        let confirmations = Confirmations::default();
+        let params = [Value::from(7)];
+
+        let token = confirmations.issue("DELETE FROM users WHERE id = ?", &params);
+        assert!(confirmations.confirm(&token, "DELETE FROM users WHERE id = ?", &params));
+        assert!(!confirmations.confirm(&token, "DELETE FROM users WHERE id = ?", &params));
+    }
+
+    #[test]
+    fn test_token_is_bound_to_statement_and_params() {
+        let confirmations = Confirmations::default();
+
+        let token = confirmations.issue("DELETE FROM users WHERE id = ?", &[Value::from(7)]);
+        assert!(!confirmations.confirm(&token, "DELETE FROM users", &[Value::from(7)]));
+
+        let token = confirmations.issue("DELETE FROM users WHERE id = ?", &[Value::from(7)]);
+        assert!(!confirmations.confirm(
+            &token,
+            "DELETE FROM users WHERE id = ?",
+            &[Value::from(8)]
+        ));
+
+        assert!(!confirmations.confirm("unknown", "DELETE FROM users", &[]));
+    }
+}
@@ -0,0 +1,199 @@
+//! Console session history
+//!
+//! Every console session (see [`DatabaseProvider::open_session`]) has its own
+//! history thread, so two console tabs each see the statements they ran. The
+//! history of a session is forgotten when the session is closed or has been
+//! idle for longer than its idle timeout.
+//!
+//! [`DatabaseProvider::open_session`]: crate::database::traits::DatabaseProvider::open_session
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::schema::QueryResult;
+
+/// Number of statements kept per session
+const HISTORY_CAPACITY: usize = 200;
+
+/// A statement run in a console session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// SQL text as submitted
+    pub sql: String,
+
+    /// Time the statement was run, in milliseconds since the Unix epoch
+    pub executed_at_milliseconds: u64,
+
+    /// Execution time in milliseconds
+    pub execution_time_milliseconds: u64,
+
+    /// Number of rows returned or affected
+    pub affected_rows: u64,
+
+    /// Error message if the statement failed
+    pub error: Option<String>,
+}
+
+impl HistoryEntry {
+    /// Entry for a statement and its outcome
+    pub fn new(sql: &str, result: Result<&QueryResult, String>) -> Self {
+        let executed_at_milliseconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+
+        match result {
+            Ok(result) => Self {
+                sql: sql.to_string(),
+                executed_at_milliseconds,
+                execution_time_milliseconds: result.execution_time_milliseconds,
+                affected_rows: result.affected_rows.max(result.rows.len() as u64),
+                error: result.error.clone(),
+            },
+            Err(error) => Self {
+                sql: sql.to_string(),
+                executed_at_milliseconds,
+                execution_time_milliseconds: 0,
+                affected_rows: 0,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+/// Response listing the history of a console session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHistoryResponse {
+    /// Identifier of the session
+    pub session: String,
+
+    /// Statements run in the session, oldest first
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// History of all open console sessions
+///
+/// Cloning a `ConsoleHistory` is cheap; all clones share the same history.
+#[derive(Clone, Default)]
+pub struct ConsoleHistory {
+    sessions: Arc<Mutex<HashMap<String, SessionHistory>>>,
+}
+
+struct SessionHistory {
+    entries: VecDeque<HistoryEntry>,
+    idle_timeout: Duration,
+    last_used: Instant,
+}
+
+impl ConsoleHistory {
+    /// Start an empty history for a newly opened session
+    pub fn open(&self, session: &str, idle_timeout: Duration) {
+        let mut sessions = self.sessions.lock().unwrap();
+        Self::remove_idle(&mut sessions);
+        sessions.insert(
+            session.to_string(),
+            SessionHistory {
+                entries: VecDeque::new(),
+                idle_timeout,
+                last_used: Instant::now(),
+            },
+        );
+    }
+
+    /// Append a statement to a session's history
+    pub fn record(&self, session: &str, entry: HistoryEntry) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(history) = sessions.get_mut(session) {
+            if history.entries.len() == HISTORY_CAPACITY {
+                history.entries.pop_front();
+            }
+            history.entries.push_back(entry);
+            history.last_used = Instant::now();
+        }
+    }
+
+    /// Statements run in a session, oldest first
+    ///
+    /// Returns `None` if the session is unknown or expired.
+    pub fn entries(&self, session: &str) -> Option<Vec<HistoryEntry>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        Self::remove_idle(&mut sessions);
+        sessions
+            .get(session)
+            .map(|history| history.entries.iter().cloned().collect())
+    }
+
+    /// Forget the history of a closed session
+    pub fn close(&self, session: &str) {
+        self.sessions.lock().unwrap().remove(session);
+    }
+
+    fn remove_idle(sessions: &mut HashMap<String, SessionHistory>) {
+        sessions.retain(|_, history| history.last_used.elapsed() < history.idle_timeout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(affected_rows: u64) -> QueryResult {
+        QueryResult {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            affected_rows,
+            execution_time_milliseconds: 3,
+            error: None,
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn test_history_is_kept_per_session() {
+        let history = ConsoleHistory::default();
+        history.open("a", Duration::from_secs(60));
+        history.open("b", Duration::from_secs(60));
+
+        history.record("a", HistoryEntry::new("BEGIN", Ok(&result(0))));
+        history.record("a", HistoryEntry::new("DELETE FROM users", Ok(&result(4))));
+        history.record(
+            "b",
+            HistoryEntry::new("SELEC 1", Err("syntax error".to_string())),
+        );
+        history.record("unknown", HistoryEntry::new("SELECT 1", Ok(&result(1))));
+
+        let a = history.entries("a").unwrap();
+        assert_eq!(a.len(), 2);
+        assert_eq!(a[1].sql, "DELETE FROM users");
+        assert_eq!(a[1].affected_rows, 4);
+
+        let b = history.entries("b").unwrap();
+        assert_eq!(b[0].error.as_deref(), Some("syntax error"));
+        assert!(history.entries("unknown").is_none());
+
+        history.close("a");
+        assert!(history.entries("a").is_none());
+    }
+
+    #[test]
+    fn test_history_capacity_and_expiry() {
+        let history = ConsoleHistory::default();
+        history.open("a", Duration::from_secs(60));
+        for index in 0..HISTORY_CAPACITY + 5 {
+            history.record(
+                "a",
+                HistoryEntry::new(&format!("SELECT {}", index), Ok(&result(1))),
+            );
+        }
+        let entries = history.entries("a").unwrap();
+        assert_eq!(entries.len(), HISTORY_CAPACITY);
+        assert_eq!(entries[0].sql, "SELECT 5");
+
+        history.open("expired", Duration::ZERO);
+        assert!(history.entries("expired").is_none());
+    }
+}
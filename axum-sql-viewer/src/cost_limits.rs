@@ -0,0 +1,175 @@
+//! Guardrails against expensive queries
+//!
+//! With limits configured, statements sent to the console are `EXPLAIN`ed
+//! before they run and rejected if the planner expects more rows or a higher
+//! cost than allowed, so an accidental cross join does not tie up a shared
+//! database. Estimates come from the top of each statement's plan and are
+//! only as good as the table statistics; placeholders are replaced with `NULL`
+//! for the estimate. SQLite's plans carry no estimates, so the limits only
+//! take effect on PostgreSQL.
+
+use crate::classify::{classify, StatementKind};
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::schema::PlanNode;
+use crate::sql::{replace_placeholders_with_null, split_statements, tokenize, Dialect, TokenKind};
+
+/// Limits on the planner's estimates for a statement (`None` means unlimited)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CostLimits {
+    /// Maximum number of rows the statement is expected to return
+    pub max_estimated_rows: Option<f64>,
+
+    /// Maximum total cost, in the database's own units
+    pub max_estimated_cost: Option<f64>,
+}
+
+impl CostLimits {
+    /// Whether any limit is set
+    pub fn is_limited(&self) -> bool {
+        self.max_estimated_rows.is_some() || self.max_estimated_cost.is_some()
+    }
+
+    /// Explanation of the first limit a plan exceeds, if any
+    ///
+    /// # Arguments
+    ///
+    /// * `plan` - Plan of a single statement; only its top-level steps are
+    ///   looked at, as their estimates include those of their children
+    pub fn exceeded_by(&self, plan: &[PlanNode]) -> Option<String> {
+        for node in plan {
+            let rows = self
+                .max_estimated_rows
+                .zip(node.estimated_rows)
+                .filter(|(max, rows)| rows > max);
+            if let Some((max, rows)) = rows {
+                return Some(format!(
+                    "Query rejected: an estimated {:.0} rows exceeds the limit of {:.0}",
+                    rows, max
+                ));
+            }
+
+            let cost = self
+                .max_estimated_cost
+                .zip(node.estimated_cost)
+                .filter(|(max, cost)| cost > max);
+            if let Some((max, cost)) = cost {
+                return Some(format!(
+                    "Query rejected: an estimated cost of {:.0} exceeds the limit of {:.0}",
+                    cost, max
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Check the estimates of every query and data change in `sql` against `limits`
+///
+/// Other statements (schema changes, `SET`, ...) are not explained. A
+/// statement the database cannot explain is let through, as running it would
+/// report the same error.
+///
+/// # Arguments
+///
+/// * `sql` - SQL as it will be run, with placeholders instead of values
+///
+/// # Returns
+///
+/// `Ok(Some(message))` if a statement exceeds a limit. Errors other than the
+/// database rejecting the statement (e.g. timeouts) are returned as `Err`.
+pub async fn check_cost<DB: DatabaseProvider>(
+    database: &DB,
+    limits: &CostLimits,
+    sql: &str,
+) -> Result<Option<String>, DatabaseError> {
+    if !limits.is_limited() {
+        return Ok(None);
+    }
+
+    let dialect = database.dialect();
+    for statement in split_statements(sql, dialect) {
+        if !is_estimated(&statement, dialect) {
+            continue;
+        }
+
+        let explained_sql = replace_placeholders_with_null(&statement, dialect);
+        match database.explain_plan(&explained_sql, false).await {
+            Ok(explained) => {
+                if let Some(message) = limits.exceeded_by(&explained.plan) {
+                    return Ok(Some(message));
+                }
+            }
+            Err(DatabaseError::Query(_) | DatabaseError::Unsupported(_)) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(None)
+}
+
+/// Whether the cost of a statement is estimated before it runs
+///
+/// `EXPLAIN` is left alone, as it does not run the statement it explains.
+fn is_estimated(statement: &str, dialect: Dialect) -> bool {
+    let first_word = tokenize(statement, dialect)
+        .into_iter()
+        .find(|token| token.kind == TokenKind::Word);
+    match classify(statement, dialect).kind {
+        StatementKind::Query => !first_word.is_some_and(|word| word.is_keyword("EXPLAIN")),
+        StatementKind::Dml => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(estimated_rows: f64, estimated_cost: f64) -> PlanNode {
+        PlanNode {
+            operation: "Nested Loop".to_string(),
+            estimated_rows: Some(estimated_rows),
+            estimated_cost: Some(estimated_cost),
+            ..PlanNode::default()
+        }
+    }
+
+    #[test]
+    fn test_limits_exceeded() {
+        let limits = CostLimits {
+            max_estimated_rows: Some(1_000_000.0),
+            max_estimated_cost: None,
+        };
+        assert_eq!(limits.exceeded_by(&[node(500.0, 1e12)]), None);
+        assert!(limits
+            .exceeded_by(&[node(2.5e9, 10.0)])
+            .unwrap()
+            .contains("2500000000 rows"));
+
+        let limits = CostLimits {
+            max_estimated_rows: None,
+            max_estimated_cost: Some(10_000.0),
+        };
+        assert!(limits
+            .exceeded_by(&[node(1.0, 20_000.0)])
+            .unwrap()
+            .contains("cost of 20000"));
+
+        // Plans without estimates (SQLite) are never rejected
+        assert_eq!(limits.exceeded_by(&[PlanNode::default()]), None);
+        assert!(!CostLimits::default().is_limited());
+    }
+
+    #[test]
+    fn test_estimated_statements() {
+        assert!(is_estimated("SELECT * FROM a, b", Dialect::Postgres));
+        assert!(is_estimated(
+            "UPDATE orders SET total = 0 FROM users",
+            Dialect::Postgres
+        ));
+        assert!(!is_estimated("EXPLAIN SELECT * FROM a", Dialect::Postgres));
+        assert!(!is_estimated(
+            "CREATE INDEX a_b ON a (b)",
+            Dialect::Postgres
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+//! Query results paged through on demand
+//!
+//! A query sent to `/api/query` with a `pageSize` is answered with its first
+//! page and a result handle. The remaining rows are only read from the
+//! database when `/api/query/{id}/rows` asks for them, and rows already read
+//! are kept, so the console can show the first page right away and load more
+//! (or scroll back) later. Until all rows are read a result holds a database
+//! connection, so results are dropped once they have been idle for a while and
+//! only a few are kept at once.
+
+use futures_util::StreamExt;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::database::traits::QueryStream;
+use crate::schema::{QueryPage, QueryStreamLine};
+
+/// Time after which an unused result is dropped
+pub const RESULT_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Maximum number of results kept at once; the least recently used is dropped
+pub const MAX_OPEN_RESULTS: usize = 8;
+
+/// Rows in a page when the request does not say
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Largest page that can be requested
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Rows of a query read so far, and the stream of the rest
+pub struct ResultCursor {
+    columns: Vec<String>,
+    rows: Vec<Value>,
+    remaining: Option<QueryStream>,
+    error: Option<String>,
+}
+
+impl ResultCursor {
+    /// Cursor over the rows of `stream`, none of which are read yet
+    pub fn new(stream: QueryStream) -> Self {
+        Self {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            remaining: Some(stream),
+            error: None,
+        }
+    }
+
+    /// Read rows until `offset + limit` rows and one more are known, or the
+    /// result ends, and return the requested page
+    ///
+    /// The extra row tells whether another page follows. A page that could
+    /// not be completed because reading failed carries the error.
+    pub async fn page(&mut self, offset: usize, limit: usize) -> QueryPage {
+        let wanted = offset.saturating_add(limit).saturating_add(1);
+        while self.rows.len() < wanted {
+            let Some(stream) = self.remaining.as_mut() else {
+                break;
+            };
+            match stream.next().await {
+                Some(Ok(QueryStreamLine::Columns { columns })) => self.columns = columns,
+                Some(Ok(QueryStreamLine::Row { row })) => self.rows.push(row),
+                Some(Ok(_)) => {}
+                Some(Err(error)) => {
+                    self.error = Some(error.to_string());
+                    self.remaining = None;
+                }
+                None => self.remaining = None,
+            }
+        }
+
+        let end = offset.saturating_add(limit).min(self.rows.len());
+        let rows = self.rows.get(offset..end).unwrap_or_default().to_vec();
+        let has_more = self.rows.len() > end;
+        QueryPage {
+            result_id: None,
+            columns: self.columns.clone(),
+            rows,
+            offset: offset as u64,
+            has_more,
+            affected_rows: 0,
+            execution_time_milliseconds: 0,
+            error: self.error.clone().filter(|_| !has_more),
+        }
+    }
+
+    /// Whether every row has been read (or reading failed)
+    pub fn is_complete(&self) -> bool {
+        self.remaining.is_none()
+    }
+}
+
+/// Results that can be paged through, by handle
+///
+/// Cloning `ResultCursors` is cheap; all clones share the same results.
+#[derive(Clone, Default)]
+pub struct ResultCursors {
+    results: Arc<Mutex<HashMap<String, OpenResult>>>,
+    uses: Arc<AtomicU64>,
+}
+
+struct OpenResult {
+    /// The cursor, or `None` while a page is read from it
+    cursor: Option<ResultCursor>,
+    last_used: Instant,
+    /// Value of the use counter at the last use, to find the least recent one
+    last_use: u64,
+}
+
+impl ResultCursors {
+    /// Keep `cursor` to be paged through
+    ///
+    /// # Returns
+    ///
+    /// Handle of the result
+    pub fn open(&self, cursor: ResultCursor) -> String {
+        let last_use = self.next_use();
+        let id = format!("result-{}", last_use);
+
+        let mut results = self.results.lock().unwrap();
+        Self::close_idle(&mut results);
+        if results.len() >= MAX_OPEN_RESULTS {
+            let least_recently_used = results
+                .iter()
+                .filter(|(_, result)| result.cursor.is_some())
+                .min_by_key(|(_, result)| result.last_use)
+                .map(|(id, _)| id.clone());
+            if let Some(least_recently_used) = least_recently_used {
+                results.remove(&least_recently_used);
+            }
+        }
+        results.insert(
+            id.clone(),
+            OpenResult {
+                cursor: Some(cursor),
+                last_used: Instant::now(),
+                last_use,
+            },
+        );
+        id
+    }
+
+    /// Take a cursor to read a page from it
+    ///
+    /// Hand it back with [`ResultCursors::release`]. Fails if the result is
+    /// unknown (or was dropped) or another page is still being read from it.
+    pub fn acquire(&self, id: &str) -> Result<ResultCursor, String> {
+        let mut results = self.results.lock().unwrap();
+        Self::close_idle(&mut results);
+
+        let result = results
+            .get_mut(id)
+            .ok_or_else(|| format!("Result not found: {}", id))?;
+        result.last_used = Instant::now();
+        result.last_use = self.next_use();
+        result
+            .cursor
+            .take()
+            .ok_or_else(|| format!("Result {} is busy reading another page", id))
+    }
+
+    /// Hand back a cursor taken with [`ResultCursors::acquire`]
+    ///
+    /// The cursor is dropped if the result was closed in the meantime.
+    pub fn release(&self, id: &str, cursor: ResultCursor) {
+        if let Some(result) = self.results.lock().unwrap().get_mut(id) {
+            result.cursor = Some(cursor);
+            result.last_used = Instant::now();
+            result.last_use = self.next_use();
+        }
+    }
+
+    /// Drop a result, stopping its query if rows are still unread
+    ///
+    /// # Returns
+    ///
+    /// Whether the result existed
+    pub fn close(&self, id: &str) -> bool {
+        self.results.lock().unwrap().remove(id).is_some()
+    }
+
+    /// Next value of the use counter
+    fn next_use(&self) -> u64 {
+        self.uses.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Drop results that have not been used within the idle timeout
+    ///
+    /// Results a page is being read from are kept.
+    fn close_idle(results: &mut HashMap<String, OpenResult>) {
+        results.retain(|_, result| {
+            result.cursor.is_none() || result.last_used.elapsed() < RESULT_IDLE_TIMEOUT
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::traits::DatabaseError;
+    use futures_util::FutureExt;
+    use serde_json::json;
+
+    fn cursor(rows: usize, error: Option<&str>) -> ResultCursor {
+        let mut lines = vec![Ok(QueryStreamLine::Columns {
+            columns: vec!["id".to_string()],
+        })];
+        lines.extend((0..rows).map(|id| {
+            Ok(QueryStreamLine::Row {
+                row: json!({ "id": id }),
+            })
+        }));
+        lines.extend(error.map(|error| Err(DatabaseError::Query(error.to_string()))));
+        ResultCursor::new(Box::pin(futures_util::stream::iter(lines)))
+    }
+
+    #[test]
+    fn test_pages_are_read_on_demand() {
+        let mut cursor = cursor(5, None);
+
+        let page = cursor.page(0, 2).now_or_never().unwrap();
+        assert_eq!(page.columns, vec!["id"]);
+        assert_eq!(page.rows, vec![json!({ "id": 0 }), json!({ "id": 1 })]);
+        assert!(page.has_more);
+        assert!(!cursor.is_complete());
+
+        let page = cursor.page(4, 2).now_or_never().unwrap();
+        assert_eq!(page.rows, vec![json!({ "id": 4 })]);
+        assert!(!page.has_more);
+        assert!(cursor.is_complete());
+
+        // Rows already read can be paged through again
+        let page = cursor.page(1, 2).now_or_never().unwrap();
+        assert_eq!(page.rows, vec![json!({ "id": 1 }), json!({ "id": 2 })]);
+        assert!(page.has_more);
+
+        assert!(cursor.page(9, 2).now_or_never().unwrap().rows.is_empty());
+    }
+
+    #[test]
+    fn test_error_ends_the_last_page() {
+        let mut cursor = cursor(3, Some("disk I/O error"));
+
+        assert_eq!(cursor.page(0, 2).now_or_never().unwrap().error, None);
+        let page = cursor.page(2, 2).now_or_never().unwrap();
+        assert_eq!(page.rows, vec![json!({ "id": 2 })]);
+        assert!(!page.has_more);
+        assert!(page.error.unwrap().contains("disk I/O error"));
+    }
+
+    #[test]
+    fn test_result_lifecycle() {
+        let cursors = ResultCursors::default();
+        let id = cursors.open(cursor(1, None));
+
+        let cursor = cursors.acquire(&id).unwrap();
+        assert!(matches!(cursors.acquire(&id), Err(error) if error.contains("busy")));
+        cursors.release(&id, cursor);
+        assert!(cursors.acquire(&id).is_ok());
+
+        assert!(cursors.close(&id));
+        assert!(matches!(cursors.acquire(&id), Err(error) if error.contains("not found")));
+        assert!(!cursors.close(&id));
+    }
+
+    #[test]
+    fn test_least_recently_used_result_is_dropped() {
+        let cursors = ResultCursors::default();
+        let first = cursors.open(cursor(1, None));
+        for _ in 1..MAX_OPEN_RESULTS {
+            cursors.open(cursor(1, None));
+        }
+
+        let latest = cursors.open(cursor(1, None));
+        assert!(cursors.acquire(&first).is_err());
+        assert!(cursors.acquire(&latest).is_ok());
+    }
+}
@@ -0,0 +1,213 @@
+//! Database files attached to a SQLite database
+//!
+//! SQLite attaches files to a single connection, so the provider keeps the
+//! list of attachments here and brings every connection it takes from the pool
+//! in line with it before use. Tables of an attached file are addressed as
+//! `alias.table`; names without a known alias in front refer to the main
+//! database, even if they contain a dot.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::database::traits::DatabaseError;
+use crate::schema::AttachedDatabase;
+use crate::sql;
+
+/// Maximum number of attached files (SQLite's default `SQLITE_MAX_ATTACHED`)
+pub const MAX_ATTACHMENTS: usize = 10;
+
+/// Schema names used by SQLite itself, the seed reset and named snapshots
+const RESERVED_ALIASES: &[&str] = &["main", "temp", "seed", "snapshot"];
+
+/// Files attached to every connection of a provider
+#[derive(Default)]
+pub struct Attachments {
+    attached: Mutex<Vec<AttachedDatabase>>,
+    /// Whether anything was ever attached; until then connections need no syncing
+    used: AtomicBool,
+}
+
+impl Attachments {
+    /// The current attachments, in the order they were added
+    pub fn list(&self) -> Vec<AttachedDatabase> {
+        self.attached.lock().unwrap().clone()
+    }
+
+    /// Whether no file was ever attached, so no connection can have any
+    pub fn is_unused(&self) -> bool {
+        !self.used.load(Ordering::Relaxed)
+    }
+
+    /// Add a file under `alias`
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - Schema name; letters, digits and underscores, not starting with a digit
+    /// * `path` - Path of the database file, as SQLite reports it in `PRAGMA database_list`
+    pub fn add(&self, alias: &str, path: &str) -> Result<AttachedDatabase, DatabaseError> {
+        validate_alias(alias)?;
+
+        let mut attached = self.attached.lock().unwrap();
+        if attached
+            .iter()
+            .any(|attachment| attachment.alias.eq_ignore_ascii_case(alias))
+        {
+            return Err(DatabaseError::InvalidAttachment(format!(
+                "alias '{}' is already in use",
+                alias
+            )));
+        }
+        if attached.len() >= MAX_ATTACHMENTS {
+            return Err(DatabaseError::InvalidAttachment(format!(
+                "at most {} databases can be attached",
+                MAX_ATTACHMENTS
+            )));
+        }
+
+        let attachment = AttachedDatabase {
+            alias: alias.to_string(),
+            path: path.to_string(),
+        };
+        attached.push(attachment.clone());
+        self.used.store(true, Ordering::Relaxed);
+        Ok(attachment)
+    }
+
+    /// Remove the file attached under `alias` (compared ignoring case)
+    pub fn remove(&self, alias: &str) -> Result<AttachedDatabase, DatabaseError> {
+        let mut attached = self.attached.lock().unwrap();
+        let position = attached
+            .iter()
+            .position(|attachment| attachment.alias.eq_ignore_ascii_case(alias))
+            .ok_or_else(|| DatabaseError::AttachmentNotFound(alias.to_string()))?;
+        Ok(attached.remove(position))
+    }
+
+    /// Split `table` into the alias of an attached file and the name within it
+    ///
+    /// The alias is returned as it was attached; names without a known alias
+    /// in front belong to the main database.
+    pub fn split<'a>(&self, table: &'a str) -> (Option<String>, &'a str) {
+        if let Some((alias, name)) = table.split_once('.') {
+            let attached = self.attached.lock().unwrap();
+            if let Some(attachment) = attached
+                .iter()
+                .find(|attachment| attachment.alias.eq_ignore_ascii_case(alias))
+            {
+                return (Some(attachment.alias.clone()), name);
+            }
+        }
+        (None, table)
+    }
+
+    /// Quote a possibly alias-qualified table name for use in SQL
+    pub fn quote_table(&self, table: &str) -> String {
+        let (alias, name) = self.split(table);
+        format!(
+            "{}{}",
+            schema_prefix(alias.as_deref()),
+            sql::quote_identifier(name)
+        )
+    }
+}
+
+/// Check that `alias` can be used as the schema name of an attached file
+pub fn validate_alias(alias: &str) -> Result<(), DatabaseError> {
+    let valid = alias
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && alias
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || character == '_');
+    if !valid {
+        return Err(DatabaseError::InvalidAttachment(format!(
+            "alias '{}' must consist of letters, digits and underscores",
+            alias
+        )));
+    }
+
+    if RESERVED_ALIASES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(alias))
+    {
+        return Err(DatabaseError::InvalidAttachment(format!(
+            "alias '{}' is reserved",
+            alias
+        )));
+    }
+
+    Ok(())
+}
+
+/// `"alias".` in front of names in an attached file, nothing for the main database
+pub fn schema_prefix(alias: Option<&str>) -> String {
+    match alias {
+        Some(alias) => format!("{}.", sql::quote_identifier(alias)),
+        None => String::new(),
+    }
+}
+
+/// Name of `table` as listed to clients: `alias.table` for attached files
+pub fn qualify(alias: Option<&str>, table: &str) -> String {
+    match alias {
+        Some(alias) => format!("{}.{}", alias, table),
+        None => table.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_alias() {
+        assert!(validate_alias("colleague").is_ok());
+        assert!(validate_alias("_copy_2").is_ok());
+        assert!(validate_alias("").is_err());
+        assert!(validate_alias("2nd").is_err());
+        assert!(validate_alias("their db").is_err());
+        assert!(validate_alias("Main").is_err());
+        assert!(validate_alias("seed").is_err());
+        assert!(validate_alias("snapshot").is_err());
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let attachments = Attachments::default();
+        assert!(attachments.is_unused());
+
+        attachments.add("other", "/data/other.db").unwrap();
+        assert!(!attachments.is_unused());
+        assert!(attachments.add("OTHER", "/data/again.db").is_err());
+
+        assert_eq!(attachments.remove("Other").unwrap().path, "/data/other.db");
+        assert!(matches!(
+            attachments.remove("other"),
+            Err(DatabaseError::AttachmentNotFound(_))
+        ));
+        // Connections may still have the file attached until they are synced
+        assert!(!attachments.is_unused());
+    }
+
+    #[test]
+    fn test_split_and_quote() {
+        let attachments = Attachments::default();
+        attachments.add("theirs", "/data/theirs.db").unwrap();
+
+        assert_eq!(
+            attachments.split("Theirs.users"),
+            (Some("theirs".to_string()), "users")
+        );
+        assert_eq!(attachments.split("users"), (None, "users"));
+        assert_eq!(attachments.split("v1.users"), (None, "v1.users"));
+
+        assert_eq!(
+            attachments.quote_table("theirs.users"),
+            "\"theirs\".\"users\""
+        );
+        assert_eq!(attachments.quote_table("v1.users"), "\"v1.users\"");
+        assert_eq!(qualify(Some("theirs"), "users"), "theirs.users");
+        assert_eq!(qualify(None, "users"), "users");
+    }
+}
@@ -0,0 +1,37 @@
+//! Row limits of the built-in providers
+//!
+//! Pages of table rows and raw query results are capped so a careless request
+//! cannot load a whole table into memory. The defaults suit a laptop; a
+//! larger machine can allow more, and CI smoke tests may want less.
+
+/// Default maximum number of rows in a page of table rows
+pub const DEFAULT_MAX_PAGE_SIZE: u64 = 500;
+
+/// Default maximum number of rows in a raw query result
+pub const DEFAULT_MAX_RESULT_ROWS: u64 = 10_000;
+
+/// Tables counted at the same time when listing tables with row counts
+///
+/// Each count holds a connection of the pool, so a few are left for other
+/// requests.
+pub const TABLE_COUNT_CONCURRENCY: usize = 4;
+
+/// Maximum numbers of rows a provider returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultLimits {
+    /// Rows in a page of table rows; larger `limit`s are reduced to this
+    pub max_page_size: u64,
+
+    /// Rows in the result of raw SQL; larger results fail with
+    /// [`DatabaseError::TooManyRows`](crate::DatabaseError::TooManyRows)
+    pub max_result_rows: u64,
+}
+
+impl Default for ResultLimits {
+    fn default() -> Self {
+        Self {
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            max_result_rows: DEFAULT_MAX_RESULT_ROWS,
+        }
+    }
+}
@@ -3,11 +3,30 @@
 //! This module provides a database-agnostic interface for schema discovery
 //! and data retrieval.
 
+pub mod limits;
 pub mod traits;
 
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub mod running;
+
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub mod sessions;
+
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub mod streaming;
+
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub mod timeouts;
+
+#[cfg(feature = "sqlite")]
+pub mod attachments;
+
 #[cfg(feature = "sqlite")]
 pub mod sqlite;
 
+#[cfg(feature = "postgres")]
+pub mod pg_values;
+
 #[cfg(feature = "postgres")]
 pub mod postgres;
 
@@ -0,0 +1,565 @@
+//! PostgreSQL values sqlx cannot decode without extra features or known types
+//!
+//! Rows of prepared statements arrive in PostgreSQL's binary format. For
+//! `interval`, `money`, `inet`, `cidr`, `macaddr`, `macaddr8`, `bit` and
+//! `varbit` the values are decoded here into the text PostgreSQL itself would
+//! print, so they show up in the grid instead of as NULL.
+//!
+//! Ranges and composite (row) types become JSON objects: ranges with their
+//! bounds, composites with their fields. Their elements are decoded by type
+//! OID for the common scalar types; others are null.
+
+use serde_json::{json, Map, Value};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::big_integers::BigIntegerFormat;
+use crate::histogram::civil_from_days;
+
+/// Address family of IPv4 in the binary `inet`/`cidr` format
+const PGSQL_AF_INET: u8 = 2;
+
+/// Address family of IPv6 in the binary `inet`/`cidr` format
+const PGSQL_AF_INET6: u8 = 3;
+
+/// Days from the Unix epoch to PostgreSQL's epoch (2000-01-01)
+const POSTGRES_EPOCH_DAYS: i64 = 10_957;
+
+/// Flags of the binary range format
+const RANGE_EMPTY: u8 = 0x01;
+const RANGE_LOWER_INCLUSIVE: u8 = 0x02;
+const RANGE_UPPER_INCLUSIVE: u8 = 0x04;
+const RANGE_LOWER_INFINITE: u8 = 0x08;
+const RANGE_UPPER_INFINITE: u8 = 0x10;
+
+/// Whether values of the type named `type_name` (as reported by sqlx) are decoded here
+pub fn is_text_decoded(type_name: &str) -> bool {
+    matches!(
+        type_name,
+        "INTERVAL" | "MONEY" | "INET" | "CIDR" | "MACADDR" | "MACADDR8" | "BIT" | "VARBIT"
+    )
+}
+
+/// Text form of a binary value of the type named `type_name`
+///
+/// Returns `None` for other types and malformed values.
+pub fn binary_to_text(type_name: &str, bytes: &[u8]) -> Option<String> {
+    match type_name {
+        "INTERVAL" => {
+            let microseconds = i64::from_be_bytes(bytes.get(0..8)?.try_into().ok()?);
+            let days = i32::from_be_bytes(bytes.get(8..12)?.try_into().ok()?);
+            let months = i32::from_be_bytes(bytes.get(12..16)?.try_into().ok()?);
+            Some(format_interval(months, days, microseconds))
+        }
+        "MONEY" => Some(format_money(i64::from_be_bytes(bytes.try_into().ok()?))),
+        "INET" | "CIDR" => format_inet(bytes),
+        "MACADDR" | "MACADDR8" if matches!(bytes.len(), 6 | 8) => Some(
+            bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<_>>()
+                .join(":"),
+        ),
+        "BIT" | "VARBIT" => format_bits(bytes),
+        _ => None,
+    }
+}
+
+/// Interval in PostgreSQL's default output style, e.g. `1 year 2 mons 3 days 04:05:06.5`
+fn format_interval(months: i32, days: i32, microseconds: i64) -> String {
+    let mut parts = Vec::new();
+    let mut negative = false;
+    for (value, unit) in [
+        (i64::from(months / 12), "year"),
+        (i64::from(months % 12), "mon"),
+        (i64::from(days), "day"),
+    ] {
+        if value != 0 {
+            // A positive field after a negative one gets an explicit sign
+            let sign = if negative && value > 0 { "+" } else { "" };
+            let plural = if value == 1 { "" } else { "s" };
+            parts.push(format!("{}{} {}{}", sign, value, unit, plural));
+            negative = value < 0;
+        }
+    }
+
+    if microseconds != 0 || parts.is_empty() {
+        let sign = if microseconds < 0 {
+            "-"
+        } else if negative {
+            "+"
+        } else {
+            ""
+        };
+        let total = microseconds.unsigned_abs();
+        let seconds = total / 1_000_000;
+        let mut time = format!(
+            "{}{:02}:{:02}:{:02}",
+            sign,
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        );
+        let fraction = total % 1_000_000;
+        if fraction != 0 {
+            time.push_str(format!(".{:06}", fraction).trim_end_matches('0'));
+        }
+        parts.push(time);
+    }
+
+    parts.join(" ")
+}
+
+/// Amount of money in cents as a decimal number, e.g. `-12.34`
+///
+/// PostgreSQL stores money in the smallest unit of the currency of
+/// `lc_monetary`; two fraction digits are assumed, as for most currencies.
+fn format_money(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{}{}.{:02}", sign, cents / 100, cents % 100)
+}
+
+/// Network address of the binary `inet`/`cidr` format, with the prefix length where needed
+///
+/// The format is the family, the prefix length, whether it is a `cidr`, the
+/// address length and the address. Host addresses of `inet` print without
+/// prefix length, like PostgreSQL does.
+fn format_inet(bytes: &[u8]) -> Option<String> {
+    let [family, bits, is_cidr, length, address @ ..] = bytes else {
+        return None;
+    };
+    if usize::from(*length) != address.len() {
+        return None;
+    }
+
+    let (address, max_bits) = match *family {
+        PGSQL_AF_INET => (
+            Ipv4Addr::from(<[u8; 4]>::try_from(address).ok()?).to_string(),
+            32,
+        ),
+        PGSQL_AF_INET6 => (
+            Ipv6Addr::from(<[u8; 16]>::try_from(address).ok()?).to_string(),
+            128,
+        ),
+        _ => return None,
+    };
+    if *is_cidr == 0 && *bits == max_bits {
+        Some(address)
+    } else {
+        Some(format!("{}/{}", address, bits))
+    }
+}
+
+/// Bit string of the binary `bit`/`varbit` format, e.g. `10110`
+///
+/// The format is the number of bits followed by the bits, most significant first.
+fn format_bits(bytes: &[u8]) -> Option<String> {
+    let length = usize::try_from(i32::from_be_bytes(bytes.get(0..4)?.try_into().ok()?)).ok()?;
+    let data = &bytes[4..];
+    if data.len() * 8 < length {
+        return None;
+    }
+
+    Some(
+        (0..length)
+            .map(|bit| {
+                if data[bit / 8] & (0x80 >> (bit % 8)) != 0 {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect(),
+    )
+}
+
+/// JSON value of a binary value of the built-in scalar type with OID `oid`
+///
+/// Numbers and booleans become JSON numbers and booleans (`int8` values in
+/// the `big_integers` format),
+/// `numeric` a string (to keep its precision), dates `YYYY-MM-DD`,
+/// timestamps `YYYY-MM-DD HH:MM:SS` (with `+00:00` for `timestamptz`) and
+/// `json`/`jsonb` their JSON.
+/// Returns `None` for other types and malformed values.
+pub fn scalar_to_json(oid: u32, bytes: &[u8], big_integers: BigIntegerFormat) -> Option<Value> {
+    let value = match oid {
+        16 => Value::Bool(*bytes.first()? != 0),
+        21 => json!(i16::from_be_bytes(bytes.try_into().ok()?)),
+        23 => json!(i32::from_be_bytes(bytes.try_into().ok()?)),
+        20 => big_integers.to_json(i64::from_be_bytes(bytes.try_into().ok()?)),
+        700 => json!(f32::from_be_bytes(bytes.try_into().ok()?)),
+        701 => json!(f64::from_be_bytes(bytes.try_into().ok()?)),
+        // text, varchar, bpchar, name
+        25 | 1043 | 1042 | 19 => Value::String(std::str::from_utf8(bytes).ok()?.to_string()),
+        1700 => Value::String(format_numeric(bytes)?),
+        1082 => {
+            let days = i32::from_be_bytes(bytes.try_into().ok()?);
+            Value::String(format_date(i64::from(days)))
+        }
+        1114 | 1184 => {
+            let microseconds = i64::from_be_bytes(bytes.try_into().ok()?);
+            let timestamp = format_timestamp(microseconds);
+            Value::String(if oid == 1184 {
+                format!("{}+00:00", timestamp.replacen(' ', "T", 1))
+            } else {
+                timestamp
+            })
+        }
+        2950 => Value::String(format_uuid(bytes)?),
+        114 => serde_json::from_slice(bytes).ok()?,
+        // jsonb is prefixed with a version byte
+        3802 => serde_json::from_slice(bytes.get(1..)?).ok()?,
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// JSON object of a binary range whose bounds are of type `element_oid`
+///
+/// Ranges are `{"lower", "upper", "lowerInclusive", "upperInclusive"}` with
+/// null for unbounded sides, and `{"empty": true}` when empty.
+pub fn range_to_json(
+    element_oid: u32,
+    bytes: &[u8],
+    big_integers: BigIntegerFormat,
+) -> Option<Value> {
+    let (&flags, mut rest) = bytes.split_first()?;
+    if flags & RANGE_EMPTY != 0 {
+        return Some(json!({ "empty": true }));
+    }
+
+    let mut bound = |infinite: u8| -> Option<Value> {
+        if flags & infinite != 0 {
+            return Some(Value::Null);
+        }
+        let (bytes, remaining) = length_prefixed(rest)?;
+        rest = remaining;
+        Some(
+            bytes
+                .and_then(|bytes| scalar_to_json(element_oid, bytes, big_integers))
+                .unwrap_or(Value::Null),
+        )
+    };
+    let lower = bound(RANGE_LOWER_INFINITE)?;
+    let upper = bound(RANGE_UPPER_INFINITE)?;
+
+    Some(json!({
+        "lower": lower,
+        "upper": upper,
+        "lowerInclusive": flags & RANGE_LOWER_INCLUSIVE != 0,
+        "upperInclusive": flags & RANGE_UPPER_INCLUSIVE != 0,
+    }))
+}
+
+/// JSON object of a binary composite value, by field name
+///
+/// Fields without a known name are named `f1`, `f2`, ... like PostgreSQL's
+/// `row_to_json` does. Nested composites and unknown types are null.
+pub fn record_to_json(
+    field_names: &[String],
+    bytes: &[u8],
+    big_integers: BigIntegerFormat,
+) -> Option<Value> {
+    let count = i32::from_be_bytes(bytes.get(0..4)?.try_into().ok()?);
+    let mut rest = &bytes[4..];
+    let mut fields = Map::new();
+    for index in 0..usize::try_from(count).ok()? {
+        let oid = u32::from_be_bytes(rest.get(0..4)?.try_into().ok()?);
+        let (value, remaining) = length_prefixed(&rest[4..])?;
+        rest = remaining;
+
+        let name = field_names
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| format!("f{}", index + 1));
+        let value = value
+            .and_then(|bytes| scalar_to_json(oid, bytes, big_integers))
+            .unwrap_or(Value::Null);
+        fields.insert(name, value);
+    }
+    Some(Value::Object(fields))
+}
+
+/// Value prefixed with its length (-1 for NULL), and the bytes after it
+fn length_prefixed(bytes: &[u8]) -> Option<(Option<&[u8]>, &[u8])> {
+    let length = i32::from_be_bytes(bytes.get(0..4)?.try_into().ok()?);
+    let rest = &bytes[4..];
+    match usize::try_from(length) {
+        Ok(length) => {
+            let value = rest.get(..length)?;
+            Some((Some(value), &rest[length..]))
+        }
+        Err(_) => Some((None, rest)),
+    }
+}
+
+/// Decimal text of a binary `numeric`
+///
+/// The format is the number of base-10000 digits, the weight of the first
+/// digit, the sign, the number of decimal places and the digits.
+fn format_numeric(bytes: &[u8]) -> Option<String> {
+    let word = |index: usize| -> Option<u16> {
+        Some(u16::from_be_bytes(
+            bytes.get(index * 2..index * 2 + 2)?.try_into().ok()?,
+        ))
+    };
+    let count = usize::from(word(0)?);
+    let weight = i64::from(word(1)? as i16);
+    let sign = word(2)?;
+    let scale = usize::from(word(3)?);
+    let digits = (0..count)
+        .map(|index| word(4 + index))
+        .collect::<Option<Vec<u16>>>()?;
+    match sign {
+        0xC000 => return Some("NaN".to_string()),
+        0xD000 => return Some("Infinity".to_string()),
+        0xF000 => return Some("-Infinity".to_string()),
+        _ => {}
+    }
+
+    // Digit of the group with weight `weight - index`, 0 outside the stored digits
+    let digit = |index: i64| -> u16 {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| digits.get(index).copied())
+            .unwrap_or(0)
+    };
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&digit(0).to_string());
+        for index in 1..=weight {
+            text.push_str(&format!("{:04}", digit(index)));
+        }
+    }
+    if scale > 0 {
+        let fraction: String = (1..=(scale as i64 + 3) / 4)
+            .map(|group| format!("{:04}", digit(weight + group)))
+            .collect();
+        text.push('.');
+        text.push_str(&fraction[..scale]);
+    }
+    Some(text)
+}
+
+/// `YYYY-MM-DD` of `days` since 2000-01-01
+fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days + POSTGRES_EPOCH_DAYS);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// `YYYY-MM-DD HH:MM:SS[.ffffff]` of `microseconds` since 2000-01-01 00:00:00
+fn format_timestamp(microseconds: i64) -> String {
+    let seconds = microseconds.div_euclid(1_000_000);
+    let fraction = microseconds.rem_euclid(1_000_000);
+    let time = seconds.rem_euclid(86_400);
+    let mut text = format!(
+        "{} {:02}:{:02}:{:02}",
+        format_date(seconds.div_euclid(86_400)),
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    );
+    if fraction != 0 {
+        text.push_str(format!(".{:06}", fraction).trim_end_matches('0'));
+    }
+    text
+}
+
+/// Hyphenated text of a binary `uuid`
+fn format_uuid(bytes: &[u8]) -> Option<String> {
+    if bytes.len() != 16 {
+        return None;
+    }
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(months: i32, days: i32, microseconds: i64) -> Vec<u8> {
+        [
+            microseconds.to_be_bytes().as_slice(),
+            &days.to_be_bytes(),
+            &months.to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_interval() {
+        assert_eq!(
+            binary_to_text("INTERVAL", &interval(14, 3, 14_706_500_000)),
+            Some("1 year 2 mons 3 days 04:05:06.5".to_string())
+        );
+        assert_eq!(
+            binary_to_text("INTERVAL", &interval(0, -1, 3_600_000_000)),
+            Some("-1 days +01:00:00".to_string())
+        );
+        assert_eq!(
+            binary_to_text("INTERVAL", &interval(0, 0, 0)),
+            Some("00:00:00".to_string())
+        );
+        assert_eq!(binary_to_text("INTERVAL", &[0; 4]), None);
+    }
+
+    #[test]
+    fn test_money() {
+        assert_eq!(
+            binary_to_text("MONEY", &123_456i64.to_be_bytes()),
+            Some("1234.56".to_string())
+        );
+        assert_eq!(
+            binary_to_text("MONEY", &(-5i64).to_be_bytes()),
+            Some("-0.05".to_string())
+        );
+    }
+
+    #[test]
+    fn test_inet() {
+        assert_eq!(
+            binary_to_text("INET", &[2, 32, 0, 4, 192, 168, 0, 1]),
+            Some("192.168.0.1".to_string())
+        );
+        assert_eq!(
+            binary_to_text("CIDR", &[2, 24, 1, 4, 10, 0, 0, 0]),
+            Some("10.0.0.0/24".to_string())
+        );
+
+        let mut ipv6 = vec![3, 64, 0, 16, 0x20, 0x01, 0x0d, 0xb8];
+        ipv6.extend([0; 12]);
+        assert_eq!(
+            binary_to_text("INET", &ipv6),
+            Some("2001:db8::/64".to_string())
+        );
+        assert_eq!(binary_to_text("INET", &[2, 32, 0, 4, 1]), None);
+    }
+
+    fn prefixed(bytes: &[u8]) -> Vec<u8> {
+        [(bytes.len() as i32).to_be_bytes().as_slice(), bytes].concat()
+    }
+
+    #[test]
+    fn test_range_to_json() {
+        // [1,10)
+        let mut int4range = vec![RANGE_LOWER_INCLUSIVE];
+        int4range.extend(prefixed(&1i32.to_be_bytes()));
+        int4range.extend(prefixed(&10i32.to_be_bytes()));
+        assert_eq!(
+            range_to_json(23, &int4range, BigIntegerFormat::Number),
+            Some(json!({
+                "lower": 1,
+                "upper": 10,
+                "lowerInclusive": true,
+                "upperInclusive": false
+            }))
+        );
+
+        // [2024-01-01 00:00:00+00,)
+        let mut tstzrange = vec![RANGE_LOWER_INCLUSIVE | RANGE_UPPER_INFINITE];
+        tstzrange.extend(prefixed(&(8766i64 * 86_400 * 1_000_000).to_be_bytes()));
+        assert_eq!(
+            range_to_json(1184, &tstzrange, BigIntegerFormat::Number),
+            Some(json!({
+                "lower": "2024-01-01T00:00:00+00:00",
+                "upper": null,
+                "lowerInclusive": true,
+                "upperInclusive": false
+            }))
+        );
+
+        assert_eq!(
+            range_to_json(23, &[RANGE_EMPTY], BigIntegerFormat::Number),
+            Some(json!({ "empty": true }))
+        );
+    }
+
+    #[test]
+    fn test_record_to_json() {
+        let mut record = 3i32.to_be_bytes().to_vec();
+        record.extend(25u32.to_be_bytes());
+        record.extend(prefixed(b"Main St"));
+        record.extend(23u32.to_be_bytes());
+        record.extend(prefixed(&42i32.to_be_bytes()));
+        record.extend(1700u32.to_be_bytes());
+        record.extend((-1i32).to_be_bytes());
+
+        let names = ["street".to_string(), "number".to_string()];
+        assert_eq!(
+            record_to_json(&names, &record, BigIntegerFormat::Number),
+            Some(json!({ "street": "Main St", "number": 42, "f3": null }))
+        );
+    }
+
+    #[test]
+    fn test_scalar_to_json() {
+        // 12345.678: digits 1, 2345, 6780 with weight 1 and 3 decimal places
+        let numeric: Vec<u8> = [3u16, 1, 0, 3, 1, 2345, 6780]
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect();
+        assert_eq!(
+            scalar_to_json(1700, &numeric, BigIntegerFormat::Number),
+            Some(json!("12345.678"))
+        );
+
+        // -0.05: digit 500 with weight -1 and 2 decimal places
+        let numeric: Vec<u8> = [1u16, 0xFFFF, 0x4000, 2, 500]
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect();
+        assert_eq!(
+            scalar_to_json(1700, &numeric, BigIntegerFormat::Number),
+            Some(json!("-0.05"))
+        );
+
+        assert_eq!(
+            scalar_to_json(1082, &(-1i32).to_be_bytes(), BigIntegerFormat::Number),
+            Some(json!("1999-12-31"))
+        );
+        assert_eq!(
+            scalar_to_json(1114, &1_500_000i64.to_be_bytes(), BigIntegerFormat::Number),
+            Some(json!("2000-01-01 00:00:01.5"))
+        );
+        assert_eq!(
+            scalar_to_json(600, &[0; 16], BigIntegerFormat::Number),
+            None
+        );
+
+        let big = 9_007_199_254_740_993i64.to_be_bytes();
+        assert_eq!(
+            scalar_to_json(20, &big, BigIntegerFormat::Number),
+            Some(json!(9_007_199_254_740_993i64))
+        );
+        assert_eq!(
+            scalar_to_json(20, &big, BigIntegerFormat::SafeNumber),
+            Some(json!("9007199254740993"))
+        );
+    }
+
+    #[test]
+    fn test_macaddr_and_bits() {
+        assert_eq!(
+            binary_to_text("MACADDR", &[0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]),
+            Some("08:00:2b:01:02:03".to_string())
+        );
+        assert_eq!(
+            binary_to_text("VARBIT", &[0, 0, 0, 5, 0b1011_0000]),
+            Some("10110".to_string())
+        );
+        assert_eq!(binary_to_text("BIT", &[0, 0, 0, 9, 0xff]), None);
+    }
+}
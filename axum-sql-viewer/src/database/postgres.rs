@@ -1,17 +1,84 @@
 //! PostgreSQL database provider implementation
 
-use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::big_integers::BigIntegerFormat;
+use crate::checksum::{checksum_expressions, table_checksum};
+use crate::codegen::column_kind;
+use crate::column_stats::{self, ColumnStats};
+use crate::database::limits::ResultLimits;
+use crate::database::pg_values;
+use crate::database::running::RunningQueries;
+use crate::database::sessions::{Sessions, SESSION_IDLE_TIMEOUT};
+use crate::database::streaming;
+use crate::database::timeouts::{is_statement_timeout, Timeouts};
+use crate::database::traits::{DatabaseError, DatabaseProvider, NotificationStream, QueryStream};
+use crate::duplicates::{self, DuplicatesResponse};
+use crate::explain;
+use crate::filters;
+use crate::fixtures::{load_order, validate_table_rows};
+use crate::histogram::{self, Histogram};
+use crate::keyset::{self, RowCursor};
+use crate::named_snapshots::validate_snapshot_name;
+use crate::references::{cascade_delete_sql, Reference};
+use crate::row_key::{primary_key_columns, validate_columns, validate_new_rows, value_to_key_text};
+use crate::runtime;
+use crate::sample::{self, SampleMethod, SampleResponse};
 use crate::schema::{
-    ColumnInfo, CountResponse, ForeignKey, IndexInfo, QueryResult, RowQuery, RowsResponse,
-    SortOrder, TableInfo, TableSchema,
+    ActiveStatement, ActivityResponse, Capabilities, ColumnInfo, CountAccuracy, CountResponse,
+    DeletedRows, ExplainResponse, FailedStatement, FixtureTable, ForeignKey, IndexInfo,
+    InsertedRows, LoadedFixture, MaintenanceOperation, MaintenanceResult, NamedSnapshot,
+    Notification, QueryResult, RowAddress, RowQuery, RowsResponse, ScriptResult, ScriptStatement,
+    SeedStatus, SessionResponse, SnapshotResponse, SnapshotRowDifferences, TableChecksum,
+    TableDefinition, TableInfo, TableSchema, TimeoutKind,
 };
+use crate::search;
+use crate::spatial::{self, GeometryFormat};
+use crate::sql::{self, Dialect};
 use async_trait::async_trait;
-use sqlx::{postgres::PgRow, Column, PgPool, Row, TypeInfo};
+use futures_util::{StreamExt, TryStreamExt};
+use sqlx::{
+    pool::PoolConnection,
+    postgres::{
+        PgArguments, PgConnection, PgHasArrayType, PgListener, PgRow, PgTypeKind, PgValueFormat,
+    },
+    query::Query,
+    types::chrono,
+    Column, Connection, Executor, PgPool, Postgres, Row, Transaction, TypeInfo, ValueRef,
+};
 use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Time after which an unused snapshot is released
+const SNAPSHOT_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Maximum number of snapshots open at once (each one holds a pool connection)
+const MAX_OPEN_SNAPSHOTS: usize = 8;
+
+/// Attempts at copying a database, which fails if a connection sneaks in meanwhile
+const DATABASE_COPY_ATTEMPTS: usize = 3;
+
+/// Prefix of the names of the schemas holding named snapshots
+const NAMED_SNAPSHOT_SCHEMA_PREFIX: &str = "sql_viewer_snapshot_";
+
+/// Open snapshot together with the transaction that exported it
+///
+/// The exported snapshot stays importable only while this transaction is open.
+struct SnapshotSession {
+    transaction: Transaction<'static, Postgres>,
+    last_used: Instant,
+}
 
 /// PostgreSQL database provider
 pub struct PostgresProvider {
     pool: PgPool,
+    snapshots: Mutex<HashMap<String, SnapshotSession>>,
+    sessions: Sessions<PgConnection>,
+    /// Backend process ids of running cancellable queries
+    running: RunningQueries<i32>,
+    timeouts: Timeouts,
+    limits: ResultLimits,
+    geometry_format: GeometryFormat,
+    big_integers: BigIntegerFormat,
 }
 
 impl PostgresProvider {
@@ -21,16 +88,101 @@ impl PostgresProvider {
     ///
     /// * `pool` - PostgreSQL connection pool
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            snapshots: Mutex::new(HashMap::new()),
+            sessions: Sessions::default(),
+            running: RunningQueries::default(),
+            timeouts: Timeouts::default(),
+            limits: ResultLimits::default(),
+            geometry_format: GeometryFormat::default(),
+            big_integers: BigIntegerFormat::default(),
+        }
+    }
+
+    /// Connection pool of the database
+    pub(crate) fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Set the connection acquire and statement timeouts for raw SQL
+    ///
+    /// # Arguments
+    ///
+    /// * `timeouts` - Time to wait for a pool connection and time a statement may run
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Set the maximum numbers of rows returned per page and per raw query
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - Rows in a page of table rows and in a raw query result
+    pub fn with_result_limits(mut self, limits: ResultLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set how PostGIS `geometry` and `geography` values are returned in rows
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - GeoJSON objects (the default) or WKT strings
+    pub fn with_geometry_format(mut self, format: GeometryFormat) -> Self {
+        self.geometry_format = format;
+        self
+    }
+
+    /// Set how integers beyond JavaScript's safe range are returned in rows
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - Numbers (the default) or strings outside the safe range
+    pub fn with_big_integer_format(mut self, format: BigIntegerFormat) -> Self {
+        self.big_integers = format;
+        self
     }
 
     /// Quote an identifier to prevent SQL injection
+    ///
+    /// Quoting keeps the identifier's case; unquoted names would be folded to
+    /// lower case.
     fn quote_identifier(identifier: &str) -> String {
-        format!("\"{}\"", identifier.replace("\"", "\"\""))
+        sql::quote_identifier(identifier)
+    }
+
+    /// Exact name of the table or view in `public` a requested name refers to
+    ///
+    /// Tables created without quotes have lower case names, tables created
+    /// with quotes keep their case; either can be requested in any case as
+    /// long as the name is unambiguous (see [`sql::resolve_table_name`]).
+    async fn resolve_table(&self, table: &str) -> Result<String, DatabaseError> {
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT c.relname::text
+             FROM pg_class c
+             JOIN pg_namespace n ON n.oid = c.relnamespace
+             WHERE n.nspname = 'public' AND c.relkind IN ('r', 'p', 'v', 'm', 'f')",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        match sql::resolve_table_name(table, &names) {
+            Ok(Some(name)) => Ok(name.to_string()),
+            Ok(None) => Err(DatabaseError::TableNotFound(table.to_string())),
+            Err(candidates) => Err(DatabaseError::AmbiguousTable(
+                table.to_string(),
+                candidates.join(", "),
+            )),
+        }
     }
 
-    /// Convert a PostgreSQL row to a JSON object
-    fn row_to_json(row: &PgRow) -> Result<serde_json::Value, DatabaseError> {
+    /// Convert a PostgreSQL row to a JSON object, with `int8` values in the `big_integers` format
+    fn row_to_json(
+        row: &PgRow,
+        big_integers: BigIntegerFormat,
+    ) -> Result<serde_json::Value, DatabaseError> {
         let mut map = serde_json::Map::new();
 
         for column in row.columns() {
@@ -53,7 +205,8 @@ impl PostgresProvider {
                 }
                 "INT8" | "BIGINT" | "BIGSERIAL" => {
                     let val: Option<i64> = row.try_get(column_name)?;
-                    val.map(|v| serde_json::Value::Number(v.into())).unwrap_or(serde_json::Value::Null)
+                    val.map(|v| big_integers.to_json(v))
+                        .unwrap_or(serde_json::Value::Null)
                 }
                 "FLOAT4" | "REAL" => {
                     let val: Option<f32> = row.try_get(column_name)?;
@@ -100,11 +253,57 @@ impl PostgresProvider {
                     val.map(serde_json::Value::String)
                         .unwrap_or(serde_json::Value::Null)
                 }
-                _ => {
-                    // Fallback: try to get as string
-                    let val: Option<String> = row.try_get(column_name).ok().flatten();
-                    val.map(serde_json::Value::String).unwrap_or(serde_json::Value::Null)
+                "BOOL[]" => Self::array_to_json(row, column_name, serde_json::Value::Bool),
+                "INT2[]" => Self::array_to_json(row, column_name, |v: i16| v.into()),
+                "INT4[]" => Self::array_to_json(row, column_name, |v: i32| v.into()),
+                "INT8[]" => Self::array_to_json(row, column_name, |v: i64| big_integers.to_json(v)),
+                "FLOAT4[]" => Self::array_to_json(row, column_name, |v: f32| f64::from(v).into()),
+                "FLOAT8[]" => Self::array_to_json(row, column_name, |v: f64| v.into()),
+                "TEXT[]" | "VARCHAR[]" | "BPCHAR[]" | "NAME[]" => {
+                    Self::array_to_json(row, column_name, serde_json::Value::String)
+                }
+                "UUID[]" => Self::array_to_json(row, column_name, |v: sqlx::types::Uuid| {
+                    v.to_string().into()
+                }),
+                "JSON[]" | "JSONB[]" => {
+                    Self::array_to_json(row, column_name, |v: serde_json::Value| v)
                 }
+                "DATE[]" => Self::array_to_json(row, column_name, |v: chrono::NaiveDate| {
+                    v.to_string().into()
+                }),
+                "TIMESTAMP[]" => {
+                    Self::array_to_json(row, column_name, |v: chrono::NaiveDateTime| {
+                        v.to_string().into()
+                    })
+                }
+                "TIMESTAMPTZ[]" => {
+                    Self::array_to_json(row, column_name, |v: chrono::DateTime<chrono::Utc>| {
+                        v.to_rfc3339().into()
+                    })
+                }
+                type_name if pg_values::is_text_decoded(type_name) => {
+                    Self::raw_to_json(row, column_name, |bytes| {
+                        pg_values::binary_to_text(type_name, bytes).map(serde_json::Value::String)
+                    })?
+                }
+                _ => match type_info.kind() {
+                    PgTypeKind::Range(element) => Self::raw_to_json(row, column_name, |bytes| {
+                        pg_values::range_to_json(element.oid()?.0, bytes, big_integers)
+                    })?,
+                    PgTypeKind::Composite(fields) => {
+                        let names: Vec<String> =
+                            fields.iter().map(|(name, _)| name.clone()).collect();
+                        Self::raw_to_json(row, column_name, |bytes| {
+                            pg_values::record_to_json(&names, bytes, big_integers)
+                        })?
+                    }
+                    _ => {
+                        // Fallback: try to get as string
+                        let val: Option<String> = row.try_get(column_name).ok().flatten();
+                        val.map(serde_json::Value::String)
+                            .unwrap_or(serde_json::Value::Null)
+                    }
+                },
             };
 
             map.insert(column_name.to_string(), value);
@@ -113,316 +312,701 @@ impl PostgresProvider {
         Ok(serde_json::Value::Object(map))
     }
 
-    /// Build a WHERE clause from filters
-    fn build_where_clause(filters: &HashMap<String, String>, parameter_offset: i32) -> (String, Vec<String>) {
-        if filters.is_empty() {
-            return (String::new(), vec![]);
+    /// JSON array of a one-dimensional array column, converting elements with `element`
+    ///
+    /// NULL elements stay null. Multi-dimensional arrays cannot be decoded
+    /// element by element and come back as NULL, like other unknown types.
+    fn array_to_json<T>(
+        row: &PgRow,
+        column_name: &str,
+        element: impl Fn(T) -> serde_json::Value,
+    ) -> serde_json::Value
+    where
+        T: for<'r> sqlx::Decode<'r, Postgres> + sqlx::Type<Postgres> + PgHasArrayType,
+    {
+        let values: Option<Vec<Option<T>>> = row.try_get(column_name).ok().flatten();
+        values
+            .map(|values| {
+                serde_json::Value::Array(
+                    values
+                        .into_iter()
+                        .map(|value| value.map(&element).unwrap_or(serde_json::Value::Null))
+                        .collect(),
+                )
+            })
+            .unwrap_or(serde_json::Value::Null)
+    }
+
+    /// Value sqlx has no decoder for, decoded from its binary form with `decode`
+    ///
+    /// Values read in the text format (e.g. by the console's simple queries)
+    /// are returned as PostgreSQL's text form. See [`pg_values`].
+    fn raw_to_json(
+        row: &PgRow,
+        column_name: &str,
+        decode: impl FnOnce(&[u8]) -> Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, DatabaseError> {
+        let value = row.try_get_raw(column_name)?;
+        if value.is_null() {
+            return Ok(serde_json::Value::Null);
         }
 
-        let mut conditions = Vec::new();
-        let mut values = Vec::new();
-        let mut param_index = parameter_offset;
+        let json = match value.format() {
+            PgValueFormat::Text => value
+                .as_str()
+                .ok()
+                .map(|text| serde_json::Value::String(text.to_string())),
+            PgValueFormat::Binary => value.as_bytes().ok().and_then(decode),
+        };
+        Ok(json.unwrap_or(serde_json::Value::Null))
+    }
 
-        for (column, filter_value) in filters {
-            let quoted_column = Self::quote_identifier(column);
+    /// Begin a read-only transaction, pinned to an open snapshot if one is given
+    ///
+    /// Rows and counts read in the returned transaction are consistent with each
+    /// other; with a snapshot they are also consistent across requests.
+    async fn begin_read(
+        &self,
+        snapshot: Option<&str>,
+    ) -> Result<Transaction<'static, Postgres>, DatabaseError> {
+        if let Some(snapshot) = snapshot {
+            self.touch_snapshot(snapshot)?;
+        }
 
-            if filter_value.contains('%') {
-                conditions.push(format!("{} LIKE ${}", quoted_column, param_index));
-            } else {
-                conditions.push(format!("{} = ${}", quoted_column, param_index));
+        let mut transaction = self.pool.begin().await?;
+
+        match snapshot {
+            Some(snapshot) => {
+                // Importing a snapshot requires REPEATABLE READ, and must happen
+                // before the first query of the transaction
+                let sql = format!(
+                    "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY; \
+                     SET TRANSACTION SNAPSHOT '{}'",
+                    snapshot.replace('\'', "''")
+                );
+                transaction.execute(sql.as_str()).await?;
+            }
+            None => {
+                transaction.execute("SET TRANSACTION READ ONLY").await?;
             }
-
-            values.push(filter_value.clone());
-            param_index += 1;
         }
 
-        let where_clause = format!(" WHERE {}", conditions.join(" AND "));
-        (where_clause, values)
+        Ok(transaction)
     }
-}
-
-#[async_trait]
-impl DatabaseProvider for PostgresProvider {
-    async fn list_tables(&self) -> Result<Vec<TableInfo>, DatabaseError> {
-        let query = r#"
-            SELECT table_name
-            FROM information_schema.tables
-            WHERE table_schema = 'public'
-              AND table_type = 'BASE TABLE'
-            ORDER BY table_name
-        "#;
 
-        let rows = sqlx::query(query)
-            .fetch_all(&self.pool)
-            .await?;
+    /// Mark a snapshot as used, releasing snapshots that have been idle too long
+    fn touch_snapshot(&self, snapshot: &str) -> Result<(), DatabaseError> {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        Self::release_idle_snapshots(&mut snapshots);
 
-        let mut tables = Vec::new();
-        for row in rows {
-            let name: String = row.try_get("table_name")?;
+        match snapshots.get_mut(snapshot) {
+            Some(session) => {
+                session.last_used = Instant::now();
+                Ok(())
+            }
+            None => Err(DatabaseError::SnapshotNotFound(snapshot.to_string())),
+        }
+    }
 
-            // Get row count for each table
-            let count_query = format!(
-                "SELECT COUNT(*) as count FROM {}",
-                Self::quote_identifier(&name)
-            );
-            let row_count: Option<u64> = sqlx::query_scalar(&count_query)
-                .fetch_one(&self.pool)
-                .await
-                .ok()
-                .map(|count: i64| count as u64);
+    /// Drop snapshots that have not been used within the idle timeout
+    ///
+    /// Dropping a session rolls back its transaction and returns the connection
+    /// to the pool.
+    fn release_idle_snapshots(snapshots: &mut HashMap<String, SnapshotSession>) {
+        snapshots.retain(|_, session| session.last_used.elapsed() < SNAPSHOT_IDLE_TIMEOUT);
+    }
 
-            tables.push(TableInfo { name, row_count });
-        }
+    /// Name of the database the pool is connected to
+    async fn current_database(&self) -> Result<String, DatabaseError> {
+        let name: String = sqlx::query_scalar("SELECT current_database()")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(name)
+    }
 
-        Ok(tables)
+    /// Name of the template database holding the seed of `database`
+    fn seed_database_name(database: &str) -> String {
+        format!("{}_seed", database)
     }
 
-    async fn get_table_schema(&self, table: &str) -> Result<TableSchema, DatabaseError> {
-        // Get column information
-        let column_query = r#"
-            SELECT
-                column_name,
-                data_type,
-                is_nullable,
-                column_default,
-                udt_name
-            FROM information_schema.columns
-            WHERE table_schema = 'public'
-              AND table_name = $1
-            ORDER BY ordinal_position
-        "#;
+    /// Open a connection to the `postgres` maintenance database
+    ///
+    /// Databases can only be created from a template or dropped while nobody
+    /// is connected to them, so this runs outside of the pool.
+    async fn maintenance_connection(&self) -> Result<PgConnection, DatabaseError> {
+        let options = self
+            .pool
+            .connect_options()
+            .as_ref()
+            .clone()
+            .database("postgres");
+        Ok(PgConnection::connect_with(&options).await?)
+    }
 
-        let column_rows = sqlx::query(column_query)
-            .bind(table)
-            .fetch_all(&self.pool)
+    /// Create `target` as a copy of `template`, disconnecting everyone from the template
+    async fn copy_database(
+        connection: &mut PgConnection,
+        template: &str,
+        target: &str,
+    ) -> Result<(), DatabaseError> {
+        let sql = format!(
+            "CREATE DATABASE {} TEMPLATE {}",
+            Self::quote_identifier(target),
+            Self::quote_identifier(template)
+        );
+
+        let mut attempt = 1;
+        loop {
+            sqlx::query(
+                "SELECT pg_terminate_backend(pid) FROM pg_stat_activity \
+                 WHERE datname = $1 AND pid <> pg_backend_pid()",
+            )
+            .bind(template)
+            .execute(&mut *connection)
             .await?;
 
-        if column_rows.is_empty() {
-            return Err(DatabaseError::TableNotFound(table.to_string()));
+            // Pool connections may reconnect between terminating and copying
+            match connection.execute(sql.as_str()).await {
+                Ok(_) => return Ok(()),
+                Err(_) if attempt < DATABASE_COPY_ATTEMPTS => attempt += 1,
+                Err(error) => return Err(error.into()),
+            }
         }
+    }
 
-        // Get primary key columns
-        let pk_query = r#"
-            SELECT kcu.column_name
-            FROM information_schema.table_constraints tc
-            JOIN information_schema.key_column_usage kcu
-              ON tc.constraint_name = kcu.constraint_name
-              AND tc.table_schema = kcu.table_schema
-            WHERE tc.table_schema = 'public'
-              AND tc.table_name = $1
-              AND tc.constraint_type = 'PRIMARY KEY'
-            ORDER BY kcu.ordinal_position
-        "#;
+    /// Name of the schema holding the named snapshot `name`
+    fn named_snapshot_schema(name: &str) -> String {
+        format!("{}{}", NAMED_SNAPSHOT_SCHEMA_PREFIX, name)
+    }
 
-        let pk_rows = sqlx::query(pk_query)
-            .bind(table)
-            .fetch_all(&self.pool)
-            .await?;
+    /// Named snapshots, or only the one called `name`, by name
+    async fn read_named_snapshots(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Vec<NamedSnapshot>, DatabaseError> {
+        let rows: Vec<(String, Vec<String>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT
+                n.nspname::text,
+                COALESCE(
+                    array_agg(c.relname::text ORDER BY c.relname) FILTER (WHERE c.oid IS NOT NULL),
+                    '{}'
+                ),
+                obj_description(n.oid, 'pg_namespace')
+            FROM pg_namespace n
+            LEFT JOIN pg_class c ON c.relnamespace = n.oid AND c.relkind = 'r'
+            WHERE starts_with(n.nspname, $1) AND ($2::text IS NULL OR n.nspname = $2)
+            GROUP BY n.oid, n.nspname
+            ORDER BY n.nspname
+            "#,
+        )
+        .bind(NAMED_SNAPSHOT_SCHEMA_PREFIX)
+        .bind(name.map(Self::named_snapshot_schema))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(schema, tables, comment)| {
+                let name = schema.strip_prefix(NAMED_SNAPSHOT_SCHEMA_PREFIX)?;
+                validate_snapshot_name(name).ok()?;
+                Some(NamedSnapshot {
+                    name: name.to_string(),
+                    tables,
+                    // The creation time is kept as the schema's comment
+                    created_at_milliseconds: comment
+                        .and_then(|comment| comment.parse().ok())
+                        .unwrap_or(0),
+                })
+            })
+            .collect())
+    }
 
-        let primary_key_columns: Vec<String> = pk_rows
+    /// The named snapshot called `name`
+    async fn read_named_snapshot(&self, name: &str) -> Result<NamedSnapshot, DatabaseError> {
+        validate_snapshot_name(name)
+            .map_err(|_| DatabaseError::SnapshotNotFound(name.to_string()))?;
+        self.read_named_snapshots(Some(name))
+            .await?
+            .pop()
+            .ok_or_else(|| DatabaseError::SnapshotNotFound(name.to_string()))
+    }
+
+    /// Quoted column list of `table` in the schema of a named snapshot
+    async fn snapshot_columns(
+        connection: &mut PgConnection,
+        schema: &str,
+        table: &str,
+    ) -> Result<String, DatabaseError> {
+        let columns: Vec<String> = sqlx::query_scalar(
+            "SELECT column_name::text FROM information_schema.columns \
+             WHERE table_schema = $1 AND table_name = $2 ORDER BY ordinal_position",
+        )
+        .bind(schema)
+        .bind(table)
+        .fetch_all(&mut *connection)
+        .await?;
+        Ok(columns
             .iter()
-            .map(|row| row.try_get::<String, _>("column_name"))
-            .collect::<Result<Vec<_>, _>>()?;
+            .map(|column| Self::quote_identifier(column))
+            .collect::<Vec<_>>()
+            .join(", "))
+    }
 
-        let primary_key = if primary_key_columns.is_empty() {
-            None
-        } else {
-            Some(primary_key_columns.clone())
-        };
+    /// Count rows on an existing connection or transaction
+    async fn count_rows_on<'c, E>(
+        executor: E,
+        schema: &TableSchema,
+        query: &RowQuery,
+    ) -> Result<u64, DatabaseError>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        let quoted_table = Self::quote_identifier(&schema.name);
+        let mut sql = format!("SELECT COUNT(*) as count FROM {}", quoted_table);
 
-        // Get foreign keys
-        let fk_query = r#"
-            SELECT
-                kcu.column_name,
-                ccu.table_name AS references_table,
-                ccu.column_name AS references_column
-            FROM information_schema.table_constraints tc
-            JOIN information_schema.key_column_usage kcu
-              ON tc.constraint_name = kcu.constraint_name
-              AND tc.table_schema = kcu.table_schema
-            JOIN information_schema.constraint_column_usage ccu
-              ON ccu.constraint_name = tc.constraint_name
-              AND ccu.table_schema = tc.table_schema
-            WHERE tc.table_schema = 'public'
-              AND tc.table_name = $1
-              AND tc.constraint_type = 'FOREIGN KEY'
-        "#;
+        // Add WHERE clause for filters and search
+        let (where_clause, filter_values) = Self::row_conditions(query, schema);
+        sql.push_str(&where_clause);
 
-        let fk_rows = sqlx::query(fk_query)
-            .bind(table)
-            .fetch_all(&self.pool)
-            .await?;
+        // Execute query
+        let mut query_builder = sqlx::query(&sql);
+        for value in &filter_values {
+            query_builder = query_builder.bind(value);
+        }
 
-        let foreign_keys: Vec<ForeignKey> = fk_rows
-            .iter()
-            .map(|row| {
-                Ok(ForeignKey {
-                    column: row.try_get("column_name")?,
-                    references_table: row.try_get("references_table")?,
-                    references_column: row.try_get("references_column")?,
-                })
-            })
-            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+        let row = query_builder.fetch_one(executor).await?;
+        let count: i64 = row.try_get("count")?;
 
-        // Get indexes
-        let index_query = r#"
+        Ok(count as u64)
+    }
+
+    /// Get the type (e.g. "character varying", "integer[]") of each column
+    ///
+    /// Unlike `information_schema.columns.data_type`, these names can be used in
+    /// casts. Length and precision modifiers are left out so that casting does
+    /// not silently truncate values; the assignment itself enforces them. The
+    /// `ctid` system column (type `tid`) is included for addressing rows.
+    async fn column_types(&self, table: &str) -> Result<HashMap<String, String>, DatabaseError> {
+        let query = r#"
             SELECT
-                i.indexname AS index_name,
-                i.indexdef AS index_definition
-            FROM pg_indexes i
-            WHERE i.schemaname = 'public'
-              AND i.tablename = $1
-              AND i.indexname NOT IN (
-                SELECT constraint_name
-                FROM information_schema.table_constraints
-                WHERE table_schema = 'public'
-                  AND table_name = $1
-                  AND constraint_type = 'PRIMARY KEY'
-              )
+                a.attname AS column_name,
+                format_type(a.atttypid, NULL) AS column_type
+            FROM pg_attribute a
+            WHERE a.attrelid = $1::regclass
+              AND (a.attnum > 0 OR a.attname = 'ctid')
+              AND NOT a.attisdropped
         "#;
 
-        let index_rows = sqlx::query(index_query)
-            .bind(table)
+        let rows = sqlx::query(query)
+            .bind(format!("public.{}", Self::quote_identifier(table)))
             .fetch_all(&self.pool)
             .await?;
 
-        let indexes: Vec<IndexInfo> = index_rows
-            .iter()
-            .map(|row| {
-                let index_name: String = row.try_get("index_name")?;
-                let index_definition: String = row.try_get("index_definition")?;
+        rows.iter()
+            .map(|row| Ok((row.try_get("column_name")?, row.try_get("column_type")?)))
+            .collect::<Result<HashMap<_, _>, sqlx::Error>>()
+            .map_err(DatabaseError::from)
+    }
 
-                // Parse column names from index definition (simplified)
-                // This is a basic implementation - could be enhanced
-                let columns = vec![]; // Would need proper parsing of index_definition
+    /// Insert `rows` into `table` (a name as resolved by `get_table_schema`)
+    async fn insert_into(
+        connection: &mut PgConnection,
+        table: &str,
+        column_types: &HashMap<String, String>,
+        rows: &[serde_json::Map<String, serde_json::Value>],
+    ) -> Result<(), DatabaseError> {
+        for (index, row) in rows.iter().enumerate() {
+            let sql = if row.is_empty() {
+                format!(
+                    "INSERT INTO {} DEFAULT VALUES",
+                    Self::quote_identifier(table)
+                )
+            } else {
+                let columns: Vec<String> = row
+                    .keys()
+                    .map(|column| Self::quote_identifier(column))
+                    .collect();
+                let placeholders: Vec<String> = row
+                    .keys()
+                    .enumerate()
+                    .map(|(position, column)| match column_types.get(column) {
+                        Some(column_type) => format!("CAST(${} AS {})", position + 1, column_type),
+                        None => format!("${}", position + 1),
+                    })
+                    .collect();
+                format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    Self::quote_identifier(table),
+                    columns.join(", "),
+                    placeholders.join(", ")
+                )
+            };
 
-                let unique = index_definition.to_uppercase().contains("UNIQUE");
+            let mut query = sqlx::query(&sql);
+            for (column, value) in row {
+                let column_type = column_types
+                    .get(column)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                query = query.bind(Self::json_to_text(value, column_type));
+            }
+            query.execute(&mut *connection).await.map_err(|error| {
+                DatabaseError::Query(format!(
+                    "Failed to insert row {} into '{}': {}",
+                    index, table, error
+                ))
+            })?;
+        }
+        Ok(())
+    }
 
-                Ok(IndexInfo {
-                    name: index_name,
-                    columns,
-                    unique,
-                })
-            })
-            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+    /// Move the sequences of `table`'s serial and identity columns past the
+    /// largest value in the table, so inserts after a fixture load do not
+    /// collide with its explicit keys
+    async fn reset_sequences(
+        connection: &mut PgConnection,
+        table: &str,
+    ) -> Result<(), DatabaseError> {
+        let qualified_table = format!("public.{}", Self::quote_identifier(table));
+        let sequences: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT a.attname, pg_get_serial_sequence($1, a.attname)
+            FROM pg_attribute a
+            WHERE a.attrelid = $1::regclass
+              AND a.attnum > 0
+              AND NOT a.attisdropped
+              AND pg_get_serial_sequence($1, a.attname) IS NOT NULL
+            "#,
+        )
+        .bind(&qualified_table)
+        .fetch_all(&mut *connection)
+        .await?;
+
+        for (column, sequence) in sequences {
+            let sql = format!(
+                "SELECT setval(CAST($1 AS regclass), COALESCE(MAX({}), 0) + 1, false) FROM {}",
+                Self::quote_identifier(&column),
+                qualified_table
+            );
+            sqlx::query(&sql)
+                .bind(&sequence)
+                .execute(&mut *connection)
+                .await?;
+        }
+        Ok(())
+    }
 
-        // Build column info
-        let columns: Vec<ColumnInfo> = column_rows
-            .iter()
-            .map(|row| {
-                let column_name: String = row.try_get("column_name")?;
-                let data_type: String = row.try_get("data_type")?;
-                let is_nullable: String = row.try_get("is_nullable")?;
-                let column_default: Option<String> = row.try_get("column_default")?;
+    /// Convert a JSON value into the text input form of a column type
+    ///
+    /// Values are bound as text and cast to the column type, so every type with
+    /// a text representation can be written.
+    fn json_to_text(value: &serde_json::Value, column_type: &str) -> Option<String> {
+        match value {
+            serde_json::Value::Null => None,
+            // JSON columns take strings as JSON text typed by the user
+            serde_json::Value::String(text) => Some(text.clone()),
+            serde_json::Value::Array(elements) if column_type.ends_with("[]") => {
+                Some(Self::array_literal(elements))
+            }
+            other => Some(other.to_string()),
+        }
+    }
 
-                Ok(ColumnInfo {
-                    name: column_name.clone(),
-                    data_type,
-                    nullable: is_nullable == "YES",
-                    default_value: column_default,
-                    is_primary_key: primary_key_columns.contains(&column_name),
-                })
+    /// Format JSON array elements as a PostgreSQL array literal (e.g. `{"a","b"}`)
+    fn array_literal(elements: &[serde_json::Value]) -> String {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+
+        let elements: Vec<String> = elements
+            .iter()
+            .map(|element| match element {
+                serde_json::Value::Null => "NULL".to_string(),
+                serde_json::Value::Array(nested) => Self::array_literal(nested),
+                serde_json::Value::String(text) => quote(text),
+                serde_json::Value::Object(_) => quote(&element.to_string()),
+                other => other.to_string(),
             })
-            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+            .collect();
 
-        Ok(TableSchema {
-            name: table.to_string(),
-            columns,
-            primary_key,
-            foreign_keys,
-            indexes,
-        })
+        format!("{{{}}}", elements.join(","))
     }
 
-    async fn get_rows(&self, table: &str, query: RowQuery) -> Result<RowsResponse, DatabaseError> {
-        // Validate table exists and get columns
-        let schema = self.get_table_schema(table).await?;
-        let column_names: Vec<String> = schema.columns.iter().map(|c| c.name.clone()).collect();
+    /// Build a WHERE clause from filters
+    fn build_where_clause(
+        filters: &HashMap<String, String>,
+        parameter_offset: i32,
+        case_insensitive: bool,
+    ) -> (String, Vec<String>) {
+        let (conditions, values) =
+            Self::filter_conditions(filters, parameter_offset, case_insensitive);
+        if conditions.is_empty() {
+            return (String::new(), values);
+        }
 
-        // Build base query
-        let quoted_table = Self::quote_identifier(table);
-        let mut sql = format!("SELECT * FROM {}", quoted_table);
+        let where_clause = format!(" WHERE {}", conditions.join(" AND "));
+        (where_clause, values)
+    }
 
-        // Add WHERE clause for filters
-        let (where_clause, filter_values) = Self::build_where_clause(&query.filters, 1);
-        sql.push_str(&where_clause);
+    /// Conditions of filters (to be joined with AND) and their bound values
+    ///
+    /// With `case_insensitive`, patterns use `ILIKE` and exact values compare
+    /// the lowercased text of the column.
+    fn filter_conditions(
+        filters: &HashMap<String, String>,
+        parameter_offset: i32,
+        case_insensitive: bool,
+    ) -> (Vec<String>, Vec<String>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
 
-        // Add ORDER BY clause
-        if let Some(sort_column) = &query.sort_by {
-            // Validate sort column exists
-            if !column_names.contains(sort_column) {
-                return Err(DatabaseError::InvalidColumn(sort_column.clone()));
-            }
+        for (param_index, (column, filter_value)) in (parameter_offset..).zip(filters) {
+            let quoted_column = Self::quote_identifier(column);
 
-            let quoted_sort = Self::quote_identifier(sort_column);
-            let sort_direction = match query.sort_order {
-                Some(SortOrder::Descending) => "DESC",
-                _ => "ASC",
+            let condition = match (filter_value.contains('%'), case_insensitive) {
+                (true, false) => format!("{} LIKE ${}", quoted_column, param_index),
+                (true, true) => format!("{}::text ILIKE ${}", quoted_column, param_index),
+                (false, false) => format!("{} = ${}", quoted_column, param_index),
+                (false, true) => {
+                    format!("lower({}::text) = lower(${})", quoted_column, param_index)
+                }
             };
-            sql.push_str(&format!(" ORDER BY {} {}", quoted_sort, sort_direction));
+            conditions.push(condition);
+
+            values.push(filter_value.clone());
         }
 
-        // Add LIMIT and OFFSET
-        let limit = query.limit.min(500); // Cap at 500 as per spec
-        sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, query.offset));
+        (conditions, values)
+    }
 
-        // Execute query
-        let mut query_builder = sqlx::query(&sql);
-        for value in &filter_values {
-            query_builder = query_builder.bind(value);
+    /// Condition of one of the `filterGroups` of a row query, binding into `values`
+    fn filter_group_condition(
+        group: &HashMap<String, String>,
+        schema: &TableSchema,
+        case_insensitive: bool,
+        values: &mut Vec<String>,
+    ) -> String {
+        let (others, booleans) = filters::boolean_filters(group, schema);
+        let (mut conditions, group_values) =
+            Self::filter_conditions(&others, values.len() as i32 + 1, case_insensitive);
+        values.extend(group_values);
+        for boolean in &booleans {
+            let placeholder = format!("${}", values.len() + 1);
+            conditions.push(boolean.condition(Dialect::Postgres, &placeholder));
+            values.push(boolean.bound_value(Dialect::Postgres));
         }
 
-        let rows = query_builder.fetch_all(&self.pool).await?;
+        // An empty group matches every row
+        if conditions.is_empty() {
+            return "1 = 1".to_string();
+        }
+        format!("({})", conditions.join(" AND "))
+    }
 
-        // Convert rows to JSON
-        let json_rows: Vec<serde_json::Value> = rows
-            .iter()
-            .map(Self::row_to_json)
-            .collect::<Result<Vec<_>, _>>()?;
+    /// Build the WHERE clause of a row query: its filters, ranges, filter groups
+    /// and search (`q`)
+    ///
+    /// Filters on boolean columns compare with the stored form of booleans.
+    fn row_conditions(query: &RowQuery, schema: &TableSchema) -> (String, Vec<String>) {
+        let (others, booleans) = filters::boolean_filters(&query.filters, schema);
+        let (mut where_clause, mut values) =
+            Self::build_where_clause(&others, 1, query.case_insensitive);
 
-        // Get total count
-        let count_result = self.count_rows(table, &query).await?;
-        let total = count_result.count;
+        let mut conditions = Vec::new();
+        for boolean in &booleans {
+            let placeholder = format!("${}", values.len() + 1);
+            conditions.push(boolean.condition(Dialect::Postgres, &placeholder));
+            values.push(boolean.bound_value(Dialect::Postgres));
+        }
+        for range in &query.ranges {
+            conditions.push(range.condition(Dialect::Postgres, &format!("${}", values.len() + 1)));
+            values.push(range.value.clone());
+        }
+        if !query.filter_groups.is_empty() {
+            let groups: Vec<String> = query
+                .filter_groups
+                .iter()
+                .map(|group| {
+                    Self::filter_group_condition(group, schema, query.case_insensitive, &mut values)
+                })
+                .collect();
+            conditions.push(format!("({})", groups.join(" OR ")));
+        }
+        if let Some(text) = query.search() {
+            let mut parameter = values.len();
+            let (condition, search_values) =
+                search::search_condition(schema, text, Dialect::Postgres, || {
+                    parameter += 1;
+                    format!("${}", parameter)
+                });
+            conditions.push(condition);
+            values.extend(search_values);
+        }
 
-        let has_more = query.offset + (json_rows.len() as u64) < total;
+        if !conditions.is_empty() {
+            let keyword = if where_clause.is_empty() {
+                "WHERE"
+            } else {
+                "AND"
+            };
+            where_clause.push_str(&format!(" {} {}", keyword, conditions.join(" AND ")));
+        }
 
-        Ok(RowsResponse {
-            rows: json_rows,
-            columns: column_names,
-            total,
-            offset: query.offset,
-            limit,
-            has_more,
-        })
+        (where_clause, values)
     }
 
-    async fn count_rows(&self, table: &str, query: &RowQuery) -> Result<CountResponse, DatabaseError> {
-        let quoted_table = Self::quote_identifier(table);
-        let mut sql = format!("SELECT COUNT(*) as count FROM {}", quoted_table);
+    /// Columns selected when reading whole rows
+    ///
+    /// Tables addressed by ctid get it (as text) as an extra first column, so
+    /// rows can be opened and edited. PostGIS columns are listed one by one,
+    /// rendered in the configured [`GeometryFormat`].
+    fn select_list(&self, schema: &TableSchema) -> String {
+        let identifier = match schema.row_address {
+            RowAddress::Ctid => Some("ctid::text AS ctid"),
+            _ => None,
+        };
+        if !spatial::has_spatial_columns(&schema.columns) {
+            return match identifier {
+                Some(identifier) => format!("{}, *", identifier),
+                None => "*".to_string(),
+            };
+        }
 
-        // Add WHERE clause for filters
-        let (where_clause, filter_values) = Self::build_where_clause(&query.filters, 1);
-        sql.push_str(&where_clause);
+        identifier
+            .map(str::to_string)
+            .into_iter()
+            .chain(
+                schema
+                    .columns
+                    .iter()
+                    .map(|column| spatial::select_item(column, self.geometry_format)),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 
-        // Execute query
-        let mut query_builder = sqlx::query(&sql);
-        for value in &filter_values {
-            query_builder = query_builder.bind(value);
+    /// Columns selected when reading only `projection` of rows (whole rows without one)
+    ///
+    /// The ctid stays in front of the projected columns.
+    fn projected_select_list(&self, schema: &TableSchema, projection: Option<&[String]>) -> String {
+        let Some(columns) = projection else {
+            return self.select_list(schema);
+        };
+        let identifier = match schema.row_address {
+            RowAddress::Ctid => Some("ctid::text AS ctid".to_string()),
+            _ => None,
+        };
+        identifier
+            .into_iter()
+            .chain(columns.iter().map(|column| {
+                match schema.columns.iter().find(|info| &info.name == column) {
+                    Some(info) => spatial::select_item(info, self.geometry_format),
+                    None => Self::quote_identifier(column),
+                }
+            }))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Bind a statement parameter with the closest matching PostgreSQL type
+    ///
+    /// Strings, arrays and objects are bound as text, and NULL as a text NULL;
+    /// statements cast them where another type is needed (`$1::date`).
+    fn bind_parameter<'q>(
+        query: Query<'q, Postgres, PgArguments>,
+        value: &serde_json::Value,
+    ) -> Query<'q, Postgres, PgArguments> {
+        match value {
+            serde_json::Value::Null => query.bind(None::<String>),
+            serde_json::Value::Bool(boolean) => query.bind(*boolean),
+            serde_json::Value::Number(number) => match number.as_i64() {
+                Some(integer) => query.bind(integer),
+                None => query.bind(number.as_f64()),
+            },
+            serde_json::Value::String(text) => query.bind(text.clone()),
+            other => query.bind(other.to_string()),
         }
+    }
 
-        let row = query_builder.fetch_one(&self.pool).await?;
-        let count: i64 = row.try_get("count")?;
+    /// Make the server stop statements on `connection` after the statement timeout
+    ///
+    /// The client-side timeout only stops waiting for a statement, which would
+    /// otherwise keep running on the server and hold its connection. With
+    /// `local`, the setting ends with the current transaction.
+    async fn set_statement_timeout(
+        &self,
+        connection: &mut PgConnection,
+        local: bool,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query("SELECT set_config('statement_timeout', $1, $2)")
+            .bind(format!("{}ms", self.timeouts.statement.as_millis()))
+            .bind(local)
+            .execute(connection)
+            .await?;
+        Ok(())
+    }
 
-        Ok(CountResponse {
-            count: count as u64,
-        })
+    /// Execute a raw SQL statement on a pool connection, with the statement
+    /// timeout enforced by the server
+    async fn run_pooled(
+        &self,
+        mut connection: PoolConnection<Postgres>,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryResult, DatabaseError> {
+        self.set_statement_timeout(&mut connection, false).await?;
+        let result = Self::run_query(
+            &mut *connection,
+            sql,
+            params,
+            self.timeouts,
+            self.limits,
+            self.big_integers,
+        )
+        .await;
+
+        // The pool may be shared with the application, which must not inherit
+        // the setting. A statement interrupted by the client-side timeout
+        // leaves the connection in an unknown state, so it is closed instead.
+        let reset = match result {
+            Err(DatabaseError::Timeout(_)) => false,
+            _ => sqlx::query("RESET statement_timeout")
+                .execute(&mut *connection)
+                .await
+                .is_ok(),
+        };
+        if !reset {
+            connection.close_on_drop();
+        }
+
+        match result {
+            Ok(QueryResult {
+                timeout: Some(kind),
+                ..
+            }) => Err(DatabaseError::Timeout(kind)),
+            result => result,
+        }
     }
 
-    async fn execute_query(&self, sql: &str) -> Result<QueryResult, DatabaseError> {
+    /// Execute a raw SQL statement on the pool or on a session's connection
+    ///
+    /// A statement the server stopped after `statement_timeout` is reported as
+    /// a result with an error and `timeout` set.
+    async fn run_query<'c, E>(
+        executor: E,
+        sql: &str,
+        params: &[serde_json::Value],
+        timeouts: Timeouts,
+        limits: ResultLimits,
+        big_integers: BigIntegerFormat,
+    ) -> Result<QueryResult, DatabaseError>
+    where
+        E: sqlx::Executor<'c, Database = Postgres>,
+    {
         let start_time = std::time::Instant::now();
+        let query = params.iter().fold(sqlx::query(sql), Self::bind_parameter);
 
         // Try to execute as a query that returns rows (SELECT)
-        let result = sqlx::query(sql).fetch_all(&self.pool).await;
+        let result = timeouts.statement(query.fetch_all(executor)).await?;
 
         let execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
 
@@ -437,6 +1021,7 @@ impl DatabaseProvider for PostgresProvider {
                         affected_rows: 0,
                         execution_time_milliseconds,
                         error: None,
+                        timeout: None,
                     })
                 } else {
                     // SELECT query with results
@@ -448,13 +1033,12 @@ impl DatabaseProvider for PostgresProvider {
 
                     let json_rows: Vec<serde_json::Value> = rows
                         .iter()
-                        .map(Self::row_to_json)
+                        .map(|row| Self::row_to_json(row, big_integers))
                         .collect::<Result<Vec<_>, _>>()?;
 
                     // Apply row limit
-                    let max_rows = 10000;
-                    if json_rows.len() > max_rows {
-                        return Err(DatabaseError::TooManyRows(max_rows as u64));
+                    if json_rows.len() as u64 > limits.max_result_rows {
+                        return Err(DatabaseError::TooManyRows(limits.max_result_rows));
                     }
 
                     Ok(QueryResult {
@@ -463,19 +1047,1807 @@ impl DatabaseProvider for PostgresProvider {
                         affected_rows: 0,
                         execution_time_milliseconds,
                         error: None,
+                        timeout: None,
                     })
                 }
             }
             Err(error) => {
                 // Return error in result
+                let timeout = is_statement_timeout(&error).then_some(TimeoutKind::Statement);
                 Ok(QueryResult {
                     columns: vec![],
                     rows: vec![],
                     affected_rows: 0,
                     execution_time_milliseconds,
                     error: Some(error.to_string()),
+                    timeout,
                 })
             }
         }
     }
 }
+
+#[async_trait]
+impl DatabaseProvider for PostgresProvider {
+    fn dialect(&self) -> Dialect {
+        Dialect::Postgres
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_live_updates: true,
+            supports_explain_analyze: true,
+            supports_schemas: true,
+            supports_returning: true,
+            supports_snapshots: true,
+            supports_sessions: true,
+            supports_cancellation: true,
+            // Bind parameters are numbered with 16 bits in the wire protocol
+            max_placeholder_count: Some(65535),
+        }
+    }
+
+    fn result_limits(&self) -> ResultLimits {
+        self.limits
+    }
+
+    async fn list_tables(&self) -> Result<Vec<TableInfo>, DatabaseError> {
+        // Row counts are the planner's estimates; reltuples is -1 until the
+        // table is first vacuumed or analyzed
+        let query = r#"
+            SELECT t.table_name, c.reltuples::bigint AS estimate
+            FROM information_schema.tables t
+            LEFT JOIN pg_catalog.pg_namespace n ON n.nspname = t.table_schema
+            LEFT JOIN pg_catalog.pg_class c
+              ON c.relnamespace = n.oid AND c.relname = t.table_name
+            WHERE t.table_schema = 'public'
+              AND t.table_type = 'BASE TABLE'
+            ORDER BY t.table_name
+        "#;
+
+        let rows = sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut tables = Vec::new();
+        for row in rows {
+            let name: String = row.try_get("table_name")?;
+            let estimate: Option<i64> = row.try_get("estimate")?;
+            let row_count = estimate
+                .filter(|estimate| *estimate >= 0)
+                .map(|estimate| estimate as u64);
+
+            tables.push(TableInfo::estimated(name, row_count));
+        }
+
+        Ok(tables)
+    }
+
+    async fn get_table_schema(&self, table: &str) -> Result<TableSchema, DatabaseError> {
+        let table = &self.resolve_table(table).await?;
+
+        // Get column information
+        let column_query = r#"
+            SELECT
+                column_name,
+                data_type,
+                is_nullable,
+                column_default,
+                udt_name
+            FROM information_schema.columns
+            WHERE table_schema = 'public'
+              AND table_name = $1
+            ORDER BY ordinal_position
+        "#;
+
+        let column_rows = sqlx::query(column_query)
+            .bind(table)
+            .fetch_all(&self.pool)
+            .await?;
+
+        if column_rows.is_empty() {
+            return Err(DatabaseError::TableNotFound(table.to_string()));
+        }
+
+        // Get primary key columns
+        let pk_query = r#"
+            SELECT kcu.column_name
+            FROM information_schema.table_constraints tc
+            JOIN information_schema.key_column_usage kcu
+              ON tc.constraint_name = kcu.constraint_name
+              AND tc.table_schema = kcu.table_schema
+            WHERE tc.table_schema = 'public'
+              AND tc.table_name = $1
+              AND tc.constraint_type = 'PRIMARY KEY'
+            ORDER BY kcu.ordinal_position
+        "#;
+
+        let pk_rows = sqlx::query(pk_query)
+            .bind(table)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let primary_key_columns: Vec<String> = pk_rows
+            .iter()
+            .map(|row| row.try_get::<String, _>("column_name"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let primary_key = if primary_key_columns.is_empty() {
+            None
+        } else {
+            Some(primary_key_columns.clone())
+        };
+
+        // Plain tables without a primary key are addressed by ctid; it is not
+        // unique across the partitions of a partitioned table and views have none
+        let row_address = if primary_key.is_some() {
+            RowAddress::PrimaryKey
+        } else {
+            let relation_kind: Option<String> = sqlx::query_scalar(
+                "SELECT c.relkind::text FROM pg_class c \
+                 JOIN pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE n.nspname = 'public' AND c.relname = $1",
+            )
+            .bind(table)
+            .fetch_optional(&self.pool)
+            .await?;
+            match relation_kind.as_deref() {
+                Some("r") => RowAddress::Ctid,
+                _ => RowAddress::None,
+            }
+        };
+
+        // Get foreign keys
+        let fk_query = r#"
+            SELECT
+                kcu.column_name,
+                ccu.table_name AS references_table,
+                ccu.column_name AS references_column
+            FROM information_schema.table_constraints tc
+            JOIN information_schema.key_column_usage kcu
+              ON tc.constraint_name = kcu.constraint_name
+              AND tc.table_schema = kcu.table_schema
+            JOIN information_schema.constraint_column_usage ccu
+              ON ccu.constraint_name = tc.constraint_name
+              AND ccu.table_schema = tc.table_schema
+            WHERE tc.table_schema = 'public'
+              AND tc.table_name = $1
+              AND tc.constraint_type = 'FOREIGN KEY'
+        "#;
+
+        let fk_rows = sqlx::query(fk_query)
+            .bind(table)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let foreign_keys: Vec<ForeignKey> = fk_rows
+            .iter()
+            .map(|row| {
+                Ok(ForeignKey {
+                    column: row.try_get("column_name")?,
+                    references_table: row.try_get("references_table")?,
+                    references_column: row.try_get("references_column")?,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        // Get indexes
+        let index_query = r#"
+            SELECT
+                i.indexname AS index_name,
+                i.indexdef AS index_definition
+            FROM pg_indexes i
+            WHERE i.schemaname = 'public'
+              AND i.tablename = $1
+              AND i.indexname NOT IN (
+                SELECT constraint_name
+                FROM information_schema.table_constraints
+                WHERE table_schema = 'public'
+                  AND table_name = $1
+                  AND constraint_type = 'PRIMARY KEY'
+              )
+        "#;
+
+        let index_rows = sqlx::query(index_query)
+            .bind(table)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let indexes: Vec<IndexInfo> = index_rows
+            .iter()
+            .map(|row| {
+                let index_name: String = row.try_get("index_name")?;
+                let index_definition: String = row.try_get("index_definition")?;
+
+                // Parse column names from index definition (simplified)
+                // This is a basic implementation - could be enhanced
+                let columns = vec![]; // Would need proper parsing of index_definition
+
+                let unique = index_definition.to_uppercase().contains("UNIQUE");
+
+                Ok(IndexInfo {
+                    name: index_name,
+                    columns,
+                    unique,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        // Build column info
+        let columns: Vec<ColumnInfo> = column_rows
+            .iter()
+            .map(|row| {
+                let column_name: String = row.try_get("column_name")?;
+                let mut data_type: String = row.try_get("data_type")?;
+                let udt_name: String = row.try_get("udt_name")?;
+                // PostGIS types are only named in udt_name
+                if data_type == "USER-DEFINED" && spatial::is_spatial_type(&udt_name) {
+                    data_type = udt_name;
+                }
+                let is_nullable: String = row.try_get("is_nullable")?;
+                let column_default: Option<String> = row.try_get("column_default")?;
+
+                Ok(ColumnInfo {
+                    name: column_name.clone(),
+                    data_type,
+                    nullable: is_nullable == "YES",
+                    default_value: column_default,
+                    is_primary_key: primary_key_columns.contains(&column_name),
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        Ok(TableSchema {
+            name: table.to_string(),
+            columns,
+            primary_key,
+            foreign_keys,
+            indexes,
+            row_address,
+        })
+    }
+
+    async fn get_rows(&self, table: &str, query: RowQuery) -> Result<RowsResponse, DatabaseError> {
+        // Validate table exists and get columns
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        let column_names: Vec<String> = schema.columns.iter().map(|c| c.name.clone()).collect();
+
+        // Fetch only the requested columns
+        let projection = query.projection();
+        if let Some(columns) = &projection {
+            validate_columns(&schema, columns)?;
+        }
+
+        // Build base query
+        let quoted_table = Self::quote_identifier(table);
+        let mut sql = format!(
+            "SELECT {} FROM {}",
+            self.projected_select_list(&schema, projection.as_deref()),
+            quoted_table
+        );
+
+        // Add WHERE clause for filters and search
+        let (where_clause, filter_values) = Self::row_conditions(&query, &schema);
+        sql.push_str(&where_clause);
+
+        // Validate sort column exists
+        if let Some(sort_column) = &query.sort_by {
+            if !column_names.contains(sort_column) {
+                return Err(DatabaseError::InvalidColumn(sort_column.clone()));
+            }
+        }
+
+        // Continue after the cursor's row instead of skipping rows
+        let mut cursor_values = Vec::new();
+        let offset = match &query.cursor {
+            Some(cursor) => {
+                let cursor = RowCursor::decode(cursor, &query)?;
+                let column_types = self.column_types(table).await?;
+                let mut parameter = filter_values.len();
+                let (condition, values) = keyset::keyset_condition(
+                    &cursor,
+                    &keyset::required_key_columns(&schema)?,
+                    keyset::sort_nullable(&schema, &query),
+                    Dialect::Postgres,
+                    |column| {
+                        parameter += 1;
+                        match column_types.get(column) {
+                            Some(column_type) => format!("${}::{}", parameter, column_type),
+                            None => format!("${}", parameter),
+                        }
+                    },
+                );
+                let keyword = if where_clause.is_empty() {
+                    "WHERE"
+                } else {
+                    "AND"
+                };
+                sql.push_str(&format!(" {} {}", keyword, condition));
+                cursor_values = values;
+                cursor.offset
+            }
+            None => query.offset,
+        };
+
+        // Add ORDER BY clause, ending with the row key (or ctid) so rows with equal sort
+        // values keep their order across pages, and pages can continue after a row
+        let key_columns = keyset::key_columns(&schema);
+        let order_columns = keyset::order_columns(&schema, &query);
+        sql.push_str(&keyset::order_clause(
+            &query,
+            order_columns.as_deref(),
+            Dialect::Postgres,
+        ));
+
+        // Add LIMIT and OFFSET; one row past the page tells whether there are more
+        let limit = query.limit.min(self.limits.max_page_size);
+        sql.push_str(&format!(" LIMIT {}", limit + 1));
+        if query.cursor.is_none() {
+            sql.push_str(&format!(" OFFSET {}", offset));
+        }
+
+        // Execute query
+        let mut query_builder = sqlx::query(&sql);
+        for value in &filter_values {
+            query_builder = query_builder.bind(value);
+        }
+        for value in &cursor_values {
+            query_builder = query_builder.bind(value_to_key_text(value));
+        }
+
+        // Rows and total count are read in the same transaction so they agree
+        let mut transaction = self.begin_read(query.snapshot.as_deref()).await?;
+        let rows = query_builder.fetch_all(&mut *transaction).await?;
+        let total = if query.count {
+            Some(Self::count_rows_on(&mut *transaction, &schema, &query).await?)
+        } else {
+            None
+        };
+        transaction.commit().await?;
+
+        // Convert rows to JSON
+        let json_rows: Vec<serde_json::Value> = rows
+            .iter()
+            .take(limit as usize)
+            .map(|row| Self::row_to_json(row, self.big_integers))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let has_more = rows.len() as u64 > limit;
+        let next_cursor = key_columns
+            .filter(|_| has_more)
+            .and_then(|key_columns| RowCursor::after(&json_rows, offset, &query, &key_columns))
+            .map(|cursor| cursor.encode());
+
+        Ok(RowsResponse {
+            rows: json_rows,
+            columns: schema
+                .row_address
+                .identifier_column()
+                .map(str::to_string)
+                .into_iter()
+                .chain(projection.unwrap_or(column_names))
+                .collect(),
+            total,
+            total_accuracy: total.map(|_| CountAccuracy::exact()),
+            offset,
+            limit,
+            has_more,
+            next_cursor,
+            labels: None,
+        })
+    }
+
+    async fn count_rows(&self, table: &str, query: &RowQuery) -> Result<CountResponse, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let mut transaction = self.begin_read(query.snapshot.as_deref()).await?;
+        let count = Self::count_rows_on(&mut *transaction, &schema, query).await?;
+        transaction.commit().await?;
+
+        Ok(CountResponse::exact(count))
+    }
+
+    async fn sample_rows(&self, table: &str, size: u64) -> Result<SampleResponse, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let size = size.min(self.limits.max_page_size);
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+
+        // Views and foreign tables cannot be sampled with TABLESAMPLE
+        let estimate: i64 = if schema.row_address == RowAddress::None {
+            -1
+        } else {
+            sqlx::query_scalar("SELECT reltuples::bigint FROM pg_class WHERE oid = $1::regclass")
+                .bind(format!("public.{}", Self::quote_identifier(&schema.name)))
+                .fetch_optional(&mut *connection)
+                .await?
+                .unwrap_or(-1)
+        };
+
+        let select = |tablesample: String| {
+            format!(
+                "SELECT {} FROM {}{} ORDER BY random() LIMIT {}",
+                self.select_list(&schema),
+                Self::quote_identifier(&schema.name),
+                tablesample,
+                size
+            )
+        };
+        let mut method = SampleMethod::Random;
+        let mut rows = Vec::new();
+        if let Some(percentage) = sample::tablesample_percentage(size, estimate) {
+            method = SampleMethod::TableSample;
+            let sql = select(format!(" TABLESAMPLE BERNOULLI ({})", percentage));
+            rows = sqlx::query(&sql).fetch_all(&mut *connection).await?;
+        }
+        // Stale estimates can leave too few rows; sample all rows then
+        if (rows.len() as u64) < size {
+            method = SampleMethod::Random;
+            rows = sqlx::query(&select(String::new()))
+                .fetch_all(&mut *connection)
+                .await?;
+        }
+
+        Ok(SampleResponse {
+            rows: rows
+                .iter()
+                .map(|row| Self::row_to_json(row, self.big_integers))
+                .collect::<Result<Vec<_>, _>>()?,
+            columns: schema
+                .row_address
+                .identifier_column()
+                .map(str::to_string)
+                .into_iter()
+                .chain(schema.columns.iter().map(|column| column.name.clone()))
+                .collect(),
+            size,
+            method,
+        })
+    }
+
+    async fn column_stats(&self, table: &str, column: &str) -> Result<ColumnStats, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let column = schema
+            .columns
+            .iter()
+            .find(|info| info.name == column)
+            .ok_or_else(|| DatabaseError::InvalidColumn(column.to_string()))?;
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+
+        // The planner's estimate saves sorting the whole column, once analyzed
+        let kind = column_kind(&column.data_type);
+        let n_distinct: Option<f32> = if column_stats::has_equality(kind, Dialect::Postgres) {
+            sqlx::query_scalar(
+                "SELECT n_distinct FROM pg_stats
+                 WHERE schemaname = 'public' AND tablename = $1 AND attname = $2
+                 ORDER BY inherited LIMIT 1",
+            )
+            .bind(&schema.name)
+            .bind(&column.name)
+            .fetch_optional(&mut *connection)
+            .await?
+        } else {
+            None
+        };
+
+        let sql = format!(
+            "SELECT {} FROM {}",
+            column_stats::stats_expressions(column, Dialect::Postgres, n_distinct.is_none())
+                .join(", "),
+            Self::quote_identifier(&schema.name)
+        );
+        let row = sqlx::query(&sql).fetch_one(&mut *connection).await?;
+        let row_count = row.try_get::<i64, _>(0)? as u64;
+        let non_null_count = row.try_get::<i64, _>(1)? as u64;
+        let counted: Option<i64> = row.try_get(5)?;
+        let distinct_count = match n_distinct {
+            Some(n_distinct) => Some(column_stats::estimated_distinct(n_distinct, row_count)),
+            None => counted.map(|count| count as u64),
+        };
+
+        Ok(ColumnStats {
+            table: schema.name.clone(),
+            column: column.name.clone(),
+            data_type: column.data_type.clone(),
+            row_count,
+            null_count: row_count - non_null_count,
+            distinct_exact: n_distinct.is_none() && distinct_count.is_some(),
+            distinct_count,
+            min: column_stats::stats_value(column, row.try_get(2)?),
+            max: column_stats::stats_value(column, row.try_get(3)?),
+            avg: row.try_get(4)?,
+        })
+    }
+
+    async fn column_histogram(
+        &self,
+        table: &str,
+        column: &str,
+        buckets: u32,
+    ) -> Result<Histogram, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let column = schema
+            .columns
+            .iter()
+            .find(|info| info.name == column)
+            .ok_or_else(|| DatabaseError::InvalidColumn(column.to_string()))?;
+        let scale = histogram::histogram_scale(column).ok_or_else(|| {
+            DatabaseError::InvalidColumn(format!(
+                "'{}' is {}; histograms need a numeric or date column",
+                column.name, column.data_type
+            ))
+        })?;
+        let buckets = histogram::bucket_count(buckets);
+        let quoted_table = Self::quote_identifier(&schema.name);
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+
+        let range_query =
+            histogram::range_query(&quoted_table, &column.name, scale, Dialect::Postgres);
+        let (lower, upper, null_count): (Option<f64>, Option<f64>, i64) =
+            sqlx::query_as(&range_query)
+                .fetch_one(&mut *connection)
+                .await?;
+
+        let buckets = match (lower, upper) {
+            (Some(lower), Some(upper)) => {
+                let counts_query = histogram::counts_query(
+                    &quoted_table,
+                    &column.name,
+                    scale,
+                    (lower, upper),
+                    buckets,
+                    Dialect::Postgres,
+                );
+                let counts: Vec<(i64, i64)> = sqlx::query_as(&counts_query)
+                    .fetch_all(&mut *connection)
+                    .await?;
+                histogram::histogram_buckets(lower, upper, buckets, &counts, scale)
+            }
+            // No values to bucket
+            _ => Vec::new(),
+        };
+
+        Ok(Histogram {
+            table: schema.name.clone(),
+            column: column.name.clone(),
+            data_type: column.data_type.clone(),
+            scale,
+            buckets,
+            null_count: null_count as u64,
+        })
+    }
+
+    async fn find_duplicates(
+        &self,
+        table: &str,
+        columns: &[String],
+        limit: u64,
+    ) -> Result<DuplicatesResponse, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        duplicates::validate_duplicate_columns(&schema, columns, Dialect::Postgres)?;
+        let limit = limit.min(self.limits.max_page_size);
+        let sql =
+            duplicates::duplicates_query(&Self::quote_identifier(&schema.name), columns, limit);
+
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        let rows = sqlx::query(&sql).fetch_all(&mut *connection).await?;
+        let rows = rows
+            .iter()
+            .map(|row| Self::row_to_json(row, self.big_integers))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (groups, truncated) = duplicates::duplicate_groups(rows, limit);
+
+        Ok(DuplicatesResponse {
+            table: schema.name.clone(),
+            columns: columns.to_vec(),
+            groups,
+            truncated,
+        })
+    }
+
+    async fn table_checksum(&self, table: &str) -> Result<TableChecksum, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let sql = format!(
+            "SELECT {} FROM {}",
+            checksum_expressions(&schema, Dialect::Postgres).join(", "),
+            Self::quote_identifier(&schema.name)
+        );
+
+        let row = sqlx::query(&sql).fetch_one(&self.pool).await?;
+        let row_count: i64 = row.try_get(0)?;
+        let values = (1..row.len())
+            .map(|index| row.try_get::<Option<String>, _>(index))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(table_checksum(&schema.name, row_count as u64, &values))
+    }
+
+    async fn execute_query(
+        &self,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryResult, DatabaseError> {
+        let connection = self.timeouts.acquire(&self.pool).await?;
+        self.run_pooled(connection, sql, params).await
+    }
+
+    async fn execute_cancellable_query(
+        &self,
+        id: &str,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryResult, DatabaseError> {
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        let backend: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut *connection)
+            .await?;
+
+        // Unregistered before the connection goes back to the pool, so the id
+        // no longer leads to this backend once it runs someone else's query
+        let running = self.running.start(id, sql, backend)?;
+        let result = self.run_pooled(connection, sql, params).await;
+        // Cancelling fails the statement with the same code as a statement timeout
+        let cancelled = running.was_cancelled();
+        drop(running);
+
+        match result {
+            Ok(result) if cancelled => Ok(QueryResult {
+                timeout: None,
+                ..result
+            }),
+            Err(DatabaseError::Timeout(TimeoutKind::Statement)) if cancelled => {
+                Err(DatabaseError::Query("Query cancelled".to_string()))
+            }
+            result => result,
+        }
+    }
+
+    async fn cancel_query(&self, id: &str) -> Result<(), DatabaseError> {
+        let backend = self.running.cancel(id)?;
+        sqlx::query("SELECT pg_cancel_backend($1)")
+            .bind(backend)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn activity(&self) -> Result<ActivityResponse, DatabaseError> {
+        let query = r#"
+            SELECT
+                pid,
+                COALESCE(state, 'unknown') AS state,
+                query,
+                usename,
+                application_name,
+                (EXTRACT(EPOCH FROM query_start) * 1000)::bigint AS started_at_milliseconds,
+                (EXTRACT(EPOCH FROM clock_timestamp() - query_start) * 1000)::bigint
+                    AS duration_milliseconds,
+                wait_event_type,
+                wait_event
+            FROM pg_stat_activity
+            WHERE datname = current_database()
+                AND backend_type = 'client backend'
+                AND pid <> pg_backend_pid()
+            ORDER BY state = 'active' DESC, query_start NULLS LAST
+        "#;
+
+        let rows = sqlx::query(query).fetch_all(&self.pool).await?;
+        let running = self.running.list();
+
+        let mut statements = Vec::with_capacity(rows.len());
+        for row in rows {
+            let process_id: i32 = row.try_get("pid")?;
+            let wait_event_type: Option<String> = row.try_get("wait_event_type")?;
+            let wait_event: Option<String> = row.try_get("wait_event")?;
+            let started_at_milliseconds: Option<i64> = row.try_get("started_at_milliseconds")?;
+            let duration_milliseconds: Option<i64> = row.try_get("duration_milliseconds")?;
+
+            statements.push(ActiveStatement {
+                process_id: Some(process_id),
+                query_id: running
+                    .iter()
+                    .find(|statement| statement.handle == process_id)
+                    .map(|statement| statement.id.clone()),
+                state: row.try_get("state")?,
+                sql: row.try_get("query")?,
+                user: row.try_get("usename")?,
+                application: row.try_get("application_name")?,
+                started_at_milliseconds: started_at_milliseconds.map(|value| value.max(0) as u64),
+                duration_milliseconds: duration_milliseconds.map(|value| value.max(0) as u64),
+                waiting_for: match (wait_event_type, wait_event) {
+                    (Some(kind), Some(event)) => Some(format!("{}: {}", kind, event)),
+                    (kind, event) => kind.or(event),
+                },
+            });
+        }
+
+        Ok(ActivityResponse {
+            statements,
+            open_connections: self.pool.size(),
+            idle_connections: self.pool.num_idle() as u32,
+        })
+    }
+
+    async fn dry_run(&self, sql: &str, params: &[serde_json::Value]) -> Result<u64, DatabaseError> {
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        let mut transaction = connection.begin().await?;
+        self.set_statement_timeout(&mut transaction, true).await?;
+        let query = params.iter().fold(sqlx::query(sql), Self::bind_parameter);
+        let result = self
+            .timeouts
+            .statement(query.execute(&mut *transaction))
+            .await;
+        transaction.rollback().await?;
+
+        Ok(result??.rows_affected())
+    }
+
+    async fn stream_query(
+        &self,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryStream, DatabaseError> {
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        let (mut sender, stream) = streaming::channel();
+        let sql = sql.to_string();
+        let params = params.to_vec();
+        let timeouts = self.timeouts;
+        let big_integers = self.big_integers;
+
+        // The task owns the connection until the last row is read or the
+        // receiving end is dropped
+        runtime::spawn_detached(async move {
+            let query = params.iter().fold(sqlx::query(&sql), Self::bind_parameter);
+            let mut rows = query.fetch(&mut *connection);
+            loop {
+                // The statement timeout applies to the wait for each row
+                let row = match timeouts.statement(rows.try_next()).await {
+                    Ok(Ok(Some(row))) => row,
+                    Ok(Ok(None)) => break,
+                    Ok(Err(error)) => return sender.error(error.into()).await,
+                    Err(error) => return sender.error(error).await,
+                };
+                let json = match Self::row_to_json(&row, big_integers) {
+                    Ok(json) => json,
+                    Err(error) => return sender.error(error).await,
+                };
+                let columns = || {
+                    row.columns()
+                        .iter()
+                        .map(|column| column.name().to_string())
+                        .collect()
+                };
+                if !sender.row(columns, json).await {
+                    break;
+                }
+            }
+        });
+
+        Ok(stream)
+    }
+
+    async fn execute_script(&self, statements: &[String]) -> Result<ScriptResult, DatabaseError> {
+        let start_time = Instant::now();
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        let mut transaction = connection.begin().await?;
+        self.set_statement_timeout(&mut transaction, true).await?;
+        let mut script = ScriptResult::default();
+
+        for (index, sql) in statements.iter().enumerate() {
+            let result = Self::run_query(
+                &mut *transaction,
+                sql,
+                &[],
+                self.timeouts,
+                self.limits,
+                self.big_integers,
+            )
+            .await;
+            let error = match result {
+                Ok(result) => match result.error {
+                    None => {
+                        script.statements.push(ScriptStatement {
+                            sql: sql.clone(),
+                            affected_rows: result.affected_rows,
+                            execution_time_milliseconds: result.execution_time_milliseconds,
+                        });
+                        continue;
+                    }
+                    Some(error) => error,
+                },
+                Err(error) => error.to_string(),
+            };
+
+            transaction.rollback().await?;
+            script.failed_statement = Some(FailedStatement {
+                index,
+                sql: sql.clone(),
+                error,
+            });
+            script.execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+            return Ok(script);
+        }
+
+        transaction.commit().await?;
+        script.committed = true;
+        script.execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+        Ok(script)
+    }
+
+    async fn explain_query(&self, sql: &str) -> Result<Vec<String>, DatabaseError> {
+        let explain_sql = format!("EXPLAIN {}", sql);
+        let rows = sqlx::query(&explain_sql).fetch_all(&self.pool).await?;
+
+        // EXPLAIN returns a single "QUERY PLAN" text column, one row per line
+        let plan = rows
+            .iter()
+            .map(|row| row.try_get::<String, _>(0))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(plan)
+    }
+
+    async fn explain_plan(
+        &self,
+        sql: &str,
+        analyze: bool,
+    ) -> Result<ExplainResponse, DatabaseError> {
+        let options = if analyze {
+            "ANALYZE, FORMAT JSON"
+        } else {
+            "FORMAT JSON"
+        };
+        let explain_sql = format!("EXPLAIN ({}) {}", options, sql);
+
+        // EXPLAIN ANALYZE runs the statement, so never keep what it did
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        let mut transaction = connection.begin().await?;
+        self.set_statement_timeout(&mut transaction, true).await?;
+        let plan: serde_json::Value = self
+            .timeouts
+            .statement(sqlx::query(&explain_sql).fetch_one(&mut *transaction))
+            .await??
+            .try_get(0)?;
+        transaction.rollback().await?;
+
+        let (planning_time_milliseconds, execution_time_milliseconds) =
+            explain::postgres_plan_times(&plan);
+        Ok(ExplainResponse {
+            explained_sql: sql.to_string(),
+            plan: explain::postgres_plan_tree(&plan),
+            planning_time_milliseconds,
+            execution_time_milliseconds,
+        })
+    }
+
+    async fn get_row(
+        &self,
+        table: &str,
+        key: &[String],
+    ) -> Result<Option<serde_json::Value>, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        let key_columns = primary_key_columns(&schema, key)?;
+        let column_types = self.column_types(table).await?;
+
+        let conditions: Vec<String> = key_columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| match column_types.get(column) {
+                Some(column_type) => format!(
+                    "{} = CAST(${} AS {})",
+                    Self::quote_identifier(column),
+                    index + 1,
+                    column_type
+                ),
+                None => format!("{} = ${}", Self::quote_identifier(column), index + 1),
+            })
+            .collect();
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {}",
+            self.select_list(&schema),
+            Self::quote_identifier(table),
+            conditions.join(" AND ")
+        );
+
+        let mut query = sqlx::query(&sql);
+        for value in key {
+            query = query.bind(value.clone());
+        }
+
+        match query.fetch_optional(&self.pool).await? {
+            Some(row) => Self::row_to_json(&row, self.big_integers).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn count_rows_with_values(
+        &self,
+        table: &str,
+        column: &str,
+        values: &[String],
+    ) -> Result<u64, DatabaseError> {
+        if values.is_empty() {
+            return Ok(0);
+        }
+
+        let column_types = self.column_types(table).await?;
+        let column_type = column_types
+            .get(column)
+            .ok_or_else(|| DatabaseError::InvalidColumn(column.to_string()))?;
+
+        let sql = format!(
+            "SELECT COUNT(*) AS count FROM {} WHERE {} = ANY(CAST($1 AS {}[]))",
+            Self::quote_identifier(table),
+            Self::quote_identifier(column),
+            column_type
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(values.to_vec())
+            .fetch_one(&self.pool)
+            .await?;
+        let count: i64 = row.try_get("count")?;
+
+        Ok(count as u64)
+    }
+
+    async fn get_rows_with_values(
+        &self,
+        table: &str,
+        column: &str,
+        values: &[String],
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>, DatabaseError> {
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let column_types = self.column_types(table).await?;
+        let column_type = column_types
+            .get(column)
+            .ok_or_else(|| DatabaseError::InvalidColumn(column.to_string()))?;
+
+        let sql = format!(
+            "SELECT * FROM {} WHERE {} = ANY(CAST($1 AS {}[])) LIMIT $2",
+            Self::quote_identifier(table),
+            Self::quote_identifier(column),
+            column_type
+        );
+
+        let rows = sqlx::query(&sql)
+            .bind(values.to_vec())
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| Self::row_to_json(row, self.big_integers))
+            .collect()
+    }
+
+    async fn update_row(
+        &self,
+        table: &str,
+        key: &[String],
+        values: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<serde_json::Value, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        let key_columns = primary_key_columns(&schema, key)?;
+        validate_columns(&schema, values.keys())?;
+        let column_types = self.column_types(table).await?;
+
+        let cast = |column: &str, parameter_index: usize| match column_types.get(column) {
+            Some(column_type) => format!("CAST(${} AS {})", parameter_index, column_type),
+            None => format!("${}", parameter_index),
+        };
+
+        let assignments: Vec<String> = values
+            .keys()
+            .enumerate()
+            .map(|(index, column)| {
+                format!(
+                    "{} = {}",
+                    Self::quote_identifier(column),
+                    cast(column, index + 1)
+                )
+            })
+            .collect();
+        let conditions: Vec<String> = key_columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                format!(
+                    "{} = {}",
+                    Self::quote_identifier(column),
+                    cast(column, values.len() + index + 1)
+                )
+            })
+            .collect();
+
+        let sql = format!(
+            "UPDATE {} SET {} WHERE {} RETURNING {}",
+            Self::quote_identifier(table),
+            assignments.join(", "),
+            conditions.join(" AND "),
+            self.select_list(&schema)
+        );
+
+        let mut query = sqlx::query(&sql);
+        for (column, value) in values {
+            let column_type = column_types
+                .get(column)
+                .map(String::as_str)
+                .unwrap_or_default();
+            query = query.bind(Self::json_to_text(value, column_type));
+        }
+        for value in key {
+            query = query.bind(value.clone());
+        }
+
+        match query.fetch_optional(&self.pool).await? {
+            Some(row) => Self::row_to_json(&row, self.big_integers),
+            None => Err(DatabaseError::RowNotFound(key.join(", "))),
+        }
+    }
+
+    async fn insert_rows(
+        &self,
+        table: &str,
+        rows: &[serde_json::Map<String, serde_json::Value>],
+    ) -> Result<InsertedRows, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        validate_new_rows(&schema, rows)?;
+        let column_types = self.column_types(table).await?;
+
+        // Dropping the transaction after a failed insert rolls back the earlier rows
+        let mut transaction = self.pool.begin().await?;
+        Self::insert_into(&mut transaction, table, &column_types, rows).await?;
+        transaction.commit().await?;
+
+        Ok(InsertedRows {
+            inserted: rows.len() as u64,
+        })
+    }
+
+    async fn delete_rows(
+        &self,
+        table: &str,
+        keys: &[Vec<String>],
+        cascade: &[Vec<Reference>],
+    ) -> Result<DeletedRows, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        let Some(first_key) = keys.first() else {
+            return Ok(DeletedRows::default());
+        };
+        let key_columns = primary_key_columns(&schema, first_key)?;
+        for key in keys {
+            primary_key_columns(&schema, key)?;
+        }
+        let column_types = self.column_types(table).await?;
+
+        // One group of key comparisons per row, with parameters numbered across all rows
+        let mut parameter_index = 0;
+        let mut row_conditions = Vec::with_capacity(keys.len());
+        for _ in keys {
+            let conditions: Vec<String> = key_columns
+                .iter()
+                .map(|column| {
+                    parameter_index += 1;
+                    match column_types.get(column) {
+                        Some(column_type) => format!(
+                            "{} = CAST(${} AS {})",
+                            Self::quote_identifier(column),
+                            parameter_index,
+                            column_type
+                        ),
+                        None => {
+                            format!("{} = ${}", Self::quote_identifier(column), parameter_index)
+                        }
+                    }
+                })
+                .collect();
+            row_conditions.push(format!("({})", conditions.join(" AND ")));
+        }
+        let root_condition = row_conditions.join(" OR ");
+
+        let mut transaction = self.pool.begin().await?;
+        let mut deleted_rows = DeletedRows::default();
+
+        for path in cascade {
+            let sql = cascade_delete_sql(table, &root_condition, path, Self::quote_identifier);
+            let mut query = sqlx::query(&sql);
+            for value in keys.iter().flatten() {
+                query = query.bind(value.clone());
+            }
+            deleted_rows.cascaded += query.execute(&mut *transaction).await?.rows_affected();
+        }
+
+        let sql = format!(
+            "DELETE FROM {} WHERE {}",
+            Self::quote_identifier(table),
+            root_condition
+        );
+        let mut query = sqlx::query(&sql);
+        for value in keys.iter().flatten() {
+            query = query.bind(value.clone());
+        }
+        deleted_rows.deleted = query.execute(&mut *transaction).await?.rows_affected();
+
+        transaction.commit().await?;
+
+        Ok(deleted_rows)
+    }
+
+    async fn begin_snapshot(&self) -> Result<SnapshotResponse, DatabaseError> {
+        {
+            let mut snapshots = self.snapshots.lock().unwrap();
+            Self::release_idle_snapshots(&mut snapshots);
+            if snapshots.len() >= MAX_OPEN_SNAPSHOTS {
+                return Err(DatabaseError::Query(format!(
+                    "Too many open snapshots (max {})",
+                    MAX_OPEN_SNAPSHOTS
+                )));
+            }
+        }
+
+        // The exporting transaction is kept open for the lifetime of the snapshot
+        let mut transaction = self.pool.begin().await?;
+        transaction
+            .execute("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .await?;
+        let snapshot: String = sqlx::query_scalar("SELECT pg_export_snapshot()")
+            .fetch_one(&mut *transaction)
+            .await?;
+
+        self.snapshots.lock().unwrap().insert(
+            snapshot.clone(),
+            SnapshotSession {
+                transaction,
+                last_used: Instant::now(),
+            },
+        );
+
+        Ok(SnapshotResponse {
+            snapshot,
+            idle_timeout_seconds: SNAPSHOT_IDLE_TIMEOUT.as_secs(),
+        })
+    }
+
+    async fn replace_rows(&self, tables: &[FixtureTable]) -> Result<LoadedFixture, DatabaseError> {
+        let mut schemas = Vec::with_capacity(tables.len());
+        let mut column_types = Vec::with_capacity(tables.len());
+        for fixture_table in tables {
+            let schema = self.get_table_schema(&fixture_table.table).await?;
+            validate_table_rows(&schema, &fixture_table.rows)?;
+            column_types.push(self.column_types(&schema.name).await?);
+            schemas.push(schema);
+        }
+
+        let mut transaction = self.pool.begin().await?;
+        let mut loaded = LoadedFixture {
+            tables: tables.len() as u64,
+            ..LoadedFixture::default()
+        };
+
+        // Referencing tables are emptied before the tables they reference
+        for schema in schemas.iter().rev() {
+            let sql = format!("DELETE FROM {}", Self::quote_identifier(&schema.name));
+            loaded.deleted += sqlx::query(&sql)
+                .execute(&mut *transaction)
+                .await?
+                .rows_affected();
+        }
+        for ((schema, fixture_table), column_types) in schemas.iter().zip(tables).zip(&column_types)
+        {
+            Self::insert_into(
+                &mut transaction,
+                &schema.name,
+                column_types,
+                &fixture_table.rows,
+            )
+            .await?;
+            Self::reset_sequences(&mut transaction, &schema.name).await?;
+            loaded.inserted += fixture_table.rows.len() as u64;
+        }
+
+        transaction.commit().await?;
+        Ok(loaded)
+    }
+
+    async fn seed_status(&self) -> Result<SeedStatus, DatabaseError> {
+        let location = Self::seed_database_name(&self.current_database().await?);
+        let exists: Option<i32> =
+            sqlx::query_scalar("SELECT 1 FROM pg_database WHERE datname = $1")
+                .bind(&location)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(SeedStatus {
+            location,
+            exists: exists.is_some(),
+        })
+    }
+
+    async fn create_seed(&self) -> Result<SeedStatus, DatabaseError> {
+        let database = self.current_database().await?;
+        let location = Self::seed_database_name(&database);
+        let mut connection = self.maintenance_connection().await?;
+
+        let drop_sql = format!(
+            "DROP DATABASE IF EXISTS {} WITH (FORCE)",
+            Self::quote_identifier(&location)
+        );
+        connection.execute(drop_sql.as_str()).await?;
+        Self::copy_database(&mut connection, &database, &location).await?;
+        connection.close().await?;
+
+        Ok(SeedStatus {
+            location,
+            exists: true,
+        })
+    }
+
+    async fn reset_to_seed(&self) -> Result<SeedStatus, DatabaseError> {
+        let status = self.seed_status().await?;
+        if !status.exists {
+            return Err(DatabaseError::SeedNotFound(status.location));
+        }
+        let database = self.current_database().await?;
+
+        // Snapshots and sessions live on connections that are about to be terminated
+        self.snapshots.lock().unwrap().clear();
+        self.sessions.clear();
+
+        // Every connection to the database is closed; the pool reconnects to
+        // the recreated database on the next request
+        let mut connection = self.maintenance_connection().await?;
+        let drop_sql = format!(
+            "DROP DATABASE IF EXISTS {} WITH (FORCE)",
+            Self::quote_identifier(&database)
+        );
+        connection.execute(drop_sql.as_str()).await?;
+        Self::copy_database(&mut connection, &status.location, &database).await?;
+        connection.close().await?;
+
+        Ok(status)
+    }
+
+    async fn release_snapshot(&self, snapshot: &str) -> Result<(), DatabaseError> {
+        let session = self.snapshots.lock().unwrap().remove(snapshot);
+
+        match session {
+            Some(session) => {
+                session.transaction.rollback().await?;
+                Ok(())
+            }
+            None => Err(DatabaseError::SnapshotNotFound(snapshot.to_string())),
+        }
+    }
+
+    async fn list_named_snapshots(&self) -> Result<Vec<NamedSnapshot>, DatabaseError> {
+        self.read_named_snapshots(None).await
+    }
+
+    async fn save_named_snapshot(
+        &self,
+        name: &str,
+        table: Option<&str>,
+    ) -> Result<NamedSnapshot, DatabaseError> {
+        validate_snapshot_name(name)?;
+        let tables = match table {
+            Some(table) => vec![self.get_table_schema(table).await?.name],
+            // Partitions are copied through their parent table
+            None => {
+                sqlx::query_scalar(
+                    "SELECT c.relname::text FROM pg_class c \
+                     JOIN pg_namespace n ON n.oid = c.relnamespace \
+                     WHERE n.nspname = 'public' AND c.relkind IN ('r', 'p') \
+                     AND NOT c.relispartition ORDER BY c.relname",
+                )
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+        let schema = Self::quote_identifier(&Self::named_snapshot_schema(name));
+        let created_at_milliseconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+
+        // A snapshot of the same name is only replaced once the copy succeeded
+        let mut transaction = self.pool.begin().await?;
+        let sql = format!(
+            "DROP SCHEMA IF EXISTS {0} CASCADE; CREATE SCHEMA {0}; COMMENT ON SCHEMA {0} IS '{1}'",
+            schema, created_at_milliseconds
+        );
+        transaction.execute(sql.as_str()).await?;
+        for table in &tables {
+            let sql = format!(
+                "CREATE TABLE {0}.{1} AS SELECT * FROM public.{1}",
+                schema,
+                Self::quote_identifier(table)
+            );
+            sqlx::query(&sql).execute(&mut *transaction).await?;
+        }
+        transaction.commit().await?;
+
+        self.read_named_snapshot(name).await
+    }
+
+    async fn restore_named_snapshot(&self, name: &str) -> Result<NamedSnapshot, DatabaseError> {
+        let snapshot = self.read_named_snapshot(name).await?;
+        let schema = Self::named_snapshot_schema(name);
+
+        let mut schemas = Vec::with_capacity(snapshot.tables.len());
+        for table in &snapshot.tables {
+            schemas.push(self.get_table_schema(table).await?);
+        }
+        // Tables referencing each other in a cycle are restored as listed;
+        // the database rejects the restore if that breaks a reference
+        let order = load_order(&schemas).unwrap_or_else(|_| (0..schemas.len()).collect());
+
+        let mut transaction = self.pool.begin().await?;
+        for &index in order.iter().rev() {
+            let sql = format!(
+                "DELETE FROM public.{}",
+                Self::quote_identifier(&schemas[index].name)
+            );
+            sqlx::query(&sql).execute(&mut *transaction).await?;
+        }
+        for &index in &order {
+            let table = &schemas[index].name;
+            let columns = Self::snapshot_columns(&mut transaction, &schema, table).await?;
+            let sql = format!(
+                "INSERT INTO public.{0} ({1}) SELECT {1} FROM {2}.{0}",
+                Self::quote_identifier(table),
+                columns,
+                Self::quote_identifier(&schema)
+            );
+            sqlx::query(&sql).execute(&mut *transaction).await?;
+            Self::reset_sequences(&mut transaction, table).await?;
+        }
+        transaction.commit().await?;
+
+        Ok(snapshot)
+    }
+
+    async fn delete_named_snapshot(&self, name: &str) -> Result<(), DatabaseError> {
+        self.read_named_snapshot(name).await?;
+        let sql = format!(
+            "DROP SCHEMA IF EXISTS {} CASCADE",
+            Self::quote_identifier(&Self::named_snapshot_schema(name))
+        );
+        sqlx::raw_sql(&sql).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn named_snapshot_differences(
+        &self,
+        name: &str,
+        table: &str,
+        limit: u64,
+    ) -> Result<SnapshotRowDifferences, DatabaseError> {
+        let snapshot = self.read_named_snapshot(name).await?;
+        if !snapshot
+            .tables
+            .iter()
+            .any(|snapshot_table| snapshot_table == table)
+        {
+            return Err(DatabaseError::TableNotFound(table.to_string()));
+        }
+        let schema = Self::named_snapshot_schema(name);
+
+        let mut connection = self.pool.acquire().await?;
+        let columns = Self::snapshot_columns(&mut connection, &schema, table).await?;
+        let current = format!("public.{}", Self::quote_identifier(table));
+        let copy = format!(
+            "{}.{}",
+            Self::quote_identifier(&schema),
+            Self::quote_identifier(table)
+        );
+
+        // Rows are compared as JSON, since not every column type has an equality operator
+        let mut differences = SnapshotRowDifferences {
+            current: Vec::new(),
+            snapshot: Vec::new(),
+            truncated: false,
+        };
+        for (from, except) in [(&current, &copy), (&copy, &current)] {
+            let sql = format!(
+                "SELECT to_jsonb(r)::text FROM (SELECT {0} FROM {1}) r \
+                 EXCEPT SELECT to_jsonb(r)::text FROM (SELECT {0} FROM {2}) r LIMIT $1",
+                columns, from, except
+            );
+            let rows: Vec<String> = sqlx::query_scalar(&sql)
+                .bind((limit + 1) as i64)
+                .fetch_all(&mut *connection)
+                .await?;
+            differences.truncated |= rows.len() as u64 > limit;
+            let rows = rows
+                .iter()
+                .take(limit as usize)
+                .map(|row| serde_json::from_str::<serde_json::Value>(row))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| DatabaseError::Serialization(error.to_string()))?;
+            if from == &current {
+                differences.current = rows;
+            } else {
+                differences.snapshot = rows;
+            }
+        }
+        Ok(differences)
+    }
+
+    async fn run_maintenance(
+        &self,
+        operation: MaintenanceOperation,
+        tables: &[String],
+    ) -> Result<MaintenanceResult, DatabaseError> {
+        if operation == MaintenanceOperation::Checkpoint {
+            return Err(DatabaseError::Unsupported(
+                "Checkpoints are only run on SQLite databases; PostgreSQL checkpoints on its own"
+                    .to_string(),
+            ));
+        }
+
+        let mut connection = self.pool.acquire().await?;
+        let database_size = "SELECT pg_database_size(current_database())";
+        let size_before: i64 = sqlx::query_scalar(database_size)
+            .fetch_one(&mut *connection)
+            .await?;
+        let start_time = Instant::now();
+
+        // VACUUM and REINDEX cannot run inside a transaction block, so each
+        // statement is sent on its own
+        let table_list = tables
+            .iter()
+            .map(|table| format!("public.{}", Self::quote_identifier(table)))
+            .collect::<Vec<_>>();
+        let statements = match operation {
+            MaintenanceOperation::Vacuum => {
+                vec![format!("VACUUM (ANALYZE) {}", table_list.join(", "))]
+            }
+            MaintenanceOperation::Analyze => vec![format!("ANALYZE {}", table_list.join(", "))],
+            MaintenanceOperation::Reindex if tables.is_empty() => {
+                vec![format!(
+                    "REINDEX DATABASE {}",
+                    Self::quote_identifier(&self.current_database().await?)
+                )]
+            }
+            MaintenanceOperation::Reindex => table_list
+                .iter()
+                .map(|table| format!("REINDEX TABLE {}", table))
+                .collect(),
+            MaintenanceOperation::Checkpoint => Vec::new(),
+        };
+        for statement in &statements {
+            connection.execute(statement.as_str()).await?;
+        }
+
+        let size_after: i64 = sqlx::query_scalar(database_size)
+            .fetch_one(&mut *connection)
+            .await?;
+        Ok(MaintenanceResult {
+            operation,
+            tables: tables.to_vec(),
+            size_before: size_before as u64,
+            size_after: size_after as u64,
+            execution_time_milliseconds: start_time.elapsed().as_millis() as u64,
+            checkpoint: None,
+        })
+    }
+
+    async fn table_definition(&self, table: &str) -> Result<TableDefinition, DatabaseError> {
+        let table = self.resolve_table(table).await?;
+        let qualified_table = format!("public.{}", Self::quote_identifier(&table));
+        let mut connection = self.pool.acquire().await?;
+
+        let kind: String =
+            sqlx::query_scalar("SELECT relkind::text FROM pg_class WHERE oid = $1::regclass")
+                .bind(&qualified_table)
+                .fetch_one(&mut *connection)
+                .await?;
+        if kind != "r" {
+            return Err(DatabaseError::Unsupported(format!(
+                "'{}' is not a table; only tables can be dumped",
+                table
+            )));
+        }
+
+        let attributes: Vec<(String, String, bool, Option<String>, String, String, bool)> =
+            sqlx::query_as(
+                r#"
+                SELECT
+                    a.attname::text,
+                    format_type(a.atttypid, a.atttypmod),
+                    a.attnotnull,
+                    pg_get_expr(d.adbin, d.adrelid),
+                    a.attidentity::text,
+                    a.attgenerated::text,
+                    pg_get_serial_sequence($1, a.attname) IS NOT NULL
+                FROM pg_attribute a
+                LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+                WHERE a.attrelid = $1::regclass
+                  AND a.attnum > 0
+                  AND NOT a.attisdropped
+                ORDER BY a.attnum
+                "#,
+            )
+            .bind(&qualified_table)
+            .fetch_all(&mut *connection)
+            .await?;
+
+        let quoted_table = Self::quote_identifier(&table);
+        let mut definition = TableDefinition {
+            table: table.clone(),
+            ..TableDefinition::default()
+        };
+        let mut lines = Vec::new();
+        for (column, data_type, not_null, default, identity, generated, owns_sequence) in attributes
+        {
+            let quoted_column = Self::quote_identifier(&column);
+            if generated == "s" {
+                lines.push(format!(
+                    "{} {} GENERATED ALWAYS AS ({}) STORED",
+                    quoted_column,
+                    data_type,
+                    default.unwrap_or_default()
+                ));
+                continue;
+            }
+
+            // Identity columns and serial columns own a sequence, which the dump
+            // moves past the restored rows
+            let serial_type = match data_type.as_str() {
+                "integer" => Some("serial"),
+                "bigint" => Some("bigserial"),
+                "smallint" => Some("smallserial"),
+                _ => None,
+            };
+            let is_serial = owns_sequence
+                && serial_type.is_some()
+                && default
+                    .as_deref()
+                    .is_some_and(|default| default.starts_with("nextval("));
+            let mut line = match (identity.as_str(), serial_type) {
+                ("a", _) => {
+                    definition.overriding_system_value = true;
+                    format!(
+                        "{} {} GENERATED ALWAYS AS IDENTITY",
+                        quoted_column, data_type
+                    )
+                }
+                ("d", _) => format!(
+                    "{} {} GENERATED BY DEFAULT AS IDENTITY",
+                    quoted_column, data_type
+                ),
+                (_, Some(serial_type)) if is_serial => format!("{} {}", quoted_column, serial_type),
+                _ => match &default {
+                    Some(default) => format!("{} {} DEFAULT {}", quoted_column, data_type, default),
+                    None => format!("{} {}", quoted_column, data_type),
+                },
+            };
+            if not_null {
+                line.push_str(" NOT NULL");
+            }
+            lines.push(line);
+
+            if owns_sequence && (identity == "a" || identity == "d" || is_serial) {
+                let literal = |text: &str| {
+                    sql::literal(
+                        &serde_json::Value::String(text.to_string()),
+                        Dialect::Postgres,
+                    )
+                };
+                definition.sequence_resets.push(format!(
+                    "SELECT setval(pg_get_serial_sequence({}, {}), \
+                     COALESCE(MAX({}), 0) + 1, false) FROM {}",
+                    literal(&quoted_table),
+                    literal(&column),
+                    quoted_column,
+                    quoted_table
+                ));
+            }
+            definition.columns.push(column);
+        }
+
+        let constraints: Vec<(String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT conname::text, contype::text, pg_get_constraintdef(oid)
+            FROM pg_constraint
+            WHERE conrelid = $1::regclass AND contype IN ('p', 'u', 'c', 'x', 'f')
+            ORDER BY contype <> 'p', conname
+            "#,
+        )
+        .bind(&qualified_table)
+        .fetch_all(&mut *connection)
+        .await?;
+        for (name, kind, constraint) in constraints {
+            let name = Self::quote_identifier(&name);
+            if kind == "f" {
+                // Added once every table is filled, so the order of the rows does not matter
+                definition.foreign_keys.push(format!(
+                    "ALTER TABLE ONLY {} ADD CONSTRAINT {} {}",
+                    quoted_table, name, constraint
+                ));
+            } else {
+                lines.push(format!("CONSTRAINT {} {}", name, constraint));
+            }
+        }
+
+        definition.indexes = sqlx::query_scalar(
+            r#"
+            SELECT pg_get_indexdef(i.indexrelid)
+            FROM pg_index i
+            WHERE i.indrelid = $1::regclass
+              AND NOT EXISTS (
+                  SELECT 1 FROM pg_constraint c
+                  WHERE c.conindid = i.indexrelid AND c.contype IN ('p', 'u', 'x')
+              )
+            ORDER BY i.indexrelid::regclass::text
+            "#,
+        )
+        .bind(&qualified_table)
+        .fetch_all(&mut *connection)
+        .await?;
+
+        definition.create_statement = format!(
+            "CREATE TABLE {} (\n    {}\n)",
+            quoted_table,
+            lines.join(",\n    ")
+        );
+        Ok(definition)
+    }
+
+    async fn dump_rows(
+        &self,
+        table: &str,
+        columns: &[String],
+        snapshot: Option<&str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let order = match &schema.primary_key {
+            Some(primary_key) => primary_key
+                .iter()
+                .map(|column| Self::quote_identifier(column))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => "ctid".to_string(),
+        };
+        let select = columns
+            .iter()
+            .map(|column| {
+                let column = Self::quote_identifier(column);
+                format!("{0}::text AS {0}", column)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "SELECT {} FROM {} ORDER BY {} LIMIT {} OFFSET {}",
+            select,
+            Self::quote_identifier(&schema.name),
+            order,
+            limit,
+            offset
+        );
+
+        let mut transaction = self.begin_read(snapshot).await?;
+        let rows = sqlx::query(&sql).fetch_all(&mut *transaction).await?;
+        transaction.commit().await?;
+
+        rows.iter()
+            .map(|row| {
+                let mut values = serde_json::Map::new();
+                for (index, column) in columns.iter().enumerate() {
+                    let value: Option<String> = row.try_get(index)?;
+                    values.insert(
+                        column.clone(),
+                        value.map_or(serde_json::Value::Null, serde_json::Value::String),
+                    );
+                }
+                Ok(serde_json::Value::Object(values))
+            })
+            .collect()
+    }
+
+    async fn open_session(&self) -> Result<SessionResponse, DatabaseError> {
+        self.sessions.ensure_capacity()?;
+        let connection = self.timeouts.acquire(&self.pool).await?.detach();
+
+        Ok(SessionResponse {
+            session: self.sessions.open(connection),
+            idle_timeout_seconds: SESSION_IDLE_TIMEOUT.as_secs(),
+        })
+    }
+
+    async fn execute_in_session(
+        &self,
+        session: &str,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryResult, DatabaseError> {
+        let mut connection = self.sessions.acquire(session)?;
+        let result = Self::run_query(
+            &mut connection,
+            sql,
+            params,
+            self.timeouts,
+            self.limits,
+            self.big_integers,
+        )
+        .await;
+
+        // A statement interrupted by the timeout leaves the connection in an
+        // unknown state, so the session is closed instead of reused
+        match result {
+            Err(DatabaseError::Timeout(_)) => {
+                let _ = self.sessions.close(session);
+            }
+            _ => self.sessions.release(session, connection),
+        }
+        result
+    }
+
+    async fn schema_fingerprint(&self) -> Result<String, DatabaseError> {
+        // Hash of the catalog entries the table schemas are built from
+        let query = r#"
+            SELECT md5(coalesce(string_agg(entry, ',' ORDER BY entry), ''))
+            FROM (
+                SELECT c.relname || '.' || a.attname || ':'
+                    || format_type(a.atttypid, a.atttypmod) || ':' || a.attnotnull AS entry
+                FROM pg_attribute a
+                JOIN pg_class c ON c.oid = a.attrelid
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = 'public'
+                  AND c.relkind IN ('r', 'p', 'v', 'm', 'f')
+                  AND a.attnum > 0
+                  AND NOT a.attisdropped
+                UNION ALL
+                SELECT c.relname || ':' || co.conname || ':' || pg_get_constraintdef(co.oid)
+                FROM pg_constraint co
+                JOIN pg_class c ON c.oid = co.conrelid
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = 'public'
+                UNION ALL
+                SELECT c.relname || ':' || pg_get_indexdef(i.indexrelid)
+                FROM pg_index i
+                JOIN pg_class c ON c.oid = i.indrelid
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = 'public'
+            ) entries
+        "#;
+
+        let fingerprint: String = sqlx::query_scalar(query).fetch_one(&self.pool).await?;
+        Ok(fingerprint)
+    }
+
+    async fn listen(&self, channels: &[String]) -> Result<NotificationStream, DatabaseError> {
+        // The listener reconnects and listens again by itself when its connection drops
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener
+            .listen_all(channels.iter().map(String::as_str))
+            .await?;
+
+        let notifications = listener.into_stream().map(|notification| {
+            notification
+                .map(|notification| Notification {
+                    channel: notification.channel().to_string(),
+                    payload: notification.payload().to_string(),
+                })
+                .map_err(DatabaseError::from)
+        });
+        Ok(Box::pin(notifications))
+    }
+
+    async fn close_session(&self, session: &str) -> Result<(), DatabaseError> {
+        if let Some(connection) = self.sessions.close(session)? {
+            connection.close().await?;
+        }
+        Ok(())
+    }
+}
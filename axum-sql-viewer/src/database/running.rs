@@ -0,0 +1,192 @@
+//! Raw queries that can be cancelled while they run
+//!
+//! A client that wants to be able to cancel a query names it with an
+//! identifier of its choice when sending it. While the query runs, the
+//! identifier maps to whatever the provider needs to stop it from another
+//! request: the backend process id on PostgreSQL, an interrupt flag checked by
+//! the progress handler on SQLite. The registered queries also show up in
+//! the activity monitor.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::database::traits::DatabaseError;
+
+/// Longest accepted query identifier
+pub const MAX_QUERY_ID_LENGTH: usize = 64;
+
+/// Cancel handles of running queries by identifier
+pub struct RunningQueries<H> {
+    queries: Mutex<HashMap<String, RunningStatement<H>>>,
+    /// Identifiers of running queries that were asked to stop
+    cancelled: Mutex<HashSet<String>>,
+}
+
+/// A registered query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningStatement<H> {
+    /// Identifier chosen by the client
+    pub id: String,
+    /// SQL of the query
+    pub sql: String,
+    /// When the query was registered, in milliseconds since the Unix epoch
+    pub started_at_milliseconds: u64,
+    /// What the provider needs to cancel the query
+    pub handle: H,
+}
+
+impl<H> Default for RunningQueries<H> {
+    fn default() -> Self {
+        Self {
+            queries: Mutex::new(HashMap::new()),
+            cancelled: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl<H: Clone> RunningQueries<H> {
+    /// Register a query that is about to run
+    ///
+    /// The query stays registered until the returned guard is dropped. Fails
+    /// if the identifier is malformed or already used by a running query.
+    pub fn start(
+        &self,
+        id: &str,
+        sql: &str,
+        handle: H,
+    ) -> Result<RunningQuery<'_, H>, DatabaseError> {
+        let valid = !id.is_empty()
+            && id.len() <= MAX_QUERY_ID_LENGTH
+            && id
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || "-_.".contains(character));
+        if !valid {
+            return Err(DatabaseError::Query(format!(
+                "Invalid query id '{}': use up to {} letters, digits, '-', '_' or '.'",
+                id, MAX_QUERY_ID_LENGTH
+            )));
+        }
+
+        let mut queries = self.queries.lock().unwrap();
+        if queries.contains_key(id) {
+            return Err(DatabaseError::Query(format!(
+                "Invalid query id '{}': a query with this id is already running",
+                id
+            )));
+        }
+        let started_at_milliseconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        queries.insert(
+            id.to_string(),
+            RunningStatement {
+                id: id.to_string(),
+                sql: sql.to_string(),
+                started_at_milliseconds,
+                handle,
+            },
+        );
+
+        Ok(RunningQuery {
+            queries: self,
+            id: id.to_string(),
+        })
+    }
+
+    /// Cancel handle of a running query
+    pub fn handle(&self, id: &str) -> Result<H, DatabaseError> {
+        self.queries
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|statement| statement.handle.clone())
+            .ok_or_else(|| DatabaseError::QueryNotFound(id.to_string()))
+    }
+
+    /// Cancel handle of a running query, noting that it is asked to stop
+    pub fn cancel(&self, id: &str) -> Result<H, DatabaseError> {
+        let queries = self.queries.lock().unwrap();
+        let statement = queries
+            .get(id)
+            .ok_or_else(|| DatabaseError::QueryNotFound(id.to_string()))?;
+        self.cancelled.lock().unwrap().insert(id.to_string());
+        Ok(statement.handle.clone())
+    }
+
+    /// All running queries, oldest first
+    pub fn list(&self) -> Vec<RunningStatement<H>> {
+        let mut statements: Vec<_> = self.queries.lock().unwrap().values().cloned().collect();
+        statements.sort_by(|a, b| {
+            (a.started_at_milliseconds, &a.id).cmp(&(b.started_at_milliseconds, &b.id))
+        });
+        statements
+    }
+}
+
+/// Registration of a running query, removed when dropped
+pub struct RunningQuery<'a, H> {
+    queries: &'a RunningQueries<H>,
+    id: String,
+}
+
+impl<H> RunningQuery<'_, H> {
+    /// Whether the query was asked to stop with [`RunningQueries::cancel`]
+    pub fn was_cancelled(&self) -> bool {
+        self.queries.cancelled.lock().unwrap().contains(&self.id)
+    }
+}
+
+impl<H> Drop for RunningQuery<'_, H> {
+    fn drop(&mut self) {
+        // Same lock order as `cancel`, so no cancellation outlives its query
+        let mut queries = self.queries.queries.lock().unwrap();
+        queries.remove(&self.id);
+        self.queries.cancelled.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_query_lifecycle() {
+        let queries: RunningQueries<u32> = RunningQueries::default();
+
+        let running = queries.start("editor-1", "SELECT 1", 42).unwrap();
+        assert_eq!(queries.handle("editor-1").unwrap(), 42);
+        assert!(!running.was_cancelled());
+        assert_eq!(queries.cancel("editor-1").unwrap(), 42);
+        assert!(running.was_cancelled());
+        assert!(queries.start("editor-1", "SELECT 2", 43).is_err());
+
+        let listed = queries.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, "editor-1");
+        assert_eq!(listed[0].sql, "SELECT 1");
+
+        drop(running);
+        assert!(matches!(
+            queries.handle("editor-1"),
+            Err(DatabaseError::QueryNotFound(_))
+        ));
+        assert!(!queries
+            .start("editor-1", "SELECT 2", 43)
+            .unwrap()
+            .was_cancelled());
+        assert!(queries.list().is_empty());
+    }
+
+    #[test]
+    fn test_query_id_validation() {
+        let queries: RunningQueries<u32> = RunningQueries::default();
+        assert!(queries.start("", "SELECT 1", 1).is_err());
+        assert!(queries.start("a/b", "SELECT 1", 1).is_err());
+        assert!(queries
+            .start(&"x".repeat(MAX_QUERY_ID_LENGTH + 1), "SELECT 1", 1)
+            .is_err());
+        assert!(queries.start("3f2a.query_7", "SELECT 1", 1).is_ok());
+    }
+}
@@ -0,0 +1,176 @@
+//! Console sessions on dedicated connections
+//!
+//! A console session keeps one connection out of the pool for its whole
+//! lifetime, so statements like `BEGIN`, `SET` or temporary tables affect only
+//! the session that issued them. The connection is detached from the pool:
+//! closing a session (or letting it expire) drops the connection, which ends
+//! any transaction left open instead of handing it to the next request.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::database::traits::DatabaseError;
+
+/// Time after which an unused session is closed
+pub const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Maximum number of sessions open at once (each one holds a connection)
+pub const MAX_OPEN_SESSIONS: usize = 8;
+
+/// Open sessions by identifier
+pub struct Sessions<C> {
+    sessions: Mutex<HashMap<String, Session<C>>>,
+    next_id: AtomicU64,
+}
+
+struct Session<C> {
+    /// The connection, or `None` while a statement runs on it
+    connection: Option<C>,
+    last_used: Instant,
+}
+
+impl<C> Default for Sessions<C> {
+    fn default() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<C> Sessions<C> {
+    /// Check that another session can be opened
+    pub fn ensure_capacity(&self) -> Result<(), DatabaseError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        Self::close_idle(&mut sessions);
+        if sessions.len() >= MAX_OPEN_SESSIONS {
+            return Err(DatabaseError::Query(format!(
+                "Too many open sessions (max {})",
+                MAX_OPEN_SESSIONS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Register a new session on `connection`
+    ///
+    /// # Returns
+    ///
+    /// Identifier of the session
+    pub fn open(&self, connection: C) -> String {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or(0);
+        let sequence = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let id = format!("{:x}-{}", started, sequence);
+
+        self.sessions.lock().unwrap().insert(
+            id.clone(),
+            Session {
+                connection: Some(connection),
+                last_used: Instant::now(),
+            },
+        );
+        id
+    }
+
+    /// Take the connection of a session to run a statement on it
+    ///
+    /// Hand it back with [`Sessions::release`]. Fails if the session is unknown
+    /// or another statement is still running on it.
+    pub fn acquire(&self, id: &str) -> Result<C, DatabaseError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        Self::close_idle(&mut sessions);
+
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| DatabaseError::SessionNotFound(id.to_string()))?;
+        session.last_used = Instant::now();
+        session.connection.take().ok_or_else(|| {
+            DatabaseError::Query(format!("Session {} is busy with another statement", id))
+        })
+    }
+
+    /// Hand back a connection taken with [`Sessions::acquire`]
+    ///
+    /// The connection is dropped if the session was closed in the meantime.
+    pub fn release(&self, id: &str, connection: C) {
+        if let Some(session) = self.sessions.lock().unwrap().get_mut(id) {
+            session.connection = Some(connection);
+            session.last_used = Instant::now();
+        }
+    }
+
+    /// Remove a session
+    ///
+    /// # Returns
+    ///
+    /// The connection, unless a statement is still running on it (it is then
+    /// dropped when the statement finishes)
+    pub fn close(&self, id: &str) -> Result<Option<C>, DatabaseError> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(id)
+            .map(|session| session.connection)
+            .ok_or_else(|| DatabaseError::SessionNotFound(id.to_string()))
+    }
+
+    /// Remove all sessions (e.g. before the database is replaced)
+    pub fn clear(&self) {
+        self.sessions.lock().unwrap().clear();
+    }
+
+    /// Drop sessions that have not been used within the idle timeout
+    ///
+    /// Sessions running a statement are kept.
+    fn close_idle(sessions: &mut HashMap<String, Session<C>>) {
+        sessions.retain(|_, session| {
+            session.connection.is_none() || session.last_used.elapsed() < SESSION_IDLE_TIMEOUT
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_lifecycle() {
+        let sessions: Sessions<&str> = Sessions::default();
+        let first = sessions.open("first connection");
+        let second = sessions.open("second connection");
+        assert_ne!(first, second);
+
+        let connection = sessions.acquire(&first).unwrap();
+        assert_eq!(connection, "first connection");
+        assert!(sessions.acquire(&first).is_err());
+        assert_eq!(sessions.acquire(&second).unwrap(), "second connection");
+
+        sessions.release(&first, connection);
+        assert_eq!(sessions.acquire(&first).unwrap(), "first connection");
+
+        assert!(sessions.close(&second).unwrap().is_none());
+        assert!(matches!(
+            sessions.acquire(&second),
+            Err(DatabaseError::SessionNotFound(_))
+        ));
+        assert!(sessions.close(&second).is_err());
+    }
+
+    #[test]
+    fn test_session_capacity() {
+        let sessions: Sessions<u8> = Sessions::default();
+        for connection in 0..MAX_OPEN_SESSIONS {
+            assert!(sessions.ensure_capacity().is_ok());
+            sessions.open(connection as u8);
+        }
+        assert!(sessions.ensure_capacity().is_err());
+
+        sessions.clear();
+        assert!(sessions.ensure_capacity().is_ok());
+    }
+}
@@ -1,19 +1,59 @@
 //! SQLite database provider implementation
 
-use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::big_integers::BigIntegerFormat;
+use crate::checksum::{checksum_expressions, table_checksum};
+use crate::classify::classify;
+use crate::column_stats::{self, ColumnStats};
+use crate::database::attachments::{self, Attachments};
+use crate::database::limits::{ResultLimits, TABLE_COUNT_CONCURRENCY};
+use crate::database::running::RunningQueries;
+use crate::database::sessions::{Sessions, SESSION_IDLE_TIMEOUT};
+use crate::database::streaming;
+use crate::database::timeouts::Timeouts;
+use crate::database::traits::{DatabaseError, DatabaseProvider, QueryStream};
+use crate::duplicates::{self, DuplicatesResponse};
+use crate::explain;
+use crate::filters;
+use crate::fixtures::validate_table_rows;
+use crate::histogram::{self, Histogram};
+use crate::keyset::{self, RowCursor};
+use crate::named_snapshots::validate_snapshot_name;
+use crate::references::{cascade_delete_sql, Reference};
+use crate::row_key::{primary_key_columns, validate_columns, validate_new_rows};
+use crate::runtime;
+use crate::sample::{SampleMethod, SampleResponse};
 use crate::schema::{
-    ColumnInfo, CountResponse, ForeignKey, IndexInfo, QueryResult, RowQuery, RowsResponse,
-    SortOrder, TableInfo, TableSchema,
+    ActiveStatement, ActivityResponse, AttachedDatabase, Capabilities, ColumnInfo, CountAccuracy,
+    CountResponse, DeletedRows, ExplainResponse, FailedStatement, FixtureTable, ForeignKey,
+    IndexInfo, InsertedRows, JournalInfo, LoadedFixture, MaintenanceOperation, MaintenanceResult,
+    NamedSnapshot, QueryResult, RowAddress, RowQuery, RowsResponse, ScriptResult, ScriptStatement,
+    SeedStatus, SessionResponse, SnapshotRowDifferences, TableChecksum, TableInfo, TableSchema,
+    WalCheckpoint,
 };
+use crate::search;
+use crate::sql::{self, Dialect};
 use async_trait::async_trait;
+use futures_util::{StreamExt, TryStreamExt};
 use serde_json::Value;
-use sqlx::sqlite::SqliteRow;
-use sqlx::{Column, Row, SqlitePool, TypeInfo, ValueRef};
-use std::time::Instant;
+use sqlx::pool::PoolConnection;
+use sqlx::query::Query;
+use sqlx::sqlite::{SqliteArguments, SqliteConnection, SqliteRow};
+use sqlx::{Column, Connection, Executor, Row, Sqlite, SqlitePool, TypeInfo, ValueRef};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 /// SQLite database provider
 pub struct SqliteProvider {
     pool: SqlitePool,
+    sessions: Sessions<SqliteConnection>,
+    /// Interrupt flags of running cancellable queries
+    running: RunningQueries<Arc<AtomicBool>>,
+    timeouts: Timeouts,
+    limits: ResultLimits,
+    big_integers: BigIntegerFormat,
+    attachments: Attachments,
 }
 
 impl SqliteProvider {
@@ -23,7 +63,50 @@ impl SqliteProvider {
     ///
     /// * `pool` - SQLite connection pool
     pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            sessions: Sessions::default(),
+            running: RunningQueries::default(),
+            timeouts: Timeouts::default(),
+            limits: ResultLimits::default(),
+            big_integers: BigIntegerFormat::default(),
+            attachments: Attachments::default(),
+        }
+    }
+
+    /// Connection pool of the database
+    pub(crate) fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Set the connection acquire and statement timeouts for raw SQL
+    ///
+    /// # Arguments
+    ///
+    /// * `timeouts` - Time to wait for a pool connection and time a statement may run
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Set the maximum numbers of rows returned per page and per raw query
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - Rows in a page of table rows and in a raw query result
+    pub fn with_result_limits(mut self, limits: ResultLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set how integers beyond JavaScript's safe range are sent as JSON
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - Numbers as they are, or strings outside the safe range
+    pub fn with_big_integer_format(mut self, format: BigIntegerFormat) -> Self {
+        self.big_integers = format;
+        self
     }
 
     /// Quote an identifier (table or column name) to prevent SQL injection
@@ -31,18 +114,202 @@ impl SqliteProvider {
     /// SQLite uses double quotes for identifiers. This function escapes any
     /// double quotes in the identifier by doubling them.
     fn quote_identifier(identifier: &str) -> String {
-        format!("\"{}\"", identifier.replace('"', "\"\""))
+        sql::quote_identifier(identifier)
+    }
+
+    /// Exact name of the table or view a requested name refers to
+    ///
+    /// SQLite itself ignores case in names, but schema lookups and responses
+    /// use the name as it was created (see [`sql::resolve_table_name`]).
+    /// Names of tables in attached files keep their `alias.` in front.
+    async fn resolve_table(&self, table: &str) -> Result<String, DatabaseError> {
+        let (alias, name) = self.attachments.split(table);
+        let query = format!(
+            "SELECT name FROM {}sqlite_master \
+             WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'",
+            attachments::schema_prefix(alias.as_deref())
+        );
+        let mut connection = self.connection().await?;
+        let names: Vec<String> = sqlx::query_scalar(&query)
+            .fetch_all(&mut *connection)
+            .await?;
+
+        match sql::resolve_table_name(name, &names) {
+            Ok(Some(name)) => Ok(attachments::qualify(alias.as_deref(), name)),
+            Ok(None) => Err(DatabaseError::TableNotFound(table.to_string())),
+            Err(candidates) => Err(DatabaseError::AmbiguousTable(
+                table.to_string(),
+                candidates
+                    .iter()
+                    .map(|candidate| attachments::qualify(alias.as_deref(), candidate))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )),
+        }
+    }
+
+    /// Quote a table name, which may start with the alias of an attached file
+    fn quote_table(&self, table: &str) -> String {
+        self.attachments.quote_table(table)
+    }
+
+    /// Take a connection from the pool with the attached files in place
+    async fn connection(&self) -> Result<PoolConnection<Sqlite>, DatabaseError> {
+        let mut connection = self.pool.acquire().await?;
+        self.sync_attachments(&mut connection).await?;
+        Ok(connection)
+    }
+
+    /// Size in bytes of the main database file
+    async fn database_size(connection: &mut SqliteConnection) -> Result<u64, DatabaseError> {
+        let size: i64 = sqlx::query_scalar(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+        Ok(size as u64)
+    }
+
+    /// Row count estimates by table from the `sqlite_stat1` of `schema_prefix`
+    ///
+    /// `sqlite_stat1` only exists after `ANALYZE`; the first number of each
+    /// `stat` is the table's row count at that time.
+    async fn row_estimates(
+        connection: &mut SqliteConnection,
+        schema_prefix: &str,
+    ) -> std::collections::HashMap<String, u64> {
+        let query = format!(
+            "SELECT tbl, MAX(CAST(stat AS INTEGER)) FROM {}sqlite_stat1 GROUP BY tbl",
+            schema_prefix
+        );
+        sqlx::query_as::<_, (String, i64)>(&query)
+            .fetch_all(&mut *connection)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(table, count)| (table, count.max(0) as u64))
+            .collect()
+    }
+
+    /// Listed table with its estimated row count, or counted if it was never analyzed
+    ///
+    /// Counts take their own connection, so several tables can be counted at once.
+    async fn listed_table(&self, name: String, estimate: Option<u64>) -> TableInfo {
+        if estimate.is_some() {
+            return TableInfo::estimated(name, estimate);
+        }
+
+        let count_query = format!("SELECT COUNT(*) FROM {}", self.quote_table(&name));
+        let row_count = match self.connection().await {
+            Ok(mut connection) => sqlx::query_scalar(&count_query)
+                .fetch_one(&mut *connection)
+                .await
+                .ok()
+                .map(|count: i64| count as u64),
+            Err(_) => None,
+        };
+        TableInfo::counted(name, row_count)
+    }
+
+    /// Checkpoint the write-ahead log of the main database, truncating the `-wal` file
+    ///
+    /// Readers still using the log keep the checkpoint from completing; it is
+    /// then reported as busy.
+    async fn checkpoint_wal(
+        &self,
+        connection: &mut SqliteConnection,
+    ) -> Result<WalCheckpoint, DatabaseError> {
+        let wal_file = self.main_file().await?.map(|file| format!("{}-wal", file));
+        let wal_size = || wal_file.as_deref().and_then(file_size);
+
+        let wal_size_before = wal_size();
+        let (busy, log_frames, checkpointed_frames): (i64, i64, i64) =
+            sqlx::query_as("PRAGMA main.wal_checkpoint(TRUNCATE)")
+                .fetch_one(&mut *connection)
+                .await?;
+
+        // Both frame counts are -1 if the database is not in WAL mode
+        Ok(WalCheckpoint {
+            busy: busy != 0,
+            log_frames: u64::try_from(log_frames).ok(),
+            checkpointed_frames: u64::try_from(checkpointed_frames).ok(),
+            wal_size_before,
+            wal_size_after: wal_size(),
+        })
+    }
+
+    /// Attach and detach files until `connection` matches the attachment list
+    ///
+    /// Attachments belong to a single connection, so every connection taken
+    /// from the pool is checked before use.
+    async fn sync_attachments(
+        &self,
+        connection: &mut SqliteConnection,
+    ) -> Result<(), DatabaseError> {
+        if self.attachments.is_unused() {
+            return Ok(());
+        }
+
+        let wanted = self.attachments.list();
+        let mut present = Vec::new();
+        for row in sqlx::query("PRAGMA database_list")
+            .fetch_all(&mut *connection)
+            .await?
+        {
+            let name: String = row.try_get("name")?;
+            let file: String = row.try_get("file")?;
+            present.push((name, file));
+        }
+        let matches = |attachment: &AttachedDatabase, name: &str, file: &str| {
+            attachment.alias.eq_ignore_ascii_case(name) && attachment.path == file
+        };
+
+        for (name, file) in &present {
+            if name == "main"
+                || name == "temp"
+                || wanted
+                    .iter()
+                    .any(|attachment| matches(attachment, name.as_str(), file.as_str()))
+            {
+                continue;
+            }
+            let sql = format!("DETACH DATABASE {}", Self::quote_identifier(name));
+            sqlx::query(&sql).execute(&mut *connection).await?;
+        }
+
+        for attachment in &wanted {
+            if present
+                .iter()
+                .any(|(name, file)| matches(attachment, name.as_str(), file.as_str()))
+            {
+                continue;
+            }
+            let sql = format!(
+                "ATTACH DATABASE ? AS {}",
+                Self::quote_identifier(&attachment.alias)
+            );
+            sqlx::query(&sql)
+                .bind(&attachment.path)
+                .execute(&mut *connection)
+                .await?;
+        }
+
+        Ok(())
     }
 
     /// Convert a SQLite row to a JSON object
     ///
     /// This handles all SQLite data types and converts them to appropriate JSON values.
-    fn row_to_json(row: &SqliteRow) -> Result<Value, DatabaseError> {
+    /// Integers are written in the `big_integers` format.
+    fn row_to_json(
+        row: &SqliteRow,
+        big_integers: BigIntegerFormat,
+    ) -> Result<Value, DatabaseError> {
         let mut map = serde_json::Map::new();
 
         for column in row.columns() {
             let column_name = column.name();
-            let value = Self::extract_column_value(row, column)?;
+            let value = Self::extract_column_value(row, column, big_integers)?;
             map.insert(column_name.to_string(), value);
         }
 
@@ -53,6 +320,7 @@ impl SqliteProvider {
     fn extract_column_value(
         row: &SqliteRow,
         column: &sqlx::sqlite::SqliteColumn,
+        big_integers: BigIntegerFormat,
     ) -> Result<Value, DatabaseError> {
         let column_name = column.name();
         let type_info = column.type_info();
@@ -73,7 +341,7 @@ impl SqliteProvider {
             "INTEGER" | "BIGINT" => {
                 // Try i64 first, which covers most integer cases
                 if let Ok(value) = row.try_get::<i64, _>(column_name) {
-                    return Ok(Value::Number(value.into()));
+                    return Ok(big_integers.to_json(value));
                 }
             }
             "REAL" | "FLOAT" | "DOUBLE" => {
@@ -120,7 +388,7 @@ impl SqliteProvider {
 
         // Fallback: try common types in order
         if let Ok(value) = row.try_get::<i64, _>(column_name) {
-            return Ok(Value::Number(value.into()));
+            return Ok(big_integers.to_json(value));
         }
         if let Ok(value) = row.try_get::<f64, _>(column_name) {
             if let Some(number) = serde_json::Number::from_f64(value) {
@@ -147,78 +415,624 @@ impl SqliteProvider {
     }
 
     /// Build a WHERE clause from filters
-    fn build_where_clause(filters: &std::collections::HashMap<String, String>) -> (String, Vec<String>) {
-        if filters.is_empty() {
-            return (String::new(), Vec::new());
+    fn build_where_clause(
+        filters: &std::collections::HashMap<String, String>,
+        case_insensitive: bool,
+    ) -> (String, Vec<String>) {
+        let (conditions, values) = Self::filter_conditions(filters, case_insensitive);
+        if conditions.is_empty() {
+            return (String::new(), values);
         }
 
+        (format!(" WHERE {}", conditions.join(" AND ")), values)
+    }
+
+    /// Conditions of filters (to be joined with AND) and their bound values
+    ///
+    /// With `case_insensitive` the comparisons use `COLLATE NOCASE`, which
+    /// ignores the case of ASCII letters.
+    fn filter_conditions(
+        filters: &std::collections::HashMap<String, String>,
+        case_insensitive: bool,
+    ) -> (Vec<String>, Vec<String>) {
         let mut conditions = Vec::new();
         let mut values = Vec::new();
+        let collation = if case_insensitive {
+            " COLLATE NOCASE"
+        } else {
+            ""
+        };
 
         for (column, filter_value) in filters {
             let quoted_column = Self::quote_identifier(column);
 
             // Support LIKE patterns with % wildcard
             if filter_value.contains('%') {
-                conditions.push(format!("{} LIKE ?", quoted_column));
+                conditions.push(format!("{} LIKE ?{}", quoted_column, collation));
                 values.push(filter_value.clone());
             } else {
-                conditions.push(format!("{} = ?", quoted_column));
+                conditions.push(format!("{} = ?{}", quoted_column, collation));
                 values.push(filter_value.clone());
             }
         }
 
-        (format!(" WHERE {}", conditions.join(" AND ")), values)
+        (conditions, values)
     }
 
-    /// Build an ORDER BY clause from sort parameters
-    fn build_order_clause(sort_by: Option<&str>, sort_order: Option<SortOrder>) -> String {
-        match (sort_by, sort_order) {
-            (Some(column), Some(order)) => {
-                let quoted_column = Self::quote_identifier(column);
-                let direction = match order {
-                    SortOrder::Ascending => "ASC",
-                    SortOrder::Descending => "DESC",
-                };
-                format!(" ORDER BY {} {}", quoted_column, direction)
+    /// Condition of one of the `filterGroups` of a row query, binding into `values`
+    fn filter_group_condition(
+        group: &std::collections::HashMap<String, String>,
+        schema: &TableSchema,
+        case_insensitive: bool,
+        values: &mut Vec<String>,
+    ) -> String {
+        let (others, booleans) = filters::boolean_filters(group, schema);
+        let (mut conditions, group_values) = Self::filter_conditions(&others, case_insensitive);
+        values.extend(group_values);
+        for boolean in &booleans {
+            conditions.push(boolean.condition(Dialect::Sqlite, "?"));
+            values.push(boolean.bound_value(Dialect::Sqlite));
+        }
+
+        // An empty group matches every row
+        if conditions.is_empty() {
+            return "1 = 1".to_string();
+        }
+        format!("({})", conditions.join(" AND "))
+    }
+
+    /// Build the WHERE clause of a row query: its filters, ranges, filter groups
+    /// and search (`q`)
+    ///
+    /// Filters on boolean columns compare with the stored form of booleans.
+    fn row_conditions(query: &RowQuery, schema: &TableSchema) -> (String, Vec<String>) {
+        let (others, booleans) = filters::boolean_filters(&query.filters, schema);
+        let (mut where_clause, mut values) =
+            Self::build_where_clause(&others, query.case_insensitive);
+
+        let mut conditions = Vec::new();
+        for boolean in &booleans {
+            conditions.push(boolean.condition(Dialect::Sqlite, "?"));
+            values.push(boolean.bound_value(Dialect::Sqlite));
+        }
+        for range in &query.ranges {
+            conditions.push(range.condition(Dialect::Sqlite, "?"));
+            values.push(range.value.clone());
+        }
+        if !query.filter_groups.is_empty() {
+            let groups: Vec<String> = query
+                .filter_groups
+                .iter()
+                .map(|group| {
+                    Self::filter_group_condition(group, schema, query.case_insensitive, &mut values)
+                })
+                .collect();
+            conditions.push(format!("({})", groups.join(" OR ")));
+        }
+        if let Some(text) = query.search() {
+            let (condition, search_values) =
+                search::search_condition(schema, text, Dialect::Sqlite, || "?".to_string());
+            conditions.push(condition);
+            values.extend(search_values);
+        }
+
+        if !conditions.is_empty() {
+            let keyword = if where_clause.is_empty() {
+                "WHERE"
+            } else {
+                "AND"
+            };
+            where_clause.push_str(&format!(" {} {}", keyword, conditions.join(" AND ")));
+        }
+
+        (where_clause, values)
+    }
+
+    /// Bind a JSON value with the closest matching SQLite type
+    ///
+    /// Arrays and objects are stored as their JSON text.
+    fn bind_json_value<'q>(
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+        value: &Value,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        match value {
+            Value::Null => query.bind(None::<String>),
+            Value::Bool(boolean) => query.bind(*boolean),
+            Value::Number(number) => match number.as_i64() {
+                Some(integer) => query.bind(integer),
+                None => query.bind(number.as_f64()),
+            },
+            Value::String(text) => query.bind(text.clone()),
+            other => query.bind(other.to_string()),
+        }
+    }
+
+    /// Path of the main database file, or `None` for an in-memory database
+    async fn main_file(&self) -> Result<Option<String>, DatabaseError> {
+        let rows = sqlx::query("PRAGMA database_list")
+            .fetch_all(&self.pool)
+            .await?;
+
+        for row in rows {
+            let name: String = row.try_get("name")?;
+            let file: String = row.try_get("file")?;
+            if name == "main" && !file.is_empty() {
+                return Ok(Some(file));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Path of the file the seed of this database is kept in (next to the database file)
+    async fn seed_path(&self) -> Result<String, DatabaseError> {
+        match self.main_file().await? {
+            Some(file) => Ok(format!("{}.seed", file)),
+            None => Err(DatabaseError::Unsupported(
+                "Seeds require a file-backed SQLite database".to_string(),
+            )),
+        }
+    }
+
+    /// Directory the named snapshots of this database are kept in (next to the database file)
+    async fn snapshot_directory(&self) -> Result<PathBuf, DatabaseError> {
+        match self.main_file().await? {
+            Some(file) => Ok(PathBuf::from(format!("{}.snapshots", file))),
+            None => Err(DatabaseError::Unsupported(
+                "Named snapshots require a file-backed SQLite database".to_string(),
+            )),
+        }
+    }
+
+    /// Path of the file of an existing named snapshot
+    async fn named_snapshot_path(&self, name: &str) -> Result<String, DatabaseError> {
+        // An invalid name could point outside of the snapshot directory
+        validate_snapshot_name(name)
+            .map_err(|_| DatabaseError::SnapshotNotFound(name.to_string()))?;
+        let path = self
+            .snapshot_directory()
+            .await?
+            .join(format!("{}.db", name));
+        if !path.is_file() {
+            return Err(DatabaseError::SnapshotNotFound(name.to_string()));
+        }
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    /// Describe the named snapshot kept in the file at `path`
+    async fn read_named_snapshot(
+        &self,
+        name: &str,
+        path: &str,
+    ) -> Result<NamedSnapshot, DatabaseError> {
+        let mut connection = self.connection().await?;
+        sqlx::query("ATTACH DATABASE ? AS snapshot")
+            .bind(path)
+            .execute(&mut *connection)
+            .await?;
+        let tables = Self::snapshot_tables(&mut connection).await;
+        sqlx::query("DETACH DATABASE snapshot")
+            .execute(&mut *connection)
+            .await?;
+
+        Ok(NamedSnapshot {
+            name: name.to_string(),
+            tables: tables?,
+            created_at_milliseconds: modified_milliseconds(path)?,
+        })
+    }
+
+    /// Names of the tables in the attached `snapshot`
+    async fn snapshot_tables(
+        connection: &mut SqliteConnection,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let tables = sqlx::query_scalar(
+            "SELECT name FROM snapshot.sqlite_master \
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .fetch_all(&mut *connection)
+        .await?;
+        Ok(tables)
+    }
+
+    /// Quoted column list of `table` in the attached `snapshot`
+    async fn snapshot_columns(
+        connection: &mut SqliteConnection,
+        table: &str,
+    ) -> Result<String, DatabaseError> {
+        let columns: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info(?, 'snapshot') ORDER BY cid")
+                .bind(table)
+                .fetch_all(&mut *connection)
+                .await?;
+        Ok(columns
+            .iter()
+            .map(|column| Self::quote_identifier(column))
+            .collect::<Vec<_>>()
+            .join(", "))
+    }
+
+    /// Replace the rows of every table in the attached `snapshot` with the snapshot's rows
+    ///
+    /// # Returns
+    ///
+    /// Names of the restored tables
+    async fn copy_from_named_snapshot(
+        connection: &mut SqliteConnection,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let tables = Self::snapshot_tables(connection).await?;
+        let mut transaction = connection.begin().await?;
+
+        for table in &tables {
+            let columns = Self::snapshot_columns(&mut transaction, table).await?;
+            let table = Self::quote_identifier(table);
+            sqlx::query(&format!("DELETE FROM main.{}", table))
+                .execute(&mut *transaction)
+                .await?;
+            let sql = format!(
+                "INSERT INTO main.{0} ({1}) SELECT {1} FROM snapshot.{0}",
+                table, columns
+            );
+            sqlx::query(&sql).execute(&mut *transaction).await?;
+        }
+
+        transaction.commit().await?;
+        Ok(tables)
+    }
+
+    /// Rows of `table` that are only in the database, and those only in the attached `snapshot`
+    async fn named_snapshot_rows(
+        connection: &mut SqliteConnection,
+        table: &str,
+        limit: u64,
+        big_integers: BigIntegerFormat,
+    ) -> Result<SnapshotRowDifferences, DatabaseError> {
+        let snapshot_table = Self::snapshot_tables(connection)
+            .await?
+            .into_iter()
+            .find(|name| name.eq_ignore_ascii_case(table))
+            .ok_or_else(|| DatabaseError::TableNotFound(table.to_string()))?;
+        let columns = Self::snapshot_columns(connection, &snapshot_table).await?;
+        let quoted = Self::quote_identifier(&snapshot_table);
+
+        let mut differences = SnapshotRowDifferences {
+            current: Vec::new(),
+            snapshot: Vec::new(),
+            truncated: false,
+        };
+        for (from, except) in [("main", "snapshot"), ("snapshot", "main")] {
+            let sql = format!(
+                "SELECT {1} FROM {2}.{0} EXCEPT SELECT {1} FROM {3}.{0} LIMIT ?",
+                quoted, columns, from, except
+            );
+            let rows = sqlx::query(&sql)
+                .bind((limit + 1) as i64)
+                .fetch_all(&mut *connection)
+                .await?;
+            differences.truncated |= rows.len() as u64 > limit;
+            let rows = rows
+                .iter()
+                .take(limit as usize)
+                .map(|row| Self::row_to_json(row, big_integers))
+                .collect::<Result<Vec<_>, _>>()?;
+            if from == "main" {
+                differences.current = rows;
+            } else {
+                differences.snapshot = rows;
+            }
+        }
+        Ok(differences)
+    }
+
+    /// Replace all tables, views, indexes and triggers with those of the attached `seed`
+    async fn copy_from_seed(connection: &mut SqliteConnection) -> Result<(), DatabaseError> {
+        let mut transaction = connection.begin().await?;
+
+        let existing: Vec<(String, String)> = sqlx::query_as(
+            "SELECT type, name FROM main.sqlite_master \
+             WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'",
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+        for (object_type, name) in existing {
+            let sql = format!(
+                "DROP {} IF EXISTS main.{}",
+                object_type.to_uppercase(),
+                Self::quote_identifier(&name)
+            );
+            sqlx::query(&sql).execute(&mut *transaction).await?;
+        }
+
+        // Tables first, so indexes, views and triggers find what they refer to
+        let definitions: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT type, name, sql FROM seed.sqlite_master \
+             WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%' \
+             ORDER BY CASE type WHEN 'table' THEN 0 WHEN 'index' THEN 1 WHEN 'view' THEN 2 ELSE 3 END",
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+        for (_, _, sql) in &definitions {
+            sqlx::query(sql).execute(&mut *transaction).await?;
+        }
+
+        for (object_type, name, _) in &definitions {
+            if object_type != "table" {
+                continue;
+            }
+            let sql = format!(
+                "INSERT INTO main.{0} SELECT * FROM seed.{0}",
+                Self::quote_identifier(name)
+            );
+            sqlx::query(&sql).execute(&mut *transaction).await?;
+        }
+
+        // Keep AUTOINCREMENT counters in line with the copied rows
+        let has_sequence: Option<i64> =
+            sqlx::query_scalar("SELECT 1 FROM seed.sqlite_master WHERE name = 'sqlite_sequence'")
+                .fetch_optional(&mut *transaction)
+                .await?;
+        if has_sequence.is_some() {
+            sqlx::query("DELETE FROM main.sqlite_sequence")
+                .execute(&mut *transaction)
+                .await?;
+            sqlx::query("INSERT INTO main.sqlite_sequence SELECT * FROM seed.sqlite_sequence")
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Insert `rows` into `table` (a name as resolved by `get_table_schema`)
+    async fn insert_into(
+        &self,
+        connection: &mut SqliteConnection,
+        table: &str,
+        rows: &[serde_json::Map<String, Value>],
+    ) -> Result<(), DatabaseError> {
+        for (index, row) in rows.iter().enumerate() {
+            let sql = if row.is_empty() {
+                format!("INSERT INTO {} DEFAULT VALUES", self.quote_table(table))
+            } else {
+                let columns: Vec<String> = row
+                    .keys()
+                    .map(|column| Self::quote_identifier(column))
+                    .collect();
+                format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    self.quote_table(table),
+                    columns.join(", "),
+                    vec!["?"; row.len()].join(", ")
+                )
+            };
+
+            let mut query = sqlx::query(&sql);
+            for value in row.values() {
+                query = Self::bind_json_value(query, value);
+            }
+            query.execute(&mut *connection).await.map_err(|error| {
+                DatabaseError::Query(format!(
+                    "Failed to insert row {} into '{}': {}",
+                    index, table, error
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Columns selected when reading whole rows
+    ///
+    /// Tables addressed by rowid get it as an extra first column, so rows can
+    /// be opened and edited.
+    fn select_list(schema: &TableSchema) -> &'static str {
+        match schema.row_address {
+            RowAddress::Rowid => "rowid, *",
+            _ => "*",
+        }
+    }
+
+    /// Columns selected when reading only `projection` of rows (whole rows without one)
+    ///
+    /// The rowid stays in front of the projected columns.
+    fn projected_select_list(schema: &TableSchema, projection: Option<&[String]>) -> String {
+        let Some(columns) = projection else {
+            return Self::select_list(schema).to_string();
+        };
+        let identifier = match schema.row_address {
+            RowAddress::Rowid => Some("rowid".to_string()),
+            _ => None,
+        };
+        identifier
+            .into_iter()
+            .chain(columns.iter().map(|column| Self::quote_identifier(column)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Execute a raw SQL statement on the pool or on a session's connection
+    async fn run_query<'c, E>(
+        executor: E,
+        sql: &str,
+        params: &[Value],
+        timeouts: Timeouts,
+        limits: ResultLimits,
+        big_integers: BigIntegerFormat,
+    ) -> Result<QueryResult, DatabaseError>
+    where
+        E: sqlx::Executor<'c, Database = Sqlite>,
+    {
+        let start_time = Instant::now();
+        let query = params.iter().fold(sqlx::query(sql), Self::bind_json_value);
+
+        // Fetch rows for statements that return them (queries, PRAGMA,
+        // RETURNING), count affected rows for the others
+        if classify(sql, Dialect::Sqlite).returns_rows {
+            // For SELECT queries, fetch all rows
+            let result = timeouts.statement(query.fetch_all(executor)).await;
+
+            let execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+
+            match result {
+                Ok(Ok(rows)) => {
+                    // Check row limit
+                    if rows.len() as u64 > limits.max_result_rows {
+                        return Err(DatabaseError::TooManyRows(limits.max_result_rows));
+                    }
+
+                    // Extract columns from first row or return empty result
+                    let columns = if let Some(first_row) = rows.first() {
+                        first_row
+                            .columns()
+                            .iter()
+                            .map(|column| column.name().to_string())
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+
+                    // Convert rows to JSON
+                    let mut json_rows = Vec::new();
+                    for row in &rows {
+                        json_rows.push(Self::row_to_json(row, big_integers)?);
+                    }
+
+                    Ok(QueryResult {
+                        columns,
+                        rows: json_rows,
+                        affected_rows: rows.len() as u64,
+                        execution_time_milliseconds,
+                        error: None,
+                        timeout: None,
+                    })
+                }
+                Ok(Err(error)) => {
+                    // SQL execution error
+                    Ok(QueryResult {
+                        columns: Vec::new(),
+                        rows: Vec::new(),
+                        affected_rows: 0,
+                        execution_time_milliseconds,
+                        error: Some(error.to_string()),
+                        timeout: None,
+                    })
+                }
+                Err(error) => {
+                    // Timeout error
+                    Err(error)
+                }
+            }
+        } else {
+            // For INSERT/UPDATE/DELETE, use execute() to get affected rows
+            let result = timeouts.statement(query.execute(executor)).await;
+
+            let execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+
+            match result {
+                Ok(Ok(query_result)) => Ok(QueryResult {
+                    columns: Vec::new(),
+                    rows: Vec::new(),
+                    affected_rows: query_result.rows_affected(),
+                    execution_time_milliseconds,
+                    error: None,
+                    timeout: None,
+                }),
+                Ok(Err(error)) => Ok(QueryResult {
+                    columns: Vec::new(),
+                    rows: Vec::new(),
+                    affected_rows: 0,
+                    execution_time_milliseconds,
+                    error: Some(error.to_string()),
+                    timeout: None,
+                }),
+                Err(error) => Err(error),
             }
-            _ => String::new(),
         }
     }
 }
 
 #[async_trait]
 impl DatabaseProvider for SqliteProvider {
+    fn dialect(&self) -> Dialect {
+        Dialect::Sqlite
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            // RETURNING needs SQLite 3.35+, which sqlx bundles
+            supports_returning: true,
+            supports_sessions: true,
+            supports_cancellation: true,
+            // SQLITE_MAX_VARIABLE_NUMBER default since SQLite 3.32
+            max_placeholder_count: Some(32766),
+            ..Capabilities::default()
+        }
+    }
+
+    fn result_limits(&self) -> ResultLimits {
+        self.limits
+    }
+
     async fn list_tables(&self) -> Result<Vec<TableInfo>, DatabaseError> {
         let query = "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name";
 
-        let rows = sqlx::query(query)
-            .fetch_all(&self.pool)
-            .await?;
+        let mut connection = self.connection().await?;
+        let rows = sqlx::query(query).fetch_all(&mut *connection).await?;
 
-        let mut tables = Vec::new();
+        // Row counts are estimated from ANALYZE statistics where there are any
+        let estimates = Self::row_estimates(&mut connection, "").await;
+        let mut listed = Vec::new();
         for row in rows {
             let name: String = row.try_get("name")?;
+            let estimate = estimates.get(&name).copied();
+            listed.push((name, estimate));
+        }
 
-            // Optionally get row count for each table
-            let count_query = format!("SELECT COUNT(*) as count FROM {}", Self::quote_identifier(&name));
-            let row_count: Option<u64> = sqlx::query_scalar(&count_query)
-                .fetch_one(&self.pool)
-                .await
-                .ok()
-                .map(|count: i64| count as u64);
+        // Tables of attached files follow those of the main database
+        for attachment in self.attachments.list() {
+            let query = format!(
+                "SELECT name FROM {}sqlite_master \
+                 WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+                attachments::schema_prefix(Some(&attachment.alias))
+            );
+            let names: Vec<String> = sqlx::query_scalar(&query)
+                .fetch_all(&mut *connection)
+                .await?;
 
-            tables.push(TableInfo { name, row_count });
+            let prefix = attachments::schema_prefix(Some(&attachment.alias));
+            let estimates = Self::row_estimates(&mut connection, &prefix).await;
+            for name in names {
+                let estimate = estimates.get(&name).copied();
+                let name = attachments::qualify(Some(&attachment.alias), &name);
+                listed.push((name, estimate));
+            }
         }
 
+        // Tables without statistics are counted a few at a time, each on its
+        // own connection, instead of one after another
+        drop(connection);
+        let tables = futures_util::stream::iter(listed)
+            .map(|(name, estimate)| self.listed_table(name, estimate))
+            .buffered(TABLE_COUNT_CONCURRENCY)
+            .collect()
+            .await;
         Ok(tables)
     }
 
     async fn get_table_schema(&self, table: &str) -> Result<TableSchema, DatabaseError> {
+        let table = &self.resolve_table(table).await?;
+        let (alias, name) = self.attachments.split(table);
+        // PRAGMAs and sqlite_master of an attached file are reached through its alias
+        let prefix = attachments::schema_prefix(alias.as_deref());
+        let mut connection = self.connection().await?;
+
         // Get column information using PRAGMA table_info
-        let table_info_query = format!("PRAGMA table_info({})", Self::quote_identifier(table));
+        let table_info_query = format!(
+            "PRAGMA {}table_info({})",
+            prefix,
+            Self::quote_identifier(name)
+        );
         let column_rows = sqlx::query(&table_info_query)
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *connection)
             .await?;
 
         if column_rows.is_empty() {
@@ -234,7 +1048,10 @@ impl DatabaseProvider for SqliteProvider {
             let name: String = row.try_get("name")?;
             let data_type: String = row.try_get("type")?;
             let not_null: i32 = row.try_get("notnull")?;
-            let default_value: Option<String> = row.try_get("dflt_value").ok();
+            // NULL when the column has no default
+            let default_value = row
+                .try_get::<Option<String>, _>("dflt_value")?
+                .filter(|default_value| !default_value.is_empty());
             let primary_key: i32 = row.try_get("pk")?;
 
             let is_primary_key = primary_key > 0;
@@ -259,17 +1076,46 @@ impl DatabaseProvider for SqliteProvider {
             Some(primary_key_columns.into_iter().map(|(_, name)| name).collect())
         };
 
-        // Get foreign key information using PRAGMA foreign_key_list
-        let foreign_key_query = format!("PRAGMA foreign_key_list({})", Self::quote_identifier(table));
+        // Tables without a primary key are addressed by rowid, unless they are
+        // views (which have none) or a column named rowid hides it
+        let row_address = if primary_key.is_some() {
+            RowAddress::PrimaryKey
+        } else {
+            let is_table_query = format!(
+                "SELECT 1 FROM {}sqlite_master WHERE type = 'table' AND name = ?",
+                prefix
+            );
+            let is_table: Option<i64> = sqlx::query_scalar(&is_table_query)
+                .bind(name)
+                .fetch_optional(&mut *connection)
+                .await?;
+            let rowid_hidden = columns
+                .iter()
+                .any(|column| column.name.eq_ignore_ascii_case("rowid"));
+            if is_table.is_some() && !rowid_hidden {
+                RowAddress::Rowid
+            } else {
+                RowAddress::None
+            }
+        };
+
+        // Get foreign key information using PRAGMA foreign_key_list
+        let foreign_key_query = format!(
+            "PRAGMA {}foreign_key_list({})",
+            prefix,
+            Self::quote_identifier(name)
+        );
         let foreign_key_rows = sqlx::query(&foreign_key_query)
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *connection)
             .await?;
 
         let mut foreign_keys = Vec::new();
         for row in foreign_key_rows {
             // PRAGMA foreign_key_list returns: id, seq, table, from, to, on_update, on_delete, match
             let column: String = row.try_get("from")?;
+            // Foreign keys never leave their file, so the alias applies to the referenced table
             let references_table: String = row.try_get("table")?;
+            let references_table = attachments::qualify(alias.as_deref(), &references_table);
             let references_column: String = row.try_get("to")?;
 
             foreign_keys.push(ForeignKey {
@@ -280,9 +1126,13 @@ impl DatabaseProvider for SqliteProvider {
         }
 
         // Get index information using PRAGMA index_list
-        let index_list_query = format!("PRAGMA index_list({})", Self::quote_identifier(table));
+        let index_list_query = format!(
+            "PRAGMA {}index_list({})",
+            prefix,
+            Self::quote_identifier(name)
+        );
         let index_rows = sqlx::query(&index_list_query)
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *connection)
             .await?;
 
         let mut indexes = Vec::new();
@@ -292,9 +1142,13 @@ impl DatabaseProvider for SqliteProvider {
             let unique: i32 = row.try_get("unique")?;
 
             // Get columns in this index using PRAGMA index_info
-            let index_info_query = format!("PRAGMA index_info({})", Self::quote_identifier(&index_name));
+            let index_info_query = format!(
+                "PRAGMA {}index_info({})",
+                prefix,
+                Self::quote_identifier(&index_name)
+            );
             let index_column_rows = sqlx::query(&index_info_query)
-                .fetch_all(&self.pool)
+                .fetch_all(&mut *connection)
                 .await?;
 
             let mut index_columns = Vec::new();
@@ -319,54 +1173,82 @@ impl DatabaseProvider for SqliteProvider {
             primary_key,
             foreign_keys,
             indexes,
+            row_address,
         })
     }
 
     async fn get_rows(&self, table: &str, query: RowQuery) -> Result<RowsResponse, DatabaseError> {
-        // Verify the table exists first
-        let table_exists: Option<i64> = sqlx::query_scalar(
-            "SELECT 1 FROM sqlite_master WHERE type='table' AND name = ? AND name NOT LIKE 'sqlite_%'"
-        )
-        .bind(table)
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if table_exists.is_none() {
-            return Err(DatabaseError::TableNotFound(table.to_string()));
+        // Snapshots are never handed out, so any identifier is unknown
+        if let Some(snapshot) = &query.snapshot {
+            return Err(DatabaseError::SnapshotNotFound(snapshot.clone()));
         }
 
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        let mut connection = self.connection().await?;
+
         // Enforce maximum limit
-        const MAX_LIMIT: u64 = 500;
-        let limit = query.limit.min(MAX_LIMIT);
+        let limit = query.limit.min(self.limits.max_page_size);
 
-        // Build WHERE clause from filters
-        let (where_clause, filter_values) = Self::build_where_clause(&query.filters);
+        // Fetch only the requested columns
+        let projection = query.projection();
+        if let Some(columns) = &projection {
+            validate_columns(&schema, columns)?;
+        }
 
-        // Build ORDER BY clause
-        let order_clause = Self::build_order_clause(
-            query.sort_by.as_deref(),
-            query.sort_order,
-        );
+        // Build WHERE clause from filters and search
+        let (where_clause, filter_values) = Self::row_conditions(&query, &schema);
 
-        // Get total count with filters applied
-        let count_query = format!(
-            "SELECT COUNT(*) FROM {}{}",
-            Self::quote_identifier(table),
-            where_clause
-        );
+        // Build ORDER BY clause, ending with the row key so pages can continue after a row
+        let key_columns = keyset::key_columns(&schema);
+        let order_clause = keyset::order_clause(&query, key_columns.as_deref(), Dialect::Sqlite);
 
-        let mut count_sql_query = sqlx::query_scalar::<_, i64>(&count_query);
-        for value in &filter_values {
-            count_sql_query = count_sql_query.bind(value);
-        }
-        let total: i64 = count_sql_query.fetch_one(&self.pool).await?;
-        let total = total as u64;
+        // Get total count with filters applied, unless skipped
+        let total = if query.count {
+            let count_query = format!(
+                "SELECT COUNT(*) FROM {}{}",
+                self.quote_table(table),
+                where_clause
+            );
+
+            let mut count_sql_query = sqlx::query_scalar::<_, i64>(&count_query);
+            for value in &filter_values {
+                count_sql_query = count_sql_query.bind(value);
+            }
+            let total: i64 = count_sql_query.fetch_one(&mut *connection).await?;
+            Some(total as u64)
+        } else {
+            None
+        };
+
+        // Continue after the cursor's row instead of skipping rows
+        let (offset, cursor_clause, cursor_values) = match &query.cursor {
+            Some(cursor) => {
+                let cursor = RowCursor::decode(cursor, &query)?;
+                let (condition, values) = keyset::keyset_condition(
+                    &cursor,
+                    &keyset::required_key_columns(&schema)?,
+                    keyset::sort_nullable(&schema, &query),
+                    Dialect::Sqlite,
+                    |_| "?".to_string(),
+                );
+                let keyword = if where_clause.is_empty() {
+                    "WHERE"
+                } else {
+                    "AND"
+                };
+                (cursor.offset, format!(" {} {}", keyword, condition), values)
+            }
+            None => (query.offset, String::new(), Vec::new()),
+        };
 
         // Build the main query
         let select_query = format!(
-            "SELECT * FROM {}{}{} LIMIT ? OFFSET ?",
-            Self::quote_identifier(table),
+            "SELECT {} FROM {}{}{}{} LIMIT ? OFFSET ?",
+            Self::projected_select_list(&schema, projection.as_deref()),
+            self.quote_table(table),
             where_clause,
+            cursor_clause,
             order_clause
         );
 
@@ -375,9 +1257,12 @@ impl DatabaseProvider for SqliteProvider {
         for value in &filter_values {
             sql_query = sql_query.bind(value);
         }
-        sql_query = sql_query.bind(limit as i64).bind(query.offset as i64);
+        sql_query = cursor_values.iter().fold(sql_query, Self::bind_json_value);
+        let skip = if query.cursor.is_some() { 0 } else { offset };
+        // One row past the page tells whether there are more
+        sql_query = sql_query.bind((limit + 1) as i64).bind(skip as i64);
 
-        let rows = sql_query.fetch_all(&self.pool).await?;
+        let rows = sql_query.fetch_all(&mut *connection).await?;
 
         // Extract column names from the first row (if any) or from schema
         let columns = if let Some(first_row) = rows.first() {
@@ -387,49 +1272,60 @@ impl DatabaseProvider for SqliteProvider {
                 .map(|col| col.name().to_string())
                 .collect()
         } else {
-            // If no rows, get columns from schema
-            let schema = self.get_table_schema(table).await?;
-            schema.columns.into_iter().map(|col| col.name).collect()
+            // If no rows, get columns from the projection or schema
+            let selected = projection
+                .unwrap_or_else(|| schema.columns.iter().map(|col| col.name.clone()).collect());
+            schema
+                .row_address
+                .identifier_column()
+                .map(str::to_string)
+                .into_iter()
+                .chain(selected)
+                .collect()
         };
 
         // Convert rows to JSON
         let mut json_rows = Vec::new();
-        for row in &rows {
-            json_rows.push(Self::row_to_json(row)?);
+        for row in rows.iter().take(limit as usize) {
+            json_rows.push(Self::row_to_json(row, self.big_integers)?);
         }
 
-        let has_more = query.offset + (json_rows.len() as u64) < total;
+        let has_more = rows.len() as u64 > limit;
+        let next_cursor = key_columns
+            .filter(|_| has_more)
+            .and_then(|key_columns| RowCursor::after(&json_rows, offset, &query, &key_columns))
+            .map(|cursor| cursor.encode());
 
         Ok(RowsResponse {
             rows: json_rows,
             columns,
             total,
-            offset: query.offset,
+            total_accuracy: total.map(|_| CountAccuracy::exact()),
+            offset,
             limit,
             has_more,
+            next_cursor,
+            labels: None,
         })
     }
 
     async fn count_rows(&self, table: &str, query: &RowQuery) -> Result<CountResponse, DatabaseError> {
-        // Verify the table exists first
-        let table_exists: Option<i64> = sqlx::query_scalar(
-            "SELECT 1 FROM sqlite_master WHERE type='table' AND name = ? AND name NOT LIKE 'sqlite_%'"
-        )
-        .bind(table)
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if table_exists.is_none() {
-            return Err(DatabaseError::TableNotFound(table.to_string()));
+        // Snapshots are never handed out, so any identifier is unknown
+        if let Some(snapshot) = &query.snapshot {
+            return Err(DatabaseError::SnapshotNotFound(snapshot.clone()));
         }
 
-        // Build WHERE clause from filters
-        let (where_clause, filter_values) = Self::build_where_clause(&query.filters);
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        let mut connection = self.connection().await?;
+
+        // Build WHERE clause from filters and search
+        let (where_clause, filter_values) = Self::row_conditions(query, &schema);
 
         // Build count query
         let count_query = format!(
             "SELECT COUNT(*) FROM {}{}",
-            Self::quote_identifier(table),
+            self.quote_table(table),
             where_clause
         );
 
@@ -438,122 +1334,1135 @@ impl DatabaseProvider for SqliteProvider {
             sql_query = sql_query.bind(value);
         }
 
-        let count: i64 = sql_query.fetch_one(&self.pool).await?;
+        let count: i64 = sql_query.fetch_one(&mut *connection).await?;
+
+        Ok(CountResponse::exact(count as u64))
+    }
+
+    async fn sample_rows(&self, table: &str, size: u64) -> Result<SampleResponse, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let size = size.min(self.limits.max_page_size);
+        // SQLite has no TABLESAMPLE, so all rows are shuffled
+        let sql = format!(
+            "SELECT {} FROM {} ORDER BY random() LIMIT {}",
+            Self::select_list(&schema),
+            self.quote_table(&schema.name),
+            size
+        );
+
+        let mut connection = self.connection().await?;
+        let rows = sqlx::query(&sql).fetch_all(&mut *connection).await?;
 
-        Ok(CountResponse {
-            count: count as u64,
+        Ok(SampleResponse {
+            rows: rows
+                .iter()
+                .map(|row| Self::row_to_json(row, self.big_integers))
+                .collect::<Result<Vec<_>, _>>()?,
+            columns: schema
+                .row_address
+                .identifier_column()
+                .map(str::to_string)
+                .into_iter()
+                .chain(schema.columns.iter().map(|column| column.name.clone()))
+                .collect(),
+            size,
+            method: SampleMethod::Random,
         })
     }
 
-    async fn execute_query(&self, sql: &str) -> Result<QueryResult, DatabaseError> {
-        let start_time = Instant::now();
+    async fn column_stats(&self, table: &str, column: &str) -> Result<ColumnStats, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let column = schema
+            .columns
+            .iter()
+            .find(|info| info.name == column)
+            .ok_or_else(|| DatabaseError::InvalidColumn(column.to_string()))?;
+
+        // SQLite keeps no distinct estimates, so they are always counted
+        let sql = format!(
+            "SELECT {} FROM {}",
+            column_stats::stats_expressions(column, Dialect::Sqlite, true).join(", "),
+            self.quote_table(&schema.name)
+        );
+        let mut connection = self.connection().await?;
+        let row = sqlx::query(&sql).fetch_one(&mut *connection).await?;
+        let row_count = row.try_get::<i64, _>(0)? as u64;
+        let non_null_count = row.try_get::<i64, _>(1)? as u64;
+
+        Ok(ColumnStats {
+            table: schema.name.clone(),
+            column: column.name.clone(),
+            data_type: column.data_type.clone(),
+            row_count,
+            null_count: row_count - non_null_count,
+            distinct_count: row.try_get::<Option<i64>, _>(5)?.map(|count| count as u64),
+            distinct_exact: true,
+            min: column_stats::stats_value(column, row.try_get(2)?),
+            max: column_stats::stats_value(column, row.try_get(3)?),
+            avg: row.try_get(4)?,
+        })
+    }
 
-        // Enforce query timeout (30 seconds)
-        const QUERY_TIMEOUT_SECONDS: u64 = 30;
+    async fn column_histogram(
+        &self,
+        table: &str,
+        column: &str,
+        buckets: u32,
+    ) -> Result<Histogram, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let column = schema
+            .columns
+            .iter()
+            .find(|info| info.name == column)
+            .ok_or_else(|| DatabaseError::InvalidColumn(column.to_string()))?;
+        let scale = histogram::histogram_scale(column).ok_or_else(|| {
+            DatabaseError::InvalidColumn(format!(
+                "'{}' is {}; histograms need a numeric or date column",
+                column.name, column.data_type
+            ))
+        })?;
+        let buckets = histogram::bucket_count(buckets);
+        let quoted_table = self.quote_table(&schema.name);
+        let mut connection = self.connection().await?;
+
+        let range_query =
+            histogram::range_query(&quoted_table, &column.name, scale, Dialect::Sqlite);
+        let (lower, upper, null_count): (Option<f64>, Option<f64>, i64) =
+            sqlx::query_as(&range_query)
+                .fetch_one(&mut *connection)
+                .await?;
 
-        // Enforce maximum result row limit
-        const MAX_RESULT_ROWS: u64 = 10000;
+        let buckets = match (lower, upper) {
+            (Some(lower), Some(upper)) => {
+                let counts_query = histogram::counts_query(
+                    &quoted_table,
+                    &column.name,
+                    scale,
+                    (lower, upper),
+                    buckets,
+                    Dialect::Sqlite,
+                );
+                let counts: Vec<(i64, i64)> = sqlx::query_as(&counts_query)
+                    .fetch_all(&mut *connection)
+                    .await?;
+                histogram::histogram_buckets(lower, upper, buckets, &counts, scale)
+            }
+            // No values to bucket
+            _ => Vec::new(),
+        };
 
-        // Check if this is a SELECT query or a write operation
-        let trimmed_sql = sql.trim().to_uppercase();
-        let is_select_query = trimmed_sql.starts_with("SELECT")
-            || trimmed_sql.starts_with("PRAGMA")
-            || trimmed_sql.starts_with("EXPLAIN");
+        Ok(Histogram {
+            table: schema.name.clone(),
+            column: column.name.clone(),
+            data_type: column.data_type.clone(),
+            scale,
+            buckets,
+            null_count: null_count as u64,
+        })
+    }
 
-        if is_select_query {
-            // For SELECT queries, fetch all rows
-            let result = tokio::time::timeout(
-                std::time::Duration::from_secs(QUERY_TIMEOUT_SECONDS),
-                sqlx::query(sql).fetch_all(&self.pool),
-            )
-            .await;
+    async fn find_duplicates(
+        &self,
+        table: &str,
+        columns: &[String],
+        limit: u64,
+    ) -> Result<DuplicatesResponse, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        duplicates::validate_duplicate_columns(&schema, columns, Dialect::Sqlite)?;
+        let limit = limit.min(self.limits.max_page_size);
+        let sql = duplicates::duplicates_query(&self.quote_table(&schema.name), columns, limit);
+
+        let mut connection = self.connection().await?;
+        let rows = sqlx::query(&sql).fetch_all(&mut *connection).await?;
+        let rows = rows
+            .iter()
+            .map(|row| Self::row_to_json(row, self.big_integers))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (groups, truncated) = duplicates::duplicate_groups(rows, limit);
+
+        Ok(DuplicatesResponse {
+            table: schema.name.clone(),
+            columns: columns.to_vec(),
+            groups,
+            truncated,
+        })
+    }
 
-            let execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+    async fn table_checksum(&self, table: &str) -> Result<TableChecksum, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let sql = format!(
+            "SELECT {} FROM {}",
+            checksum_expressions(&schema, Dialect::Sqlite).join(", "),
+            self.quote_table(&schema.name)
+        );
 
-            match result {
-                Ok(Ok(rows)) => {
-                    // Check row limit
-                    if rows.len() > MAX_RESULT_ROWS as usize {
-                        return Err(DatabaseError::TooManyRows(MAX_RESULT_ROWS));
-                    }
+        let mut connection = self.connection().await?;
+        let row = sqlx::query(&sql).fetch_one(&mut *connection).await?;
+        let row_count: i64 = row.try_get(0)?;
+        let values = (1..row.len())
+            .map(|index| row.try_get::<Option<String>, _>(index))
+            .collect::<Result<Vec<_>, _>>()?;
 
-                    // Extract columns from first row or return empty result
-                    let columns = if let Some(first_row) = rows.first() {
-                        first_row
-                            .columns()
-                            .iter()
-                            .map(|column| column.name().to_string())
-                            .collect()
-                    } else {
-                        Vec::new()
-                    };
+        Ok(table_checksum(&schema.name, row_count as u64, &values))
+    }
 
-                    // Convert rows to JSON
-                    let mut json_rows = Vec::new();
-                    for row in &rows {
-                        json_rows.push(Self::row_to_json(row)?);
-                    }
+    async fn execute_query(
+        &self,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<QueryResult, DatabaseError> {
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        self.sync_attachments(&mut connection).await?;
+        Self::run_query(
+            &mut *connection,
+            sql,
+            params,
+            self.timeouts,
+            self.limits,
+            self.big_integers,
+        )
+        .await
+    }
 
-                    Ok(QueryResult {
-                        columns,
-                        rows: json_rows,
-                        affected_rows: rows.len() as u64,
-                        execution_time_milliseconds,
-                        error: None,
-                    })
-                }
-                Ok(Err(error)) => {
-                    // SQL execution error
-                    Ok(QueryResult {
-                        columns: Vec::new(),
-                        rows: Vec::new(),
-                        affected_rows: 0,
-                        execution_time_milliseconds,
-                        error: Some(error.to_string()),
-                    })
-                }
-                Err(_) => {
-                    // Timeout error
-                    Err(DatabaseError::Timeout)
+    async fn execute_cancellable_query(
+        &self,
+        id: &str,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<QueryResult, DatabaseError> {
+        // Registered before waiting for a connection, so a query cancelled
+        // meanwhile is interrupted right away
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _running = self.running.start(id, sql, cancelled.clone())?;
+
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        self.sync_attachments(&mut connection).await?;
+
+        // SQLite calls the progress handler every 1000 virtual machine
+        // instructions and interrupts the statement once it returns false
+        connection
+            .lock_handle()
+            .await?
+            .set_progress_handler(1000, move || !cancelled.load(Ordering::Relaxed));
+        let result = Self::run_query(
+            &mut *connection,
+            sql,
+            params,
+            self.timeouts,
+            self.limits,
+            self.big_integers,
+        )
+        .await;
+        connection.lock_handle().await?.remove_progress_handler();
+
+        result
+    }
+
+    async fn cancel_query(&self, id: &str) -> Result<(), DatabaseError> {
+        self.running.cancel(id)?.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn activity(&self) -> Result<ActivityResponse, DatabaseError> {
+        // SQLite has no view of other connections' statements, so this lists
+        // the queries run through the viewer that can be cancelled
+        let now_milliseconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        let statements = self
+            .running
+            .list()
+            .into_iter()
+            .map(|statement| ActiveStatement {
+                query_id: Some(statement.id),
+                state: if statement.handle.load(Ordering::Relaxed) {
+                    "cancelling".to_string()
+                } else {
+                    "active".to_string()
+                },
+                sql: Some(statement.sql),
+                started_at_milliseconds: Some(statement.started_at_milliseconds),
+                duration_milliseconds: Some(
+                    now_milliseconds.saturating_sub(statement.started_at_milliseconds),
+                ),
+                ..ActiveStatement::default()
+            })
+            .collect();
+
+        Ok(ActivityResponse {
+            statements,
+            open_connections: self.pool.size(),
+            idle_connections: self.pool.num_idle() as u32,
+        })
+    }
+
+    async fn dry_run(&self, sql: &str, params: &[Value]) -> Result<u64, DatabaseError> {
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        self.sync_attachments(&mut connection).await?;
+        let mut transaction = connection.begin().await?;
+        let query = params.iter().fold(sqlx::query(sql), Self::bind_json_value);
+        let result = self
+            .timeouts
+            .statement(query.execute(&mut *transaction))
+            .await;
+        transaction.rollback().await?;
+
+        Ok(result??.rows_affected())
+    }
+
+    async fn stream_query(
+        &self,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<QueryStream, DatabaseError> {
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        self.sync_attachments(&mut connection).await?;
+        let (mut sender, stream) = streaming::channel();
+        let sql = sql.to_string();
+        let params = params.to_vec();
+        let timeouts = self.timeouts;
+        let big_integers = self.big_integers;
+
+        // The task owns the connection until the last row is read or the
+        // receiving end is dropped
+        runtime::spawn_detached(async move {
+            let query = params.iter().fold(sqlx::query(&sql), Self::bind_json_value);
+            let mut rows = query.fetch(&mut *connection);
+            loop {
+                // The statement timeout applies to the wait for each row
+                let row = match timeouts.statement(rows.try_next()).await {
+                    Ok(Ok(Some(row))) => row,
+                    Ok(Ok(None)) => break,
+                    Ok(Err(error)) => return sender.error(error.into()).await,
+                    Err(error) => return sender.error(error).await,
+                };
+                let json = match Self::row_to_json(&row, big_integers) {
+                    Ok(json) => json,
+                    Err(error) => return sender.error(error).await,
+                };
+                let columns = || {
+                    row.columns()
+                        .iter()
+                        .map(|column| column.name().to_string())
+                        .collect()
+                };
+                if !sender.row(columns, json).await {
+                    break;
                 }
             }
-        } else {
-            // For INSERT/UPDATE/DELETE, use execute() to get affected rows
-            let result = tokio::time::timeout(
-                std::time::Duration::from_secs(QUERY_TIMEOUT_SECONDS),
-                sqlx::query(sql).execute(&self.pool),
+        });
+
+        Ok(stream)
+    }
+
+    async fn execute_script(&self, statements: &[String]) -> Result<ScriptResult, DatabaseError> {
+        let start_time = Instant::now();
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        self.sync_attachments(&mut connection).await?;
+        let mut transaction = connection.begin().await?;
+        let mut script = ScriptResult::default();
+
+        for (index, sql) in statements.iter().enumerate() {
+            let result = Self::run_query(
+                &mut *transaction,
+                sql,
+                &[],
+                self.timeouts,
+                self.limits,
+                self.big_integers,
             )
             .await;
+            let error = match result {
+                Ok(result) => match result.error {
+                    None => {
+                        script.statements.push(ScriptStatement {
+                            sql: sql.clone(),
+                            affected_rows: result.affected_rows,
+                            execution_time_milliseconds: result.execution_time_milliseconds,
+                        });
+                        continue;
+                    }
+                    Some(error) => error,
+                },
+                Err(error) => error.to_string(),
+            };
+
+            transaction.rollback().await?;
+            script.failed_statement = Some(FailedStatement {
+                index,
+                sql: sql.clone(),
+                error,
+            });
+            script.execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+            return Ok(script);
+        }
 
-            let execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+        transaction.commit().await?;
+        script.committed = true;
+        script.execution_time_milliseconds = start_time.elapsed().as_millis() as u64;
+        Ok(script)
+    }
 
-            match result {
-                Ok(Ok(query_result)) => {
-                    Ok(QueryResult {
-                        columns: Vec::new(),
-                        rows: Vec::new(),
-                        affected_rows: query_result.rows_affected(),
-                        execution_time_milliseconds,
-                        error: None,
-                    })
+    async fn explain_query(&self, sql: &str) -> Result<Vec<String>, DatabaseError> {
+        let explain_sql = format!("EXPLAIN QUERY PLAN {}", sql);
+        let mut connection = self.connection().await?;
+        let rows = sqlx::query(&explain_sql)
+            .fetch_all(&mut *connection)
+            .await?;
+
+        // EXPLAIN QUERY PLAN returns: id, parent, notused, detail
+        // Indent each step below its parent to preserve the tree shape
+        let mut depths: std::collections::HashMap<i64, usize> = std::collections::HashMap::new();
+        let mut plan = Vec::new();
+        for row in rows {
+            let id: i64 = row.try_get("id")?;
+            let parent: i64 = row.try_get("parent")?;
+            let detail: String = row.try_get("detail")?;
+
+            let depth = depths.get(&parent).map(|depth| depth + 1).unwrap_or(0);
+            depths.insert(id, depth);
+            plan.push(format!("{}{}", "  ".repeat(depth), detail));
+        }
+
+        Ok(plan)
+    }
+
+    async fn explain_plan(
+        &self,
+        sql: &str,
+        analyze: bool,
+    ) -> Result<ExplainResponse, DatabaseError> {
+        let explain_sql = format!("EXPLAIN QUERY PLAN {}", sql);
+        let mut connection = self.connection().await?;
+        let rows = sqlx::query(&explain_sql)
+            .fetch_all(&mut *connection)
+            .await?;
+
+        let steps = rows
+            .iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("id")?,
+                    row.try_get("parent")?,
+                    row.try_get("detail")?,
+                ))
+            })
+            .collect::<Result<Vec<(i64, i64, String)>, sqlx::Error>>()?;
+
+        // SQLite has no EXPLAIN ANALYZE; the best it offers is timing the whole statement
+        let execution_time_milliseconds = if analyze {
+            let start_time = Instant::now();
+            let mut transaction = connection.begin().await?;
+            self.timeouts
+                .statement(sqlx::query(sql).fetch_all(&mut *transaction))
+                .await??;
+            transaction.rollback().await?;
+            Some(start_time.elapsed().as_secs_f64() * 1000.0)
+        } else {
+            None
+        };
+
+        Ok(ExplainResponse {
+            explained_sql: sql.to_string(),
+            plan: explain::sqlite_plan_tree(&steps),
+            planning_time_milliseconds: None,
+            execution_time_milliseconds,
+        })
+    }
+
+    async fn get_row(&self, table: &str, key: &[String]) -> Result<Option<Value>, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        let mut connection = self.connection().await?;
+        let key_columns = primary_key_columns(&schema, key)?;
+
+        let conditions: Vec<String> = key_columns
+            .iter()
+            .map(|column| format!("{} = ?", Self::quote_identifier(column)))
+            .collect();
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {}",
+            Self::select_list(&schema),
+            self.quote_table(table),
+            conditions.join(" AND ")
+        );
+
+        let mut query = sqlx::query(&sql);
+        // Key values are text; column affinity converts them for the comparison
+        for value in key {
+            query = query.bind(value.clone());
+        }
+
+        match query.fetch_optional(&mut *connection).await? {
+            Some(row) => Self::row_to_json(&row, self.big_integers).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn count_rows_with_values(
+        &self,
+        table: &str,
+        column: &str,
+        values: &[String],
+    ) -> Result<u64, DatabaseError> {
+        if values.is_empty() {
+            return Ok(0);
+        }
+
+        let mut connection = self.connection().await?;
+        let placeholders = vec!["?"; values.len()].join(", ");
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE {} IN ({})",
+            self.quote_table(table),
+            Self::quote_identifier(column),
+            placeholders
+        );
+
+        let mut query = sqlx::query_scalar::<_, i64>(&sql);
+        for value in values {
+            query = query.bind(value.clone());
+        }
+
+        let count = query.fetch_one(&mut *connection).await?;
+        Ok(count as u64)
+    }
+
+    async fn get_rows_with_values(
+        &self,
+        table: &str,
+        column: &str,
+        values: &[String],
+        limit: u64,
+    ) -> Result<Vec<Value>, DatabaseError> {
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut connection = self.connection().await?;
+        let placeholders = vec!["?"; values.len()].join(", ");
+        let sql = format!(
+            "SELECT * FROM {} WHERE {} IN ({}) LIMIT ?",
+            self.quote_table(table),
+            Self::quote_identifier(column),
+            placeholders
+        );
+
+        let mut query = sqlx::query(&sql);
+        for value in values {
+            query = query.bind(value.clone());
+        }
+        let rows = query.bind(limit as i64).fetch_all(&mut *connection).await?;
+
+        rows.iter()
+            .map(|row| Self::row_to_json(row, self.big_integers))
+            .collect()
+    }
+
+    async fn update_row(
+        &self,
+        table: &str,
+        key: &[String],
+        values: &serde_json::Map<String, Value>,
+    ) -> Result<Value, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        let mut connection = self.connection().await?;
+        let key_columns = primary_key_columns(&schema, key)?;
+        validate_columns(&schema, values.keys())?;
+
+        let assignments: Vec<String> = values
+            .keys()
+            .map(|column| format!("{} = ?", Self::quote_identifier(column)))
+            .collect();
+        let conditions: Vec<String> = key_columns
+            .iter()
+            .map(|column| format!("{} = ?", Self::quote_identifier(column)))
+            .collect();
+
+        let sql = format!(
+            "UPDATE {} SET {} WHERE {} RETURNING {}",
+            self.quote_table(table),
+            assignments.join(", "),
+            conditions.join(" AND "),
+            Self::select_list(&schema)
+        );
+
+        let mut query = sqlx::query(&sql);
+        for value in values.values() {
+            query = Self::bind_json_value(query, value);
+        }
+        // Key values are text; column affinity converts them for the comparison
+        for value in key {
+            query = query.bind(value.clone());
+        }
+
+        match query.fetch_optional(&mut *connection).await? {
+            Some(row) => Self::row_to_json(&row, self.big_integers),
+            None => Err(DatabaseError::RowNotFound(key.join(", "))),
+        }
+    }
+
+    async fn insert_rows(
+        &self,
+        table: &str,
+        rows: &[serde_json::Map<String, Value>],
+    ) -> Result<InsertedRows, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        validate_new_rows(&schema, rows)?;
+        let mut connection = self.connection().await?;
+
+        // Dropping the transaction after a failed insert rolls back the earlier rows
+        let mut transaction = connection.begin().await?;
+        self.insert_into(&mut transaction, table, rows).await?;
+        transaction.commit().await?;
+
+        Ok(InsertedRows {
+            inserted: rows.len() as u64,
+        })
+    }
+
+    async fn delete_rows(
+        &self,
+        table: &str,
+        keys: &[Vec<String>],
+        cascade: &[Vec<Reference>],
+    ) -> Result<DeletedRows, DatabaseError> {
+        let schema = self.get_table_schema(table).await?;
+        let table = schema.name.as_str();
+        let mut connection = self.connection().await?;
+        let Some(first_key) = keys.first() else {
+            return Ok(DeletedRows::default());
+        };
+        let key_columns = primary_key_columns(&schema, first_key)?;
+        for key in keys {
+            primary_key_columns(&schema, key)?;
+        }
+
+        let key_condition: Vec<String> = key_columns
+            .iter()
+            .map(|column| format!("{} = ?", Self::quote_identifier(column)))
+            .collect();
+        let root_condition =
+            vec![format!("({})", key_condition.join(" AND ")); keys.len()].join(" OR ");
+
+        let mut transaction = connection.begin().await?;
+        let mut deleted_rows = DeletedRows::default();
+
+        for path in cascade {
+            let sql =
+                cascade_delete_sql(table, &root_condition, path, |name| self.quote_table(name));
+            let mut query = sqlx::query(&sql);
+            for value in keys.iter().flatten() {
+                query = query.bind(value.clone());
+            }
+            deleted_rows.cascaded += query.execute(&mut *transaction).await?.rows_affected();
+        }
+
+        let sql = format!(
+            "DELETE FROM {} WHERE {}",
+            self.quote_table(table),
+            root_condition
+        );
+        let mut query = sqlx::query(&sql);
+        // Key values are text; column affinity converts them for the comparison
+        for value in keys.iter().flatten() {
+            query = query.bind(value.clone());
+        }
+        deleted_rows.deleted = query.execute(&mut *transaction).await?.rows_affected();
+
+        transaction.commit().await?;
+
+        Ok(deleted_rows)
+    }
+
+    async fn replace_rows(&self, tables: &[FixtureTable]) -> Result<LoadedFixture, DatabaseError> {
+        let mut schemas = Vec::with_capacity(tables.len());
+        for fixture_table in tables {
+            let schema = self.get_table_schema(&fixture_table.table).await?;
+            validate_table_rows(&schema, &fixture_table.rows)?;
+            schemas.push(schema);
+        }
+
+        let mut connection = self.connection().await?;
+        let mut transaction = connection.begin().await?;
+        let mut loaded = LoadedFixture {
+            tables: tables.len() as u64,
+            ..LoadedFixture::default()
+        };
+
+        // Referencing tables are emptied before the tables they reference
+        for schema in schemas.iter().rev() {
+            let sql = format!("DELETE FROM {}", self.quote_table(&schema.name));
+            loaded.deleted += sqlx::query(&sql)
+                .execute(&mut *transaction)
+                .await?
+                .rows_affected();
+        }
+        for (schema, fixture_table) in schemas.iter().zip(tables) {
+            self.insert_into(&mut transaction, &schema.name, &fixture_table.rows)
+                .await?;
+            loaded.inserted += fixture_table.rows.len() as u64;
+        }
+
+        transaction.commit().await?;
+        Ok(loaded)
+    }
+
+    async fn seed_status(&self) -> Result<SeedStatus, DatabaseError> {
+        let location = self.seed_path().await?;
+        let exists = std::path::Path::new(&location).exists();
+
+        Ok(SeedStatus { location, exists })
+    }
+
+    async fn create_seed(&self) -> Result<SeedStatus, DatabaseError> {
+        let location = self.seed_path().await?;
+
+        // VACUUM INTO refuses to overwrite an existing file
+        match runtime::remove_file(&location).await {
+            Ok(()) => {}
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(DatabaseError::Query(format!(
+                    "Failed to remove previous seed '{}': {}",
+                    location, error
+                )))
+            }
+        }
+
+        // Writes a consistent, compacted copy of the database
+        sqlx::query("VACUUM INTO ?")
+            .bind(&location)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(SeedStatus {
+            location,
+            exists: true,
+        })
+    }
+
+    async fn reset_to_seed(&self) -> Result<SeedStatus, DatabaseError> {
+        let status = self.seed_status().await?;
+        if !status.exists {
+            return Err(DatabaseError::SeedNotFound(status.location));
+        }
+
+        // Open transactions in console sessions would block the reset
+        self.sessions.clear();
+
+        // The database file stays in place (other connections keep using it);
+        // its contents are replaced from the attached seed on one connection
+        let mut connection = self.pool.acquire().await?;
+        let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys")
+            .fetch_one(&mut *connection)
+            .await?;
+        // Only takes effect outside of a transaction
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query("ATTACH DATABASE ? AS seed")
+            .bind(&status.location)
+            .execute(&mut *connection)
+            .await?;
+
+        let result = Self::copy_from_seed(&mut connection).await;
+
+        // Restore the connection before returning it to the pool, even on failure
+        sqlx::query("DETACH DATABASE seed")
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query(&format!("PRAGMA foreign_keys = {}", foreign_keys))
+            .execute(&mut *connection)
+            .await?;
+
+        result.map(|()| status)
+    }
+
+    async fn list_named_snapshots(&self) -> Result<Vec<NamedSnapshot>, DatabaseError> {
+        let directory = self.snapshot_directory().await?;
+        let entries = match std::fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => {
+                return Err(DatabaseError::Query(format!(
+                    "Failed to read snapshot directory '{}': {}",
+                    directory.display(),
+                    error
+                )))
+            }
+        };
+
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                let name = file_name.strip_suffix(".db")?;
+                validate_snapshot_name(name).ok()?;
+                Some(name.to_string())
+            })
+            .collect();
+        names.sort();
+
+        let mut snapshots = Vec::with_capacity(names.len());
+        for name in names {
+            let path = self.named_snapshot_path(&name).await?;
+            snapshots.push(self.read_named_snapshot(&name, &path).await?);
+        }
+        Ok(snapshots)
+    }
+
+    async fn save_named_snapshot(
+        &self,
+        name: &str,
+        table: Option<&str>,
+    ) -> Result<NamedSnapshot, DatabaseError> {
+        validate_snapshot_name(name)?;
+        let table = match table {
+            Some(table) => {
+                let table = self.resolve_table(table).await?;
+                if self.attachments.split(&table).0.is_some() {
+                    return Err(DatabaseError::Unsupported(
+                        "Named snapshots only hold tables of the main database".to_string(),
+                    ));
                 }
-                Ok(Err(error)) => {
-                    Ok(QueryResult {
-                        columns: Vec::new(),
-                        rows: Vec::new(),
-                        affected_rows: 0,
-                        execution_time_milliseconds,
-                        error: Some(error.to_string()),
+                Some(table)
+            }
+            None => None,
+        };
+
+        let directory = self.snapshot_directory().await?;
+        runtime::create_dir_all(&directory).await.map_err(|error| {
+            DatabaseError::Query(format!(
+                "Failed to create snapshot directory '{}': {}",
+                directory.display(),
+                error
+            ))
+        })?;
+        let path = directory
+            .join(format!("{}.db", name))
+            .to_string_lossy()
+            .into_owned();
+
+        // VACUUM INTO refuses to overwrite an existing file, ATTACH would add to it
+        match runtime::remove_file(&path).await {
+            Ok(()) => {}
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(DatabaseError::Query(format!(
+                    "Failed to remove previous snapshot '{}': {}",
+                    path, error
+                )))
+            }
+        }
+
+        match table {
+            None => {
+                sqlx::query("VACUUM INTO ?")
+                    .bind(&path)
+                    .execute(&self.pool)
+                    .await?;
+            }
+            Some(table) => {
+                // Attaching a missing file creates an empty database
+                let mut connection = self.connection().await?;
+                sqlx::query("ATTACH DATABASE ? AS snapshot")
+                    .bind(&path)
+                    .execute(&mut *connection)
+                    .await?;
+                let sql = format!(
+                    "CREATE TABLE snapshot.{0} AS SELECT * FROM main.{0}",
+                    Self::quote_identifier(&table)
+                );
+                let result = sqlx::query(&sql).execute(&mut *connection).await;
+                sqlx::query("DETACH DATABASE snapshot")
+                    .execute(&mut *connection)
+                    .await?;
+                result?;
+            }
+        }
+
+        self.read_named_snapshot(name, &path).await
+    }
+
+    async fn restore_named_snapshot(&self, name: &str) -> Result<NamedSnapshot, DatabaseError> {
+        let path = self.named_snapshot_path(name).await?;
+
+        // Open transactions in console sessions would block the restore
+        self.sessions.clear();
+
+        // Rows are copied in any order, so references are only checked by
+        // whoever changes the rows next
+        let mut connection = self.connection().await?;
+        let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys")
+            .fetch_one(&mut *connection)
+            .await?;
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query("ATTACH DATABASE ? AS snapshot")
+            .bind(&path)
+            .execute(&mut *connection)
+            .await?;
+
+        let result = Self::copy_from_named_snapshot(&mut connection).await;
+
+        sqlx::query("DETACH DATABASE snapshot")
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query(&format!("PRAGMA foreign_keys = {}", foreign_keys))
+            .execute(&mut *connection)
+            .await?;
+
+        Ok(NamedSnapshot {
+            name: name.to_string(),
+            tables: result?,
+            created_at_milliseconds: modified_milliseconds(&path)?,
+        })
+    }
+
+    async fn delete_named_snapshot(&self, name: &str) -> Result<(), DatabaseError> {
+        let path = self.named_snapshot_path(name).await?;
+        runtime::remove_file(&path).await.map_err(|error| {
+            DatabaseError::Query(format!("Failed to remove snapshot '{}': {}", path, error))
+        })
+    }
+
+    async fn named_snapshot_differences(
+        &self,
+        name: &str,
+        table: &str,
+        limit: u64,
+    ) -> Result<SnapshotRowDifferences, DatabaseError> {
+        let path = self.named_snapshot_path(name).await?;
+
+        let mut connection = self.connection().await?;
+        sqlx::query("ATTACH DATABASE ? AS snapshot")
+            .bind(&path)
+            .execute(&mut *connection)
+            .await?;
+        let result =
+            Self::named_snapshot_rows(&mut connection, table, limit, self.big_integers).await;
+        sqlx::query("DETACH DATABASE snapshot")
+            .execute(&mut *connection)
+            .await?;
+
+        result
+    }
+
+    async fn run_maintenance(
+        &self,
+        operation: MaintenanceOperation,
+        tables: &[String],
+    ) -> Result<MaintenanceResult, DatabaseError> {
+        let mut connection = self.connection().await?;
+        let size_before = Self::database_size(&mut connection).await?;
+        let start_time = Instant::now();
+
+        // VACUUM and checkpoints always work on the whole main database file
+        let (statements, tables) = match (operation, tables) {
+            (MaintenanceOperation::Vacuum, _) => (vec!["VACUUM".to_string()], Vec::new()),
+            (MaintenanceOperation::Checkpoint, _) => (Vec::new(), Vec::new()),
+            (_, []) => (vec![operation.name().to_uppercase()], Vec::new()),
+            (_, tables) => (
+                tables
+                    .iter()
+                    .map(|table| {
+                        format!(
+                            "{} {}",
+                            operation.name().to_uppercase(),
+                            self.attachments.quote_table(table)
+                        )
                     })
-                }
-                Err(_) => {
-                    Err(DatabaseError::Timeout)
-                }
+                    .collect(),
+                tables.to_vec(),
+            ),
+        };
+        for statement in &statements {
+            connection.execute(statement.as_str()).await?;
+        }
+        let checkpoint = match operation {
+            MaintenanceOperation::Checkpoint => Some(self.checkpoint_wal(&mut connection).await?),
+            _ => None,
+        };
+
+        Ok(MaintenanceResult {
+            operation,
+            tables,
+            size_before,
+            size_after: Self::database_size(&mut connection).await?,
+            execution_time_milliseconds: start_time.elapsed().as_millis() as u64,
+            checkpoint,
+        })
+    }
+
+    async fn journal_info(&self) -> Result<JournalInfo, DatabaseError> {
+        let mut connection = self.pool.acquire().await?;
+        let journal_mode: String = sqlx::query_scalar("PRAGMA main.journal_mode")
+            .fetch_one(&mut *connection)
+            .await?;
+        let wal_autocheckpoint: i64 = sqlx::query_scalar("PRAGMA wal_autocheckpoint")
+            .fetch_one(&mut *connection)
+            .await?;
+        let page_size: i64 = sqlx::query_scalar("PRAGMA main.page_size")
+            .fetch_one(&mut *connection)
+            .await?;
+
+        let wal = journal_mode.eq_ignore_ascii_case("wal");
+        let wal_size = match self.main_file().await? {
+            // The log is only created once something is written
+            Some(file) if wal => Some(file_size(&format!("{}-wal", file)).unwrap_or(0)),
+            _ => None,
+        };
+
+        Ok(JournalInfo {
+            journal_mode: journal_mode.to_lowercase(),
+            wal,
+            wal_size,
+            wal_autocheckpoint: wal_autocheckpoint.max(0) as u64,
+            page_size: page_size as u64,
+        })
+    }
+
+    async fn open_session(&self) -> Result<SessionResponse, DatabaseError> {
+        self.sessions.ensure_capacity()?;
+        let mut connection = self.timeouts.acquire(&self.pool).await?;
+        // Files attached later are not added to sessions that are already open
+        self.sync_attachments(&mut connection).await?;
+        let connection = connection.detach();
+
+        Ok(SessionResponse {
+            session: self.sessions.open(connection),
+            idle_timeout_seconds: SESSION_IDLE_TIMEOUT.as_secs(),
+        })
+    }
+
+    async fn execute_in_session(
+        &self,
+        session: &str,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<QueryResult, DatabaseError> {
+        let mut connection = self.sessions.acquire(session)?;
+        let result = Self::run_query(
+            &mut connection,
+            sql,
+            params,
+            self.timeouts,
+            self.limits,
+            self.big_integers,
+        )
+        .await;
+
+        // A statement interrupted by the timeout leaves the connection in an
+        // unknown state, so the session is closed instead of reused
+        match result {
+            Err(DatabaseError::Timeout(_)) => {
+                let _ = self.sessions.close(session);
             }
+            _ => self.sessions.release(session, connection),
+        }
+        result
+    }
+
+    async fn list_attachments(&self) -> Result<Vec<AttachedDatabase>, DatabaseError> {
+        Ok(self.attachments.list())
+    }
+
+    async fn attach_database(
+        &self,
+        alias: &str,
+        path: &str,
+    ) -> Result<AttachedDatabase, DatabaseError> {
+        // ATTACH would create a missing file; only existing databases are attached.
+        // The full path is what PRAGMA database_list reports for the file.
+        let path = std::fs::canonicalize(path)
+            .ok()
+            .filter(|path| path.is_file())
+            .and_then(|path| path.to_str().map(str::to_string))
+            .ok_or_else(|| {
+                DatabaseError::InvalidAttachment(format!("'{}' is not a file on the server", path))
+            })?;
+        let attachment = self.attachments.add(alias, &path)?;
+
+        // A file that is not a database only fails once it is read
+        let check = async {
+            let mut connection = self.connection().await?;
+            let query = format!(
+                "SELECT COUNT(*) FROM {}sqlite_master",
+                attachments::schema_prefix(Some(&attachment.alias))
+            );
+            sqlx::query_scalar::<_, i64>(&query)
+                .fetch_one(&mut *connection)
+                .await?;
+            Ok::<(), DatabaseError>(())
+        };
+        if let Err(error) = check.await {
+            self.attachments.remove(alias)?;
+            return Err(DatabaseError::InvalidAttachment(format!(
+                "failed to attach '{}': {}",
+                path, error
+            )));
+        }
+
+        Ok(attachment)
+    }
+
+    async fn detach_database(&self, alias: &str) -> Result<(), DatabaseError> {
+        // Pool connections drop the file the next time they are used
+        self.attachments.remove(alias)?;
+        Ok(())
+    }
+
+    async fn schema_fingerprint(&self) -> Result<String, DatabaseError> {
+        // Incremented by SQLite on every schema change, separately per file
+        let mut connection = self.connection().await?;
+        let version: i64 = sqlx::query_scalar("PRAGMA schema_version")
+            .fetch_one(&mut *connection)
+            .await?;
+
+        let mut fingerprint = version.to_string();
+        for attachment in self.attachments.list() {
+            let query = format!(
+                "PRAGMA {}schema_version",
+                attachments::schema_prefix(Some(&attachment.alias))
+            );
+            let version: i64 = sqlx::query_scalar(&query)
+                .fetch_one(&mut *connection)
+                .await?;
+            fingerprint.push_str(&format!(";{}:{}", attachment.alias, version));
+        }
+        Ok(fingerprint)
+    }
+
+    async fn close_session(&self, session: &str) -> Result<(), DatabaseError> {
+        if let Some(connection) = self.sessions.close(session)? {
+            connection.close().await?;
         }
+        Ok(())
     }
 }
 
+/// Last modification time of the file at `path`, in milliseconds since the Unix epoch
+fn modified_milliseconds(path: &str) -> Result<u64, DatabaseError> {
+    let modified = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|error| {
+            DatabaseError::Query(format!("Failed to read snapshot '{}': {}", path, error))
+        })?;
+    Ok(modified
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0))
+}
+
+/// Size in bytes of the file at `path`, if there is one
+fn file_size(path: &str) -> Option<u64> {
+    std::fs::metadata(path).ok().map(|metadata| metadata.len())
+}
+
 /// Simple base64 encoding for BLOB data
 fn base64_encode(data: &[u8]) -> String {
     const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
@@ -607,6 +2516,7 @@ fn base64_encode(data: &[u8]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{column, table, ColumnInfoExt};
 
     #[test]
     fn test_quote_identifier() {
@@ -623,25 +2533,36 @@ mod tests {
         filters.insert("name".to_string(), "John".to_string());
         filters.insert("age".to_string(), "30".to_string());
 
-        let (clause, values) = SqliteProvider::build_where_clause(&filters);
+        let (clause, values) = SqliteProvider::build_where_clause(&filters, false);
         assert!(clause.contains("WHERE"));
         assert!(clause.contains("\"name\""));
         assert!(clause.contains("\"age\""));
+        assert!(!clause.contains("NOCASE"));
         assert_eq!(values.len(), 2);
+
+        let (clause, _) = SqliteProvider::build_where_clause(&filters, true);
+        assert!(clause.contains("\"name\" = ? COLLATE NOCASE"));
     }
 
     #[test]
-    fn test_build_order_clause() {
-        let clause = SqliteProvider::build_order_clause(Some("name"), Some(SortOrder::Ascending));
-        assert!(clause.contains("ORDER BY"));
-        assert!(clause.contains("\"name\""));
-        assert!(clause.contains("ASC"));
-
-        let clause = SqliteProvider::build_order_clause(Some("id"), Some(SortOrder::Descending));
-        assert!(clause.contains("DESC"));
+    fn test_filter_group_condition() {
+        let schema = table("users")
+            .column(column("id", "INTEGER").not_null())
+            .column(column("active", "BOOLEAN").not_null())
+            .primary_key(&["id"])
+            .build();
+        let mut group = std::collections::HashMap::new();
+        group.insert("active".to_string(), "yes".to_string());
 
-        let clause = SqliteProvider::build_order_clause(None, None);
-        assert!(clause.is_empty());
+        let mut values = Vec::new();
+        let condition = SqliteProvider::filter_group_condition(&group, &schema, false, &mut values);
+        assert_eq!(condition, "(\"active\" = ?)");
+        assert_eq!(values, vec!["1"]);
+
+        let empty = std::collections::HashMap::new();
+        let condition = SqliteProvider::filter_group_condition(&empty, &schema, false, &mut values);
+        assert_eq!(condition, "1 = 1");
+        assert_eq!(values.len(), 1);
     }
 
     #[test]
@@ -651,4 +2572,47 @@ mod tests {
         assert!(!encoded.is_empty());
         assert!(encoded.chars().all(|c| c.is_alphanumeric() || c == '+' || c == '/' || c == '='));
     }
+
+    #[tokio::test]
+    async fn test_stringified_big_integers_bind_back() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let database =
+            SqliteProvider::new(pool).with_big_integer_format(BigIntegerFormat::SafeNumber);
+        let setup = "CREATE TABLE t (id INTEGER PRIMARY KEY, amount INTEGER);
+                     INSERT INTO t VALUES (1, 9007199254740993)";
+        database.execute_query(setup, &[]).await.unwrap();
+
+        // Filters and parameters take the string the value was sent as
+        let query = serde_json::from_value(serde_json::json!({
+            "filters": { "amount": "9007199254740993" }
+        }))
+        .unwrap();
+        let page = database.get_rows("t", query).await.unwrap();
+        assert_eq!(
+            page.rows,
+            [serde_json::json!({ "id": 1, "amount": "9007199254740993" })]
+        );
+
+        let sql = "SELECT id FROM t WHERE amount = ?";
+        let result = database
+            .execute_query(sql, &[serde_json::json!("9007199254740993")])
+            .await
+            .unwrap();
+        assert_eq!(result.rows, [serde_json::json!({ "id": 1 })]);
+
+        // Edits store the string as an integer, without rounding it
+        let values = serde_json::json!({ "amount": "9007199254740995" });
+        let row = database
+            .update_row("t", &["1".to_string()], values.as_object().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(row["amount"], "9007199254740995");
+
+        let sql = "SELECT typeof(amount) AS type, amount = 9007199254740995 AS exact FROM t";
+        let result = database.execute_query(sql, &[]).await.unwrap();
+        assert_eq!(
+            result.rows,
+            [serde_json::json!({ "type": "integer", "exact": 1 })]
+        );
+    }
 }
@@ -0,0 +1,62 @@
+//! Query results streamed row by row
+//!
+//! The rows are read by a background task that holds the connection and hands
+//! them to the response through a bounded channel. A slow client therefore
+//! slows down the reading instead of making rows pile up in memory, and a
+//! client that goes away stops the query.
+
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::database::traits::{DatabaseError, QueryStream};
+use crate::schema::QueryStreamLine;
+
+/// Rows read ahead of the client
+const BUFFERED_ROWS: usize = 256;
+
+/// Create the two ends of a streamed result
+pub fn channel() -> (RowSender, QueryStream) {
+    let (sender, receiver) = mpsc::channel(BUFFERED_ROWS);
+    let stream = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|line| (line, receiver))
+    });
+
+    let sender = RowSender {
+        sender,
+        columns_sent: false,
+    };
+    (sender, Box::pin(stream))
+}
+
+/// Sending end of a streamed result, used by the task reading the rows
+pub struct RowSender {
+    sender: mpsc::Sender<Result<QueryStreamLine, DatabaseError>>,
+    columns_sent: bool,
+}
+
+impl RowSender {
+    /// Send a row, preceded by the column names if it is the first one
+    ///
+    /// # Returns
+    ///
+    /// Whether the client is still listening; reading should stop otherwise
+    pub async fn row(&mut self, columns: impl FnOnce() -> Vec<String>, row: Value) -> bool {
+        if !self.columns_sent {
+            self.columns_sent = true;
+            let columns = QueryStreamLine::Columns { columns: columns() };
+            if self.sender.send(Ok(columns)).await.is_err() {
+                return false;
+            }
+        }
+
+        self.sender
+            .send(Ok(QueryStreamLine::Row { row }))
+            .await
+            .is_ok()
+    }
+
+    /// Report an error that ends the stream
+    pub async fn error(self, error: DatabaseError) {
+        let _ = self.sender.send(Err(error)).await;
+    }
+}
@@ -0,0 +1,133 @@
+//! Connection and statement timeouts of the built-in providers
+//!
+//! Waiting for a free pool connection and running a statement are limited
+//! separately, and a timeout reports which of the two fired
+//! ([`TimeoutKind::Acquire`] or [`TimeoutKind::Statement`]), so a saturated
+//! pool can be told apart from a slow query.
+
+use std::future::Future;
+use std::time::Duration;
+
+use sqlx::pool::PoolConnection;
+use sqlx::{Database, Pool};
+
+use crate::database::traits::DatabaseError;
+use crate::runtime;
+use crate::schema::TimeoutKind;
+
+/// Default time to wait for a free pool connection
+pub const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time a statement may run
+pub const DEFAULT_STATEMENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeouts applied by a provider to raw SQL and console sessions
+///
+/// The pool's own `acquire_timeout` still applies to the provider's other
+/// queries; running out of it is reported as an acquire timeout as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Time to wait for a free pool connection
+    pub acquire: Duration,
+
+    /// Time a statement may run
+    pub statement: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            acquire: DEFAULT_ACQUIRE_TIMEOUT,
+            statement: DEFAULT_STATEMENT_TIMEOUT,
+        }
+    }
+}
+
+/// Whether the server stopped a statement because of its `statement_timeout`
+///
+/// PostgreSQL reports this as `query_canceled` (57014). Only the code is
+/// checked, as the message is translated by the server's `lc_messages`; a
+/// cancellation by `pg_cancel_backend` has the same code, so providers tell
+/// queries they cancelled apart themselves.
+pub fn is_statement_timeout(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .is_some_and(|error| error.code().as_deref() == Some("57014"))
+}
+
+impl Timeouts {
+    /// Take a connection from `pool`, waiting at most the acquire timeout
+    pub async fn acquire<DB: Database>(
+        &self,
+        pool: &Pool<DB>,
+    ) -> Result<PoolConnection<DB>, DatabaseError> {
+        match runtime::timeout(self.acquire, pool.acquire()).await {
+            Ok(connection) => Ok(connection?),
+            Err(_) => Err(DatabaseError::Timeout(TimeoutKind::Acquire)),
+        }
+    }
+
+    /// Run a statement, giving up once the statement timeout has elapsed
+    pub async fn statement<F: Future>(&self, future: F) -> Result<F::Output, DatabaseError> {
+        runtime::timeout(self.statement, future)
+            .await
+            .map_err(|_| DatabaseError::Timeout(TimeoutKind::Statement))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::error::ErrorKind;
+    use std::borrow::Cow;
+    use std::error::Error;
+    use std::fmt;
+
+    /// Database error with a code and a message in the server's language
+    #[derive(Debug)]
+    struct ServerError(&'static str);
+
+    impl fmt::Display for ServerError {
+        fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("Abbruch der Anweisung wegen Zeitüberschreitung")
+        }
+    }
+
+    impl Error for ServerError {}
+
+    impl sqlx::error::DatabaseError for ServerError {
+        fn message(&self) -> &str {
+            "Abbruch der Anweisung wegen Zeitüberschreitung"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    #[test]
+    fn test_statement_timeout_by_code() {
+        let timeout = sqlx::Error::Database(Box::new(ServerError("57014")));
+        assert!(is_statement_timeout(&timeout));
+
+        let other = sqlx::Error::Database(Box::new(ServerError("42P01")));
+        assert!(!is_statement_timeout(&other));
+        assert!(!is_statement_timeout(&sqlx::Error::PoolTimedOut));
+    }
+}
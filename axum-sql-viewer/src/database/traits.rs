@@ -1,19 +1,74 @@
 //! Database provider trait
 //!
 //! This trait defines the interface that all database implementations must provide.
+//!
+//! The trait itself does not depend on sqlx or tokio: the built-in providers
+//! are behind the `sqlite` and `postgres` features, and the API layer only
+//! reaches the async runtime through [`crate::runtime`]. A custom provider
+//! (for example one backed by sql.js or SQLite on OPFS in the browser) can be
+//! built with `default-features = false` and passed to
+//! [`SqlViewerLayer::new`](crate::SqlViewerLayer::new). Implementations use
+//! [`async_trait`](crate::async_trait); the returned futures must be `Send`
+//! because axum requires it, which on single-threaded WebAssembly targets can
+//! be satisfied by wrapping JavaScript handles in a send wrapper.
 
-use crate::schema::{CountResponse, QueryResult, RowQuery, RowsResponse, TableInfo, TableSchema};
+use crate::column_stats::ColumnStats;
+use crate::database::limits::ResultLimits;
+use crate::duplicates::DuplicatesResponse;
+use crate::histogram::Histogram;
+use crate::references::Reference;
+use crate::sample::SampleResponse;
+use crate::schema::{
+    ActivityResponse, AttachedDatabase, Capabilities, CountResponse, DeletedRows, ExplainResponse,
+    FixtureTable, InsertedRows, JournalInfo, LoadedFixture, MaintenanceOperation,
+    MaintenanceResult, NamedSnapshot, Notification, QueryResult, QueryStreamLine, RowQuery,
+    RowsResponse, ScriptResult, SeedStatus, SessionResponse, SnapshotResponse,
+    SnapshotRowDifferences, TableChecksum, TableDefinition, TableInfo, TableSchema, TimeoutKind,
+};
+use crate::sql::Dialect;
 use async_trait::async_trait;
+use futures_util::Stream;
+use std::pin::Pin;
 use thiserror::Error;
 
+/// Lines of a streamed query result, see [`DatabaseProvider::stream_query`]
+pub type QueryStream = Pin<Box<dyn Stream<Item = Result<QueryStreamLine, DatabaseError>> + Send>>;
+
+/// Notifications received by [`DatabaseProvider::listen`]
+pub type NotificationStream =
+    Pin<Box<dyn Stream<Item = Result<Notification, DatabaseError>> + Send>>;
+
 /// Database provider trait for schema discovery and data access
 ///
 /// Implementations of this trait provide database-specific logic for
 /// discovering schema information and fetching data.
 #[async_trait]
 pub trait DatabaseProvider: Send + Sync + 'static {
+    /// SQL dialect spoken by this database
+    fn dialect(&self) -> Dialect;
+
+    /// Optional features supported by this database
+    ///
+    /// The default implementation reports none of them, so a provider only
+    /// has to list what it actually supports.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// Maximum numbers of rows this provider returns
+    ///
+    /// The API layer caps page sizes to these as well. The default
+    /// implementation reports [`ResultLimits::default`].
+    fn result_limits(&self) -> ResultLimits {
+        ResultLimits::default()
+    }
+
     /// List all table names in the database
     ///
+    /// Row counts are estimates from the database's statistics where it keeps
+    /// them (`exact: false`), as counting every table takes minutes on large
+    /// databases; `GET /api/tables?exact=true` counts them instead.
+    ///
     /// # Returns
     ///
     /// A vector of table information, optionally including row counts
@@ -30,8 +85,35 @@ pub trait DatabaseProvider: Send + Sync + 'static {
     /// Complete schema information including columns, keys, and indexes
     async fn get_table_schema(&self, table: &str) -> Result<TableSchema, DatabaseError>;
 
+    /// Get schema information for every table in the database
+    ///
+    /// The default implementation calls `get_table_schema` for each table
+    /// returned by `list_tables`.
+    ///
+    /// # Returns
+    ///
+    /// Schema information for all tables, in the order returned by `list_tables`
+    async fn get_all_table_schemas(&self) -> Result<Vec<TableSchema>, DatabaseError> {
+        let tables = self.list_tables().await?;
+
+        let mut schemas = Vec::with_capacity(tables.len());
+        for table in tables {
+            schemas.push(self.get_table_schema(&table.name).await?);
+        }
+
+        Ok(schemas)
+    }
+
     /// Fetch rows with pagination, sorting, and filtering
     ///
+    /// Rows are ordered by the sort column and then by the row key, and pages
+    /// after the first can be read with the `nextCursor` of the previous page
+    /// instead of an offset (see [`crate::keyset`]).
+    ///
+    /// Providers read one row past the page to set `has_more`, so it does not
+    /// depend on `total`; with `query.count` false the rows are not counted
+    /// and `total` is `None`.
+    ///
     /// # Arguments
     ///
     /// * `table` - Name of the table
@@ -54,6 +136,101 @@ pub trait DatabaseProvider: Send + Sync + 'static {
     /// Total row count
     async fn count_rows(&self, table: &str, query: &RowQuery) -> Result<CountResponse, DatabaseError>;
 
+    /// Randomly picked rows of a table, to look at a slice of a large table
+    ///
+    /// Each row has the same chance of being picked (see [`crate::sample`]).
+    /// The default implementation reports samples as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `size` - Number of rows to pick, at most the maximum page size
+    async fn sample_rows(&self, table: &str, size: u64) -> Result<SampleResponse, DatabaseError> {
+        let _ = (table, size);
+        Err(DatabaseError::Unsupported(
+            "Samples are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Minimum, maximum, average, NULL and distinct counts of a column
+    ///
+    /// Computed by the database in a single aggregate query (see
+    /// [`crate::column_stats`]). The default implementation reports column
+    /// statistics as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `column` - Name of the column
+    async fn column_stats(&self, table: &str, column: &str) -> Result<ColumnStats, DatabaseError> {
+        let _ = (table, column);
+        Err(DatabaseError::Unsupported(
+            "Column statistics are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Distribution of the values of a numeric or date column in equal-width buckets
+    ///
+    /// See [`crate::histogram`]. Columns of other types are rejected with
+    /// [`DatabaseError::InvalidColumn`]. The default implementation reports
+    /// histograms as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `column` - Name of the column
+    /// * `buckets` - Number of buckets, see [`crate::histogram::bucket_count`]
+    async fn column_histogram(
+        &self,
+        table: &str,
+        column: &str,
+        buckets: u32,
+    ) -> Result<Histogram, DatabaseError> {
+        let _ = (table, column, buckets);
+        Err(DatabaseError::Unsupported(
+            "Histograms are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Groups of rows sharing the values of `columns`, the largest first
+    ///
+    /// See [`crate::duplicates`]. Unknown columns and columns that cannot be
+    /// compared are rejected with [`DatabaseError::InvalidColumn`]. The default
+    /// implementation reports duplicate detection as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `columns` - Columns to group the rows by
+    /// * `limit` - Number of groups to return, at most the maximum page size
+    async fn find_duplicates(
+        &self,
+        table: &str,
+        columns: &[String],
+        limit: u64,
+    ) -> Result<DuplicatesResponse, DatabaseError> {
+        let _ = (table, columns, limit);
+        Err(DatabaseError::Unsupported(
+            "Duplicate detection is not supported by this database".to_string(),
+        ))
+    }
+
+    /// Fingerprint of a table's data that changes when its rows do
+    ///
+    /// Built from the values of [`crate::checksum::checksum_expressions`] in
+    /// a single query. The default implementation reports checksums as
+    /// unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    async fn table_checksum(&self, table: &str) -> Result<TableChecksum, DatabaseError> {
+        let _ = table;
+        Err(DatabaseError::Unsupported(
+            "Table checksums are not supported by this database".to_string(),
+        ))
+    }
+
     /// Execute a raw SQL query
     ///
     /// # Security Warning
@@ -64,11 +241,668 @@ pub trait DatabaseProvider: Send + Sync + 'static {
     /// # Arguments
     ///
     /// * `sql` - SQL query to execute
+    /// * `params` - Values bound to the placeholders of the statement, in order
+    ///   (`?` on SQLite, `$1`, `$2`, ... on PostgreSQL)
     ///
     /// # Returns
     ///
     /// Query results with execution metadata
-    async fn execute_query(&self, sql: &str) -> Result<QueryResult, DatabaseError>;
+    async fn execute_query(
+        &self,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryResult, DatabaseError>;
+
+    /// Execute a raw SQL query that can be cancelled while it runs
+    ///
+    /// Same as `execute_query`, but until the query finishes, `cancel_query`
+    /// with the same identifier stops it. The default implementation runs the
+    /// query without making it cancellable.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier chosen by the client, unique among running queries
+    /// * `sql` - SQL query to execute
+    /// * `params` - Values bound to the placeholders of the statement, in order
+    async fn execute_cancellable_query(
+        &self,
+        id: &str,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryResult, DatabaseError> {
+        let _ = id;
+        self.execute_query(sql, params).await
+    }
+
+    /// Run a statement in a transaction that is rolled back afterwards
+    ///
+    /// Tells how many rows a `DELETE` or `UPDATE` would change without keeping
+    /// the change. The default implementation reports dry runs as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - SQL statement to try
+    /// * `params` - Values bound to the placeholders of the statement, in order
+    ///
+    /// # Returns
+    ///
+    /// The number of rows the statement affected
+    async fn dry_run(&self, sql: &str, params: &[serde_json::Value]) -> Result<u64, DatabaseError> {
+        let _ = (sql, params);
+        Err(DatabaseError::Unsupported(
+            "Dry runs are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Execute a raw SQL query, handing out its rows as they are read
+    ///
+    /// Unlike `execute_query`, the rows are not collected first, so a result
+    /// of any size can be sent without holding it in memory and without the
+    /// row limit. The stream yields the column names before the first row and
+    /// then one line per row; it ends after the last row or the first error.
+    /// Dropping the stream stops the query. The default implementation reports
+    /// streaming as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - SQL query to execute
+    /// * `params` - Values bound to the placeholders of the query, in order
+    ///
+    /// # Returns
+    ///
+    /// Stream of column and row lines
+    async fn stream_query(
+        &self,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryStream, DatabaseError> {
+        let _ = (sql, params);
+        Err(DatabaseError::Unsupported(
+            "Streaming results is not supported by this database".to_string(),
+        ))
+    }
+
+    /// Stop a query started with `execute_cancellable_query`
+    ///
+    /// The query then fails with the database's cancellation error. The
+    /// default implementation reports cancellation as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Identifier the query was started with
+    async fn cancel_query(&self, id: &str) -> Result<(), DatabaseError> {
+        let _ = id;
+        Err(DatabaseError::Unsupported(
+            "Query cancellation is not supported by this database".to_string(),
+        ))
+    }
+
+    /// Statements currently running on the database
+    ///
+    /// The default implementation reports the activity monitor as unsupported.
+    ///
+    /// # Returns
+    ///
+    /// The database's connections and their statements, and the pool's usage
+    async fn activity(&self) -> Result<ActivityResponse, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "The activity monitor is not supported by this database".to_string(),
+        ))
+    }
+
+    /// Run several statements in one transaction
+    ///
+    /// The statements run in order; if one fails, the transaction is rolled
+    /// back and the statements after it are skipped. A failing statement is
+    /// reported in the result rather than as an error. The default
+    /// implementation reports scripts as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `statements` - Individual SQL statements, without bind parameters
+    ///
+    /// # Returns
+    ///
+    /// The statements that ran and, if the script was rolled back, the one that failed
+    async fn execute_script(&self, statements: &[String]) -> Result<ScriptResult, DatabaseError> {
+        let _ = statements;
+        Err(DatabaseError::Unsupported(
+            "Scripts are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Explain how the database would execute a statement, without running it
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - A single SQL statement without bind parameters
+    ///
+    /// # Returns
+    ///
+    /// The lines of the query plan as reported by the database
+    async fn explain_query(&self, sql: &str) -> Result<Vec<String>, DatabaseError>;
+
+    /// Explain a statement as a tree of plan steps
+    ///
+    /// Without `analyze` the statement is not run. With it, the statement is
+    /// run to measure it, and any changes it makes are rolled back where the
+    /// database allows; callers should only analyze read-only statements.
+    ///
+    /// The default implementation reports plan trees as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - A single SQL statement without bind parameters
+    /// * `analyze` - Whether to run the statement for actual rows and timings
+    ///
+    /// # Returns
+    ///
+    /// The plan (see [`crate::explain`]), with `explained_sql` set to `sql`
+    async fn explain_plan(
+        &self,
+        sql: &str,
+        analyze: bool,
+    ) -> Result<ExplainResponse, DatabaseError> {
+        let _ = (sql, analyze);
+        Err(DatabaseError::Unsupported(
+            "Plan trees are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Open a snapshot that subsequent row reads can be pinned to
+    ///
+    /// Reads passing the returned identifier as `RowQuery::snapshot` all see the
+    /// database as it was when the snapshot was opened, so paging through a table
+    /// is not affected by concurrent writes.
+    ///
+    /// The default implementation reports snapshots as unsupported.
+    ///
+    /// # Returns
+    ///
+    /// Identifier of the new snapshot and how long it stays open when unused
+    async fn begin_snapshot(&self) -> Result<SnapshotResponse, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "Snapshot reads are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Fetch a single row identified by its primary key
+    ///
+    /// The default implementation reports single-row access as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `key` - Primary key values, in the order of the table's primary key columns
+    ///
+    /// # Returns
+    ///
+    /// The row, or `None` if no row has this key
+    async fn get_row(
+        &self,
+        table: &str,
+        key: &[String],
+    ) -> Result<Option<serde_json::Value>, DatabaseError> {
+        let _ = (table, key);
+        Err(DatabaseError::Unsupported(
+            "Single-row access is not supported by this database".to_string(),
+        ))
+    }
+
+    /// Count the rows whose `column` equals any of `values`
+    ///
+    /// Used to find rows referencing other rows through a foreign key. The
+    /// values are given as text and compared using the column's type.
+    ///
+    /// The default implementation reports the count as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `column` - Column to compare
+    /// * `values` - Values to look for (no rows match an empty list)
+    ///
+    /// # Returns
+    ///
+    /// Number of matching rows
+    async fn count_rows_with_values(
+        &self,
+        table: &str,
+        column: &str,
+        values: &[String],
+    ) -> Result<u64, DatabaseError> {
+        let _ = (table, column, values);
+        Err(DatabaseError::Unsupported(
+            "Counting rows by value is not supported by this database".to_string(),
+        ))
+    }
+
+    /// Fetch the rows whose `column` equals any of `values`
+    ///
+    /// Used to follow foreign keys in either direction. The values are given as
+    /// text and compared using the column's type.
+    ///
+    /// The default implementation reports the lookup as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `column` - Column to compare
+    /// * `values` - Values to look for (no rows match an empty list)
+    /// * `limit` - Maximum number of rows to return
+    ///
+    /// # Returns
+    ///
+    /// The matching rows, in no particular order
+    async fn get_rows_with_values(
+        &self,
+        table: &str,
+        column: &str,
+        values: &[String],
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>, DatabaseError> {
+        let _ = (table, column, values, limit);
+        Err(DatabaseError::Unsupported(
+            "Fetching rows by value is not supported by this database".to_string(),
+        ))
+    }
+
+    /// Update columns of a single row identified by its primary key
+    ///
+    /// The default implementation reports updates as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `key` - Primary key values, in the order of the table's primary key columns
+    /// * `values` - New values by column name
+    ///
+    /// # Returns
+    ///
+    /// The row as stored after the update
+    async fn update_row(
+        &self,
+        table: &str,
+        key: &[String],
+        values: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<serde_json::Value, DatabaseError> {
+        let _ = (table, key, values);
+        Err(DatabaseError::Unsupported(
+            "Row updates are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Insert rows in a single transaction
+    ///
+    /// Rows are checked with [`validate_new_rows`](crate::row_key::validate_new_rows)
+    /// before anything is written; if any insert fails, the transaction is
+    /// rolled back and no row is inserted.
+    ///
+    /// The default implementation reports inserts as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `rows` - Values of each row by column name; omitted columns get their default
+    ///
+    /// # Returns
+    ///
+    /// Number of rows inserted
+    async fn insert_rows(
+        &self,
+        table: &str,
+        rows: &[serde_json::Map<String, serde_json::Value>],
+    ) -> Result<InsertedRows, DatabaseError> {
+        let _ = (table, rows);
+        Err(DatabaseError::Unsupported(
+            "Row inserts are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Delete rows identified by their primary keys, in a single transaction
+    ///
+    /// Referencing rows along the `cascade` paths are deleted first, deepest
+    /// path first. Referencing rows outside these paths are left to the
+    /// database's own constraints.
+    ///
+    /// The default implementation reports deletes as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `keys` - Primary key values of each row, in the order of the table's primary key columns
+    /// * `cascade` - Paths returned by [`cascade_paths`](crate::references::cascade_paths), or none
+    ///
+    /// # Returns
+    ///
+    /// Number of rows deleted from the table and from referencing tables
+    async fn delete_rows(
+        &self,
+        table: &str,
+        keys: &[Vec<String>],
+        cascade: &[Vec<Reference>],
+    ) -> Result<DeletedRows, DatabaseError> {
+        let _ = (table, keys, cascade);
+        Err(DatabaseError::Unsupported(
+            "Row deletes are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Replace all rows of several tables in one transaction
+    ///
+    /// The tables are emptied last to first, then filled first to last, so
+    /// tables referenced by foreign keys have to come before the tables
+    /// referencing them (see [`crate::fixtures::load_order`]). Nothing is
+    /// changed if any statement fails. The default implementation reports
+    /// fixtures as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `tables` - Tables and their new rows, as objects of values by column name
+    ///
+    /// # Returns
+    ///
+    /// Number of tables replaced and of rows deleted and inserted
+    async fn replace_rows(&self, tables: &[FixtureTable]) -> Result<LoadedFixture, DatabaseError> {
+        let _ = tables;
+        Err(DatabaseError::Unsupported(
+            "Fixtures are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Report whether a seed exists and where it is kept
+    ///
+    /// The default implementation reports seeds as unsupported.
+    async fn seed_status(&self) -> Result<SeedStatus, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "Seeds are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Save the current state of the database as its seed
+    ///
+    /// An existing seed is replaced. The default implementation reports seeds
+    /// as unsupported.
+    ///
+    /// # Returns
+    ///
+    /// The new seed state
+    async fn create_seed(&self) -> Result<SeedStatus, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "Seeds are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Replace the contents of the database with its seed
+    ///
+    /// The default implementation reports seeds as unsupported.
+    ///
+    /// # Returns
+    ///
+    /// The seed state
+    async fn reset_to_seed(&self) -> Result<SeedStatus, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "Seeds are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Release a snapshot opened with `begin_snapshot`
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - Identifier returned by `begin_snapshot`
+    async fn release_snapshot(&self, snapshot: &str) -> Result<(), DatabaseError> {
+        Err(DatabaseError::SnapshotNotFound(snapshot.to_string()))
+    }
+
+    /// List the named snapshots, by name
+    ///
+    /// The default implementation reports named snapshots as unsupported.
+    async fn list_named_snapshots(&self) -> Result<Vec<NamedSnapshot>, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "Named snapshots are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Copy the rows of `table`, or of all tables, into a named snapshot
+    ///
+    /// A snapshot of the same name is replaced. See
+    /// [`named_snapshots`](crate::named_snapshots).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Valid snapshot name (see `validate_snapshot_name`)
+    /// * `table` - Table to copy, or `None` for all tables
+    async fn save_named_snapshot(
+        &self,
+        name: &str,
+        table: Option<&str>,
+    ) -> Result<NamedSnapshot, DatabaseError> {
+        let _ = (name, table);
+        Err(DatabaseError::Unsupported(
+            "Named snapshots are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Replace the rows of the snapshot's tables with those in the snapshot
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the snapshot
+    async fn restore_named_snapshot(&self, name: &str) -> Result<NamedSnapshot, DatabaseError> {
+        Err(DatabaseError::SnapshotNotFound(name.to_string()))
+    }
+
+    /// Delete a named snapshot
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the snapshot
+    async fn delete_named_snapshot(&self, name: &str) -> Result<(), DatabaseError> {
+        Err(DatabaseError::SnapshotNotFound(name.to_string()))
+    }
+
+    /// Read the rows that differ between `table` and its copy in a snapshot
+    ///
+    /// Rows are compared by all columns of the copy.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the snapshot
+    /// * `table` - Name of the table
+    /// * `limit` - Maximum number of rows read from each side
+    async fn named_snapshot_differences(
+        &self,
+        name: &str,
+        table: &str,
+        limit: u64,
+    ) -> Result<SnapshotRowDifferences, DatabaseError> {
+        let _ = (table, limit);
+        Err(DatabaseError::SnapshotNotFound(name.to_string()))
+    }
+
+    /// Run an upkeep operation on `tables`, or on the whole database
+    ///
+    /// The default implementation reports maintenance as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - Operation to run
+    /// * `tables` - Exact table names (from `get_table_schema`); empty for all tables
+    async fn run_maintenance(
+        &self,
+        operation: MaintenanceOperation,
+        tables: &[String],
+    ) -> Result<MaintenanceResult, DatabaseError> {
+        let _ = (operation, tables);
+        Err(DatabaseError::Unsupported(
+            "Maintenance is not supported by this database".to_string(),
+        ))
+    }
+
+    /// Journal mode and write-ahead log size of a SQLite database
+    ///
+    /// The default implementation reports journal information as unsupported.
+    async fn journal_info(&self) -> Result<JournalInfo, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "Journal information is not supported by this database".to_string(),
+        ))
+    }
+
+    /// Statements recreating `table`, for a dump (see [`crate::dump`])
+    ///
+    /// The default implementation reports dumps as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    async fn table_definition(&self, table: &str) -> Result<TableDefinition, DatabaseError> {
+        let _ = table;
+        Err(DatabaseError::Unsupported(
+            "Dumps are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Read a page of rows of `table` for a dump, in a stable order
+    ///
+    /// Rows are objects by column name whose values are the database's text
+    /// form of each value (or null), so they can be written back unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table
+    /// * `columns` - Columns to read, from `table_definition`
+    /// * `snapshot` - Snapshot from `begin_snapshot` to read in, if any
+    /// * `offset` - Number of rows to skip
+    /// * `limit` - Maximum number of rows to read
+    async fn dump_rows(
+        &self,
+        table: &str,
+        columns: &[String],
+        snapshot: Option<&str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>, DatabaseError> {
+        let _ = (table, columns, snapshot, offset, limit);
+        Err(DatabaseError::Unsupported(
+            "Dumps are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Open a console session on a dedicated connection
+    ///
+    /// Statements executed in the session with `execute_in_session` share
+    /// their connection state (open transactions, settings, temporary tables)
+    /// with each other but not with other sessions or requests.
+    ///
+    /// The default implementation reports sessions as unsupported.
+    ///
+    /// # Returns
+    ///
+    /// Identifier of the new session and how long it stays open when unused
+    async fn open_session(&self) -> Result<SessionResponse, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "Console sessions are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Execute a raw SQL statement in a console session
+    ///
+    /// Same as `execute_query`, but on the session's connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - Identifier returned by `open_session`
+    /// * `sql` - SQL statement to execute
+    /// * `params` - Values bound to the placeholders of the statement, in order
+    async fn execute_in_session(
+        &self,
+        session: &str,
+        sql: &str,
+        params: &[serde_json::Value],
+    ) -> Result<QueryResult, DatabaseError> {
+        let _ = (sql, params);
+        Err(DatabaseError::SessionNotFound(session.to_string()))
+    }
+
+    /// Close a console session, rolling back any transaction left open
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - Identifier returned by `open_session`
+    async fn close_session(&self, session: &str) -> Result<(), DatabaseError> {
+        Err(DatabaseError::SessionNotFound(session.to_string()))
+    }
+
+    /// List the database files attached with `attach_database`
+    ///
+    /// The default implementation reports attached databases as unsupported.
+    async fn list_attachments(&self) -> Result<Vec<AttachedDatabase>, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "Attached databases are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Attach another database file, exposing its tables as `alias.table`
+    ///
+    /// The default implementation reports attached databases as unsupported.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - Schema name to attach the file as
+    /// * `path` - Path of an existing database file on the server
+    ///
+    /// # Returns
+    ///
+    /// The new attachment
+    async fn attach_database(
+        &self,
+        alias: &str,
+        path: &str,
+    ) -> Result<AttachedDatabase, DatabaseError> {
+        let _ = (alias, path);
+        Err(DatabaseError::Unsupported(
+            "Attached databases are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Detach a database file attached with `attach_database`
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - Schema name the file is attached as
+    async fn detach_database(&self, alias: &str) -> Result<(), DatabaseError> {
+        Err(DatabaseError::AttachmentNotFound(alias.to_string()))
+    }
+
+    /// A value that changes whenever tables, columns, keys or indexes change
+    ///
+    /// Lets the schema watcher notice changes without loading every schema.
+    /// The default implementation reports fingerprints as unsupported; the
+    /// watcher then compares the full schema instead.
+    async fn schema_fingerprint(&self) -> Result<String, DatabaseError> {
+        Err(DatabaseError::Unsupported(
+            "Schema fingerprints are not supported by this database".to_string(),
+        ))
+    }
+
+    /// Listen for notifications sent to `channels` (PostgreSQL's `LISTEN`)
+    ///
+    /// # Arguments
+    ///
+    /// * `channels` - Channel names, used as they are (not case folded)
+    ///
+    /// # Returns
+    ///
+    /// Stream of notifications on a dedicated connection. It ends with an
+    /// error if the connection is lost for good; notifications sent meanwhile
+    /// are missed.
+    async fn listen(&self, channels: &[String]) -> Result<NotificationStream, DatabaseError> {
+        let _ = channels;
+        Err(DatabaseError::Unsupported(
+            "Notifications are not supported by this database".to_string(),
+        ))
+    }
 }
 
 /// Database error type
@@ -82,13 +916,17 @@ pub enum DatabaseError {
     #[error("Table not found: {0}")]
     TableNotFound(String),
 
+    /// Table name matches several tables that differ only in case
+    #[error("Invalid table name: '{0}' matches {1}; use the exact name")]
+    AmbiguousTable(String, String),
+
     /// Invalid column name
     #[error("Invalid column: {0}")]
     InvalidColumn(String),
 
-    /// Query timeout
-    #[error("Query timeout exceeded")]
-    Timeout,
+    /// Connection acquire or statement timeout
+    #[error("{0} timeout exceeded")]
+    Timeout(TimeoutKind),
 
     /// Result set too large
     #[error("Result set too large (max {0} rows)")]
@@ -97,10 +935,84 @@ pub enum DatabaseError {
     /// Serialization error
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    /// Operation not supported by this database
+    #[error("Not supported: {0}")]
+    Unsupported(String),
+
+    /// Snapshot unknown or expired
+    #[error("Snapshot not found: {0}")]
+    SnapshotNotFound(String),
+
+    /// Pagination cursor is malformed or does not match the query
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
+
+    /// Filter parameter has an unknown operator or an unparseable value
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    /// Row key does not match the table's primary key
+    #[error("Invalid row key: {0}")]
+    InvalidKey(String),
+
+    /// Row to be inserted does not match the table's columns
+    #[error("Invalid row {index}: {reason}")]
+    InvalidRow {
+        /// Position of the row in the request
+        index: usize,
+        /// What is wrong with the row
+        reason: String,
+    },
+
+    /// No row with the given key exists
+    #[error("Row not found: {0}")]
+    RowNotFound(String),
+
+    /// No seed has been created
+    #[error("Seed not found: {0}")]
+    SeedNotFound(String),
+
+    /// Name of a named snapshot is not usable
+    #[error("Invalid snapshot name: {0}")]
+    InvalidSnapshotName(String),
+
+    /// Alias or file of a database to attach is not usable
+    #[error("Invalid attachment: {0}")]
+    InvalidAttachment(String),
+
+    /// No database is attached under the alias
+    #[error("Attachment not found: {0}")]
+    AttachmentNotFound(String),
+
+    /// Console session unknown or expired
+    #[error("Session not found: {0}")]
+    SessionNotFound(String),
+
+    /// No query with the identifier is running
+    #[error("Query not found: {0}")]
+    QueryNotFound(String),
+}
+
+impl DatabaseError {
+    /// Which timeout fired, if this is a timeout
+    pub fn timeout_kind(&self) -> Option<TimeoutKind> {
+        match self {
+            DatabaseError::Timeout(kind) => Some(*kind),
+            _ => None,
+        }
+    }
 }
 
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
 impl From<sqlx::Error> for DatabaseError {
     fn from(error: sqlx::Error) -> Self {
-        DatabaseError::Query(error.to_string())
+        match error {
+            sqlx::Error::PoolTimedOut => DatabaseError::Timeout(TimeoutKind::Acquire),
+            error if crate::database::timeouts::is_statement_timeout(&error) => {
+                DatabaseError::Timeout(TimeoutKind::Statement)
+            }
+            error => DatabaseError::Query(error.to_string()),
+        }
     }
 }
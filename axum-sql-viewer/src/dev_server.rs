@@ -0,0 +1,94 @@
+//! Proxy to a frontend development server
+//!
+//! When the crate is built without the frontend (no `frontend/dist`), requests
+//! for the viewer's pages can be forwarded to a running Vite dev server instead,
+//! so Rust and frontend changes can be worked on side by side with hot reload.
+
+use axum::{
+    body::Body,
+    extract::{OriginalUri, Request},
+    http::{header, Uri, Version},
+    response::Response,
+};
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::TokioExecutor;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::runtime;
+
+/// Time to wait for the dev server when checking whether it is running
+const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Forwards requests to a frontend dev server
+#[derive(Clone)]
+pub struct DevServerProxy {
+    url: Arc<String>,
+    client: Client<HttpConnector, Body>,
+}
+
+impl DevServerProxy {
+    /// Create a proxy for the dev server at `url` (e.g. "http://localhost:5174")
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: Arc::new(url.into().trim_end_matches('/').to_string()),
+            client: Client::builder(TokioExecutor::new()).build_http(),
+        }
+    }
+
+    /// URL of the dev server
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Check whether the dev server is accepting requests
+    pub async fn is_reachable(&self) -> bool {
+        let Ok(uri) = self.url.parse::<Uri>() else {
+            return false;
+        };
+        let request = match Request::get(uri).body(Body::empty()) {
+            Ok(request) => request,
+            Err(_) => return false,
+        };
+
+        matches!(
+            runtime::timeout(REACHABILITY_TIMEOUT, self.client.request(request)).await,
+            Ok(Ok(_))
+        )
+    }
+
+    /// Forward a request to the dev server and return its response
+    ///
+    /// The full original path is forwarded (including the viewer's base path),
+    /// so the dev server must be started with a matching base, e.g.
+    /// `pnpm dev --base /sql-viewer/`.
+    pub async fn forward(&self, request: Request) -> Result<Response, String> {
+        let (mut parts, body) = request.into_parts();
+
+        // Nested routers strip the base path from the URI, the original keeps it
+        let original_uri = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map(|OriginalUri(uri)| uri.clone())
+            .unwrap_or_else(|| parts.uri.clone());
+        let path_and_query = original_uri
+            .path_and_query()
+            .map(|value| value.as_str())
+            .unwrap_or("/");
+
+        parts.uri = format!("{}{}", self.url, path_and_query)
+            .parse()
+            .map_err(|error| format!("Invalid dev server URL: {}", error))?;
+        parts.version = Version::HTTP_11;
+        // Let the client set the host of the dev server
+        parts.headers.remove(header::HOST);
+
+        let response = self
+            .client
+            .request(Request::from_parts(parts, body))
+            .await
+            .map_err(|error| format!("Dev server at {} not reachable: {}", self.url, error))?;
+
+        Ok(response.map(Body::new))
+    }
+}
@@ -0,0 +1,341 @@
+//! Entity-relationship diagram generation
+//!
+//! Turns the discovered table schemas and foreign keys into diagram definitions
+//! that can be pasted into documentation.
+
+use crate::schema::{ColumnInfo, ForeignKey, TableSchema};
+use serde::{Deserialize, Serialize};
+
+/// Cardinality of the referencing side of a foreign key relationship
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Cardinality {
+    /// Each referenced row is referenced by at most one row (unique foreign key column)
+    ZeroOrOne,
+    /// Each referenced row may be referenced by any number of rows
+    ZeroOrMany,
+}
+
+/// Determine the cardinality of the referencing side of a foreign key
+///
+/// A foreign key column that is the sole column of a unique index (or the whole
+/// primary key) can only point at each parent row once.
+pub fn foreign_key_cardinality(schema: &TableSchema, foreign_key: &ForeignKey) -> Cardinality {
+    let is_unique_index = schema
+        .indexes
+        .iter()
+        .any(|index| index.unique && index.columns == std::slice::from_ref(&foreign_key.column));
+    let is_primary_key = schema
+        .primary_key
+        .as_ref()
+        .is_some_and(|columns| columns == std::slice::from_ref(&foreign_key.column));
+
+    if is_unique_index || is_primary_key {
+        Cardinality::ZeroOrOne
+    } else {
+        Cardinality::ZeroOrMany
+    }
+}
+
+/// Whether the foreign key column of a table accepts NULL values
+pub fn foreign_key_is_optional(schema: &TableSchema, foreign_key: &ForeignKey) -> bool {
+    schema
+        .columns
+        .iter()
+        .find(|column| column.name == foreign_key.column)
+        .map(|column| column.nullable)
+        .unwrap_or(true)
+}
+
+/// Normalized relationship graph of the whole database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationshipGraph {
+    /// One node per table
+    pub nodes: Vec<GraphNode>,
+
+    /// One edge per foreign key column
+    pub edges: Vec<GraphEdge>,
+}
+
+/// A table in the relationship graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNode {
+    /// Node identifier (the table name)
+    pub id: String,
+
+    /// Columns of the table
+    pub columns: Vec<ColumnInfo>,
+
+    /// Primary key column names (if any)
+    pub primary_key: Option<Vec<String>>,
+}
+
+/// A foreign key relationship in the relationship graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    /// Edge identifier (`table.column->referenced_table.referenced_column`)
+    pub id: String,
+
+    /// Referencing table
+    pub source: String,
+
+    /// Referencing column
+    pub source_column: String,
+
+    /// Referenced table
+    pub target: String,
+
+    /// Referenced column
+    pub target_column: String,
+
+    /// How many referencing rows may point at the same referenced row
+    pub cardinality: Cardinality,
+
+    /// Whether the referencing column accepts NULL (the relationship is optional)
+    pub optional: bool,
+
+    /// Whether the referenced table is not among the graph's nodes
+    pub dangling: bool,
+}
+
+/// Build the relationship graph for the given tables
+pub fn relationship_graph(schemas: &[TableSchema]) -> RelationshipGraph {
+    let nodes = schemas
+        .iter()
+        .map(|schema| GraphNode {
+            id: schema.name.clone(),
+            columns: schema.columns.clone(),
+            primary_key: schema.primary_key.clone(),
+        })
+        .collect();
+
+    let edges = schemas
+        .iter()
+        .flat_map(|schema| {
+            schema
+                .foreign_keys
+                .iter()
+                .map(move |foreign_key| GraphEdge {
+                    id: format!(
+                        "{}.{}->{}.{}",
+                        schema.name,
+                        foreign_key.column,
+                        foreign_key.references_table,
+                        foreign_key.references_column
+                    ),
+                    source: schema.name.clone(),
+                    source_column: foreign_key.column.clone(),
+                    target: foreign_key.references_table.clone(),
+                    target_column: foreign_key.references_column.clone(),
+                    cardinality: foreign_key_cardinality(schema, foreign_key),
+                    optional: foreign_key_is_optional(schema, foreign_key),
+                    dangling: !schemas
+                        .iter()
+                        .any(|candidate| candidate.name == foreign_key.references_table),
+                })
+        })
+        .collect();
+
+    RelationshipGraph { nodes, edges }
+}
+
+/// Render a Mermaid `erDiagram` definition for the given tables
+///
+/// Every table becomes an entity listing its columns (with `PK`/`FK` markers)
+/// and every foreign key becomes a relationship labelled with the column name.
+pub fn mermaid_er_diagram(schemas: &[TableSchema]) -> String {
+    let mut output = String::from("erDiagram\n");
+
+    for schema in schemas {
+        output.push_str(&format!("    {} {{\n", mermaid_entity_name(&schema.name)));
+
+        for column in &schema.columns {
+            let is_foreign_key = schema
+                .foreign_keys
+                .iter()
+                .any(|foreign_key| foreign_key.column == column.name);
+
+            let mut keys = Vec::new();
+            if column.is_primary_key {
+                keys.push("PK");
+            }
+            if is_foreign_key {
+                keys.push("FK");
+            }
+
+            output.push_str(&format!(
+                "        {} {}",
+                mermaid_attribute_type(&column.data_type),
+                mermaid_attribute_name(&column.name)
+            ));
+            if !keys.is_empty() {
+                output.push_str(&format!(" {}", keys.join(", ")));
+            }
+            output.push('\n');
+        }
+
+        output.push_str("    }\n");
+    }
+
+    for schema in schemas {
+        for foreign_key in &schema.foreign_keys {
+            let child_marker = match foreign_key_cardinality(schema, foreign_key) {
+                Cardinality::ZeroOrOne => "|o",
+                Cardinality::ZeroOrMany => "}o",
+            };
+            let parent_marker = if foreign_key_is_optional(schema, foreign_key) {
+                "o|"
+            } else {
+                "||"
+            };
+
+            output.push_str(&format!(
+                "    {} {}--{} {} : \"{}\"\n",
+                mermaid_entity_name(&schema.name),
+                child_marker,
+                parent_marker,
+                mermaid_entity_name(&foreign_key.references_table),
+                foreign_key.column.replace('"', "'")
+            ));
+        }
+    }
+
+    output
+}
+
+/// Format an entity name, quoting it when it is not a plain identifier
+fn mermaid_entity_name(name: &str) -> String {
+    if is_plain_identifier(name) {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "'"))
+    }
+}
+
+/// Format an attribute name (Mermaid does not support quoting attribute names)
+fn mermaid_attribute_name(name: &str) -> String {
+    sanitize(name, |character| {
+        character.is_ascii_alphanumeric() || character == '_' || character == '-'
+    })
+}
+
+/// Format an attribute type, e.g. `character varying` becomes `character_varying`
+fn mermaid_attribute_type(data_type: &str) -> String {
+    if data_type.is_empty() {
+        return "ANY".to_string();
+    }
+
+    sanitize(data_type, |character| {
+        character.is_ascii_alphanumeric() || "_-()[]".contains(character)
+    })
+}
+
+fn is_plain_identifier(name: &str) -> bool {
+    let mut characters = name.chars();
+    match characters.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => characters.all(|character| {
+            character.is_ascii_alphanumeric() || character == '_' || character == '-'
+        }),
+        _ => false,
+    }
+}
+
+fn sanitize(value: &str, is_allowed: impl Fn(char) -> bool) -> String {
+    let sanitized: String = value
+        .chars()
+        .map(|character| {
+            if is_allowed(character) {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    // Mermaid tokens must not start with a digit
+    if sanitized.starts_with(|character: char| character.is_ascii_digit()) {
+        format!("_{}", sanitized)
+    } else {
+        sanitized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::IndexInfo;
+    use crate::test_support::{column, table, ColumnInfoExt};
+
+    fn sample_schemas() -> Vec<TableSchema> {
+        vec![
+            table("users")
+                .column(column("id", "INTEGER").not_null())
+                .column(column("name", "character varying").not_null())
+                .primary_key(&["id"])
+                .build(),
+            table("orders")
+                .column(column("id", "INTEGER").not_null())
+                .column(column("user_id", "INTEGER"))
+                .primary_key(&["id"])
+                .foreign_key("user_id", "users", "id")
+                .build(),
+        ]
+    }
+
+    #[test]
+    fn test_mermaid_er_diagram() {
+        let diagram = mermaid_er_diagram(&sample_schemas());
+        assert!(diagram.starts_with("erDiagram\n"));
+        assert!(diagram.contains("    users {\n"));
+        assert!(diagram.contains("        INTEGER id PK\n"));
+        assert!(diagram.contains("        character_varying name\n"));
+        assert!(diagram.contains("        INTEGER user_id FK\n"));
+        assert!(diagram.contains("    orders }o--o| users : \"user_id\"\n"));
+    }
+
+    #[test]
+    fn test_foreign_key_cardinality() {
+        let mut schemas = sample_schemas();
+        let orders = &mut schemas[1];
+        let foreign_key = orders.foreign_keys[0].clone();
+        assert_eq!(
+            foreign_key_cardinality(orders, &foreign_key),
+            Cardinality::ZeroOrMany
+        );
+
+        orders.indexes.push(IndexInfo {
+            name: "orders_user_id_unique".to_string(),
+            columns: vec!["user_id".to_string()],
+            unique: true,
+        });
+        assert_eq!(
+            foreign_key_cardinality(orders, &foreign_key),
+            Cardinality::ZeroOrOne
+        );
+    }
+
+    #[test]
+    fn test_relationship_graph() {
+        let graph = relationship_graph(&sample_schemas());
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+
+        let edge = &graph.edges[0];
+        assert_eq!(edge.id, "orders.user_id->users.id");
+        assert_eq!(edge.source, "orders");
+        assert_eq!(edge.target, "users");
+        assert_eq!(edge.cardinality, Cardinality::ZeroOrMany);
+        assert!(edge.optional);
+        assert!(!edge.dangling);
+    }
+
+    #[test]
+    fn test_mermaid_names() {
+        assert_eq!(mermaid_entity_name("order items"), "\"order items\"");
+        assert_eq!(mermaid_attribute_type("VARCHAR(255)"), "VARCHAR(255)");
+        assert_eq!(mermaid_attribute_name("2fa secret"), "_2fa_secret");
+    }
+}
@@ -0,0 +1,292 @@
+//! Logical dumps of PostgreSQL tables
+//!
+//! A dump is a SQL script recreating tables with their rows, laid out like
+//! the output of `pg_dump`: the tables are created first, then filled (with
+//! `COPY ... FROM stdin` blocks, or `INSERT` statements), and sequence
+//! positions, indexes and foreign keys follow once every row is in. Values
+//! are written in PostgreSQL's text form, so they read back exactly.
+//!
+//! The script runs in one transaction, e.g. with
+//! `psql --single-transaction -f dump.sql`.
+
+use serde_json::Value;
+
+use crate::schema::{DumpQuery, TableDefinition};
+use crate::sql::quote_identifier;
+
+/// Writes the parts of a dump, in order
+///
+/// The start comes first, then for every table (in the order given) its
+/// data start, rows and data end, and finally the end of the dump.
+pub struct DumpWriter {
+    tables: Vec<TableDefinition>,
+    schema: bool,
+    data: bool,
+    inserts: bool,
+}
+
+impl DumpWriter {
+    /// Create a writer for `tables`, which are filled in the order given
+    pub fn new(tables: Vec<TableDefinition>, query: &DumpQuery) -> Self {
+        Self {
+            tables,
+            schema: !query.data_only,
+            data: !query.schema_only,
+            inserts: query.inserts,
+        }
+    }
+
+    /// Tables of the dump, in the order they are filled
+    pub fn tables(&self) -> &[TableDefinition] {
+        &self.tables
+    }
+
+    /// Whether rows are written
+    pub fn writes_data(&self) -> bool {
+        self.data
+    }
+
+    /// Header, settings and the statements creating the tables
+    pub fn start(&self) -> String {
+        let mut output = format!(
+            "--\n-- PostgreSQL dump of {} table{}, written by axum-sql-viewer\n--\n\n\
+             SET client_encoding = 'UTF8';\n\
+             SET standard_conforming_strings = on;\n\n\
+             BEGIN;\n",
+            self.tables.len(),
+            if self.tables.len() == 1 { "" } else { "s" }
+        );
+
+        if self.schema {
+            for table in &self.tables {
+                output.push_str(&section("Table", &table.table));
+                output.push_str(&table.create_statement);
+                output.push_str(";\n");
+            }
+        }
+        output
+    }
+
+    /// Comment and `COPY` line before the rows of the table at `index`
+    pub fn table_start(&self, index: usize) -> String {
+        let table = &self.tables[index];
+        let mut output = section("Data for", &table.table);
+        if !self.inserts && !table.columns.is_empty() {
+            output.push_str(&format!(
+                "COPY {} ({}) FROM stdin;\n",
+                quote_identifier(&table.table),
+                column_list(&table.columns)
+            ));
+        }
+        output
+    }
+
+    /// Rows of the table at `index`, as objects of text values by column name
+    pub fn rows(&self, index: usize, rows: &[Value]) -> String {
+        let table = &self.tables[index];
+        if table.columns.is_empty() {
+            return String::new();
+        }
+
+        let mut output = String::new();
+        for row in rows {
+            let values = table
+                .columns
+                .iter()
+                .map(|column| row.get(column).unwrap_or(&Value::Null));
+
+            if self.inserts {
+                let values: Vec<String> = values.map(text_literal).collect();
+                output.push_str(&format!(
+                    "INSERT INTO {} ({}){} VALUES ({});\n",
+                    quote_identifier(&table.table),
+                    column_list(&table.columns),
+                    if table.overriding_system_value {
+                        " OVERRIDING SYSTEM VALUE"
+                    } else {
+                        ""
+                    },
+                    values.join(", ")
+                ));
+            } else {
+                let fields: Vec<String> = values.map(copy_field).collect();
+                output.push_str(&fields.join("\t"));
+                output.push('\n');
+            }
+        }
+        output
+    }
+
+    /// End of the rows of the table at `index`
+    pub fn table_end(&self, index: usize) -> String {
+        if !self.inserts && !self.tables[index].columns.is_empty() {
+            "\\.\n".to_string()
+        } else {
+            String::new()
+        }
+    }
+
+    /// Sequence positions, indexes, foreign keys and the end of the transaction
+    pub fn finish(&self) -> String {
+        let mut statements: Vec<&String> = Vec::new();
+        if self.data {
+            statements.extend(self.tables.iter().flat_map(|table| &table.sequence_resets));
+        }
+        if self.schema {
+            statements.extend(self.tables.iter().flat_map(|table| &table.indexes));
+            statements.extend(self.tables.iter().flat_map(|table| &table.foreign_keys));
+        }
+
+        let mut output = String::new();
+        if !statements.is_empty() {
+            output.push_str("\n--\n-- Sequences, indexes and foreign keys\n--\n\n");
+            for statement in statements {
+                output.push_str(statement);
+                output.push_str(";\n");
+            }
+        }
+        output.push_str("\nCOMMIT;\n");
+        output
+    }
+}
+
+/// Comment introducing a part of the dump
+fn section(title: &str, table: &str) -> String {
+    format!("\n--\n-- {} {}\n--\n\n", title, table)
+}
+
+/// Quoted, comma-separated column names
+fn column_list(columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|column| quote_identifier(column))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Text of a value (non-strings, e.g. from anonymization, as JSON)
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// A value as a field of a `COPY` text row
+///
+/// NULL is `\N`; backslashes and the characters separating fields and rows
+/// are escaped.
+fn copy_field(value: &Value) -> String {
+    let Some(text) = text(value) else {
+        return "\\N".to_string();
+    };
+
+    let mut field = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' => field.push_str("\\\\"),
+            '\t' => field.push_str("\\t"),
+            '\n' => field.push_str("\\n"),
+            '\r' => field.push_str("\\r"),
+            other => field.push(other),
+        }
+    }
+    field
+}
+
+/// A value as a string literal, which PostgreSQL casts to the column's type
+fn text_literal(value: &Value) -> String {
+    match text(value) {
+        Some(text) => format!("'{}'", text.replace('\'', "''")),
+        None => "NULL".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn users() -> TableDefinition {
+        TableDefinition {
+            table: "users".to_string(),
+            create_statement: "CREATE TABLE \"users\" (\n    \"id\" serial NOT NULL\n)".to_string(),
+            columns: vec!["id".to_string(), "bio".to_string()],
+            overriding_system_value: false,
+            sequence_resets: vec!["SELECT setval('users_id_seq', 3, false)".to_string()],
+            indexes: vec!["CREATE INDEX users_bio ON public.users USING btree (bio)".to_string()],
+            foreign_keys: Vec::new(),
+        }
+    }
+
+    fn dump(writer: &DumpWriter, rows: &[Value]) -> String {
+        let mut output = writer.start();
+        if writer.writes_data() {
+            output.push_str(&writer.table_start(0));
+            output.push_str(&writer.rows(0, rows));
+            output.push_str(&writer.table_end(0));
+        }
+        output.push_str(&writer.finish());
+        output
+    }
+
+    #[test]
+    fn test_copy_rows() {
+        let writer = DumpWriter::new(vec![users()], &DumpQuery::default());
+        let rows = [
+            json!({"id": "1", "bio": "tab\there\nback\\slash"}),
+            json!({"id": "2", "bio": null}),
+        ];
+
+        let output = dump(&writer, &rows);
+        assert!(output.contains("CREATE TABLE \"users\" (\n    \"id\" serial NOT NULL\n);\n"));
+        assert!(output.contains(
+            "COPY \"users\" (\"id\", \"bio\") FROM stdin;\n\
+             1\ttab\\there\\nback\\\\slash\n\
+             2\t\\N\n\
+             \\.\n"
+        ));
+        assert!(output.contains("SELECT setval('users_id_seq', 3, false);\n"));
+        assert!(output.ends_with("\nCOMMIT;\n"));
+    }
+
+    #[test]
+    fn test_insert_rows() {
+        let query = DumpQuery {
+            inserts: true,
+            ..DumpQuery::default()
+        };
+        let mut table = users();
+        table.overriding_system_value = true;
+        let writer = DumpWriter::new(vec![table], &query);
+
+        assert_eq!(
+            writer.rows(0, &[json!({"id": "1", "bio": "it's"})]),
+            "INSERT INTO \"users\" (\"id\", \"bio\") OVERRIDING SYSTEM VALUE VALUES ('1', 'it''s');\n"
+        );
+        assert_eq!(writer.table_end(0), "");
+    }
+
+    #[test]
+    fn test_schema_and_data_only() {
+        let schema_only = DumpQuery {
+            schema_only: true,
+            ..DumpQuery::default()
+        };
+        let output = dump(&DumpWriter::new(vec![users()], &schema_only), &[]);
+        assert!(output.contains("CREATE TABLE"));
+        assert!(output.contains("CREATE INDEX"));
+        assert!(!output.contains("COPY"));
+        assert!(!output.contains("setval"));
+
+        let data_only = DumpQuery {
+            data_only: true,
+            ..DumpQuery::default()
+        };
+        let output = dump(&DumpWriter::new(vec![users()], &data_only), &[]);
+        assert!(!output.contains("CREATE"));
+        assert!(output.contains("COPY"));
+        assert!(output.contains("setval"));
+    }
+}
@@ -0,0 +1,193 @@
+//! Groups of rows sharing the values of some columns
+//!
+//! Duplicates are found with `GROUP BY <columns> HAVING COUNT(*) > 1`, the
+//! largest groups first. Like `GROUP BY` itself, NULLs are taken as equal to
+//! each other, so rows with NULL in all the chosen columns form a group.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::codegen::column_kind;
+use crate::column_stats::has_equality;
+use crate::database::traits::DatabaseError;
+use crate::schema::TableSchema;
+use crate::sql::{quote_identifier, Dialect};
+
+/// Number of groups returned when not given
+pub const DEFAULT_DUPLICATE_GROUPS: u64 = 100;
+
+/// Name of the selected row count of a group, read back by [`duplicate_groups`]
+const COUNT_COLUMN: &str = "__duplicate_count";
+
+/// Rows sharing the same values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    /// The shared values, by column name
+    pub values: Map<String, Value>,
+
+    /// Number of rows with these values
+    pub count: u64,
+}
+
+/// Duplicate groups of a table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatesResponse {
+    /// Table name
+    pub table: String,
+
+    /// Columns the rows were grouped by
+    pub columns: Vec<String>,
+
+    /// Groups of more than one row, the largest first
+    pub groups: Vec<DuplicateGroup>,
+
+    /// Whether there are more groups than were returned
+    pub truncated: bool,
+}
+
+/// Check the columns to group `schema`'s rows by
+///
+/// Columns must exist, be given once and be comparable for equality (not
+/// PostgreSQL's `json`).
+pub fn validate_duplicate_columns(
+    schema: &TableSchema,
+    columns: &[String],
+    dialect: Dialect,
+) -> Result<(), DatabaseError> {
+    if columns.is_empty() {
+        return Err(DatabaseError::InvalidColumn(
+            "no columns to find duplicates by".to_string(),
+        ));
+    }
+
+    for (index, column) in columns.iter().enumerate() {
+        let info = schema
+            .columns
+            .iter()
+            .find(|info| &info.name == column)
+            .ok_or_else(|| DatabaseError::InvalidColumn(column.clone()))?;
+        if columns[..index].contains(column) {
+            return Err(DatabaseError::InvalidColumn(format!(
+                "'{}' is given more than once",
+                column
+            )));
+        }
+        if !has_equality(column_kind(&info.data_type), dialect) {
+            return Err(DatabaseError::InvalidColumn(format!(
+                "'{}' is {}, which cannot be compared for equality",
+                column, info.data_type
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Query reading up to `limit + 1` duplicate groups, the largest first
+///
+/// `table` is the quoted table name. The extra group tells whether the
+/// result was truncated.
+pub fn duplicates_query(table: &str, columns: &[String], limit: u64) -> String {
+    let columns = columns
+        .iter()
+        .map(|column| quote_identifier(column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "SELECT {}, COUNT(*) AS {} FROM {} GROUP BY {} HAVING COUNT(*) > 1 \
+         ORDER BY COUNT(*) DESC, {} LIMIT {}",
+        columns,
+        quote_identifier(COUNT_COLUMN),
+        table,
+        columns,
+        columns,
+        limit + 1
+    )
+}
+
+/// Groups of the rows read with [`duplicates_query`], and whether there are more than `limit`
+pub fn duplicate_groups(rows: Vec<Value>, limit: u64) -> (Vec<DuplicateGroup>, bool) {
+    let truncated = rows.len() as u64 > limit;
+    let groups = rows
+        .into_iter()
+        .take(limit as usize)
+        .filter_map(|row| match row {
+            Value::Object(mut values) => {
+                let count = values.remove(COUNT_COLUMN)?.as_u64()?;
+                Some(DuplicateGroup { values, count })
+            }
+            _ => None,
+        })
+        .collect();
+    (groups, truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::table;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_duplicate_columns() {
+        let schema = table("users")
+            .columns(&[("email", "text"), ("settings", "json")])
+            .build();
+        let columns = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(
+            validate_duplicate_columns(&schema, &columns(&["email"]), Dialect::Postgres).is_ok()
+        );
+        assert!(validate_duplicate_columns(&schema, &columns(&[]), Dialect::Sqlite).is_err());
+        assert!(validate_duplicate_columns(&schema, &columns(&["name"]), Dialect::Sqlite).is_err());
+        assert!(validate_duplicate_columns(
+            &schema,
+            &columns(&["email", "email"]),
+            Dialect::Sqlite
+        )
+        .is_err());
+        assert!(
+            validate_duplicate_columns(&schema, &columns(&["settings"]), Dialect::Postgres)
+                .is_err()
+        );
+        assert!(
+            validate_duplicate_columns(&schema, &columns(&["settings"]), Dialect::Sqlite).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_duplicates_query() {
+        assert_eq!(
+            duplicates_query("\"users\"", &["email".to_string(), "name".to_string()], 10),
+            "SELECT \"email\", \"name\", COUNT(*) AS \"__duplicate_count\" FROM \"users\" \
+             GROUP BY \"email\", \"name\" HAVING COUNT(*) > 1 \
+             ORDER BY COUNT(*) DESC, \"email\", \"name\" LIMIT 11"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_groups() {
+        let rows = vec![
+            json!({"email": "a@example.com", "__duplicate_count": 3}),
+            json!({"email": null, "__duplicate_count": 2}),
+        ];
+
+        let (groups, truncated) = duplicate_groups(rows.clone(), 1);
+        assert!(truncated);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(groups[0].values.get("email"), Some(&json!("a@example.com")));
+        assert!(!groups[0].values.contains_key("__duplicate_count"));
+
+        let (groups, truncated) = duplicate_groups(rows, 2);
+        assert!(!truncated);
+        assert_eq!(groups[1].values.get("email"), Some(&Value::Null));
+    }
+}
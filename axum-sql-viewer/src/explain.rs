@@ -0,0 +1,481 @@
+//! Query plan and reference analysis
+//!
+//! Helpers used to explain application queries: extracting the indexes a plan
+//! uses, building plan trees from the databases' EXPLAIN output and matching
+//! the identifiers of a statement against the live schema.
+
+use serde_json::{Map, Value};
+
+use crate::schema::{PlanNode, ReferencedTable, TableSchema};
+use crate::sql::{tokenize, Dialect, Token, TokenKind};
+
+/// Fields of a PostgreSQL plan node that have their own place in [`PlanNode`]
+const POSTGRES_NODE_FIELDS: [&str; 9] = [
+    "Node Type",
+    "Relation Name",
+    "Index Name",
+    "Plan Rows",
+    "Total Cost",
+    "Actual Rows",
+    "Actual Loops",
+    "Actual Total Time",
+    "Plans",
+];
+
+/// Extract the names of indexes used by a query plan
+///
+/// Understands SQLite's `EXPLAIN QUERY PLAN` details (`USING INDEX name`,
+/// `USING COVERING INDEX name`, `USING INTEGER PRIMARY KEY`) and Postgres's
+/// text plans (`Index Scan using name on table`, `Bitmap Index Scan on name`).
+pub fn extract_plan_indexes(plan: &[String]) -> Vec<String> {
+    const NAMED_INDEX_MARKERS: [&str; 5] = [
+        "USING COVERING INDEX ",
+        "USING INDEX ",
+        "Scan Backward using ",
+        "Scan using ",
+        "Bitmap Index Scan on ",
+    ];
+
+    let mut indexes: Vec<String> = Vec::new();
+
+    for line in plan {
+        let mut found = None;
+
+        for marker in NAMED_INDEX_MARKERS {
+            if let Some(position) = line.find(marker) {
+                let rest = &line[position + marker.len()..];
+                let name: String = rest
+                    .chars()
+                    .take_while(|character| !character.is_whitespace() && *character != '(')
+                    .collect();
+                found = Some(name.trim_matches('"').to_string());
+                break;
+            }
+        }
+
+        if found.is_none() && line.contains("PRIMARY KEY") && line.contains("USING") {
+            found = Some("PRIMARY KEY".to_string());
+        }
+
+        if let Some(name) = found {
+            if !name.is_empty() && !indexes.contains(&name) {
+                indexes.push(name);
+            }
+        }
+    }
+
+    indexes
+}
+
+/// Build the plan tree from the rows of SQLite's `EXPLAIN QUERY PLAN`
+///
+/// # Arguments
+///
+/// * `steps` - `(id, parent, detail)` of each row, in the order returned;
+///   steps whose parent is not among them are top-level steps
+pub fn sqlite_plan_tree(steps: &[(i64, i64, String)]) -> Vec<PlanNode> {
+    fn children(steps: &[(i64, i64, String)], parent: i64) -> Vec<PlanNode> {
+        steps
+            .iter()
+            .filter(|(id, step_parent, _)| *step_parent == parent && *id != parent)
+            .map(|(id, _, detail)| PlanNode {
+                children: children(steps, *id),
+                ..sqlite_plan_node(detail)
+            })
+            .collect()
+    }
+
+    steps
+        .iter()
+        .filter(|(_, parent, _)| !steps.iter().any(|(id, _, _)| id == parent))
+        .map(|(id, _, detail)| PlanNode {
+            children: children(steps, *id),
+            ..sqlite_plan_node(detail)
+        })
+        .collect()
+}
+
+/// Plan node for one SQLite plan step, without children
+///
+/// `SCAN table` and `SEARCH table USING ...` steps name the table they read;
+/// other steps (`USE TEMP B-TREE FOR ORDER BY`, `CO-ROUTINE`, ...) are kept
+/// as their whole text.
+fn sqlite_plan_node(detail: &str) -> PlanNode {
+    let (operation, relation) = match detail.split_once(' ') {
+        Some((operation @ ("SCAN" | "SEARCH"), rest)) => {
+            // SQLite before 3.36 wrote `SCAN TABLE name`
+            let rest = rest.strip_prefix("TABLE ").unwrap_or(rest);
+            let relation = rest.split_whitespace().next().map(str::to_string);
+            (operation.to_string(), relation)
+        }
+        _ => (detail.to_string(), None),
+    };
+
+    PlanNode {
+        operation,
+        detail: detail.to_string(),
+        relation,
+        index: extract_plan_indexes(&[detail.to_string()])
+            .into_iter()
+            .next(),
+        ..PlanNode::default()
+    }
+}
+
+/// Build the plan tree from the output of PostgreSQL's `EXPLAIN (FORMAT JSON)`
+///
+/// # Arguments
+///
+/// * `plan` - The JSON document, an array with one `{"Plan": ...}` object per statement
+pub fn postgres_plan_tree(plan: &Value) -> Vec<PlanNode> {
+    plan.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|statement| statement.get("Plan")?.as_object())
+        .map(postgres_plan_node)
+        .collect()
+}
+
+/// Planning and execution time reported by PostgreSQL's `EXPLAIN (ANALYZE, FORMAT JSON)`
+///
+/// # Returns
+///
+/// `(planning, execution)` in milliseconds; `None` if the plan was not analyzed
+pub fn postgres_plan_times(plan: &Value) -> (Option<f64>, Option<f64>) {
+    let statement = plan.get(0);
+    let time = |key: &str| statement.and_then(|statement| statement.get(key)?.as_f64());
+    (time("Planning Time"), time("Execution Time"))
+}
+
+/// Plan node for one PostgreSQL plan node and its subplans
+fn postgres_plan_node(node: &Map<String, Value>) -> PlanNode {
+    let text = |key: &str| node.get(key).and_then(Value::as_str).map(str::to_string);
+    let operation = text("Node Type").unwrap_or_default();
+    let relation = text("Relation Name");
+    let index = text("Index Name");
+
+    // Same shape as the text format, e.g. `Index Scan using users_pkey on users`
+    let mut detail = operation.clone();
+    if let Some(index) = &index {
+        detail.push_str(&format!(" using {}", index));
+    }
+    if let Some(relation) = &relation {
+        detail.push_str(&format!(" on {}", relation));
+    }
+
+    let children: Vec<PlanNode> = node
+        .get("Plans")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+        .map(postgres_plan_node)
+        .collect();
+
+    // `Actual Total Time` is the average of a single run
+    let number = |key: &str| node.get(key).and_then(Value::as_f64);
+    let actual_loops = number("Actual Loops");
+    let actual_time_milliseconds =
+        number("Actual Total Time").map(|time| time * actual_loops.unwrap_or(1.0));
+    let self_time_milliseconds = actual_time_milliseconds.map(|total| {
+        let children_time: f64 = children
+            .iter()
+            .filter_map(|child| child.actual_time_milliseconds)
+            .sum();
+        (total - children_time).max(0.0)
+    });
+
+    PlanNode {
+        operation,
+        detail,
+        relation,
+        index,
+        estimated_rows: number("Plan Rows"),
+        estimated_cost: number("Total Cost"),
+        actual_rows: number("Actual Rows"),
+        actual_loops,
+        actual_time_milliseconds,
+        self_time_milliseconds,
+        properties: node
+            .iter()
+            .filter(|(key, _)| !POSTGRES_NODE_FIELDS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect(),
+        children,
+    }
+}
+
+/// Match the identifiers used in a statement against the known table schemas
+///
+/// Identifiers are compared the way the database compares them (see
+/// [`Token::refers_to`]).
+///
+/// # Returns
+///
+/// The referenced tables (with the columns of each table that appear in the
+/// statement) and the names following `FROM`/`JOIN`/`INTO`/`UPDATE` that do not
+/// match any known table.
+pub fn analyze_references(
+    sql: &str,
+    dialect: Dialect,
+    schemas: &[TableSchema],
+) -> (Vec<ReferencedTable>, Vec<String>) {
+    let tokens: Vec<Token<'_>> = tokenize(sql, dialect)
+        .into_iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+
+    let is_match = |token: &Token<'_>, name: &str| token.refers_to(name, dialect);
+
+    let mut referenced_tables = Vec::new();
+    for schema in schemas {
+        if !tokens.iter().any(|token| is_match(token, &schema.name)) {
+            continue;
+        }
+
+        let columns = schema
+            .columns
+            .iter()
+            .filter(|column| tokens.iter().any(|token| is_match(token, &column.name)))
+            .map(|column| column.name.clone())
+            .collect();
+
+        referenced_tables.push(ReferencedTable {
+            name: schema.name.clone(),
+            columns,
+        });
+    }
+
+    let mut unknown_tables = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        let introduces_table = ["FROM", "JOIN", "INTO", "UPDATE"]
+            .iter()
+            .any(|keyword| token.is_keyword(keyword));
+        if !introduces_table {
+            continue;
+        }
+
+        // Skip a schema qualifier such as `public.users`
+        let mut candidate = tokens.get(index + 1);
+        if tokens.get(index + 2).is_some_and(|token| token.text == ".") {
+            candidate = tokens.get(index + 3);
+        }
+
+        let Some(candidate) = candidate else {
+            continue;
+        };
+        let Some(name) = candidate.identifier() else {
+            continue;
+        };
+
+        let is_known = schemas
+            .iter()
+            .any(|schema| is_match(candidate, &schema.name));
+        if !is_known && !unknown_tables.contains(&name) {
+            unknown_tables.push(name);
+        }
+    }
+
+    (referenced_tables, unknown_tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{ColumnInfo, RowAddress};
+
+    #[test]
+    fn test_extract_plan_indexes() {
+        let sqlite_plan = vec![
+            "SEARCH users USING INDEX users_email (email=?)".to_string(),
+            "SEARCH orders USING INTEGER PRIMARY KEY (rowid=?)".to_string(),
+            "SCAN products".to_string(),
+        ];
+        assert_eq!(
+            extract_plan_indexes(&sqlite_plan),
+            vec!["users_email", "PRIMARY KEY"]
+        );
+
+        let postgres_plan = vec![
+            "Nested Loop  (cost=0.29..16.34 rows=1 width=64)".to_string(),
+            "  ->  Index Scan using users_pkey on users  (cost=0.15..8.17 rows=1 width=32)"
+                .to_string(),
+            "  ->  Bitmap Index Scan on orders_user_id_idx  (cost=0.00..4.16 rows=1 width=0)"
+                .to_string(),
+        ];
+        assert_eq!(
+            extract_plan_indexes(&postgres_plan),
+            vec!["users_pkey", "orders_user_id_idx"]
+        );
+    }
+
+    #[test]
+    fn test_sqlite_plan_tree() {
+        let steps = vec![
+            (
+                2,
+                0,
+                "SEARCH users USING INDEX users_email (email=?)".to_string(),
+            ),
+            (5, 0, "SCAN TABLE orders".to_string()),
+            (7, 5, "CORRELATED SCALAR SUBQUERY 1".to_string()),
+            (9, 7, "SCAN items".to_string()),
+            (12, 0, "USE TEMP B-TREE FOR ORDER BY".to_string()),
+        ];
+
+        let tree = sqlite_plan_tree(&steps);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree[0].operation, "SEARCH");
+        assert_eq!(tree[0].relation.as_deref(), Some("users"));
+        assert_eq!(tree[0].index.as_deref(), Some("users_email"));
+        assert_eq!(tree[1].relation.as_deref(), Some("orders"));
+        assert_eq!(
+            tree[1].children[0].operation,
+            "CORRELATED SCALAR SUBQUERY 1"
+        );
+        assert_eq!(
+            tree[1].children[0].children[0].relation.as_deref(),
+            Some("items")
+        );
+        assert_eq!(tree[2].operation, "USE TEMP B-TREE FOR ORDER BY");
+        assert!(tree[2].children.is_empty());
+    }
+
+    #[test]
+    fn test_postgres_plan_tree() {
+        let plan = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Hash Join",
+                "Join Type": "Inner",
+                "Total Cost": 42.5,
+                "Plan Rows": 10,
+                "Plans": [
+                    {
+                        "Node Type": "Seq Scan",
+                        "Relation Name": "orders",
+                        "Filter": "(total > 100)",
+                        "Plan Rows": 10
+                    },
+                    {
+                        "Node Type": "Index Scan",
+                        "Relation Name": "users",
+                        "Index Name": "users_pkey"
+                    }
+                ]
+            }
+        }]);
+
+        let tree = postgres_plan_tree(&plan);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].detail, "Hash Join");
+        assert_eq!(tree[0].estimated_cost, Some(42.5));
+        assert_eq!(tree[0].estimated_rows, Some(10.0));
+        assert_eq!(
+            tree[0].properties.get("Join Type"),
+            Some(&serde_json::json!("Inner"))
+        );
+        assert!(!tree[0].properties.contains_key("Plans"));
+
+        let children = &tree[0].children;
+        assert_eq!(children[0].detail, "Seq Scan on orders");
+        assert_eq!(
+            children[0].properties.get("Filter"),
+            Some(&serde_json::json!("(total > 100)"))
+        );
+        assert_eq!(children[1].detail, "Index Scan using users_pkey on users");
+        assert_eq!(children[1].index.as_deref(), Some("users_pkey"));
+    }
+
+    #[test]
+    fn test_postgres_analyzed_plan_tree() {
+        let plan = serde_json::json!([{
+            "Plan": {
+                "Node Type": "Nested Loop",
+                "Actual Rows": 20,
+                "Actual Loops": 1,
+                "Actual Total Time": 5.0,
+                "Plans": [
+                    {
+                        "Node Type": "Seq Scan",
+                        "Relation Name": "orders",
+                        "Actual Rows": 20,
+                        "Actual Loops": 1,
+                        "Actual Total Time": 1.5
+                    },
+                    {
+                        "Node Type": "Index Scan",
+                        "Relation Name": "users",
+                        "Index Name": "users_pkey",
+                        "Actual Rows": 1,
+                        "Actual Loops": 20,
+                        "Actual Total Time": 0.1
+                    }
+                ]
+            },
+            "Planning Time": 0.25,
+            "Execution Time": 5.5
+        }]);
+
+        assert_eq!(postgres_plan_times(&plan), (Some(0.25), Some(5.5)));
+
+        let tree = postgres_plan_tree(&plan);
+        let children = &tree[0].children;
+        assert_eq!(children[1].actual_rows, Some(1.0));
+        assert_eq!(children[1].actual_loops, Some(20.0));
+        assert_eq!(children[1].actual_time_milliseconds, Some(2.0));
+        assert_eq!(tree[0].actual_time_milliseconds, Some(5.0));
+        assert_eq!(tree[0].self_time_milliseconds, Some(1.5));
+        assert!(!tree[0].properties.contains_key("Actual Loops"));
+
+        let estimated =
+            postgres_plan_tree(&serde_json::json!([{ "Plan": { "Node Type": "Result" } }]));
+        assert_eq!(estimated[0].actual_time_milliseconds, None);
+        assert_eq!(postgres_plan_times(&serde_json::json!([{}])), (None, None));
+    }
+
+    #[test]
+    fn test_analyze_references() {
+        let schemas = vec![TableSchema {
+            name: "users".to_string(),
+            columns: vec![
+                ColumnInfo {
+                    name: "id".to_string(),
+                    data_type: "INTEGER".to_string(),
+                    nullable: false,
+                    default_value: None,
+                    is_primary_key: true,
+                },
+                ColumnInfo {
+                    name: "email".to_string(),
+                    data_type: "TEXT".to_string(),
+                    nullable: false,
+                    default_value: None,
+                    is_primary_key: false,
+                },
+            ],
+            primary_key: Some(vec!["id".to_string()]),
+            foreign_keys: vec![],
+            indexes: vec![],
+            row_address: RowAddress::PrimaryKey,
+        }];
+
+        let (referenced, unknown) = analyze_references(
+            "SELECT ID FROM Users JOIN accounts ON accounts.user_id = users.id WHERE name = 'email'",
+            Dialect::Sqlite,
+            &schemas,
+        );
+        assert_eq!(referenced.len(), 1);
+        assert_eq!(referenced[0].name, "users");
+        assert_eq!(referenced[0].columns, vec!["id"]);
+        assert_eq!(unknown, vec!["accounts"]);
+
+        // PostgreSQL folds unquoted names to lower case, quoted names keep theirs
+        let (referenced, unknown) = analyze_references(
+            r#"SELECT * FROM Users JOIN "Users" ON true"#,
+            Dialect::Postgres,
+            &schemas,
+        );
+        assert_eq!(referenced.len(), 1);
+        assert_eq!(unknown, vec!["Users"]);
+    }
+}
@@ -0,0 +1,720 @@
+//! Export formatting
+//!
+//! Turns rows (as returned by the providers' `row_to_json`) into export files.
+//! Locale options control how numbers and dates are written and which CSV
+//! delimiter is used, so files open correctly in spreadsheet applications set
+//! up for other regions (e.g. European Excel expects `;` and decimal commas).
+//! CSV options control how NULL is written, which fields are quoted and whether
+//! the file starts with a byte order mark, for tools that are picky about them.
+//! NDJSON and JSON keep the values as returned by the database, one object per
+//! line or as one array; SQL writes one `INSERT` statement per row.
+
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::schema::ExportFormat;
+use crate::sql::{literal, quote_identifier, Dialect};
+use crate::xlsx::XlsxWriter;
+
+/// Locale options for exports
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportOptions {
+    /// Field delimiter for CSV (`,` by default, `;` for European Excel)
+    pub delimiter: char,
+
+    /// Decimal separator for fractional numbers (`.` by default)
+    pub decimal_separator: char,
+
+    /// Output pattern for dates and timestamps (e.g. `%d.%m.%Y %H:%M`)
+    ///
+    /// Supported fields: `%Y` (year), `%m` (month), `%d` (day), `%H` (hour),
+    /// `%M` (minute), `%S` (second) and `%%`. Values are kept as returned by
+    /// the database (ISO 8601) when not set.
+    pub date_format: Option<String>,
+
+    /// How NULL is written to CSV
+    pub null_value: CsvNull,
+
+    /// Which CSV fields are quoted
+    pub quoting: CsvQuoting,
+
+    /// Start CSV files with a UTF-8 byte order mark (lets Excel detect the encoding)
+    #[serde(deserialize_with = "bool_or_text")]
+    pub bom: bool,
+}
+
+/// Deserialize a boolean given as JSON or as query string text
+///
+/// The options are flattened into the export request, which makes query
+/// string values arrive as text even for boolean fields.
+fn bool_or_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrText {
+        Bool(bool),
+        Text(String),
+    }
+
+    match BoolOrText::deserialize(deserializer)? {
+        BoolOrText::Bool(value) => Ok(value),
+        BoolOrText::Text(text) => match text.as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(serde::de::Error::custom(format!(
+                "Invalid boolean: {}",
+                text
+            ))),
+        },
+    }
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            decimal_separator: '.',
+            date_format: None,
+            null_value: CsvNull::default(),
+            quoting: CsvQuoting::default(),
+            bom: false,
+        }
+    }
+}
+
+/// Representation of NULL in CSV
+///
+/// NULL is never quoted, so with [`CsvQuoting::Always`] it can be told apart
+/// from text that looks the same.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CsvNull {
+    /// An empty field
+    #[default]
+    Empty,
+
+    /// `\N`, as used by PostgreSQL `COPY` and MySQL `LOAD DATA`
+    BackslashN,
+
+    /// The word `NULL`
+    Null,
+}
+
+impl CsvNull {
+    /// Text written for NULL
+    pub fn token(self) -> &'static str {
+        match self {
+            CsvNull::Empty => "",
+            CsvNull::BackslashN => "\\N",
+            CsvNull::Null => "NULL",
+        }
+    }
+}
+
+/// Quoting policy for CSV fields
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CsvQuoting {
+    /// Quote fields containing the delimiter, a quote or a line break, and
+    /// text equal to the NULL representation
+    #[default]
+    Minimal,
+
+    /// Quote every field except NULL
+    Always,
+}
+
+impl ExportOptions {
+    /// Check that the options produce readable files
+    pub fn validate(&self) -> Result<(), String> {
+        if matches!(self.delimiter, '"' | '\r' | '\n') {
+            return Err(format!("Invalid delimiter: {:?}", self.delimiter));
+        }
+        if self.null_value.token().contains(self.delimiter) {
+            return Err(format!(
+                "Invalid delimiter for the NULL representation: {:?}",
+                self.delimiter
+            ));
+        }
+        if self.decimal_separator.is_ascii_digit()
+            || matches!(self.decimal_separator, '-' | '\r' | '\n')
+        {
+            return Err(format!(
+                "Invalid decimal separator: {:?}",
+                self.decimal_separator
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Format a value as text according to the locale options
+///
+/// NULL becomes an empty string, arrays and objects their JSON text.
+pub fn format_value(value: &Value, options: &ExportOptions) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(boolean) => boolean.to_string(),
+        Value::Number(number) => {
+            let text = number.to_string();
+            if options.decimal_separator == '.' {
+                text
+            } else {
+                text.replace('.', &options.decimal_separator.to_string())
+            }
+        }
+        Value::String(text) => match &options.date_format {
+            Some(pattern) => format_date(text, pattern).unwrap_or_else(|| text.clone()),
+            None => text.clone(),
+        },
+        other => other.to_string(),
+    }
+}
+
+/// Format one CSV record of text fields (including the line ending)
+///
+/// Fields are quoted according to the quoting policy of the options.
+pub fn csv_record<I, S>(fields: I, options: &ExportOptions) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    csv_line(
+        fields
+            .into_iter()
+            .map(|field| csv_field(Some(field.as_ref()), options)),
+        options,
+    )
+}
+
+/// Format a row as a CSV record with the values of `columns`, in order
+pub fn csv_row(row: &Value, columns: &[String], options: &ExportOptions) -> String {
+    csv_line(
+        columns.iter().map(|column| match row.get(column) {
+            None | Some(Value::Null) => csv_field(None, options),
+            Some(value) => csv_field(Some(&format_value(value, options)), options),
+        }),
+        options,
+    )
+}
+
+/// Join formatted fields into a record
+fn csv_line(fields: impl Iterator<Item = String>, options: &ExportOptions) -> String {
+    let fields: Vec<String> = fields.collect();
+    format!("{}\r\n", fields.join(&options.delimiter.to_string()))
+}
+
+/// Format a single CSV field, `None` being NULL
+fn csv_field(field: Option<&str>, options: &ExportOptions) -> String {
+    let Some(field) = field else {
+        return options.null_value.token().to_string();
+    };
+
+    let null_token = options.null_value.token();
+    let needs_quotes = match options.quoting {
+        CsvQuoting::Always => true,
+        CsvQuoting::Minimal => {
+            field.contains(options.delimiter)
+                || field.contains(['"', '\r', '\n'])
+                || (!null_token.is_empty() && field == null_token)
+        }
+    };
+
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Format rows as CSV records with the values of `columns`, in order
+pub fn csv_rows(rows: &[Value], columns: &[String], options: &ExportOptions) -> String {
+    rows.iter()
+        .map(|row| csv_row(row, columns, options))
+        .collect()
+}
+
+/// Format a row as an NDJSON line with the values of `columns`, in order
+///
+/// Values are written as returned by the database; the locale options do not
+/// apply. Missing columns are written as `null`.
+pub fn ndjson_row(row: &Value, columns: &[String]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .map(|column| {
+            format!(
+                "{}:{}",
+                Value::String(column.clone()),
+                row.get(column).unwrap_or(&Value::Null)
+            )
+        })
+        .collect();
+    format!("{{{}}}\n", fields.join(","))
+}
+
+/// Format a row as an `INSERT` statement for `table` with the values of `columns`
+///
+/// Values are written as literals of `dialect`; the locale options do not
+/// apply. Missing columns are written as `NULL`.
+pub fn sql_insert(table: &str, row: &Value, columns: &[String], dialect: Dialect) -> String {
+    let names: Vec<String> = columns
+        .iter()
+        .map(|column| quote_identifier(column))
+        .collect();
+    let values: Vec<String> = columns
+        .iter()
+        .map(|column| literal(row.get(column).unwrap_or(&Value::Null), dialect))
+        .collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({});\n",
+        quote_identifier(table),
+        names.join(", "),
+        values.join(", ")
+    )
+}
+
+impl ExportFormat {
+    /// File name extension of the format
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Json => "json",
+            ExportFormat::Sql => "sql",
+        }
+    }
+
+    /// Media type of the format
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Sql => "application/sql; charset=utf-8",
+        }
+    }
+}
+
+/// Encoder turning pages of rows into the bytes of an export file
+///
+/// Call [`start`](ExportEncoder::start) once, then [`rows`](ExportEncoder::rows)
+/// for each page and [`finish`](ExportEncoder::finish) at the end; the returned
+/// bytes, concatenated, form the file.
+pub enum ExportEncoder {
+    /// CSV records formatted with the locale options
+    Csv {
+        columns: Vec<String>,
+        options: ExportOptions,
+    },
+
+    /// Excel workbook with a single worksheet (numbers and booleans keep their type)
+    Xlsx(XlsxWriter),
+
+    /// One JSON object per row (no header)
+    Ndjson { columns: Vec<String> },
+
+    /// A JSON array with one object per row
+    Json {
+        columns: Vec<String>,
+        /// Whether a row was written, so the next one needs a comma
+        started: bool,
+    },
+
+    /// One `INSERT` statement per row
+    Sql {
+        table: String,
+        columns: Vec<String>,
+        dialect: Dialect,
+    },
+}
+
+impl ExportEncoder {
+    /// Create an encoder for the values of `columns`, in order
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - File format
+    /// * `table` - Name of the exported table (the worksheet name for XLSX)
+    /// * `columns` - Columns to export
+    /// * `options` - Locale options (CSV only)
+    /// * `dialect` - Dialect of the database (SQL only)
+    pub fn new(
+        format: ExportFormat,
+        table: &str,
+        columns: Vec<String>,
+        options: ExportOptions,
+        dialect: Dialect,
+    ) -> Self {
+        match format {
+            ExportFormat::Csv => ExportEncoder::Csv { columns, options },
+            ExportFormat::Xlsx => ExportEncoder::Xlsx(XlsxWriter::new(table, columns)),
+            ExportFormat::Ndjson => ExportEncoder::Ndjson { columns },
+            ExportFormat::Json => ExportEncoder::Json {
+                columns,
+                started: false,
+            },
+            ExportFormat::Sql => ExportEncoder::Sql {
+                table: table.to_string(),
+                columns,
+                dialect,
+            },
+        }
+    }
+
+    /// Start of the file, including the header row
+    pub fn start(&mut self) -> Vec<u8> {
+        match self {
+            ExportEncoder::Csv { columns, options } => {
+                let mut start = if options.bom {
+                    "\u{FEFF}".to_string()
+                } else {
+                    String::new()
+                };
+                start.push_str(&csv_record(&*columns, options));
+                start.into_bytes()
+            }
+            ExportEncoder::Xlsx(writer) => writer.start(),
+            ExportEncoder::Json { .. } => b"[".to_vec(),
+            ExportEncoder::Ndjson { .. } | ExportEncoder::Sql { .. } => Vec::new(),
+        }
+    }
+
+    /// Encode a page of rows
+    pub fn rows(&mut self, rows: &[Value]) -> Result<Vec<u8>, String> {
+        match self {
+            ExportEncoder::Csv { columns, options } => {
+                Ok(csv_rows(rows, columns, options).into_bytes())
+            }
+            ExportEncoder::Xlsx(writer) => writer.rows(rows),
+            ExportEncoder::Ndjson { columns } => Ok(rows
+                .iter()
+                .map(|row| ndjson_row(row, columns))
+                .collect::<String>()
+                .into_bytes()),
+            ExportEncoder::Json { columns, started } => {
+                let mut encoded = String::new();
+                for row in rows {
+                    // The NDJSON line without its newline is the row's object
+                    let object = ndjson_row(row, columns);
+                    encoded.push_str(if *started { ",\n" } else { "\n" });
+                    encoded.push_str(object.trim_end());
+                    *started = true;
+                }
+                Ok(encoded.into_bytes())
+            }
+            ExportEncoder::Sql {
+                table,
+                columns,
+                dialect,
+            } => Ok(rows
+                .iter()
+                .map(|row| sql_insert(table, row, columns, *dialect))
+                .collect::<String>()
+                .into_bytes()),
+        }
+    }
+
+    /// Encode the rows of a page until `budget` bytes are used up
+    ///
+    /// Rows are encoded one at a time. Encoders keep state between rows, so a
+    /// row cannot be taken back once encoded: the row that crosses the budget
+    /// is kept, and the rows after it are left out. Without a budget the whole
+    /// page is encoded.
+    ///
+    /// # Returns
+    ///
+    /// The encoded rows and how many of the page's rows they contain
+    pub fn rows_within(
+        &mut self,
+        rows: &[Value],
+        budget: Option<u64>,
+    ) -> Result<(Vec<u8>, usize), String> {
+        let Some(budget) = budget else {
+            return Ok((self.rows(rows)?, rows.len()));
+        };
+
+        let mut encoded = Vec::new();
+        for (index, row) in rows.iter().enumerate() {
+            if encoded.len() as u64 >= budget {
+                return Ok((encoded, index));
+            }
+            encoded.extend(self.rows(std::slice::from_ref(row))?);
+        }
+
+        Ok((encoded, rows.len()))
+    }
+
+    /// End of the file
+    pub fn finish(&mut self) -> Vec<u8> {
+        match self {
+            ExportEncoder::Csv { .. }
+            | ExportEncoder::Ndjson { .. }
+            | ExportEncoder::Sql { .. } => Vec::new(),
+            ExportEncoder::Json { .. } => b"\n]\n".to_vec(),
+            ExportEncoder::Xlsx(writer) => writer.finish(),
+        }
+    }
+}
+
+/// Reformat an ISO 8601 date or timestamp (`YYYY-MM-DD[( |T)HH:MM[:SS[.fff]]][zone]`)
+///
+/// Returns `None` if `text` is not a date, so other strings are left alone.
+fn format_date(text: &str, pattern: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let digits = |range: std::ops::Range<usize>| -> Option<&str> {
+        let part = text.get(range)?;
+        part.bytes()
+            .all(|byte| byte.is_ascii_digit())
+            .then_some(part)
+    };
+
+    let year = digits(0..4)?;
+    let month = digits(5..7)?;
+    let day = digits(8..10)?;
+    if bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+
+    let (hour, minute, second) = if bytes.len() > 10 {
+        if !matches!(bytes[10], b' ' | b'T') || bytes.get(13) != Some(&b':') {
+            return None;
+        }
+        let second = match bytes.get(16) {
+            Some(b':') => digits(17..19)?,
+            _ => "00",
+        };
+        (digits(11..13)?, digits(14..16)?, second)
+    } else {
+        ("00", "00", "00")
+    };
+
+    let mut output = String::with_capacity(pattern.len() + 8);
+    let mut characters = pattern.chars();
+    while let Some(character) = characters.next() {
+        if character != '%' {
+            output.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('Y') => output.push_str(year),
+            Some('m') => output.push_str(month),
+            Some('d') => output.push_str(day),
+            Some('H') => output.push_str(hour),
+            Some('M') => output.push_str(minute),
+            Some('S') => output.push_str(second),
+            Some('%') => output.push('%'),
+            Some(other) => {
+                output.push('%');
+                output.push(other);
+            }
+            None => output.push('%'),
+        }
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn european() -> ExportOptions {
+        ExportOptions {
+            delimiter: ';',
+            decimal_separator: ',',
+            date_format: Some("%d.%m.%Y %H:%M".to_string()),
+            ..ExportOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_format_value_with_locale() {
+        let options = european();
+        assert_eq!(format_value(&json!(1234.5), &options), "1234,5");
+        assert_eq!(format_value(&json!(42), &options), "42");
+        assert_eq!(format_value(&json!(null), &options), "");
+        assert_eq!(
+            format_value(&json!("2024-03-07T09:15:00Z"), &options),
+            "07.03.2024 09:15"
+        );
+        assert_eq!(
+            format_value(&json!("2024-03-07"), &options),
+            "07.03.2024 00:00"
+        );
+        assert_eq!(format_value(&json!("not a date"), &options), "not a date");
+        assert_eq!(
+            format_value(&json!("2024-03-07 extra"), &options),
+            "2024-03-07 extra"
+        );
+    }
+
+    #[test]
+    fn test_csv_row_quoting() {
+        let options = european();
+        let row = json!({ "id": 1, "price": 9.99, "note": "a; \"b\"" });
+        let columns = vec!["id".to_string(), "price".to_string(), "note".to_string()];
+
+        assert_eq!(
+            csv_row(&row, &columns, &options),
+            "1;9,99;\"a; \"\"b\"\"\"\r\n"
+        );
+        assert_eq!(
+            csv_row(&row, &columns, &ExportOptions::default()),
+            "1,9.99,\"a; \"\"b\"\"\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_null_and_quoting() {
+        let row = json!({ "id": 1, "note": null, "code": "NULL", "empty": "" });
+        let columns: Vec<String> = ["id", "note", "code", "empty"]
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+
+        let literal = ExportOptions {
+            null_value: CsvNull::Null,
+            ..ExportOptions::default()
+        };
+        assert_eq!(csv_row(&row, &columns, &literal), "1,NULL,\"NULL\",\r\n");
+
+        let escaped = ExportOptions {
+            null_value: CsvNull::BackslashN,
+            quoting: CsvQuoting::Always,
+            ..ExportOptions::default()
+        };
+        assert_eq!(
+            csv_row(&row, &columns, &escaped),
+            "\"1\",\\N,\"NULL\",\"\"\r\n"
+        );
+
+        let mut encoder = ExportEncoder::new(
+            ExportFormat::Csv,
+            "notes",
+            vec!["id".to_string()],
+            ExportOptions {
+                bom: true,
+                ..ExportOptions::default()
+            },
+            Dialect::Sqlite,
+        );
+        assert_eq!(encoder.start(), "\u{FEFF}id\r\n".as_bytes());
+    }
+
+    #[test]
+    fn test_ndjson_rows() {
+        let columns = vec!["id".to_string(), "name".to_string(), "tags".to_string()];
+        let mut encoder = ExportEncoder::new(
+            ExportFormat::Ndjson,
+            "users",
+            columns,
+            ExportOptions::default(),
+            Dialect::Sqlite,
+        );
+        assert!(encoder.start().is_empty());
+
+        let rows = vec![
+            json!({ "tags": ["a", "b"], "name": "Ann \"A\"\n", "id": 1 }),
+            json!({ "id": 2.5, "name": null }),
+        ];
+        assert_eq!(
+            String::from_utf8(encoder.rows(&rows).unwrap()).unwrap(),
+            "{\"id\":1,\"name\":\"Ann \\\"A\\\"\\n\",\"tags\":[\"a\",\"b\"]}\n\
+             {\"id\":2.5,\"name\":null,\"tags\":null}\n"
+        );
+        assert!(encoder.finish().is_empty());
+    }
+
+    #[test]
+    fn test_json_rows() {
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let mut encoder = ExportEncoder::new(
+            ExportFormat::Json,
+            "users",
+            columns,
+            ExportOptions::default(),
+            Dialect::Sqlite,
+        );
+
+        let mut file = encoder.start();
+        file.extend(encoder.rows(&[json!({ "id": 1, "name": "Ann" })]).unwrap());
+        file.extend(encoder.rows(&[json!({ "id": 2 })]).unwrap());
+        file.extend(encoder.finish());
+        let file: Value = serde_json::from_slice(&file).unwrap();
+        assert_eq!(
+            file,
+            json!([{ "id": 1, "name": "Ann" }, { "id": 2, "name": null }])
+        );
+
+        // Without rows the file is still an array
+        let mut encoder = ExportEncoder::new(
+            ExportFormat::Json,
+            "users",
+            vec![],
+            ExportOptions::default(),
+            Dialect::Sqlite,
+        );
+        let mut file = encoder.start();
+        file.extend(encoder.finish());
+        assert_eq!(serde_json::from_slice::<Value>(&file).unwrap(), json!([]));
+    }
+
+    #[test]
+    fn test_sql_inserts() {
+        let columns = vec!["id".to_string(), "name".to_string(), "active".to_string()];
+        let row = json!({ "id": 7, "name": "O'Brien", "active": true });
+        assert_eq!(
+            sql_insert("users", &row, &columns, Dialect::Postgres),
+            "INSERT INTO \"users\" (\"id\", \"name\", \"active\") VALUES (7, 'O''Brien', TRUE);\n"
+        );
+
+        let mut encoder = ExportEncoder::new(
+            ExportFormat::Sql,
+            "users",
+            columns,
+            ExportOptions::default(),
+            Dialect::Sqlite,
+        );
+        assert_eq!(
+            String::from_utf8(encoder.rows(&[json!({ "id": 8 })]).unwrap()).unwrap(),
+            "INSERT INTO \"users\" (\"id\", \"name\", \"active\") VALUES (8, NULL, NULL);\n"
+        );
+    }
+
+    #[test]
+    fn test_rows_within_budget() {
+        let mut encoder = ExportEncoder::new(
+            ExportFormat::Ndjson,
+            "users",
+            vec!["id".to_string()],
+            ExportOptions::default(),
+            Dialect::Sqlite,
+        );
+        let rows = vec![json!({ "id": 1 }), json!({ "id": 2 }), json!({ "id": 3 })];
+
+        // Each row is 9 bytes: {"id":1}\n; the row crossing the budget is kept
+        let (encoded, taken) = encoder.rows_within(&rows, Some(12)).unwrap();
+        assert_eq!(taken, 2);
+        assert_eq!(encoded.len(), 18);
+        assert_eq!(encoder.rows_within(&rows, Some(18)).unwrap().1, 2);
+        assert_eq!(encoder.rows_within(&rows, None).unwrap().1, 3);
+        assert_eq!(encoder.rows_within(&rows, Some(0)).unwrap().1, 0);
+    }
+
+    #[test]
+    fn test_validate_options() {
+        assert!(european().validate().is_ok());
+        assert!(ExportOptions {
+            delimiter: '"',
+            ..ExportOptions::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! Export size guardrails
+//!
+//! Exports of huge tables can be limited by number of rows and by file size,
+//! for all tables or per table. Instead of failing, an export over its limits
+//! is by default cut off after the first rows, or (on request) reduced to a
+//! sample spread evenly over the table, and an [`ExportManifest`] explains what
+//! was left out. The row limit is checked against a count of the matching rows
+//! before the export starts; the size limit is checked while the file is
+//! written and does not include the end of the file (e.g. the XLSX archive
+//! directory).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::schema::{ExportManifest, OnExportLimit};
+
+/// Rows read per window of a sample
+///
+/// Small enough for a sample to cover many parts of the table, large enough
+/// to keep the number of queries low.
+const SAMPLE_WINDOW_ROWS: u64 = 100;
+
+/// Size limits of an export (`None` means unlimited)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportLimits {
+    /// Maximum number of rows
+    pub max_rows: Option<u64>,
+
+    /// Maximum file size in bytes
+    pub max_bytes: Option<u64>,
+}
+
+impl ExportLimits {
+    /// Whether any limit is set
+    pub fn is_limited(&self) -> bool {
+        self.max_rows.is_some() || self.max_bytes.is_some()
+    }
+}
+
+/// Configured export limits: a default and overrides by table name
+///
+/// Cloning `ExportGuardrails` is cheap; all clones share the same configuration.
+#[derive(Debug, Clone, Default)]
+pub struct ExportGuardrails {
+    default: ExportLimits,
+    tables: Arc<HashMap<String, ExportLimits>>,
+}
+
+impl ExportGuardrails {
+    /// Create the configuration from default limits and limits by table name
+    pub fn new(default: ExportLimits, tables: HashMap<String, ExportLimits>) -> Self {
+        Self {
+            default,
+            tables: Arc::new(tables),
+        }
+    }
+
+    /// Limits for exports of a table
+    pub fn for_table(&self, table: &str) -> ExportLimits {
+        self.tables.get(table).copied().unwrap_or(self.default)
+    }
+}
+
+/// Consecutive rows to export, starting at `offset` in sort order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowWindow {
+    /// Position of the first row
+    pub offset: u64,
+
+    /// Number of rows
+    pub rows: u64,
+}
+
+/// Decide which rows to export
+///
+/// # Arguments
+///
+/// * `total` - Number of rows matching the export (counted only if a row limit is set)
+/// * `limits` - Limits for the table
+/// * `on_limit` - What to do if there are more rows than the limit
+///
+/// # Returns
+///
+/// The windows to read in order, and a manifest if rows are left out, or an
+/// error if the export is refused
+pub fn plan_rows(
+    total: Option<u64>,
+    limits: ExportLimits,
+    on_limit: OnExportLimit,
+) -> Result<(Vec<RowWindow>, Option<ExportManifest>), String> {
+    let Some(max_rows) = limits.max_rows else {
+        let everything = RowWindow {
+            offset: 0,
+            rows: u64::MAX,
+        };
+        return Ok((vec![everything], None));
+    };
+    let first_rows = RowWindow {
+        offset: 0,
+        rows: max_rows,
+    };
+
+    let total = match total {
+        Some(total) if total > max_rows => total,
+        _ => return Ok((vec![first_rows], None)),
+    };
+
+    let (windows, warning) = match on_limit {
+        OnExportLimit::Fail => {
+            return Err(format!(
+                "Export too large: {} rows exceed the limit of {} rows",
+                total, max_rows
+            ))
+        }
+        OnExportLimit::Truncate => (
+            vec![first_rows],
+            format!(
+                "Only the first {} of {} rows were exported (row limit)",
+                max_rows, total
+            ),
+        ),
+        OnExportLimit::Sample => (
+            sample_windows(total, max_rows),
+            format!(
+                "A sample of {} of {} rows, spread evenly over the table, was exported (row limit)",
+                max_rows, total
+            ),
+        ),
+    };
+
+    let manifest = ExportManifest {
+        mode: on_limit,
+        total_rows: Some(total),
+        warnings: vec![warning],
+    };
+    Ok((windows, Some(manifest)))
+}
+
+/// Windows of about [`SAMPLE_WINDOW_ROWS`] rows spread evenly over `total`
+/// rows, adding up to at most `max_rows` rows
+pub fn sample_windows(total: u64, max_rows: u64) -> Vec<RowWindow> {
+    if max_rows == 0 {
+        return Vec::new();
+    }
+    if total <= max_rows {
+        return vec![RowWindow {
+            offset: 0,
+            rows: total,
+        }];
+    }
+
+    let count = max_rows.div_ceil(SAMPLE_WINDOW_ROWS);
+    let offset_of = |index: u64| (u128::from(index) * u128::from(total) / u128::from(count)) as u64;
+
+    (0..count)
+        .map(|index| {
+            let offset = offset_of(index);
+            // Spread the rows over the windows; a window never reaches into the next one
+            let rows = max_rows / count + u64::from(index < max_rows % count);
+            RowWindow {
+                offset,
+                rows: rows.min(offset_of(index + 1) - offset),
+            }
+        })
+        .collect()
+}
+
+/// Add the warning for an export cut off at its size limit to the manifest
+///
+/// # Arguments
+///
+/// * `manifest` - Manifest of the row limit, if any
+/// * `on_limit` - What the export was asked to do at its limits
+/// * `rows` - Rows written before the limit was reached
+/// * `max_bytes` - The size limit
+pub fn byte_limit_manifest(
+    manifest: Option<ExportManifest>,
+    on_limit: OnExportLimit,
+    rows: u64,
+    max_bytes: u64,
+) -> ExportManifest {
+    let mut manifest = manifest.unwrap_or(ExportManifest {
+        mode: on_limit,
+        total_rows: None,
+        warnings: Vec::new(),
+    });
+    manifest.warnings.push(format!(
+        "The export was cut off after {} rows at the limit of {} bytes",
+        rows, max_bytes
+    ));
+    manifest
+}
+
+/// Position of an export in its windows
+///
+/// Each window is read in pages; a window ends early when the table has no
+/// more rows, which also ends the export.
+#[derive(Debug, Clone)]
+pub struct ExportPages {
+    windows: Vec<RowWindow>,
+    current: usize,
+    read: u64,
+}
+
+impl ExportPages {
+    /// Start at the beginning of the first window
+    pub fn new(mut windows: Vec<RowWindow>) -> Self {
+        windows.retain(|window| window.rows > 0);
+        Self {
+            windows,
+            current: 0,
+            read: 0,
+        }
+    }
+
+    /// Offset and limit of the next page, or `None` when the export is complete
+    pub fn next_page(&self, page_size: u64) -> Option<(u64, u64)> {
+        let window = self.windows.get(self.current)?;
+        Some((
+            window.offset + self.read,
+            (window.rows - self.read).min(page_size),
+        ))
+    }
+
+    /// Move past a page that returned `rows` rows
+    ///
+    /// `has_more` tells whether the table has rows after the page.
+    pub fn advance(&mut self, rows: u64, has_more: bool) {
+        let Some(window) = self.windows.get(self.current) else {
+            return;
+        };
+
+        self.read += rows;
+        if rows == 0 || !has_more {
+            // Later windows start further into the table
+            self.current = self.windows.len();
+        } else if self.read >= window.rows {
+            self.current += 1;
+            self.read = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_rows() {
+        let limits = ExportLimits {
+            max_rows: Some(1000),
+            max_bytes: None,
+        };
+
+        let (windows, manifest) =
+            plan_rows(None, ExportLimits::default(), OnExportLimit::Fail).unwrap();
+        assert_eq!(windows[0].rows, u64::MAX);
+        assert!(manifest.is_none());
+
+        let (windows, manifest) = plan_rows(Some(800), limits, OnExportLimit::Fail).unwrap();
+        assert_eq!(
+            windows,
+            vec![RowWindow {
+                offset: 0,
+                rows: 1000
+            }]
+        );
+        assert!(manifest.is_none());
+
+        let (windows, manifest) = plan_rows(Some(5000), limits, OnExportLimit::Truncate).unwrap();
+        assert_eq!(
+            windows,
+            vec![RowWindow {
+                offset: 0,
+                rows: 1000
+            }]
+        );
+        let manifest = manifest.unwrap();
+        assert_eq!(manifest.total_rows, Some(5000));
+        assert_eq!(manifest.mode, OnExportLimit::Truncate);
+
+        let (windows, _) = plan_rows(Some(5000), limits, OnExportLimit::Sample).unwrap();
+        assert_eq!(windows.len(), 10);
+
+        assert!(plan_rows(Some(5000), limits, OnExportLimit::Fail)
+            .unwrap_err()
+            .contains("too large"));
+    }
+
+    #[test]
+    fn test_sample_windows() {
+        let windows = sample_windows(1_000_000, 250);
+        assert_eq!(
+            windows,
+            vec![
+                RowWindow {
+                    offset: 0,
+                    rows: 84
+                },
+                RowWindow {
+                    offset: 333_333,
+                    rows: 83
+                },
+                RowWindow {
+                    offset: 666_666,
+                    rows: 83
+                },
+            ]
+        );
+
+        // Windows never overlap, even when the sample is nearly the whole table
+        for (total, max_rows) in [(150, 120), (1001, 1000), (101, 100), (7, 3)] {
+            let windows = sample_windows(total, max_rows);
+            let rows: u64 = windows.iter().map(|window| window.rows).sum();
+            assert!(rows <= max_rows);
+            assert!(rows > max_rows - windows.len() as u64);
+            for pair in windows.windows(2) {
+                assert!(pair[0].offset + pair[0].rows <= pair[1].offset);
+            }
+            assert!(windows.last().unwrap().offset < total);
+        }
+    }
+
+    #[test]
+    fn test_export_pages() {
+        let mut pages = ExportPages::new(vec![
+            RowWindow {
+                offset: 0,
+                rows: 700,
+            },
+            RowWindow {
+                offset: 5000,
+                rows: 100,
+            },
+            RowWindow {
+                offset: 9000,
+                rows: 100,
+            },
+        ]);
+
+        assert_eq!(pages.next_page(500), Some((0, 500)));
+        pages.advance(500, true);
+        assert_eq!(pages.next_page(500), Some((500, 200)));
+        pages.advance(200, true);
+        assert_eq!(pages.next_page(500), Some((5000, 100)));
+        // The table ended inside the second window
+        pages.advance(40, false);
+        assert_eq!(pages.next_page(500), None);
+    }
+}
@@ -0,0 +1,498 @@
+//! Column filters read from `filter[...]` query parameters
+//!
+//! `filter[column]=value` matches a column's value (with `%` wildcards), and
+//! `filter[column][gte]=2024-01-01T00:00:00Z` compares it with a date or
+//! timestamp. Timestamps compared as text mostly do not work (`2024-01-01
+//! 10:00:00` sorts before `2024-01-01T00:00:00Z`), so range values are parsed
+//! as ISO 8601 and compared as points in time: SQLite through `julianday()`,
+//! which understands the usual text formats, and PostgreSQL by casting the
+//! value to `timestamptz`.
+//!
+//! Values of `filter[column]` on boolean columns are normalized too:
+//! `is_active=true` is compared with 1 on SQLite, which stores booleans as 0
+//! and 1, and with `true::boolean` on PostgreSQL.
+
+use std::collections::HashMap;
+
+use crate::codegen::{column_kind, ColumnKind};
+use crate::database::traits::DatabaseError;
+use crate::row_key::bracketed_parameters;
+use crate::schema::TableSchema;
+use crate::sql::{quote_identifier, Dialect};
+
+/// Name of the filter query parameters
+pub const FILTER_PARAMETER: &str = "filter";
+
+/// Comparison of a range filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RangeOperator {
+    /// After the value (`gt`)
+    GreaterThan,
+
+    /// At or after the value (`gte`)
+    GreaterOrEqual,
+
+    /// Before the value (`lt`)
+    LessThan,
+
+    /// At or before the value (`lte`)
+    LessOrEqual,
+}
+
+impl RangeOperator {
+    /// Parse the operator of a `filter[column][operator]` parameter
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "gt" => Some(RangeOperator::GreaterThan),
+            "gte" => Some(RangeOperator::GreaterOrEqual),
+            "lt" => Some(RangeOperator::LessThan),
+            "lte" => Some(RangeOperator::LessOrEqual),
+            _ => None,
+        }
+    }
+
+    /// Name of the operator in query parameters
+    pub fn name(self) -> &'static str {
+        match self {
+            RangeOperator::GreaterThan => "gt",
+            RangeOperator::GreaterOrEqual => "gte",
+            RangeOperator::LessThan => "lt",
+            RangeOperator::LessOrEqual => "lte",
+        }
+    }
+
+    /// SQL comparison operator
+    pub fn sql(self) -> &'static str {
+        match self {
+            RangeOperator::GreaterThan => ">",
+            RangeOperator::GreaterOrEqual => ">=",
+            RangeOperator::LessThan => "<",
+            RangeOperator::LessOrEqual => "<=",
+        }
+    }
+}
+
+/// Comparison of a column with a date or timestamp
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RangeFilter {
+    /// Column name
+    pub column: String,
+
+    /// How the column is compared
+    pub operator: RangeOperator,
+
+    /// ISO 8601 timestamp (`YYYY-MM-DDTHH:MM:SS[.fff](Z|+HH:MM)`)
+    pub value: String,
+}
+
+impl RangeFilter {
+    /// Range filter with its value parsed as an ISO 8601 date or timestamp
+    pub fn new(column: &str, operator: RangeOperator, value: &str) -> Result<Self, DatabaseError> {
+        let value = parse_timestamp(value).ok_or_else(|| {
+            DatabaseError::InvalidFilter(format!(
+                "'{}' of column '{}' is not an ISO 8601 date or timestamp",
+                value, column
+            ))
+        })?;
+        Ok(RangeFilter {
+            column: column.to_string(),
+            operator,
+            value,
+        })
+    }
+
+    /// Condition comparing the column with the value bound to `placeholder`
+    pub fn condition(&self, dialect: Dialect, placeholder: &str) -> String {
+        let column = quote_identifier(&self.column);
+        match dialect {
+            Dialect::Sqlite => format!(
+                "julianday({}) {} julianday({})",
+                column,
+                self.operator.sql(),
+                placeholder
+            ),
+            Dialect::Postgres => format!(
+                "{} {} {}::timestamptz",
+                column,
+                self.operator.sql(),
+                placeholder
+            ),
+        }
+    }
+}
+
+/// Equality of a boolean column with a boolean value
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BooleanFilter {
+    /// Column name
+    pub column: String,
+
+    /// Value the column must have
+    pub value: bool,
+}
+
+impl BooleanFilter {
+    /// Condition comparing the column with the value bound to `placeholder`
+    pub fn condition(&self, dialect: Dialect, placeholder: &str) -> String {
+        let column = quote_identifier(&self.column);
+        match dialect {
+            Dialect::Sqlite => format!("{} = {}", column, placeholder),
+            Dialect::Postgres => format!("{} = {}::boolean", column, placeholder),
+        }
+    }
+
+    /// Value to bind for the placeholder (SQLite stores booleans as 0 and 1)
+    pub fn bound_value(&self, dialect: Dialect) -> String {
+        let value = match (dialect, self.value) {
+            (Dialect::Sqlite, true) => "1",
+            (Dialect::Sqlite, false) => "0",
+            (Dialect::Postgres, true) => "true",
+            (Dialect::Postgres, false) => "false",
+        };
+        value.to_string()
+    }
+}
+
+/// Split the filters on boolean columns with boolean values off the others
+///
+/// Returns the remaining filters and the boolean ones, sorted. Values that
+/// are not booleans (see [`parse_boolean`]) stay plain filters.
+pub fn boolean_filters(
+    values: &HashMap<String, String>,
+    schema: &TableSchema,
+) -> (HashMap<String, String>, Vec<BooleanFilter>) {
+    let mut others = HashMap::new();
+    let mut booleans = Vec::new();
+    for (column, value) in values {
+        let boolean = schema
+            .columns
+            .iter()
+            .find(|info| &info.name == column)
+            .filter(|info| column_kind(&info.data_type) == ColumnKind::Boolean)
+            .and_then(|_| parse_boolean(value));
+        match boolean {
+            Some(value) => booleans.push(BooleanFilter {
+                column: column.clone(),
+                value,
+            }),
+            None => {
+                others.insert(column.clone(), value.clone());
+            }
+        }
+    }
+
+    booleans.sort();
+    (others, booleans)
+}
+
+/// Parse a boolean filter value (`true`/`false`, `t`/`f`, `1`/`0`, `yes`/`no`, `on`/`off`)
+pub fn parse_boolean(text: &str) -> Option<bool> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "true" | "t" | "1" | "yes" | "y" | "on" => Some(true),
+        "false" | "f" | "0" | "no" | "n" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Filters of a request, by kind
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnFilters {
+    /// Values (or `%` patterns) by column name, from `filter[column]`
+    pub values: HashMap<String, String>,
+
+    /// Range comparisons, from `filter[column][operator]`, sorted
+    pub ranges: Vec<RangeFilter>,
+}
+
+/// Read the `filter[column]` and `filter[column][operator]` query parameters
+pub fn filter_parameters(
+    parameters: &HashMap<String, String>,
+) -> Result<ColumnFilters, DatabaseError> {
+    let mut filters = ColumnFilters::default();
+    for (key, value) in bracketed_parameters(parameters, FILTER_PARAMETER) {
+        let Some((column, operator)) = key.split_once("][") else {
+            filters.values.insert(key, value);
+            continue;
+        };
+        let operator = RangeOperator::parse(operator).ok_or_else(|| {
+            DatabaseError::InvalidFilter(format!(
+                "unknown operator '{}' (expected gt, gte, lt or lte)",
+                operator
+            ))
+        })?;
+        filters
+            .ranges
+            .push(RangeFilter::new(column, operator, &value)?);
+    }
+
+    // Sorted so that the same parameters give the same SQL
+    filters.ranges.sort();
+    Ok(filters)
+}
+
+/// Parse an ISO 8601 date or timestamp into `YYYY-MM-DDTHH:MM:SS[.fff](Z|+HH:MM)`
+///
+/// Accepts `YYYY-MM-DD` and `YYYY-MM-DD(T| )HH:MM[:SS[.fff]]` followed by an
+/// optional `Z`, `+HH`, `+HHMM` or `+HH:MM`. Values without a time zone are
+/// taken as UTC, dates as their midnight.
+pub fn parse_timestamp(text: &str) -> Option<String> {
+    let text = text.trim();
+    let bytes = text.as_bytes();
+    if bytes.len() < 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+
+    let (year, month, day) = (
+        number(text.get(0..4)?)?,
+        number(text.get(5..7)?)?,
+        number(text.get(8..10)?)?,
+    );
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+    let date = &text[..10];
+    if bytes.len() == 10 {
+        return Some(format!("{}T00:00:00Z", date));
+    }
+
+    if !matches!(bytes[10], b'T' | b' ') || bytes.get(13) != Some(&b':') {
+        return None;
+    }
+    let (hour, minute) = (number(text.get(11..13)?)?, number(text.get(14..16)?)?);
+
+    let mut rest = text.get(16..)?;
+    let mut second = 0;
+    let mut fraction = "";
+    if let Some(after) = rest.strip_prefix(':') {
+        second = number(after.get(..2)?)?;
+        rest = &after[2..];
+        if let Some(after) = rest.strip_prefix('.') {
+            let length = after.bytes().take_while(u8::is_ascii_digit).count();
+            if length == 0 {
+                return None;
+            }
+            fraction = &rest[..length + 1];
+            rest = &after[length..];
+        }
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    Some(format!(
+        "{}T{:02}:{:02}:{:02}{}{}",
+        date,
+        hour,
+        minute,
+        second,
+        fraction,
+        time_zone(rest)?
+    ))
+}
+
+/// Normalize a time zone designator to `Z` or `+HH:MM` (none means UTC)
+fn time_zone(text: &str) -> Option<String> {
+    if text.is_empty() || text == "Z" {
+        return Some("Z".to_string());
+    }
+
+    let sign = text.get(..1).filter(|sign| matches!(*sign, "+" | "-"))?;
+    let offset = &text[1..];
+    let (hours, minutes) = match offset.len() {
+        2 => (offset, "00"),
+        4 => (offset.get(..2)?, offset.get(2..)?),
+        5 if offset.as_bytes()[2] == b':' => (offset.get(..2)?, offset.get(3..)?),
+        _ => return None,
+    };
+    let (hours, minutes) = (number(hours)?, number(minutes)?);
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(format!("{}{:02}:{:02}", sign, hours, minutes))
+}
+
+/// Value of a string of ASCII digits
+fn number(text: &str) -> Option<u32> {
+    if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Number of days in a month of the Gregorian calendar
+fn days_in_month(year: u32, month: u32) -> u32 {
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(
+            parse_timestamp("2024-01-01").as_deref(),
+            Some("2024-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            parse_timestamp("2024-01-01T00:00:00Z").as_deref(),
+            Some("2024-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            parse_timestamp("2024-02-29 13:45").as_deref(),
+            Some("2024-02-29T13:45:00Z")
+        );
+        assert_eq!(
+            parse_timestamp("2024-03-07T09:15:30.250+0130").as_deref(),
+            Some("2024-03-07T09:15:30.250+01:30")
+        );
+        assert_eq!(
+            parse_timestamp("2024-03-07T09:15:30-05").as_deref(),
+            Some("2024-03-07T09:15:30-05:00")
+        );
+
+        for invalid in [
+            "",
+            "yesterday",
+            "2023-02-29",
+            "2024-13-01",
+            "2024-01-01T24:00",
+            "2024-01-01T10:00:00.",
+            "2024-01-01T10:00+5",
+            "2024-01-01T10:00:00; DROP TABLE users",
+        ] {
+            assert_eq!(parse_timestamp(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_filter_parameters() {
+        let parameters = HashMap::from([
+            ("filter[status]".to_string(), "active".to_string()),
+            (
+                "filter[created_at][lt]".to_string(),
+                "2024-02-01".to_string(),
+            ),
+            (
+                "filter[created_at][gte]".to_string(),
+                "2024-01-01T00:00:00Z".to_string(),
+            ),
+            ("limit".to_string(), "10".to_string()),
+        ]);
+
+        let filters = filter_parameters(&parameters).unwrap();
+        assert_eq!(
+            filters.values,
+            HashMap::from([("status".to_string(), "active".to_string())])
+        );
+        assert_eq!(
+            filters.ranges,
+            vec![
+                RangeFilter {
+                    column: "created_at".to_string(),
+                    operator: RangeOperator::GreaterOrEqual,
+                    value: "2024-01-01T00:00:00Z".to_string(),
+                },
+                RangeFilter {
+                    column: "created_at".to_string(),
+                    operator: RangeOperator::LessThan,
+                    value: "2024-02-01T00:00:00Z".to_string(),
+                },
+            ]
+        );
+
+        let unknown = HashMap::from([("filter[id][ne]".to_string(), "1".to_string())]);
+        assert!(matches!(
+            filter_parameters(&unknown),
+            Err(DatabaseError::InvalidFilter(_))
+        ));
+        let invalid = HashMap::from([("filter[id][gt]".to_string(), "soon".to_string())]);
+        assert!(matches!(
+            filter_parameters(&invalid),
+            Err(DatabaseError::InvalidFilter(_))
+        ));
+    }
+
+    #[test]
+    fn test_boolean_filters() {
+        let schema = TableSchema {
+            name: "users".to_string(),
+            columns: [
+                ("is_active", "BOOLEAN"),
+                ("name", "TEXT"),
+                ("admin", "bool"),
+            ]
+            .iter()
+            .map(|(name, data_type)| crate::schema::ColumnInfo {
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                nullable: false,
+                default_value: None,
+                is_primary_key: false,
+            })
+            .collect(),
+            primary_key: None,
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: crate::schema::RowAddress::PrimaryKey,
+        };
+        let values = HashMap::from([
+            ("is_active".to_string(), "true".to_string()),
+            ("admin".to_string(), "No".to_string()),
+            ("name".to_string(), "true".to_string()),
+        ]);
+
+        let (others, booleans) = boolean_filters(&values, &schema);
+        assert_eq!(
+            others,
+            HashMap::from([("name".to_string(), "true".to_string())])
+        );
+        assert_eq!(
+            booleans,
+            vec![
+                BooleanFilter {
+                    column: "admin".to_string(),
+                    value: false,
+                },
+                BooleanFilter {
+                    column: "is_active".to_string(),
+                    value: true,
+                },
+            ]
+        );
+        assert_eq!(
+            booleans[1].condition(Dialect::Sqlite, "?"),
+            "\"is_active\" = ?"
+        );
+        assert_eq!(booleans[1].bound_value(Dialect::Sqlite), "1");
+        assert_eq!(
+            booleans[1].condition(Dialect::Postgres, "$1"),
+            "\"is_active\" = $1::boolean"
+        );
+        assert_eq!(booleans[0].bound_value(Dialect::Postgres), "false");
+
+        // Values that are not booleans are left to the database
+        let unknown = HashMap::from([("is_active".to_string(), "maybe".to_string())]);
+        assert_eq!(boolean_filters(&unknown, &schema).0, unknown);
+    }
+
+    #[test]
+    fn test_range_conditions() {
+        let filter =
+            RangeFilter::new("created_at", RangeOperator::GreaterOrEqual, "2024-01-01").unwrap();
+        assert_eq!(
+            filter.condition(Dialect::Sqlite, "?"),
+            "julianday(\"created_at\") >= julianday(?)"
+        );
+        assert_eq!(
+            filter.condition(Dialect::Postgres, "$2"),
+            "\"created_at\" >= $2::timestamptz"
+        );
+    }
+}
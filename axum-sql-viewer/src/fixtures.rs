@@ -0,0 +1,355 @@
+//! Fixture documents
+//!
+//! A fixture holds the rows of several tables, so a development database can
+//! be put back into a known state. As JSON it is an object of row arrays by
+//! table name. As YAML it is the same structure, a mapping of row sequences
+//! by table name, with the tables kept in document order:
+//!
+//! ```yaml
+//! users:
+//! - id: 1
+//!   name: Alice
+//! posts: []
+//! ```
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{Map, Value};
+
+use crate::database::traits::DatabaseError;
+use crate::row_key::validate_new_rows;
+use crate::schema::{FixtureFormat, FixtureTable, TableSchema};
+
+impl FixtureFormat {
+    /// File name extension
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FixtureFormat::Json => "json",
+            FixtureFormat::Yaml => "yaml",
+        }
+    }
+
+    /// Value of the `Content-Type` header
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            FixtureFormat::Json => "application/json",
+            FixtureFormat::Yaml => "application/yaml; charset=utf-8",
+        }
+    }
+
+    /// Format named by the `Content-Type` of an upload, if any
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match essence.as_str() {
+            "application/json" => Some(FixtureFormat::Json),
+            "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => {
+                Some(FixtureFormat::Yaml)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Write `tables` as a fixture document, keeping their order
+pub fn write_fixture(tables: &[FixtureTable], format: FixtureFormat) -> String {
+    match format {
+        FixtureFormat::Json => write_json(tables),
+        FixtureFormat::Yaml => write_yaml(tables),
+    }
+}
+
+/// Read a fixture document
+///
+/// # Returns
+///
+/// The tables (in document order for YAML, by name for JSON), or a
+/// description of what is wrong
+pub fn parse_fixture(text: &str, format: FixtureFormat) -> Result<Vec<FixtureTable>, String> {
+    match format {
+        FixtureFormat::Json => parse_json(text),
+        FixtureFormat::Yaml => parse_yaml(text),
+    }
+}
+
+/// Order in which the tables of a fixture are filled: referenced tables first
+///
+/// Tables are emptied in the reverse order. References to tables outside the
+/// fixture and to the table itself are ignored; otherwise the order of
+/// `schemas` is kept.
+///
+/// # Returns
+///
+/// Indexes into `schemas`, or the names of the tables referencing each other
+/// in a cycle
+pub fn load_order(schemas: &[TableSchema]) -> Result<Vec<usize>, Vec<String>> {
+    let mut placed = vec![false; schemas.len()];
+    let mut order = Vec::with_capacity(schemas.len());
+
+    while order.len() < schemas.len() {
+        let ready = |index: usize| {
+            let schema = &schemas[index];
+            schema.foreign_keys.iter().all(|foreign_key| {
+                foreign_key.references_table == schema.name
+                    || schemas.iter().zip(&placed).all(|(other, placed)| {
+                        other.name != foreign_key.references_table || *placed
+                    })
+            })
+        };
+
+        match (0..schemas.len()).find(|&index| !placed[index] && ready(index)) {
+            Some(index) => {
+                placed[index] = true;
+                order.push(index);
+            }
+            None => {
+                return Err(schemas
+                    .iter()
+                    .zip(&placed)
+                    .filter(|(_, placed)| !**placed)
+                    .map(|(schema, _)| schema.name.clone())
+                    .collect())
+            }
+        }
+    }
+
+    Ok(order)
+}
+
+/// Check the rows of a fixture table like those of a bulk insert, naming the
+/// table in errors
+pub fn validate_table_rows(
+    schema: &TableSchema,
+    rows: &[Map<String, Value>],
+) -> Result<(), DatabaseError> {
+    validate_new_rows(schema, rows).map_err(|error| match error {
+        DatabaseError::InvalidRow { index, reason } => DatabaseError::InvalidRow {
+            index,
+            reason: format!("{} (table '{}')", reason, schema.name),
+        },
+        error => error,
+    })
+}
+
+fn write_json(tables: &[FixtureTable]) -> String {
+    // Written by hand, as a JSON map would sort the tables by name
+    let mut document = String::from("{");
+    for (index, table) in tables.iter().enumerate() {
+        if index > 0 {
+            document.push(',');
+        }
+        document.push_str("\n  ");
+        document.push_str(&Value::String(table.table.clone()).to_string());
+        document.push_str(": [");
+        for (row_index, row) in table.rows.iter().enumerate() {
+            if row_index > 0 {
+                document.push(',');
+            }
+            document.push_str("\n    ");
+            document.push_str(&Value::Object(row.clone()).to_string());
+        }
+        if !table.rows.is_empty() {
+            document.push_str("\n  ");
+        }
+        document.push(']');
+    }
+    if !tables.is_empty() {
+        document.push('\n');
+    }
+    document.push_str("}\n");
+    document
+}
+
+fn write_yaml(tables: &[FixtureTable]) -> String {
+    serde_norway::to_string(&YamlDocument(tables)).expect("JSON values are valid YAML")
+}
+
+/// Tables as a YAML mapping in their own order, which a map type would not keep
+struct YamlDocument<'a>(&'a [FixtureTable]);
+
+impl Serialize for YamlDocument<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut document = serializer.serialize_map(Some(self.0.len()))?;
+        for table in self.0 {
+            document.serialize_entry(&table.table, &table.rows)?;
+        }
+        document.end()
+    }
+}
+
+fn parse_json(text: &str) -> Result<Vec<FixtureTable>, String> {
+    let document: Value = serde_json::from_str(text).map_err(|error| error.to_string())?;
+    let Value::Object(tables) = document else {
+        return Err("expected an object of row arrays by table name".to_string());
+    };
+
+    tables
+        .into_iter()
+        .map(|(table, rows)| {
+            let Value::Array(rows) = rows else {
+                return Err(format!("rows of '{}' must be an array", table));
+            };
+            let rows = rows
+                .into_iter()
+                .enumerate()
+                .map(|(index, row)| match row {
+                    Value::Object(row) => Ok(row),
+                    _ => Err(format!("row {} of '{}' must be an object", index, table)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(FixtureTable { table, rows })
+        })
+        .collect()
+}
+
+fn parse_yaml(text: &str) -> Result<Vec<FixtureTable>, String> {
+    let document: serde_norway::Value =
+        serde_norway::from_str(text).map_err(|error| error.to_string())?;
+    let tables = match document {
+        serde_norway::Value::Null => return Ok(Vec::new()),
+        serde_norway::Value::Mapping(tables) => tables,
+        _ => return Err("expected a mapping of row sequences by table name".to_string()),
+    };
+
+    tables
+        .into_iter()
+        .map(|(table, rows)| {
+            let serde_norway::Value::String(table) = table else {
+                return Err("table names must be strings".to_string());
+            };
+            let rows = serde_norway::from_value(rows)
+                .map_err(|error| format!("rows of '{}': {}", table, error))?;
+            Ok(FixtureTable { table, rows })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use serde_json::json;
+
+    fn fixture() -> Vec<FixtureTable> {
+        let rows = |value: Value| match value {
+            Value::Array(rows) => rows
+                .into_iter()
+                .map(|row| row.as_object().unwrap().clone())
+                .collect(),
+            _ => unreachable!(),
+        };
+        vec![
+            FixtureTable {
+                table: "audit log".to_string(),
+                rows: Vec::new(),
+            },
+            FixtureTable {
+                table: "users".to_string(),
+                rows: rows(json!([
+                    {"id": 1, "name": "Alice \"Al\" Smith", "active": true, "note": null},
+                    {"id": 2, "name": "Bob: the builder", "settings": {"theme": "dark"}},
+                    {}
+                ])),
+            },
+        ]
+    }
+
+    fn table(name: &str, references: &[&str]) -> TableSchema {
+        references
+            .iter()
+            .fold(
+                test_support::table(name).primary_key(&["id"]),
+                |table, references_table| {
+                    table.foreign_key(&format!("{}_id", references_table), references_table, "id")
+                },
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // Tables in name order, as JSON objects are read sorted
+        let tables = fixture();
+        for format in [FixtureFormat::Json, FixtureFormat::Yaml] {
+            let document = write_fixture(&tables, format);
+            assert_eq!(parse_fixture(&document, format).unwrap(), tables);
+        }
+
+        let yaml = write_fixture(&tables, FixtureFormat::Yaml);
+        assert!(yaml.starts_with("audit log: []\nusers:\n- active: true\n  id: 1\n"));
+        assert!(yaml.ends_with("- {}\n"));
+    }
+
+    #[test]
+    fn test_parse_handwritten_yaml() {
+        let yaml = "\
+# Two users
+users:
+- id: 1
+  name: Alice # the admin
+  nickname: 'Al''s'
+  manager: ~
+  \"yes\": yes
+  bio: |
+    Likes SQL
+posts: []
+";
+        let tables = parse_fixture(yaml, FixtureFormat::Yaml).unwrap();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(
+            Value::Object(tables[0].rows[0].clone()),
+            json!({
+                "id": 1,
+                "name": "Alice",
+                "nickname": "Al's",
+                "manager": null,
+                "yes": "yes",
+                "bio": "Likes SQL\n"
+            })
+        );
+        assert!(tables[1].rows.is_empty());
+
+        assert!(parse_fixture("  - id: 1\n", FixtureFormat::Yaml).is_err());
+        assert!(parse_fixture("users:\n  - [1]\n", FixtureFormat::Yaml).is_err());
+        assert!(parse_fixture("users: 3\n", FixtureFormat::Yaml).is_err());
+    }
+
+    #[test]
+    fn test_parse_json_shape() {
+        assert!(parse_fixture("[]", FixtureFormat::Json).is_err());
+        assert!(parse_fixture("{\"users\": {}}", FixtureFormat::Json).is_err());
+        assert!(parse_fixture("{\"users\": [1]}", FixtureFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_load_order() {
+        let schemas = vec![
+            table("comments", &["posts", "users"]),
+            table("posts", &["users"]),
+            table("users", &["users", "companies"]),
+        ];
+        assert_eq!(load_order(&schemas).unwrap(), vec![2, 1, 0]);
+
+        let cycle = vec![table("a", &["b"]), table("b", &["a"]), table("c", &[])];
+        assert_eq!(
+            load_order(&cycle).unwrap_err(),
+            vec!["a".to_string(), "b".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_from_content_type() {
+        assert_eq!(
+            FixtureFormat::from_content_type("application/json; charset=utf-8"),
+            Some(FixtureFormat::Json)
+        );
+        assert_eq!(
+            FixtureFormat::from_content_type("text/yaml"),
+            Some(FixtureFormat::Yaml)
+        );
+        assert_eq!(FixtureFormat::from_content_type("text/plain"), None);
+    }
+}
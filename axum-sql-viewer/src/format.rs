@@ -0,0 +1,486 @@
+//! SQL pretty-printing
+//!
+//! A small token-based formatter: clauses start on their own line with their
+//! content indented below them, top-level list items and `AND`/`OR` conditions
+//! go on separate lines, and subqueries are indented one level deeper.
+//! Unquoted words are written in upper case if they are keywords; literals,
+//! quoted identifiers and comments are kept exactly as written.
+
+use crate::sql::{tokenize, Dialect, Token, TokenKind};
+
+/// Indentation used for each nesting level
+const INDENT: &str = "  ";
+
+/// Keywords that start a clause (content is indented on the following lines)
+const CLAUSE_KEYWORDS: &[&str] = &[
+    "SELECT",
+    "FROM",
+    "WHERE",
+    "GROUP",
+    "HAVING",
+    "ORDER",
+    "LIMIT",
+    "OFFSET",
+    "UNION",
+    "INTERSECT",
+    "EXCEPT",
+    "INSERT",
+    "VALUES",
+    "UPDATE",
+    "SET",
+    "DELETE",
+    "RETURNING",
+    "WITH",
+    "WINDOW",
+];
+
+/// Clauses whose content stays on the same line
+const INLINE_CLAUSE_KEYWORDS: &[&str] = &["LIMIT", "OFFSET", "UNION", "INTERSECT", "EXCEPT"];
+
+/// Words that continue the clause keyword on the same line (e.g. `GROUP BY`)
+const CLAUSE_CONTINUATION_KEYWORDS: &[&str] =
+    &["BY", "ALL", "DISTINCT", "INTO", "FROM", "RECURSIVE"];
+
+/// Words starting a join, placed on their own line within the FROM clause
+const JOIN_KEYWORDS: &[&str] = &["JOIN", "LEFT", "RIGHT", "FULL", "INNER", "CROSS", "NATURAL"];
+
+/// Previous words after which a clause keyword is part of another construct
+/// (e.g. `ON DELETE SET NULL`, `DO UPDATE SET`, `IS DISTINCT FROM`)
+const CLAUSE_BLOCKING_KEYWORDS: &[&str] = &["ON", "DO", "DELETE", "UPDATE", "DISTINCT", "FOR"];
+
+/// Keywords written in upper case
+const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "ANALYZE",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "AUTOINCREMENT",
+    "BEGIN",
+    "BETWEEN",
+    "BY",
+    "CASCADE",
+    "CASE",
+    "CAST",
+    "CHECK",
+    "COLLATE",
+    "COLUMN",
+    "COMMIT",
+    "CONFLICT",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DO",
+    "DROP",
+    "ELSE",
+    "END",
+    "ESCAPE",
+    "EXCEPT",
+    "EXISTS",
+    "EXPLAIN",
+    "FALSE",
+    "FILTER",
+    "FIRST",
+    "FOR",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GLOB",
+    "GROUP",
+    "HAVING",
+    "IF",
+    "ILIKE",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTERVAL",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "LAST",
+    "LATERAL",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NATURAL",
+    "NOT",
+    "NOTHING",
+    "NULL",
+    "NULLS",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "OVER",
+    "PARTITION",
+    "PRAGMA",
+    "PRIMARY",
+    "RECURSIVE",
+    "REFERENCES",
+    "REPLACE",
+    "RESTRICT",
+    "RETURNING",
+    "RIGHT",
+    "ROLLBACK",
+    "SELECT",
+    "SET",
+    "SIMILAR",
+    "TABLE",
+    "THEN",
+    "TRANSACTION",
+    "TRUE",
+    "TRUNCATE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USING",
+    "VACUUM",
+    "VALUES",
+    "VIEW",
+    "WHEN",
+    "WHERE",
+    "WINDOW",
+    "WITH",
+];
+
+/// Characters that form (possibly multi-character) operators
+const OPERATOR_CHARACTERS: &str = "<>=!|&~@#%^*/+-";
+
+/// Pretty-print SQL text
+///
+/// Multiple statements are separated by a blank line.
+pub fn format_sql(sql: &str, dialect: Dialect) -> String {
+    let mut formatter = Formatter::default();
+    let tokens = tokenize(sql, dialect);
+
+    let mut adjacent = false;
+    for (index, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Whitespace => adjacent = false,
+            TokenKind::Comment => {
+                formatter.write_comment(token.text);
+                adjacent = false;
+            }
+            _ => {
+                let next = tokens[index + 1..].iter().find(|token| {
+                    !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment)
+                });
+                formatter.write_token(token, next, adjacent);
+                adjacent = true;
+            }
+        }
+    }
+
+    formatter.output.trim_end().to_string()
+}
+
+#[derive(Default)]
+struct Formatter {
+    output: String,
+
+    /// Subquery nesting depth
+    depth: usize,
+
+    /// For each open parenthesis, the depth outside of it if it contains a subquery
+    parentheses: Vec<Option<usize>>,
+
+    /// A clause keyword was written and its content has not started yet
+    clause_content_pending: bool,
+
+    /// A line comment was written, the next token must start a new line
+    line_break_pending: bool,
+
+    /// The next `AND` belongs to a `BETWEEN`
+    between_pending: bool,
+
+    /// Upper-cased text of the last two tokens written (most recent last)
+    previous: [Option<(TokenKind, String)>; 2],
+
+    /// The last token written was a unary sign
+    unary_pending: bool,
+}
+
+impl Formatter {
+    fn write_token(&mut self, token: &Token<'_>, next: Option<&Token<'_>>, adjacent: bool) {
+        let upper = token.text.to_ascii_uppercase();
+        let is_word = token.kind == TokenKind::Word;
+        let text = if is_word && KEYWORDS.contains(&upper.as_str()) {
+            upper.clone()
+        } else {
+            token.text.to_string()
+        };
+        let at_block_level = self.parentheses.last().is_none_or(Option::is_some);
+
+        if token.kind == TokenKind::Punctuation && token.text == ";" {
+            self.output.push(';');
+            self.output.push_str("\n\n");
+            self.depth = 0;
+            self.parentheses.clear();
+            self.clause_content_pending = false;
+            self.line_break_pending = false;
+            self.between_pending = false;
+            self.previous = [None, None];
+            return;
+        }
+
+        if token.kind == TokenKind::Punctuation && token.text == ")" {
+            if let Some(Some(outer_depth)) = self.parentheses.pop() {
+                self.depth = outer_depth;
+                self.clause_content_pending = false;
+                self.new_line(self.depth + 1);
+                self.push(token, ")");
+                return;
+            }
+        }
+
+        let previous_word = self.previous_word(1);
+        let is_clause = is_word
+            && at_block_level
+            && CLAUSE_KEYWORDS.contains(&upper.as_str())
+            && !previous_word.is_some_and(|word| CLAUSE_BLOCKING_KEYWORDS.contains(&word));
+        let is_join_start = is_word
+            && at_block_level
+            && JOIN_KEYWORDS.contains(&upper.as_str())
+            && !previous_word.is_some_and(|word| JOIN_KEYWORDS.contains(&word));
+        let continues_clause = self.clause_content_pending
+            && is_word
+            && CLAUSE_CONTINUATION_KEYWORDS.contains(&upper.as_str());
+
+        if is_clause {
+            self.new_line(self.depth);
+            self.clause_content_pending = !INLINE_CLAUSE_KEYWORDS.contains(&upper.as_str());
+            self.line_break_pending = false;
+        } else if is_join_start || (self.clause_content_pending && !continues_clause) {
+            self.clause_content_pending = false;
+            self.new_line(self.depth + 1);
+        } else if self.line_break_pending
+            || (at_block_level
+                && is_word
+                && (upper == "AND" || upper == "OR")
+                && !(upper == "AND" && self.between_pending))
+        {
+            self.new_line(self.depth + 1);
+        } else if self.needs_space(token, &upper, adjacent) {
+            self.output.push(' ');
+        }
+        self.line_break_pending = false;
+
+        if is_word && upper == "BETWEEN" {
+            self.between_pending = true;
+        } else if is_word && upper == "AND" {
+            self.between_pending = false;
+        }
+
+        self.unary_pending = (token.text == "-" || token.text == "+")
+            && match &self.previous[1] {
+                None => true,
+                Some((TokenKind::Punctuation, previous)) => previous != ")",
+                Some((TokenKind::Word, previous)) => KEYWORDS.contains(&previous.as_str()),
+                Some(_) => false,
+            };
+
+        self.push(token, &text);
+
+        if token.kind == TokenKind::Punctuation && token.text == "(" {
+            let is_block =
+                next.is_some_and(|next| next.is_keyword("SELECT") || next.is_keyword("WITH"));
+            if is_block {
+                // The subquery's clauses are indented below the clause content
+                self.parentheses.push(Some(self.depth));
+                self.depth += 2;
+            } else {
+                self.parentheses.push(None);
+            }
+        } else if token.kind == TokenKind::Punctuation && token.text == "," && at_block_level {
+            self.new_line(self.depth + 1);
+        }
+    }
+
+    fn write_comment(&mut self, comment: &str) {
+        if !self.at_line_start() {
+            self.output.push(' ');
+        }
+        self.output.push_str(comment.trim_end());
+
+        if comment.starts_with("--") {
+            self.line_break_pending = true;
+        }
+    }
+
+    /// Whether a space separates the previous token from `token`
+    fn needs_space(&self, token: &Token<'_>, upper: &str, adjacent: bool) -> bool {
+        let Some((previous_kind, previous)) = &self.previous[1] else {
+            return false;
+        };
+        if self.at_line_start() || self.unary_pending {
+            return false;
+        }
+
+        let text = token.text;
+        if matches!(text, "," | ")" | "." | "::" | "]")
+            || matches!(previous.as_str(), "(" | "." | "::" | "[")
+        {
+            return false;
+        }
+
+        if text == "(" || text == "[" {
+            return match previous_kind {
+                // Function calls and subscripts, except a table name after INTO
+                TokenKind::Word if !KEYWORDS.contains(&previous.as_str()) => {
+                    self.previous_word(0) == Some("INTO")
+                }
+                TokenKind::QuotedIdentifier => self.previous_word(0) == Some("INTO"),
+                TokenKind::Punctuation => previous != ")" || text == "(",
+                _ => true,
+            };
+        }
+
+        // Multi-character operators such as >=, <> or ->>
+        let is_operator = |value: &str| {
+            value
+                .chars()
+                .all(|character| OPERATOR_CHARACTERS.contains(character))
+        };
+        if adjacent
+            && *previous_kind == TokenKind::Punctuation
+            && is_operator(previous)
+            && is_operator(upper)
+        {
+            return false;
+        }
+
+        true
+    }
+
+    /// Upper-cased word written `index` tokens back (1 = most recent)
+    fn previous_word(&self, index: usize) -> Option<&str> {
+        match &self.previous[index] {
+            Some((TokenKind::Word, word)) => Some(word.as_str()),
+            _ => None,
+        }
+    }
+
+    fn push(&mut self, token: &Token<'_>, text: &str) {
+        self.output.push_str(text);
+        self.previous.swap(0, 1);
+        self.previous[1] = Some((token.kind, text.to_ascii_uppercase()));
+    }
+
+    fn new_line(&mut self, depth: usize) {
+        let trimmed_length = self.output.trim_end_matches(' ').len();
+        self.output.truncate(trimmed_length);
+        if !self.output.is_empty() && !self.output.ends_with('\n') {
+            self.output.push('\n');
+        }
+        self.output.push_str(&INDENT.repeat(depth));
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.output.is_empty() || self.output.ends_with('\n') || self.output.ends_with(' ')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::PostgreSqlDialect;
+    use sqlparser::parser::Parser;
+
+    /// Assert that formatting `sql` does not change the statements it parses to
+    fn assert_same_statements(sql: &str) -> String {
+        let parse = |sql: &str| {
+            Parser::parse_sql(&PostgreSqlDialect {}, sql)
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        let formatted = format_sql(sql, Dialect::Postgres);
+        assert_eq!(
+            parse(&formatted),
+            parse(sql),
+            "formatted as:\n{}",
+            formatted
+        );
+        formatted
+    }
+
+    #[test]
+    fn test_format_select() {
+        let sql = "select u.id, count(*) as total from users u left join orders o on o.user_id = u.id \
+                   where u.active = true and u.age between 18 and 65 group by u.id order by total desc limit 10";
+        assert_eq!(
+            format_sql(sql, Dialect::Postgres),
+            "SELECT\n  u.id,\n  count(*) AS total\nFROM\n  users u\n  LEFT JOIN orders o ON o.user_id = u.id\n\
+             WHERE\n  u.active = TRUE\n  AND u.age BETWEEN 18 AND 65\nGROUP BY\n  u.id\nORDER BY\n  total DESC\nLIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_format_subquery_and_operators() {
+        let sql =
+            "SELECT * FROM t WHERE id IN (SELECT id FROM s WHERE x >= -1) AND data->>'k' <> 'a, b'";
+        assert_eq!(
+            format_sql(sql, Dialect::Postgres),
+            "SELECT\n  *\nFROM\n  t\nWHERE\n  id IN (\n    SELECT\n      id\n    FROM\n      s\n    WHERE\n      x >= -1\n  )\n  AND data ->> 'k' <> 'a, b'"
+        );
+    }
+
+    #[test]
+    fn test_format_statements_and_comments() {
+        let sql = "insert into users (id, name) values (1, 'a'), (2, 'b'); -- done\nupdate users set name = 'c' where id = 1";
+        assert_eq!(
+            format_sql(sql, Dialect::Sqlite),
+            "INSERT INTO\n  users (id, name)\nVALUES\n  (1, 'a'),\n  (2, 'b');\n\n-- done\nUPDATE\n  users\nSET\n  name = 'c'\nWHERE\n  id = 1"
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_comments() {
+        let formatted = assert_same_statements(
+            "select a -- first, b\n, b /* not -- a line comment */ from t where c = 1 -- last",
+        );
+        assert!(formatted.contains("-- first, b\n"));
+        assert!(formatted.contains("/* not -- a line comment */"));
+        assert!(formatted.ends_with("-- last"));
+    }
+
+    #[test]
+    fn test_format_keeps_dollar_quoted_strings() {
+        let formatted = assert_same_statements(
+            "select $$it's -- not a comment$$ as a, $fn$ select 1; from $x$ $fn$ as b from t",
+        );
+        assert!(formatted.contains("$$it's -- not a comment$$"));
+        assert!(formatted.contains("$fn$ select 1; from $x$ $fn$"));
+    }
+
+    #[test]
+    fn test_format_keeps_escape_strings() {
+        let formatted =
+            assert_same_statements(r"select e'it\'s\n', E'a''b' from t where x = U&'d\0061t'");
+        assert!(formatted.contains(r"e'it\'s\n'"));
+        assert!(formatted.contains(r"E'a''b'"));
+        assert!(formatted.contains(r"U&'d\0061t'"));
+    }
+
+    #[test]
+    fn test_format_keeps_quoted_identifiers() {
+        let formatted = assert_same_statements(
+            r#"select "Select", "a""b", "from" from "My Table" where "where" = 'x'"#,
+        );
+        assert!(formatted.contains(r#""Select","#));
+        assert!(formatted.contains(r#""a""b""#));
+        assert!(formatted.contains(r#""My Table""#));
+    }
+}
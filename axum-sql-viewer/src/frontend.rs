@@ -5,15 +5,19 @@
 
 use axum::{
     body::Body,
-    extract::{Path, State},
+    extract::{Path, Request, State},
     http::{header, StatusCode},
-    response::Response,
+    response::{IntoResponse, Json, Response},
     routing::get,
     Router,
 };
 use include_dir::{include_dir, Dir};
+use serde::Serialize;
 use std::sync::Arc;
 
+#[cfg(feature = "dev-server")]
+use crate::dev_server::DevServerProxy;
+
 // Embed the frontend dist directory at compile time
 static FRONTEND_DISTRIBUTION: Dir = include_dir!("$CARGO_MANIFEST_DIR/frontend/dist");
 
@@ -21,6 +25,10 @@ static FRONTEND_DISTRIBUTION: Dir = include_dir!("$CARGO_MANIFEST_DIR/frontend/d
 #[derive(Clone)]
 pub struct FrontendState {
     pub base_path: Arc<String>,
+
+    /// Dev server to forward requests to when no frontend is embedded
+    #[cfg(feature = "dev-server")]
+    pub dev_server: Option<DevServerProxy>,
 }
 
 impl FrontendState {
@@ -28,27 +36,149 @@ impl FrontendState {
     pub fn new(base_path: String) -> Self {
         Self {
             base_path: Arc::new(base_path),
+            #[cfg(feature = "dev-server")]
+            dev_server: None,
         }
     }
 }
 
+/// Availability of the frontend, as reported by GET /frontend-status
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontendStatus {
+    /// Whether the built frontend is embedded in the binary
+    pub embedded_assets: bool,
+
+    /// URL of the configured frontend dev server (if any)
+    pub dev_server_url: Option<String>,
+
+    /// Whether the dev server answered (None if no dev server is configured)
+    pub dev_server_reachable: Option<bool>,
+
+    /// Whether the frontend can currently be served
+    pub ready: bool,
+}
+
 /// Create a router for serving frontend assets
 ///
 /// This returns a Router that serves:
 /// - GET / -> index.html with injected <base href> tag
 /// - GET /assets/* -> static assets with long-term caching
+/// - GET /frontend-status -> whether the frontend is available
 ///
 /// # Arguments
 ///
 /// * `base_path` - The base URL path where the frontend is mounted (e.g., "/sql-viewer")
 pub fn create_frontend_router(base_path: String) -> Router {
-    let state = FrontendState::new(base_path);
+    frontend_router(FrontendState::new(base_path))
+}
 
+/// Create a router for serving frontend assets, falling back to a dev server
+///
+/// Behaves like [`create_frontend_router`] when the frontend is embedded.
+/// Otherwise every request is forwarded to the dev server at `dev_server_url`,
+/// which must serve the frontend below the same base path
+/// (e.g. `pnpm dev --base /sql-viewer/`).
+///
+/// # Arguments
+///
+/// * `base_path` - The base URL path where the frontend is mounted (e.g., "/sql-viewer")
+/// * `dev_server_url` - URL of the frontend dev server (e.g., "http://localhost:5174")
+#[cfg(feature = "dev-server")]
+pub fn create_dev_server_frontend_router(base_path: String, dev_server_url: String) -> Router {
+    let mut state = FrontendState::new(base_path);
+    state.dev_server = Some(DevServerProxy::new(dev_server_url));
+    frontend_router(state)
+}
+
+fn frontend_router(state: FrontendState) -> Router {
     // Note: Axum 0.8 uses {*wildcard} syntax for wildcard captures
-    Router::new()
+    let router = Router::new()
         .route("/", get(serve_index_page))
         .route("/assets/{*path}", get(serve_static_asset))
-        .with_state(state)
+        .route("/frontend-status", get(serve_frontend_status));
+
+    // Dev server modules live outside /assets (e.g. /src/main.tsx, /@vite/client)
+    #[cfg(feature = "dev-server")]
+    let router = router.fallback(forward_to_dev_server);
+
+    router.with_state(state)
+}
+
+/// Report whether the frontend can be served
+///
+/// Polled by the fallback page to reload once the frontend becomes available.
+async fn serve_frontend_status(State(state): State<FrontendState>) -> Response {
+    let embedded_assets = FRONTEND_DISTRIBUTION.get_file("index.html").is_some();
+    let (dev_server_url, dev_server_reachable) = dev_server_status(&state).await;
+
+    let status = FrontendStatus {
+        embedded_assets,
+        ready: embedded_assets || dev_server_reachable == Some(true),
+        dev_server_url,
+        dev_server_reachable,
+    };
+
+    (
+        StatusCode::OK,
+        [(header::CACHE_CONTROL, "no-cache")],
+        Json(status),
+    )
+        .into_response()
+}
+
+/// URL of the configured dev server and whether it is reachable
+#[cfg(feature = "dev-server")]
+async fn dev_server_status(state: &FrontendState) -> (Option<String>, Option<bool>) {
+    match &state.dev_server {
+        Some(dev_server) => (
+            Some(dev_server.url().to_string()),
+            Some(dev_server.is_reachable().await),
+        ),
+        None => (None, None),
+    }
+}
+
+#[cfg(not(feature = "dev-server"))]
+async fn dev_server_status(_state: &FrontendState) -> (Option<String>, Option<bool>) {
+    (None, None)
+}
+
+/// Forward a request to the dev server when no frontend is embedded
+///
+/// Returns `None` if the request should be handled locally.
+#[cfg(feature = "dev-server")]
+async fn try_forward_to_dev_server(state: &FrontendState, request: Request) -> Option<Response> {
+    if FRONTEND_DISTRIBUTION.get_file("index.html").is_some() {
+        return None;
+    }
+    let dev_server = state.dev_server.as_ref()?;
+
+    match dev_server.forward(request).await {
+        Ok(response) => Some(response),
+        Err(error) => {
+            eprintln!("{}", error);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "dev-server"))]
+async fn try_forward_to_dev_server(_state: &FrontendState, _request: Request) -> Option<Response> {
+    None
+}
+
+/// Fallback handler forwarding any other path to the dev server
+#[cfg(feature = "dev-server")]
+async fn forward_to_dev_server(State(state): State<FrontendState>, request: Request) -> Response {
+    match try_forward_to_dev_server(&state, request).await {
+        Some(response) => response,
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(Body::from("Not found"))
+            .unwrap(),
+    }
 }
 
 /// Serve the index.html file at the root path
@@ -58,7 +188,7 @@ pub fn create_frontend_router(base_path: String) -> Router {
 /// mount point.
 ///
 /// Caching: max-age=3600 (1 hour) for index.html
-async fn serve_index_page(State(state): State<FrontendState>) -> Response {
+async fn serve_index_page(State(state): State<FrontendState>, request: Request) -> Response {
     // Try to serve embedded index.html, fallback to placeholder
     if let Some(file) = FRONTEND_DISTRIBUTION.get_file("index.html") {
         let mut contents = String::from_utf8_lossy(file.contents()).to_string();
@@ -78,7 +208,11 @@ async fn serve_index_page(State(state): State<FrontendState>) -> Response {
             .body(Body::from(contents))
             .unwrap()
     } else {
-        serve_fallback_page()
+        if let Some(response) = try_forward_to_dev_server(&state, request).await {
+            return response;
+        }
+
+        serve_fallback_page(&state)
     }
 }
 
@@ -88,7 +222,11 @@ async fn serve_index_page(State(state): State<FrontendState>) -> Response {
 /// appropriate content types and long-term caching headers.
 ///
 /// Caching: max-age=31536000 (1 year) for static assets
-async fn serve_static_asset(Path(path): Path<String>) -> Response {
+async fn serve_static_asset(
+    State(state): State<FrontendState>,
+    Path(path): Path<String>,
+    request: Request,
+) -> Response {
     // Path already has the wildcard part extracted (e.g., "index-Dm3cA5i_.js")
     // We need to prepend "assets/" to match the embedded directory structure from Vite
     let asset_path = format!("assets/{}", path);
@@ -107,6 +245,10 @@ async fn serve_static_asset(Path(path): Path<String>) -> Response {
             .body(Body::from(contents))
             .unwrap()
     } else {
+        if let Some(response) = try_forward_to_dev_server(&state, request).await {
+            return response;
+        }
+
         Response::builder()
             .status(StatusCode::NOT_FOUND)
             .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
@@ -119,8 +261,9 @@ async fn serve_static_asset(Path(path): Path<String>) -> Response {
 ///
 /// This page is shown when the frontend/dist directory is not present
 /// or index.html is not found. It provides clear instructions on how
-/// to build the frontend.
-fn serve_fallback_page() -> Response {
+/// to build the frontend, and polls GET /frontend-status to reload itself
+/// once the frontend (or the configured dev server) becomes available.
+fn serve_fallback_page(state: &FrontendState) -> Response {
     let html = r#"<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -203,6 +346,10 @@ fn serve_fallback_page() -> Response {
             border-radius: 4px;
             color: #0c5460;
         }
+        .status {
+            color: #555;
+            font-style: italic;
+        }
     </style>
 </head>
 <body>
@@ -214,11 +361,17 @@ fn serve_fallback_page() -> Response {
             <p>The frontend has not been built yet. To use axum-sql-viewer, you need to build the React frontend.</p>
         </div>
 
+        <p id="status" class="status">Checking frontend status...</p>
+
         <h2>📦 Development Setup</h2>
         <p>To build the frontend during development:</p>
         <pre>cd axum-sql-viewer/frontend
 pnpm install
 pnpm build</pre>
+        <p>Or, with the <code>dev-server</code> feature and <code>SqlViewerLayer::with_frontend_dev_server</code>, run the Vite dev server with hot reload:</p>
+        <pre>cd axum-sql-viewer/frontend
+pnpm dev --base __BASE_PATH__/</pre>
+        <p>This page reloads automatically once the frontend is available.</p>
 
         <h2>🚀 Using Pre-built Package</h2>
         <div class="info">
@@ -228,11 +381,70 @@ pnpm build</pre>
         <h2>🔌 API Endpoints</h2>
         <p>The REST API is still available for direct access:</p>
         <ul>
-            <li><code>GET /api/tables</code> - List all tables in the database</li>
+            <li><code>GET /api/tables</code> - List all tables in the database with estimated row counts (<code>?exact=true</code> counts them)</li>
             <li><code>GET /api/tables/:name</code> - Get table schema information</li>
+            <li><code>GET /api/search?q=:text</code> - Find tables and columns by name across the schema</li>
+            <li><code>POST /api/cache/invalidate</code> - Empty the schema cache</li>
             <li><code>GET /api/tables/:name/rows</code> - Fetch rows with pagination and filtering</li>
+            <li><code>POST /api/tables/:name/rows</code> - Fetch rows with a JSON query body, including OR-combined filter groups</li>
             <li><code>GET /api/tables/:name/count</code> - Get total row count</li>
-            <li><code>POST /api/query</code> - Execute raw SQL queries</li>
+            <li><code>GET /api/tables/:name/checksum</code> - Fingerprint of the table's data, to poll for changes</li>
+            <li><code>GET /api/tables/:name/sample?n=100</code> - Randomly picked rows of a table</li>
+            <li><code>GET /api/tables/:name/duplicates?columns=email</code> - Groups of duplicate rows</li>
+            <li><code>GET /api/tables/:name/profile</code> - Data profile of a table's columns</li>
+            <li><code>GET /api/tables/:name/columns/:column/stats</code> - Minimum, maximum, average, NULL and distinct counts of a column</li>
+            <li><code>GET /api/tables/:name/columns/:column/histogram</code> - Distribution of a numeric or date column in equal-width buckets</li>
+            <li><code>GET /api/tables/:name/rows/:key</code> - Fetch a single row by primary key</li>
+            <li><code>GET|PATCH|DELETE /api/tables/:name/row?pk[column]=value</code> - Fetch, update or delete a row addressed by its (composite) primary key</li>
+            <li><code>GET /api/tables/:name/rows/:key/related</code> - Parent rows referenced by a row's foreign keys</li>
+            <li><code>GET /api/tables/:name/rows/:key/referenced-by</code> - Rows of other tables referencing a row</li>
+            <li><code>PATCH /api/tables/:name/rows/:key</code> - Update columns of a row by primary key</li>
+            <li><code>POST /api/tables/:name/rows:bulk</code> - Insert an array of rows in one transaction</li>
+            <li><code>POST /api/tables/:name/delete</code> - Delete rows by primary key, checking foreign key references first</li>
+            <li><code>GET /api/tables/:name/json-schema</code> - JSON Schema (draft-07) of a table row</li>
+            <li><code>GET /api/tables/:name/codegen?lang=rust|typescript</code> - Generate a Rust struct or TypeScript interface for a table</li>
+            <li><code>GET /api/tables/:name/export?format=csv</code> - Stream the filtered and sorted table as a CSV, Excel (<code>format=xlsx</code>) NDJSON (<code>format=ndjson</code>) or SQL <code>INSERT</code> (<code>format=sql</code>) download</li>
+            <li><code>POST /api/tables/:name/exports</code> - Export a table to a file as a job; download it from <code>GET /api/exports/:id</code> (resumable with <code>Range</code>)</li>
+            <li><code>POST /api/query</code> - Execute (or only validate) raw SQL queries, or download their rows</li>
+            <li><code>POST /api/query/inspect</code> - Explain an application query without running it</li>
+            <li><code>POST /api/query/format</code> - Pretty-print SQL for the database's dialect</li>
+            <li><code>POST /api/query/:id/cancel</code> - Cancel a running query sent with this id</li>
+            <li><code>GET /api/query/:id/rows</code> - Fetch more rows of a paged query result</li>
+            <li><code>DELETE /api/query/:id/rows</code> - Drop a paged query result</li>
+            <li><code>GET/POST /api/saved-queries</code> - List (by tag or favorite) or save queries</li>
+            <li><code>PUT/DELETE /api/saved-queries/:id</code> - Replace or delete a saved query</li>
+            <li><code>POST /api/permalinks</code> - Share a table view or query</li>
+            <li><code>GET /api/permalinks/:token</code> - Open a shared view</li>
+            <li><code>POST /api/explain</code> - Query plan of a statement as a tree, without running it (<code>analyze</code> for actual rows and timings)</li>
+            <li><code>POST /api/query/script</code> - Run several statements in one transaction, rolling back on error</li>
+            <li><code>POST /api/console/sessions</code> - Open an isolated console session</li>
+            <li><code>POST /api/console/sessions/:session/query</code> - Execute SQL in a console session</li>
+            <li><code>GET /api/console/sessions/:session/history</code> - List the statements run in a console session</li>
+            <li><code>DELETE /api/console/sessions/:session</code> - Close a console session</li>
+            <li><code>POST /api/tx/begin</code> - Begin a transaction on a dedicated connection</li>
+            <li><code>POST /api/tx/:tx/query</code> - Execute SQL in a transaction</li>
+            <li><code>POST /api/tx/:tx/commit</code> - Commit a transaction</li>
+            <li><code>POST /api/tx/:tx/rollback</code> - Roll back a transaction</li>
+            <li><code>GET /api/erd.mmd</code> - Mermaid ER diagram of all tables</li>
+            <li><code>GET /api/graph</code> - Table relationship graph (nodes and foreign key edges)</li>
+            <li><code>GET /api/tables/:name/columns/:column/lineage</code> - Columns referencing or referenced by a column through foreign keys (transitive)</li>
+            <li><code>POST /api/snapshots</code> - Open a snapshot for consistent paging (PostgreSQL)</li>
+            <li><code>POST /api/snapshots/named</code> - Save a table or all tables as a named snapshot (<code>GET</code> lists); <code>POST /api/snapshots/named/:name/restore</code> restores it (as jobs)</li>
+            <li><code>GET /api/snapshots/named/:name/diff?table=</code> - Rows added, removed and changed since a named snapshot</li>
+            <li><code>GET /api/resolve?table=&amp;key=</code> - Resolve a row reference (exists flag, label, referencing row counts) for permalinks</li>
+            <li><code>GET /api/seed</code> - Seed status; <code>POST /api/seed</code> saves the database as seed, <code>POST /api/seed/reset</code> resets to it (as jobs)</li>
+            <li><code>POST /api/maintenance/:operation?tables=</code> - Run <code>vacuum</code>, <code>analyze</code>, <code>reindex</code> or <code>checkpoint</code> (SQLite WAL) on selected tables or the whole database (as a job); <code>GET /api/maintenance/journal</code> shows the journal mode and WAL size</li>
+            <li><code>GET /api/migrations</code> - Migrations applied by sqlx (<code>_sqlx_migrations</code>) or Diesel (<code>__diesel_schema_migrations</code>) with versions, checksums and timestamps</li>
+            <li><code>POST /api/migrations/run</code> - Apply the pending migrations of the migrator passed to <code>with_migrator</code> (as a job)</li>
+            <li><code>GET /api/fixtures?tables=&amp;format=yaml</code> - Download selected tables as a fixture; <code>POST /api/fixtures</code> replaces their rows with a fixture's</li>
+            <li><code>GET /api/dump?tables=&amp;schemaOnly=&amp;dataOnly=&amp;inserts=</code> - Download selected tables as a SQL script with their schema and rows, like <code>pg_dump</code> (PostgreSQL only)</li>
+            <li><code>POST /api/attachments</code> - Attach another SQLite database file, listing its tables as <code>alias.table</code> (<code>GET</code> lists, <code>DELETE /api/attachments/:alias</code> detaches)</li>
+            <li><code>GET /api/activity</code> - Statements running on the database and the viewer's connection usage</li>
+            <li><code>GET /api/jobs/:id</code> - State of a background job (<code>GET /api/jobs</code> lists them)</li>
+            <li><code>GET /api/config</code> - Database dialect, supported features and API limits</li>
+            <li><code>GET /api/events</code> - Server-sent events (schema changes)</li>
+            <li><code>GET /api/notifications</code> - Server-sent events (database notifications)</li>
+            <li><code>GET /api/commands</code> - Available actions with their parameters (for command palettes and scripts)</li>
         </ul>
 
         <h2>📚 Documentation</h2>
@@ -248,10 +460,43 @@ pnpm build</pre>
             <p>This tool exposes your entire database and should NEVER be used in production or on public networks. It has no authentication or authorization built in.</p>
         </div>
     </div>
+    <script>
+        (function () {
+            const statusUrl = __STATUS_URL__;
+            const statusElement = document.getElementById("status");
+
+            async function checkStatus() {
+                try {
+                    const response = await fetch(statusUrl, { cache: "no-store" });
+                    const status = await response.json();
+                    if (status.ready) {
+                        window.location.reload();
+                        return;
+                    }
+                    statusElement.textContent = status.devServerUrl
+                        ? "Waiting for the dev server at " + status.devServerUrl + "..."
+                        : "Waiting for the frontend to be built...";
+                } catch (error) {
+                    // The server is most likely restarting after a rebuild
+                    statusElement.textContent = "Server not reachable, retrying...";
+                }
+                setTimeout(checkStatus, 2000);
+            }
+
+            checkStatus();
+        })();
+    </script>
 </body>
 </html>
 "#;
 
+    let status_url = serde_json::to_string(&format!("{}/frontend-status", state.base_path))
+        .unwrap_or_default()
+        .replace("</", "<\\/");
+    let html = html
+        .replace("__STATUS_URL__", &status_url)
+        .replace("__BASE_PATH__", &escape_html(&state.base_path));
+
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
@@ -260,6 +505,14 @@ pnpm build</pre>
         .unwrap()
 }
 
+/// Escape text for inclusion in HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,7 +553,7 @@ mod tests {
 
     #[test]
     fn test_fallback_page_has_content() {
-        let response = serve_fallback_page();
+        let response = serve_fallback_page(&FrontendState::new("/sql-viewer".to_string()));
         assert_eq!(response.status(), StatusCode::OK);
 
         // Verify content-type header
@@ -309,6 +562,11 @@ mod tests {
         assert_eq!(content_type.unwrap(), "text/html; charset=utf-8");
     }
 
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("/a<b>&\"c\""), "/a&lt;b&gt;&amp;&quot;c&quot;");
+    }
+
     #[test]
     fn test_router_creation() {
         let router = create_frontend_router("/sql-viewer".to_string());
@@ -0,0 +1,337 @@
+//! Bucketed distributions of numeric and date columns
+//!
+//! A histogram splits the range between a column's smallest and largest value
+//! into buckets of equal width and counts the values in each, so clients can
+//! draw the shape of a column without reading its rows. Two queries are
+//! needed: one for the range, and one grouping the values by bucket, with
+//! `width_bucket` on PostgreSQL and a `CASE` over the bucket bounds on SQLite.
+//!
+//! Dates and timestamps are bucketed as seconds since the Unix epoch; their
+//! bucket bounds are returned as ISO 8601 timestamps in UTC.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::codegen::{column_kind, ColumnKind};
+use crate::schema::ColumnInfo;
+use crate::sql::{quote_identifier, Dialect};
+
+/// Number of buckets when not given
+pub const DEFAULT_BUCKETS: u32 = 20;
+
+/// Largest number of buckets of a histogram
+pub const MAX_BUCKETS: u32 = 100;
+
+/// Scale a column's values are bucketed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistogramScale {
+    /// Numbers; bounds are numbers
+    Number,
+
+    /// Dates and timestamps; bounds are ISO 8601 timestamps
+    Time,
+}
+
+/// Values between two bounds (the lower one included)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    /// Lower bound
+    pub lower: Value,
+
+    /// Upper bound (included for the last bucket)
+    pub upper: Value,
+
+    /// Number of values in the bucket
+    pub count: u64,
+}
+
+/// Distribution of a column's values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Histogram {
+    /// Table name
+    pub table: String,
+
+    /// Column name
+    pub column: String,
+
+    /// SQL data type of the column
+    pub data_type: String,
+
+    /// Whether the bounds are numbers or timestamps
+    pub scale: HistogramScale,
+
+    /// Buckets from the smallest to the largest value (empty without values)
+    pub buckets: Vec<HistogramBucket>,
+
+    /// Number of rows with NULL in the column, which are in no bucket
+    pub null_count: u64,
+}
+
+/// Scale of a column, if it can have a histogram
+pub fn histogram_scale(column: &ColumnInfo) -> Option<HistogramScale> {
+    match column_kind(&column.data_type) {
+        ColumnKind::Integer | ColumnKind::Float | ColumnKind::Decimal => {
+            Some(HistogramScale::Number)
+        }
+        ColumnKind::Date | ColumnKind::DateTime => Some(HistogramScale::Time),
+        _ => None,
+    }
+}
+
+/// Number of buckets used when `requested` were asked for (1 to [`MAX_BUCKETS`])
+pub fn bucket_count(requested: u32) -> u32 {
+    requested.clamp(1, MAX_BUCKETS)
+}
+
+/// Expression of a column's value on its scale, as a floating point number
+pub fn value_expression(column: &str, scale: HistogramScale, dialect: Dialect) -> String {
+    let quoted = quote_identifier(column);
+    match (scale, dialect) {
+        (HistogramScale::Number, Dialect::Sqlite) => format!("CAST({} AS REAL)", quoted),
+        (HistogramScale::Number, Dialect::Postgres) => format!("{}::float8", quoted),
+        (HistogramScale::Time, Dialect::Sqlite) => {
+            format!("(julianday({}) - 2440587.5) * 86400.0", quoted)
+        }
+        (HistogramScale::Time, Dialect::Postgres) => {
+            format!("extract(epoch FROM {})::float8", quoted)
+        }
+    }
+}
+
+/// Expression numbering the bucket (1 to `buckets`) of the value `value`
+///
+/// The largest value is counted in the last bucket. When all values are
+/// equal there is only one bucket.
+pub fn bucket_expression(
+    value: &str,
+    lower: f64,
+    upper: f64,
+    buckets: u32,
+    dialect: Dialect,
+) -> String {
+    if buckets == 1 || lower >= upper {
+        return "1".to_string();
+    }
+
+    match dialect {
+        Dialect::Postgres => format!(
+            "LEAST(width_bucket({}, {}, {}, {}), {})",
+            value, lower, upper, buckets, buckets
+        ),
+        Dialect::Sqlite => {
+            let width = (upper - lower) / f64::from(buckets);
+            let cases: String = (1..buckets)
+                .map(|bucket| {
+                    format!(
+                        " WHEN {} < {} THEN {}",
+                        value,
+                        lower + width * f64::from(bucket),
+                        bucket
+                    )
+                })
+                .collect();
+            format!("CASE{} ELSE {} END", cases, buckets)
+        }
+    }
+}
+
+/// Query reading the smallest and largest value of a column and its NULL count
+///
+/// `table` is the quoted table name.
+pub fn range_query(table: &str, column: &str, scale: HistogramScale, dialect: Dialect) -> String {
+    let value = value_expression(column, scale, dialect);
+    format!(
+        "SELECT MIN({}), MAX({}), COUNT(*) - COUNT({}) FROM {}",
+        value,
+        value,
+        quote_identifier(column),
+        table
+    )
+}
+
+/// Query counting the values of a column by bucket number, between `lower` and `upper`
+///
+/// `table` is the quoted table name.
+pub fn counts_query(
+    table: &str,
+    column: &str,
+    scale: HistogramScale,
+    (lower, upper): (f64, f64),
+    buckets: u32,
+    dialect: Dialect,
+) -> String {
+    let value = value_expression(column, scale, dialect);
+    let bucket = bucket_expression(&value, lower, upper, buckets, dialect);
+    let bucket = match dialect {
+        Dialect::Sqlite => bucket,
+        Dialect::Postgres => format!("({})::bigint", bucket),
+    };
+    format!(
+        "SELECT {}, COUNT(*) FROM {} WHERE {} IS NOT NULL GROUP BY 1",
+        bucket, table, value
+    )
+}
+
+/// Buckets between `lower` and `upper` with the `counts` read by bucket number
+///
+/// Buckets without values are included with a count of 0.
+pub fn histogram_buckets(
+    lower: f64,
+    upper: f64,
+    buckets: u32,
+    counts: &[(i64, i64)],
+    scale: HistogramScale,
+) -> Vec<HistogramBucket> {
+    let buckets = if lower >= upper { 1 } else { buckets };
+    let width = (upper - lower) / f64::from(buckets);
+    (1..=buckets)
+        .map(|bucket| {
+            let count = counts
+                .iter()
+                .filter(|(number, _)| *number == i64::from(bucket))
+                .map(|(_, count)| *count as u64)
+                .sum();
+            let upper = if bucket == buckets {
+                upper
+            } else {
+                lower + width * f64::from(bucket)
+            };
+            HistogramBucket {
+                lower: bound(lower + width * f64::from(bucket - 1), scale),
+                upper: bound(upper, scale),
+                count,
+            }
+        })
+        .collect()
+}
+
+/// JSON value of a bucket bound on `scale`
+fn bound(value: f64, scale: HistogramScale) -> Value {
+    match scale {
+        HistogramScale::Number => serde_json::json!(value),
+        HistogramScale::Time => Value::String(format_epoch(value)),
+    }
+}
+
+/// ISO 8601 timestamp in UTC of `seconds` since the Unix epoch, to the second
+fn format_epoch(seconds: f64) -> String {
+    let seconds = seconds.round() as i64;
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Year, month and day of the day `days` after 1970-01-01 (proleptic Gregorian calendar)
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Counted in 400-year eras starting on March 1st, so leap days end a year
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn column(data_type: &str) -> ColumnInfo {
+        test_support::column("value", data_type)
+    }
+
+    #[test]
+    fn test_histogram_scale() {
+        assert_eq!(
+            histogram_scale(&column("integer")),
+            Some(HistogramScale::Number)
+        );
+        assert_eq!(
+            histogram_scale(&column("NUMERIC(10, 2)")),
+            Some(HistogramScale::Number)
+        );
+        assert_eq!(
+            histogram_scale(&column("timestamp with time zone")),
+            Some(HistogramScale::Time)
+        );
+        assert_eq!(histogram_scale(&column("text")), None);
+        assert_eq!(bucket_count(0), 1);
+        assert_eq!(bucket_count(1000), MAX_BUCKETS);
+    }
+
+    #[test]
+    fn test_bucket_expression() {
+        assert_eq!(
+            bucket_expression("v", 0.0, 10.0, 4, Dialect::Postgres),
+            "LEAST(width_bucket(v, 0, 10, 4), 4)"
+        );
+        assert_eq!(
+            bucket_expression("v", 0.0, 10.0, 4, Dialect::Sqlite),
+            "CASE WHEN v < 2.5 THEN 1 WHEN v < 5 THEN 2 WHEN v < 7.5 THEN 3 ELSE 4 END"
+        );
+        assert_eq!(bucket_expression("v", 3.0, 3.0, 4, Dialect::Postgres), "1");
+    }
+
+    #[test]
+    fn test_queries() {
+        assert_eq!(
+            range_query("\"events\"", "at", HistogramScale::Time, Dialect::Postgres),
+            "SELECT MIN(extract(epoch FROM \"at\")::float8), \
+             MAX(extract(epoch FROM \"at\")::float8), COUNT(*) - COUNT(\"at\") FROM \"events\""
+        );
+        assert_eq!(
+            counts_query(
+                "\"orders\"",
+                "total",
+                HistogramScale::Number,
+                (0.0, 10.0),
+                2,
+                Dialect::Sqlite
+            ),
+            "SELECT CASE WHEN CAST(\"total\" AS REAL) < 5 THEN 1 ELSE 2 END, COUNT(*) \
+             FROM \"orders\" WHERE CAST(\"total\" AS REAL) IS NOT NULL GROUP BY 1"
+        );
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let buckets = histogram_buckets(0.0, 10.0, 4, &[(1, 3), (4, 2)], HistogramScale::Number);
+        let counts: Vec<u64> = buckets.iter().map(|bucket| bucket.count).collect();
+        assert_eq!(counts, vec![3, 0, 0, 2]);
+        assert_eq!(buckets[1].lower, serde_json::json!(2.5));
+        assert_eq!(buckets[3].upper, serde_json::json!(10.0));
+
+        let single = histogram_buckets(3.0, 3.0, 4, &[(1, 7)], HistogramScale::Number);
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].count, 7);
+    }
+
+    #[test]
+    fn test_format_epoch() {
+        assert_eq!(format_epoch(0.0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_epoch(1_709_210_096.4), "2024-02-29T12:34:56Z");
+        assert_eq!(format_epoch(-86_400.0), "1969-12-31T00:00:00Z");
+    }
+}
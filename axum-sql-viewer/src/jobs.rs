@@ -0,0 +1,221 @@
+//! Background jobs
+//!
+//! Long-running operations (e.g. resetting the database to its seed) run as
+//! jobs: the request starting them returns immediately with a job identifier,
+//! and the job's progress is polled at `/api/jobs/:id`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runtime;
+
+/// Number of finished jobs kept for polling
+const FINISHED_JOB_CAPACITY: usize = 100;
+
+/// State of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Information about a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    /// Job identifier
+    pub id: u64,
+
+    /// Kind of job (e.g. "seed.reset")
+    pub kind: String,
+
+    /// Current state
+    pub status: JobStatus,
+
+    /// Start time in milliseconds since the Unix epoch
+    pub started_at_milliseconds: u64,
+
+    /// End time in milliseconds since the Unix epoch (once finished)
+    pub finished_at_milliseconds: Option<u64>,
+
+    /// Result of a successful job
+    pub result: Option<serde_json::Value>,
+
+    /// Error message of a failed job
+    pub error: Option<String>,
+}
+
+/// Registry of running and recently finished jobs
+///
+/// Cloning a `JobRegistry` is cheap; all clones share the same jobs.
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    inner: Arc<JobRegistryInner>,
+}
+
+#[derive(Default)]
+struct JobRegistryInner {
+    next_id: AtomicU64,
+    jobs: Mutex<VecDeque<JobInfo>>,
+}
+
+impl JobRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a job running `future`
+    ///
+    /// With the `runtime-tokio` feature the job runs in the background and this
+    /// returns right away; without it the job has finished when this returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Kind of job, reported in its information
+    /// * `future` - Work of the job, producing a JSON result or an error message
+    ///
+    /// # Returns
+    ///
+    /// Information about the job at the time this returns
+    pub async fn start<F>(&self, kind: &str, future: F) -> JobInfo
+    where
+        F: Future<Output = Result<serde_json::Value, String>> + Send + 'static,
+    {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = JobInfo {
+            id,
+            kind: kind.to_string(),
+            status: JobStatus::Running,
+            started_at_milliseconds: now_milliseconds(),
+            finished_at_milliseconds: None,
+            result: None,
+            error: None,
+        };
+        self.inner.jobs.lock().unwrap().push_back(job.clone());
+
+        let registry = self.clone();
+        runtime::spawn(async move {
+            let outcome = future.await;
+            registry.finish(id, outcome);
+        })
+        .await;
+
+        self.get(id).unwrap_or(job)
+    }
+
+    /// Get a job by identifier
+    pub fn get(&self, id: u64) -> Option<JobInfo> {
+        let jobs = self.inner.jobs.lock().unwrap();
+        jobs.iter().find(|job| job.id == id).cloned()
+    }
+
+    /// All known jobs, most recent first
+    pub fn list(&self) -> Vec<JobInfo> {
+        let jobs = self.inner.jobs.lock().unwrap();
+        jobs.iter().rev().cloned().collect()
+    }
+
+    /// Whether a job matching `predicate` on its kind is still running
+    pub fn is_running(&self, predicate: impl Fn(&str) -> bool) -> bool {
+        let jobs = self.inner.jobs.lock().unwrap();
+        jobs.iter()
+            .any(|job| job.status == JobStatus::Running && predicate(&job.kind))
+    }
+
+    /// Record the outcome of a job, dropping the oldest finished jobs
+    fn finish(&self, id: u64, outcome: Result<serde_json::Value, String>) {
+        let mut jobs = self.inner.jobs.lock().unwrap();
+
+        if let Some(job) = jobs.iter_mut().find(|job| job.id == id) {
+            job.finished_at_milliseconds = Some(now_milliseconds());
+            match outcome {
+                Ok(result) => {
+                    job.status = JobStatus::Succeeded;
+                    job.result = Some(result);
+                }
+                Err(error) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(error);
+                }
+            }
+        }
+
+        let mut finished = jobs
+            .iter()
+            .filter(|job| job.status != JobStatus::Running)
+            .count();
+        while finished > FINISHED_JOB_CAPACITY {
+            match jobs.iter().position(|job| job.status != JobStatus::Running) {
+                Some(index) => {
+                    jobs.remove(index);
+                    finished -= 1;
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+fn now_milliseconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn running_job(registry: &JobRegistry, kind: &str) -> u64 {
+        let id = registry.inner.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        registry.inner.jobs.lock().unwrap().push_back(JobInfo {
+            id,
+            kind: kind.to_string(),
+            status: JobStatus::Running,
+            started_at_milliseconds: 0,
+            finished_at_milliseconds: None,
+            result: None,
+            error: None,
+        });
+        id
+    }
+
+    #[test]
+    fn test_finish_records_outcome() {
+        let registry = JobRegistry::new();
+        let id = running_job(&registry, "seed.reset");
+        assert!(registry.is_running(|kind| kind.starts_with("seed.")));
+
+        registry.finish(id, Err("boom".to_string()));
+
+        let job = registry.get(id).unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error.as_deref(), Some("boom"));
+        assert!(job.finished_at_milliseconds.is_some());
+        assert!(!registry.is_running(|kind| kind.starts_with("seed.")));
+    }
+
+    #[test]
+    fn test_finished_jobs_are_bounded() {
+        let registry = JobRegistry::new();
+        let running = running_job(&registry, "long");
+
+        for _ in 0..FINISHED_JOB_CAPACITY + 5 {
+            let id = running_job(&registry, "short");
+            registry.finish(id, Ok(serde_json::Value::Null));
+        }
+
+        let jobs = registry.list();
+        assert_eq!(jobs.len(), FINISHED_JOB_CAPACITY + 1);
+        assert!(registry.get(running).is_some());
+        assert_eq!(jobs[0].id, FINISHED_JOB_CAPACITY as u64 + 6);
+    }
+}
@@ -0,0 +1,121 @@
+//! Row responses serialized while they are sent
+//!
+//! `Json(response)` writes the whole body into one buffer before anything is
+//! sent, so a page of wide rows is held twice: as `serde_json::Value`s and as
+//! text. [`streamed_rows`] writes the other fields up front and the rows a
+//! chunk at a time while the body is read, dropping each row once it is
+//! written, so large TEXT and BLOB values are not held twice.
+
+use axum::body::Body;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Rows serialized into one chunk of the body
+const ROWS_PER_CHUNK: usize = 64;
+
+/// JSON response of `value` with `rows` as its `rows` field, written a chunk of rows at a time
+///
+/// `value` is serialized without its own `rows` field (which callers leave
+/// empty, e.g. with `std::mem::take`).
+pub fn streamed_rows<T: Serialize>(status: StatusCode, value: &T, rows: Vec<Value>) -> Response {
+    let (head, tail) = match envelope(value) {
+        Ok(parts) => parts,
+        Err(error) => {
+            eprintln!("Failed to serialize response: {}", error);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": error })),
+            )
+                .into_response();
+        }
+    };
+
+    let chunks = body_chunks(head, rows, tail).map(Ok::<_, std::io::Error>);
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        Body::from_stream(futures_util::stream::iter(chunks)),
+    )
+        .into_response()
+}
+
+/// Text before and after the rows: `{"rows":[` and `],...other fields}`
+fn envelope<T: Serialize>(value: &T) -> Result<(String, String), String> {
+    let Value::Object(mut fields) =
+        serde_json::to_value(value).map_err(|error| error.to_string())?
+    else {
+        return Err("response is not a JSON object".to_string());
+    };
+    fields.remove("rows");
+
+    let others = serde_json::to_string(&fields).map_err(|error| error.to_string())?;
+    let tail = if fields.is_empty() {
+        "]}".to_string()
+    } else {
+        // Continue the object after the rows with the other fields
+        format!("],{}", &others[1..])
+    };
+    Ok((r#"{"rows":["#.to_string(), tail))
+}
+
+/// Chunks of the body, serializing `rows` only as the chunks are taken
+fn body_chunks(
+    head: String,
+    rows: Vec<Value>,
+    tail: String,
+) -> impl Iterator<Item = Vec<u8>> + Send + 'static {
+    let mut rows = rows.into_iter();
+    let mut first = true;
+    let row_chunks = std::iter::from_fn(move || {
+        let mut chunk = Vec::new();
+        for row in rows.by_ref().take(ROWS_PER_CHUNK) {
+            if !first {
+                chunk.push(b',');
+            }
+            first = false;
+            // Serializing a `Value` into memory cannot fail
+            let _ = serde_json::to_writer(&mut chunk, &row);
+        }
+        (!chunk.is_empty()).then_some(chunk)
+    });
+
+    std::iter::once(head.into_bytes())
+        .chain(row_chunks)
+        .chain(std::iter::once(tail.into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn body(value: &Value, rows: Vec<Value>) -> Value {
+        let (head, tail) = envelope(value).unwrap();
+        let bytes: Vec<u8> = body_chunks(head, rows, tail).flatten().collect();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_streamed_body_matches_json() {
+        let rows: Vec<Value> = (0..150)
+            .map(|id| json!({"id": id, "name": format!("row {}", id)}))
+            .collect();
+        let response = json!({"rows": [], "total": 150, "hasMore": false});
+
+        let mut expected = response.clone();
+        expected["rows"] = Value::Array(rows.clone());
+        assert_eq!(body(&response, rows), expected);
+    }
+
+    #[test]
+    fn test_streamed_body_edge_cases() {
+        assert_eq!(body(&json!({}), Vec::new()), json!({"rows": []}));
+        assert_eq!(
+            body(&json!({"rows": [1]}), vec![json!(2)]),
+            json!({"rows": [2]})
+        );
+        assert!(envelope(&json!([1, 2])).is_err());
+    }
+}
@@ -0,0 +1,476 @@
+//! Keyset (seek) pagination of table rows
+//!
+//! Pages read with `OFFSET` get slower the further in they start, since the
+//! database still walks past every skipped row. A page read with a cursor
+//! instead continues after the last row of the previous page: rows are
+//! ordered by the sort column and then by the row key (primary key or
+//! `rowid`), and the next page is selected with `WHERE (sort, key) > (?, ?)`,
+//! which an index on the key (and sort column) answers directly.
+//!
+//! Cursors are opaque to clients: the JSON of a [`RowCursor`], hex-encoded.
+//! Tables addressed by `ctid` get no cursors, as PostgreSQL cannot read them
+//! in `ctid` order without sorting the whole table.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::database::traits::DatabaseError;
+use crate::schema::{NullsOrder, RowAddress, RowQuery, SortOrder, TableSchema};
+use crate::sql::{quote_identifier, Dialect};
+
+/// Position after the last row of a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowCursor {
+    /// Offset of the next page, reported back in its response
+    pub offset: u64,
+
+    /// Column the rows are sorted by
+    pub sort_by: Option<String>,
+
+    /// Whether the rows are sorted in descending order
+    pub descending: bool,
+
+    /// Where NULLs of the sort column were asked to go
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nulls: Option<NullsOrder>,
+
+    /// Value of the sort column in the last row
+    pub sort_value: Value,
+
+    /// Values of the key columns in the last row
+    pub key: Vec<Value>,
+}
+
+impl RowCursor {
+    /// Cursor after the last of `rows` (read at `offset`), if the rows have key values
+    pub fn after(
+        rows: &[Value],
+        offset: u64,
+        query: &RowQuery,
+        key_columns: &[String],
+    ) -> Option<Self> {
+        let last = rows.last()?;
+        // Rows fetched without the sort column cannot be continued after
+        let sort_value = match &query.sort_by {
+            Some(column) => last.get(column)?.clone(),
+            None => Value::Null,
+        };
+        let key = key_columns
+            .iter()
+            .map(|column| last.get(column).filter(|value| !value.is_null()).cloned())
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            offset: offset + rows.len() as u64,
+            sort_by: query.sort_by.clone(),
+            descending: is_descending(query),
+            nulls: query.nulls,
+            sort_value,
+            key,
+        })
+    }
+
+    /// Opaque text handed to clients
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        json.bytes().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Read the cursor sent with `query`, checking that it was made for the same sort
+    pub fn decode(text: &str, query: &RowQuery) -> Result<Self, DatabaseError> {
+        let invalid = || DatabaseError::InvalidCursor("not a cursor of this endpoint".to_string());
+        if !text.len().is_multiple_of(2) || !text.is_ascii() {
+            return Err(invalid());
+        }
+        let bytes = (0..text.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&text[index..index + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        let cursor: Self = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+
+        if cursor.sort_by != query.sort_by
+            || cursor.descending != is_descending(query)
+            || cursor.nulls != query.nulls
+        {
+            return Err(DatabaseError::InvalidCursor(
+                "the sort column or order changed; start again without a cursor".to_string(),
+            ));
+        }
+        Ok(cursor)
+    }
+}
+
+/// Whether `query` sorts in descending order (ascending when not set)
+fn is_descending(query: &RowQuery) -> bool {
+    matches!(query.sort_order, Some(SortOrder::Descending))
+}
+
+/// Columns identifying rows in keyset order, if the table has any
+///
+/// These are the primary key columns, or `rowid` for SQLite tables without
+/// one.
+pub fn key_columns(schema: &TableSchema) -> Option<Vec<String>> {
+    match schema
+        .primary_key
+        .as_ref()
+        .filter(|columns| !columns.is_empty())
+    {
+        Some(columns) => Some(columns.clone()),
+        None if schema.row_address == RowAddress::Rowid => Some(vec!["rowid".to_string()]),
+        None => None,
+    }
+}
+
+/// Columns ending the `ORDER BY` of `query` so rows sharing a sort value keep their order
+///
+/// These are the [`key_columns`], or `ctid` for PostgreSQL tables addressed
+/// by it when a sort column is set. Without them, pages read by offset could
+/// overlap or skip rows whose sort values are equal. Unsorted `ctid` tables
+/// are left unordered rather than sorting the whole table for every page.
+pub fn order_columns(schema: &TableSchema, query: &RowQuery) -> Option<Vec<String>> {
+    key_columns(schema).or_else(|| {
+        (schema.row_address == RowAddress::Ctid && query.sort_by.is_some())
+            .then(|| vec!["ctid".to_string()])
+    })
+}
+
+/// Key columns of a table read with a cursor
+pub fn required_key_columns(schema: &TableSchema) -> Result<Vec<String>, DatabaseError> {
+    key_columns(schema).ok_or_else(|| {
+        DatabaseError::InvalidCursor(format!(
+            "table '{}' has no primary key or rowid to continue after",
+            schema.name
+        ))
+    })
+}
+
+/// Whether the column `query` sorts by can hold NULL
+pub fn sort_nullable(schema: &TableSchema, query: &RowQuery) -> bool {
+    schema
+        .columns
+        .iter()
+        .any(|column| Some(&column.name) == query.sort_by.as_ref() && column.nullable)
+}
+
+/// Whether NULLs of the sort column come first, as asked for or by the database's default
+///
+/// Without `nulls`, SQLite puts NULLs first in ascending order and PostgreSQL
+/// last; descending order reverses both.
+fn nulls_first(nulls: Option<NullsOrder>, descending: bool, dialect: Dialect) -> bool {
+    match nulls {
+        Some(nulls) => nulls == NullsOrder::First,
+        None => (dialect == Dialect::Sqlite) != descending,
+    }
+}
+
+/// `ORDER BY` clause for `query`, ending with the key columns (if any) so the order is total
+///
+/// With `nulls` set, PostgreSQL sorts by the column with `NULLS FIRST` or
+/// `NULLS LAST`, and SQLite by `column IS NULL` before the column itself.
+pub fn order_clause(query: &RowQuery, key_columns: Option<&[String]>, dialect: Dialect) -> String {
+    let direction = if is_descending(query) { "DESC" } else { "ASC" };
+    let mut columns: Vec<String> = Vec::new();
+    if let Some(sort_by) = &query.sort_by {
+        let sort = quote_identifier(sort_by);
+        match (query.nulls, dialect) {
+            (None, _) => columns.push(format!("{} {}", sort, direction)),
+            (Some(nulls), Dialect::Postgres) => {
+                let position = match nulls {
+                    NullsOrder::First => "FIRST",
+                    NullsOrder::Last => "LAST",
+                };
+                columns.push(format!("{} {} NULLS {}", sort, direction, position));
+            }
+            (Some(nulls), Dialect::Sqlite) => {
+                // `IS NULL` is 1 for NULLs, so ascending puts them last
+                let position = match nulls {
+                    NullsOrder::First => "DESC",
+                    NullsOrder::Last => "ASC",
+                };
+                columns.push(format!("{} IS NULL {}", sort, position));
+                columns.push(format!("{} {}", sort, direction));
+            }
+        }
+    }
+    columns.extend(
+        key_columns
+            .unwrap_or_default()
+            .iter()
+            .map(|column| format!("{} {}", quote_identifier(column), direction)),
+    );
+
+    if columns.is_empty() {
+        String::new()
+    } else {
+        format!(" ORDER BY {}", columns.join(", "))
+    }
+}
+
+/// Condition selecting the rows after `cursor`, and the values to bind in order
+///
+/// `placeholder` returns the parameter placeholder for a value of the given
+/// column (e.g. `?`, or `$3::integer`). NULLs of the sort column go where
+/// the cursor's `nulls` put them, or by default first in ascending order on
+/// SQLite and last on PostgreSQL; `sort_nullable` is false when the sort
+/// column cannot hold them.
+pub fn keyset_condition(
+    cursor: &RowCursor,
+    key_columns: &[String],
+    sort_nullable: bool,
+    dialect: Dialect,
+    mut placeholder: impl FnMut(&str) -> String,
+) -> (String, Vec<Value>) {
+    let operator = if cursor.descending { "<" } else { ">" };
+    let row_comparison = |columns: Vec<&String>, placeholders: Vec<String>| {
+        let columns: Vec<String> = columns
+            .into_iter()
+            .map(|column| quote_identifier(column))
+            .collect();
+        if columns.len() == 1 {
+            format!("{} {} {}", columns[0], operator, placeholders[0])
+        } else {
+            format!(
+                "({}) {} ({})",
+                columns.join(", "),
+                operator,
+                placeholders.join(", ")
+            )
+        }
+    };
+
+    let Some(sort_column) = &cursor.sort_by else {
+        let placeholders = key_columns
+            .iter()
+            .map(|column| placeholder(column))
+            .collect();
+        return (
+            row_comparison(key_columns.iter().collect(), placeholders),
+            cursor.key.clone(),
+        );
+    };
+
+    let nulls_first = nulls_first(cursor.nulls, cursor.descending, dialect);
+    let sort = quote_identifier(sort_column);
+
+    if cursor.sort_value.is_null() {
+        // Past the other rows with NULL, then (if NULLs come first) on to the non-NULL values
+        let placeholders = key_columns
+            .iter()
+            .map(|column| placeholder(column))
+            .collect();
+        let condition = format!(
+            "({} IS NULL AND {})",
+            sort,
+            row_comparison(key_columns.iter().collect(), placeholders)
+        );
+        let condition = if nulls_first {
+            format!("({} OR {} IS NOT NULL)", condition, sort)
+        } else {
+            condition
+        };
+        return (condition, cursor.key.clone());
+    }
+
+    let placeholders = std::iter::once(sort_column)
+        .chain(key_columns)
+        .map(|column| placeholder(column))
+        .collect();
+    let condition = row_comparison(
+        std::iter::once(sort_column).chain(key_columns).collect(),
+        placeholders,
+    );
+    let condition = if sort_nullable && !nulls_first {
+        format!("({} OR {} IS NULL)", condition, sort)
+    } else {
+        condition
+    };
+
+    let mut values = vec![cursor.sort_value.clone()];
+    values.extend(cursor.key.iter().cloned());
+    (condition, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn query(sort_by: Option<&str>, sort_order: Option<SortOrder>) -> RowQuery {
+        RowQuery {
+            offset: 0,
+            limit: 2,
+            sort_by: sort_by.map(str::to_string),
+            sort_order,
+            nulls: None,
+            filters: HashMap::new(),
+            ranges: Vec::new(),
+            filter_groups: Vec::new(),
+            snapshot: None,
+            cursor: None,
+            count: true,
+            columns: None,
+            case_insensitive: false,
+            q: None,
+        }
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let query = query(Some("name"), Some(SortOrder::Descending));
+        let rows = [json!({"id": 1, "name": "b"}), json!({"id": 2, "name": "a"})];
+        let cursor = RowCursor::after(&rows, 100, &query, &["id".to_string()]).unwrap();
+        assert_eq!(cursor.offset, 102);
+        assert_eq!(cursor.sort_value, json!("a"));
+        assert_eq!(cursor.key, vec![json!(2)]);
+
+        let text = cursor.encode();
+        assert!(text.chars().all(|character| character.is_ascii_hexdigit()));
+        assert_eq!(RowCursor::decode(&text, &query).unwrap(), cursor);
+
+        let resorted = RowCursor::decode(&text, &self::query(Some("name"), None));
+        assert!(matches!(resorted, Err(DatabaseError::InvalidCursor(_))));
+        assert!(matches!(
+            RowCursor::decode("zz", &query),
+            Err(DatabaseError::InvalidCursor(_))
+        ));
+    }
+
+    #[test]
+    fn test_cursor_needs_key_values() {
+        let query = query(None, None);
+        let rows = [json!({"id": null})];
+        assert_eq!(
+            RowCursor::after(&rows, 0, &query, &["id".to_string()]),
+            None
+        );
+        assert_eq!(RowCursor::after(&[], 0, &query, &["id".to_string()]), None);
+
+        let sorted = self::query(Some("name"), None);
+        let rows = [json!({"id": 1})];
+        assert_eq!(
+            RowCursor::after(&rows, 0, &sorted, &["id".to_string()]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_order_clause() {
+        let key = ["id".to_string()];
+        assert_eq!(
+            order_clause(&query(None, None), Some(&key), Dialect::Sqlite),
+            " ORDER BY \"id\" ASC"
+        );
+        assert_eq!(
+            order_clause(
+                &query(Some("name"), Some(SortOrder::Descending)),
+                Some(&key),
+                Dialect::Postgres
+            ),
+            " ORDER BY \"name\" DESC, \"id\" DESC"
+        );
+        assert_eq!(order_clause(&query(None, None), None, Dialect::Sqlite), "");
+
+        let nulls_last = RowQuery {
+            nulls: Some(NullsOrder::Last),
+            ..query(Some("deleted_at"), Some(SortOrder::Descending))
+        };
+        assert_eq!(
+            order_clause(&nulls_last, Some(&key), Dialect::Postgres),
+            " ORDER BY \"deleted_at\" DESC NULLS LAST, \"id\" DESC"
+        );
+        assert_eq!(
+            order_clause(&nulls_last, Some(&key), Dialect::Sqlite),
+            " ORDER BY \"deleted_at\" IS NULL ASC, \"deleted_at\" DESC, \"id\" DESC"
+        );
+    }
+
+    #[test]
+    fn test_order_columns() {
+        let mut schema = TableSchema {
+            name: "events".to_string(),
+            columns: Vec::new(),
+            primary_key: Some(vec!["id".to_string()]),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: RowAddress::PrimaryKey,
+        };
+        let sorted = query(Some("name"), None);
+        assert_eq!(
+            order_columns(&schema, &sorted),
+            Some(vec!["id".to_string()])
+        );
+
+        schema.primary_key = None;
+        schema.row_address = RowAddress::Ctid;
+        assert_eq!(
+            order_columns(&schema, &sorted),
+            Some(vec!["ctid".to_string()])
+        );
+        assert_eq!(order_columns(&schema, &query(None, None)), None);
+
+        schema.row_address = RowAddress::None;
+        assert_eq!(order_columns(&schema, &sorted), None);
+    }
+
+    #[test]
+    fn test_keyset_condition() {
+        let key = ["a".to_string(), "b".to_string()];
+        let cursor = RowCursor {
+            offset: 10,
+            sort_by: None,
+            descending: false,
+            nulls: None,
+            sort_value: Value::Null,
+            key: vec![json!(1), json!(2)],
+        };
+        let (sql, values) = keyset_condition(&cursor, &key, true, Dialect::Sqlite, |_| "?".into());
+        assert_eq!(sql, "(\"a\", \"b\") > (?, ?)");
+        assert_eq!(values, vec![json!(1), json!(2)]);
+
+        let key = ["id".to_string()];
+        let sorted = RowCursor {
+            sort_by: Some("name".to_string()),
+            sort_value: json!("m"),
+            key: vec![json!(5)],
+            ..cursor
+        };
+        let mut index = 0;
+        let (sql, values) = keyset_condition(&sorted, &key, true, Dialect::Postgres, |column| {
+            index += 1;
+            format!(
+                "${}::{}",
+                index,
+                if column == "id" { "integer" } else { "text" }
+            )
+        });
+        assert_eq!(
+            sql,
+            "((\"name\", \"id\") > ($1::text, $2::integer) OR \"name\" IS NULL)"
+        );
+        assert_eq!(values, vec![json!("m"), json!(5)]);
+
+        let (sql, _) = keyset_condition(&sorted, &key, true, Dialect::Sqlite, |_| "?".into());
+        assert_eq!(sql, "(\"name\", \"id\") > (?, ?)");
+
+        let null = RowCursor {
+            sort_value: Value::Null,
+            ..sorted.clone()
+        };
+        let (sql, values) = keyset_condition(&null, &key, true, Dialect::Sqlite, |_| "?".into());
+        assert_eq!(
+            sql,
+            "((\"name\" IS NULL AND \"id\" > ?) OR \"name\" IS NOT NULL)"
+        );
+        assert_eq!(values, vec![json!(5)]);
+
+        let nulls_last = RowCursor {
+            nulls: Some(NullsOrder::Last),
+            ..sorted
+        };
+        let (sql, _) = keyset_condition(&nulls_last, &key, true, Dialect::Sqlite, |_| "?".into());
+        assert_eq!(sql, "((\"name\", \"id\") > (?, ?) OR \"name\" IS NULL)");
+    }
+}
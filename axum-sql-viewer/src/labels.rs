@@ -0,0 +1,147 @@
+//! Labels for foreign key values
+//!
+//! A foreign key value such as `user_id = 7` is easier to read with the label
+//! of the referenced row next to it (`7 (Alice Johnson)`). Labels are taken
+//! from the display column of the referenced table, which can be configured
+//! per table and is otherwise guessed with [`display_column`].
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::row_key::{display_column, value_to_key_text};
+use crate::schema::TableSchema;
+
+/// Labels of referenced rows, by foreign key column and then by value
+pub type ForeignKeyLabels = HashMap<String, HashMap<String, String>>;
+
+/// Configured display columns, by table name
+///
+/// Cloning `DisplayColumns` is cheap; all clones share the same configuration.
+#[derive(Debug, Clone, Default)]
+pub struct DisplayColumns {
+    columns: Arc<HashMap<String, String>>,
+}
+
+impl DisplayColumns {
+    /// Create the configuration from display columns by table name
+    pub fn new(columns: HashMap<String, String>) -> Self {
+        Self {
+            columns: Arc::new(columns),
+        }
+    }
+
+    /// Column whose value labels the rows of a table
+    ///
+    /// Returns the configured column if the table has it, otherwise the column
+    /// chosen by [`display_column`].
+    pub fn column_for<'a>(&'a self, schema: &'a TableSchema) -> Option<&'a str> {
+        match self.columns.get(&schema.name) {
+            Some(column) if schema.columns.iter().any(|info| &info.name == column) => {
+                Some(column.as_str())
+            }
+            _ => display_column(schema),
+        }
+    }
+}
+
+/// Distinct non-NULL values of `column` in `rows`, in order of appearance
+pub fn column_values(rows: &[Value], column: &str) -> Vec<String> {
+    let mut values: Vec<String> = Vec::new();
+
+    for value in rows
+        .iter()
+        .filter_map(|row| row.get(column))
+        .filter_map(value_to_key_text)
+    {
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+
+    values
+}
+
+/// Labels of referenced rows by the value of the referenced column
+///
+/// # Arguments
+///
+/// * `rows` - Rows of the referenced table
+/// * `references_column` - Column the foreign key points at
+/// * `display_column` - Column holding the labels
+pub fn labels_by_value(
+    rows: &[Value],
+    references_column: &str,
+    display_column: &str,
+) -> HashMap<String, String> {
+    rows.iter()
+        .filter_map(|row| {
+            let value = row.get(references_column).and_then(value_to_key_text)?;
+            let label = row.get(display_column).and_then(value_to_key_text)?;
+            Some((value, label))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{ColumnInfo, RowAddress};
+    use serde_json::json;
+
+    #[test]
+    fn test_column_for_prefers_configuration() {
+        let column = |name: &str, is_primary_key: bool| ColumnInfo {
+            name: name.to_string(),
+            data_type: "TEXT".to_string(),
+            nullable: false,
+            default_value: None,
+            is_primary_key,
+        };
+        let schema = TableSchema {
+            name: "users".to_string(),
+            columns: vec![
+                column("id", true),
+                column("name", false),
+                column("nickname", false),
+            ],
+            primary_key: Some(vec!["id".to_string()]),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: RowAddress::PrimaryKey,
+        };
+
+        assert_eq!(DisplayColumns::default().column_for(&schema), Some("name"));
+
+        let configured = DisplayColumns::new(HashMap::from([(
+            "users".to_string(),
+            "nickname".to_string(),
+        )]));
+        assert_eq!(configured.column_for(&schema), Some("nickname"));
+
+        let unknown = DisplayColumns::new(HashMap::from([(
+            "users".to_string(),
+            "missing".to_string(),
+        )]));
+        assert_eq!(unknown.column_for(&schema), Some("name"));
+    }
+
+    #[test]
+    fn test_labels_by_value() {
+        let rows = vec![
+            json!({ "id": 1, "author_id": 7 }),
+            json!({ "id": 2, "author_id": 7 }),
+            json!({ "id": 3, "author_id": null }),
+            json!({ "id": 4, "author_id": 9 }),
+        ];
+        assert_eq!(column_values(&rows, "author_id"), vec!["7", "9"]);
+
+        let users = vec![
+            json!({ "id": 7, "name": "Alice Johnson" }),
+            json!({ "id": 9, "name": null }),
+        ];
+        let labels = labels_by_value(&users, "id", "name");
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels["7"], "Alice Johnson");
+    }
+}
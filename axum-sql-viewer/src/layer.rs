@@ -4,8 +4,11 @@
 //! into an Axum application.
 
 use crate::database::traits::DatabaseProvider;
-use axum::{routing::get, routing::post, Router};
+use axum::{middleware, Router};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::cors::CorsLayer;
 
 #[cfg(feature = "sqlite")]
@@ -14,11 +17,31 @@ use crate::database::sqlite::SqliteProvider;
 #[cfg(feature = "postgres")]
 use crate::database::postgres::PostgresProvider;
 
-use crate::api::{
-    count_rows_handler, execute_query_handler, get_rows_handler, get_table_schema_handler,
-    list_tables_handler,
-};
+use crate::anonymize::AnonymizationRules;
+use crate::api::{create_api_router_with_state, ViewerState};
+use crate::artifacts::{default_directory, ArtifactStore, DEFAULT_RETENTION};
+use crate::confirmations::Confirmations;
+use crate::cost_limits::CostLimits;
+use crate::export_limits::{ExportGuardrails, ExportLimits};
+#[cfg(feature = "dev-server")]
+use crate::frontend::create_dev_server_frontend_router;
 use crate::frontend::create_frontend_router;
+use crate::labels::DisplayColumns;
+use crate::migrations::Migrations;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+use crate::migrations::SqlxMigrator;
+use crate::notifications::forward_notifications;
+use crate::profile::Profiler;
+use crate::replay::{record_request, RequestRecorder};
+use crate::runtime;
+use crate::saved_queries::{SavedQueries, SavedQueryStorage};
+use crate::schema_cache::SchemaCache;
+use crate::schema_watch::watch_schema;
+
+#[cfg(feature = "query-log")]
+use crate::api::create_statements_router;
+#[cfg(feature = "query-log")]
+use crate::query_log::QueryLog;
 
 /// Main layer for integrating SQL viewer into an Axum application
 ///
@@ -38,6 +61,26 @@ use crate::frontend::create_frontend_router;
 pub struct SqlViewerLayer<DB: DatabaseProvider> {
     base_path: String,
     database: Arc<DB>,
+    request_recorder: Option<RequestRecorder>,
+    export_directory: Option<PathBuf>,
+    export_retention: Duration,
+    export_limits: ExportLimits,
+    table_export_limits: HashMap<String, ExportLimits>,
+    anonymization: AnonymizationRules,
+    display_columns: HashMap<String, String>,
+    schema_prefetch: Option<SchemaPrefetch>,
+    schema_cache_ttl: Option<Duration>,
+    schema_watch: Option<Duration>,
+    notification_channels: Vec<String>,
+    confirm_destructive_statements: bool,
+    cost_limits: CostLimits,
+    saved_query_storage: SavedQueryStorage,
+    migrations: Migrations,
+    profile_ttl: Option<Duration>,
+    #[cfg(feature = "query-log")]
+    query_log: Option<QueryLog>,
+    #[cfg(feature = "dev-server")]
+    frontend_dev_server: Option<String>,
 }
 
 impl<DB: DatabaseProvider> SqlViewerLayer<DB> {
@@ -51,9 +94,235 @@ impl<DB: DatabaseProvider> SqlViewerLayer<DB> {
         Self {
             base_path: base_path.into(),
             database: Arc::new(database),
+            request_recorder: None,
+            export_directory: None,
+            export_retention: DEFAULT_RETENTION,
+            export_limits: ExportLimits::default(),
+            table_export_limits: HashMap::new(),
+            anonymization: AnonymizationRules::default(),
+            display_columns: HashMap::new(),
+            schema_prefetch: None,
+            schema_cache_ttl: None,
+            schema_watch: None,
+            notification_channels: Vec::new(),
+            confirm_destructive_statements: true,
+            cost_limits: CostLimits::default(),
+            saved_query_storage: SavedQueryStorage::Memory,
+            migrations: Migrations::default(),
+            profile_ttl: None,
+            #[cfg(feature = "query-log")]
+            query_log: None,
+            #[cfg(feature = "dev-server")]
+            frontend_dev_server: None,
         }
     }
 
+    /// Record every API request/response pair as a JSON fixture in `directory`
+    ///
+    /// The recorded fixtures can be served without a database using
+    /// [`create_replay_router`](crate::replay::create_replay_router).
+    pub fn with_request_recording(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.request_recorder = Some(RequestRecorder::new(directory));
+        self
+    }
+
+    /// Write export files to `directory` instead of the system's temporary directory
+    pub fn with_export_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.export_directory = Some(directory.into());
+        self
+    }
+
+    /// Keep export files available for download for `retention` (one hour by default)
+    ///
+    /// Downloads can be resumed with `Range` requests until the file expires.
+    pub fn with_export_retention(mut self, retention: Duration) -> Self {
+        self.export_retention = retention;
+        self
+    }
+
+    /// Limit the size of exports of every table (unlimited by default)
+    ///
+    /// Exports over the limits are truncated, or sampled if the request asks
+    /// for it (`onLimit`), and say so in a warning instead of failing. The
+    /// row limit costs a count of the matching rows before each export.
+    pub fn with_export_limits(mut self, limits: ExportLimits) -> Self {
+        self.export_limits = limits;
+        self
+    }
+
+    /// Limit the size of exports of `table`, overriding [`with_export_limits`](Self::with_export_limits)
+    pub fn with_table_export_limits(
+        mut self,
+        table: impl Into<String>,
+        limits: ExportLimits,
+    ) -> Self {
+        self.table_export_limits.insert(table.into(), limits);
+        self
+    }
+
+    /// Anonymize the values of matching columns in exports of every format
+    ///
+    /// Rules replace values with NULL, a hash or a made-up value, so files
+    /// containing personal data can be shared. Rows shown in the viewer and
+    /// query results are not affected.
+    pub fn with_anonymization(mut self, rules: AnonymizationRules) -> Self {
+        self.anonymization = rules;
+        self
+    }
+
+    /// Label rows of `table` with the values of `column`
+    ///
+    /// Labels are shown for foreign keys referencing the table (rows endpoints
+    /// with `labels=true`) and when resolving row references. Tables without a
+    /// configured column use a column named like `name`, `title` or `email`,
+    /// or else their first text column.
+    pub fn with_display_column(
+        mut self,
+        table: impl Into<String>,
+        column: impl Into<String>,
+    ) -> Self {
+        self.display_columns.insert(table.into(), column.into());
+        self
+    }
+
+    /// Discover and cache the full schema in the background when the router is built
+    ///
+    /// The table list and every table schema are loaded right away instead of
+    /// on the first page load, and later requests for them are answered from
+    /// the cache. Requires a running tokio runtime when [`into_router`](Self::into_router)
+    /// is called; otherwise the cache is filled on first use.
+    ///
+    /// # Arguments
+    ///
+    /// * `include_counts` - Also keep the row counts of the table list; they
+    ///   are not refreshed, so they become approximate as the data changes
+    pub fn with_schema_prefetch(mut self, include_counts: bool) -> Self {
+        self.schema_prefetch = Some(SchemaPrefetch { include_counts });
+        self
+    }
+
+    /// Cache the table list and table schemas, reading them again once they are older than `ttl`
+    ///
+    /// The sidebar lists the tables on every render, while the schema rarely
+    /// changes. Entries are loaded on first use (or right away with
+    /// [`with_schema_prefetch`](Self::with_schema_prefetch), which then also
+    /// expires them after `ttl`); row counts in the cached table list are up
+    /// to `ttl` old and marked as not exact. `POST {base_path}/api/cache/invalidate`
+    /// empties the cache before that.
+    ///
+    /// # Arguments
+    ///
+    /// * `ttl` - How long cached entries are used
+    pub fn with_schema_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.schema_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Check the schema for changes every `interval` and notify open viewers
+    ///
+    /// When tables are created, dropped or altered (e.g. by a migration), the
+    /// schema cache is cleared and a `schemaChanged` event is sent to clients
+    /// listening on `{base_path}/api/events`, which refresh their table list.
+    /// Each check compares a cheap schema fingerprint first (`PRAGMA
+    /// schema_version` on SQLite, a catalog hash on PostgreSQL). Requires a
+    /// running tokio runtime when [`into_router`](Self::into_router) is called.
+    pub fn with_schema_watch(mut self, interval: Duration) -> Self {
+        self.schema_watch = Some(interval);
+        self
+    }
+
+    /// Forward notifications sent to `channels` with `NOTIFY` to open viewers
+    ///
+    /// A background task `LISTEN`s on the channels over a dedicated connection
+    /// and sends each notification as a `notification` event to clients
+    /// listening on `{base_path}/api/notifications`. A viewer showing a table
+    /// reloads its rows when a notification arrives whose payload is empty or
+    /// the table's name. Only PostgreSQL supports notifications. Requires a
+    /// running tokio runtime when [`into_router`](Self::into_router) is called.
+    pub fn with_notification_channels(
+        mut self,
+        channels: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.notification_channels = channels.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether destructive statements (`DELETE`, `UPDATE`, `DROP`, `TRUNCATE`,
+    /// `MERGE`, `INSERT ... SELECT`, data-modifying CTEs) sent as raw SQL must
+    /// be confirmed with a second request before they run (on by default)
+    ///
+    /// The first request answers with status 428, the estimated number of
+    /// affected rows and a confirmation token to send along with the statement.
+    pub fn with_destructive_confirmation(mut self, enabled: bool) -> Self {
+        self.confirm_destructive_statements = enabled;
+        self
+    }
+
+    /// Reject statements sent to the console whose estimated cost exceeds `limits`
+    /// (unlimited by default)
+    ///
+    /// Queries and data changes are `EXPLAIN`ed before they run, which costs a
+    /// round trip each. The limits only apply to databases reporting estimates,
+    /// i.e. PostgreSQL; see [`cost_limits`](crate::cost_limits).
+    pub fn with_cost_limits(mut self, limits: CostLimits) -> Self {
+        self.cost_limits = limits;
+        self
+    }
+
+    /// Profile tables on request at `{base_path}/api/tables/{name}/profile`,
+    /// reusing each profile until it is older than `ttl`
+    ///
+    /// A profile reads a random sample of up to 1000 rows and reports per
+    /// column the share of NULLs, the kinds of values and how consistent they
+    /// are, the most frequent values and the lengths of text values (see
+    /// [`profile`](crate::profile)). Off by default, since sampling reads the
+    /// whole table unless PostgreSQL can use `TABLESAMPLE`.
+    pub fn with_profiling(mut self, ttl: Duration) -> Self {
+        self.profile_ttl = Some(ttl);
+        self
+    }
+
+    /// Keep queries saved from the console in the JSON file at `path`
+    ///
+    /// The file is created on the first save. Without this or
+    /// [`with_saved_queries_table`](Self::with_saved_queries_table), saved
+    /// queries are kept in memory and lost when the server restarts.
+    pub fn with_saved_queries_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.saved_query_storage = SavedQueryStorage::File(path.into());
+        self
+    }
+
+    /// Keep queries saved from the console in a table of the viewed database
+    ///
+    /// The table ([`SAVED_QUERIES_TABLE`](crate::saved_queries::SAVED_QUERIES_TABLE))
+    /// is created on first use and shows up in the table list like any other.
+    pub fn with_saved_queries_table(mut self) -> Self {
+        self.saved_query_storage = SavedQueryStorage::Table;
+        self
+    }
+
+    /// Expose statements captured by a [`QueryLog`] at `{base_path}/api/statements`
+    ///
+    /// The query log's layer must be installed in the application's tracing
+    /// subscriber for statements to be recorded.
+    #[cfg(feature = "query-log")]
+    pub fn with_query_log(mut self, query_log: QueryLog) -> Self {
+        self.query_log = Some(query_log);
+        self
+    }
+
+    /// Forward frontend requests to a dev server when no frontend is embedded
+    ///
+    /// Intended for working on the frontend: start Vite with the viewer's base
+    /// path (`pnpm dev --base /sql-viewer/`) and pass its URL here, e.g.
+    /// `"http://localhost:5174"`. Has no effect if the crate was built with the
+    /// frontend embedded.
+    #[cfg(feature = "dev-server")]
+    pub fn with_frontend_dev_server(mut self, url: impl Into<String>) -> Self {
+        self.frontend_dev_server = Some(url.into());
+        self
+    }
+
     /// Convert into an Axum Router that can be merged
     ///
     /// This method consumes the layer and returns a Router that can be merged
@@ -68,16 +337,74 @@ impl<DB: DatabaseProvider> SqlViewerLayer<DB> {
         let base_path = self.base_path.clone();
 
         // Create API router with all endpoints
-        // Note: Axum 0.8 uses {param} syntax instead of :param
-        let api_router = Router::new()
-            .route("/tables", get(list_tables_handler::<DB>))
-            .route("/tables/{name}", get(get_table_schema_handler::<DB>))
-            .route("/tables/{name}/rows", get(get_rows_handler::<DB>))
-            .route("/tables/{name}/count", get(count_rows_handler::<DB>))
-            .route("/query", post(execute_query_handler::<DB>))
-            .with_state(database);
+        let mut state = ViewerState::new(database);
+        state.exports = ArtifactStore::new(
+            self.export_directory.unwrap_or_else(default_directory),
+            self.export_retention,
+        );
+        state.export_limits = ExportGuardrails::new(self.export_limits, self.table_export_limits);
+        state.anonymization = self.anonymization;
+        state.display_columns = DisplayColumns::new(self.display_columns);
+        state.confirmations = Confirmations::new(self.confirm_destructive_statements);
+        state.cost_limits = self.cost_limits;
+        state.saved_queries = SavedQueries::new(self.saved_query_storage);
+        state.migrations = self.migrations;
+        if let Some(ttl) = self.profile_ttl {
+            state.profiler = Profiler::new(ttl);
+        }
+        if let Some(ttl) = self.schema_cache_ttl {
+            let include_counts = match &self.schema_prefetch {
+                Some(prefetch) => prefetch.include_counts,
+                None => true,
+            };
+            state.schema_cache = SchemaCache::new(include_counts).with_ttl(ttl);
+        }
+        if let Some(prefetch) = self.schema_prefetch {
+            if !state.schema_cache.is_enabled() {
+                state.schema_cache = SchemaCache::new(prefetch.include_counts);
+            }
+            let database = self.database.clone();
+            let cache = state.schema_cache.clone();
+            runtime::spawn_detached(async move {
+                if let Err(error) = cache.prefetch(database.as_ref()).await {
+                    eprintln!("Failed to prefetch schema: {}", error);
+                }
+            });
+        }
+        if let Some(interval) = self.schema_watch {
+            runtime::spawn_detached(watch_schema(
+                self.database.clone(),
+                state.schema_cache.clone(),
+                state.schema_events.clone(),
+                interval,
+            ));
+        }
+        if !self.notification_channels.is_empty() {
+            runtime::spawn_detached(forward_notifications(
+                self.database.clone(),
+                self.notification_channels,
+                state.notifications.clone(),
+            ));
+        }
+        let mut api_router = create_api_router_with_state(state);
+
+        #[cfg(feature = "query-log")]
+        if let Some(query_log) = self.query_log {
+            api_router = api_router.merge(create_statements_router(query_log));
+        }
+
+        // Record fixtures for all API routes (applied last so every route is covered)
+        if let Some(recorder) = self.request_recorder {
+            api_router = api_router.layer(middleware::from_fn_with_state(recorder, record_request));
+        }
 
         // Create frontend router
+        #[cfg(feature = "dev-server")]
+        let frontend_router = match self.frontend_dev_server {
+            Some(url) => create_dev_server_frontend_router(base_path.clone(), url),
+            None => create_frontend_router(base_path.clone()),
+        };
+        #[cfg(not(feature = "dev-server"))]
         let frontend_router = create_frontend_router(base_path.clone());
 
         // Nest API router under /api and frontend at root
@@ -91,6 +418,11 @@ impl<DB: DatabaseProvider> SqlViewerLayer<DB> {
     }
 }
 
+/// Options for discovering the schema when the router is built
+struct SchemaPrefetch {
+    include_counts: bool,
+}
+
 #[cfg(feature = "sqlite")]
 impl SqlViewerLayer<SqliteProvider> {
     /// Create a new SQL viewer for SQLite
@@ -102,6 +434,16 @@ impl SqlViewerLayer<SqliteProvider> {
     pub fn sqlite(base_path: impl Into<String>, pool: sqlx::SqlitePool) -> Self {
         Self::new(base_path, SqliteProvider::new(pool))
     }
+
+    /// List the migrations of `migrator` that are not applied yet and apply
+    /// them from the viewer (`POST {base_path}/api/migrations/run`)
+    ///
+    /// Pass the application's own migrator, e.g. `sqlx::migrate!()`.
+    pub fn with_migrator(mut self, migrator: sqlx::migrate::Migrator) -> Self {
+        let pool = self.database.pool().clone();
+        self.migrations = Migrations::new(Arc::new(SqlxMigrator::new(migrator, pool)));
+        self
+    }
 }
 
 #[cfg(feature = "postgres")]
@@ -115,4 +457,14 @@ impl SqlViewerLayer<PostgresProvider> {
     pub fn postgres(base_path: impl Into<String>, pool: sqlx::PgPool) -> Self {
         Self::new(base_path, PostgresProvider::new(pool))
     }
+
+    /// List the migrations of `migrator` that are not applied yet and apply
+    /// them from the viewer (`POST {base_path}/api/migrations/run`)
+    ///
+    /// Pass the application's own migrator, e.g. `sqlx::migrate!()`.
+    pub fn with_migrator(mut self, migrator: sqlx::migrate::Migrator) -> Self {
+        let pool = self.database.pool().clone();
+        self.migrations = Migrations::new(Arc::new(SqlxMigrator::new(migrator, pool)));
+        self
+    }
 }
@@ -9,6 +9,10 @@
 //! - Column sorting and filtering
 //! - Raw SQL query execution
 //! - Support for SQLite and PostgreSQL
+//! - Recording of API fixtures and database-free replay for frontend development
+//! - Optional capture of the host application's sqlx statements (`query-log` feature)
+//! - Optional proxying to a frontend dev server when no frontend is embedded (`dev-server` feature)
+//! - Optional typed HTTP client for driving a running viewer (`client` feature)
 //!
 //! ## Security Warning
 //!
@@ -41,18 +45,82 @@
 //! ```
 
 // Public modules
+pub mod anonymize;
 pub mod api;
+pub mod artifacts;
+pub mod big_integers;
+pub mod broadcast;
+pub mod checksum;
+pub mod classify;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod codegen;
+pub mod column_stats;
+pub mod confirmations;
+pub mod console;
+pub mod cost_limits;
+pub mod cursors;
 pub mod database;
+#[cfg(feature = "dev-server")]
+pub mod dev_server;
+pub mod diagram;
+pub mod dump;
+pub mod duplicates;
+pub mod explain;
+pub mod export;
+pub mod export_limits;
+pub mod filters;
+pub mod fixtures;
+pub mod format;
 pub mod frontend;
+pub mod histogram;
+pub mod jobs;
+pub mod json_body;
+pub mod keyset;
+pub mod labels;
 pub mod layer;
+pub mod lineage;
+pub mod migrations;
+pub mod named_snapshots;
+pub mod notifications;
+pub mod permalinks;
+pub mod profile;
+#[cfg(feature = "query-log")]
+pub mod query_log;
+pub mod references;
+pub mod replay;
+pub mod row_key;
+pub mod runtime;
+pub mod sample;
+pub mod saved_queries;
 pub mod schema;
+pub mod schema_cache;
+pub mod schema_search;
+pub mod schema_watch;
+pub mod search;
+pub mod spatial;
+pub mod sql;
+#[cfg(test)]
+mod test_support;
+pub mod validate;
+pub mod xlsx;
 
 // Public exports
+pub use big_integers::BigIntegerFormat;
+pub use cost_limits::CostLimits;
+pub use export_limits::ExportLimits;
 pub use layer::SqlViewerLayer;
+#[cfg(feature = "query-log")]
+pub use query_log::QueryLog;
 pub use schema::{ColumnInfo, ForeignKey, IndexInfo, TableSchema};
+pub use spatial::GeometryFormat;
 
 // Re-export database providers
-pub use database::traits::DatabaseProvider;
+pub use database::limits::ResultLimits;
+pub use database::traits::{DatabaseError, DatabaseProvider};
+
+// Re-exported for implementing `DatabaseProvider` outside this crate
+pub use async_trait::async_trait;
 
 #[cfg(feature = "sqlite")]
 pub use database::sqlite::SqliteProvider;
@@ -60,6 +128,9 @@ pub use database::sqlite::SqliteProvider;
 #[cfg(feature = "postgres")]
 pub use database::postgres::PostgresProvider;
 
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub use database::timeouts::Timeouts;
+
 // Error type
 use thiserror::Error;
 
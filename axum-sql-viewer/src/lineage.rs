@@ -0,0 +1,226 @@
+//! Column-level lineage derived from foreign keys
+//!
+//! A foreign key copies the value of the referenced column into the
+//! referencing column, so following foreign keys from a column shows where its
+//! values come from (upstream) and where they end up (downstream). Chains
+//! continue through columns that are both referenced and referencing, such as
+//! the primary key of a one-to-one extension table.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+use crate::schema::TableSchema;
+
+/// Maximum number of hops followed in each direction
+pub const MAX_LINEAGE_DEPTH: usize = 10;
+
+/// A column of a table
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnReference {
+    /// Table name
+    pub table: String,
+
+    /// Column name
+    pub column: String,
+}
+
+/// A column reached by following foreign keys
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageHop {
+    /// Table name
+    pub table: String,
+
+    /// Column name
+    pub column: String,
+
+    /// Number of foreign keys between the starting column and this one
+    pub depth: usize,
+
+    /// Column one hop closer to the starting column
+    pub via: ColumnReference,
+}
+
+/// Columns connected to a column through foreign keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnLineage {
+    /// Table name
+    pub table: String,
+
+    /// Column name
+    pub column: String,
+
+    /// Maximum number of hops followed in each direction
+    pub depth: usize,
+
+    /// Columns whose values this column references, nearest first
+    pub upstream: Vec<LineageHop>,
+
+    /// Columns referencing this column's values, nearest first
+    pub downstream: Vec<LineageHop>,
+}
+
+/// Follow foreign keys from a column in both directions, up to `depth` hops
+///
+/// Every column is reported once, at the smallest depth it is reached at, so
+/// cycles (including self-referencing tables) terminate.
+pub fn column_lineage(
+    schemas: &[TableSchema],
+    table: &str,
+    column: &str,
+    depth: usize,
+) -> ColumnLineage {
+    let start = ColumnReference {
+        table: table.to_string(),
+        column: column.to_string(),
+    };
+
+    ColumnLineage {
+        table: table.to_string(),
+        column: column.to_string(),
+        depth,
+        upstream: walk(&start, depth, |current| {
+            referenced_columns(schemas, current)
+        }),
+        downstream: walk(&start, depth, |current| {
+            referencing_columns(schemas, current)
+        }),
+    }
+}
+
+/// Breadth-first search over the columns returned by `neighbours`
+fn walk(
+    start: &ColumnReference,
+    depth: usize,
+    neighbours: impl Fn(&ColumnReference) -> Vec<ColumnReference>,
+) -> Vec<LineageHop> {
+    let mut visited = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([(start.clone(), 0)]);
+    let mut hops = Vec::new();
+
+    while let Some((current, current_depth)) = queue.pop_front() {
+        if current_depth == depth {
+            continue;
+        }
+        for next in neighbours(&current) {
+            if !visited.insert(next.clone()) {
+                continue;
+            }
+            hops.push(LineageHop {
+                table: next.table.clone(),
+                column: next.column.clone(),
+                depth: current_depth + 1,
+                via: current.clone(),
+            });
+            queue.push_back((next, current_depth + 1));
+        }
+    }
+
+    hops
+}
+
+/// Columns referenced by the foreign keys on `column`
+fn referenced_columns(schemas: &[TableSchema], column: &ColumnReference) -> Vec<ColumnReference> {
+    schemas
+        .iter()
+        .filter(|schema| schema.name == column.table)
+        .flat_map(|schema| &schema.foreign_keys)
+        .filter(|foreign_key| foreign_key.column == column.column)
+        .map(|foreign_key| ColumnReference {
+            table: foreign_key.references_table.clone(),
+            column: foreign_key.references_column.clone(),
+        })
+        .collect()
+}
+
+/// Columns whose foreign keys reference `column`
+fn referencing_columns(schemas: &[TableSchema], column: &ColumnReference) -> Vec<ColumnReference> {
+    schemas
+        .iter()
+        .flat_map(|schema| {
+            schema
+                .foreign_keys
+                .iter()
+                .filter(|foreign_key| {
+                    foreign_key.references_table == column.table
+                        && foreign_key.references_column == column.column
+                })
+                .map(|foreign_key| ColumnReference {
+                    table: schema.name.clone(),
+                    column: foreign_key.column.clone(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::table;
+
+    fn sample_schemas() -> Vec<TableSchema> {
+        let keyed = |name| table(name).primary_key(&["id"]);
+        vec![
+            keyed("users").build(),
+            // One-to-one extension of users sharing its primary key
+            keyed("profiles").foreign_key("id", "users", "id").build(),
+            keyed("avatars")
+                .foreign_key("profile_id", "profiles", "id")
+                .build(),
+            keyed("orders")
+                .foreign_key("user_id", "users", "id")
+                .build(),
+            keyed("employees")
+                .foreign_key("manager_id", "employees", "id")
+                .build(),
+        ]
+    }
+
+    fn names(hops: &[LineageHop]) -> Vec<(String, usize)> {
+        hops.iter()
+            .map(|hop| (format!("{}.{}", hop.table, hop.column), hop.depth))
+            .collect()
+    }
+
+    #[test]
+    fn test_downstream_lineage() {
+        let lineage = column_lineage(&sample_schemas(), "users", "id", 3);
+        assert!(lineage.upstream.is_empty());
+        assert_eq!(
+            names(&lineage.downstream),
+            vec![
+                ("profiles.id".to_string(), 1),
+                ("orders.user_id".to_string(), 1),
+                ("avatars.profile_id".to_string(), 2),
+            ]
+        );
+        assert_eq!(
+            lineage.downstream[2].via,
+            ColumnReference {
+                table: "profiles".to_string(),
+                column: "id".to_string(),
+            }
+        );
+
+        let shallow = column_lineage(&sample_schemas(), "users", "id", 1);
+        assert_eq!(shallow.downstream.len(), 2);
+    }
+
+    #[test]
+    fn test_upstream_lineage_and_cycles() {
+        let lineage = column_lineage(&sample_schemas(), "avatars", "profile_id", 5);
+        assert_eq!(
+            names(&lineage.upstream),
+            vec![("profiles.id".to_string(), 1), ("users.id".to_string(), 2)]
+        );
+        assert!(lineage.downstream.is_empty());
+
+        let lineage = column_lineage(&sample_schemas(), "employees", "id", 5);
+        assert_eq!(
+            names(&lineage.downstream),
+            vec![("employees.manager_id".to_string(), 1)]
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! Migration histories of sqlx and Diesel
+//!
+//! Both tools record the migrations they applied in a table of the database:
+//! sqlx in `_sqlx_migrations` (version, description, time, success, checksum
+//! and execution time), Diesel in `__diesel_schema_migrations` (version and
+//! time). They are read into one shape for `/api/migrations`, which the
+//! frontend shows instead of the plain rows of these tables.
+//!
+//! With a [`MigrationSource`] configured (the host application's sqlx
+//! `Migrator`, see `SqlViewerLayer::with_migrator`), the viewer also lists the
+//! migrations that are not applied yet and can apply them.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::schema::{Migration, MigrationHistory, MigrationTool};
+use crate::sql::{quote_identifier, Dialect};
+
+/// Table sqlx records its migrations in
+pub const SQLX_MIGRATIONS_TABLE: &str = "_sqlx_migrations";
+
+/// Table Diesel records its migrations in
+pub const DIESEL_MIGRATIONS_TABLE: &str = "__diesel_schema_migrations";
+
+/// Tool recording its migrations in `table`, if any
+pub fn migration_tool(table: &str) -> Option<MigrationTool> {
+    match table {
+        SQLX_MIGRATIONS_TABLE => Some(MigrationTool::Sqlx),
+        DIESEL_MIGRATIONS_TABLE => Some(MigrationTool::Diesel),
+        _ => None,
+    }
+}
+
+/// Error message when applying migrations without a migrator
+pub const MIGRATOR_REQUIRED: &str =
+    "Applying migrations requires a migrator (SqlViewerLayer::with_migrator)";
+
+/// Migrations of the host application that the viewer can apply
+#[async_trait]
+pub trait MigrationSource: Send + Sync {
+    /// Migrations to apply, by version (without their `down` migrations)
+    fn migrations(&self) -> Vec<Migration>;
+
+    /// Apply the migrations not applied yet, in order
+    async fn run(&self) -> Result<(), DatabaseError>;
+}
+
+/// Configured migration source, if any
+#[derive(Clone, Default)]
+pub struct Migrations {
+    source: Option<Arc<dyn MigrationSource>>,
+}
+
+impl Migrations {
+    /// Apply migrations from `source`
+    pub fn new(source: Arc<dyn MigrationSource>) -> Self {
+        Self {
+            source: Some(source),
+        }
+    }
+
+    /// Whether migrations can be applied
+    pub fn is_configured(&self) -> bool {
+        self.source.is_some()
+    }
+
+    /// Migrations of the source not recorded in `histories` (none without a source)
+    pub fn pending(&self, histories: &[MigrationHistory]) -> Vec<Migration> {
+        match &self.source {
+            Some(source) => pending_migrations(source.migrations(), histories),
+            None => Vec::new(),
+        }
+    }
+
+    /// Apply the pending migrations
+    pub async fn run(&self) -> Result<(), DatabaseError> {
+        match &self.source {
+            Some(source) => source.run().await,
+            None => Err(DatabaseError::Unsupported(MIGRATOR_REQUIRED.to_string())),
+        }
+    }
+}
+
+/// Migrations of `available` whose version sqlx has not recorded in `histories`
+///
+/// A migration that failed is recorded, so it is not pending; sqlx refuses to
+/// run later migrations until it is fixed.
+fn pending_migrations(available: Vec<Migration>, histories: &[MigrationHistory]) -> Vec<Migration> {
+    let applied: Vec<&str> = histories
+        .iter()
+        .filter(|history| history.tool == MigrationTool::Sqlx)
+        .flat_map(|history| &history.migrations)
+        .map(|migration| migration.version.as_str())
+        .collect();
+
+    available
+        .into_iter()
+        .filter(|migration| !applied.contains(&migration.version.as_str()))
+        .collect()
+}
+
+/// A sqlx `Migrator` applying its migrations to a pool
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+pub struct SqlxMigrator<DB: sqlx::Database> {
+    migrator: sqlx::migrate::Migrator,
+    pool: sqlx::Pool<DB>,
+}
+
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+impl<DB: sqlx::Database> SqlxMigrator<DB> {
+    /// Apply the migrations of `migrator` to `pool`
+    pub fn new(migrator: sqlx::migrate::Migrator, pool: sqlx::Pool<DB>) -> Self {
+        Self { migrator, pool }
+    }
+
+    /// Up migrations of the migrator
+    fn available(&self) -> Vec<Migration> {
+        self.migrator
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .map(|migration| Migration {
+                version: migration.version.to_string(),
+                description: Some(migration.description.to_string()),
+                installed_on: None,
+                success: None,
+                checksum: Some(
+                    migration
+                        .checksum
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect(),
+                ),
+                execution_time_milliseconds: None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl MigrationSource for SqlxMigrator<sqlx::Sqlite> {
+    fn migrations(&self) -> Vec<Migration> {
+        self.available()
+    }
+
+    async fn run(&self) -> Result<(), DatabaseError> {
+        self.migrator
+            .run(&self.pool)
+            .await
+            .map_err(|error| DatabaseError::Query(error.to_string()))
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl MigrationSource for SqlxMigrator<sqlx::Postgres> {
+    fn migrations(&self) -> Vec<Migration> {
+        self.available()
+    }
+
+    async fn run(&self) -> Result<(), DatabaseError> {
+        self.migrator
+            .run(&self.pool)
+            .await
+            .map_err(|error| DatabaseError::Query(error.to_string()))
+    }
+}
+
+/// Read the migration histories of every migration table in the database
+pub async fn read_migrations<DB: DatabaseProvider>(
+    database: &DB,
+) -> Result<Vec<MigrationHistory>, DatabaseError> {
+    let mut histories = Vec::new();
+    for table in database.list_tables().await? {
+        let Some(tool) = migration_tool(&table.name) else {
+            continue;
+        };
+
+        let sql = migrations_query(tool, &table.name, database.dialect());
+        let result = database.execute_query(&sql, &[]).await?;
+        if let Some(error) = result.error {
+            return Err(DatabaseError::Query(error));
+        }
+        histories.push(MigrationHistory {
+            table: table.name,
+            tool,
+            migrations: result
+                .rows
+                .iter()
+                .map(|row| migration_from_row(tool, row))
+                .collect(),
+        });
+    }
+    Ok(histories)
+}
+
+/// Query reading the migrations recorded in `table`, by version
+///
+/// Times are read as text and checksums as hexadecimal, so every provider
+/// returns them the same way.
+fn migrations_query(tool: MigrationTool, table: &str, dialect: Dialect) -> String {
+    let table = quote_identifier(table);
+    match tool {
+        MigrationTool::Sqlx => {
+            let checksum = match dialect {
+                Dialect::Postgres => "encode(checksum, 'hex')",
+                Dialect::Sqlite => "hex(checksum)",
+            };
+            format!(
+                "SELECT CAST(version AS TEXT) AS version, description, \
+                 CAST(installed_on AS TEXT) AS installed_on, success, {} AS checksum, \
+                 execution_time FROM {} ORDER BY version",
+                checksum, table
+            )
+        }
+        MigrationTool::Diesel => format!(
+            "SELECT version, CAST(run_on AS TEXT) AS installed_on FROM {} ORDER BY version",
+            table
+        ),
+    }
+}
+
+/// Read a migration from a row of [`migrations_query`]
+fn migration_from_row(tool: MigrationTool, row: &Value) -> Migration {
+    let text = |column: &str| match row.get(column) {
+        Some(Value::String(text)) => Some(text.clone()),
+        Some(Value::Number(number)) => Some(number.to_string()),
+        _ => None,
+    };
+    if tool == MigrationTool::Diesel {
+        return Migration {
+            version: text("version").unwrap_or_default(),
+            description: None,
+            installed_on: text("installed_on"),
+            success: None,
+            checksum: None,
+            execution_time_milliseconds: None,
+        };
+    }
+
+    Migration {
+        version: text("version").unwrap_or_default(),
+        description: text("description"),
+        installed_on: text("installed_on"),
+        // A boolean on PostgreSQL, 0 or 1 on SQLite
+        success: match row.get("success") {
+            Some(Value::Bool(success)) => Some(*success),
+            Some(Value::Number(number)) => number.as_i64().map(|number| number != 0),
+            _ => None,
+        },
+        checksum: text("checksum").map(|checksum| checksum.to_lowercase()),
+        // sqlx stores nanoseconds
+        execution_time_milliseconds: row
+            .get("execution_time")
+            .and_then(Value::as_i64)
+            .map(|nanoseconds| nanoseconds.max(0) as u64 / 1_000_000),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migration_tool() {
+        assert_eq!(
+            migration_tool("_sqlx_migrations"),
+            Some(MigrationTool::Sqlx)
+        );
+        assert_eq!(
+            migration_tool("__diesel_schema_migrations"),
+            Some(MigrationTool::Diesel)
+        );
+        assert_eq!(migration_tool("migrations"), None);
+    }
+
+    #[test]
+    fn test_sqlx_migration_from_row() {
+        let row = json!({
+            "version": "20240101120000",
+            "description": "create users",
+            "installed_on": "2024-01-01 12:00:00",
+            "success": 1,
+            "checksum": "A1B2",
+            "execution_time": 12_500_000,
+        });
+        assert_eq!(
+            migration_from_row(MigrationTool::Sqlx, &row),
+            Migration {
+                version: "20240101120000".to_string(),
+                description: Some("create users".to_string()),
+                installed_on: Some("2024-01-01 12:00:00".to_string()),
+                success: Some(true),
+                checksum: Some("a1b2".to_string()),
+                execution_time_milliseconds: Some(12),
+            }
+        );
+
+        let failed = json!({"version": 2, "success": false});
+        let migration = migration_from_row(MigrationTool::Sqlx, &failed);
+        assert_eq!(migration.version, "2");
+        assert_eq!(migration.success, Some(false));
+    }
+
+    #[test]
+    fn test_pending_migrations() {
+        let migration = |version: &str| Migration {
+            version: version.to_string(),
+            description: None,
+            installed_on: None,
+            success: None,
+            checksum: None,
+            execution_time_milliseconds: None,
+        };
+        let histories = [MigrationHistory {
+            table: SQLX_MIGRATIONS_TABLE.to_string(),
+            tool: MigrationTool::Sqlx,
+            migrations: vec![migration("1"), migration("2")],
+        }];
+
+        let pending = pending_migrations(
+            vec![migration("1"), migration("2"), migration("3")],
+            &histories,
+        );
+        assert_eq!(pending, vec![migration("3")]);
+        assert_eq!(pending_migrations(vec![migration("1")], &[]).len(), 1);
+    }
+
+    #[test]
+    fn test_diesel_migration_from_row() {
+        let row = json!({"version": "00000000000000", "installed_on": "2024-01-01 12:00:00"});
+        let migration = migration_from_row(MigrationTool::Diesel, &row);
+        assert_eq!(migration.version, "00000000000000");
+        assert_eq!(migration.success, None);
+        assert_eq!(migration.checksum, None);
+    }
+}
@@ -0,0 +1,187 @@
+//! Named snapshots of table rows
+//!
+//! A named snapshot is a copy of the rows of one table or of all tables, kept
+//! under a name so the data can be restored or compared with later, e.g.
+//! before and after clicking through a feature by hand. SQLite keeps each
+//! snapshot in a file next to the database (written with `VACUUM INTO` for
+//! the whole database), PostgreSQL in a schema of its own holding a copy of
+//! each table (`CREATE TABLE ... AS`).
+//!
+//! Unlike the seed, restoring a snapshot only replaces rows: the tables must
+//! still exist and have the snapshot's columns.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::database::traits::DatabaseError;
+use crate::schema::{ChangedRow, SnapshotDiff, SnapshotRowDifferences, TableSchema};
+
+/// Maximum length of a snapshot name
+///
+/// Keeps the PostgreSQL schema name (name plus prefix) within 63 bytes.
+pub const MAX_SNAPSHOT_NAME_LENGTH: usize = 40;
+
+/// Maximum number of differing rows read from each side when comparing
+pub const MAX_DIFF_ROWS: u64 = 1000;
+
+/// Check that `name` can name a snapshot file and schema
+///
+/// Names consist of letters, digits, underscores and dashes.
+pub fn validate_snapshot_name(name: &str) -> Result<(), DatabaseError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_SNAPSHOT_NAME_LENGTH
+        && name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || matches!(character, '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(DatabaseError::InvalidSnapshotName(format!(
+            "'{}' must be 1 to {} letters, digits, underscores or dashes",
+            name, MAX_SNAPSHOT_NAME_LENGTH
+        )))
+    }
+}
+
+/// Pair the differing rows of a table by primary key
+///
+/// A row on both sides with the same key was changed; the other rows were
+/// added or removed. Tables without a primary key only have added and
+/// removed rows.
+///
+/// # Arguments
+///
+/// * `snapshot` - Name of the snapshot
+/// * `schema` - Current schema of the table
+/// * `differences` - Differing rows as read by the database
+pub fn diff_rows(
+    snapshot: &str,
+    schema: &TableSchema,
+    differences: SnapshotRowDifferences,
+) -> SnapshotDiff {
+    let mut diff = SnapshotDiff {
+        snapshot: snapshot.to_string(),
+        table: schema.name.clone(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+        truncated: differences.truncated,
+    };
+
+    let Some(primary_key) = &schema.primary_key else {
+        diff.added = differences.current;
+        diff.removed = differences.snapshot;
+        return diff;
+    };
+    let key = |row: &Value| -> Vec<Value> {
+        primary_key
+            .iter()
+            .map(|column| row.get(column).cloned().unwrap_or(Value::Null))
+            .collect()
+    };
+
+    let mut before_by_key: HashMap<String, Value> = HashMap::new();
+    let mut key_order = Vec::new();
+    for row in differences.snapshot {
+        let row_key = Value::Array(key(&row)).to_string();
+        key_order.push(row_key.clone());
+        before_by_key.insert(row_key, row);
+    }
+
+    for after in differences.current {
+        let row_key = Value::Array(key(&after)).to_string();
+        match before_by_key.remove(&row_key) {
+            Some(before) => {
+                let columns = changed_columns(&before, &after);
+                diff.changed.push(ChangedRow {
+                    before,
+                    after,
+                    columns,
+                });
+            }
+            None => diff.added.push(after),
+        }
+    }
+    diff.removed = key_order
+        .iter()
+        .filter_map(|row_key| before_by_key.remove(row_key))
+        .collect();
+
+    diff
+}
+
+/// Columns whose values differ between two versions of a row, in the order of `after`
+fn changed_columns(before: &Value, after: &Value) -> Vec<String> {
+    let Some(after) = after.as_object() else {
+        return Vec::new();
+    };
+    after
+        .iter()
+        .filter(|(column, value)| before.get(column.as_str()) != Some(*value))
+        .map(|(column, _)| column.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::table;
+    use serde_json::json;
+
+    #[test]
+    fn test_snapshot_names() {
+        assert!(validate_snapshot_name("before-checkout_2").is_ok());
+        assert!(validate_snapshot_name("").is_err());
+        assert!(validate_snapshot_name("../users").is_err());
+        assert!(validate_snapshot_name(&"a".repeat(MAX_SNAPSHOT_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_diff_rows_by_primary_key() {
+        let differences = SnapshotRowDifferences {
+            current: vec![
+                json!({"id": 1, "name": "Alice", "plan": "pro"}),
+                json!({"id": 3, "name": "Carol", "plan": "free"}),
+            ],
+            snapshot: vec![
+                json!({"id": 2, "name": "Bob", "plan": "free"}),
+                json!({"id": 1, "name": "Alice", "plan": "free"}),
+            ],
+            truncated: false,
+        };
+
+        let diff = diff_rows(
+            "before",
+            &table("users").primary_key(&["id"]).build(),
+            differences,
+        );
+        assert_eq!(diff.table, "users");
+        assert_eq!(
+            diff.added,
+            vec![json!({"id": 3, "name": "Carol", "plan": "free"})]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![json!({"id": 2, "name": "Bob", "plan": "free"})]
+        );
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].before["plan"], "free");
+        assert_eq!(diff.changed[0].after["plan"], "pro");
+        assert_eq!(diff.changed[0].columns, vec!["plan"]);
+    }
+
+    #[test]
+    fn test_diff_rows_without_primary_key() {
+        let differences = SnapshotRowDifferences {
+            current: vec![json!({"message": "b"})],
+            snapshot: vec![json!({"message": "a"})],
+            truncated: true,
+        };
+
+        let diff = diff_rows("before", &table("users").build(), differences);
+        assert_eq!(diff.added, vec![json!({"message": "b"})]);
+        assert_eq!(diff.removed, vec![json!({"message": "a"})]);
+        assert!(diff.changed.is_empty());
+        assert!(diff.truncated);
+    }
+}
@@ -0,0 +1,55 @@
+//! Database notifications forwarded to the browser
+//!
+//! With notification channels configured on the layer, a background task
+//! `LISTEN`s on them and publishes every notification to the clients listening
+//! on `/api/notifications`. An application under development can then
+//! `NOTIFY` after changing data, and open viewers refresh the affected table.
+
+use futures_util::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::broadcast::Broadcast;
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::runtime;
+use crate::schema::Notification;
+
+/// Time to wait before listening again after the connection was lost
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Publishes database notifications to any number of listeners
+pub type NotificationEvents = Broadcast<Notification>;
+
+/// Forward notifications sent to `channels` to `events`
+///
+/// Runs for as long as the process does, listening again after a delay when
+/// the connection is lost. Stops right away if the database does not support
+/// notifications.
+pub async fn forward_notifications<DB: DatabaseProvider>(
+    database: Arc<DB>,
+    channels: Vec<String>,
+    events: NotificationEvents,
+) {
+    loop {
+        match database.listen(&channels).await {
+            Ok(mut notifications) => {
+                while let Some(notification) = notifications.next().await {
+                    match notification {
+                        Ok(notification) => events.publish(notification),
+                        Err(error) => {
+                            eprintln!("Stopped receiving notifications: {}", error);
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(DatabaseError::Unsupported(message)) => {
+                eprintln!("Not listening for notifications: {}", message);
+                return;
+            }
+            Err(error) => eprintln!("Failed to listen for notifications: {}", error),
+        }
+
+        runtime::sleep(RECONNECT_DELAY).await;
+    }
+}
@@ -0,0 +1,230 @@
+//! Shareable links to a view of the console
+//!
+//! A view (a table with its filters, sort and search, or a query with its
+//! parameters) is kept on the server under a short opaque token, so a link
+//! carrying only the token opens the same grid for someone else. Views are
+//! kept in memory: links stop working when the server restarts, and the
+//! oldest are dropped once too many were shared.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::schema::{QueryParams, SortOrder};
+
+/// Maximum number of views kept; sharing another drops the oldest
+pub const MAX_PERMALINKS: usize = 10_000;
+
+/// Largest view that can be shared, in bytes of JSON
+pub const MAX_VIEW_BYTES: usize = 64 * 1024;
+
+/// What a permalink opens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SharedView {
+    /// Rows of a table
+    #[serde(rename_all = "camelCase")]
+    Table {
+        /// Name of the table
+        table: String,
+
+        /// Column filters, as for `/api/tables/:name/rows`
+        #[serde(default)]
+        filters: BTreeMap<String, String>,
+
+        /// Column to sort by
+        #[serde(default)]
+        sort_by: Option<String>,
+
+        /// Sort direction
+        #[serde(default)]
+        sort_order: Option<SortOrder>,
+
+        /// Text typed into the search box
+        #[serde(default)]
+        search: Option<String>,
+    },
+
+    /// A query in the console
+    #[serde(rename_all = "camelCase")]
+    Query {
+        /// SQL text
+        sql: String,
+
+        /// Values to bind to the query's placeholders
+        #[serde(default)]
+        parameters: QueryParams,
+    },
+}
+
+/// Response containing a shared view and its token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermalinkResponse {
+    /// Token that opens the view
+    pub token: String,
+
+    /// The shared view
+    pub view: SharedView,
+}
+
+/// Shared views, by token
+///
+/// Cloning `Permalinks` is cheap; all clones share the same views.
+#[derive(Clone)]
+pub struct Permalinks {
+    views: Arc<Mutex<HashMap<String, StoredView>>>,
+    sequence: Arc<AtomicU64>,
+    random: RandomState,
+}
+
+struct StoredView {
+    view: SharedView,
+    /// Value of the sequence when the view was shared, to find the oldest
+    sequence: u64,
+}
+
+impl Default for Permalinks {
+    fn default() -> Self {
+        Self {
+            views: Arc::new(Mutex::new(HashMap::new())),
+            sequence: Arc::new(AtomicU64::new(0)),
+            random: RandomState::new(),
+        }
+    }
+}
+
+impl Permalinks {
+    /// Keep `view` under a new token
+    ///
+    /// Fails if the view names no table or SQL, or is too large.
+    ///
+    /// # Returns
+    ///
+    /// Token that opens the view
+    pub fn share(&self, view: SharedView) -> Result<String, String> {
+        validate(&view)?;
+
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let token = format!(
+            "{}{}",
+            encode(self.random.hash_one(sequence)),
+            encode(sequence)
+        );
+
+        let mut views = self.views.lock().unwrap();
+        if views.len() >= MAX_PERMALINKS {
+            let oldest = views
+                .iter()
+                .min_by_key(|(_, stored)| stored.sequence)
+                .map(|(token, _)| token.clone());
+            if let Some(oldest) = oldest {
+                views.remove(&oldest);
+            }
+        }
+        views.insert(token.clone(), StoredView { view, sequence });
+        Ok(token)
+    }
+
+    /// The view shared under `token`, if it is still kept
+    pub fn resolve(&self, token: &str) -> Option<SharedView> {
+        self.views
+            .lock()
+            .unwrap()
+            .get(token)
+            .map(|stored| stored.view.clone())
+    }
+}
+
+/// Check that a view names what it opens and is not too large
+fn validate(view: &SharedView) -> Result<(), String> {
+    match view {
+        SharedView::Table { table, .. } if table.trim().is_empty() => {
+            return Err("table must not be empty".to_string())
+        }
+        SharedView::Query { sql, .. } if sql.trim().is_empty() => {
+            return Err("sql must not be empty".to_string())
+        }
+        _ => {}
+    }
+
+    let size = serde_json::to_vec(view)
+        .map_err(|error| error.to_string())?
+        .len();
+    if size > MAX_VIEW_BYTES {
+        return Err(format!(
+            "View is too large to share: {} bytes (at most {})",
+            size, MAX_VIEW_BYTES
+        ));
+    }
+    Ok(())
+}
+
+/// Write `value` with the letters and digits that need no escaping in URLs
+fn encode(mut value: u64) -> String {
+    const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+    let mut encoded = Vec::new();
+    loop {
+        encoded.push(ALPHABET[(value % ALPHABET.len() as u64) as usize]);
+        value /= ALPHABET.len() as u64;
+        if value == 0 {
+            break;
+        }
+    }
+    encoded.reverse();
+    String::from_utf8(encoded).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn table_view(table: &str) -> SharedView {
+        serde_json::from_value(json!({
+            "type": "table",
+            "table": table,
+            "filters": { "status": "active" },
+            "sortBy": "created_at",
+            "sortOrder": "descending"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_shared_view_resolves() {
+        let permalinks = Permalinks::default();
+        let token = permalinks.share(table_view("users")).unwrap();
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        let view = permalinks.resolve(&token).unwrap();
+        assert_eq!(
+            serde_json::to_value(&view).unwrap(),
+            serde_json::to_value(table_view("users")).unwrap()
+        );
+        assert!(permalinks.resolve("unknown").is_none());
+    }
+
+    #[test]
+    fn test_tokens_are_distinct() {
+        let permalinks = Permalinks::default();
+        let first = permalinks.share(table_view("users")).unwrap();
+        let second = permalinks.share(table_view("users")).unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_empty_or_oversized_views_are_rejected() {
+        let permalinks = Permalinks::default();
+        assert!(permalinks.share(table_view(" ")).is_err());
+
+        let view = SharedView::Query {
+            sql: "x".repeat(MAX_VIEW_BYTES + 1),
+            parameters: QueryParams::default(),
+        };
+        assert!(permalinks.share(view).unwrap_err().contains("too large"));
+    }
+}
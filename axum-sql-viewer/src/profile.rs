@@ -0,0 +1,396 @@
+//! Data profiles of tables
+//!
+//! A profile describes what a table's columns actually hold, as opposed to
+//! what their declared types allow: how many values are NULL, which kinds of
+//! values occur (e.g. numbers stored as text, or a few timestamps among free
+//! text in a SQLite column), the most frequent values and how long text
+//! values are. Profiles are computed from a random sample of rows (see
+//! [`crate::sample`]), so they are cheap on large tables but approximate.
+//!
+//! Profiling is opt-in (`SqlViewerLayer::with_profiling`). Profiles are kept
+//! in a [`Profiler`] and reused until they are older than its time to live.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::filters::parse_timestamp;
+use crate::sample::SampleMethod;
+use crate::schema::TableSchema;
+
+/// Number of rows sampled for a profile (at most the maximum page size)
+pub const PROFILE_SAMPLE_SIZE: u64 = 1000;
+
+/// Number of most frequent values reported per column
+pub const TOP_VALUES: usize = 5;
+
+/// Error of profile requests while profiling is not enabled
+pub const PROFILING_DISABLED: &str =
+    "Profiling is not enabled; enable it with SqlViewerLayer::with_profiling";
+
+/// Profile of a table's columns, computed from a sample of its rows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableProfile {
+    /// Table name
+    pub table: String,
+
+    /// Number of rows the profile was computed from
+    pub sampled_rows: u64,
+
+    /// How the rows were sampled
+    pub method: SampleMethod,
+
+    /// When the profile was computed (milliseconds since the Unix epoch)
+    pub profiled_at_milliseconds: u64,
+
+    /// Profiles of the columns, in table order
+    pub columns: Vec<ColumnProfile>,
+}
+
+/// Profile of the sampled values of a column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnProfile {
+    /// Column name
+    pub column: String,
+
+    /// Declared SQL data type
+    pub data_type: String,
+
+    /// Number of sampled NULLs
+    pub null_count: u64,
+
+    /// Share of sampled rows with NULL (0 to 1)
+    pub null_ratio: f64,
+
+    /// Number of sampled values of each kind (`integer`, `real`, `boolean`,
+    /// `timestamp`, `json` or `text`), recognizing numbers and timestamps
+    /// stored as text
+    pub value_kinds: BTreeMap<String, u64>,
+
+    /// Most common kind of value, if any value is not NULL
+    pub dominant_kind: Option<String>,
+
+    /// Share of non-NULL values of the dominant kind (1 when all agree)
+    pub consistency: f64,
+
+    /// Most frequent values, most frequent first
+    pub top_values: Vec<ValueCount>,
+
+    /// Lengths of text values, if there are any
+    pub lengths: Option<LengthDistribution>,
+}
+
+/// A value and how often it was sampled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueCount {
+    /// The value
+    pub value: Value,
+
+    /// Number of sampled rows with it
+    pub count: u64,
+}
+
+/// Distribution of the lengths (in characters) of text values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LengthDistribution {
+    /// Shortest length
+    pub min: u64,
+
+    /// Longest length
+    pub max: u64,
+
+    /// Average length
+    pub average: f64,
+
+    /// Median length
+    pub median: u64,
+
+    /// Length that 90% of the values do not exceed
+    pub p90: u64,
+}
+
+/// Opt-in profiling with cached profiles
+///
+/// Cloning a `Profiler` is cheap; all clones share the same profiles.
+#[derive(Clone, Default)]
+pub struct Profiler {
+    inner: Option<Arc<Profiles>>,
+}
+
+struct Profiles {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, TableProfile)>>,
+}
+
+impl Profiler {
+    /// Profiler answering requests with [`PROFILING_DISABLED`]
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Enabled profiler reusing profiles until they are older than `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            inner: Some(Arc::new(Profiles {
+                ttl,
+                entries: Mutex::new(HashMap::new()),
+            })),
+        }
+    }
+
+    /// Whether tables can be profiled
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Profile of `table`, from the cache unless it is stale or `refresh` is set
+    pub async fn profile<DB: DatabaseProvider>(
+        &self,
+        database: &DB,
+        table: &str,
+        refresh: bool,
+    ) -> Result<TableProfile, DatabaseError> {
+        let Some(profiles) = &self.inner else {
+            return Err(DatabaseError::Unsupported(PROFILING_DISABLED.to_string()));
+        };
+
+        if !refresh {
+            let entries = profiles.entries.lock().unwrap();
+            if let Some((stored_at, profile)) = entries.get(table) {
+                if stored_at.elapsed() < profiles.ttl {
+                    return Ok(profile.clone());
+                }
+            }
+        }
+
+        let schema = database.get_table_schema(table).await?;
+        let sample = database
+            .sample_rows(&schema.name, PROFILE_SAMPLE_SIZE)
+            .await?;
+        let profile = profile_table(&schema, &sample.rows, sample.method);
+
+        let mut entries = profiles.entries.lock().unwrap();
+        entries.retain(|_, (stored_at, _)| stored_at.elapsed() < profiles.ttl);
+        entries.insert(table.to_string(), (Instant::now(), profile.clone()));
+        Ok(profile)
+    }
+}
+
+/// Profile the columns of `schema` from sampled `rows` (objects by column name)
+pub fn profile_table(schema: &TableSchema, rows: &[Value], method: SampleMethod) -> TableProfile {
+    let columns = schema
+        .columns
+        .iter()
+        .map(|column| {
+            let values: Vec<&Value> = rows
+                .iter()
+                .map(|row| row.get(&column.name).unwrap_or(&Value::Null))
+                .collect();
+            profile_column(&column.name, &column.data_type, &values)
+        })
+        .collect();
+
+    TableProfile {
+        table: schema.name.clone(),
+        sampled_rows: rows.len() as u64,
+        method,
+        profiled_at_milliseconds: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default(),
+        columns,
+    }
+}
+
+/// Profile of one column's sampled `values`
+fn profile_column(name: &str, data_type: &str, values: &[&Value]) -> ColumnProfile {
+    let present: Vec<&Value> = values
+        .iter()
+        .copied()
+        .filter(|value| !value.is_null())
+        .collect();
+    let null_count = (values.len() - present.len()) as u64;
+
+    let mut value_kinds = BTreeMap::new();
+    for value in &present {
+        *value_kinds
+            .entry(value_kind(value).to_string())
+            .or_insert(0) += 1;
+    }
+    // Ties go to the kind that sorts first, so profiles are reproducible
+    let dominant = value_kinds
+        .iter()
+        .max_by(|(kind, count), (other_kind, other_count)| {
+            count.cmp(other_count).then(other_kind.cmp(kind))
+        })
+        .map(|(kind, count)| (kind.clone(), *count));
+
+    ColumnProfile {
+        column: name.to_string(),
+        data_type: data_type.to_string(),
+        null_count,
+        null_ratio: ratio(null_count, values.len()),
+        consistency: match &dominant {
+            Some((_, count)) => ratio(*count, present.len()),
+            None => 1.0,
+        },
+        dominant_kind: dominant.map(|(kind, _)| kind),
+        value_kinds,
+        top_values: top_values(&present),
+        lengths: length_distribution(&present),
+    }
+}
+
+/// Kind of a sampled value, looking into text for numbers, timestamps and JSON
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "real",
+        Value::Number(_) => "integer",
+        Value::Object(_) | Value::Array(_) => "json",
+        Value::String(text) => {
+            let text = text.trim();
+            if text.parse::<i64>().is_ok() {
+                "integer"
+            } else if text.parse::<f64>().is_ok_and(f64::is_finite) {
+                "real"
+            } else if parse_timestamp(text).is_some() {
+                "timestamp"
+            } else if (text.starts_with('{') || text.starts_with('['))
+                && serde_json::from_str::<Value>(text).is_ok()
+            {
+                "json"
+            } else {
+                "text"
+            }
+        }
+        Value::Null => "null",
+    }
+}
+
+/// The [`TOP_VALUES`] most frequent values, ties in the order of their text
+fn top_values(values: &[&Value]) -> Vec<ValueCount> {
+    let mut counts: HashMap<String, (&Value, u64)> = HashMap::new();
+    for value in values {
+        let key = match value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        counts.entry(key).or_insert((value, 0)).1 += 1;
+    }
+
+    let mut counts: Vec<(String, (&Value, u64))> = counts.into_iter().collect();
+    counts.sort_by(|(key, (_, count)), (other_key, (_, other_count))| {
+        other_count.cmp(count).then(key.cmp(other_key))
+    });
+    counts
+        .into_iter()
+        .take(TOP_VALUES)
+        .map(|(_, (value, count))| ValueCount {
+            value: value.clone(),
+            count,
+        })
+        .collect()
+}
+
+/// Distribution of the lengths of the text values among `values`
+fn length_distribution(values: &[&Value]) -> Option<LengthDistribution> {
+    let mut lengths: Vec<u64> = values
+        .iter()
+        .filter_map(|value| value.as_str())
+        .map(|text| text.chars().count() as u64)
+        .collect();
+    if lengths.is_empty() {
+        return None;
+    }
+    lengths.sort_unstable();
+
+    let percentile = |share: f64| {
+        let index = ((lengths.len() as f64 * share).ceil() as usize).saturating_sub(1);
+        lengths[index.min(lengths.len() - 1)]
+    };
+    Some(LengthDistribution {
+        min: lengths[0],
+        max: lengths[lengths.len() - 1],
+        average: lengths.iter().sum::<u64>() as f64 / lengths.len() as f64,
+        median: percentile(0.5),
+        p90: percentile(0.9),
+    })
+}
+
+/// `part` of `total` as a share between 0 and 1 (0 without a total)
+fn ratio(part: u64, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::table;
+    use serde_json::json;
+
+    #[test]
+    fn test_profile_table() {
+        let schema = table("events")
+            .columns(&[("id", "INTEGER"), ("payload", "TEXT")])
+            .build();
+        let rows = [
+            json!({"id": 1, "payload": "42"}),
+            json!({"id": 2, "payload": "hello"}),
+            json!({"id": 3, "payload": "hello"}),
+            json!({"id": 4, "payload": null}),
+        ];
+
+        let profile = profile_table(&schema, &rows, SampleMethod::Random);
+        assert_eq!(profile.sampled_rows, 4);
+
+        let id = &profile.columns[0];
+        assert_eq!(id.null_count, 0);
+        assert_eq!(id.dominant_kind.as_deref(), Some("integer"));
+        assert_eq!(id.consistency, 1.0);
+        assert_eq!(id.lengths, None);
+
+        let payload = &profile.columns[1];
+        assert_eq!(payload.null_ratio, 0.25);
+        assert_eq!(payload.value_kinds.get("text"), Some(&2));
+        assert_eq!(payload.value_kinds.get("integer"), Some(&1));
+        assert_eq!(payload.dominant_kind.as_deref(), Some("text"));
+        assert!((payload.consistency - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            payload.top_values[0],
+            ValueCount {
+                value: json!("hello"),
+                count: 2
+            }
+        );
+        let lengths = payload.lengths.as_ref().unwrap();
+        assert_eq!((lengths.min, lengths.max, lengths.median), (2, 5, 5));
+    }
+
+    #[test]
+    fn test_value_kind() {
+        assert_eq!(value_kind(&json!(1.5)), "real");
+        assert_eq!(value_kind(&json!("1.5")), "real");
+        assert_eq!(value_kind(&json!("NaN")), "text");
+        assert_eq!(value_kind(&json!("2024-01-01T10:00:00Z")), "timestamp");
+        assert_eq!(value_kind(&json!("{\"a\": 1}")), "json");
+        assert_eq!(value_kind(&json!(true)), "boolean");
+    }
+
+    #[test]
+    fn test_disabled_profiler() {
+        assert!(!Profiler::disabled().is_enabled());
+        assert!(Profiler::new(Duration::from_secs(60)).is_enabled());
+    }
+}
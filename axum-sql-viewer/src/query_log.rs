@@ -0,0 +1,261 @@
+//! Capture of SQL statements logged by sqlx
+//!
+//! sqlx reports every executed statement as a `tracing` event with the
+//! `sqlx::query` target. [`QueryLog`] provides a `tracing-subscriber` layer that
+//! records those events into a bounded in-memory buffer, so the statements the
+//! host application runs (not just the viewer's own queries) can be inspected
+//! from the viewer.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use axum_sql_viewer::{QueryLog, SqlViewerLayer};
+//! use sqlx::SqlitePool;
+//! use tracing_subscriber::prelude::*;
+//!
+//! # async fn example() {
+//! let query_log = QueryLog::new(500);
+//! tracing_subscriber::registry().with(query_log.layer()).init();
+//!
+//! let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+//! let viewer = SqlViewerLayer::sqlite("/sql-viewer", pool).with_query_log(query_log);
+//! # }
+//! ```
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Target used by sqlx for statement logging events
+const SQLX_QUERY_TARGET: &str = "sqlx::query";
+
+/// A statement captured from sqlx's statement logging
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggedStatement {
+    /// Monotonically increasing identifier (useful for incremental polling)
+    pub id: u64,
+
+    /// Time the statement finished, in milliseconds since the Unix epoch
+    pub timestamp_milliseconds: u64,
+
+    /// Log level the statement was reported at (slow statements are logged at a higher level)
+    pub level: String,
+
+    /// Full SQL text of the statement
+    pub statement: String,
+
+    /// Short summary of the statement as produced by sqlx
+    pub summary: Option<String>,
+
+    /// Number of rows affected by the statement
+    pub rows_affected: Option<u64>,
+
+    /// Number of rows returned by the statement
+    pub rows_returned: Option<u64>,
+
+    /// Execution time in milliseconds
+    pub elapsed_milliseconds: Option<f64>,
+}
+
+/// Bounded buffer of recently executed statements
+///
+/// Cloning a `QueryLog` is cheap; all clones share the same buffer.
+#[derive(Clone)]
+pub struct QueryLog {
+    inner: Arc<QueryLogInner>,
+}
+
+struct QueryLogInner {
+    capacity: usize,
+    next_id: AtomicU64,
+    statements: Mutex<VecDeque<LoggedStatement>>,
+}
+
+impl QueryLog {
+    /// Create a new query log keeping at most `capacity` statements
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - Maximum number of statements kept in memory
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(QueryLogInner {
+                capacity: capacity.max(1),
+                next_id: AtomicU64::new(1),
+                statements: Mutex::new(VecDeque::new()),
+            }),
+        }
+    }
+
+    /// Create a `tracing-subscriber` layer that records sqlx statements into this log
+    ///
+    /// Install the layer in the host application's subscriber. sqlx logs
+    /// statements at `DEBUG` by default, so the subscriber must not filter
+    /// out `sqlx::query` events at that level.
+    pub fn layer(&self) -> QueryLogLayer {
+        QueryLogLayer { log: self.clone() }
+    }
+
+    /// Return recorded statements, newest first
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - Only return statements with an identifier greater than this value
+    /// * `limit` - Maximum number of statements to return
+    pub fn recent(&self, since: Option<u64>, limit: usize) -> Vec<LoggedStatement> {
+        let statements = self.inner.statements.lock().unwrap();
+        statements
+            .iter()
+            .rev()
+            .filter(|statement| since.is_none_or(|since| statement.id > since))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Remove all recorded statements
+    pub fn clear(&self) {
+        self.inner.statements.lock().unwrap().clear();
+    }
+
+    fn record(&self, mut statement: LoggedStatement) {
+        statement.id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+
+        let mut statements = self.inner.statements.lock().unwrap();
+        while statements.len() >= self.inner.capacity {
+            statements.pop_front();
+        }
+        statements.push_back(statement);
+    }
+}
+
+/// `tracing-subscriber` layer feeding a [`QueryLog`]
+pub struct QueryLogLayer {
+    log: QueryLog,
+}
+
+impl<S: Subscriber> Layer<S> for QueryLogLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let metadata = event.metadata();
+        if metadata.target() != SQLX_QUERY_TARGET {
+            return;
+        }
+
+        let mut visitor = StatementVisitor::default();
+        event.record(&mut visitor);
+
+        let Some(statement) = visitor.statement else {
+            return;
+        };
+
+        let timestamp_milliseconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+
+        self.log.record(LoggedStatement {
+            id: 0,
+            timestamp_milliseconds,
+            level: metadata.level().to_string(),
+            statement,
+            summary: visitor.summary,
+            rows_affected: visitor.rows_affected,
+            rows_returned: visitor.rows_returned,
+            elapsed_milliseconds: visitor.elapsed_seconds.map(|seconds| seconds * 1000.0),
+        });
+    }
+}
+
+/// Collects the fields sqlx attaches to its statement events
+#[derive(Default)]
+struct StatementVisitor {
+    statement: Option<String>,
+    summary: Option<String>,
+    rows_affected: Option<u64>,
+    rows_returned: Option<u64>,
+    elapsed_seconds: Option<f64>,
+}
+
+impl Visit for StatementVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "db.statement" => self.statement = Some(value.to_string()),
+            "summary" => self.summary = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "rows_affected" => self.rows_affected = Some(value),
+            "rows_returned" => self.rows_returned = Some(value),
+            _ => {}
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_u64(field, value.max(0) as u64);
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_seconds = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        // Statement text and summary are recorded as strings by current sqlx
+        // versions; fall back to the debug representation for anything else
+        match field.name() {
+            "db.statement" if self.statement.is_none() => {
+                self.statement = Some(format!("{:?}", value).trim_matches('"').to_string())
+            }
+            "summary" if self.summary.is_none() => {
+                self.summary = Some(format!("{:?}", value).trim_matches('"').to_string())
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement(sql: &str) -> LoggedStatement {
+        LoggedStatement {
+            id: 0,
+            timestamp_milliseconds: 0,
+            level: "DEBUG".to_string(),
+            statement: sql.to_string(),
+            summary: None,
+            rows_affected: None,
+            rows_returned: None,
+            elapsed_milliseconds: None,
+        }
+    }
+
+    #[test]
+    fn test_query_log_capacity_and_order() {
+        let log = QueryLog::new(2);
+        log.record(statement("SELECT 1"));
+        log.record(statement("SELECT 2"));
+        log.record(statement("SELECT 3"));
+
+        let recent = log.recent(None, 10);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].statement, "SELECT 3");
+        assert_eq!(recent[1].statement, "SELECT 2");
+
+        let newer = log.recent(Some(recent[1].id), 10);
+        assert_eq!(newer.len(), 1);
+        assert_eq!(newer[0].statement, "SELECT 3");
+    }
+}
@@ -0,0 +1,190 @@
+//! Foreign key reference traversal
+//!
+//! Finds the rows that reference a table through foreign keys, and builds the
+//! statements needed to delete them before the referenced rows ("cascade").
+
+use crate::schema::{ForeignKey, TableSchema};
+
+/// A foreign key, seen from the table it references
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// Referencing table
+    pub table: String,
+
+    /// Foreign key column in the referencing table
+    pub column: String,
+
+    /// Referenced column in the parent table
+    pub references_column: String,
+}
+
+/// Foreign keys of all tables that reference `table`
+pub fn references_to<'a>(
+    schemas: &'a [TableSchema],
+    table: &'a str,
+) -> impl Iterator<Item = Reference> + 'a {
+    schemas.iter().flat_map(move |schema| {
+        schema
+            .foreign_keys
+            .iter()
+            .filter(move |foreign_key| foreign_key.references_table == table)
+            .map(move |foreign_key: &ForeignKey| Reference {
+                table: schema.name.clone(),
+                column: foreign_key.column.clone(),
+                references_column: foreign_key.references_column.clone(),
+            })
+    })
+}
+
+/// Chains of references that have to be deleted before rows of `table`
+///
+/// Each path starts at a table referencing `table` directly and ends at the
+/// table whose rows it deletes. Paths are ordered so that every path comes
+/// before the paths it depends on (deepest first), which is the order the
+/// deletes have to run in. A table is not revisited within a path, so for
+/// self-referencing tables and cycles only the first level is followed.
+pub fn cascade_paths(schemas: &[TableSchema], table: &str) -> Vec<Vec<Reference>> {
+    let mut paths = Vec::new();
+    collect_paths(
+        schemas,
+        table,
+        &mut vec![table.to_string()],
+        &mut Vec::new(),
+        &mut paths,
+    );
+    paths
+}
+
+fn collect_paths(
+    schemas: &[TableSchema],
+    table: &str,
+    visited_tables: &mut Vec<String>,
+    path: &mut Vec<Reference>,
+    paths: &mut Vec<Vec<Reference>>,
+) {
+    for reference in references_to(schemas, table) {
+        let is_cycle = visited_tables.contains(&reference.table);
+
+        path.push(reference.clone());
+        if !is_cycle {
+            visited_tables.push(reference.table.clone());
+            collect_paths(schemas, &reference.table, visited_tables, path, paths);
+            visited_tables.pop();
+        }
+        paths.push(path.clone());
+        path.pop();
+    }
+}
+
+/// Build the DELETE statement for the last table of a cascade path
+///
+/// The rows to delete are selected with nested subqueries starting from the
+/// rows of `root_table` matching `root_condition`, so all of them have to be
+/// run before the root rows themselves are deleted.
+///
+/// # Arguments
+///
+/// * `root_table` - Table whose rows are being deleted
+/// * `root_condition` - SQL condition selecting those rows (may contain placeholders)
+/// * `path` - Path returned by [`cascade_paths`]
+/// * `quote_identifier` - Identifier quoting of the database
+pub fn cascade_delete_sql(
+    root_table: &str,
+    root_condition: &str,
+    path: &[Reference],
+    quote_identifier: impl Fn(&str) -> String,
+) -> String {
+    let Some((target, parents)) = path.split_last() else {
+        return String::new();
+    };
+
+    let mut selection = format!(
+        "SELECT {} FROM {} WHERE {}",
+        quote_identifier(&path[0].references_column),
+        quote_identifier(root_table),
+        root_condition
+    );
+    for (index, parent) in parents.iter().enumerate() {
+        let child = &path[index + 1];
+        selection = format!(
+            "SELECT {} FROM {} WHERE {} IN ({})",
+            quote_identifier(&child.references_column),
+            quote_identifier(&parent.table),
+            quote_identifier(&parent.column),
+            selection
+        );
+    }
+
+    format!(
+        "DELETE FROM {} WHERE {} IN ({})",
+        quote_identifier(&target.table),
+        quote_identifier(&target.column),
+        selection
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::table;
+
+    /// `users`, `posts` by users and `comments` on posts
+    fn blog_schemas() -> Vec<TableSchema> {
+        vec![
+            table("users").primary_key(&["id"]).build(),
+            table("posts")
+                .primary_key(&["id"])
+                .foreign_key("author_id", "users", "id")
+                .build(),
+            table("comments")
+                .primary_key(&["id"])
+                .foreign_key("post_id", "posts", "id")
+                .build(),
+        ]
+    }
+
+    fn quote(identifier: &str) -> String {
+        format!("\"{}\"", identifier)
+    }
+
+    #[test]
+    fn test_cascade_paths_deepest_first() {
+        let mut schemas = blog_schemas();
+        schemas.push(
+            table("employees")
+                .primary_key(&["id"])
+                .foreign_key("manager_id", "employees", "id")
+                .build(),
+        );
+
+        let paths = cascade_paths(&schemas, "users");
+        let tables: Vec<Vec<&str>> = paths
+            .iter()
+            .map(|path| {
+                path.iter()
+                    .map(|reference| reference.table.as_str())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(tables, vec![vec!["posts", "comments"], vec!["posts"]]);
+
+        let paths = cascade_paths(&schemas, "employees");
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0][0].column, "manager_id");
+    }
+
+    #[test]
+    fn test_cascade_delete_sql() {
+        let paths = cascade_paths(&blog_schemas(), "users");
+
+        assert_eq!(
+            cascade_delete_sql("users", "\"id\" = ?", &paths[0], quote),
+            "DELETE FROM \"comments\" WHERE \"post_id\" IN (SELECT \"id\" FROM \"posts\" \
+             WHERE \"author_id\" IN (SELECT \"id\" FROM \"users\" WHERE \"id\" = ?))"
+        );
+        assert_eq!(
+            cascade_delete_sql("users", "\"id\" = ?", &paths[1], quote),
+            "DELETE FROM \"posts\" WHERE \"author_id\" IN (SELECT \"id\" FROM \"users\" WHERE \"id\" = ?)"
+        );
+    }
+}
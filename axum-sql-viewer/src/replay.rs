@@ -0,0 +1,379 @@
+//! Capture and replay of API request fixtures
+//!
+//! In recording mode every API request/response pair is written as a JSON file
+//! into a fixture directory. The replay router serves those files back without
+//! a database, so the frontend can be developed (or a bug reproduced) without
+//! access to the original dataset.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use axum::Router;
+//! use axum_sql_viewer::{replay::create_replay_router, SqlViewerLayer};
+//! use sqlx::SqlitePool;
+//!
+//! # async fn example() {
+//! // Record against a real database...
+//! let pool = SqlitePool::connect("sqlite:./app.db").await.unwrap();
+//! let recording = SqlViewerLayer::sqlite("/sql-viewer", pool)
+//!     .with_request_recording("./fixtures")
+//!     .into_router();
+//!
+//! // ...and replay later without one
+//! let replay: Router = create_replay_router("/sql-viewer", "./fixtures");
+//! # }
+//! ```
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::frontend::create_frontend_router;
+use crate::runtime;
+
+/// Maximum request or response body size that is recorded
+const MAX_RECORDED_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+/// A recorded API request/response pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestFixture {
+    /// HTTP method of the request
+    pub method: String,
+
+    /// Request path relative to the API root (e.g. "/tables/users/rows")
+    pub path: String,
+
+    /// Raw query string of the request (if any)
+    pub query: Option<String>,
+
+    /// Request body (JSON when possible, otherwise text)
+    pub request_body: serde_json::Value,
+
+    /// Response status code
+    pub status: u16,
+
+    /// Response content type
+    pub content_type: Option<String>,
+
+    /// Response body (JSON when possible, otherwise text)
+    pub response_body: serde_json::Value,
+}
+
+/// Records API request/response pairs into a fixture directory
+#[derive(Clone)]
+pub struct RequestRecorder {
+    directory: Arc<PathBuf>,
+}
+
+impl RequestRecorder {
+    /// Create a recorder writing fixtures into `directory`
+    ///
+    /// The directory is created on the first recorded request if it does not exist.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: Arc::new(directory.into()),
+        }
+    }
+
+    async fn write_fixture(
+        &self,
+        fixture: &RequestFixture,
+        request_body: &[u8],
+    ) -> std::io::Result<()> {
+        runtime::create_dir_all(self.directory.as_ref()).await?;
+
+        let file_name = fixture_file_name(
+            &fixture.method,
+            &fixture.path,
+            fixture.query.as_deref(),
+            request_body,
+        );
+        let contents = serde_json::to_vec_pretty(fixture)?;
+        runtime::write_file(self.directory.join(file_name), contents).await
+    }
+}
+
+/// Middleware recording each request/response pair passing through the API router
+///
+/// Streaming responses (server-sent events) are passed through unrecorded.
+pub async fn record_request(
+    State(recorder): State<RequestRecorder>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = request.into_parts();
+    let request_bytes = match to_bytes(body, MAX_RECORDED_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            return (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(serde_json::json!({
+                    "error": format!("Request body too large to record: {}", error)
+                })),
+            )
+                .into_response()
+        }
+    };
+
+    let method = parts.method.to_string();
+    let path = parts.uri.path().to_string();
+    let query = parts.uri.query().map(str::to_string);
+
+    let response = next
+        .run(Request::from_parts(
+            parts,
+            Body::from(request_bytes.clone()),
+        ))
+        .await;
+
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    if content_type
+        .as_deref()
+        .is_some_and(|value| value.starts_with("text/event-stream"))
+    {
+        return response;
+    }
+
+    let (response_parts, response_body) = response.into_parts();
+    let response_bytes = match to_bytes(response_body, MAX_RECORDED_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            eprintln!("Failed to buffer response for recording: {}", error);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": "Response body too large to record"
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let fixture = RequestFixture {
+        method,
+        path,
+        query,
+        request_body: body_to_value(&request_bytes),
+        status: response_parts.status.as_u16(),
+        content_type,
+        response_body: body_to_value(&response_bytes),
+    };
+
+    if let Err(error) = recorder.write_fixture(&fixture, &request_bytes).await {
+        eprintln!(
+            "Failed to write request fixture for {} {}: {}",
+            fixture.method, fixture.path, error
+        );
+    }
+
+    Response::from_parts(response_parts, Body::from(response_bytes))
+}
+
+/// Create a router serving recorded fixtures instead of a live database
+///
+/// The returned router mounts the frontend at `{base_path}/` and answers every
+/// request below `{base_path}/api` from the fixture directory. Requests without
+/// a matching fixture get a 404 response.
+///
+/// # Arguments
+///
+/// * `base_path` - The URL path where the viewer will be mounted
+/// * `fixture_directory` - Directory containing fixtures written in recording mode
+pub fn create_replay_router(
+    base_path: impl Into<String>,
+    fixture_directory: impl Into<PathBuf>,
+) -> Router {
+    let base_path = base_path.into();
+    let replay_router = Router::new()
+        .fallback(replay_request)
+        .with_state(Arc::new(fixture_directory.into()));
+
+    Router::new()
+        .nest(&format!("{}/api", base_path), replay_router)
+        .nest(&base_path, create_frontend_router(base_path.clone()))
+}
+
+/// Fallback handler answering a request from its recorded fixture
+async fn replay_request(State(directory): State<Arc<PathBuf>>, request: Request) -> Response {
+    let (parts, body) = request.into_parts();
+    let request_bytes = to_bytes(body, MAX_RECORDED_BODY_BYTES)
+        .await
+        .unwrap_or_default();
+
+    let file_name = fixture_file_name(
+        parts.method.as_str(),
+        parts.uri.path(),
+        parts.uri.query(),
+        &request_bytes,
+    );
+
+    let fixture: RequestFixture = match runtime::read_file(directory.join(&file_name)).await {
+        Ok(contents) => match serde_json::from_slice(&contents) {
+            Ok(fixture) => fixture,
+            Err(error) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({
+                        "error": format!("Invalid fixture {}: {}", file_name, error)
+                    })),
+                )
+                    .into_response()
+            }
+        },
+        Err(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "error": format!(
+                        "No fixture recorded for {} {} (expected {})",
+                        parts.method,
+                        parts.uri,
+                        file_name
+                    )
+                })),
+            )
+                .into_response()
+        }
+    };
+
+    let body = match &fixture.response_body {
+        serde_json::Value::String(text) if !is_json_content(fixture.content_type.as_deref()) => {
+            text.clone()
+        }
+        value => value.to_string(),
+    };
+
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = StatusCode::from_u16(fixture.status).unwrap_or(StatusCode::OK);
+    if let Some(content_type) = fixture
+        .content_type
+        .as_deref()
+        .and_then(|value| HeaderValue::from_str(value).ok())
+    {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, content_type);
+    }
+    response
+}
+
+/// Build the file name of a fixture from the parts identifying a request
+///
+/// The name stays readable (method and path) while a hash of the query string
+/// and body keeps distinct requests to the same path apart.
+fn fixture_file_name(method: &str, path: &str, query: Option<&str>, body: &[u8]) -> String {
+    let readable_path: String = path
+        .trim_matches('/')
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || character == '-' || character == '_' {
+                character
+            } else {
+                '_'
+            }
+        })
+        .take(80)
+        .collect();
+
+    let mut hash = Fnv1a::new();
+    hash.update(method.as_bytes());
+    hash.update(b"\n");
+    hash.update(path.as_bytes());
+    hash.update(b"\n");
+    hash.update(query.unwrap_or_default().as_bytes());
+    hash.update(b"\n");
+    // Methods without a body never carry one in a meaningful way
+    if method != Method::GET.as_str() && method != Method::HEAD.as_str() {
+        hash.update(body);
+    }
+
+    format!(
+        "{}-{}-{:016x}.json",
+        method.to_lowercase(),
+        readable_path,
+        hash.finish()
+    )
+}
+
+fn body_to_value(bytes: &[u8]) -> serde_json::Value {
+    if bytes.is_empty() {
+        return serde_json::Value::Null;
+    }
+    serde_json::from_slice(bytes)
+        .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+fn is_json_content(content_type: Option<&str>) -> bool {
+    content_type.is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// 64-bit FNV-1a hash, used because its output is stable across Rust releases
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_file_name() {
+        let name = fixture_file_name("GET", "/tables/users/rows", Some("offset=0&limit=100"), b"");
+        assert!(name.starts_with("get-tables_users_rows-"));
+        assert!(name.ends_with(".json"));
+
+        // Stable for identical requests, distinct for different query strings and bodies
+        assert_eq!(
+            name,
+            fixture_file_name("GET", "/tables/users/rows", Some("offset=0&limit=100"), b"")
+        );
+        assert_ne!(
+            name,
+            fixture_file_name(
+                "GET",
+                "/tables/users/rows",
+                Some("offset=100&limit=100"),
+                b""
+            )
+        );
+        assert_ne!(
+            fixture_file_name("POST", "/query", None, br#"{"sql":"SELECT 1"}"#),
+            fixture_file_name("POST", "/query", None, br#"{"sql":"SELECT 2"}"#)
+        );
+    }
+
+    #[test]
+    fn test_body_to_value() {
+        assert_eq!(body_to_value(b""), serde_json::Value::Null);
+        assert_eq!(body_to_value(br#"{"a":1}"#), serde_json::json!({ "a": 1 }));
+        assert_eq!(body_to_value(b"erDiagram"), serde_json::json!("erDiagram"));
+    }
+}
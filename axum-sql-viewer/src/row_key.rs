@@ -0,0 +1,603 @@
+//! Addressing single rows by primary key
+//!
+//! A row is identified by the values of its primary key columns, in the order
+//! of `TableSchema::primary_key`. In URLs they form a single path segment with
+//! the values separated by commas (e.g. `/rows/42` or `/rows/7,en`); commas and
+//! backslashes inside a value are escaped with a backslash.
+//!
+//! The canonical form for query strings names every key column instead
+//! (`?pk[user_id]=7&pk[lang]=en`), so no escaping or column order is needed;
+//! it is what join tables without a single-column key should use. JSON bodies
+//! accept either form (see [`RowKey`]).
+//!
+//! Tables without a primary key are addressed by the database's row identifier
+//! instead (`rowid` or `ctid`, see [`RowAddress`]), which then acts as a
+//! single-column key. Tables without either cannot be addressed at all, so
+//! single-row reads and edits fail with an invalid key error.
+
+use std::collections::HashMap;
+
+use crate::database::traits::DatabaseError;
+use crate::schema::{RowKey, TableSchema};
+
+/// Name of the query parameters carrying primary key values (`pk[column]=value`)
+pub const PRIMARY_KEY_PARAMETER: &str = "pk";
+
+/// Split a row key path segment into its primary key values
+///
+/// The segment is expected to be percent-decoded already.
+pub fn parse_row_key(segment: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut current = String::new();
+    let mut characters = segment.chars();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' => {
+                if let Some(escaped) = characters.next() {
+                    current.push(escaped);
+                }
+            }
+            ',' => values.push(std::mem::take(&mut current)),
+            _ => current.push(character),
+        }
+    }
+    values.push(current);
+
+    values
+}
+
+/// Join primary key values into a row key path segment (before percent-encoding)
+pub fn format_row_key(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| value.replace('\\', "\\\\").replace(',', "\\,"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Percent-encode a value for use as a single URL path segment
+///
+/// Unreserved characters (RFC 3986) are kept, everything else is encoded as
+/// UTF-8 bytes.
+pub fn encode_path_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+/// Columns addressing the rows of a table: the primary key or the row identifier
+fn address_columns(schema: &TableSchema) -> Result<Vec<String>, DatabaseError> {
+    if let Some(columns) = schema
+        .primary_key
+        .as_ref()
+        .filter(|columns| !columns.is_empty())
+    {
+        return Ok(columns.clone());
+    }
+
+    match schema.row_address.identifier_column() {
+        Some(column) => Ok(vec![column.to_string()]),
+        None => Err(DatabaseError::InvalidKey(format!(
+            "table '{}' has no primary key or row identifier, so its rows cannot be addressed",
+            schema.name
+        ))),
+    }
+}
+
+/// Get the key columns of a table, checking that `key` matches them
+///
+/// These are the primary key columns, or the row identifier column for tables
+/// without a primary key.
+///
+/// # Returns
+///
+/// The key column names, in the same order as the key values
+pub fn primary_key_columns(
+    schema: &TableSchema,
+    key: &[String],
+) -> Result<Vec<String>, DatabaseError> {
+    let columns = address_columns(schema)?;
+
+    if columns.len() != key.len() {
+        return Err(DatabaseError::InvalidKey(format!(
+            "expected {} value(s) for primary key ({}), got {}",
+            columns.len(),
+            columns.join(", "),
+            key.len()
+        )));
+    }
+
+    Ok(columns)
+}
+
+/// Build a row key from primary key values given by column name
+pub fn key_from_columns(
+    schema: &TableSchema,
+    values: &HashMap<String, String>,
+) -> Result<Vec<String>, DatabaseError> {
+    let columns = address_columns(schema)?;
+
+    if let Some(unknown) = values.keys().find(|name| !columns.contains(name)) {
+        return Err(DatabaseError::InvalidKey(format!(
+            "'{}' is not a primary key column ({})",
+            unknown,
+            columns.join(", ")
+        )));
+    }
+
+    columns
+        .iter()
+        .map(|column| {
+            values.get(column).cloned().ok_or_else(|| {
+                DatabaseError::InvalidKey(format!(
+                    "missing value for primary key column '{}'",
+                    column
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Values by column name from `name[column]=value` query parameters
+///
+/// Other parameters are ignored, so these can be combined with other options.
+pub fn bracketed_parameters(
+    parameters: &HashMap<String, String>,
+    name: &str,
+) -> HashMap<String, String> {
+    parameters
+        .iter()
+        .filter_map(|(parameter, value)| {
+            let column = parameter
+                .strip_prefix(name)?
+                .strip_prefix('[')?
+                .strip_suffix(']')?;
+            Some((column.to_string(), value.clone()))
+        })
+        .collect()
+}
+
+/// Primary key values by column name from `pk[column]=value` query parameters
+pub fn primary_key_parameters(parameters: &HashMap<String, String>) -> HashMap<String, String> {
+    bracketed_parameters(parameters, PRIMARY_KEY_PARAMETER)
+}
+
+/// Build a row key from `pk[column]=value` query parameters
+pub fn key_from_query(
+    schema: &TableSchema,
+    parameters: &HashMap<String, String>,
+) -> Result<Vec<String>, DatabaseError> {
+    key_from_columns(schema, &primary_key_parameters(parameters))
+}
+
+/// Format a row key as percent-encoded `pk[column]=value` query parameters
+///
+/// # Arguments
+///
+/// * `columns` - Primary key columns, as returned by [`primary_key_columns`]
+/// * `key` - Primary key values in the same order
+pub fn format_key_query(columns: &[String], key: &[String]) -> String {
+    columns
+        .iter()
+        .zip(key)
+        .map(|(column, value)| {
+            format!(
+                "{}[{}]={}",
+                PRIMARY_KEY_PARAMETER,
+                encode_path_segment(column),
+                encode_path_segment(value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Resolve a row key given in either form to the primary key values
+pub fn resolve_row_key(schema: &TableSchema, key: &RowKey) -> Result<Vec<String>, DatabaseError> {
+    let key = match key {
+        RowKey::Segment(segment) => parse_row_key(segment),
+        RowKey::Columns(values) => {
+            let values = values
+                .iter()
+                .map(|(column, value)| {
+                    let text = value_to_key_text(value).ok_or_else(|| {
+                        DatabaseError::InvalidKey(format!(
+                            "primary key column '{}' cannot be null",
+                            column
+                        ))
+                    })?;
+                    Ok((column.clone(), text))
+                })
+                .collect::<Result<HashMap<_, _>, DatabaseError>>()?;
+            key_from_columns(schema, &values)?
+        }
+    };
+    primary_key_columns(schema, &key)?;
+
+    Ok(key)
+}
+
+/// Column names preferred for labelling a row, most preferred first
+const DISPLAY_COLUMN_NAMES: &[&str] = &[
+    "name",
+    "display_name",
+    "title",
+    "label",
+    "username",
+    "email",
+    "slug",
+    "code",
+];
+
+/// Choose the column whose value best describes a row to a human
+///
+/// Prefers well-known names (e.g. `name`, `title`, `email`), then the first
+/// text column outside the primary key.
+pub fn display_column(schema: &TableSchema) -> Option<&str> {
+    let is_text = |data_type: &str| {
+        let data_type = data_type.to_ascii_lowercase();
+        ["char", "text", "clob", "string"]
+            .iter()
+            .any(|text_type| data_type.contains(text_type))
+    };
+
+    DISPLAY_COLUMN_NAMES
+        .iter()
+        .find_map(|name| {
+            schema
+                .columns
+                .iter()
+                .find(|column| column.name.eq_ignore_ascii_case(name))
+        })
+        .or_else(|| {
+            schema
+                .columns
+                .iter()
+                .find(|column| !column.is_primary_key && is_text(&column.data_type))
+        })
+        .map(|column| column.name.as_str())
+}
+
+/// Text form of a column value, as used in row keys and value comparisons
+///
+/// Returns `None` for NULL, which never matches anything.
+pub fn value_to_key_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Check that every column of an update exists in the table
+pub fn validate_columns<'a>(
+    schema: &TableSchema,
+    columns: impl IntoIterator<Item = &'a String>,
+) -> Result<(), DatabaseError> {
+    let mut has_columns = false;
+
+    for column in columns {
+        has_columns = true;
+        if !schema.columns.iter().any(|info| &info.name == column) {
+            return Err(DatabaseError::InvalidColumn(column.clone()));
+        }
+    }
+
+    if !has_columns {
+        return Err(DatabaseError::InvalidColumn(
+            "no columns to update".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check rows to be inserted against the table's columns
+///
+/// Every key must name a column, and columns that are `NOT NULL` without a
+/// default must be given a non-null value. Primary key columns are exempt
+/// because the database may generate them (e.g. `INTEGER PRIMARY KEY` in
+/// SQLite). Other constraints are left to the database.
+pub fn validate_new_rows(
+    schema: &TableSchema,
+    rows: &[serde_json::Map<String, serde_json::Value>],
+) -> Result<(), DatabaseError> {
+    for (index, row) in rows.iter().enumerate() {
+        let invalid = |reason: String| DatabaseError::InvalidRow { index, reason };
+
+        if let Some(column) = row
+            .keys()
+            .find(|column| !schema.columns.iter().any(|info| &info.name == *column))
+        {
+            return Err(invalid(format!("unknown column '{}'", column)));
+        }
+
+        for column in &schema.columns {
+            if column.nullable || column.is_primary_key {
+                continue;
+            }
+            match row.get(&column.name) {
+                Some(serde_json::Value::Null) => {
+                    return Err(invalid(format!("column '{}' cannot be null", column.name)))
+                }
+                None if column.default_value.is_none() => {
+                    return Err(invalid(format!("column '{}' is required", column.name)))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{ColumnInfo, RowAddress};
+
+    #[test]
+    fn test_parse_row_key() {
+        assert_eq!(parse_row_key("42"), vec!["42"]);
+        assert_eq!(parse_row_key("7,en"), vec!["7", "en"]);
+        assert_eq!(parse_row_key(r"a\,b,c\\"), vec!["a,b", r"c\"]);
+        assert_eq!(parse_row_key(""), vec![""]);
+    }
+
+    #[test]
+    fn test_format_row_key_round_trip() {
+        let values = vec!["a,b".to_string(), r"c\".to_string(), "d".to_string()];
+        assert_eq!(format_row_key(&values), r"a\,b,c\\,d");
+        assert_eq!(parse_row_key(&format_row_key(&values)), values);
+    }
+
+    #[test]
+    fn test_key_from_columns() {
+        let schema = TableSchema {
+            name: "translations".to_string(),
+            columns: Vec::new(),
+            primary_key: Some(vec!["id".to_string(), "lang".to_string()]),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: RowAddress::PrimaryKey,
+        };
+        let values = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        };
+
+        assert_eq!(
+            key_from_columns(&schema, &values(&[("lang", "en"), ("id", "7")])).unwrap(),
+            vec!["7", "en"]
+        );
+        assert!(key_from_columns(&schema, &values(&[("id", "7")])).is_err());
+        assert!(
+            key_from_columns(&schema, &values(&[("id", "7"), ("lang", "en"), ("x", "1")])).is_err()
+        );
+    }
+
+    #[test]
+    fn test_key_from_query() {
+        let schema = TableSchema {
+            name: "memberships".to_string(),
+            columns: Vec::new(),
+            primary_key: Some(vec!["user_id".to_string(), "group id".to_string()]),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: RowAddress::PrimaryKey,
+        };
+        let parameters: HashMap<String, String> = [
+            ("pk[group id]", "a,b"),
+            ("pk[user_id]", "7"),
+            ("onReferenced", "cascade"),
+        ]
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+        let key = key_from_query(&schema, &parameters).unwrap();
+        assert_eq!(key, vec!["7", "a,b"]);
+        assert_eq!(
+            format_key_query(schema.primary_key.as_ref().unwrap(), &key),
+            "pk[user_id]=7&pk[group%20id]=a%2Cb"
+        );
+        assert!(key_from_query(&schema, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_row_identifier_key() {
+        let mut schema = TableSchema {
+            name: "events".to_string(),
+            columns: Vec::new(),
+            primary_key: None,
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: RowAddress::Ctid,
+        };
+        assert_eq!(
+            primary_key_columns(&schema, &["(0,3)".to_string()]).unwrap(),
+            vec!["ctid"]
+        );
+        let values = [("ctid".to_string(), "(0,3)".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(key_from_columns(&schema, &values).unwrap(), vec!["(0,3)"]);
+
+        schema.row_address = RowAddress::None;
+        assert!(matches!(
+            primary_key_columns(&schema, &["(0,3)".to_string()]),
+            Err(DatabaseError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_row_key() {
+        let schema = TableSchema {
+            name: "translations".to_string(),
+            columns: Vec::new(),
+            primary_key: Some(vec!["id".to_string(), "lang".to_string()]),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: RowAddress::PrimaryKey,
+        };
+        let columns = [
+            ("id".to_string(), serde_json::json!(7)),
+            ("lang".to_string(), serde_json::json!("en")),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            resolve_row_key(&schema, &RowKey::Segment("7,en".to_string())).unwrap(),
+            vec!["7", "en"]
+        );
+        assert_eq!(
+            resolve_row_key(&schema, &RowKey::Columns(columns)).unwrap(),
+            vec!["7", "en"]
+        );
+        assert!(resolve_row_key(&schema, &RowKey::Segment("7".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_display_column() {
+        let column = |name: &str, data_type: &str, is_primary_key: bool| ColumnInfo {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable: false,
+            default_value: None,
+            is_primary_key,
+        };
+        let mut schema = TableSchema {
+            name: "users".to_string(),
+            columns: vec![
+                column("id", "INTEGER", true),
+                column("bio", "TEXT", false),
+                column("Email", "VARCHAR(255)", false),
+            ],
+            primary_key: Some(vec!["id".to_string()]),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: RowAddress::PrimaryKey,
+        };
+        assert_eq!(display_column(&schema), Some("Email"));
+
+        schema.columns.remove(2);
+        assert_eq!(display_column(&schema), Some("bio"));
+
+        schema.columns.remove(1);
+        assert_eq!(display_column(&schema), None);
+    }
+
+    #[test]
+    fn test_encode_path_segment() {
+        assert_eq!(encode_path_segment("users"), "users");
+        assert_eq!(encode_path_segment("order items/2"), "order%20items%2F2");
+        assert_eq!(encode_path_segment(r"a\,b"), "a%5C%2Cb");
+        assert_eq!(encode_path_segment("ü"), "%C3%BC");
+    }
+
+    #[test]
+    fn test_validate_new_rows() {
+        let column =
+            |name: &str, nullable: bool, default_value: Option<&str>, is_primary_key| ColumnInfo {
+                name: name.to_string(),
+                data_type: "TEXT".to_string(),
+                nullable,
+                default_value: default_value.map(str::to_string),
+                is_primary_key,
+            };
+        let schema = TableSchema {
+            name: "users".to_string(),
+            columns: vec![
+                column("id", false, None, true),
+                column("email", false, None, false),
+                column("role", false, Some("'member'"), false),
+                column("nickname", true, None, false),
+            ],
+            primary_key: Some(vec!["id".to_string()]),
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: RowAddress::PrimaryKey,
+        };
+        let row = |value: serde_json::Value| value.as_object().unwrap().clone();
+
+        assert!(validate_new_rows(
+            &schema,
+            &[
+                row(serde_json::json!({ "email": "a@example.com" })),
+                row(serde_json::json!({ "id": 2, "email": "b@example.com", "nickname": null })),
+            ]
+        )
+        .is_ok());
+
+        let error = validate_new_rows(
+            &schema,
+            &[
+                row(serde_json::json!({ "email": "a@example.com" })),
+                row(serde_json::json!({ "nickname": "b" })),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid row 1: column 'email' is required"
+        );
+        assert!(matches!(
+            validate_new_rows(
+                &schema,
+                &[row(serde_json::json!({ "email": "a", "role": null }))]
+            ),
+            Err(DatabaseError::InvalidRow { index: 0, .. })
+        ));
+        assert!(matches!(
+            validate_new_rows(
+                &schema,
+                &[row(serde_json::json!({ "email": "a", "age": 3 }))]
+            ),
+            Err(DatabaseError::InvalidRow { index: 0, .. })
+        ));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_validate_new_rows_of_sqlite_table() {
+        use crate::database::sqlite::SqliteProvider;
+        use crate::database::traits::DatabaseProvider;
+
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, \
+             email TEXT NOT NULL, active BOOLEAN NOT NULL DEFAULT 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let database = SqliteProvider::new(pool);
+        let row = |value: serde_json::Value| value.as_object().unwrap().clone();
+
+        // Rejected before the database reports a NOT NULL constraint failure
+        let missing_email = [row(serde_json::json!({ "name": "x" }))];
+        let error = database.insert_rows("users", &missing_email).await;
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "Invalid row 0: column 'email' is required"
+        );
+
+        let defaulted = [row(
+            serde_json::json!({ "name": "x", "email": "x@example.com" }),
+        )];
+        let inserted = database.insert_rows("users", &defaulted).await.unwrap();
+        assert_eq!(inserted.inserted, 1);
+    }
+}
@@ -0,0 +1,244 @@
+//! Async runtime abstraction
+//!
+//! Timers, background tasks and file access used by the API layer go through
+//! this module instead of calling tokio directly. With the `runtime-tokio`
+//! feature (enabled by default) they are backed by tokio. Without it, timers
+//! and background tasks are handed to a [`Runtime`] installed with
+//! [`set_runtime`], so the API layer can be driven by another executor (e.g.
+//! `wasm-bindgen-futures` with a provider backed by sql.js or OPFS). Using a
+//! timer or spawning a task before a runtime is installed panics.
+//!
+//! Without `runtime-tokio`, file access is blocking (and reports an error where
+//! the target has no filesystem).
+
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(not(feature = "runtime-tokio"))]
+use futures_util::future::{self, BoxFuture, Either};
+#[cfg(not(feature = "runtime-tokio"))]
+use std::sync::OnceLock;
+
+/// Executor running timers and background tasks when tokio is not used
+#[cfg(not(feature = "runtime-tokio"))]
+pub trait Runtime: Send + Sync + 'static {
+    /// Run `future` in the background, without waiting for it
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+
+    /// A future completing once `duration` has passed
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+#[cfg(not(feature = "runtime-tokio"))]
+static RUNTIME: OnceLock<Box<dyn Runtime>> = OnceLock::new();
+
+/// Install the runtime used for timers and background tasks
+///
+/// Must be called before the layer is built. Returns the runtime back if one
+/// was already installed.
+#[cfg(not(feature = "runtime-tokio"))]
+pub fn set_runtime<R: Runtime>(runtime: R) -> Result<(), Box<dyn Runtime>> {
+    RUNTIME.set(Box::new(runtime))
+}
+
+/// The installed runtime
+#[cfg(not(feature = "runtime-tokio"))]
+fn installed() -> &'static dyn Runtime {
+    RUNTIME.get().map(Box::as_ref).expect(
+        "no async runtime installed: enable the `runtime-tokio` feature \
+         or call `axum_sql_viewer::runtime::set_runtime` first",
+    )
+}
+
+/// Error returned by [`timeout`] when the duration elapsed before the future completed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+/// Run a future, giving up once `duration` has elapsed
+#[cfg(feature = "runtime-tokio")]
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    tokio::time::timeout(duration, future)
+        .await
+        .map_err(|_| Elapsed)
+}
+
+/// Run a future, giving up once `duration` has elapsed
+#[cfg(not(feature = "runtime-tokio"))]
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    let future = std::pin::pin!(future);
+    match future::select(future, installed().sleep(duration)).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(Elapsed),
+    }
+}
+
+/// Run a future in the background
+#[cfg(feature = "runtime-tokio")]
+pub async fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(future);
+}
+
+/// Run a future in the background
+#[cfg(not(feature = "runtime-tokio"))]
+pub async fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    installed().spawn(Box::pin(future));
+}
+
+/// Wait for `duration` to pass
+#[cfg(feature = "runtime-tokio")]
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Wait for `duration` to pass
+#[cfg(not(feature = "runtime-tokio"))]
+pub async fn sleep(duration: Duration) {
+    installed().sleep(duration).await;
+}
+
+/// Start a future in the background without waiting for it
+///
+/// Can be called outside of async code. The future is dropped if no tokio
+/// runtime is running on the current thread.
+#[cfg(feature = "runtime-tokio")]
+pub fn spawn_detached<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        handle.spawn(future);
+    }
+}
+
+/// Start a future in the background without waiting for it
+///
+/// Can be called outside of async code.
+#[cfg(not(feature = "runtime-tokio"))]
+pub fn spawn_detached<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    installed().spawn(Box::pin(future));
+}
+
+/// Read the entire contents of a file
+#[cfg(feature = "runtime-tokio")]
+pub async fn read_file(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    tokio::fs::read(path).await
+}
+
+/// Read the entire contents of a file
+#[cfg(not(feature = "runtime-tokio"))]
+pub async fn read_file(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    std::fs::read(path)
+}
+
+/// Write `contents` to a file, replacing it if it exists
+#[cfg(feature = "runtime-tokio")]
+pub async fn write_file(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    tokio::fs::write(path, contents).await
+}
+
+/// Write `contents` to a file, replacing it if it exists
+#[cfg(not(feature = "runtime-tokio"))]
+pub async fn write_file(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    std::fs::write(path, contents)
+}
+
+/// Create a directory and all of its missing parents
+#[cfg(feature = "runtime-tokio")]
+pub async fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    tokio::fs::create_dir_all(path).await
+}
+
+/// Create a directory and all of its missing parents
+#[cfg(not(feature = "runtime-tokio"))]
+pub async fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    std::fs::create_dir_all(path)
+}
+
+/// Remove a file
+#[cfg(feature = "runtime-tokio")]
+pub async fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    tokio::fs::remove_file(path).await
+}
+
+/// Remove a file
+#[cfg(not(feature = "runtime-tokio"))]
+pub async fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    std::fs::remove_file(path)
+}
+
+/// Append `contents` to a file, creating it if it does not exist
+#[cfg(feature = "runtime-tokio")]
+pub async fn append_file(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(contents.as_ref()).await?;
+    file.flush().await
+}
+
+/// Append `contents` to a file, creating it if it does not exist
+#[cfg(not(feature = "runtime-tokio"))]
+pub async fn append_file(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    use std::io::Write;
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(contents.as_ref())
+}
+
+/// Read `length` bytes of a file starting at `offset`
+#[cfg(feature = "runtime-tokio")]
+pub async fn read_file_range(
+    path: impl AsRef<Path>,
+    offset: u64,
+    length: u64,
+) -> io::Result<Vec<u8>> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(io::SeekFrom::Start(offset)).await?;
+    let mut buffer = vec![0; length as usize];
+    file.read_exact(&mut buffer).await?;
+    Ok(buffer)
+}
+
+/// Read `length` bytes of a file starting at `offset`
+#[cfg(not(feature = "runtime-tokio"))]
+pub async fn read_file_range(
+    path: impl AsRef<Path>,
+    offset: u64,
+    length: u64,
+) -> io::Result<Vec<u8>> {
+    use std::io::{Read, Seek};
+
+    let mut file = std::fs::File::open(path)?;
+    file.seek(io::SeekFrom::Start(offset))?;
+    let mut buffer = vec![0; length as usize];
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
@@ -0,0 +1,77 @@
+//! Random samples of table rows
+//!
+//! A sample shows a representative slice of a large table without paging
+//! through it. Small tables are sampled with `ORDER BY random()`, which reads
+//! and sorts every row. Large PostgreSQL tables are first thinned out with
+//! `TABLESAMPLE BERNOULLI`, which keeps each row with the same probability
+//! while reading far fewer rows into the sort.
+
+use serde::{Deserialize, Serialize};
+
+/// Number of rows sampled when not given
+pub const DEFAULT_SAMPLE_SIZE: u64 = 100;
+
+/// Estimated row count from which PostgreSQL tables are sampled with `TABLESAMPLE`
+pub const TABLESAMPLE_THRESHOLD: u64 = 100_000;
+
+/// How many times the requested rows `TABLESAMPLE` keeps on average, so that
+/// few samples come up short
+const OVERSAMPLING: f64 = 4.0;
+
+/// How the rows of a sample were picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SampleMethod {
+    /// `ORDER BY random()` over all rows
+    Random,
+
+    /// `TABLESAMPLE BERNOULLI` and then `ORDER BY random()` over the kept rows
+    TableSample,
+}
+
+/// Randomly picked rows of a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampleResponse {
+    /// The sampled rows, in random order
+    pub rows: Vec<serde_json::Value>,
+
+    /// Column names in the result
+    pub columns: Vec<String>,
+
+    /// Number of rows asked for (after the page size limit); fewer are
+    /// returned when the table is smaller
+    pub size: u64,
+
+    /// How the rows were picked
+    pub method: SampleMethod,
+}
+
+/// Percentage of rows `TABLESAMPLE BERNOULLI` keeps to sample `size` rows, if it is worth it
+///
+/// `estimate` is the planner's row count (negative before the table was
+/// first analyzed). Tables below [`TABLESAMPLE_THRESHOLD`] rows, or with no
+/// estimate, are sampled over all their rows instead.
+pub fn tablesample_percentage(size: u64, estimate: i64) -> Option<f64> {
+    let estimate = u64::try_from(estimate).ok()?;
+    if estimate < TABLESAMPLE_THRESHOLD || size == 0 {
+        return None;
+    }
+
+    let percentage = size as f64 * OVERSAMPLING * 100.0 / estimate as f64;
+    (percentage < 100.0).then_some(percentage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tablesample_percentage() {
+        assert_eq!(tablesample_percentage(100, 1_000_000), Some(0.04));
+        assert_eq!(tablesample_percentage(100, 99_999), None);
+        assert_eq!(tablesample_percentage(100, -1), None);
+        assert_eq!(tablesample_percentage(0, 1_000_000), None);
+        assert_eq!(tablesample_percentage(50_000, 150_000), None);
+    }
+}
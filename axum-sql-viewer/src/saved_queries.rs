@@ -0,0 +1,658 @@
+//! Saved queries
+//!
+//! Queries saved from the console with a name, an optional description and the
+//! parameter values to run them with. Tags and a favorite flag help finding
+//! them among many, and the list can be filtered by either. By default they are kept in memory,
+//! which survives reloading the page but not restarting the server. Building
+//! the layer with [`with_saved_queries_file`] keeps them in a JSON file, and
+//! [`with_saved_queries_table`] in a table the viewer creates in the database
+//! ([`SAVED_QUERIES_TABLE`]).
+//!
+//! [`with_saved_queries_file`]: crate::SqlViewerLayer::with_saved_queries_file
+//! [`with_saved_queries_table`]: crate::SqlViewerLayer::with_saved_queries_table
+
+use futures_util::lock::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::runtime;
+use crate::schema::{QueryParams, QueryResult};
+
+/// Table the queries are kept in with [`SavedQueryStorage::Table`]
+pub const SAVED_QUERIES_TABLE: &str = "_sql_viewer_saved_queries";
+
+/// A saved query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedQuery {
+    /// Identifier of the saved query
+    pub id: String,
+
+    /// Name shown in the list of saved queries
+    pub name: String,
+
+    /// SQL text
+    pub sql: String,
+
+    /// What the query is for
+    pub description: Option<String>,
+
+    /// Values to bind to the query's placeholders when it is run
+    #[serde(default)]
+    pub parameters: QueryParams,
+
+    /// Tags to group the query by
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Whether the query is listed among the favorites
+    #[serde(default)]
+    pub favorite: bool,
+
+    /// Time the query was saved, in milliseconds since the Unix epoch
+    pub created_at_milliseconds: u64,
+
+    /// Time the query was last changed, in milliseconds since the Unix epoch
+    pub updated_at_milliseconds: u64,
+}
+
+/// Request body for creating or replacing a saved query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedQueryRequest {
+    /// Name shown in the list of saved queries
+    pub name: String,
+
+    /// SQL text
+    pub sql: String,
+
+    /// What the query is for
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Values to bind to the query's placeholders when it is run
+    #[serde(default)]
+    pub parameters: QueryParams,
+
+    /// Tags to group the query by
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Whether the query is listed among the favorites
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+/// Query parameters for listing saved queries
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedQueryFilter {
+    /// Only list queries with this tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+
+    /// Only list favorites (`true`) or queries that are not (`false`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favorite: Option<bool>,
+}
+
+impl SavedQueryFilter {
+    /// Whether `query` is listed with this filter
+    pub fn matches(&self, query: &SavedQuery) -> bool {
+        let tagged = match self.tag.as_deref().map(str::trim) {
+            Some(tag) if !tag.is_empty() => query.tags.iter().any(|candidate| candidate == tag),
+            _ => true,
+        };
+        tagged
+            && self
+                .favorite
+                .is_none_or(|favorite| query.favorite == favorite)
+    }
+}
+
+/// Response listing the saved queries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedQueriesResponse {
+    /// Saved queries, favorites first and then by name
+    pub queries: Vec<SavedQuery>,
+}
+
+/// Where saved queries are kept
+#[derive(Debug, Clone, Default)]
+pub enum SavedQueryStorage {
+    /// In memory, until the server restarts
+    #[default]
+    Memory,
+
+    /// In a JSON file, read on first use and rewritten on every change
+    File(PathBuf),
+
+    /// In [`SAVED_QUERIES_TABLE`], created on first use
+    Table,
+}
+
+/// Error from reading or changing saved queries
+#[derive(Debug, Error)]
+pub enum SavedQueryError {
+    /// No saved query with the identifier exists
+    #[error("Saved query not found: {0}")]
+    NotFound(String),
+
+    /// The name or SQL is missing
+    #[error("Invalid saved query: {0}")]
+    Invalid(String),
+
+    /// The file could not be read or written
+    #[error("Failed to store saved queries: {0}")]
+    Storage(String),
+
+    /// The table could not be read or written
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+}
+
+/// Saved queries and where they are kept
+///
+/// Cloning `SavedQueries` is cheap; all clones share the same queries.
+#[derive(Clone, Default)]
+pub struct SavedQueries {
+    storage: Arc<SavedQueryStorage>,
+    /// Queries kept in memory or read from the file (`None` until first use)
+    /// and, for the table, whether it was created; held while the file is
+    /// written so changes are not written out of order
+    state: Arc<Mutex<StoredQueries>>,
+    sequence: Arc<AtomicU64>,
+}
+
+#[derive(Default)]
+struct StoredQueries {
+    queries: Option<Vec<SavedQuery>>,
+    table_created: bool,
+}
+
+impl SavedQueries {
+    /// Create the store for `storage`
+    pub fn new(storage: SavedQueryStorage) -> Self {
+        Self {
+            storage: Arc::new(storage),
+            ..Self::default()
+        }
+    }
+
+    /// Saved queries passing `filter`, favorites first and then by name
+    pub async fn list<DB: DatabaseProvider>(
+        &self,
+        database: &DB,
+        filter: &SavedQueryFilter,
+    ) -> Result<Vec<SavedQuery>, SavedQueryError> {
+        let mut state = self.state.lock().await;
+        let mut queries = match self.storage.as_ref() {
+            SavedQueryStorage::Table => {
+                self.create_table(&mut state, database).await?;
+                let sql = format!("SELECT * FROM {}", SAVED_QUERIES_TABLE);
+                let result = run(database, &sql, json!({})).await?;
+                result
+                    .rows
+                    .iter()
+                    .map(saved_query_from_row)
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            _ => self.load(&mut state).await?.clone(),
+        };
+        queries.retain(|query| filter.matches(query));
+        queries.sort_by(|first, second| {
+            (!first.favorite, &first.name).cmp(&(!second.favorite, &second.name))
+        });
+        Ok(queries)
+    }
+
+    /// Save a new query
+    pub async fn create<DB: DatabaseProvider>(
+        &self,
+        database: &DB,
+        request: SavedQueryRequest,
+    ) -> Result<SavedQuery, SavedQueryError> {
+        validate(&request)?;
+        let now = now_milliseconds();
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let query = SavedQuery {
+            id: format!("{:x}-{}", now, sequence),
+            name: request.name,
+            sql: request.sql,
+            description: request.description,
+            parameters: request.parameters,
+            tags: normalize_tags(request.tags),
+            favorite: request.favorite,
+            created_at_milliseconds: now,
+            updated_at_milliseconds: now,
+        };
+
+        let mut state = self.state.lock().await;
+        match self.storage.as_ref() {
+            SavedQueryStorage::Table => {
+                self.create_table(&mut state, database).await?;
+                let sql = format!(
+                    "INSERT INTO {} (id, name, sql, description, parameters, tags, favorite, \
+                     created_at_milliseconds, updated_at_milliseconds) \
+                     VALUES (:id, :name, :sql, :description, :parameters, :tags, :favorite, \
+                     :created, :updated)",
+                    SAVED_QUERIES_TABLE
+                );
+                run(database, &sql, table_values(&query)?).await?;
+            }
+            _ => {
+                self.load(&mut state).await?.push(query.clone());
+                self.save(&state).await?;
+            }
+        }
+        Ok(query)
+    }
+
+    /// Replace the name, SQL, description, parameters, tags and favorite flag
+    /// of a saved query
+    pub async fn update<DB: DatabaseProvider>(
+        &self,
+        database: &DB,
+        id: &str,
+        request: SavedQueryRequest,
+    ) -> Result<SavedQuery, SavedQueryError> {
+        validate(&request)?;
+        let mut state = self.state.lock().await;
+        let mut query = self
+            .find(&mut state, database, id)
+            .await?
+            .ok_or_else(|| SavedQueryError::NotFound(id.to_string()))?;
+        query.name = request.name;
+        query.sql = request.sql;
+        query.description = request.description;
+        query.parameters = request.parameters;
+        query.tags = normalize_tags(request.tags);
+        query.favorite = request.favorite;
+        query.updated_at_milliseconds = now_milliseconds();
+
+        match self.storage.as_ref() {
+            SavedQueryStorage::Table => {
+                let sql = format!(
+                    "UPDATE {} SET name = :name, sql = :sql, description = :description, \
+                     parameters = :parameters, tags = :tags, favorite = :favorite, \
+                     updated_at_milliseconds = :updated WHERE id = :id",
+                    SAVED_QUERIES_TABLE
+                );
+                run(database, &sql, table_values(&query)?).await?;
+            }
+            _ => {
+                let queries = self.load(&mut state).await?;
+                if let Some(stored) = queries.iter_mut().find(|stored| stored.id == id) {
+                    *stored = query.clone();
+                }
+                self.save(&state).await?;
+            }
+        }
+        Ok(query)
+    }
+
+    /// Delete a saved query
+    pub async fn delete<DB: DatabaseProvider>(
+        &self,
+        database: &DB,
+        id: &str,
+    ) -> Result<(), SavedQueryError> {
+        let mut state = self.state.lock().await;
+        if self.find(&mut state, database, id).await?.is_none() {
+            return Err(SavedQueryError::NotFound(id.to_string()));
+        }
+
+        match self.storage.as_ref() {
+            SavedQueryStorage::Table => {
+                let sql = format!("DELETE FROM {} WHERE id = :id", SAVED_QUERIES_TABLE);
+                run(database, &sql, json!({ "id": id })).await?;
+            }
+            _ => {
+                self.load(&mut state).await?.retain(|query| query.id != id);
+                self.save(&state).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up a saved query by identifier
+    async fn find<DB: DatabaseProvider>(
+        &self,
+        state: &mut StoredQueries,
+        database: &DB,
+        id: &str,
+    ) -> Result<Option<SavedQuery>, SavedQueryError> {
+        match self.storage.as_ref() {
+            SavedQueryStorage::Table => {
+                self.create_table(state, database).await?;
+                let sql = format!("SELECT * FROM {} WHERE id = :id", SAVED_QUERIES_TABLE);
+                let result = run(database, &sql, json!({ "id": id })).await?;
+                result.rows.first().map(saved_query_from_row).transpose()
+            }
+            _ => Ok(self
+                .load(state)
+                .await?
+                .iter()
+                .find(|query| query.id == id)
+                .cloned()),
+        }
+    }
+
+    /// Queries kept in memory, reading the file on first use
+    async fn load<'a>(
+        &self,
+        state: &'a mut StoredQueries,
+    ) -> Result<&'a mut Vec<SavedQuery>, SavedQueryError> {
+        if state.queries.is_none() {
+            let queries = match self.storage.as_ref() {
+                SavedQueryStorage::File(path) => match runtime::read_file(path).await {
+                    Ok(contents) => serde_json::from_slice(&contents).map_err(|error| {
+                        SavedQueryError::Storage(format!("{}: {}", path.display(), error))
+                    })?,
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                    Err(error) => {
+                        return Err(SavedQueryError::Storage(format!(
+                            "{}: {}",
+                            path.display(),
+                            error
+                        )))
+                    }
+                },
+                _ => Vec::new(),
+            };
+            state.queries = Some(queries);
+        }
+        Ok(state.queries.get_or_insert_with(Vec::new))
+    }
+
+    /// Write the queries to the file, if they are kept in one
+    async fn save(&self, state: &StoredQueries) -> Result<(), SavedQueryError> {
+        let (SavedQueryStorage::File(path), Some(queries)) =
+            (self.storage.as_ref(), state.queries.as_ref())
+        else {
+            return Ok(());
+        };
+
+        let contents = serde_json::to_vec_pretty(queries)
+            .map_err(|error| SavedQueryError::Storage(error.to_string()))?;
+        runtime::write_file(path, contents)
+            .await
+            .map_err(|error| SavedQueryError::Storage(format!("{}: {}", path.display(), error)))
+    }
+
+    /// Create the table of saved queries unless that was done before
+    ///
+    /// A table created before queries had tags and a favorite flag gets the
+    /// missing columns added.
+    async fn create_table<DB: DatabaseProvider>(
+        &self,
+        state: &mut StoredQueries,
+        database: &DB,
+    ) -> Result<(), SavedQueryError> {
+        if state.table_created {
+            return Ok(());
+        }
+
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (\
+             id TEXT PRIMARY KEY, \
+             name TEXT NOT NULL, \
+             sql TEXT NOT NULL, \
+             description TEXT, \
+             parameters TEXT NOT NULL, \
+             tags TEXT NOT NULL DEFAULT '[]', \
+             favorite INTEGER NOT NULL DEFAULT 0, \
+             created_at_milliseconds BIGINT NOT NULL, \
+             updated_at_milliseconds BIGINT NOT NULL)",
+            SAVED_QUERIES_TABLE
+        );
+        run(database, &sql, json!({})).await?;
+
+        let schema = database.get_table_schema(SAVED_QUERIES_TABLE).await?;
+        for (column, definition) in [
+            ("tags", "TEXT NOT NULL DEFAULT '[]'"),
+            ("favorite", "INTEGER NOT NULL DEFAULT 0"),
+        ] {
+            let exists = schema
+                .columns
+                .iter()
+                .any(|existing| existing.name == column);
+            if !exists {
+                let sql = format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    SAVED_QUERIES_TABLE, column, definition
+                );
+                run(database, &sql, json!({})).await?;
+            }
+        }
+        state.table_created = true;
+        Ok(())
+    }
+}
+
+/// Check that a saved query has a name and SQL
+fn validate(request: &SavedQueryRequest) -> Result<(), SavedQueryError> {
+    if request.name.trim().is_empty() {
+        return Err(SavedQueryError::Invalid(
+            "name must not be empty".to_string(),
+        ));
+    }
+    if request.sql.trim().is_empty() {
+        return Err(SavedQueryError::Invalid(
+            "sql must not be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Trim tags and drop empty and repeated ones
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|existing| existing == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+/// Run a statement on the table of saved queries with named parameters
+async fn run<DB: DatabaseProvider>(
+    database: &DB,
+    sql: &str,
+    parameters: Value,
+) -> Result<QueryResult, SavedQueryError> {
+    let parameters: QueryParams = serde_json::from_value(parameters)
+        .map_err(|error| SavedQueryError::Storage(error.to_string()))?;
+    let (sql, values) = parameters
+        .bind(sql, database.dialect())
+        .map_err(SavedQueryError::Storage)?;
+
+    let result = database.execute_query(&sql, &values).await?;
+    match result.error {
+        Some(error) => Err(DatabaseError::Query(error).into()),
+        None => Ok(result),
+    }
+}
+
+/// Named parameters for the columns of a saved query
+fn table_values(query: &SavedQuery) -> Result<Value, SavedQueryError> {
+    let parameters = serde_json::to_string(&query.parameters)
+        .map_err(|error| SavedQueryError::Storage(error.to_string()))?;
+    let tags = serde_json::to_string(&query.tags)
+        .map_err(|error| SavedQueryError::Storage(error.to_string()))?;
+    Ok(json!({
+        "id": query.id,
+        "name": query.name,
+        "sql": query.sql,
+        "description": query.description,
+        "parameters": parameters,
+        "tags": tags,
+        "favorite": i64::from(query.favorite),
+        "created": query.created_at_milliseconds,
+        "updated": query.updated_at_milliseconds,
+    }))
+}
+
+/// Read a saved query from a row of the table
+fn saved_query_from_row(row: &Value) -> Result<SavedQuery, SavedQueryError> {
+    let text = |column: &str| row.get(column).and_then(Value::as_str).map(str::to_string);
+    let milliseconds = |column: &str| row.get(column).and_then(Value::as_u64).unwrap_or(0);
+    let parameters = match text("parameters") {
+        Some(parameters) => serde_json::from_str(&parameters)
+            .map_err(|error| SavedQueryError::Storage(error.to_string()))?,
+        None => QueryParams::default(),
+    };
+    let tags = match text("tags") {
+        Some(tags) => serde_json::from_str(&tags)
+            .map_err(|error| SavedQueryError::Storage(error.to_string()))?,
+        None => Vec::new(),
+    };
+    // Stored as 0 or 1, but a database may report it as a boolean
+    let favorite = match row.get("favorite") {
+        Some(Value::Bool(favorite)) => *favorite,
+        Some(value) => value.as_i64().unwrap_or(0) != 0,
+        None => false,
+    };
+
+    Ok(SavedQuery {
+        id: text("id").unwrap_or_default(),
+        name: text("name").unwrap_or_default(),
+        sql: text("sql").unwrap_or_default(),
+        description: text("description"),
+        parameters,
+        tags,
+        favorite,
+        created_at_milliseconds: milliseconds("created_at_milliseconds"),
+        updated_at_milliseconds: milliseconds("updated_at_milliseconds"),
+    })
+}
+
+fn now_milliseconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_query_from_row() {
+        let row = json!({
+            "id": "18c-1",
+            "name": "Active users",
+            "sql": "SELECT * FROM users WHERE active = :active",
+            "description": null,
+            "parameters": "{\"active\":true}",
+            "created_at_milliseconds": 1700000000000u64,
+            "updated_at_milliseconds": 1700000005000u64,
+        });
+
+        let query = saved_query_from_row(&row).unwrap();
+        assert_eq!(query.name, "Active users");
+        assert_eq!(query.description, None);
+        assert!(matches!(
+            query.parameters,
+            QueryParams::Named(ref values) if values["active"] == json!(true)
+        ));
+        assert!(query.tags.is_empty());
+        assert!(!query.favorite);
+        assert_eq!(query.updated_at_milliseconds, 1700000005000);
+    }
+
+    #[test]
+    fn test_filter_by_tag_and_favorite() {
+        let query = SavedQuery {
+            id: "18c-3".to_string(),
+            name: "Monthly revenue".to_string(),
+            sql: "SELECT 1".to_string(),
+            description: None,
+            parameters: QueryParams::default(),
+            tags: normalize_tags(vec![
+                " reports ".to_string(),
+                "finance".to_string(),
+                "reports".to_string(),
+                String::new(),
+            ]),
+            favorite: false,
+            created_at_milliseconds: 1,
+            updated_at_milliseconds: 1,
+        };
+        assert_eq!(query.tags, vec!["reports", "finance"]);
+
+        let filter = |tag: Option<&str>, favorite: Option<bool>| SavedQueryFilter {
+            tag: tag.map(str::to_string),
+            favorite,
+        };
+        assert!(filter(None, None).matches(&query));
+        assert!(filter(Some("reports"), None).matches(&query));
+        assert!(filter(Some(""), None).matches(&query));
+        assert!(!filter(Some("users"), None).matches(&query));
+        assert!(filter(Some("finance"), Some(false)).matches(&query));
+        assert!(!filter(None, Some(true)).matches(&query));
+    }
+
+    #[test]
+    fn test_table_values_round_trip() {
+        let query = SavedQuery {
+            id: "18c-2".to_string(),
+            name: "By email".to_string(),
+            sql: "SELECT * FROM users WHERE email = ?".to_string(),
+            description: Some("Look up a user".to_string()),
+            parameters: QueryParams::Positional(vec![json!("alice@example.com")]),
+            tags: vec!["users".to_string(), "support".to_string()],
+            favorite: true,
+            created_at_milliseconds: 1,
+            updated_at_milliseconds: 2,
+        };
+
+        let mut row = table_values(&query).unwrap();
+        row["created_at_milliseconds"] = row["created"].clone();
+        row["updated_at_milliseconds"] = row["updated"].clone();
+        let read = saved_query_from_row(&row).unwrap();
+        assert_eq!(read.description.as_deref(), Some("Look up a user"));
+        assert!(
+            matches!(read.parameters, QueryParams::Positional(ref values) if values.len() == 1)
+        );
+        assert_eq!(read.tags, vec!["users", "support"]);
+        assert!(read.favorite);
+        assert_eq!(read.created_at_milliseconds, 1);
+    }
+
+    #[test]
+    fn test_validate_requires_name_and_sql() {
+        let request = SavedQueryRequest {
+            name: " ".to_string(),
+            sql: "SELECT 1".to_string(),
+            description: None,
+            parameters: QueryParams::default(),
+            tags: Vec::new(),
+            favorite: false,
+        };
+        assert!(matches!(
+            validate(&request),
+            Err(SavedQueryError::Invalid(_))
+        ));
+
+        let request = SavedQueryRequest {
+            name: "One".to_string(),
+            sql: String::new(),
+            ..request
+        };
+        assert!(matches!(
+            validate(&request),
+            Err(SavedQueryError::Invalid(_))
+        ));
+    }
+}
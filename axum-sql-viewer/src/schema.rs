@@ -3,9 +3,10 @@
 //! These types represent database schema information discovered at runtime.
 
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
 /// Complete schema information for a database table
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TableSchema {
     /// Name of the table
@@ -22,10 +23,51 @@ pub struct TableSchema {
 
     /// Index definitions
     pub indexes: Vec<IndexInfo>,
+
+    /// How single rows are addressed for detail views and edits
+    #[serde(default)]
+    pub row_address: RowAddress,
+}
+
+/// How single rows of a table are addressed
+///
+/// Tables without a primary key fall back to a row identifier of the database
+/// where one exists; its value is included in every row read as an extra
+/// column named after it (`rowid` or `ctid`) and is used as the row key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RowAddress {
+    /// By the values of the primary key columns
+    #[default]
+    PrimaryKey,
+
+    /// By SQLite's `rowid` (stable, except that `VACUUM` may renumber it)
+    Rowid,
+
+    /// By PostgreSQL's `ctid`, the physical location of the row version
+    ///
+    /// The `ctid` changes whenever the row is updated, so an edit based on a
+    /// stale read finds no row instead of changing another one; the row
+    /// returned by an update carries the new `ctid`.
+    Ctid,
+
+    /// Rows cannot be addressed (e.g. views); single-row reads and edits are disabled
+    None,
+}
+
+impl RowAddress {
+    /// Name of the row identifier column, for tables without a primary key
+    pub fn identifier_column(self) -> Option<&'static str> {
+        match self {
+            RowAddress::Rowid => Some("rowid"),
+            RowAddress::Ctid => Some("ctid"),
+            RowAddress::PrimaryKey | RowAddress::None => None,
+        }
+    }
 }
 
 /// Information about a single column
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ColumnInfo {
     /// Column name
@@ -45,7 +87,7 @@ pub struct ColumnInfo {
 }
 
 /// Foreign key constraint information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ForeignKey {
     /// Column name in this table
@@ -59,7 +101,7 @@ pub struct ForeignKey {
 }
 
 /// Index information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IndexInfo {
     /// Index name
@@ -81,10 +123,80 @@ pub struct TableInfo {
 
     /// Approximate row count (if available)
     pub row_count: Option<u64>,
+
+    /// How and when the row count was taken (if there is one)
+    #[serde(flatten)]
+    pub row_count_accuracy: Option<CountAccuracy>,
 }
 
-/// Query parameters for fetching rows
-#[derive(Debug, Clone, Deserialize)]
+impl TableInfo {
+    /// Table with a row count counted exactly just now
+    pub fn counted(name: String, row_count: Option<u64>) -> Self {
+        Self {
+            name,
+            row_count,
+            row_count_accuracy: row_count.map(|_| CountAccuracy::exact()),
+        }
+    }
+
+    /// Table with a row count estimated just now (e.g. from planner statistics)
+    pub fn estimated(name: String, row_count: Option<u64>) -> Self {
+        Self {
+            name,
+            row_count,
+            row_count_accuracy: row_count.map(|_| CountAccuracy::estimated()),
+        }
+    }
+}
+
+/// How and when a row count was taken
+///
+/// Counts are exact when taken with `COUNT(*)` for the response they are in;
+/// estimates and counts kept from an earlier request (e.g. by the schema
+/// cache) are not, and `as_of_milliseconds` tells how old they are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountAccuracy {
+    /// Whether the count was taken exactly for this response
+    #[serde(default)]
+    pub exact: bool,
+
+    /// When the count was taken, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub as_of_milliseconds: u64,
+}
+
+impl CountAccuracy {
+    /// Accuracy of a count taken exactly just now
+    pub fn exact() -> Self {
+        Self {
+            exact: true,
+            as_of_milliseconds: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
+
+    /// Accuracy of an estimate read just now
+    pub fn estimated() -> Self {
+        Self {
+            exact: false,
+            ..Self::exact()
+        }
+    }
+
+    /// The same count, reused later (no longer exact)
+    pub fn reused(self) -> Self {
+        Self {
+            exact: false,
+            ..self
+        }
+    }
+}
+
+/// Query parameters (or POSTed body) for fetching rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RowQuery {
     /// Starting offset for pagination
@@ -101,15 +213,77 @@ pub struct RowQuery {
     /// Sort order
     pub sort_order: Option<SortOrder>,
 
+    /// Where rows with NULL in the sort column go (the database's default
+    /// when not set: last in ascending order on PostgreSQL, first on SQLite)
+    pub nulls: Option<NullsOrder>,
+
     /// Column filters (column_name -> filter_value)
     #[serde(default)]
     pub filters: std::collections::HashMap<String, String>,
+
+    /// Date and timestamp comparisons (`filter[column][gte]=...`), read by
+    /// the handlers with [`crate::filters::filter_parameters`]
+    #[serde(skip)]
+    pub ranges: Vec<crate::filters::RangeFilter>,
+
+    /// Groups of column filters of which at least one must match (each
+    /// group's filters all), e.g. `[{"status": "failed"}, {"status":
+    /// "cancelled"}]`; only in POSTed bodies
+    #[serde(default)]
+    pub filter_groups: Vec<std::collections::HashMap<String, String>>,
+
+    /// Whether filters match text ignoring case (`ILIKE` on PostgreSQL,
+    /// `COLLATE NOCASE` on SQLite)
+    #[serde(default)]
+    pub case_insensitive: bool,
+
+    /// Text to search for in all textual columns (see [`crate::search`])
+    pub q: Option<String>,
+
+    /// Snapshot to read from (see `POST /api/snapshots`)
+    pub snapshot: Option<String>,
+
+    /// Continue after the page that returned this cursor (`nextCursor`)
+    /// instead of skipping `offset` rows (see [`crate::keyset`])
+    pub cursor: Option<String>,
+
+    /// Whether to count the matching rows for `total` (`count=false` skips the
+    /// `COUNT(*)`, the slowest part of a page on large tables)
+    #[serde(default = "default_count")]
+    pub count: bool,
+
+    /// Columns to fetch, separated by commas (all columns when not set)
+    pub columns: Option<String>,
+}
+
+impl RowQuery {
+    /// Names of the columns to fetch, if only some were asked for
+    pub fn projection(&self) -> Option<Vec<String>> {
+        let columns: Vec<String> = self
+            .columns
+            .as_deref()?
+            .split(',')
+            .map(str::trim)
+            .filter(|column| !column.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!columns.is_empty()).then_some(columns)
+    }
+
+    /// Text searched for, if any was given
+    pub fn search(&self) -> Option<&str> {
+        self.q.as_deref().filter(|text| !text.is_empty())
+    }
 }
 
 fn default_limit() -> u64 {
     100
 }
 
+fn default_count() -> bool {
+    true
+}
+
 /// Sort order for row queries
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -118,6 +292,17 @@ pub enum SortOrder {
     Descending,
 }
 
+/// Position of NULLs of the sort column in row queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NullsOrder {
+    /// Rows with NULL come before all others
+    First,
+
+    /// Rows with NULL come after all others
+    Last,
+}
+
 /// Response containing table rows
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -128,8 +313,13 @@ pub struct RowsResponse {
     /// Column names in the result
     pub columns: Vec<String>,
 
-    /// Total number of rows in the table (with filters applied)
-    pub total: u64,
+    /// Total number of rows in the table (with filters applied), unless
+    /// requested with `count=false`
+    pub total: Option<u64>,
+
+    /// How and when `total` was counted (absent without `total`)
+    #[serde(flatten)]
+    pub total_accuracy: Option<CountAccuracy>,
 
     /// Current offset
     pub offset: u64,
@@ -139,6 +329,23 @@ pub struct RowsResponse {
 
     /// Whether there are more rows available
     pub has_more: bool,
+
+    /// Cursor reading the next page, for tables with a primary key or `rowid`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+
+    /// Labels of referenced rows by foreign key column and value (with `labels=true`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<crate::labels::ForeignKeyLabels>,
+}
+
+/// Options for row responses that are handled by the API layer
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowLabelsQuery {
+    /// Resolve foreign key values to labels of the referenced rows
+    #[serde(default)]
+    pub labels: bool,
 }
 
 /// Response from listing tables
@@ -149,12 +356,175 @@ pub struct TablesResponse {
     pub tables: Vec<TableInfo>,
 }
 
+/// Query parameters for listing tables
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TablesQuery {
+    /// Count the rows of every table with `COUNT(*)` instead of estimating
+    #[serde(default)]
+    pub exact: bool,
+}
+
 /// Request to execute a raw SQL query
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryRequest {
     /// SQL query to execute
     pub sql: String,
+
+    /// Values bound to the statement's placeholders
+    #[serde(default)]
+    pub params: QueryParams,
+
+    /// Identifier to cancel the query by while it runs (`/api/query` only)
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Token from a [`ConfirmationRequired`] response that allows running a
+    /// destructive statement
+    #[serde(default)]
+    pub confirmation: Option<String>,
+
+    /// Send the result as NDJSON lines while the rows are read, instead of
+    /// one JSON document (`/api/query` only)
+    #[serde(default)]
+    pub stream: bool,
+
+    /// Answer with the first `pageSize` rows and a handle to fetch the rest
+    /// from (`/api/query` only)
+    #[serde(default)]
+    pub page_size: Option<usize>,
+
+    /// Only check the statement, without running it (`/api/query` only)
+    #[serde(default)]
+    pub validate_only: bool,
+
+    /// Send the rows as a file to download in this format instead of JSON
+    /// (`/api/query` only)
+    #[serde(default)]
+    pub format: Option<ExportFormat>,
+}
+
+/// Result of checking a query sent with `validateOnly`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryValidation {
+    /// Whether the SQL is valid
+    pub valid: bool,
+
+    /// Whether the database checked every statement against the schema (by
+    /// `EXPLAIN`), rather than only the parser checking the syntax
+    pub explained: bool,
+
+    /// First problem found
+    pub error: Option<String>,
+
+    /// Line of the problem, starting at 1, if known
+    pub line: Option<u64>,
+
+    /// Column of the problem, starting at 1, if known
+    pub column: Option<u64>,
+}
+
+/// Values for the placeholders of a raw query
+///
+/// Either a JSON array bound to positional placeholders (`?`, `$1`) in order,
+/// or a JSON object bound to named ones (`:name`), which makes a saved query
+/// reusable with different inputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QueryParams {
+    Positional(Vec<serde_json::Value>),
+    Named(serde_json::Map<String, serde_json::Value>),
+}
+
+impl Default for QueryParams {
+    fn default() -> Self {
+        QueryParams::Positional(Vec::new())
+    }
+}
+
+impl QueryParams {
+    /// SQL to run and the values to bind to its placeholders in order
+    ///
+    /// Named parameters are substituted with the dialect's positional
+    /// placeholders (see [`crate::sql::bind_named_parameters`]).
+    ///
+    /// # Returns
+    ///
+    /// The statement and its values, or an error message for named parameters
+    /// that don't fit the statement
+    pub fn bind<'a>(
+        &'a self,
+        sql: &'a str,
+        dialect: crate::sql::Dialect,
+    ) -> Result<(Cow<'a, str>, Cow<'a, [serde_json::Value]>), String> {
+        match self {
+            QueryParams::Positional(values) => Ok((Cow::Borrowed(sql), Cow::Borrowed(values))),
+            QueryParams::Named(values) => {
+                let (sql, values) = crate::sql::bind_named_parameters(sql, values, dialect)?;
+                Ok((Cow::Owned(sql), Cow::Owned(values)))
+            }
+        }
+    }
+}
+
+/// Request to run a script of several statements in one transaction
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptRequest {
+    /// Statements separated by semicolons
+    pub sql: String,
+
+    /// Token from a [`ConfirmationRequired`] response that allows running a
+    /// script with a destructive statement
+    #[serde(default)]
+    pub confirmation: Option<String>,
+}
+
+/// Outcome of a script run in one transaction
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptResult {
+    /// Whether the transaction was committed (false if it was rolled back)
+    pub committed: bool,
+
+    /// Statements that ran successfully, in order
+    pub statements: Vec<ScriptStatement>,
+
+    /// Statement that failed and caused the rollback
+    pub failed_statement: Option<FailedStatement>,
+
+    /// Execution time of the whole script in milliseconds
+    pub execution_time_milliseconds: u64,
+}
+
+/// A statement of a script that ran successfully
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptStatement {
+    /// SQL of the statement
+    pub sql: String,
+
+    /// Number of rows affected (or returned, for queries)
+    pub affected_rows: u64,
+
+    /// Execution time in milliseconds
+    pub execution_time_milliseconds: u64,
+}
+
+/// The statement of a script that failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedStatement {
+    /// Position of the statement in the script, starting at 0
+    pub index: usize,
+
+    /// SQL of the statement
+    pub sql: String,
+
+    /// Error reported by the database
+    pub error: String,
 }
 
 /// Result from executing a query
@@ -175,6 +545,74 @@ pub struct QueryResult {
 
     /// Error message if the query failed
     pub error: Option<String>,
+
+    /// Which timeout fired, if the query timed out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<TimeoutKind>,
+}
+
+/// A page of a query result, see [`crate::cursors`]
+///
+/// Has the fields of a [`QueryResult`], so a client that reads the first page
+/// like a complete result keeps working.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryPage {
+    /// Handle to fetch more rows from with `/api/query/{id}/rows` (`None` if
+    /// the first page already holds every row)
+    pub result_id: Option<String>,
+
+    /// Column names in the result
+    pub columns: Vec<String>,
+
+    /// Rows of this page
+    pub rows: Vec<serde_json::Value>,
+
+    /// Position of the first row of this page in the result
+    pub offset: u64,
+
+    /// Whether rows follow this page
+    pub has_more: bool,
+
+    /// Always 0, since only queries returning rows are paged
+    pub affected_rows: u64,
+
+    /// Time spent reading this page in milliseconds
+    pub execution_time_milliseconds: u64,
+
+    /// Error that ended the result early, reported with its last page
+    pub error: Option<String>,
+}
+
+/// Query parameters for `/api/query/{id}/rows`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResultRowsQuery {
+    /// Position of the first row to return
+    #[serde(default)]
+    pub offset: usize,
+
+    /// Number of rows to return
+    pub limit: Option<usize>,
+}
+
+/// Limit hit by an operation that timed out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeoutKind {
+    /// No pool connection became free in time (the pool is saturated)
+    Acquire,
+
+    /// The statement did not finish in time
+    Statement,
+}
+
+impl std::fmt::Display for TimeoutKind {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeoutKind::Acquire => write!(formatter, "Connection acquire"),
+            TimeoutKind::Statement => write!(formatter, "Statement"),
+        }
+    }
 }
 
 /// Response for row count queries
@@ -183,4 +621,1279 @@ pub struct QueryResult {
 pub struct CountResponse {
     /// Total number of rows
     pub count: u64,
+
+    /// How and when the rows were counted
+    #[serde(flatten)]
+    pub accuracy: CountAccuracy,
+}
+
+impl CountResponse {
+    /// Count taken exactly just now
+    pub fn exact(count: u64) -> Self {
+        Self {
+            count,
+            accuracy: CountAccuracy::exact(),
+        }
+    }
+}
+
+/// Response containing a single row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowResponse {
+    /// The row, as returned in `RowsResponse::rows`
+    pub row: serde_json::Value,
+}
+
+/// Row referenced by a foreign key of another row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedRow {
+    /// Foreign key column in the row's table
+    pub column: String,
+
+    /// Referenced table
+    pub table: String,
+
+    /// Referenced column
+    pub references_column: String,
+
+    /// The referenced row (`None` if the foreign key is NULL or dangling)
+    pub row: Option<serde_json::Value>,
+}
+
+/// Rows referenced by a row through its foreign keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedRowsResponse {
+    /// One entry per foreign key of the table
+    pub related: Vec<RelatedRow>,
+}
+
+/// Query parameters for looking up referencing rows
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferencingRowsQuery {
+    /// Maximum number of rows returned per foreign key
+    #[serde(default = "default_referencing_limit")]
+    pub limit: u64,
+}
+
+fn default_referencing_limit() -> u64 {
+    20
+}
+
+/// Rows of another table referencing a row through one foreign key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferencingRows {
+    /// Referencing table
+    pub table: String,
+
+    /// Foreign key column in the referencing table
+    pub column: String,
+
+    /// Referenced column in the row's table
+    pub references_column: String,
+
+    /// Total number of referencing rows
+    pub count: u64,
+
+    /// Referencing rows (at most `limit`)
+    pub rows: Vec<serde_json::Value>,
+}
+
+/// Rows referencing a row, grouped by foreign key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferencingRowsResponse {
+    /// One entry per foreign key pointing at the row's table
+    pub referenced_by: Vec<ReferencingRows>,
+}
+
+/// Response from updating a row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRowResponse {
+    /// The row as stored after the update
+    pub row: serde_json::Value,
+}
+
+/// What to do with rows that are referenced by other rows when deleting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OnReferenced {
+    /// Delete nothing if any row is referenced
+    #[default]
+    Abort,
+
+    /// Delete only the rows that are not referenced
+    Skip,
+
+    /// Delete the referencing rows (recursively) as well
+    Cascade,
+}
+
+/// Primary key of a row in a request body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RowKey {
+    /// Values separated by commas, as in the `/rows/:key` path segment (`"7,en"`)
+    Segment(String),
+
+    /// Values by column name (`{ "user_id": 7, "lang": "en" }`)
+    Columns(std::collections::HashMap<String, serde_json::Value>),
+}
+
+/// Request to delete rows by primary key
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteRowsRequest {
+    /// Row keys
+    pub keys: Vec<RowKey>,
+
+    /// Handling of rows referenced through foreign keys
+    #[serde(default)]
+    pub on_referenced: OnReferenced,
+}
+
+/// Query parameters for deleting a single row (besides its `pk[column]` values)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteRowQuery {
+    /// Handling of rows referenced through foreign keys
+    #[serde(default)]
+    pub on_referenced: OnReferenced,
+}
+
+/// Number of rows removed by a delete
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedRows {
+    /// Rows deleted from the table itself
+    pub deleted: u64,
+
+    /// Referencing rows deleted from other tables (cascade only)
+    pub cascaded: u64,
+}
+
+/// Result of a bulk insert
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertedRows {
+    /// Number of rows inserted
+    pub inserted: u64,
+}
+
+/// Result of a delete request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteRowsResponse {
+    /// Rows deleted from the table itself
+    pub deleted: u64,
+
+    /// Referencing rows deleted from other tables (cascade only)
+    pub cascaded: u64,
+
+    /// Keys of rows left in place because they are referenced
+    pub skipped: Vec<String>,
+
+    /// Keys that matched no row
+    pub missing: Vec<String>,
+
+    /// Rows in other tables referencing the requested rows, per foreign key
+    pub references: Vec<ReferenceCount>,
+}
+
+/// Query parameters for resolving a row reference
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveQuery {
+    /// Table containing the row
+    pub table: String,
+
+    /// Primary key values, comma-separated (same format as the `/rows/:key` path segment)
+    pub key: String,
+}
+
+/// Number of rows in another table referencing a row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceCount {
+    /// Referencing table
+    pub table: String,
+
+    /// Foreign key column in the referencing table
+    pub column: String,
+
+    /// Number of referencing rows
+    pub count: u64,
+}
+
+/// Resolved row reference, used to build permalinks to single records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveResponse {
+    /// Table containing the row
+    pub table: String,
+
+    /// Canonical row key (values escaped and joined with commas)
+    pub key: String,
+
+    /// Primary key values by column name
+    pub primary_key: serde_json::Map<String, serde_json::Value>,
+
+    /// Whether a row with this key exists
+    pub exists: bool,
+
+    /// Column used to label the row (if the table has a suitable one)
+    pub display_column: Option<String>,
+
+    /// Human-readable label of the row (the display column's value, or the key)
+    pub display_value: Option<String>,
+
+    /// Rows in other tables referencing this row through a foreign key
+    pub references: Vec<ReferenceCount>,
+}
+
+/// Target language of generated code
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CodegenLanguage {
+    /// Struct deriving `sqlx::FromRow`
+    Rust,
+
+    /// Interface matching the rows returned by the API
+    Typescript,
+}
+
+/// Query parameters for code generation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodegenQuery {
+    /// Language to generate code for
+    pub lang: CodegenLanguage,
+}
+
+/// Query parameters for column lineage
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageQuery {
+    /// Maximum number of foreign keys to follow in each direction
+    #[serde(default = "default_lineage_depth")]
+    pub depth: usize,
+}
+
+fn default_lineage_depth() -> usize {
+    3
+}
+
+/// Query parameters for searching the schema
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaSearchQuery {
+    /// Text to search for in table and column names
+    #[serde(default)]
+    pub q: String,
+}
+
+/// Query parameters for sampling rows
+#[derive(Debug, Clone, Deserialize)]
+pub struct SampleQuery {
+    /// Number of rows to sample
+    #[serde(default = "default_sample_size")]
+    pub n: u64,
+}
+
+fn default_sample_size() -> u64 {
+    crate::sample::DEFAULT_SAMPLE_SIZE
+}
+
+/// Query parameters for column histograms
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistogramQuery {
+    /// Number of buckets
+    #[serde(default = "default_histogram_buckets")]
+    pub buckets: u32,
+}
+
+fn default_histogram_buckets() -> u32 {
+    crate::histogram::DEFAULT_BUCKETS
+}
+
+/// Query parameters for finding duplicate rows
+#[derive(Debug, Clone, Deserialize)]
+pub struct DuplicatesQuery {
+    /// Columns to group the rows by, separated by commas
+    #[serde(default)]
+    pub columns: String,
+
+    /// Number of groups to return
+    #[serde(default = "default_duplicate_groups")]
+    pub limit: u64,
+}
+
+impl DuplicatesQuery {
+    /// Names of the columns to group the rows by
+    pub fn column_names(&self) -> Vec<String> {
+        self.columns
+            .split(',')
+            .map(str::trim)
+            .filter(|column| !column.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+fn default_duplicate_groups() -> u64 {
+    crate::duplicates::DEFAULT_DUPLICATE_GROUPS
+}
+
+/// Query parameters for table profiles
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProfileQuery {
+    /// Compute a new profile instead of returning a cached one
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Response from opening a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotResponse {
+    /// Identifier to pass as `snapshot` when fetching rows
+    pub snapshot: String,
+
+    /// Seconds of inactivity after which the snapshot is released
+    pub idle_timeout_seconds: u64,
+}
+
+/// Response from opening a console session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResponse {
+    /// Identifier of the session (used in the session's URLs)
+    pub session: String,
+
+    /// Seconds of inactivity after which the session is closed
+    pub idle_timeout_seconds: u64,
+}
+
+/// Statements running on the database and the viewer's connection usage
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityResponse {
+    /// Connections of the database, longest running statement first
+    pub statements: Vec<ActiveStatement>,
+
+    /// Connections the viewer's pool holds open
+    pub open_connections: u32,
+
+    /// Open connections of the viewer's pool not in use
+    pub idle_connections: u32,
+}
+
+/// A connection of the database and the statement it runs (or ran last)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveStatement {
+    /// Backend process id (PostgreSQL)
+    pub process_id: Option<i32>,
+
+    /// Identifier the statement was sent to `/api/query` with, for cancelling it
+    pub query_id: Option<String>,
+
+    /// Connection state, e.g. `active`, `idle` or `idle in transaction`
+    pub state: String,
+
+    /// SQL of the statement
+    pub sql: Option<String>,
+
+    /// Database user of the connection
+    pub user: Option<String>,
+
+    /// Application name the client set
+    pub application: Option<String>,
+
+    /// When the statement started, in milliseconds since the Unix epoch
+    pub started_at_milliseconds: Option<u64>,
+
+    /// How long the statement has been running, in milliseconds
+    pub duration_milliseconds: Option<u64>,
+
+    /// What the statement is waiting for, e.g. `Lock: transactionid`
+    pub waiting_for: Option<String>,
+}
+
+/// Response to beginning an interactive transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionResponse {
+    /// Identifier of the transaction (used in the transaction's URLs)
+    pub tx: String,
+
+    /// Seconds of inactivity after which the transaction is rolled back
+    pub idle_timeout_seconds: u64,
+}
+
+/// Response to a destructive statement that has not been confirmed yet
+///
+/// The statement runs once it is sent again, unchanged and with the same
+/// parameters, along with `confirmation`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmationRequired {
+    /// Explanation for clients that only look at the error message
+    pub error: String,
+
+    /// What kind of destructive statement it is
+    pub statement_kind: crate::classify::DestructiveKind,
+
+    /// Rows the statement would change, found by running it in a transaction
+    /// that is rolled back (`None` if unknown, and for `DROP` and `TRUNCATE`)
+    pub estimated_affected_rows: Option<u64>,
+
+    /// Token to send as `confirmation` to run the statement
+    pub confirmation: String,
+
+    /// Seconds until the token expires
+    pub expires_in_seconds: u64,
+}
+
+/// One line of a streamed query result (`"stream": true`)
+///
+/// A stream consists of the column names (left out when there are no rows),
+/// one line per row, and finally an end line, or an error line if reading the
+/// rows failed part way through.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum QueryStreamLine {
+    /// Column names, sent before the first row
+    Columns { columns: Vec<String> },
+
+    /// A row as an object keyed by column name
+    Row { row: serde_json::Value },
+
+    /// The result is complete
+    #[serde(rename_all = "camelCase")]
+    End {
+        row_count: u64,
+        execution_time_milliseconds: u64,
+    },
+
+    /// Reading the rows failed; no line follows
+    Error { error: String },
+}
+
+/// Where the value of a command parameter is passed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ParameterLocation {
+    /// Substituted into the `{name}` placeholder of the path
+    Path,
+
+    /// Passed as a query string parameter
+    Query,
+
+    /// Passed as a field of the JSON request body
+    Body,
+}
+
+/// Parameter accepted by a command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandParameter {
+    /// Parameter name
+    pub name: String,
+
+    /// Where the parameter is passed
+    pub location: ParameterLocation,
+
+    /// Whether the command fails without this parameter
+    pub required: bool,
+
+    /// Short description of the parameter
+    pub description: String,
+}
+
+/// Server-side action that can be invoked through the API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Command {
+    /// Stable identifier (e.g. "table.rows" or "table.open:users")
+    pub id: String,
+
+    /// Human-readable title
+    pub title: String,
+
+    /// Group the command belongs to (e.g. "tables", "query")
+    pub category: String,
+
+    /// HTTP method used to invoke the command
+    pub method: String,
+
+    /// Path relative to the viewer's base path, with `{name}` placeholders
+    /// for path parameters
+    pub path: String,
+
+    /// Parameters the command accepts
+    pub parameters: Vec<CommandParameter>,
+}
+
+/// Response listing the available commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandsResponse {
+    /// Available commands, generic actions first, then one per table
+    pub commands: Vec<Command>,
+}
+
+/// Seed state of the database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedStatus {
+    /// Where the seed is kept (file path for SQLite, template database name for PostgreSQL)
+    pub location: String,
+
+    /// Whether a seed has been created
+    pub exists: bool,
+}
+
+/// Upkeep operation run by `/api/maintenance/:operation`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MaintenanceOperation {
+    /// Reclaim the space of deleted rows (`VACUUM`, with `ANALYZE` on PostgreSQL)
+    Vacuum,
+
+    /// Refresh the statistics the query planner works with (`ANALYZE`)
+    Analyze,
+
+    /// Rebuild indexes (`REINDEX`)
+    Reindex,
+
+    /// Move the pages of SQLite's write-ahead log into the database file and
+    /// truncate the log (`PRAGMA wal_checkpoint(TRUNCATE)`)
+    Checkpoint,
+}
+
+impl MaintenanceOperation {
+    /// Operation named `name` in the URL
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "vacuum" => Some(Self::Vacuum),
+            "analyze" => Some(Self::Analyze),
+            "reindex" => Some(Self::Reindex),
+            "checkpoint" => Some(Self::Checkpoint),
+            _ => None,
+        }
+    }
+
+    /// Name of the operation in the URL and in job kinds
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Vacuum => "vacuum",
+            Self::Analyze => "analyze",
+            Self::Reindex => "reindex",
+            Self::Checkpoint => "checkpoint",
+        }
+    }
+}
+
+/// Query parameters of the maintenance endpoints
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceQuery {
+    /// Comma-separated table names (the whole database when not set)
+    pub tables: Option<String>,
+}
+
+/// Result of a maintenance operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceResult {
+    /// Operation that ran
+    pub operation: MaintenanceOperation,
+
+    /// Tables it ran on (empty for the whole database)
+    pub tables: Vec<String>,
+
+    /// Size in bytes of the database before the operation
+    pub size_before: u64,
+
+    /// Size in bytes of the database after the operation
+    pub size_after: u64,
+
+    /// Time the operation took
+    pub execution_time_milliseconds: u64,
+
+    /// Outcome of a checkpoint (`checkpoint` only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<WalCheckpoint>,
+}
+
+/// Outcome of a checkpoint of SQLite's write-ahead log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalCheckpoint {
+    /// Whether another connection kept the checkpoint from completing
+    pub busy: bool,
+
+    /// Pages in the log (`None` if the database is not in WAL mode)
+    pub log_frames: Option<u64>,
+
+    /// Pages of the log moved into the database file
+    pub checkpointed_frames: Option<u64>,
+
+    /// Size in bytes of the `-wal` file before the checkpoint
+    pub wal_size_before: Option<u64>,
+
+    /// Size in bytes of the `-wal` file after the checkpoint
+    pub wal_size_after: Option<u64>,
+}
+
+/// Journal settings of a SQLite database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalInfo {
+    /// Journal mode (`wal`, `delete`, `truncate`, `persist`, `memory` or `off`)
+    pub journal_mode: String,
+
+    /// Whether the database uses a write-ahead log
+    pub wal: bool,
+
+    /// Size in bytes of the `-wal` file (`None` without one, e.g. in memory)
+    pub wal_size: Option<u64>,
+
+    /// Log pages after which SQLite checkpoints on its own (0 when it never does)
+    pub wal_autocheckpoint: u64,
+
+    /// Size in bytes of a database page
+    pub page_size: u64,
+}
+
+/// Named copy of the rows of one or all tables, see [`crate::named_snapshots`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedSnapshot {
+    /// Name the snapshot was saved under
+    pub name: String,
+
+    /// Tables copied into the snapshot
+    pub tables: Vec<String>,
+
+    /// When the snapshot was taken, in milliseconds since the Unix epoch
+    pub created_at_milliseconds: u64,
+}
+
+/// Request body for saving a named snapshot
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSnapshotRequest {
+    /// Name of the snapshot; a snapshot of the same name is replaced
+    pub name: String,
+
+    /// Table to copy (all tables when not set)
+    #[serde(default)]
+    pub table: Option<String>,
+}
+
+/// Query parameters of the snapshot diff endpoint
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiffQuery {
+    /// Table to compare; may be left out if the snapshot holds a single table
+    #[serde(default)]
+    pub table: Option<String>,
+}
+
+/// Rows that differ between a table and its copy in a snapshot
+///
+/// Rows that were updated appear on both sides, once with their old and once
+/// with their new values.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotRowDifferences {
+    /// Rows in the table that are not in the snapshot
+    pub current: Vec<serde_json::Value>,
+
+    /// Rows in the snapshot that are no longer in the table
+    pub snapshot: Vec<serde_json::Value>,
+
+    /// Whether there were more differing rows than were read
+    pub truncated: bool,
+}
+
+/// Changes to a table since a named snapshot was taken
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiff {
+    /// Name of the snapshot
+    pub snapshot: String,
+
+    /// Table that was compared
+    pub table: String,
+
+    /// Rows inserted since the snapshot
+    pub added: Vec<serde_json::Value>,
+
+    /// Rows deleted since the snapshot
+    pub removed: Vec<serde_json::Value>,
+
+    /// Rows with the same primary key whose values changed
+    pub changed: Vec<ChangedRow>,
+
+    /// Whether only some of the differences are listed
+    pub truncated: bool,
+}
+
+/// Row whose values changed since a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedRow {
+    /// Row as it is in the snapshot
+    pub before: serde_json::Value,
+
+    /// Row as it is now
+    pub after: serde_json::Value,
+
+    /// Columns whose values differ
+    pub columns: Vec<String>,
+}
+
+/// Database file attached to the main database under an alias
+///
+/// Its tables are listed as `alias.table`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachedDatabase {
+    /// Schema name the file is attached as
+    pub alias: String,
+
+    /// Path of the database file on the server
+    pub path: String,
+}
+
+/// Request to attach a database file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachDatabaseRequest {
+    /// Schema name to attach the file as
+    pub alias: String,
+
+    /// Path of the database file on the server
+    pub path: String,
+}
+
+/// Request to inspect an application query
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectQueryRequest {
+    /// SQL query as written in the application, placeholders included
+    pub sql: String,
+}
+
+/// Request to explain a query as a plan tree
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainRequest {
+    /// Single SQL statement; placeholders are replaced with NULL
+    pub sql: String,
+
+    /// Run the statement to report actual row counts and timings
+    /// (read-only statements only)
+    #[serde(default)]
+    pub analyze: bool,
+}
+
+/// Request to pretty-print SQL
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatQueryRequest {
+    /// SQL text to format (one or more statements)
+    pub sql: String,
+}
+
+/// Pretty-printed SQL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatQueryResponse {
+    /// Formatted SQL text
+    pub sql: String,
+}
+
+/// Table referenced by an inspected query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferencedTable {
+    /// Table name as it exists in the schema
+    pub name: String,
+
+    /// Columns of this table that appear in the query
+    pub columns: Vec<String>,
+}
+
+/// Result of inspecting an application query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectQueryResponse {
+    /// Query with comments removed and whitespace collapsed
+    pub normalized_sql: String,
+
+    /// Query actually passed to EXPLAIN (placeholders replaced with NULL)
+    pub explained_sql: String,
+
+    /// Placeholders found in the query, in order of appearance
+    pub placeholders: Vec<String>,
+
+    /// Query plan lines as reported by the database
+    pub plan: Vec<String>,
+
+    /// Indexes the plan would use
+    pub indexes: Vec<String>,
+
+    /// Known tables (and their columns) referenced by the query
+    pub referenced_tables: Vec<ReferencedTable>,
+
+    /// Names used as tables that do not exist in the schema
+    pub unknown_tables: Vec<String>,
+
+    /// Error message if the query could not be explained
+    pub error: Option<String>,
+}
+
+/// Query plan of a statement as a tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainResponse {
+    /// Query actually passed to EXPLAIN (placeholders replaced with NULL)
+    pub explained_sql: String,
+
+    /// Top-level steps of the plan (SQLite plans can have several)
+    pub plan: Vec<PlanNode>,
+
+    /// Time the database spent planning the statement (PostgreSQL, when analyzed)
+    pub planning_time_milliseconds: Option<f64>,
+
+    /// Time the statement took to run, when analyzed
+    pub execution_time_milliseconds: Option<f64>,
+}
+
+/// A step of a query plan, with the steps feeding into it as children
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanNode {
+    /// Kind of step, e.g. `Seq Scan`, `Hash Join` or `SEARCH`
+    pub operation: String,
+
+    /// One-line description, e.g. `Index Scan using users_pkey on users`
+    pub detail: String,
+
+    /// Table the step reads
+    pub relation: Option<String>,
+
+    /// Index the step uses
+    pub index: Option<String>,
+
+    /// Rows the planner expects the step to return (PostgreSQL)
+    pub estimated_rows: Option<f64>,
+
+    /// Planner's total cost estimate of the step (PostgreSQL)
+    pub estimated_cost: Option<f64>,
+
+    /// Rows the step actually returned per run (PostgreSQL, when analyzed)
+    pub actual_rows: Option<f64>,
+
+    /// Number of times the step ran (PostgreSQL, when analyzed)
+    pub actual_loops: Option<f64>,
+
+    /// Time spent in the step including its children, over all runs
+    /// (PostgreSQL, when analyzed)
+    pub actual_time_milliseconds: Option<f64>,
+
+    /// Time spent in the step itself, without its children
+    /// (PostgreSQL, when analyzed)
+    pub self_time_milliseconds: Option<f64>,
+
+    /// Other fields reported by the database, e.g. `Filter` or `Join Type`
+    pub properties: serde_json::Map<String, serde_json::Value>,
+
+    /// Steps whose output this step consumes
+    pub children: Vec<PlanNode>,
+}
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    /// Comma-separated values
+    #[default]
+    Csv,
+
+    /// Excel workbook
+    Xlsx,
+
+    /// Newline-delimited JSON, one object per row
+    Ndjson,
+
+    /// JSON array of objects, one per row
+    Json,
+
+    /// `INSERT` statements, one per row
+    Sql,
+}
+
+/// Document format of a fixture
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FixtureFormat {
+    /// Object of row arrays by table name
+    #[default]
+    Json,
+
+    /// The same structure as a YAML block mapping
+    Yaml,
+}
+
+/// Query parameters for fixture downloads and uploads
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureQuery {
+    /// Comma-separated table names (all tables when not set; downloads only)
+    pub tables: Option<String>,
+
+    /// Document format (taken from the content type of uploads when not set)
+    pub format: Option<FixtureFormat>,
+}
+
+/// Rows of one table in a fixture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureTable {
+    /// Table name
+    pub table: String,
+
+    /// Rows as objects of values by column name
+    pub rows: Vec<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Query parameters of the dump endpoint, named after the `pg_dump` options
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpQuery {
+    /// Comma-separated table names (all tables when not set)
+    pub tables: Option<String>,
+
+    /// Only write the statements creating the tables
+    #[serde(default)]
+    pub schema_only: bool,
+
+    /// Only write the rows
+    #[serde(default)]
+    pub data_only: bool,
+
+    /// Write rows as `INSERT` statements instead of `COPY` blocks
+    #[serde(default)]
+    pub inserts: bool,
+}
+
+/// Statements recreating a table in a dump, see [`crate::dump`]
+///
+/// Statements have no trailing semicolon.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableDefinition {
+    /// Table name
+    pub table: String,
+
+    /// `CREATE TABLE` statement with every constraint except foreign keys
+    pub create_statement: String,
+
+    /// Columns whose values are dumped (generated columns are left out)
+    pub columns: Vec<String>,
+
+    /// Whether inserts have to override a `GENERATED ALWAYS` identity column
+    pub overriding_system_value: bool,
+
+    /// Statements moving sequences past the dumped keys
+    pub sequence_resets: Vec<String>,
+
+    /// Statements creating the indexes not backing a constraint
+    pub indexes: Vec<String>,
+
+    /// Statements adding the foreign keys, once every table is filled
+    pub foreign_keys: Vec<String>,
+}
+
+/// Result of loading a fixture
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedFixture {
+    /// Number of tables replaced
+    pub tables: u64,
+
+    /// Number of rows deleted from them
+    pub deleted: u64,
+
+    /// Number of rows inserted
+    pub inserted: u64,
+}
+
+/// Request to export a table as a downloadable file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRequest {
+    /// File format
+    #[serde(default)]
+    pub format: ExportFormat,
+
+    /// Column name to sort by
+    pub sort_by: Option<String>,
+
+    /// Sort order
+    pub sort_order: Option<SortOrder>,
+
+    /// Where rows with NULL in the sort column go, as for rows
+    pub nulls: Option<NullsOrder>,
+
+    /// Column filters (column_name -> filter_value)
+    #[serde(default)]
+    pub filters: std::collections::HashMap<String, String>,
+
+    /// Date and timestamp comparisons, as for rows (query parameters only)
+    #[serde(skip)]
+    pub ranges: Vec<crate::filters::RangeFilter>,
+
+    /// Groups of column filters of which one must match, as for rows (bodies only)
+    #[serde(default)]
+    pub filter_groups: Vec<std::collections::HashMap<String, String>>,
+
+    /// Whether filters match text ignoring case, as for rows
+    #[serde(default)]
+    pub case_insensitive: bool,
+
+    /// Text to search for in all textual columns, as for rows
+    pub q: Option<String>,
+
+    /// What to do when the table is larger than the configured export limits
+    #[serde(default)]
+    pub on_limit: OnExportLimit,
+
+    /// Locale options (delimiter, decimal separator, date format)
+    #[serde(flatten)]
+    pub options: crate::export::ExportOptions,
+}
+
+/// What to do with an export exceeding its size limits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OnExportLimit {
+    /// Export the first rows, up to the limits
+    #[default]
+    Truncate,
+
+    /// Export a sample of rows spread evenly over the table
+    Sample,
+
+    /// Refuse the export
+    Fail,
+}
+
+/// Explanation of why an export contains fewer rows than requested
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportManifest {
+    /// How the rows were reduced
+    pub mode: OnExportLimit,
+
+    /// Rows matching the export's filters (if they were counted)
+    pub total_rows: Option<u64>,
+
+    /// Limits that were hit, as human-readable warnings
+    pub warnings: Vec<String>,
+}
+
+/// Export file produced by an export job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportArtifactResponse {
+    /// Artifact identifier
+    pub id: String,
+
+    /// Suggested file name
+    pub file_name: String,
+
+    /// Size of the file in bytes
+    pub size: u64,
+
+    /// Number of exported rows
+    pub rows: u64,
+
+    /// Download URL (supports `Range` requests for resuming)
+    pub url: String,
+
+    /// Seconds the file is kept for download
+    pub expires_in_seconds: u64,
+
+    /// Present if the export was truncated or sampled to stay within its limits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<ExportManifest>,
+}
+
+/// Optional features of a database provider
+///
+/// The API layer checks these before using a feature, and the frontend reads
+/// them from `/api/config` to hide what the database cannot do.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    /// Changes can be pushed to clients as they happen (e.g. PostgreSQL LISTEN/NOTIFY)
+    pub supports_live_updates: bool,
+
+    /// Queries can be profiled with `EXPLAIN ANALYZE`
+    pub supports_explain_analyze: bool,
+
+    /// Tables live in named schemas (e.g. `public.users`)
+    pub supports_schemas: bool,
+
+    /// `INSERT`, `UPDATE` and `DELETE` support `RETURNING`
+    pub supports_returning: bool,
+
+    /// Row reads can be pinned to a snapshot (`POST /api/snapshots`)
+    pub supports_snapshots: bool,
+
+    /// Console sessions with their own connection (`POST /api/console/sessions`)
+    pub supports_sessions: bool,
+
+    /// Running raw queries can be cancelled (`POST /api/query/:id/cancel`)
+    pub supports_cancellation: bool,
+
+    /// Maximum number of bind parameters in one statement (`None` if unknown)
+    pub max_placeholder_count: Option<u32>,
+}
+
+/// Response of the configuration endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigResponse {
+    /// Version of the JSON API (see `X-Api-Version`)
+    pub api_version: u32,
+
+    /// SQL dialect of the database
+    pub dialect: crate::sql::Dialect,
+
+    /// Optional features supported by the database
+    pub capabilities: Capabilities,
+
+    /// Maximum number of rows returned per page
+    pub max_page_size: u64,
+
+    /// Maximum number of rows in the result of raw SQL
+    pub max_result_rows: u64,
+
+    /// Seconds export files are kept for download
+    pub export_retention_seconds: u64,
+
+    /// Tables can be profiled (`GET /api/tables/:name/profile`)
+    #[serde(default)]
+    pub profiling: bool,
+}
+
+/// Notification sent with PostgreSQL's `NOTIFY` to a channel the viewer listens on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    /// Channel the notification was sent to
+    pub channel: String,
+
+    /// Payload of the notification (empty if none was given)
+    pub payload: String,
+}
+
+/// Fingerprint of a table's data, see [`crate::checksum`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableChecksum {
+    /// Name of the table
+    pub table: String,
+
+    /// 16 hexadecimal digits that change when rows are inserted, deleted or
+    /// (depending on the table) updated
+    pub checksum: String,
+
+    /// Number of rows in the table
+    pub row_count: u64,
+}
+
+/// Tool that recorded a migration history, see [`crate::migrations`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MigrationTool {
+    /// sqlx (`_sqlx_migrations`)
+    Sqlx,
+
+    /// Diesel (`__diesel_schema_migrations`)
+    Diesel,
+}
+
+/// An applied migration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Migration {
+    /// Version of the migration (a number or timestamp)
+    pub version: String,
+
+    /// Description taken from the migration's file name (sqlx only)
+    pub description: Option<String>,
+
+    /// When the migration was applied, as stored by the database
+    pub installed_on: Option<String>,
+
+    /// Whether the migration completed (sqlx only; a failed migration blocks later ones)
+    pub success: Option<bool>,
+
+    /// Checksum of the migration's SQL in hexadecimal (sqlx only)
+    pub checksum: Option<String>,
+
+    /// Time the migration took to apply (sqlx only)
+    pub execution_time_milliseconds: Option<u64>,
+}
+
+/// Migrations recorded in one table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationHistory {
+    /// Table the migrations are recorded in
+    pub table: String,
+
+    /// Tool that recorded them
+    pub tool: MigrationTool,
+
+    /// Applied migrations, by version
+    pub migrations: Vec<Migration>,
+}
+
+/// Response for the migrations endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationsResponse {
+    /// Migration histories found in the database (empty if none)
+    pub histories: Vec<MigrationHistory>,
+
+    /// Whether a migrator is configured, so pending migrations can be applied
+    pub runnable: bool,
+
+    /// Migrations of the configured migrator not applied yet, by version
+    pub pending: Vec<Migration>,
+}
+
+/// Result of a job applying pending migrations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationRunResult {
+    /// Migrations applied, by version
+    pub applied: Vec<Migration>,
+
+    /// Time the migrations took to apply
+    pub execution_time_milliseconds: u64,
 }
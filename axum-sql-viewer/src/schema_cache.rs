@@ -0,0 +1,288 @@
+//! Cache of discovered schema information
+//!
+//! Listing tables and loading their schemas takes a burst of catalog queries
+//! (`PRAGMA` on SQLite, `information_schema` on PostgreSQL). With schema
+//! prefetching enabled on the layer, the full schema is discovered in a
+//! background task when the router is built and kept in a [`SchemaCache`], so
+//! the first page load is answered from memory. A cache with a time to live
+//! (`SqlViewerLayer::with_schema_cache_ttl`) is filled on first use instead
+//! and refetches entries once they are older than that; `POST
+//! /api/cache/invalidate` empties it. Without either the cache is disabled and
+//! every request goes to the database.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::schema::{CountAccuracy, TableInfo, TableSchema};
+
+/// Cached table list and table schemas
+///
+/// Cloning a `SchemaCache` is cheap; all clones share the same entries.
+#[derive(Clone, Default)]
+pub struct SchemaCache {
+    inner: Option<Arc<Mutex<CachedSchema>>>,
+}
+
+#[derive(Default)]
+struct CachedSchema {
+    tables: Option<Entry<Vec<TableInfo>>>,
+    schemas: HashMap<String, Entry<TableSchema>>,
+    include_counts: bool,
+    ttl: Option<Duration>,
+}
+
+/// Cached value with the time it was stored
+struct Entry<T> {
+    value: T,
+    stored_at: Instant,
+}
+
+impl<T: Clone> Entry<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            stored_at: Instant::now(),
+        }
+    }
+
+    /// The value, unless it is older than `ttl`
+    fn fresh(&self, ttl: Option<Duration>) -> Option<T> {
+        match ttl {
+            Some(ttl) if self.stored_at.elapsed() >= ttl => None,
+            _ => Some(self.value.clone()),
+        }
+    }
+}
+
+impl SchemaCache {
+    /// Create an enabled, still empty cache
+    ///
+    /// # Arguments
+    ///
+    /// * `include_counts` - Whether to keep the row counts reported when the
+    ///   tables were listed; these are not refreshed, so they are approximate
+    ///   (marked as not exact, with the time they were counted)
+    pub fn new(include_counts: bool) -> Self {
+        Self {
+            inner: Some(Arc::new(Mutex::new(CachedSchema {
+                include_counts,
+                ..CachedSchema::default()
+            }))),
+        }
+    }
+
+    /// Refetch entries once they are older than `ttl`
+    ///
+    /// Without a time to live, entries are kept until the cache is cleared.
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        if let Some(inner) = &self.inner {
+            inner.lock().unwrap().ttl = Some(ttl);
+        }
+        self
+    }
+
+    /// A cache that keeps nothing (every lookup goes to the database)
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Whether lookups are answered from the cache
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Discover all tables and their schemas and store them in the cache
+    ///
+    /// Does nothing if the cache is disabled.
+    pub async fn prefetch<DB: DatabaseProvider>(&self, database: &DB) -> Result<(), DatabaseError> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let tables = database.list_tables().await?;
+        let mut schemas = Vec::with_capacity(tables.len());
+        for table in &tables {
+            schemas.push(database.get_table_schema(&table.name).await?);
+        }
+
+        self.store_tables(tables);
+        for schema in schemas {
+            self.store_schema(schema);
+        }
+        Ok(())
+    }
+
+    /// List all tables, from the cache if they were listed before
+    pub async fn list_tables<DB: DatabaseProvider>(
+        &self,
+        database: &DB,
+    ) -> Result<Vec<TableInfo>, DatabaseError> {
+        if let Some(tables) = self.cached_tables() {
+            return Ok(tables);
+        }
+
+        let tables = database.list_tables().await?;
+        self.store_tables(tables.clone());
+        Ok(tables)
+    }
+
+    /// Get the schema of a table, from the cache if it was loaded before
+    pub async fn table_schema<DB: DatabaseProvider>(
+        &self,
+        database: &DB,
+        table: &str,
+    ) -> Result<TableSchema, DatabaseError> {
+        if let Some(schema) = self.cached_schema(table) {
+            return Ok(schema);
+        }
+
+        let schema = database.get_table_schema(table).await?;
+        self.store_schema(schema.clone());
+        Ok(schema)
+    }
+
+    /// Get the schemas of all tables, from the cache if all of them were loaded before
+    pub async fn all_table_schemas<DB: DatabaseProvider>(
+        &self,
+        database: &DB,
+    ) -> Result<Vec<TableSchema>, DatabaseError> {
+        if let Some(schemas) = self.cached_all_schemas() {
+            return Ok(schemas);
+        }
+
+        let schemas = database.get_all_table_schemas().await?;
+        for schema in &schemas {
+            self.store_schema(schema.clone());
+        }
+        Ok(schemas)
+    }
+
+    /// Forget all cached entries (e.g. after the schema was changed)
+    pub fn clear(&self) {
+        if let Some(inner) = &self.inner {
+            let mut cached = inner.lock().unwrap();
+            cached.tables = None;
+            cached.schemas.clear();
+        }
+    }
+
+    fn cached_tables(&self) -> Option<Vec<TableInfo>> {
+        let cached = self.inner.as_ref()?.lock().unwrap();
+        cached.tables.as_ref()?.fresh(cached.ttl)
+    }
+
+    fn cached_schema(&self, table: &str) -> Option<TableSchema> {
+        let cached = self.inner.as_ref()?.lock().unwrap();
+        cached.schemas.get(table)?.fresh(cached.ttl)
+    }
+
+    /// All schemas in table list order, if the list and every schema are cached
+    fn cached_all_schemas(&self) -> Option<Vec<TableSchema>> {
+        let cached = self.inner.as_ref()?.lock().unwrap();
+        cached
+            .tables
+            .as_ref()?
+            .fresh(cached.ttl)?
+            .iter()
+            .map(|table| cached.schemas.get(&table.name)?.fresh(cached.ttl))
+            .collect()
+    }
+
+    fn store_tables(&self, mut tables: Vec<TableInfo>) {
+        if let Some(inner) = &self.inner {
+            let mut cached = inner.lock().unwrap();
+            for table in &mut tables {
+                if cached.include_counts {
+                    table.row_count_accuracy = table.row_count_accuracy.map(CountAccuracy::reused);
+                } else {
+                    table.row_count = None;
+                    table.row_count_accuracy = None;
+                }
+            }
+            cached.tables = Some(Entry::new(tables));
+        }
+    }
+
+    fn store_schema(&self, schema: TableSchema) {
+        if let Some(inner) = &self.inner {
+            inner
+                .lock()
+                .unwrap()
+                .schemas
+                .insert(schema.name.clone(), Entry::new(schema));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn table(name: &str, row_count: Option<u64>) -> TableInfo {
+        TableInfo::counted(name.to_string(), row_count)
+    }
+
+    fn schema(name: &str) -> TableSchema {
+        test_support::table(name).build()
+    }
+
+    #[test]
+    fn test_cached_all_schemas_requires_every_table() {
+        let cache = SchemaCache::new(true);
+        assert!(cache.cached_all_schemas().is_none());
+
+        cache.store_tables(vec![table("users", Some(3)), table("posts", None)]);
+        cache.store_schema(schema("posts"));
+        assert!(cache.cached_all_schemas().is_none());
+
+        cache.store_schema(schema("users"));
+        let names: Vec<String> = cache
+            .cached_all_schemas()
+            .unwrap()
+            .into_iter()
+            .map(|schema| schema.name)
+            .collect();
+        assert_eq!(names, vec!["users", "posts"]);
+        let users = &cache.cached_tables().unwrap()[0];
+        assert_eq!(users.row_count, Some(3));
+        assert!(!users.row_count_accuracy.unwrap().exact);
+
+        cache.clear();
+        assert!(cache.cached_tables().is_none());
+        assert!(cache.cached_schema("users").is_none());
+    }
+
+    #[test]
+    fn test_expired_entries() {
+        let cache = SchemaCache::new(true).with_ttl(Duration::ZERO);
+        cache.store_tables(vec![table("users", Some(3))]);
+        cache.store_schema(schema("users"));
+        assert!(cache.cached_tables().is_none());
+        assert!(cache.cached_schema("users").is_none());
+        assert!(cache.cached_all_schemas().is_none());
+
+        let cache = SchemaCache::new(true).with_ttl(Duration::from_secs(60));
+        cache.store_tables(vec![table("users", Some(3))]);
+        cache.store_schema(schema("users"));
+        assert_eq!(cache.cached_tables().unwrap().len(), 1);
+        assert!(cache.cached_schema("users").is_some());
+    }
+
+    #[test]
+    fn test_counts_and_disabled_cache() {
+        let cache = SchemaCache::new(false);
+        cache.store_tables(vec![table("users", Some(3))]);
+        assert_eq!(cache.cached_tables().unwrap()[0].row_count, None);
+        assert_eq!(cache.cached_tables().unwrap()[0].row_count_accuracy, None);
+
+        let disabled = SchemaCache::disabled();
+        disabled.store_tables(vec![table("users", Some(3))]);
+        disabled.store_schema(schema("users"));
+        assert!(!disabled.is_enabled());
+        assert!(disabled.cached_tables().is_none());
+        assert!(disabled.cached_schema("users").is_none());
+    }
+}
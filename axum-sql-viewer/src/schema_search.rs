@@ -0,0 +1,150 @@
+//! Searching table and column names across the whole schema
+//!
+//! Answers questions like "which tables have a `tenant_id` column" in one
+//! request, instead of fetching the schema of every table. Names match when
+//! they contain the search text, ignoring case; exact matches come first.
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::TableSchema;
+
+/// Kind of object a search matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SchemaObjectKind {
+    /// A table (or view)
+    Table,
+
+    /// A column of a table
+    Column,
+}
+
+/// A table or column whose name contains the search text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaMatch {
+    /// Whether a table or a column matched
+    pub kind: SchemaObjectKind,
+
+    /// Table name (the table of the column, for columns)
+    pub table: String,
+
+    /// Column name, for columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+
+    /// SQL data type of the column, for columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
+
+    /// Whether the whole name equals the search text (ignoring case)
+    pub exact: bool,
+}
+
+/// Tables and columns matching a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaSearchResult {
+    /// Text searched for
+    pub query: String,
+
+    /// Matching objects, exact matches first and otherwise in schema order
+    pub matches: Vec<SchemaMatch>,
+}
+
+/// Find the tables and columns whose names contain `text`, ignoring case
+pub fn search_schema(schemas: &[TableSchema], text: &str) -> SchemaSearchResult {
+    let needle = text.to_lowercase();
+    let matched = |name: &str| {
+        let name = name.to_lowercase();
+        name.contains(&needle).then_some(name == needle)
+    };
+
+    let mut matches = Vec::new();
+    for schema in schemas {
+        if let Some(exact) = matched(&schema.name) {
+            matches.push(SchemaMatch {
+                kind: SchemaObjectKind::Table,
+                table: schema.name.clone(),
+                column: None,
+                data_type: None,
+                exact,
+            });
+        }
+        for column in &schema.columns {
+            if let Some(exact) = matched(&column.name) {
+                matches.push(SchemaMatch {
+                    kind: SchemaObjectKind::Column,
+                    table: schema.name.clone(),
+                    column: Some(column.name.clone()),
+                    data_type: Some(column.data_type.clone()),
+                    exact,
+                });
+            }
+        }
+    }
+
+    // Stable, so the schema order is kept within exact and partial matches
+    matches.sort_by_key(|found| !found.exact);
+
+    SchemaSearchResult {
+        query: text.to_string(),
+        matches,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn table(name: &str, columns: &[&str]) -> TableSchema {
+        test_support::table(name).text_columns(columns).build()
+    }
+
+    fn names(result: &SchemaSearchResult) -> Vec<String> {
+        result
+            .matches
+            .iter()
+            .map(|found| match &found.column {
+                Some(column) => format!("{}.{}", found.table, column),
+                None => found.table.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_search_columns_across_tables() {
+        let schemas = vec![
+            table("users", &["id", "tenant_id", "email"]),
+            table("tenants", &["id", "name"]),
+            table("orders", &["id", "Tenant_ID", "previous_tenant_id"]),
+        ];
+
+        let result = search_schema(&schemas, "tenant_id");
+        assert_eq!(
+            names(&result),
+            vec![
+                "users.tenant_id",
+                "orders.Tenant_ID",
+                "orders.previous_tenant_id"
+            ]
+        );
+        assert!(result.matches[0].exact);
+        assert!(!result.matches[2].exact);
+        assert_eq!(result.matches[0].data_type.as_deref(), Some("TEXT"));
+    }
+
+    #[test]
+    fn test_search_tables_and_columns() {
+        let schemas = vec![
+            table("users", &["id", "tenant_id"]),
+            table("tenants", &["id", "name"]),
+        ];
+
+        let result = search_schema(&schemas, "TENANT");
+        assert_eq!(names(&result), vec!["users.tenant_id", "tenants"]);
+        assert_eq!(result.matches[1].kind, SchemaObjectKind::Table);
+        assert!(search_schema(&schemas, "missing").matches.is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+//! Schema change notifications
+//!
+//! With schema watching enabled on the layer, a background task checks the
+//! database's schema fingerprint at a fixed interval. When it changes (e.g.
+//! because a migration ran), the table schemas are compared with the ones seen
+//! before, the schema cache is cleared and a [`SchemaChange`] is published to
+//! every client listening on `/api/events`, so open viewers refresh their table
+//! list without a reload.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::broadcast::Broadcast;
+use crate::database::traits::DatabaseProvider;
+use crate::runtime;
+use crate::schema::TableSchema;
+use crate::schema_cache::SchemaCache;
+
+/// Tables that changed between two looks at the schema
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaChange {
+    /// Tables that were created
+    pub added: Vec<String>,
+
+    /// Tables that were dropped
+    pub removed: Vec<String>,
+
+    /// Tables whose columns, keys or indexes changed
+    pub changed: Vec<String>,
+}
+
+impl SchemaChange {
+    /// Whether no table changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare two lists of table schemas by table name
+pub fn diff_schemas(previous: &[TableSchema], current: &[TableSchema]) -> SchemaChange {
+    let previous_by_name: HashMap<&str, &TableSchema> = previous
+        .iter()
+        .map(|schema| (schema.name.as_str(), schema))
+        .collect();
+    let current_names: HashSet<&str> = current.iter().map(|schema| schema.name.as_str()).collect();
+
+    let mut change = SchemaChange::default();
+    for schema in current {
+        match previous_by_name.get(schema.name.as_str()) {
+            None => change.added.push(schema.name.clone()),
+            Some(previous) if *previous != schema => change.changed.push(schema.name.clone()),
+            Some(_) => {}
+        }
+    }
+    change.removed = previous
+        .iter()
+        .filter(|schema| !current_names.contains(schema.name.as_str()))
+        .map(|schema| schema.name.clone())
+        .collect();
+
+    change
+}
+
+/// Publishes schema changes to any number of listeners
+pub type SchemaEvents = Broadcast<SchemaChange>;
+
+/// Check the schema for changes every `interval`
+///
+/// Runs for as long as the process does. A change is published and clears
+/// `cache` only when the schemas differ, so fingerprint changes that do not
+/// affect any table (e.g. a dropped sequence) stay silent.
+pub async fn watch_schema<DB: DatabaseProvider>(
+    database: Arc<DB>,
+    cache: SchemaCache,
+    events: SchemaEvents,
+    interval: Duration,
+) {
+    let mut fingerprint = database.schema_fingerprint().await.ok();
+    let mut schemas = database.get_all_table_schemas().await.ok();
+
+    loop {
+        runtime::sleep(interval).await;
+
+        // Without a fingerprint every check compares the full schema
+        let current_fingerprint = database.schema_fingerprint().await.ok();
+        if current_fingerprint.is_some() && current_fingerprint == fingerprint {
+            continue;
+        }
+
+        let current = match database.get_all_table_schemas().await {
+            Ok(current) => current,
+            Err(error) => {
+                eprintln!("Failed to check the schema for changes: {}", error);
+                continue;
+            }
+        };
+        fingerprint = current_fingerprint;
+
+        if let Some(previous) = &schemas {
+            let change = diff_schemas(previous, &current);
+            if !change.is_empty() {
+                cache.clear();
+                events.publish(change);
+            }
+        }
+        schemas = Some(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use futures_util::StreamExt;
+    use std::task::{Context, Poll};
+
+    fn table(name: &str, columns: &[&str]) -> TableSchema {
+        test_support::table(name).text_columns(columns).build()
+    }
+
+    #[test]
+    fn test_diff_schemas() {
+        let previous = vec![table("users", &["id"]), table("logs", &["id"])];
+        let current = vec![table("users", &["id", "email"]), table("invoices", &["id"])];
+
+        assert_eq!(
+            diff_schemas(&previous, &current),
+            SchemaChange {
+                added: vec!["invoices".to_string()],
+                removed: vec!["logs".to_string()],
+                changed: vec!["users".to_string()],
+            }
+        );
+        assert!(diff_schemas(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_subscribers_receive_later_changes() {
+        let events = SchemaEvents::default();
+        events.publish(SchemaChange {
+            added: vec!["before".to_string()],
+            ..SchemaChange::default()
+        });
+
+        let mut subscription = Box::pin(events.subscribe());
+        let mut context = Context::from_waker(futures_util::task::noop_waker_ref());
+        assert!(subscription.poll_next_unpin(&mut context).is_pending());
+
+        let change = SchemaChange {
+            added: vec!["after".to_string()],
+            ..SchemaChange::default()
+        };
+        events.publish(change.clone());
+        assert_eq!(
+            subscription.poll_next_unpin(&mut context),
+            Poll::Ready(Some(change))
+        );
+        assert!(subscription.poll_next_unpin(&mut context).is_pending());
+    }
+}
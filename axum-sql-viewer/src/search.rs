@@ -0,0 +1,142 @@
+//! Searching all textual columns of a table
+//!
+//! The `q` parameter of the rows endpoint matches rows containing the text in
+//! any of their textual columns, so a table can be searched without picking a
+//! column first. Which columns are textual is decided from their declared
+//! type; numbers, dates, JSON and BLOBs are not searched.
+
+use crate::schema::TableSchema;
+use crate::sql::{quote_identifier, Dialect};
+
+/// Character escaping `%`, `_` and itself in search patterns
+const ESCAPE: char = '\\';
+
+/// Whether a column of this declared type holds text
+///
+/// Covers `TEXT`, `CHAR`, `VARCHAR`, `CHARACTER VARYING`, `CLOB` and
+/// `citext`, but not arrays of them.
+pub fn is_textual(data_type: &str) -> bool {
+    let data_type = data_type.to_ascii_uppercase();
+    !data_type.ends_with("[]")
+        && (data_type.contains("CHAR") || data_type.contains("TEXT") || data_type.contains("CLOB"))
+}
+
+/// Names of the columns searched in a table
+pub fn search_columns(schema: &TableSchema) -> Vec<&str> {
+    schema
+        .columns
+        .iter()
+        .filter(|column| is_textual(&column.data_type))
+        .map(|column| column.name.as_str())
+        .collect()
+}
+
+/// `LIKE` pattern matching values containing `text`, with wildcards in it escaped
+pub fn contains_pattern(text: &str) -> String {
+    let mut pattern = String::with_capacity(text.len() + 2);
+    pattern.push('%');
+    for character in text.chars() {
+        if matches!(character, '%' | '_') || character == ESCAPE {
+            pattern.push(ESCAPE);
+        }
+        pattern.push(character);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Condition matching rows with `text` in any textual column, and its bound values
+///
+/// Each column gets its own placeholder (from `placeholder`) bound to the
+/// same pattern. Matching ignores case: PostgreSQL uses `ILIKE`, and SQLite's
+/// `LIKE` already ignores the case of ASCII letters. A table without textual
+/// columns matches no rows.
+pub fn search_condition(
+    schema: &TableSchema,
+    text: &str,
+    dialect: Dialect,
+    mut placeholder: impl FnMut() -> String,
+) -> (String, Vec<String>) {
+    let columns = search_columns(schema);
+    if columns.is_empty() {
+        return ("1 = 0".to_string(), Vec::new());
+    }
+
+    let operator = match dialect {
+        Dialect::Sqlite => "LIKE",
+        Dialect::Postgres => "ILIKE",
+    };
+    let pattern = contains_pattern(text);
+    let conditions: Vec<String> = columns
+        .iter()
+        .map(|column| {
+            format!(
+                "{} {} {} ESCAPE '{}'",
+                quote_identifier(column),
+                operator,
+                placeholder(),
+                ESCAPE
+            )
+        })
+        .collect();
+
+    (
+        format!("({})", conditions.join(" OR ")),
+        vec![pattern; columns.len()],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::table;
+
+    fn schema(columns: &[(&str, &str)]) -> TableSchema {
+        table("users").columns(columns).build()
+    }
+
+    #[test]
+    fn test_textual_columns() {
+        let schema = schema(&[
+            ("id", "INTEGER"),
+            ("name", "TEXT"),
+            ("email", "character varying"),
+            ("tags", "text[]"),
+            ("settings", "jsonb"),
+            ("code", "CHAR(4)"),
+        ]);
+        assert_eq!(search_columns(&schema), vec!["name", "email", "code"]);
+    }
+
+    #[test]
+    fn test_contains_pattern() {
+        assert_eq!(contains_pattern("alice"), "%alice%");
+        assert_eq!(contains_pattern("50%_off\\"), "%50\\%\\_off\\\\%");
+    }
+
+    #[test]
+    fn test_search_condition() {
+        let users = schema(&[
+            ("id", "integer"),
+            ("name", "text"),
+            ("email", "varchar(255)"),
+        ]);
+        let mut index = 2;
+        let (condition, values) = search_condition(&users, "Al", Dialect::Postgres, || {
+            index += 1;
+            format!("${}", index)
+        });
+        assert_eq!(
+            condition,
+            "(\"name\" ILIKE $3 ESCAPE '\\' OR \"email\" ILIKE $4 ESCAPE '\\')"
+        );
+        assert_eq!(values, vec!["%Al%", "%Al%"]);
+
+        let (condition, values) =
+            search_condition(&schema(&[("id", "INTEGER")]), "Al", Dialect::Sqlite, || {
+                "?".to_string()
+            });
+        assert_eq!(condition, "1 = 0");
+        assert!(values.is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+//! PostGIS geometry and geography columns
+//!
+//! PostGIS stores shapes in a binary format that reaches the viewer as
+//! opaque bytes. Columns of its `geometry` and `geography` types are
+//! therefore selected through `ST_AsGeoJSON` (as JSON objects) or `ST_AsText`
+//! (as WKT strings, e.g. `POINT(13.4 52.5)`), so rows show the actual shapes.
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::ColumnInfo;
+use crate::sql::quote_identifier;
+
+/// How PostGIS values are returned in rows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GeometryFormat {
+    /// GeoJSON geometry objects (`ST_AsGeoJSON`)
+    #[default]
+    GeoJson,
+
+    /// Well-known text strings (`ST_AsText`)
+    Wkt,
+}
+
+/// Whether a column type (`udt_name` in `information_schema.columns`) is a PostGIS type
+pub fn is_spatial_type(type_name: &str) -> bool {
+    matches!(type_name, "geometry" | "geography")
+}
+
+/// Whether any of `columns` holds PostGIS values
+pub fn has_spatial_columns(columns: &[ColumnInfo]) -> bool {
+    columns
+        .iter()
+        .any(|column| is_spatial_type(&column.data_type))
+}
+
+/// Select list item of `column`, rendering PostGIS values in `format`
+///
+/// Other columns are selected as they are. Rendered values keep the column's
+/// name, so rows have the same keys either way.
+pub fn select_item(column: &ColumnInfo, format: GeometryFormat) -> String {
+    let quoted = quote_identifier(&column.name);
+    if !is_spatial_type(&column.data_type) {
+        return quoted;
+    }
+
+    match format {
+        GeometryFormat::GeoJson => format!("ST_AsGeoJSON({})::json AS {}", quoted, quoted),
+        GeometryFormat::Wkt => format!("ST_AsText({}) AS {}", quoted, quoted),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::column;
+
+    #[test]
+    fn test_select_item() {
+        let location = column("location", "geography");
+        assert_eq!(
+            select_item(&location, GeometryFormat::GeoJson),
+            "ST_AsGeoJSON(\"location\")::json AS \"location\""
+        );
+        assert_eq!(
+            select_item(&location, GeometryFormat::Wkt),
+            "ST_AsText(\"location\") AS \"location\""
+        );
+        assert_eq!(
+            select_item(&column("name", "text"), GeometryFormat::Wkt),
+            "\"name\""
+        );
+        assert!(has_spatial_columns(&[column("id", "integer"), location]));
+        assert!(!has_spatial_columns(&[column("id", "integer")]));
+    }
+}
@@ -0,0 +1,663 @@
+//! Lightweight SQL text processing
+//!
+//! This is not a full SQL parser. It splits SQL text into tokens well enough to
+//! tell string literals, quoted identifiers, comments and placeholders apart,
+//! which is all that is needed to normalize statements and inspect the
+//! identifiers they reference.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// SQL dialect spoken by a database provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Dialect {
+    Sqlite,
+    Postgres,
+}
+
+/// Kind of a lexical SQL token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Spaces, tabs and newlines
+    Whitespace,
+    /// `-- line` or `/* block */` comment
+    Comment,
+    /// Keyword or unquoted identifier
+    Word,
+    /// Identifier quoted with `"`, or `` ` `` / `[]` on SQLite
+    QuotedIdentifier,
+    /// String literal, including Postgres dollar-quoted strings
+    String,
+    /// Numeric literal
+    Number,
+    /// Bind parameter such as `?`, `?1`, `$1`, `:name`, `@name`
+    Placeholder,
+    /// Any other single character (operators, parentheses, commas, ...)
+    Punctuation,
+}
+
+/// A token borrowed from the original SQL text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+}
+
+impl Token<'_> {
+    /// Whether the token is the given keyword (case-insensitive)
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    /// Identifier name with quotes removed, if the token is an identifier
+    pub fn identifier(&self) -> Option<String> {
+        match self.kind {
+            TokenKind::Word => Some(self.text.to_string()),
+            TokenKind::QuotedIdentifier => Some(unquote_identifier(self.text)),
+            _ => None,
+        }
+    }
+
+    /// Whether the token is an identifier naming the object called `name`
+    ///
+    /// PostgreSQL folds unquoted identifiers to lower case and compares quoted
+    /// ones exactly, so `Users` refers to `users` but not to `"Users"`. SQLite
+    /// compares identifiers case-insensitively (ASCII only), quoted or not.
+    pub fn refers_to(&self, name: &str, dialect: Dialect) -> bool {
+        match (self.kind, dialect) {
+            (TokenKind::Word, Dialect::Postgres) => self.text.to_ascii_lowercase() == name,
+            (TokenKind::QuotedIdentifier, Dialect::Postgres) => {
+                unquote_identifier(self.text) == name
+            }
+            (TokenKind::Word | TokenKind::QuotedIdentifier, Dialect::Sqlite) => self
+                .identifier()
+                .is_some_and(|identifier| identifier.eq_ignore_ascii_case(name)),
+            _ => false,
+        }
+    }
+}
+
+/// Find the table or view a name from a request refers to
+///
+/// An exact match wins. Otherwise the name may differ in case from exactly one
+/// table, so `/api/tables/Users` finds `users` (which is what PostgreSQL makes
+/// of an unquoted `Users`) and `/api/tables/users` finds a table created as
+/// `"Users"`.
+///
+/// # Returns
+///
+/// The table's exact name, or `Err` with the candidates if several tables
+/// differ from the name only in case (`Ok(None)` if none matches)
+pub fn resolve_table_name<'a>(
+    requested: &str,
+    tables: &'a [String],
+) -> Result<Option<&'a str>, Vec<&'a str>> {
+    if let Some(exact) = tables.iter().find(|table| *table == requested) {
+        return Ok(Some(exact));
+    }
+
+    let candidates: Vec<&str> = tables
+        .iter()
+        .filter(|table| table.eq_ignore_ascii_case(requested))
+        .map(String::as_str)
+        .collect();
+    match candidates.as_slice() {
+        [] => Ok(None),
+        [table] => Ok(Some(table)),
+        _ => Err(candidates),
+    }
+}
+
+/// Split SQL text into tokens
+///
+/// Concatenating the text of all returned tokens yields the original input.
+pub fn tokenize(sql: &str, dialect: Dialect) -> Vec<Token<'_>> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while position < sql.len() {
+        let rest = &sql[position..];
+        let first = rest.chars().next().unwrap_or_default();
+        let (kind, length) = if first.is_whitespace() {
+            (TokenKind::Whitespace, take_while(rest, char::is_whitespace))
+        } else if rest.starts_with("--") {
+            (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let length = comment.find("*/").map(|end| end + 4).unwrap_or(rest.len());
+            (TokenKind::Comment, length)
+        } else if first == '\'' {
+            (TokenKind::String, quoted_length(rest, '\'', '\''))
+        } else if first == '"' {
+            (TokenKind::QuotedIdentifier, quoted_length(rest, '"', '"'))
+        } else if first == '`' && dialect == Dialect::Sqlite {
+            (TokenKind::QuotedIdentifier, quoted_length(rest, '`', '`'))
+        } else if first == '[' && dialect == Dialect::Sqlite {
+            (TokenKind::QuotedIdentifier, quoted_length(rest, '[', ']'))
+        } else if first.is_ascii_digit() || (first == '.' && next_is_digit(bytes, position + 1)) {
+            (TokenKind::Number, number_length(rest))
+        } else if first == '$' && next_is_digit(bytes, position + 1) {
+            (
+                TokenKind::Placeholder,
+                1 + take_while(&rest[1..], |character| character.is_ascii_digit()),
+            )
+        } else if first == '$' && dialect == Dialect::Postgres {
+            match dollar_quoted_length(rest) {
+                Some(length) => (TokenKind::String, length),
+                None => (TokenKind::Punctuation, 1),
+            }
+        } else if let Some(length) = prefixed_string_length(rest, dialect) {
+            (TokenKind::String, length)
+        } else if first == '?' {
+            (
+                TokenKind::Placeholder,
+                1 + take_while(&rest[1..], |character| character.is_ascii_digit()),
+            )
+        } else if (first == ':' || first == '@' || first == '$')
+            && rest[1..].starts_with(is_identifier_start)
+            && !(first == ':' && position > 0 && bytes[position - 1] == b':')
+        {
+            (
+                TokenKind::Placeholder,
+                1 + take_while(&rest[1..], is_identifier_part),
+            )
+        } else if is_identifier_start(first) {
+            let length = take_while(rest, |character| {
+                is_identifier_part(character) || (character == '$' && dialect == Dialect::Postgres)
+            });
+            (TokenKind::Word, length)
+        } else if first == ':' && rest.starts_with("::") {
+            (TokenKind::Punctuation, 2)
+        } else {
+            (TokenKind::Punctuation, first.len_utf8())
+        };
+
+        tokens.push(Token {
+            kind,
+            text: &rest[..length],
+        });
+        position += length;
+    }
+
+    tokens
+}
+
+/// Remove the quotes around a quoted identifier and unescape doubled quotes
+pub fn unquote_identifier(text: &str) -> String {
+    let mut characters = text.chars();
+    match (characters.next(), text.chars().last()) {
+        (Some('"'), Some('"')) if text.len() >= 2 => text[1..text.len() - 1].replace("\"\"", "\""),
+        (Some('`'), Some('`')) if text.len() >= 2 => text[1..text.len() - 1].replace("``", "`"),
+        (Some('['), Some(']')) if text.len() >= 2 => text[1..text.len() - 1].to_string(),
+        _ => text.to_string(),
+    }
+}
+
+/// Quote an identifier with double quotes, doubling embedded quotes
+///
+/// Both dialects accept double-quoted identifiers.
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Write a JSON value (as returned by the providers' `row_to_json`) as a SQL literal
+///
+/// Strings are single-quoted with embedded quotes doubled; backslashes need no
+/// escaping in either dialect (PostgreSQL has `standard_conforming_strings` on
+/// by default). Booleans become `TRUE`/`FALSE` in PostgreSQL and `1`/`0` in
+/// SQLite, which stores them as integers. Arrays and objects are written as
+/// their JSON text.
+pub fn literal(value: &Value, dialect: Dialect) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(boolean) => match (dialect, boolean) {
+            (Dialect::Postgres, true) => "TRUE".to_string(),
+            (Dialect::Postgres, false) => "FALSE".to_string(),
+            (Dialect::Sqlite, boolean) => u8::from(*boolean).to_string(),
+        },
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => format!("'{}'", text.replace('\'', "''")),
+        Value::Array(_) | Value::Object(_) => {
+            format!("'{}'", value.to_string().replace('\'', "''"))
+        }
+    }
+}
+
+/// Normalize a statement to a single line
+///
+/// Comments are removed, runs of whitespace collapse to a single space and a
+/// trailing semicolon is dropped. String literals and quoted identifiers are
+/// left untouched.
+pub fn normalize(sql: &str, dialect: Dialect) -> String {
+    let mut output = String::with_capacity(sql.len());
+
+    for token in tokenize(sql, dialect) {
+        match token.kind {
+            TokenKind::Whitespace | TokenKind::Comment => {
+                if !output.is_empty() && !output.ends_with(' ') {
+                    output.push(' ');
+                }
+            }
+            _ => output.push_str(token.text),
+        }
+    }
+
+    let trimmed = output.trim_end();
+    trimmed
+        .strip_suffix(';')
+        .unwrap_or(trimmed)
+        .trim_end()
+        .to_string()
+}
+
+/// Split a script into individual statements on top-level semicolons
+///
+/// Semicolons inside string literals, quoted identifiers and comments do not
+/// terminate a statement. Empty statements are skipped.
+pub fn split_statements(sql: &str, dialect: Dialect) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+
+    for token in tokenize(sql, dialect) {
+        if token.kind == TokenKind::Punctuation && token.text == ";" {
+            if has_content(&current, dialect) {
+                statements.push(current.trim().to_string());
+            }
+            current.clear();
+        } else {
+            current.push_str(token.text);
+        }
+    }
+
+    if has_content(&current, dialect) {
+        statements.push(current.trim().to_string());
+    }
+
+    statements
+}
+
+/// Whether the SQL text contains anything besides whitespace and comments
+fn has_content(sql: &str, dialect: Dialect) -> bool {
+    tokenize(sql, dialect)
+        .iter()
+        .any(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment))
+}
+
+/// Collect the placeholders of a statement in order of appearance
+pub fn placeholders(sql: &str, dialect: Dialect) -> Vec<String> {
+    tokenize(sql, dialect)
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Placeholder)
+        .map(|token| token.text.to_string())
+        .collect()
+}
+
+/// Replace every placeholder with a literal `NULL`
+///
+/// This makes a parameterized application query explainable without binding
+/// actual values.
+pub fn replace_placeholders_with_null(sql: &str, dialect: Dialect) -> String {
+    tokenize(sql, dialect)
+        .into_iter()
+        .map(|token| {
+            if token.kind == TokenKind::Placeholder {
+                "NULL"
+            } else {
+                token.text
+            }
+        })
+        .collect()
+}
+
+/// Rewrite named placeholders (`:name`, `@name`) into positional ones
+///
+/// Each distinct name gets the next number, in order of first appearance, and
+/// becomes `?N` on SQLite or `$N` on PostgreSQL, so a name used twice binds the
+/// same value. Values for names the statement doesn't use are ignored.
+///
+/// # Returns
+///
+/// The rewritten SQL and the values to bind in order, or an error message if a
+/// name has no value or the statement also contains positional placeholders
+pub fn bind_named_parameters(
+    sql: &str,
+    params: &Map<String, Value>,
+    dialect: Dialect,
+) -> Result<(String, Vec<Value>), String> {
+    let mut output = String::with_capacity(sql.len());
+    let mut names: Vec<&str> = Vec::new();
+
+    for token in tokenize(sql, dialect) {
+        if token.kind != TokenKind::Placeholder {
+            output.push_str(token.text);
+            continue;
+        }
+
+        let name = &token.text[1..];
+        if token.text.starts_with('?') || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(format!(
+                "Invalid query parameters: positional placeholder {} used with named ones",
+                token.text
+            ));
+        }
+
+        let position = match names.iter().position(|known| *known == name) {
+            Some(index) => index + 1,
+            None => {
+                names.push(name);
+                names.len()
+            }
+        };
+        match dialect {
+            Dialect::Sqlite => output.push('?'),
+            Dialect::Postgres => output.push('$'),
+        }
+        output.push_str(&position.to_string());
+    }
+
+    let values = names
+        .into_iter()
+        .map(|name| {
+            params
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("Invalid query parameters: no value for :{}", name))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((output, values))
+}
+
+fn take_while(text: &str, predicate: impl Fn(char) -> bool) -> usize {
+    text.char_indices()
+        .find(|(_, character)| !predicate(*character))
+        .map(|(index, _)| index)
+        .unwrap_or(text.len())
+}
+
+fn next_is_digit(bytes: &[u8], index: usize) -> bool {
+    bytes.get(index).is_some_and(|byte| byte.is_ascii_digit())
+}
+
+fn is_identifier_start(character: char) -> bool {
+    character.is_alphabetic() || character == '_'
+}
+
+fn is_identifier_part(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+/// Length of a quoted token where the closing quote is escaped by doubling it
+fn quoted_length(text: &str, open: char, close: char) -> usize {
+    let mut characters = text.char_indices().skip(1).peekable();
+    while let Some((index, character)) = characters.next() {
+        if character == close {
+            if open == close && characters.peek().map(|(_, next)| *next) == Some(close) {
+                characters.next();
+                continue;
+            }
+            return index + close.len_utf8();
+        }
+    }
+    text.len()
+}
+
+/// Length of a string literal with a prefix, such as `E'\n'`, `X'ff'` or `U&'d\0061t'`
+fn prefixed_string_length(text: &str, dialect: Dialect) -> Option<usize> {
+    let prefix_length = match (text.as_bytes(), dialect) {
+        ([b'x' | b'X', b'\'', ..], _) => 1,
+        ([b'e' | b'E' | b'b' | b'B', b'\'', ..], Dialect::Postgres) => 1,
+        ([b'u' | b'U', b'&', b'\'', ..], Dialect::Postgres) => 2,
+        _ => return None,
+    };
+
+    let literal = &text[prefix_length..];
+    let length = if text.starts_with(['e', 'E']) {
+        escaped_quoted_length(literal)
+    } else {
+        quoted_length(literal, '\'', '\'')
+    };
+    Some(prefix_length + length)
+}
+
+/// Length of a single-quoted string where quotes can also be escaped with a backslash
+fn escaped_quoted_length(text: &str) -> usize {
+    let mut characters = text.char_indices().skip(1).peekable();
+    while let Some((index, character)) = characters.next() {
+        match character {
+            '\\' => {
+                characters.next();
+            }
+            '\'' if characters.peek().map(|(_, next)| *next) == Some('\'') => {
+                characters.next();
+            }
+            '\'' => return index + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+fn number_length(text: &str) -> usize {
+    let mut length = take_while(text, |character| {
+        character.is_ascii_digit() || character == '.'
+    });
+    let rest = &text[length..];
+    if rest.starts_with(['e', 'E']) {
+        let exponent = &rest[1..];
+        let sign_length = usize::from(exponent.starts_with(['+', '-']));
+        let digits = take_while(&exponent[sign_length..], |character| {
+            character.is_ascii_digit()
+        });
+        if digits > 0 {
+            length += 1 + sign_length + digits;
+        }
+    }
+    length
+}
+
+/// Length of a Postgres dollar-quoted string such as `$body$ ... $body$`
+fn dollar_quoted_length(text: &str) -> Option<usize> {
+    let tag_length = 1 + take_while(&text[1..], is_identifier_part);
+    if !text[tag_length..].starts_with('$') {
+        return None;
+    }
+
+    let tag = &text[..tag_length + 1];
+    let body_start = tag.len();
+    text[body_start..]
+        .find(tag)
+        .map(|end| body_start + end + tag.len())
+        .or(Some(text.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_named_parameters() {
+        let params = serde_json::json!({ "email": "a@example.com", "active": true, "unused": 1 });
+        let params = params.as_object().unwrap();
+        let sql = "SELECT ':email', x::text FROM t WHERE a = :email OR b = @active OR c = :email";
+
+        let (rewritten, values) = bind_named_parameters(sql, params, Dialect::Postgres).unwrap();
+        assert_eq!(
+            rewritten,
+            "SELECT ':email', x::text FROM t WHERE a = $1 OR b = $2 OR c = $1"
+        );
+        assert_eq!(
+            values,
+            vec![serde_json::json!("a@example.com"), Value::Bool(true)]
+        );
+
+        let (rewritten, values) = bind_named_parameters(
+            "UPDATE t SET a = :active WHERE b = :email",
+            params,
+            Dialect::Sqlite,
+        )
+        .unwrap();
+        assert_eq!(rewritten, "UPDATE t SET a = ?1 WHERE b = ?2");
+        assert_eq!(values.len(), 2);
+
+        let missing = bind_named_parameters("SELECT :name", params, Dialect::Sqlite);
+        assert!(missing.unwrap_err().starts_with("Invalid"));
+        let mixed = bind_named_parameters("SELECT :email, ?", params, Dialect::Sqlite);
+        assert!(mixed.unwrap_err().contains("positional"));
+    }
+
+    #[test]
+    fn test_literals() {
+        assert_eq!(quote_identifier("odd\"name"), "\"odd\"\"name\"");
+        assert_eq!(literal(&Value::Null, Dialect::Sqlite), "NULL");
+        assert_eq!(literal(&Value::Bool(true), Dialect::Sqlite), "1");
+        assert_eq!(literal(&Value::Bool(false), Dialect::Postgres), "FALSE");
+        assert_eq!(literal(&serde_json::json!(-2.5), Dialect::Postgres), "-2.5");
+        assert_eq!(
+            literal(&serde_json::json!("it's C:\\temp"), Dialect::Postgres),
+            "'it''s C:\\temp'"
+        );
+        assert_eq!(
+            literal(&serde_json::json!({ "name": "O'Brien" }), Dialect::Sqlite),
+            "'{\"name\":\"O''Brien\"}'"
+        );
+    }
+
+    fn kinds(sql: &str, dialect: Dialect) -> Vec<(TokenKind, &str)> {
+        tokenize(sql, dialect)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_identifier_case() {
+        let tokens = tokenize(r#"Users "Users" users"#, Dialect::Postgres);
+        let words: Vec<Token<'_>> = tokens
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .collect();
+        assert!(words[0].refers_to("users", Dialect::Postgres));
+        assert!(!words[0].refers_to("Users", Dialect::Postgres));
+        assert!(words[1].refers_to("Users", Dialect::Postgres));
+        assert!(!words[1].refers_to("users", Dialect::Postgres));
+        assert!(words[1].refers_to("users", Dialect::Sqlite));
+        assert!(words[2].refers_to("USERS", Dialect::Sqlite));
+
+        let tables = vec![
+            "Users".to_string(),
+            "orders".to_string(),
+            "ORDERS".to_string(),
+        ];
+        assert_eq!(resolve_table_name("Users", &tables), Ok(Some("Users")));
+        assert_eq!(resolve_table_name("users", &tables), Ok(Some("Users")));
+        assert_eq!(resolve_table_name("orders", &tables), Ok(Some("orders")));
+        assert_eq!(
+            resolve_table_name("Orders", &tables),
+            Err(vec!["orders", "ORDERS"])
+        );
+        assert_eq!(resolve_table_name("invoices", &tables), Ok(None));
+    }
+
+    #[test]
+    fn test_tokenize_round_trip() {
+        let sql = "SELECT \"a\"\"b\", 'it''s' -- comment\nFROM t WHERE x = $1 /* block */;";
+        let tokens = tokenize(sql, Dialect::Postgres);
+        let joined: String = tokens.iter().map(|token| token.text).collect();
+        assert_eq!(joined, sql);
+    }
+
+    #[test]
+    fn test_tokenize_placeholders() {
+        assert_eq!(
+            kinds(
+                "x = ? AND y = ?2 AND z = :name AND w = @other",
+                Dialect::Sqlite
+            ),
+            vec![
+                (TokenKind::Word, "x"),
+                (TokenKind::Punctuation, "="),
+                (TokenKind::Placeholder, "?"),
+                (TokenKind::Word, "AND"),
+                (TokenKind::Word, "y"),
+                (TokenKind::Punctuation, "="),
+                (TokenKind::Placeholder, "?2"),
+                (TokenKind::Word, "AND"),
+                (TokenKind::Word, "z"),
+                (TokenKind::Punctuation, "="),
+                (TokenKind::Placeholder, ":name"),
+                (TokenKind::Word, "AND"),
+                (TokenKind::Word, "w"),
+                (TokenKind::Punctuation, "="),
+                (TokenKind::Placeholder, "@other"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_postgres_specifics() {
+        assert_eq!(
+            kinds("SELECT $tag$it's$tag$, id::text FROM t", Dialect::Postgres),
+            vec![
+                (TokenKind::Word, "SELECT"),
+                (TokenKind::String, "$tag$it's$tag$"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Word, "id"),
+                (TokenKind::Punctuation, "::"),
+                (TokenKind::Word, "text"),
+                (TokenKind::Word, "FROM"),
+                (TokenKind::Word, "t"),
+            ]
+        );
+        assert_eq!(
+            kinds(r"SELECT E'it\'s', x'ff', U&'d\0061t'", Dialect::Postgres),
+            vec![
+                (TokenKind::Word, "SELECT"),
+                (TokenKind::String, r"E'it\'s'"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::String, "x'ff'"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::String, r"U&'d\0061t'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize(
+                "SELECT *\n  FROM users -- all of them\n WHERE name = 'a  b';",
+                Dialect::Sqlite
+            ),
+            "SELECT * FROM users WHERE name = 'a  b'"
+        );
+    }
+
+    #[test]
+    fn test_replace_placeholders_with_null() {
+        assert_eq!(
+            replace_placeholders_with_null(
+                "SELECT * FROM t WHERE a = $1 AND b = '$2'",
+                Dialect::Postgres
+            ),
+            "SELECT * FROM t WHERE a = NULL AND b = '$2'"
+        );
+        assert_eq!(
+            placeholders("a = ? AND b = ?", Dialect::Sqlite),
+            vec!["?", "?"]
+        );
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements(
+                "SELECT ';'; -- trailing;\n UPDATE t SET a = 1;;",
+                Dialect::Sqlite
+            ),
+            vec!["SELECT ';'", "-- trailing;\n UPDATE t SET a = 1"]
+        );
+    }
+}
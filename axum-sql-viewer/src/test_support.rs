@@ -0,0 +1,121 @@
+//! Schemas for unit tests
+//!
+//! Tables start without columns or a primary key and are addressed by
+//! `rowid`, the way SQLite addresses such tables. Setting a primary key
+//! switches them to primary key addressing and marks the key columns.
+
+use crate::schema::{ColumnInfo, ForeignKey, RowAddress, TableSchema};
+
+/// A nullable column without a default value
+pub fn column(name: &str, data_type: &str) -> ColumnInfo {
+    ColumnInfo {
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        nullable: true,
+        default_value: None,
+        is_primary_key: false,
+    }
+}
+
+/// Changes to columns made with [`column`]
+pub trait ColumnInfoExt {
+    /// The column with a `NOT NULL` constraint
+    fn not_null(self) -> Self;
+
+    /// The column with the default value expression `default_value`
+    fn default_value(self, default_value: &str) -> Self;
+}
+
+impl ColumnInfoExt for ColumnInfo {
+    fn not_null(self) -> Self {
+        ColumnInfo {
+            nullable: false,
+            ..self
+        }
+    }
+
+    fn default_value(self, default_value: &str) -> Self {
+        ColumnInfo {
+            default_value: Some(default_value.to_string()),
+            ..self
+        }
+    }
+}
+
+/// Start building the schema of the table `name`
+pub fn table(name: &str) -> TableBuilder {
+    TableBuilder {
+        schema: TableSchema {
+            name: name.to_string(),
+            columns: Vec::new(),
+            primary_key: None,
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            row_address: RowAddress::Rowid,
+        },
+    }
+}
+
+/// Builder of a [`TableSchema`]
+pub struct TableBuilder {
+    schema: TableSchema,
+}
+
+impl TableBuilder {
+    /// Add a column
+    pub fn column(mut self, column: ColumnInfo) -> Self {
+        self.schema.columns.push(column);
+        self
+    }
+
+    /// Add nullable columns by name and data type
+    pub fn columns(self, columns: &[(&str, &str)]) -> Self {
+        columns.iter().fold(self, |builder, (name, data_type)| {
+            builder.column(column(name, data_type))
+        })
+    }
+
+    /// Add nullable `TEXT` columns
+    pub fn text_columns(self, names: &[&str]) -> Self {
+        names
+            .iter()
+            .fold(self, |builder, name| builder.column(column(name, "TEXT")))
+    }
+
+    /// Address rows by the primary key made of `columns`
+    pub fn primary_key(mut self, columns: &[&str]) -> Self {
+        self.schema.primary_key = Some(columns.iter().map(|column| column.to_string()).collect());
+        self.schema.row_address = RowAddress::PrimaryKey;
+        self
+    }
+
+    /// Address rows without a primary key differently (e.g. by `ctid`, or not at all)
+    pub fn row_address(mut self, row_address: RowAddress) -> Self {
+        self.schema.row_address = row_address;
+        self
+    }
+
+    /// Add a foreign key from `column` to `references_table.references_column`
+    pub fn foreign_key(
+        mut self,
+        column: &str,
+        references_table: &str,
+        references_column: &str,
+    ) -> Self {
+        self.schema.foreign_keys.push(ForeignKey {
+            column: column.to_string(),
+            references_table: references_table.to_string(),
+            references_column: references_column.to_string(),
+        });
+        self
+    }
+
+    /// The finished schema, with the primary key columns marked
+    pub fn build(mut self) -> TableSchema {
+        let primary_key = self.schema.primary_key.clone().unwrap_or_default();
+        for column in &mut self.schema.columns {
+            column.is_primary_key = primary_key.contains(&column.name);
+        }
+        self.schema
+    }
+}
@@ -0,0 +1,295 @@
+//! Checking statements without running them
+//!
+//! A statement sent to `/api/query` with `validateOnly` is parsed with
+//! `sqlparser`, and queries are also `EXPLAIN`ed by the database, which
+//! catches unknown tables, columns and functions as well. Nothing is executed,
+//! so an editor can check the SQL while it is typed. Errors are placed by the
+//! parser's position, or by the token the database's message names.
+//!
+//! `sqlparser` does not know every database-specific syntax. A statement it
+//! rejects but the database explains without complaint is therefore valid;
+//! statements that are not explained (writes, schema changes) are judged by
+//! the parser alone.
+
+use regex::Regex;
+use sqlparser::dialect::{PostgreSqlDialect, SQLiteDialect};
+use sqlparser::parser::Parser;
+use std::sync::OnceLock;
+
+use crate::classify::{classify, StatementKind};
+use crate::database::traits::{DatabaseError, DatabaseProvider};
+use crate::schema::QueryValidation;
+use crate::sql::{replace_placeholders_with_null, split_statements, tokenize, Dialect, TokenKind};
+
+/// Syntax error found by parsing a statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// Parser message, without the position
+    pub message: String,
+
+    /// Line of the error, starting at 1
+    pub line: Option<u64>,
+
+    /// Column of the error, starting at 1
+    pub column: Option<u64>,
+}
+
+/// Parse `sql` and return the first syntax error, if any
+///
+/// Placeholders of any style (`?`, `$1`, `:name`) are accepted.
+pub fn syntax_error(sql: &str, dialect: Dialect) -> Option<SyntaxError> {
+    let error = match dialect {
+        Dialect::Sqlite => Parser::parse_sql(&SQLiteDialect {}, sql).err(),
+        Dialect::Postgres => Parser::parse_sql(
+            &PostgreSqlDialect {},
+            &positional_placeholders(sql, dialect),
+        )
+        .err(),
+    }?;
+
+    static POSITION: OnceLock<Regex> = OnceLock::new();
+    let position =
+        POSITION.get_or_init(|| Regex::new(r"\s*at Line: (\d+), Column: (\d+)").unwrap());
+
+    let message = error.to_string();
+    let message = message
+        .strip_prefix("sql parser error: ")
+        .unwrap_or(&message);
+    let captures = position.captures(message);
+    let number = |index| {
+        captures
+            .as_ref()
+            .and_then(|captures| captures.get(index))
+            .and_then(|number| number.as_str().parse().ok())
+    };
+
+    Some(SyntaxError {
+        message: position.replace(message, "").into_owned(),
+        line: number(1),
+        column: number(2),
+    })
+}
+
+/// Check `sql` without running it
+///
+/// Placeholders are replaced by `NULL` for `EXPLAIN`, so parameters are not
+/// needed.
+///
+/// # Returns
+///
+/// Whether the SQL is valid and the first problem found. Errors other than
+/// the database rejecting a statement (e.g. timeouts) are returned as `Err`.
+pub async fn validate<DB: DatabaseProvider>(
+    database: &DB,
+    sql: &str,
+) -> Result<QueryValidation, DatabaseError> {
+    let dialect = database.dialect();
+    let statements = split_statements(sql, dialect);
+    if statements.is_empty() {
+        return Ok(QueryValidation {
+            error: Some("No SQL statement".to_string()),
+            ..QueryValidation::default()
+        });
+    }
+
+    let syntax_error = syntax_error(sql, dialect);
+    let mut explained = true;
+    let mut offset = 0;
+    for statement in &statements {
+        // Statements are copied from `sql` without their surrounding whitespace
+        offset = sql[offset..]
+            .find(statement.as_str())
+            .map_or(offset, |found| offset + found);
+        if !is_explainable(statement, dialect) {
+            explained = false;
+            continue;
+        }
+
+        let explained_sql = replace_placeholders_with_null(statement, dialect);
+        match database.explain_query(&explained_sql).await {
+            Ok(_) => {}
+            // The parser's message tells where the error is; the database's
+            // often does not
+            Err(DatabaseError::Query(_)) if syntax_error.is_some() => {
+                explained = false;
+                break;
+            }
+            Err(DatabaseError::Query(message)) => {
+                let position = error_position(sql, offset, statement, &message, dialect);
+                return Ok(QueryValidation {
+                    explained: true,
+                    error: Some(message),
+                    line: position.map(|(line, _)| line),
+                    column: position.map(|(_, column)| column),
+                    ..QueryValidation::default()
+                });
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    // A syntax error does not count if the database explained every
+    // statement, as it then accepted syntax the parser does not know
+    match syntax_error {
+        Some(error) if !explained => Ok(QueryValidation {
+            error: Some(error.message),
+            line: error.line,
+            column: error.column,
+            ..QueryValidation::default()
+        }),
+        _ => Ok(QueryValidation {
+            valid: true,
+            explained,
+            ..QueryValidation::default()
+        }),
+    }
+}
+
+/// Line and column of the token a database error message names
+///
+/// SQLite (`near "AND": syntax error`) and PostgreSQL (`syntax error at or
+/// near "AND"`) name the token they stopped at, but not where it is. The
+/// position is only known if `statement`, found at `offset` in `sql`,
+/// contains that token once.
+fn error_position(
+    sql: &str,
+    offset: usize,
+    statement: &str,
+    message: &str,
+    dialect: Dialect,
+) -> Option<(u64, u64)> {
+    static NEAR: OnceLock<Regex> = OnceLock::new();
+    let near = NEAR.get_or_init(|| Regex::new(r#"near "([^"]+)""#).unwrap());
+    let token = near.captures(message)?.get(1)?.as_str();
+
+    let mut start = offset;
+    let mut matches = Vec::new();
+    for candidate in tokenize(statement, dialect) {
+        if candidate.text.eq_ignore_ascii_case(token) {
+            matches.push(start);
+        }
+        start += candidate.text.len();
+    }
+    let [start] = matches[..] else {
+        return None;
+    };
+
+    let before = &sql[..start];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    Some((line as u64, column as u64))
+}
+
+/// `sql` with named placeholders replaced by `$1`, padded to the same length
+///
+/// Named placeholders are the viewer's own on PostgreSQL, and unknown to the
+/// parser. Keeping the length keeps the positions of syntax errors.
+fn positional_placeholders(sql: &str, dialect: Dialect) -> String {
+    tokenize(sql, dialect)
+        .into_iter()
+        .map(|token| {
+            if token.kind == TokenKind::Placeholder && !token.text.starts_with(['$', '?']) {
+                format!("{:<width$}", "$1", width = token.text.len())
+            } else {
+                token.text.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Whether a statement is a query that can be explained
+///
+/// `EXPLAIN` itself is not explained again.
+fn is_explainable(statement: &str, dialect: Dialect) -> bool {
+    let class = classify(statement, dialect);
+    let first_word = tokenize(statement, dialect)
+        .into_iter()
+        .find(|token| token.kind == TokenKind::Word);
+    class.kind == StatementKind::Query
+        && class.read_only
+        && !first_word.is_some_and(|word| word.is_keyword("EXPLAIN"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax_error_position() {
+        let error = syntax_error("SELECT *\nFROM users WHERE id = = 1", Dialect::Sqlite).unwrap();
+        assert_eq!((error.line, error.column), (Some(2), Some(23)));
+        assert_eq!(error.message, "Expected: an expression, found: =");
+        assert!(!error.message.contains("Line:"));
+        assert!(!error.message.starts_with("sql parser error"));
+
+        assert_eq!(syntax_error("SELECT * FROM users", Dialect::Sqlite), None);
+        assert_eq!(
+            syntax_error("SELECT $1::int, name FROM users", Dialect::Postgres),
+            None
+        );
+        assert_eq!(
+            syntax_error(
+                "SELECT * FROM posts WHERE author = :user",
+                Dialect::Postgres
+            ),
+            None
+        );
+
+        // Positions after a named placeholder are kept
+        let error = syntax_error("SELECT :user = = 1", Dialect::Postgres).unwrap();
+        assert_eq!((error.line, error.column), (Some(1), Some(16)));
+    }
+
+    #[test]
+    fn test_error_position() {
+        let sql = "SELECT 1;\nSELECT * FROM users\nWHERE AND id = 1 OR id = 2";
+        let offset = sql.find("SELECT *").unwrap();
+        let statement = "SELECT * FROM users\nWHERE AND id = 1 OR id = 2";
+        let position = |message| error_position(sql, offset, statement, message, Dialect::Sqlite);
+
+        assert_eq!(position("near \"AND\": syntax error"), Some((3, 7)));
+        assert_eq!(position("syntax error at or near \"and\""), Some((3, 7)));
+        assert_eq!(position("near \"SELECT\": syntax error"), Some((2, 1)));
+        // Tokens the statement contains more than once are ambiguous
+        assert_eq!(position("near \"id\": syntax error"), None);
+        assert_eq!(position("no such table: users"), None);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_validate_database_error_position() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE users (id INTEGER, name TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let database = crate::database::sqlite::SqliteProvider::new(pool);
+
+        // SQLite has no QUALIFY clause, which the parser accepts
+        let sql = "SELECT 1;\nSELECT * FROM users WHERE id = 1 QUALIFY id";
+        let validation = validate(&database, sql).await.unwrap();
+        assert!(!validation.valid);
+        assert_eq!((validation.line, validation.column), (Some(2), Some(34)));
+    }
+
+    #[test]
+    fn test_only_queries_are_explained() {
+        assert!(is_explainable("SELECT * FROM users", Dialect::Sqlite));
+        assert!(is_explainable(
+            "WITH recent AS (SELECT 1) SELECT * FROM recent",
+            Dialect::Postgres
+        ));
+        assert!(!is_explainable("DELETE FROM users", Dialect::Sqlite));
+        assert!(!is_explainable("EXPLAIN SELECT 1", Dialect::Sqlite));
+        assert!(!is_explainable(
+            "SELECT * FROM users FOR UPDATE",
+            Dialect::Postgres
+        ));
+    }
+}
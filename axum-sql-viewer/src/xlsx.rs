@@ -0,0 +1,362 @@
+//! Streaming XLSX writer
+//!
+//! Writes a single-sheet workbook while the rows arrive, so exports to Excel
+//! do not have to hold the table in memory. An XLSX file is a ZIP archive of
+//! XML parts; the archive is written with the `zip` crate in streaming mode, so
+//! the worksheet entry's checksum and size follow its data, and the worksheet
+//! uses ZIP64 sizes so that it can grow past 4 GiB. Entries are stored without
+//! compression, so the bytes returned for each page match the rows written.
+//!
+//! Strings are written inline (no shared string table) and no styles are
+//! defined, which keeps the writer small; the header row is frozen.
+
+use serde_json::Value;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use zip::write::{SimpleFileOptions, StreamWriter};
+use zip::{CompressionMethod, ZipWriter};
+
+/// Maximum number of rows on an Excel worksheet (including the header row)
+pub const MAX_ROWS: u64 = 1_048_576;
+
+/// Maximum number of characters in an Excel cell
+const MAX_CELL_CHARACTERS: usize = 32_767;
+
+/// Integers with more digits than this lose precision in Excel and are written as text
+const MAX_EXACT_DIGITS: usize = 15;
+
+const CONTENT_TYPES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+    r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+    r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+    r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    r#"<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+    r#"</Types>"#
+);
+
+const PACKAGE_RELATIONSHIPS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+    r#"</Relationships>"#
+);
+
+const WORKBOOK_RELATIONSHIPS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>"#,
+    r#"</Relationships>"#
+);
+
+const WORKSHEET_START: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+    r#"<sheetViews><sheetView workbookViewId="0">"#,
+    r#"<pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/>"#,
+    r#"</sheetView></sheetViews><sheetData>"#
+);
+
+const WORKSHEET_END: &str = "</sheetData></worksheet>";
+
+/// Streaming writer for a workbook with a single worksheet
+///
+/// Call [`start`](XlsxWriter::start) once, then [`rows`](XlsxWriter::rows) for
+/// each page and [`finish`](XlsxWriter::finish) at the end; the returned bytes,
+/// concatenated, form the file.
+pub struct XlsxWriter {
+    sheet_name: String,
+    columns: Vec<String>,
+    /// The archive, until it is finished
+    zip: Option<ZipWriter<StreamWriter<Output>>>,
+    output: Output,
+    rows_written: u64,
+}
+
+impl XlsxWriter {
+    /// Create a writer for a sheet with the given name and columns
+    pub fn new(sheet_name: &str, columns: Vec<String>) -> Self {
+        let output = Output::default();
+        Self {
+            sheet_name: sheet_name_of(sheet_name),
+            columns,
+            zip: Some(ZipWriter::new_stream(output.clone())),
+            output,
+            rows_written: 0,
+        }
+    }
+
+    /// Start the file: the fixed parts of the workbook and the header row
+    pub fn start(&mut self) -> Vec<u8> {
+        let workbook = format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+                r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+                r#"<sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#
+            ),
+            escape_xml(&self.sheet_name)
+        );
+
+        let parts = [
+            ("[Content_Types].xml", CONTENT_TYPES),
+            ("_rels/.rels", PACKAGE_RELATIONSHIPS),
+            ("xl/workbook.xml", workbook.as_str()),
+            ("xl/_rels/workbook.xml.rels", WORKBOOK_RELATIONSHIPS),
+        ];
+        let header: Vec<Value> = self
+            .columns
+            .iter()
+            .map(|column| Value::String(column.clone()))
+            .collect();
+        let mut sheet = String::from(WORKSHEET_START);
+        sheet.push_str(&row_xml(1, &header));
+        self.rows_written = 1;
+
+        if let Some(zip) = &mut self.zip {
+            let options =
+                SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+            for (name, contents) in parts {
+                zip.start_file(name, options).expect(IN_MEMORY);
+                zip.write_all(contents.as_bytes()).expect(IN_MEMORY);
+            }
+            zip.start_file("xl/worksheets/sheet1.xml", options.large_file(true))
+                .expect(IN_MEMORY);
+            zip.write_all(sheet.as_bytes()).expect(IN_MEMORY);
+        }
+        self.output.take()
+    }
+
+    /// Write rows with the values of the writer's columns, in order
+    ///
+    /// Fails if the sheet would exceed Excel's row limit.
+    pub fn rows(&mut self, rows: &[Value]) -> Result<Vec<u8>, String> {
+        if self.rows_written + rows.len() as u64 > MAX_ROWS {
+            return Err(format!(
+                "Too many rows for an Excel worksheet (max {})",
+                MAX_ROWS - 1
+            ));
+        }
+
+        let mut sheet = String::new();
+        for row in rows {
+            self.rows_written += 1;
+            let values: Vec<Value> = self
+                .columns
+                .iter()
+                .map(|column| row.get(column).cloned().unwrap_or(Value::Null))
+                .collect();
+            sheet.push_str(&row_xml(self.rows_written, &values));
+        }
+
+        if let Some(zip) = &mut self.zip {
+            zip.write_all(sheet.as_bytes()).expect(IN_MEMORY);
+        }
+        Ok(self.output.take())
+    }
+
+    /// End the worksheet and write the archive's central directory
+    pub fn finish(&mut self) -> Vec<u8> {
+        if let Some(mut zip) = self.zip.take() {
+            zip.write_all(WORKSHEET_END.as_bytes()).expect(IN_MEMORY);
+            zip.finish().expect(IN_MEMORY);
+        }
+        self.output.take()
+    }
+}
+
+/// Writing the archive only fails if its output does, and the output is memory
+const IN_MEMORY: &str = "writing an XLSX archive to memory failed";
+
+/// Bytes of the archive written since they were last taken
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Output {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// XML of a worksheet row (`number` starts at 1)
+fn row_xml(number: u64, values: &[Value]) -> String {
+    let mut xml = format!(r#"<row r="{}">"#, number);
+    for (index, value) in values.iter().enumerate() {
+        let reference = format!("{}{}", column_name(index), number);
+        match value {
+            Value::Null => {}
+            Value::Bool(boolean) => xml.push_str(&format!(
+                r#"<c r="{}" t="b"><v>{}</v></c>"#,
+                reference,
+                u8::from(*boolean)
+            )),
+            Value::Number(number) if is_exact_in_excel(number) => {
+                xml.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, reference, number))
+            }
+            Value::Number(number) => xml.push_str(&string_cell(&reference, &number.to_string())),
+            Value::String(text) => xml.push_str(&string_cell(&reference, text)),
+            other => xml.push_str(&string_cell(&reference, &other.to_string())),
+        }
+    }
+    xml.push_str("</row>");
+    xml
+}
+
+/// XML of a cell holding an inline string
+fn string_cell(reference: &str, text: &str) -> String {
+    let text: String = text.chars().take(MAX_CELL_CHARACTERS).collect();
+    format!(
+        r#"<c r="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+        reference,
+        escape_xml(&text)
+    )
+}
+
+/// Whether Excel can hold the number without losing digits
+fn is_exact_in_excel(number: &serde_json::Number) -> bool {
+    if number.is_f64() {
+        return true;
+    }
+    let digits = number.to_string().trim_start_matches('-').chars().count();
+    digits <= MAX_EXACT_DIGITS
+}
+
+/// Spreadsheet column name for a zero-based column index (`A`, ..., `Z`, `AA`, ...)
+pub fn column_name(index: usize) -> String {
+    let mut name = Vec::new();
+    let mut remaining = index + 1;
+    while remaining > 0 {
+        let letter = (remaining - 1) % 26;
+        name.push(b'A' + letter as u8);
+        remaining = (remaining - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Sheet name Excel accepts: at most 31 characters, none of `[]:*?/\`
+fn sheet_name_of(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|character| {
+            if matches!(character, '[' | ']' | ':' | '*' | '?' | '/' | '\\') {
+                '_'
+            } else {
+                character
+            }
+        })
+        .take(31)
+        .collect();
+
+    if name.trim().is_empty() {
+        "Sheet1".to_string()
+    } else {
+        name
+    }
+}
+
+/// Escape text for XML, dropping control characters XML cannot represent
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(character),
+            character if character < ' ' => {}
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::{Cursor, Read};
+    use zip::ZipArchive;
+
+    #[test]
+    fn test_column_name() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+    }
+
+    #[test]
+    fn test_row_xml() {
+        let xml = row_xml(
+            2,
+            &[
+                json!(7),
+                json!(null),
+                json!("a < b & \"c\""),
+                json!(true),
+                json!(1234567890123456789_i64),
+            ],
+        );
+        assert_eq!(
+            xml,
+            concat!(
+                r#"<row r="2"><c r="A2"><v>7</v></c>"#,
+                r#"<c r="C2" t="inlineStr"><is><t xml:space="preserve">a &lt; b &amp; &quot;c&quot;</t></is></c>"#,
+                r#"<c r="D2" t="b"><v>1</v></c>"#,
+                r#"<c r="E2" t="inlineStr"><is><t xml:space="preserve">1234567890123456789</t></is></c>"#,
+                "</row>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_workbook_archive() {
+        let mut writer = XlsxWriter::new("users", vec!["id".to_string(), "name".to_string()]);
+        let mut file = writer.start();
+        file.extend(writer.rows(&[json!({ "id": 1, "name": "Alice" })]).unwrap());
+        file.extend(writer.finish());
+
+        let mut archive = ZipArchive::new(Cursor::new(file)).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert_eq!(names.len(), 5);
+
+        let mut part = |name: &str| {
+            let mut text = String::new();
+            archive
+                .by_name(name)
+                .unwrap()
+                .read_to_string(&mut text)
+                .unwrap();
+            text
+        };
+        assert!(
+            part("xl/workbook.xml").contains(r#"<sheet name="users" sheetId="1" r:id="rId1"/>"#)
+        );
+        let sheet = part("xl/worksheets/sheet1.xml");
+        assert!(sheet.starts_with(WORKSHEET_START));
+        assert!(sheet
+            .contains(r#"<c r="B2" t="inlineStr"><is><t xml:space="preserve">Alice</t></is></c>"#));
+        assert!(sheet.ends_with(WORKSHEET_END));
+    }
+
+    #[test]
+    fn test_sheet_name() {
+        assert_eq!(sheet_name_of("public/users"), "public_users");
+        assert_eq!(sheet_name_of(""), "Sheet1");
+        assert_eq!(sheet_name_of(&"x".repeat(40)).len(), 31);
+    }
+}